@@ -1,4 +1,5 @@
 use crate::*;
+use zeroize::Zeroize;
 
 pub(crate) fn mem_copy(dst: &mut [u8], dst_offset: usize, src: &[u8]) -> CryptoResult<()> {
     let dst_subslice = dst
@@ -52,6 +53,16 @@ impl Clone for DynCryptoBytes {
     }
 }
 
+/// Overwrite the bytes with zeroes, e.g. once a key is no longer needed.
+/// Buffers are also zeroed when they are dropped.
+impl Zeroize for DynCryptoBytes {
+    fn zeroize(&mut self) {
+        let mut write = self.write();
+        let bytes: &mut [u8] = &mut write;
+        bytes.zeroize();
+    }
+}
+
 /// internal insecure buffer
 #[derive(Debug)]
 pub(crate) struct InsecureBytes(Vec<u8>);
@@ -64,6 +75,13 @@ impl InsecureBytes {
     }
 }
 
+// sodium zeroes secure buffers when it frees them, so do the same here
+impl Drop for InsecureBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl<'lt> CryptoBytesRead<'lt> for &'lt [u8] {}
 impl<'lt> CryptoBytesRead<'lt> for &'lt mut [u8] {}
 impl<'lt> CryptoBytesWrite<'lt> for &'lt mut [u8] {}
@@ -113,6 +113,27 @@ mod tests {
         .unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn zeroize_buffers() {
+        use zeroize::Zeroize;
+        let _ = crypto_init_sodium();
+        tokio::task::spawn(async move {
+            for mut buf in vec![
+                crypto_secure_buffer(8).unwrap(),
+                crypto_insecure_buffer(8).unwrap(),
+            ] {
+                crypto_randombytes_buf(&mut buf).await.unwrap();
+                buf.zeroize();
+                assert_eq!(
+                    "[0, 0, 0, 0, 0, 0, 0, 0]",
+                    &format!("{:?}", buf.read().deref()),
+                );
+            }
+        })
+        .await
+        .unwrap();
+    }
+
     #[test]
     fn double_check_same_blake2b_hash() {
         assert_eq!(
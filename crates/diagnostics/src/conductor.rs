@@ -1,8 +1,8 @@
 use crate::display::human_size;
 use holochain::conductor::{state::ConductorState, ConductorStateDb};
-use holochain_state::{db::CONDUCTOR_STATE, env::EnvironmentWrite, prelude::*};
+use holochain_state::{db::CONDUCTOR_STATE, env::EnvironmentRead, prelude::*};
 
-pub async fn dump_conductor_state(env: EnvironmentWrite) -> anyhow::Result<ConductorState> {
+pub async fn dump_conductor_state(env: EnvironmentRead) -> anyhow::Result<ConductorState> {
     let g = env.guard();
     let r = g.reader()?;
     let db = ConductorStateDb::new(env.get_db(&CONDUCTOR_STATE)?)?;
@@ -1,6 +1,8 @@
 //! Holochain Diagnostics
 //!
 //! This is a simple program that spits out some info about LMDB databases.
+//! The databases are opened read-only, so it's safe to run against the
+//! databases of a conductor which is still running.
 //! It is written as a separate binary so that builds can be fast for quick
 //! feedback loops when debugging. In the future, this could be reorganized
 //! as a library of helper functions alongside a binary that calls into the lib,
@@ -10,7 +12,7 @@
 use cell::dump_cell_state;
 use conductor::dump_conductor_state;
 use holochain_keystore::test_keystore::spawn_test_keystore;
-use holochain_state::env::{EnvironmentKind, EnvironmentWrite};
+use holochain_state::env::{EnvironmentKind, EnvironmentRead};
 use std::path::PathBuf;
 use structopt::StructOpt;
 use wasm::dump_wasm_state;
@@ -32,7 +34,7 @@ async fn run() -> anyhow::Result<()> {
     let keystore = spawn_test_keystore(Vec::new()).await.unwrap();
 
     // set up the various environments
-    let wasm_env = EnvironmentWrite::new(
+    let wasm_env = EnvironmentRead::open_read_only(
         opt.lmdb_path.as_ref(),
         EnvironmentKind::Wasm,
         keystore.clone(),
@@ -46,7 +48,7 @@ async fn run() -> anyhow::Result<()> {
     dump_wasm_state(wasm_env).await?;
 
     // set up the various environments
-    let conductor_env = EnvironmentWrite::new(
+    let conductor_env = EnvironmentRead::open_read_only(
         opt.lmdb_path.as_ref(),
         EnvironmentKind::Conductor,
         keystore.clone(),
@@ -68,7 +70,7 @@ async fn run() -> anyhow::Result<()> {
     for (_app_id, cells) in conductor_state.active_apps {
         for cell in cells {
             let (cell_id, cell_nick) = cell.into_inner();
-            let cell_env = EnvironmentWrite::new(
+            let cell_env = EnvironmentRead::open_read_only(
                 opt.lmdb_path.as_ref(),
                 EnvironmentKind::Cell(cell_id.clone()),
                 keystore.clone(),
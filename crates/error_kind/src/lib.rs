@@ -0,0 +1,208 @@
+#![deny(missing_docs)]
+//! Machine readable kinds for errors.
+//!
+//! Each crate's error enum is free to grow whatever variants it needs, but
+//! callers on the far side of a crate or wire boundary usually only need to
+//! know what *sort* of thing went wrong: was it bad input, is it worth
+//! retrying, is it our fault? [ErrorKind] answers that, and [HasErrorKind]
+//! lets every error enum say which kind each of its variants is.
+//!
+//! Errors are often boxed into an `Other` variant when they cross a crate
+//! boundary, which loses their type. Wrapping them in a [KindedError] first
+//! keeps the kind so [kind_of_boxed] can find it again on the other side.
+
+use serde::{Deserialize, Serialize};
+
+/// What sort of thing went wrong
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Something which was asked for doesn't exist
+    NotFound,
+    /// The request was malformed or referred to something invalid
+    InvalidInput,
+    /// The caller isn't allowed to do this
+    Unauthorized,
+    /// Data failed validation
+    Validation,
+    /// The caller has used up a quota
+    QuotaExceeded,
+    /// Something took too long
+    Timeout,
+    /// A service or peer can't be reached right now, so retrying may help
+    Unavailable,
+    /// The work was stopped before it finished, e.g. during shutdown
+    Cancelled,
+    /// The underlying storage failed
+    Storage,
+    /// Data couldn't be serialized or deserialized
+    Serialization,
+    /// Anything else, which is most likely a bug
+    Internal,
+}
+
+impl ErrorKind {
+    /// Whether the same request might succeed if it's tried again later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Timeout | Self::Unavailable | Self::Cancelled)
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::NotFound => "not found",
+            Self::InvalidInput => "invalid input",
+            Self::Unauthorized => "unauthorized",
+            Self::Validation => "validation failed",
+            Self::QuotaExceeded => "quota exceeded",
+            Self::Timeout => "timed out",
+            Self::Unavailable => "unavailable",
+            Self::Cancelled => "cancelled",
+            Self::Storage => "storage error",
+            Self::Serialization => "serialization error",
+            Self::Internal => "internal error",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// An error which knows what kind it is
+pub trait HasErrorKind {
+    /// What sort of thing went wrong
+    fn error_kind(&self) -> ErrorKind;
+}
+
+impl<E: HasErrorKind + ?Sized> HasErrorKind for Box<E> {
+    fn error_kind(&self) -> ErrorKind {
+        (**self).error_kind()
+    }
+}
+
+/// Boxed error type used by the `Other` variants of the p2p errors
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// An error which keeps its kind after it has been boxed
+#[derive(Debug, thiserror::Error)]
+#[error("{source}")]
+pub struct KindedError {
+    kind: ErrorKind,
+    source: BoxError,
+}
+
+impl KindedError {
+    /// Box an error, remembering its kind
+    pub fn new<E>(e: E) -> Self
+    where
+        E: HasErrorKind + std::error::Error + Send + Sync + 'static,
+    {
+        Self {
+            kind: e.error_kind(),
+            source: Box::new(e),
+        }
+    }
+
+    /// Box an error which doesn't know its own kind
+    pub fn with_kind(kind: ErrorKind, e: impl Into<BoxError>) -> Self {
+        Self {
+            kind,
+            source: e.into(),
+        }
+    }
+
+    /// Unwrap the original error
+    pub fn into_inner(self) -> BoxError {
+        self.source
+    }
+}
+
+impl HasErrorKind for KindedError {
+    fn error_kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// Find the kind of a type erased error by looking for a [KindedError]
+/// in its chain of sources. Anything else is [ErrorKind::Internal].
+pub fn kind_of_boxed(e: &(dyn std::error::Error + 'static)) -> ErrorKind {
+    let mut next = Some(e);
+    while let Some(e) = next {
+        if let Some(kinded) = e.downcast_ref::<KindedError>() {
+            return kinded.kind;
+        }
+        next = e.source();
+    }
+    ErrorKind::Internal
+}
+
+/// An error as it's sent over the wire: the kind for code to act on
+/// and a message for people to read
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// What sort of thing went wrong
+    pub kind: ErrorKind,
+    /// The error's Display
+    pub message: String,
+}
+
+impl ErrorReport {
+    /// Report an error of a known kind
+    pub fn new(kind: ErrorKind, message: impl std::fmt::Display) -> Self {
+        Self {
+            kind,
+            message: message.to_string(),
+        }
+    }
+
+    /// Report an error which knows its own kind
+    pub fn from_error<E: HasErrorKind + std::fmt::Display>(e: &E) -> Self {
+        Self::new(e.error_kind(), e)
+    }
+}
+
+impl std::fmt::Display for ErrorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("nothing here")]
+    struct Missing;
+
+    impl HasErrorKind for Missing {
+        fn error_kind(&self) -> ErrorKind {
+            ErrorKind::NotFound
+        }
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("wrapped: {0}")]
+    struct Wrapper(#[source] KindedError);
+
+    #[test]
+    fn kind_survives_boxing() {
+        let boxed: BoxError = Box::new(KindedError::new(Missing));
+        assert_eq!(kind_of_boxed(boxed.as_ref()), ErrorKind::NotFound);
+        assert_eq!(boxed.to_string(), "nothing here");
+
+        let wrapped: BoxError = Box::new(Wrapper(KindedError::new(Missing)));
+        assert_eq!(kind_of_boxed(wrapped.as_ref()), ErrorKind::NotFound);
+
+        let unknown: BoxError = "oops".into();
+        assert_eq!(kind_of_boxed(unknown.as_ref()), ErrorKind::Internal);
+    }
+
+    #[test]
+    fn report_serializes_kind() {
+        let report = ErrorReport::from_error(&Missing);
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            r#"{"kind":"not_found","message":"nothing here"}"#
+        );
+    }
+}
@@ -1,5 +1,9 @@
 /// Thin wrapper around update! for app entries.
-/// The hash evalutes to the HeaderHash of the deleted element, the input is the new app entry.
+/// The hash evalutes to the HeaderHash of the updated element, the input is the new app entry.
+///
+/// The hash can also be the EntryHash of the updated entry. The host then updates the latest
+/// live element for the entry, i.e. the one which hasn't been updated yet. If several of them
+/// haven't, the update fails as a conflict and the app has to pick one by its HeaderHash.
 ///
 /// Updates can reference create and update elements (header+entry) but not deletes.
 ///
@@ -17,6 +21,7 @@
 ///
 /// let foo_zero_header_hash: HeaderHash = commit_entry!(Foo(0))?;
 /// let foo_ten_update_header_hash: HeaderHash = update_entry!(foo_zero_header_hash, Foo(10))?;
+/// let foo_twenty_update_header_hash: HeaderHash = update_entry!(hash_entry!(Foo(10))?, Foo(20))?;
 /// ```
 ///
 /// @todo in the future this will be true because we will have the concept of 'redirects':
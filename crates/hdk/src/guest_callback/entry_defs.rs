@@ -23,7 +23,7 @@
 ///  e.g. the following are equivalent
 ///
 /// ```ignore
-/// #[hdk_entry(id = "foo", visibility = "private", required_validations = 6, )]
+/// #[hdk_entry(id = "foo", visibility = "private", required_validations = 6, required_validation_type = "sub_chain", references = "author, parent")]
 /// pub struct Foo;
 /// ```
 ///
@@ -33,6 +33,9 @@
 /// entry_def!(Foo EntryDef {
 ///   id: "foo".into(),
 ///   visibility: EntryVisibility::Private,
+///   required_validations: 6.into(),
+///   required_validation_type: RequiredValidationType::SubChain,
+///   references: vec!["author".into(), "parent".into()],
 ///   ..Default::default()
 /// });
 /// ```
@@ -59,6 +62,10 @@ macro_rules! entry_def {
             pub fn required_validations() -> $crate::prelude::RequiredValidations {
                 Self::entry_def().required_validations
             }
+
+            pub fn required_validation_type() -> $crate::prelude::RequiredValidationType {
+                Self::entry_def().required_validation_type
+            }
         }
 
         impl TryFrom<&$crate::prelude::Entry> for $t {
@@ -0,0 +1,32 @@
+/// Shorthand to implement the index_op callback with a function from an element to its records.
+///
+/// The callback is run for every valid app entry this zome defines, as the entry is integrated
+/// by an authority. It must be deterministic so every authority indexes an entry the same way.
+/// Entries which shouldn't be indexed return no records.
+///
+/// e.g. the following are the same
+///
+/// ```ignore
+/// index_op!(index_person);
+/// ```
+///
+/// ```ignore
+/// #[hdk_extern]
+/// fn index_op(element: Element) -> ExternResult<IndexOpCallbackResult> {
+///   Ok(index_person(element)?.into())
+/// }
+/// ```
+///
+/// where `index_person` is a `fn(Element) -> ExternResult<Vec<IndexRecord>>`.
+/// @see query_index!
+#[macro_export]
+macro_rules! index_op {
+    ( $f:path ) => {
+        #[hdk_extern]
+        fn index_op(
+            element: $crate::prelude::Element,
+        ) -> $crate::prelude::ExternResult<$crate::prelude::IndexOpCallbackResult> {
+            Ok($crate::prelude::IndexOpCallbackResult::from($f(element)?))
+        }
+    };
+}
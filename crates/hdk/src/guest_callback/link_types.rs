@@ -0,0 +1,34 @@
+/// Shorthand to implement the link types callback similar to the entry_defs![ .. ] macro.
+///
+/// Each link type is a zome-unique string id. The position of each id in the list is what ends
+/// up in the `CreateLink` header, so only ever append new link types to the end of the list.
+///
+/// A link type can also declare that its tags start with a typed value by passing a
+/// `LinkTypeDef::ordered(id, LinkTagOrder)` instead of the id, which lets its links be found
+/// by a range of values with get_links_in_range!.
+///
+/// e.g. the following are the same
+///
+/// ```ignore
+/// link_types![ "follows", LinkTypeDef::ordered("posted_at", LinkTagOrder::Timestamp) ];
+/// ```
+///
+/// ```ignore
+/// #[hdk_extern]
+/// fn link_types(_: ()) -> ExternResult<LinkTypesCallbackResult> {
+///   Ok(vec![ "follows".into(), LinkTypeDef::ordered("posted_at", LinkTagOrder::Timestamp) ].into())
+/// }
+/// ```
+///
+/// Sys validation rejects any typed link that references a link type not declared here.
+/// @see create_typed_link!
+/// @see get_links_in_range!
+#[macro_export]
+macro_rules! link_types {
+    [ $( $link_type:expr ),* ] => {
+        #[hdk_extern]
+        fn link_types(_: ()) -> $crate::prelude::ExternResult<$crate::prelude::LinkTypesCallbackResult> {
+            Ok($crate::prelude::LinkTypesCallbackResult::from(vec![ $( $crate::prelude::LinkTypeDef::from($link_type) ),* ]))
+        }
+    };
+}
@@ -142,6 +142,8 @@ entry_def!(Path EntryDef {
     id: core::str::from_utf8(&NAME).unwrap().into(),
     crdt_type: CrdtType,
     required_validations: RequiredValidations::default(),
+    required_validation_type: RequiredValidationType::default(),
+    references: vec![],
     visibility: EntryVisibility::Public,
 });
 
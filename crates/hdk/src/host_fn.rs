@@ -1,30 +1,46 @@
 pub mod agent_info;
 pub mod call;
+pub mod call_provenance;
 pub mod call_remote;
 pub mod create;
 pub mod create_link;
+pub mod create_links;
 pub mod debug;
 pub mod decrypt;
 pub mod delete;
 pub mod delete_link;
+pub mod delete_links;
 pub mod emit_signal;
 pub mod encrypt;
 pub mod entry_type_properties;
 pub mod get;
 pub mod get_details;
+pub mod get_link_aggregate;
 pub mod get_link_details;
 pub mod get_links;
+pub mod get_links_in_range;
 pub mod hash_entry;
 pub mod keystore;
+pub mod list_pins;
+pub mod observe_chain_head;
+pub mod pin;
 pub mod property;
 pub mod query;
+pub mod query_index;
 pub mod random_bytes;
 pub mod schedule;
 pub mod show_env;
 pub mod sign;
+pub mod subscribe;
 pub mod sys_time;
+pub mod trace_event;
+pub mod unpin;
 pub mod unreachable;
+pub mod unsubscribe;
 pub mod update;
+pub mod validation_package_count;
+pub mod validation_package_query;
+pub mod verify_chain_head_observation;
 pub mod zome_info;
 
 /// Simple wrapper around the holochain_wasmer_guest host_call! macro.
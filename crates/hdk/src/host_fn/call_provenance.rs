@@ -0,0 +1,25 @@
+/// The path of calls which led to the running zome function, ending with it.
+///
+/// ```ignore
+/// if let Some(provenance) = call_provenance!()? {
+///     let origin = provenance.origin;
+/// }
+/// ```
+///
+/// The path starts with the agent who called from outside any zome and adds a hop for each zome
+/// function called on the way, including through `call_remote!`.
+/// Only the immediate caller is authenticated by the network, the rest of the path is as they
+/// reported it.
+/// Callbacks such as init have no path, so this is `None` in them.
+#[macro_export]
+macro_rules! call_provenance {
+    () => {{
+        $crate::prelude::host_externs!(__call_provenance);
+
+        $crate::host_fn!(
+            __call_provenance,
+            $crate::prelude::CallProvenanceInput::new(()),
+            $crate::prelude::CallProvenanceOutput
+        )
+    }};
+}
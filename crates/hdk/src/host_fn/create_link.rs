@@ -63,7 +63,44 @@ macro_rules! create_link {
 
         $crate::host_fn!(
             __create_link,
-            $crate::prelude::CreateLinkInput::new(($base, $target, $tag.into())),
+            $crate::prelude::CreateLinkInput::new(($base, $target, None, $tag.into())),
+            $crate::prelude::CreateLinkOutput
+        )
+    }};
+}
+
+/// Create a link from a base entry to a target entry with a declared link type.
+///
+/// This is the same as create_link! except that the link also carries the position of the link
+/// type in the zome's link_types callback. The link type is given as anything that can be
+/// converted into a LinkTypeId, e.g. a string matching one of the ids passed to link_types!.
+///
+/// The host will fail the call if the link type is not declared by the zome, and sys validation
+/// will reject typed links with out of range link types from other agents.
+///
+/// ```ignore
+/// link_types![ "follows" ];
+///
+/// create_typed_link!(alice_hash, bob_hash, "follows")?;
+/// ```
+///
+/// @see link_types!
+#[macro_export]
+macro_rules! create_typed_link {
+    ( $base:expr, $target:expr, $link_type:expr ) => {
+        $crate::create_typed_link!($base, $target, $link_type, vec![])
+    };
+    ( $base:expr, $target:expr, $link_type:expr, $tag:expr ) => {{
+        $crate::prelude::host_externs!(__create_link);
+
+        $crate::host_fn!(
+            __create_link,
+            $crate::prelude::CreateLinkInput::new((
+                $base,
+                $target,
+                Some($crate::prelude::LinkTypeId::from($link_type)),
+                $tag.into()
+            )),
             $crate::prelude::CreateLinkOutput
         )
     }};
@@ -0,0 +1,30 @@
+/// Create many links at once, e.g. to index the entries of an import.
+///
+/// Each link is a `CreateLinkInput`, as passed to the host by create_link! or create_typed_link!.
+/// All the links are committed in one pass over the source chain, so creating hundreds of links
+/// only crosses between the guest and the host once.
+/// The header hashes are returned in the same order as the links.
+///
+/// ```ignore
+/// let headers: Vec<HeaderHash> = create_links!(vec![
+///     CreateLinkInput::new((base.clone(), post_a, None, LinkTag::new("a"))),
+///     CreateLinkInput::new((base, post_b, Some("follows".into()), LinkTag::new("b"))),
+/// ])?;
+/// ```
+///
+/// Either every link is committed or, if any of them fails, e.g. with an undeclared link type,
+/// none are.
+///
+/// @see create_link!
+#[macro_export]
+macro_rules! create_links {
+    ( $links:expr ) => {{
+        $crate::prelude::host_externs!(__create_links);
+
+        $crate::host_fn!(
+            __create_links,
+            $crate::prelude::CreateLinksInput::new($links),
+            $crate::prelude::CreateLinksOutput
+        )
+    }};
+}
@@ -0,0 +1,25 @@
+/// Delete many links at once by the headers which created them.
+///
+/// All the deletes are committed in one pass over the source chain, and their header hashes are
+/// returned in the same order as the link creation headers.
+///
+/// ```ignore
+/// let link_headers: Vec<HeaderHash> = create_links!(links)?;
+/// let deletes: Vec<HeaderHash> = delete_links!(link_headers)?;
+/// ```
+///
+/// If any of the link creation headers can't be found then nothing is deleted.
+///
+/// @see delete_link!
+#[macro_export]
+macro_rules! delete_links {
+    ( $add_link_headers:expr ) => {{
+        $crate::prelude::host_externs!(__delete_links);
+
+        $crate::host_fn!(
+            __delete_links,
+            $crate::prelude::DeleteLinksInput::new($add_link_headers),
+            $crate::prelude::DeleteLinksOutput
+        )
+    }};
+}
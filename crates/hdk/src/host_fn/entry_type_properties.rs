@@ -1 +1,26 @@
-//! @todo
+/// Get the entry defs registered by zomes in the DNA.
+///
+/// ```ignore
+/// let own: BTreeMap<ZomeName, EntryDefs> = entry_type_properties!(EntryDefsQuery::ThisZome)?;
+/// let everyone = entry_type_properties!(EntryDefsQuery::AllZomes)?;
+/// ```
+///
+/// Each zome's defs are in the order it declared them, with their ids, visibility and required
+/// validations.
+/// Libraries compiled into many different zomes, e.g. migration helpers, can look these up at
+/// runtime to adapt to the entry types the DNA actually declares.
+///
+/// Zomes which don't define any entry types are only listed when asked for by name, with no
+/// defs. Asking for a zome which isn't in the DNA is an error.
+#[macro_export]
+macro_rules! entry_type_properties {
+    ( $query:expr ) => {{
+        $crate::prelude::host_externs!(__entry_type_properties);
+
+        $crate::host_fn!(
+            __entry_type_properties,
+            $crate::prelude::EntryTypePropertiesInput::new($query),
+            $crate::prelude::EntryTypePropertiesOutput
+        )
+    }};
+}
@@ -53,6 +53,6 @@ macro_rules! get {
         )
     }};
     ( $input:expr ) => {
-        get!($input, $crate::prelude::GetOptions)
+        get!($input, $crate::prelude::GetOptions::default())
     };
 }
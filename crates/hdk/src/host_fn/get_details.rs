@@ -42,6 +42,18 @@
 ///       e.g. the DNA itself, links, migrations, etc.
 ///       However the element will still be returned by get_details! if a header hash is passed,
 ///       these header-only elements will have None as the entry value.
+///
+/// Passing `GetOptions::default().follow_references(depth)` also returns the elements which the
+/// entry references in the `references` of the details. References are the hashes held by the
+/// fields named in the entry def's `references`, and the host follows them `depth` levels deep
+/// in the same call, e.g. a comment's post and that post's author profile with a depth of 2.
+///
+/// ```ignore
+/// #[hdk_entry(id = "comment", references = "post")]
+/// struct Comment { post: EntryHash, text: String }
+///
+/// let details = get_details!(comment_hash, GetOptions::default().follow_references(2))?;
+/// ```
 #[macro_export]
 macro_rules! get_details {
     ( $hash:expr, $options:expr ) => {{
@@ -52,6 +64,6 @@ macro_rules! get_details {
         )
     }};
     ( $hash:expr ) => {
-        get_details!($hash, $crate::prelude::GetOptions)
+        get_details!($hash, $crate::prelude::GetOptions::default())
     };
 }
@@ -0,0 +1,33 @@
+/// Returns a count, sum, min or max of the tag values of an ordered link type's links on a base.
+///
+/// The aggregate is computed by the host, so only the result comes back instead of every link,
+/// e.g. to tally votes or total a balance. Tags are read as the `LinkTagOrder` declared for the
+/// link type in link_types!, and tags without a value are skipped. Timestamps can't be summed.
+///
+/// Like get_links_in_range! this reads the ordered index kept by this agent as an authority for
+/// the base, so it only aggregates links this agent holds. Deleted links are not included.
+///
+/// ```ignore
+/// link_types![ LinkTypeDef::ordered("votes", LinkTagOrder::U64) ];
+///
+/// let tally = get_link_aggregate!(proposal, "votes", LinkAggregateOp::Sum)?;
+/// ```
+///
+/// @see get_links_in_range!
+/// @see link_types!
+#[macro_export]
+macro_rules! get_link_aggregate {
+    ( $base:expr, $link_type:expr, $op:expr ) => {{
+        $crate::prelude::host_externs!(__get_link_aggregate);
+
+        $crate::host_fn!(
+            __get_link_aggregate,
+            $crate::prelude::GetLinkAggregateInput::new((
+                $base,
+                $crate::prelude::LinkTypeId::from($link_type),
+                $op
+            )),
+            $crate::prelude::GetLinkAggregateOutput
+        )
+    }};
+}
@@ -0,0 +1,40 @@
+/// Returns the links of an ordered link type on a base, with tags from `start` up to but not
+/// including `end`, in the order of their tags.
+///
+/// The link type must have been declared with a `LinkTagOrder` in link_types!, and the bounds
+/// are tags built the same way as the links' tags, e.g. with `LinkTag::from_timestamp`. Either
+/// bound can be `None` to leave that end of the range open.
+///
+/// Unlike get_links! this reads the ordered index kept by this agent as an authority for the
+/// base, so it only finds links this agent holds. Deleted links are not returned.
+///
+/// ```ignore
+/// link_types![ LinkTypeDef::ordered("posted_at", LinkTagOrder::Timestamp) ];
+///
+/// let last_week = get_links_in_range!(
+///     base,
+///     "posted_at",
+///     Some(LinkTag::from_timestamp(week_ago)),
+///     Some(LinkTag::from_timestamp(now))
+/// )?;
+/// ```
+///
+/// @see get_links!
+/// @see link_types!
+#[macro_export]
+macro_rules! get_links_in_range {
+    ( $base:expr, $link_type:expr, $start:expr, $end:expr ) => {{
+        $crate::prelude::host_externs!(__get_links_in_range);
+
+        $crate::host_fn!(
+            __get_links_in_range,
+            $crate::prelude::GetLinksInRangeInput::new((
+                $base,
+                $crate::prelude::LinkTypeId::from($link_type),
+                $start,
+                $end
+            )),
+            $crate::prelude::GetLinksInRangeOutput
+        )
+    }};
+}
@@ -0,0 +1,19 @@
+/// List every hash pinned on this agent's cell, whether by this zome, another zome or an admin.
+///
+/// ```ignore
+/// let pins: Vec<AnyDhtHash> = list_pins!()?;
+/// ```
+///
+/// @see pin!
+#[macro_export]
+macro_rules! list_pins {
+    () => {{
+        $crate::prelude::host_externs!(__list_pins);
+
+        $crate::host_fn!(
+            __list_pins,
+            $crate::prelude::ListPinsInput::new(()),
+            $crate::prelude::ListPinsOutput
+        )
+    }};
+}
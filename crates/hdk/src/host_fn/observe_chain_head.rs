@@ -0,0 +1,27 @@
+/// Ask an agent's activity authorities to sign what they see at the head of its source chain.
+///
+/// ```ignore
+/// let observations: Vec<SignedChainHeadObservation> = observe_chain_head!(agent)?;
+/// ```
+///
+/// Each observation carries the agent's own signed header at the head of its chain, signed again
+/// by the authority which saw it, along with when it was seen. Store them as evidence: if the
+/// agent later presents a chain with a different header at an observed position, or a shorter
+/// chain, `ChainHeadObservation::dispute` says whether it forked or rolled back.
+///
+/// Authorities which hold none of the agent's chain send nothing, and observations with bad
+/// signatures are dropped, so the result may be empty.
+///
+/// @see verify_chain_head_observation!
+#[macro_export]
+macro_rules! observe_chain_head {
+    ( $agent:expr ) => {{
+        $crate::prelude::host_externs!(__observe_chain_head);
+
+        $crate::host_fn!(
+            __observe_chain_head,
+            $crate::prelude::ObserveChainHeadInput::new($agent),
+            $crate::prelude::ObserveChainHeadOutput
+        )
+    }};
+}
@@ -0,0 +1,25 @@
+/// Pin a hash so this agent's cell always holds its data.
+///
+/// The data is fetched if the cell doesn't hold it yet, and is then kept in the cache for as
+/// long as the hash is pinned. The cell also serves pinned data to other agents who ask for it,
+/// even when the hash is outside the cell's arc, which keeps important data available when its
+/// authorities are offline. Pinning an already pinned hash is harmless.
+///
+/// ```ignore
+/// pin!(hash_entry!(post)?)?;
+/// ```
+///
+/// @see unpin!
+/// @see list_pins!
+#[macro_export]
+macro_rules! pin {
+    ( $hash:expr ) => {{
+        $crate::prelude::host_externs!(__pin);
+
+        $crate::host_fn!(
+            __pin,
+            $crate::prelude::PinInput::new($hash.into()),
+            $crate::prelude::PinOutput
+        )
+    }};
+}
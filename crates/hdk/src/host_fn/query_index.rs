@@ -0,0 +1,26 @@
+/// Search one of this zome's indexes by exact key or key range.
+///
+/// Indexes are built by the `index_op` callback as entries are integrated, so only the entries
+/// that this agent holds as an authority can be found, and a record is only found once its
+/// entry has been integrated.
+///
+/// Returns the matching records in key order as `IndexHits`.
+///
+/// ```ignore
+/// let adults = query_index!(IndexQuery::range("by_age", IndexKey::from_u64(18), IndexKey::from_u64(u64::MAX)))?;
+/// let bobs = query_index!(IndexQuery::exact("by_name", "bob").limit(10))?;
+/// ```
+///
+/// @see index_op!
+#[macro_export]
+macro_rules! query_index {
+    ( $query:expr ) => {{
+        $crate::prelude::host_externs!(__query_index);
+
+        $crate::host_fn!(
+            __query_index,
+            $crate::prelude::QueryIndexInput::new($query),
+            $crate::prelude::QueryIndexOutput
+        )
+    }};
+}
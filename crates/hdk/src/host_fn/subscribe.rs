@@ -0,0 +1,27 @@
+/// Ask the authorities for a basis hash to tell this agent when ops are integrated there.
+///
+/// This is a live query: instead of polling get! or get_links! for changes, the authorities
+/// send the hashes of newly integrated ops to this agent's cell, which emits them to the app
+/// as a `BasisChanged` signal. The signal names the basis, the ops and the authority.
+///
+/// Notifications are best effort. An authority that goes offline, or an authority that joins
+/// after the subscription was made, won't send anything, so resubscribe from time to time if
+/// missing a change matters. Subscribing again to the same basis is harmless.
+///
+/// ```ignore
+/// subscribe!(hash_entry!(post)?)?;
+/// ```
+///
+/// @see unsubscribe!
+#[macro_export]
+macro_rules! subscribe {
+    ( $basis:expr ) => {{
+        $crate::prelude::host_externs!(__subscribe);
+
+        $crate::host_fn!(
+            __subscribe,
+            $crate::prelude::SubscribeInput::new($basis.into()),
+            $crate::prelude::SubscribeOutput
+        )
+    }};
+}
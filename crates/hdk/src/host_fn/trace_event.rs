@@ -0,0 +1,26 @@
+/// Record an event, or add to a counter, on the tracing span of the running zome call.
+///
+/// ```ignore
+/// trace_event!(TraceEvent::event("cache_miss").with_field("key", &key))?;
+/// trace_event!(TraceEvent::counter("entries_scanned", scanned))?;
+/// ```
+///
+/// Events are logged by the conductor under the `zome_trace` target, along with the zome which
+/// emitted them, and exported with the call's span when the conductor exports spans to a
+/// collector.
+/// This lets hApp developers profile their own zome logic without debugging through the guest.
+///
+/// Note: Counters aren't totalled by the conductor, each one is recorded with its increment for
+/// the collector to aggregate.
+#[macro_export]
+macro_rules! trace_event {
+    ( $event:expr ) => {{
+        $crate::prelude::host_externs!(__trace_event);
+
+        $crate::host_fn!(
+            __trace_event,
+            $crate::prelude::TraceEventInput::new($event),
+            $crate::prelude::TraceEventOutput
+        )
+    }};
+}
@@ -0,0 +1,22 @@
+/// Unpin a hash pinned with pin!.
+///
+/// Its data stays in the cache but is no longer guaranteed to be held.
+/// Unpinning a hash that isn't pinned does nothing.
+///
+/// ```ignore
+/// unpin!(hash_entry!(post)?)?;
+/// ```
+///
+/// @see pin!
+#[macro_export]
+macro_rules! unpin {
+    ( $hash:expr ) => {{
+        $crate::prelude::host_externs!(__unpin);
+
+        $crate::host_fn!(
+            __unpin,
+            $crate::prelude::UnpinInput::new($hash.into()),
+            $crate::prelude::UnpinOutput
+        )
+    }};
+}
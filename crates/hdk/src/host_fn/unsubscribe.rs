@@ -0,0 +1,19 @@
+/// Ask the authorities for a basis hash to stop telling this agent about changes there.
+///
+/// ```ignore
+/// unsubscribe!(hash_entry!(post)?)?;
+/// ```
+///
+/// @see subscribe!
+#[macro_export]
+macro_rules! unsubscribe {
+    ( $basis:expr ) => {{
+        $crate::prelude::host_externs!(__unsubscribe);
+
+        $crate::host_fn!(
+            __unsubscribe,
+            $crate::prelude::UnsubscribeInput::new($basis.into()),
+            $crate::prelude::UnsubscribeOutput
+        )
+    }};
+}
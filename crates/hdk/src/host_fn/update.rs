@@ -19,7 +19,7 @@ macro_rules! update {
 
         $crate::host_fn!(
             __update,
-            $crate::prelude::UpdateInput::new(($type, $input, $hash)),
+            $crate::prelude::UpdateInput::new(($type, $input, $hash.into())),
             $crate::prelude::UpdateOutput
         )
     }};
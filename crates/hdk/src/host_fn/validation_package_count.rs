@@ -0,0 +1,32 @@
+/// Count the elements of the validation package which match a filter.
+///
+/// Only usable in validate callbacks, for entry defs with a required validation type of
+/// SubChain or Full. The counting is done by the host, so the package doesn't need to be
+/// walked in wasm, e.g. to allow at most 5 posts per day:
+///
+/// ```ignore
+/// let today = QueryFilter::new()
+///     .header_type(HeaderType::Create)
+///     .entry_type(post_entry_type)
+///     .timestamp_range(day_start..day_end);
+/// if validation_package_count!(today)? >= 5 {
+///     return Ok(ValidateCallbackResult::Invalid("Too many posts today".into()));
+/// }
+/// ```
+///
+/// As with query!, headers without an entry match any entry type,
+/// so filter on the header type too when counting entries.
+///
+/// @see validation_package_query!
+#[macro_export]
+macro_rules! validation_package_count {
+    ( $filter:expr ) => {{
+        $crate::prelude::host_externs!(__validation_package_count);
+
+        $crate::host_fn!(
+            __validation_package_count,
+            $crate::prelude::ValidationPackageCountInput::new($filter),
+            $crate::prelude::ValidationPackageCountOutput
+        )
+    }};
+}
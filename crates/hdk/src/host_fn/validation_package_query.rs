@@ -0,0 +1,22 @@
+/// The elements of the validation package which match a filter, in sequence order.
+///
+/// Only usable in validate callbacks, for entry defs with a required validation type of
+/// SubChain or Full. Entries are left out unless the filter includes them.
+///
+/// ```ignore
+/// let recent = validation_package_query!(QueryFilter::new().timestamp_range(since..until))?;
+/// ```
+///
+/// @see validation_package_count!
+#[macro_export]
+macro_rules! validation_package_query {
+    ( $filter:expr ) => {{
+        $crate::prelude::host_externs!(__validation_package_query);
+
+        $crate::host_fn!(
+            __validation_package_query,
+            $crate::prelude::ValidationPackageQueryInput::new($filter),
+            $crate::prelude::ValidationPackageQueryOutput
+        )
+    }};
+}
@@ -0,0 +1,25 @@
+/// Check that a chain head observation was signed by the authority which made it, and that the
+/// head it saw was signed by the observed agent.
+///
+/// ```ignore
+/// if verify_chain_head_observation!(observation.clone())? {
+///     let dispute = observation.observation.dispute(&chain);
+/// }
+/// ```
+///
+/// Observations received from other agents, e.g. as evidence in an entry, should be checked
+/// before they're trusted. This is available everywhere, including validation callbacks.
+///
+/// @see observe_chain_head!
+#[macro_export]
+macro_rules! verify_chain_head_observation {
+    ( $observation:expr ) => {{
+        $crate::prelude::host_externs!(__verify_chain_head_observation);
+
+        $crate::host_fn!(
+            __verify_chain_head_observation,
+            $crate::prelude::VerifyChainHeadObservationInput::new($observation),
+            $crate::prelude::VerifyChainHeadObservationOutput
+        )
+    }};
+}
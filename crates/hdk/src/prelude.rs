@@ -1,23 +1,30 @@
 pub use crate::agent_info;
+pub use crate::call_provenance;
 pub use crate::call_remote;
 pub use crate::create;
 pub use crate::create_cap_claim;
 pub use crate::create_cap_grant;
 pub use crate::create_entry;
 pub use crate::create_link;
+pub use crate::create_links;
+pub use crate::create_typed_link;
 pub use crate::debug;
 pub use crate::delete;
 pub use crate::delete_cap_grant;
 pub use crate::delete_entry;
 pub use crate::delete_link;
+pub use crate::delete_links;
 pub use crate::entry_def;
 pub use crate::entry_defs;
+pub use crate::entry_type_properties;
 pub use crate::error::HdkError;
 pub use crate::generate_cap_secret;
 pub use crate::get;
 pub use crate::get_details;
+pub use crate::get_link_aggregate;
 pub use crate::get_link_details;
 pub use crate::get_links;
+pub use crate::get_links_in_range;
 pub use crate::hash_entry;
 pub use crate::hash_path::anchor::anchor;
 pub use crate::hash_path::anchor::get_anchor;
@@ -26,14 +33,27 @@ pub use crate::hash_path::anchor::list_anchor_tags;
 pub use crate::hash_path::anchor::list_anchor_type_addresses;
 pub use crate::hash_path::anchor::Anchor;
 pub use crate::hash_path::path::Path;
+pub use crate::index_op;
+pub use crate::link_types;
+pub use crate::list_pins;
 pub use crate::map_extern;
 pub use crate::map_extern::ExternResult;
+pub use crate::observe_chain_head;
+pub use crate::pin;
 pub use crate::query;
+pub use crate::query_index;
 pub use crate::random_bytes;
+pub use crate::subscribe;
 pub use crate::sys_time;
+pub use crate::trace_event;
+pub use crate::unpin;
+pub use crate::unsubscribe;
 pub use crate::update;
 pub use crate::update_cap_grant;
 pub use crate::update_entry;
+pub use crate::validation_package_count;
+pub use crate::validation_package_query;
+pub use crate::verify_chain_head_observation;
 pub use crate::zome_info;
 pub use hdk3_derive::hdk_entry;
 pub use hdk3_derive::hdk_extern;
@@ -53,16 +73,31 @@ pub use holochain_zome_types::element::{Element, ElementVec};
 pub use holochain_zome_types::entry::*;
 pub use holochain_zome_types::entry_def::*;
 pub use holochain_zome_types::header::*;
+pub use holochain_zome_types::index::*;
 pub use holochain_zome_types::init::InitCallbackResult;
+pub use holochain_zome_types::link::LinkAggregate;
+pub use holochain_zome_types::link::LinkAggregateOp;
 pub use holochain_zome_types::link::LinkDetails;
 pub use holochain_zome_types::link::LinkTag;
+pub use holochain_zome_types::link::LinkTagOrder;
+pub use holochain_zome_types::link::LinkTagValue;
+pub use holochain_zome_types::link::LinkTypeDef;
+pub use holochain_zome_types::link::LinkTypeId;
+pub use holochain_zome_types::link::LinkTypesCallbackResult;
 pub use holochain_zome_types::link::Links;
 pub use holochain_zome_types::metadata::Details;
 pub use holochain_zome_types::migrate_agent::MigrateAgent;
 pub use holochain_zome_types::migrate_agent::MigrateAgentCallbackResult;
+pub use holochain_zome_types::observation::{
+    ChainHeadDispute, ChainHeadObservation, SignedChainHeadObservation,
+};
 pub use holochain_zome_types::post_commit::PostCommitCallbackResult;
+pub use holochain_zome_types::provenance::{CallHop, CallProvenance};
 pub use holochain_zome_types::query::ChainQueryFilter as QueryFilter;
+pub use holochain_zome_types::trace::{TraceEvent, TraceEventKind};
+pub use holochain_zome_types::validate::RequiredValidationType;
 pub use holochain_zome_types::validate::ValidateCallbackResult;
+pub use holochain_zome_types::validate::ValidateData;
 pub use holochain_zome_types::validate::ValidationPackage;
 pub use holochain_zome_types::validate::ValidationPackageCallbackResult;
 pub use holochain_zome_types::validate_link_add::ValidateCreateLinkCallbackResult;
@@ -10,6 +10,7 @@ struct EntryDefId(holochain_zome_types::entry_def::EntryDefId);
 struct EntryVisibility(holochain_zome_types::entry_def::EntryVisibility);
 struct CrdtType(holochain_zome_types::crdt::CrdtType);
 struct RequiredValidations(holochain_zome_types::entry_def::RequiredValidations);
+struct RequiredValidationType(holochain_zome_types::validate::RequiredValidationType);
 
 impl Parse for EntryDef {
     fn parse(input: ParseStream) -> Result<Self> {
@@ -17,7 +18,10 @@ impl Parse for EntryDef {
         let mut required_validations =
             holochain_zome_types::entry_def::RequiredValidations::default();
         let mut visibility = holochain_zome_types::entry_def::EntryVisibility::default();
+        let mut required_validation_type =
+            holochain_zome_types::validate::RequiredValidationType::default();
         let crdt_type = holochain_zome_types::crdt::CrdtType::default();
+        let mut references = vec![];
 
         let vars = Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated(input)?;
         for var in vars {
@@ -56,6 +60,36 @@ impl Parse for EntryDef {
                             _ => unreachable!(),
                         };
                     }
+                    "required_validation_type" => {
+                        match var.lit {
+                            syn::Lit::Str(s) => required_validation_type = match s.value().as_str()
+                            {
+                                "element" => {
+                                    holochain_zome_types::validate::RequiredValidationType::Element
+                                }
+                                "sub_chain" => {
+                                    holochain_zome_types::validate::RequiredValidationType::SubChain
+                                }
+                                "full" => {
+                                    holochain_zome_types::validate::RequiredValidationType::Full
+                                }
+                                _ => unreachable!(),
+                            },
+                            _ => unreachable!(),
+                        };
+                    }
+                    "references" => match var.lit {
+                        // a comma separated list of field names
+                        syn::Lit::Str(s) => {
+                            references = s
+                                .value()
+                                .split(',')
+                                .map(|field| field.trim().to_string())
+                                .filter(|field| !field.is_empty())
+                                .collect()
+                        }
+                        _ => unreachable!(),
+                    },
                     "crdt_type" => {
                         unimplemented!();
                     }
@@ -68,6 +102,8 @@ impl Parse for EntryDef {
             required_validations,
             visibility,
             crdt_type,
+            required_validation_type,
+            references,
         }))
     }
 }
@@ -117,12 +153,30 @@ impl quote::ToTokens for EntryVisibility {
     }
 }
 
+impl quote::ToTokens for RequiredValidationType {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let variant = syn::Ident::new(
+            match self.0 {
+                holochain_zome_types::validate::RequiredValidationType::Element => "Element",
+                holochain_zome_types::validate::RequiredValidationType::SubChain => "SubChain",
+                holochain_zome_types::validate::RequiredValidationType::Full => "Full",
+            },
+            proc_macro2::Span::call_site(),
+        );
+        tokens.append_all(quote::quote! {
+            hdk3::prelude::RequiredValidationType::#variant
+        });
+    }
+}
+
 impl quote::ToTokens for EntryDef {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         let id = EntryDefId(self.0.id.clone());
         let visibility = EntryVisibility(self.0.visibility);
         let crdt_type = CrdtType(self.0.crdt_type);
         let required_validations = RequiredValidations(self.0.required_validations);
+        let required_validation_type = RequiredValidationType(self.0.required_validation_type);
+        let references = &self.0.references;
 
         tokens.append_all(quote::quote! {
             hdk3::prelude::EntryDef {
@@ -130,6 +184,8 @@ impl quote::ToTokens for EntryDef {
                 visibility: #visibility,
                 crdt_type: #crdt_type,
                 required_validations: #required_validations,
+                required_validation_type: #required_validation_type,
+                references: vec![#(String::from(#references)),*],
             }
         });
     }
@@ -1,4 +1,5 @@
 use crate::{has_hash::HasHash, HashType, PrimitiveHashType};
+use subtle::ConstantTimeEq;
 
 pub(crate) const HASH_CORE_LEN: usize = 32;
 pub(crate) const HASH_LOC_LEN: usize = 4;
@@ -10,7 +11,7 @@ pub(crate) const HASH_SERIALIZED_LEN: usize = HASH_CORE_LEN + HASH_LOC_LEN;
 /// type which specifies what it is a hash of.
 // TODO: make holochain_serial! / the derive able to deal with a type param
 // or if not, implement the TryFroms manually...
-#[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
 pub struct HoloHash<T> {
     #[serde(with = "serde_bytes")]
     hash: Vec<u8>,
@@ -60,6 +61,26 @@ impl<T: HashType> HoloHash<T> {
     }
 }
 
+/// Constant time equality check for HoloHash.
+/// Agent keys are compared when checking who may do what, so comparisons
+/// which return early on the first differing byte would let a remote agent
+/// learn how close a guessed key is by measuring latency.
+impl<T: HashType> PartialEq for HoloHash<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash_type == other.hash_type && bool::from(self.hash.ct_eq(&other.hash))
+    }
+}
+
+impl<T: HashType> Eq for HoloHash<T> {}
+
+/// Must agree with the constant time PartialEq
+impl<T: HashType> std::hash::Hash for HoloHash<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+        self.hash_type.hash(state);
+    }
+}
+
 impl<P: PrimitiveHashType> HoloHash<P> {
     /// Construct from 36 raw bytes, using the known PrimitiveHashType
     pub fn from_raw_bytes(hash: Vec<u8>) -> Self {
@@ -151,6 +172,19 @@ mod tests {
         assert_type("DhtOpHash", DhtOpHash::from_raw_bytes(vec![0xdb; 36]));
     }
 
+    #[test]
+    fn test_eq_compares_bytes_and_type() {
+        let mut bytes = vec![0xdb; 36];
+        let agent = AgentPubKey::from_raw_bytes(bytes.clone());
+        assert_eq!(agent, AgentPubKey::from_raw_bytes(bytes.clone()));
+        bytes[35] = 0;
+        assert_ne!(agent, AgentPubKey::from_raw_bytes(bytes));
+
+        let entry: AnyDhtHash = EntryHash::from_raw_bytes(vec![0xdb; 36]).into();
+        let header: AnyDhtHash = HeaderHash::from_raw_bytes(vec![0xdb; 36]).into();
+        assert_ne!(entry, header);
+    }
+
     #[test]
     #[should_panic]
     fn test_fails_with_bad_size() {
@@ -0,0 +1,105 @@
+use holochain::conductor::sandbox::{spawn_sandbox_conductor, SandboxConductor};
+use holochain_types::{
+    dna::DnaFile,
+    observability::{self, Output},
+};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use tracing::*;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "hc-sandbox",
+    about = "Spin up a set of throwaway conductors running a DNA, for local development."
+)]
+struct Opt {
+    #[structopt(help = "Path to the DnaFile (*.dna.gz) to install on every conductor")]
+    dna_path: PathBuf,
+
+    #[structopt(
+        short = "n",
+        long,
+        help = "How many conductors to spawn, each with its own agent",
+        default_value = "1"
+    )]
+    num_conductors: usize,
+
+    #[structopt(
+        long,
+        help = "The AppId to install the DNA under",
+        default_value = "sandbox"
+    )]
+    app_id: String,
+
+    #[structopt(
+        short = "r",
+        long,
+        help = "Directory to create the conductors in. If omitted, a temporary
+    directory is used and removed on exit"
+    )]
+    root: Option<PathBuf>,
+
+    #[structopt(long, help = "See `holochain --help`", default_value = "None")]
+    structured: Output,
+}
+
+fn main() {
+    holochain::conductor::tokio_runtime().block_on(async_main())
+}
+
+async fn async_main() {
+    human_panic::setup_panic!();
+
+    let opt = Opt::from_args();
+    observability::init_fmt(opt.structured).expect("Failed to start contextual logging");
+
+    let dna_content = tokio::fs::read(&opt.dna_path)
+        .await
+        .expect("Could not read the DnaFile");
+    let dna = DnaFile::from_file_content(&dna_content)
+        .await
+        .expect("Could not parse the DnaFile");
+
+    // Keep the tempdir alive until shutdown, so it's removed on exit
+    let (root, _tmpdir) = match opt.root {
+        Some(root) => (root, None),
+        None => {
+            let tmpdir = tempdir::TempDir::new("hc-sandbox").expect("Could not create tempdir");
+            (tmpdir.path().to_owned(), Some(tmpdir))
+        }
+    };
+
+    let mut sandboxes: Vec<SandboxConductor> = Vec::with_capacity(opt.num_conductors);
+    for i in 0..opt.num_conductors {
+        let sandbox = spawn_sandbox_conductor(
+            root.join(format!("conductor-{}", i)),
+            dna.clone(),
+            opt.app_id.clone(),
+        )
+        .await
+        .expect("Could not spawn sandbox conductor");
+        debug!(root = ?sandbox.root, "spawned sandbox conductor");
+        sandboxes.push(sandbox);
+    }
+
+    println!("DNA: {}", dna.dna_hash());
+    for (i, sandbox) in sandboxes.iter().enumerate() {
+        println!(
+            "Conductor {}:\n    root:       {}\n    admin port: {}\n    app port:   {}\n    agent:      {}",
+            i,
+            sandbox.root.display(),
+            sandbox.admin_port,
+            sandbox.app_port,
+            sandbox.agent_key,
+        );
+    }
+    println!("Sandbox ready. Press Ctrl-C to shut down.");
+
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Could not listen for Ctrl-C");
+
+    for sandbox in sandboxes {
+        sandbox.handle.shutdown().await;
+    }
+}
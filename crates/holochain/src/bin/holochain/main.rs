@@ -1,8 +1,12 @@
 use holochain::conductor::{
-    compat::load_conductor_from_legacy_config, config::ConductorConfig, error::ConductorError,
-    interactive, paths::ConfigFilePath, Conductor, ConductorHandle,
+    compat::load_conductor_from_legacy_config,
+    config::{ConductorConfig, LoggerConfig},
+    error::ConductorError,
+    interactive,
+    paths::ConfigFilePath,
+    Conductor, ConductorHandle, DEFAULT_SHUTDOWN_DEADLINE,
 };
-use holochain_types::observability::{self, Output};
+use holochain_types::observability::{self, OtlpGuard, Output};
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -47,28 +51,66 @@ struct Opt {
     useful when running a conductor for the first time"
     )]
     interactive: bool,
-}
 
-fn main() {
-    holochain::conductor::tokio_runtime()
-        // the async_main function should only end if our program is done
-        .block_on(async_main())
+    #[structopt(
+        long,
+        help = "Print a default conductor configuration, with every option described, and exit"
+    )]
+    print_default_config: bool,
 }
 
-async fn async_main() {
+fn main() {
     // Sets up a human-readable panic message with a request for bug reports
     //
     // See https://docs.rs/human-panic/1.0.3/human_panic/
     human_panic::setup_panic!();
 
     let opt = Opt::from_args();
-    observability::init_fmt(opt.structured).expect("Failed to start contextual logging");
-    debug!("observability initialized");
 
-    let conductor = if let Some(legacy_config_path) = opt.legacy_tryorama_config_path {
-        conductor_handle_from_legacy_config_path(&legacy_config_path).await
+    if opt.print_default_config {
+        print!("{}", ConductorConfig::default_commented_toml());
+        return;
+    }
+
+    // The config decides how the runtime is built, so it has to be loaded
+    // before there is a runtime to load it on
+    let config = if opt.legacy_tryorama_config_path.is_some() {
+        None
     } else {
-        conductor_handle_from_config_path(opt.config_path.clone(), opt.interactive).await
+        Some(load_config_from_path(
+            opt.config_path.clone(),
+            opt.interactive,
+        ))
+    };
+    let runtime_config = config
+        .as_ref()
+        .and_then(|config| config.tokio_runtime.clone())
+        .unwrap_or_default();
+
+    holochain::conductor::tokio_runtime_with_config(&runtime_config)
+        // the async_main function should only end if our program is done
+        .block_on(async_main(opt, config))
+}
+
+async fn async_main(opt: Opt, config: Option<ConductorConfig>) {
+    // Spans are exported until this is dropped at the end of main
+    let logging_guard;
+    let conductor = match (opt.legacy_tryorama_config_path, config) {
+        (Some(legacy_config_path), _) => {
+            logging_guard = init_logging(opt.structured, None);
+            conductor_handle_from_legacy_config_path(&legacy_config_path).await
+        }
+        (None, Some(config)) => {
+            // The config may ask for logs to go to files, so logging is
+            // initialized only once it's loaded
+            logging_guard = init_logging(opt.structured, config.logger.as_ref());
+            Conductor::builder()
+                .config(config)
+                .build()
+                .await
+                .expect("Could not initialize Conductor from configuration")
+        }
+        (None, None) => unreachable!("Config is always loaded without a legacy config"),
     };
 
     info!("Conductor successfully initialized.");
@@ -78,6 +120,8 @@ async fn async_main() {
     // interfaces are running, and can be connected to.
     println!("{}", MAGIC_CONDUCTOR_READY_STRING);
 
+    tokio::task::spawn(shutdown_on_signal(conductor.clone()));
+
     // Await on the main JoinHandle, keeping the process alive until all
     // Conductor activity has ceased
     conductor
@@ -90,9 +134,55 @@ async fn async_main() {
             e
         })
         .expect("Error while joining threads during shutdown");
+    drop(logging_guard);
+}
 
-    // TODO: on SIGINT/SIGKILL, kill the conductor:
-    // conductor.kill().await
+/// Shut the conductor down gracefully on SIGTERM or SIGINT
+#[cfg(unix)]
+async fn shutdown_on_signal(conductor: ConductorHandle) {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sigterm = signal(SignalKind::terminate()).expect("Couldn't listen for SIGTERM");
+    let mut sigint = signal(SignalKind::interrupt()).expect("Couldn't listen for SIGINT");
+    tokio::select! {
+        _ = sigterm.recv() => info!("Received SIGTERM"),
+        _ = sigint.recv() => info!("Received SIGINT"),
+    }
+    conductor
+        .shutdown_gracefully(DEFAULT_SHUTDOWN_DEADLINE)
+        .await;
+}
+
+/// Shut the conductor down gracefully on ctrl-c
+#[cfg(not(unix))]
+async fn shutdown_on_signal(conductor: ConductorHandle) {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Couldn't listen for ctrl-c");
+    info!("Received ctrl-c");
+    conductor
+        .shutdown_gracefully(DEFAULT_SHUTDOWN_DEADLINE)
+        .await;
+}
+
+fn init_logging(structured: Output, logger: Option<&LoggerConfig>) -> Option<OtlpGuard> {
+    let LoggerConfig { file, otlp } = logger.cloned().unwrap_or_default();
+    let guard = match (file, otlp) {
+        (file, Some(otlp)) => Some(
+            observability::init_fmt_with_otlp(structured, file, otlp)
+                .expect("Failed to start exporting spans"),
+        ),
+        (Some(file), None) => {
+            observability::init_fmt_with_file(structured, file)
+                .expect("Failed to start contextual logging");
+            None
+        }
+        (None, None) => {
+            observability::init_fmt(structured).expect("Failed to start contextual logging");
+            None
+        }
+    };
+    debug!("observability initialized");
+    guard
 }
 
 async fn conductor_handle_from_legacy_config_path(legacy_config_path: &Path) -> ConductorHandle {
@@ -104,10 +194,7 @@ async fn conductor_handle_from_legacy_config_path(legacy_config_path: &Path) ->
         .expect("Couldn't initialize conductor from legacy config")
 }
 
-async fn conductor_handle_from_config_path(
-    config_path: Option<PathBuf>,
-    interactive: bool,
-) -> ConductorHandle {
+fn load_config_from_path(config_path: Option<PathBuf>, interactive: bool) -> ConductorConfig {
     let config_path_default = config_path.is_none();
     let config_path: ConfigFilePath = config_path.map(Into::into).unwrap_or_default();
     debug!("config_path: {}", config_path);
@@ -139,12 +226,7 @@ async fn conductor_handle_from_config_path(
         }
     }
 
-    // Initialize the Conductor
-    Conductor::builder()
-        .config(config)
-        .build()
-        .await
-        .expect("Could not initialize Conductor from configuration")
+    config
 }
 
 /// Load config, throw friendly error on failure
@@ -158,6 +240,10 @@ fn load_config(config_path: &ConfigFilePath, config_path_default: bool) -> Condu
             display_friendly_malformed_config_message(config_path, err);
             std::process::exit(ERROR_CODE);
         }
+        Err(ConductorError::ConfigError(err)) => {
+            display_friendly_malformed_config_message(config_path, err);
+            std::process::exit(ERROR_CODE);
+        }
         result => result.expect("Could not load conductor config"),
     }
 }
@@ -196,13 +282,17 @@ automatically create a default config file.
     }
 }
 
-fn display_friendly_malformed_config_message(config_path: &ConfigFilePath, error: toml::de::Error) {
+fn display_friendly_malformed_config_message(
+    config_path: &ConfigFilePath,
+    error: impl std::fmt::Display,
+) {
     println!(
         "
 The specified config file ({})
-could not be parsed, because it is not valid TOML. Please check and fix the
-file, or delete the file and run the conductor again with the -i flag to create
-a valid default configuration. Details:
+could not be loaded, because it is not a valid conductor configuration. Please
+check and fix the file, or delete the file and run the conductor again with the
+-i flag to create a valid default configuration. Run the conductor with
+--print-default-config to see every option. Details:
 
     {}
 
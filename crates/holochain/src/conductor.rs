@@ -13,6 +13,7 @@
 // TODO: clean up allows once parent is fully documented
 
 pub mod api;
+pub mod blocking_detector;
 mod cell;
 #[allow(missing_docs)]
 pub mod compat;
@@ -26,29 +27,44 @@ pub mod entry_def_store;
 #[allow(missing_docs)]
 pub mod error;
 pub mod handle;
+pub mod health;
 pub mod interactive;
 #[allow(missing_docs)]
 pub mod interface;
 pub mod manager;
+pub mod passphrase_service;
 pub mod paths;
+pub mod sandbox;
 pub mod state;
+pub mod zome_call_scheduler;
 
 pub use cell::{error::CellError, Cell};
-pub use conductor::{Conductor, ConductorBuilder, ConductorStateDb};
+pub use conductor::{Conductor, ConductorBuilder, ConductorStateDb, DEFAULT_SHUTDOWN_DEADLINE};
 pub use handle::ConductorHandle;
 
 /// setup a tokio runtime that meets the conductor's needs
 pub fn tokio_runtime() -> tokio::runtime::Runtime {
-    tokio::runtime::Builder::new()
+    tokio_runtime_with_config(&Default::default())
+}
+
+/// setup a tokio runtime that meets the conductor's needs, with the thread
+/// counts overridden by config
+pub fn tokio_runtime_with_config(config: &config::TokioRuntimeConfig) -> tokio::runtime::Runtime {
+    let mut builder = tokio::runtime::Builder::new();
+    builder
         // we use both IO and Time tokio utilities
         .enable_all()
         // we want to use multiple threads
         .threaded_scheduler()
         // we want to use thread count matching cpu count
         // (sometimes tokio by default only uses half cpu core threads)
-        .core_threads(num_cpus::get())
+        .core_threads(config.core_threads.unwrap_or_else(num_cpus::get))
         // give our threads a descriptive name (they'll be numbered too)
-        .thread_name("holochain-tokio-thread")
+        .thread_name("holochain-tokio-thread");
+    if let Some(max_threads) = config.max_threads {
+        builder.max_threads(max_threads);
+    }
+    builder
         // build the runtime
         .build()
         // panic if we cannot (we cannot run without it)
@@ -3,12 +3,17 @@
 use super::error::{ConductorApiError, ConductorApiResult};
 use crate::conductor::{entry_def_store::EntryDefBufferKey, ConductorHandle};
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::signal::{CellSignal, Signal};
 use crate::core::workflow::ZomeCallInvocationResult;
 use async_trait::async_trait;
 use holo_hash::DnaHash;
 use holochain_keystore::KeystoreSender;
-use holochain_types::{autonomic::AutonomicCue, cell::CellId, dna::DnaFile};
-use holochain_zome_types::entry_def::EntryDef;
+use holochain_types::{
+    autonomic::AutonomicCue,
+    cell::CellId,
+    dna::{zome::Zome, DnaFile},
+};
+use holochain_zome_types::{entry_def::EntryDef, link::LinkTypes};
 use tracing::*;
 
 /// The concrete implementation of [CellConductorApiT], which is used to give
@@ -80,6 +85,32 @@ impl CellConductorApiT for CellConductorApi {
     async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef> {
         self.conductor_handle.get_entry_def(key).await
     }
+
+    async fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes> {
+        self.conductor_handle.get_link_types(zome).await
+    }
+
+    async fn add_link_types(&self, zome: Zome, link_types: LinkTypes) {
+        self.conductor_handle.add_link_types(zome, link_types).await
+    }
+
+    async fn emit_signal(&self, signal: Signal) {
+        // An error just means no interface is listening
+        let _ = self
+            .conductor_handle
+            .signal_broadcaster()
+            .await
+            .send(CellSignal {
+                cell_id: self.cell_id.clone(),
+                signal,
+            });
+    }
+
+    async fn cell_died(&self, error: String) {
+        if let Err(e) = self.conductor_handle.cell_died(&self.cell_id, error).await {
+            error!(cell_id = ?self.cell_id, error = ?e, "Couldn't pause the apps of a dead Cell");
+        }
+    }
 }
 
 /// The "internal" Conductor API interface, for a Cell to talk to its calling Conductor.
@@ -115,4 +146,19 @@ pub trait CellConductorApiT: Clone + Send + Sync + Sized {
 
     /// Get a [EntryDef] from the [EntryDefBuf]
     async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef>;
+
+    /// Get the [LinkTypes] a zome declares from the [DnaStore],
+    /// if they've been added
+    async fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes>;
+
+    /// Add the [LinkTypes] a zome declares to the [DnaStore],
+    /// so the zome's callback needn't be run again
+    async fn add_link_types(&self, zome: Zome, link_types: LinkTypes);
+
+    /// Send a [Signal] from this cell to the interfaces of its app
+    async fn emit_signal(&self, signal: Signal);
+
+    /// Tell the conductor one of this cell's tasks has died,
+    /// so the cell can't do its work until it's started again
+    async fn cell_died(&self, error: String);
 }
@@ -3,18 +3,31 @@ use crate::conductor::api::error::{
     ConductorApiError, ConductorApiResult, ExternalApiWireError, SerializationError,
 };
 use crate::conductor::{
-    config::AdminInterfaceConfig,
+    config::{AdminInterfaceConfig, AdminScope, AdminToken},
     error::CreateAppError,
-    interface::error::{InterfaceError, InterfaceResult},
-    ConductorHandle,
+    health::HealthReport,
+    interface::{
+        error::{InterfaceError, InterfaceResult},
+        signal_routes::AppInterfaceBinding,
+    },
+    ConductorHandle, DEFAULT_SHUTDOWN_DEADLINE,
 };
+use crate::core::dht_metrics::DhtSizeEstimate;
+use crate::core::dht_snapshot::SnapshotImportReport;
+use crate::core::queue_consumer::{QueueInfo, QueueTrigger};
+use crate::core::redundancy::RedundancyReport;
+use crate::core::state::limbo_dump::LimboOpInfo;
 use holo_hash::*;
 use holochain_keystore::KeystoreSenderExt;
+pub use holochain_p2p::actor::GossipRound;
 use holochain_serialized_bytes::prelude::*;
 use holochain_types::{
-    app::{AppId, InstallAppDnaPayload, InstallAppPayload, InstalledApp, InstalledCell},
-    cell::CellId,
-    dna::{DnaFile, JsonProperties},
+    app::{
+        AppBundle, AppBundleSource, AppId, AppInfo, AppStatus, CellNick, InstallAppBundlePayload,
+        InstallAppDnaPayload, InstallAppPayload, InstalledApp, InstalledCell, MembraneProof,
+    },
+    cell::{CellId, CellIdInfo},
+    dna::{DnaFile, DnaInfo, JsonProperties},
 };
 use std::path::PathBuf;
 use tracing::*;
@@ -53,6 +66,9 @@ pub struct RealAdminInterfaceApi {
     /// Needed to spawn an App interface
     // TODO: is this needed? it's not currently being used.
     app_api: RealAppInterfaceApi,
+
+    /// The tokens which may use this interface. Empty if it's open.
+    tokens: Vec<AdminToken>,
 }
 
 impl RealAdminInterfaceApi {
@@ -61,8 +77,137 @@ impl RealAdminInterfaceApi {
         RealAdminInterfaceApi {
             conductor_handle,
             app_api,
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Only accept requests carrying one of these tokens
+    pub(crate) fn with_tokens(mut self, tokens: Vec<AdminToken>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// Unwrap a request from its token, along with the token's scope.
+    /// On an open interface any token is ignored.
+    fn authorize(
+        &self,
+        request: AdminRequest,
+    ) -> ConductorApiResult<(Option<AdminScope>, AdminRequest)> {
+        match request {
+            AdminRequest::WithToken { request, .. } if self.tokens.is_empty() => {
+                Ok((None, *request))
+            }
+            AdminRequest::WithToken { token, request } => {
+                let admin_token = self
+                    .tokens
+                    .iter()
+                    .find(|t| t.token == token)
+                    .ok_or_else(|| ConductorApiError::Unauthorized("unknown token".into()))?;
+                Ok((admin_token.scope.clone(), *request))
+            }
+            request if self.tokens.is_empty() => Ok((None, request)),
+            _ => Err(ConductorApiError::Unauthorized(
+                "this interface needs a token".into(),
+            )),
+        }
+    }
+
+    /// Check that a scoped token may make a request.
+    /// Scoped tokens can manage the apps and Cells their scope covers, but
+    /// nothing conductor-wide and nothing which touches the conductor's
+    /// filesystem, so they can only install apps from bundle bytes.
+    /// Installing creates Cells for the given agent, so the agent must be
+    /// in the scope even when the app id is.
+    async fn check_scope(
+        &self,
+        scope: &AdminScope,
+        request: &AdminRequest,
+    ) -> ConductorApiResult<()> {
+        use AdminRequest::*;
+        let apps = self.conductor_handle.list_apps(None).await?;
+        let allowed = match request {
+            GenerateAgentPubKey | ListCellIds | ListApps { .. } => true,
+            InstallAppBundle(payload) => {
+                matches!(payload.source, AppBundleSource::Bytes(_))
+                    && scope.agents.contains(&payload.agent_key)
+            }
+            ActivateApp { app_id } | DeactivateApp { app_id } => apps
+                .iter()
+                .any(|app| &app.app_id == app_id && scope.covers_app(app)),
+            DumpState { cell_id }
+            | DumpLimbo { cell_id }
+            | EstimateDhtSize { cell_id, .. }
+            | ListQueueTriggers { cell_id }
+            | TriggerQueue { cell_id, .. }
+            | CheckRedundancy { cell_id }
+            | PinHashes { cell_id, .. }
+            | UnpinHashes { cell_id, .. }
+            | ListPins { cell_id } => scope.covers_cell(&apps, cell_id),
+            _ => false,
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(ConductorApiError::Unauthorized(
+                "the token's scope doesn't cover this request".into(),
+            ))
         }
     }
+
+    /// Leave out of a response whatever a scoped token can't see
+    async fn filter_response(
+        &self,
+        scope: &AdminScope,
+        response: AdminResponse,
+    ) -> ConductorApiResult<AdminResponse> {
+        Ok(match response {
+            AdminResponse::AppsListed(mut apps) => {
+                apps.retain(|app| scope.covers_app(app));
+                AdminResponse::AppsListed(apps)
+            }
+            AdminResponse::ListCellIds(mut cells) => {
+                let apps = self.conductor_handle.list_apps(None).await?;
+                cells.retain(|cell| scope.covers_cell(&apps, &cell.cell_id));
+                AdminResponse::ListCellIds(cells)
+            }
+            response => response,
+        })
+    }
+
+    /// Install the Dnas of an app and run genesis on the resulting cells
+    async fn install_app_dnas(
+        &self,
+        app_id: AppId,
+        agent_key: AgentPubKey,
+        dnas: Vec<(DnaFile, CellNick, Option<MembraneProof>)>,
+    ) -> ConductorApiResult<InstalledApp> {
+        // Install Dnas
+        let tasks = dnas.into_iter().map(|(dna, nick, membrane_proof)| async {
+            let hash = dna.dna_hash().clone();
+            let cell_id = CellId::from((hash, agent_key.clone()));
+            self.conductor_handle.install_dna(dna).await?;
+            ConductorApiResult::Ok((InstalledCell::new(cell_id, nick), membrane_proof))
+        });
+
+        // Join all the install tasks
+        let cell_ids_with_proofs = futures::future::join_all(tasks)
+            .await
+            .into_iter()
+            // Check all passed and return the proofs
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Call genesis
+        self.conductor_handle
+            .clone()
+            .install_app(app_id.clone(), cell_ids_with_proofs.clone())
+            .await?;
+
+        let cell_data = cell_ids_with_proofs
+            .into_iter()
+            .map(|(cell_data, _)| cell_data)
+            .collect();
+        Ok(InstalledApp { app_id, cell_data })
+    }
 }
 
 #[async_trait::async_trait]
@@ -71,8 +216,13 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
         &self,
         request: AdminRequest,
     ) -> ConductorApiResult<AdminResponse> {
+        let (scope, request) = self.authorize(request)?;
+        if let Some(scope) = &scope {
+            self.check_scope(scope, &request).await?;
+        }
+
         use AdminRequest::*;
-        match request {
+        let response = match request {
             AddAdminInterfaces(configs) => Ok(AdminResponse::AdminInterfacesAdded(
                 self.conductor_handle
                     .clone()
@@ -87,43 +237,62 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                     dnas,
                 } = *payload;
 
-                // Install Dnas
+                // Read Dnas
                 let tasks = dnas.into_iter().map(|dna_payload| async {
                     let InstallAppDnaPayload {
                         path,
                         properties,
+                        uuid,
                         membrane_proof,
                         nick,
                     } = dna_payload;
-                    let dna = read_parse_dna(path, properties).await?;
-                    let hash = dna.dna_hash().clone();
-                    let cell_id = CellId::from((hash.clone(), agent_key.clone()));
-                    self.conductor_handle.install_dna(dna).await?;
-                    ConductorApiResult::Ok((InstalledCell::new(cell_id, nick), membrane_proof))
+                    let dna = read_parse_dna(path, properties, uuid).await?;
+                    ConductorApiResult::Ok((dna, nick, membrane_proof))
                 });
 
-                // Join all the install tasks
-                let cell_ids_with_proofs = futures::future::join_all(tasks)
+                // Join all the read tasks
+                let dnas = futures::future::join_all(tasks)
                     .await
                     .into_iter()
-                    // Check all passed and return the proofs
                     .collect::<Result<Vec<_>, _>>()?;
 
-                // Call genesis
-                self.conductor_handle
-                    .clone()
-                    .install_app(app_id.clone(), cell_ids_with_proofs.clone())
-                    .await?;
+                let app = self.install_app_dnas(app_id, agent_key, dnas).await?;
+                Ok(AdminResponse::AppInstalled(app))
+            }
+            InstallAppBundle(payload) => {
+                let InstallAppBundlePayload {
+                    app_id,
+                    agent_key,
+                    source,
+                    uuid,
+                    mut membrane_proofs,
+                } = *payload;
 
-                let cell_data = cell_ids_with_proofs
-                    .into_iter()
-                    .map(|(cell_data, _)| cell_data)
-                    .collect();
-                let app = InstalledApp { app_id, cell_data };
+                let bundle = match source {
+                    AppBundleSource::Bytes(bytes) => AppBundle::unpack(&bytes).await?,
+                    AppBundleSource::Path(path) => AppBundle::read_from_file(path).await?,
+                };
+                let app_id = app_id.unwrap_or_else(|| bundle.manifest().app_name().to_string());
+                trace!(?app_id, roles = ?bundle.manifest().roles());
+
+                let mut dnas = Vec::new();
+                for (nick, dna) in bundle.resolve_cells().await? {
+                    let dna = dna.with_overrides(uuid.clone(), None).await?;
+                    let membrane_proof = membrane_proofs.remove(&nick);
+                    dnas.push((dna, nick, membrane_proof));
+                }
+
+                let app = self.install_app_dnas(app_id, agent_key, dnas).await?;
                 Ok(AdminResponse::AppInstalled(app))
             }
             ListDnas => {
-                let dna_list = self.conductor_handle.list_dnas().await?;
+                let mut dna_list = Vec::new();
+                for dna_hash in self.conductor_handle.list_dnas().await? {
+                    if let Some(dna) = self.conductor_handle.get_dna(&dna_hash).await {
+                        dna_list.push(DnaInfo::from(&dna));
+                    }
+                }
+                dna_list.sort_by(|a, b| a.dna_hash_b64.cmp(&b.dna_hash_b64));
                 Ok(AdminResponse::ListDnas(dna_list))
             }
             GenerateAgentPubKey => {
@@ -137,7 +306,13 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
             }
             ListCellIds => {
                 let cell_ids = self.conductor_handle.list_cell_ids().await?;
-                Ok(AdminResponse::ListCellIds(cell_ids))
+                Ok(AdminResponse::ListCellIds(
+                    cell_ids.into_iter().map(CellIdInfo::from).collect(),
+                ))
+            }
+            ListApps { status_filter } => {
+                let apps = self.conductor_handle.list_apps(status_filter).await?;
+                Ok(AdminResponse::AppsListed(apps))
             }
             ActivateApp { app_id } => {
                 // Activate app
@@ -166,12 +341,30 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 self.conductor_handle.deactivate_app(app_id.clone()).await?;
                 Ok(AdminResponse::AppDeactivated)
             }
-            AttachAppInterface { port } => {
+            AttachAppInterface { port, app_ids } => {
                 let port = port.unwrap_or(0);
                 let port = self
                     .conductor_handle
                     .clone()
-                    .add_app_interface(port)
+                    .add_app_interface(port, app_ids)
+                    .await?;
+                Ok(AdminResponse::AppInterfaceAttached { port })
+            }
+            BindAppInterface { port, app_ids } => {
+                self.conductor_handle
+                    .bind_app_interface(port, app_ids)
+                    .await?;
+                Ok(AdminResponse::AppInterfaceBound)
+            }
+            ListAppInterfaceBindings => Ok(AdminResponse::AppInterfaceBindingsListed(
+                self.conductor_handle.list_app_interface_bindings().await,
+            )),
+            AttachHttpAppInterface { port } => {
+                let port = port.unwrap_or(0);
+                let port = self
+                    .conductor_handle
+                    .clone()
+                    .add_http_app_interface(port)
                     .await?;
                 Ok(AdminResponse::AppInterfaceAttached { port })
             }
@@ -179,24 +372,133 @@ impl AdminInterfaceApi for RealAdminInterfaceApi {
                 let state = self.conductor_handle.dump_cell_state(&cell_id).await?;
                 Ok(AdminResponse::JsonState(state))
             }
+            DumpLimbo { cell_id } => {
+                let ops = self.conductor_handle.dump_cell_limbo(&cell_id).await?;
+                Ok(AdminResponse::LimboDumped(ops))
+            }
+            RecordNetworkEvents { cell_id, path } => {
+                self.conductor_handle
+                    .record_network_events(&cell_id, path)
+                    .await?;
+                Ok(AdminResponse::NetworkEventsRecording)
+            }
+            ReplayNetworkEvents { cell_id, path } => {
+                let count = self
+                    .conductor_handle
+                    .replay_network_events(&cell_id, path)
+                    .await?;
+                Ok(AdminResponse::NetworkEventsReplayed(count))
+            }
+            ExportDhtSnapshot { cell_id, path } => {
+                let count = self
+                    .conductor_handle
+                    .export_dht_snapshot(&cell_id, path)
+                    .await?;
+                Ok(AdminResponse::DhtSnapshotExported(count))
+            }
+            ImportDhtSnapshot { cell_id, path } => {
+                let report = self
+                    .conductor_handle
+                    .import_dht_snapshot(&cell_id, path)
+                    .await?;
+                Ok(AdminResponse::DhtSnapshotImported(report))
+            }
+            PeerReputations { dna_hash } => {
+                let scores = self.conductor_handle.peer_reputations(&dna_hash).await?;
+                Ok(AdminResponse::PeerReputations(scores))
+            }
+            GossipRounds { dna_hash } => {
+                let rounds = self.conductor_handle.gossip_rounds(&dna_hash).await?;
+                Ok(AdminResponse::GossipRounds(rounds))
+            }
+            EstimateDhtSize { cell_id, samples } => {
+                let estimate = self
+                    .conductor_handle
+                    .estimate_dht_size(&cell_id, samples)
+                    .await?;
+                Ok(AdminResponse::DhtSizeEstimated(estimate))
+            }
+            ListQueueTriggers { cell_id } => {
+                let queues = self.conductor_handle.queue_info(&cell_id).await?;
+                Ok(AdminResponse::QueueTriggersListed(queues))
+            }
+            TriggerQueue { cell_id, queue } => {
+                self.conductor_handle.trigger_queue(&cell_id, queue).await?;
+                Ok(AdminResponse::QueueTriggered)
+            }
+            CheckRedundancy { cell_id } => {
+                let report = self.conductor_handle.check_redundancy(&cell_id).await?;
+                Ok(AdminResponse::RedundancyChecked(report))
+            }
+            PinHashes { cell_id, hashes } => {
+                self.conductor_handle.pin_hashes(&cell_id, hashes).await?;
+                Ok(AdminResponse::HashesPinned)
+            }
+            UnpinHashes { cell_id, hashes } => {
+                self.conductor_handle.unpin_hashes(&cell_id, hashes).await?;
+                Ok(AdminResponse::HashesUnpinned)
+            }
+            ListPins { cell_id } => {
+                let pins = self.conductor_handle.list_pins(&cell_id).await?;
+                Ok(AdminResponse::PinsListed(pins))
+            }
+            CheckHealth => Ok(AdminResponse::HealthChecked(
+                self.conductor_handle.check_health().await,
+            )),
+            UnlockKeystore { passphrase } => {
+                let errors = self
+                    .conductor_handle
+                    .clone()
+                    .unlock_keystore(passphrase)
+                    .await?;
+                if !errors.is_empty() {
+                    error!(
+                        msg = "Failed to create the following active apps",
+                        cell_startup_errors = ?errors
+                    );
+                }
+                Ok(AdminResponse::KeystoreUnlocked)
+            }
+            Shutdown { deadline_ms } => {
+                let deadline = deadline_ms
+                    .map(std::time::Duration::from_millis)
+                    .unwrap_or(DEFAULT_SHUTDOWN_DEADLINE);
+                // Shut down in the background so that this response can
+                // still be sent
+                let conductor_handle = self.conductor_handle.clone();
+                tokio::task::spawn(
+                    async move { conductor_handle.shutdown_gracefully(deadline).await },
+                );
+                Ok(AdminResponse::ShuttingDown)
+            }
+            WithToken { .. } => Err(ConductorApiError::Unauthorized(
+                "requests can't carry more than one token".into(),
+            )),
+        }?;
+
+        match scope {
+            Some(scope) => self.filter_response(&scope, response).await,
+            None => Ok(response),
         }
     }
 }
 
-/// Reads the [Dna] from disk and parses to [SerializedBytes]
+/// Reads the [Dna] from disk and parses to [SerializedBytes],
+/// applying any properties and UUID to override
 async fn read_parse_dna(
     dna_path: PathBuf,
     properties: Option<JsonProperties>,
+    uuid: Option<String>,
 ) -> ConductorApiResult<DnaFile> {
     let dna_content = tokio::fs::read(dna_path)
         .await
         .map_err(|e| ConductorApiError::DnaReadError(format!("{:?}", e)))?;
-    let mut dna = DnaFile::from_file_content(&dna_content).await?;
-    if let Some(properties) = properties {
-        let properties = SerializedBytes::try_from(properties).map_err(SerializationError::from)?;
-        dna = dna.with_properties(properties).await?;
-    }
-    Ok(dna)
+    let dna = DnaFile::from_file_content(&dna_content).await?;
+    let properties = properties
+        .map(SerializedBytes::try_from)
+        .transpose()
+        .map_err(SerializationError::from)?;
+    Ok(dna.with_overrides(uuid, properties).await?)
 }
 
 #[async_trait::async_trait]
@@ -234,12 +536,24 @@ pub enum AdminRequest {
     /// Triggers genesis to be run on all cells and
     /// Dnas to be stored
     InstallApp(Box<InstallAppPayload>),
-    /// List all installed [Dna]s
+    /// Install an app from an [AppBundle], provisioning a Cell for each
+    /// role in the bundle's manifest.
+    /// Triggers genesis to be run on all cells and
+    /// Dnas to be stored
+    InstallAppBundle(Box<InstallAppBundlePayload>),
+    /// List all installed [Dna]s, with their names and zomes
     ListDnas,
     /// Generate a new AgentPubKey
     GenerateAgentPubKey,
-    /// List all the cell ids in the conductor
+    /// List all the Cells running in the conductor
     ListCellIds,
+    /// List the installed apps, with each one's status, whether its Cells
+    /// are running and why not, and its Cells
+    ListApps {
+        /// Only list apps with this status. Use None to list every app.
+        #[serde(default)]
+        status_filter: Option<AppStatus>,
+    },
     /// Activate an app
     ActivateApp {
         /// The AppId to activate
@@ -255,12 +569,163 @@ pub enum AdminRequest {
         /// Optional port, use None to let the
         /// OS choose a free port
         port: Option<u16>,
+        /// The apps whose signals the interface receives.
+        /// Use None to receive signals from every app.
+        #[serde(default)]
+        app_ids: Option<Vec<AppId>>,
+    },
+    /// Change which apps' signals an attached app interface receives
+    BindAppInterface {
+        /// The port the app interface is attached on
+        port: u16,
+        /// The apps whose signals the interface receives.
+        /// Use None to receive signals from every app.
+        app_ids: Option<Vec<AppId>>,
+    },
+    /// List which apps' signals each app interface receives
+    ListAppInterfaceBindings,
+    /// Attach an app interface which serves JSON over HTTP,
+    /// for clients which can't use websockets.
+    /// Needs the conductor to be built with the `http_interface` feature.
+    AttachHttpAppInterface {
+        /// Optional port, use None to let the
+        /// OS choose a free port
+        port: Option<u16>,
     },
     /// Dump the state of a cell
     DumpState {
         /// The CellId for which to dump state
         cell_id: Box<CellId>,
     },
+    /// List every op a cell is holding in validation or integration limbo,
+    /// with what it's waiting on, to diagnose data which never becomes live
+    DumpLimbo {
+        /// The CellId whose limbo to dump
+        cell_id: Box<CellId>,
+    },
+    /// Record every network event a cell receives to a file, so that it
+    /// can be replayed elsewhere to reproduce a bug
+    RecordNetworkEvents {
+        /// The CellId whose events to record
+        cell_id: Box<CellId>,
+        /// The file to append events to. `None` stops recording.
+        path: Option<std::path::PathBuf>,
+    },
+    /// Feed the events from a recording back through a cell's network
+    /// event handlers, in the order they were received
+    ReplayNetworkEvents {
+        /// The CellId to replay the events to
+        cell_id: Box<CellId>,
+        /// The recording to replay
+        path: std::path::PathBuf,
+    },
+    /// Export every op a cell has integrated as valid to a snapshot file,
+    /// which can seed a new network for the same Dna
+    ExportDhtSnapshot {
+        /// The CellId to export
+        cell_id: Box<CellId>,
+        /// Where to write the snapshot
+        path: std::path::PathBuf,
+    },
+    /// Import a snapshot file into a cell. The ops are validated like any
+    /// others published to the cell, and ops whose hash or signature
+    /// doesn't check out are dropped.
+    ImportDhtSnapshot {
+        /// The CellId to import into. Its Dna must match the snapshot's.
+        cell_id: Box<CellId>,
+        /// The snapshot to import
+        path: std::path::PathBuf,
+    },
+    /// Inspect the reputation of every peer that has misbehaved on a Dna's
+    /// network, e.g. by timing out or sending bad data
+    PeerReputations {
+        /// The Dna whose network to inspect
+        dna_hash: DnaHash,
+    },
+    /// Inspect the most recent gossip rounds with each peer on a Dna's
+    /// network: how many ops were offered, accepted and sent, how long
+    /// each round took and what went wrong. Useful when data isn't
+    /// converging.
+    GossipRounds {
+        /// The Dna whose network to inspect
+        dna_hash: DnaHash,
+    },
+    /// Estimate how many ops a Dna's DHT holds, and how many copies of
+    /// each there are, by asking the authorities around random locations
+    /// how many ops they hold
+    EstimateDhtSize {
+        /// The CellId whose network to sample
+        cell_id: Box<CellId>,
+        /// How many locations to sample
+        samples: u8,
+    },
+    /// List the workflow queues of a cell, with whether each has a
+    /// pending trigger and how much work is waiting on it
+    ListQueueTriggers {
+        /// The CellId whose queues to inspect
+        cell_id: Box<CellId>,
+    },
+    /// Manually fire a workflow trigger on a cell, e.g. to unstick
+    /// data which isn't propagating
+    TriggerQueue {
+        /// The CellId whose queue to trigger
+        cell_id: Box<CellId>,
+        /// The queue to trigger
+        queue: QueueTrigger,
+    },
+    /// Ask the authorities how many of them hold a sample of a cell's
+    /// authored ops, and publish again any held by too few
+    CheckRedundancy {
+        /// The CellId whose authored ops to check
+        cell_id: Box<CellId>,
+    },
+    /// Pin hashes on a cell so their data is always held locally and served
+    /// to other agents, even outside the cell's arc.
+    /// Data the cell doesn't hold yet is fetched first.
+    PinHashes {
+        /// The CellId to pin the hashes on
+        cell_id: Box<CellId>,
+        /// The hashes to pin
+        hashes: Vec<AnyDhtHash>,
+    },
+    /// Unpin hashes on a cell
+    UnpinHashes {
+        /// The CellId to unpin the hashes on
+        cell_id: Box<CellId>,
+        /// The hashes to unpin
+        hashes: Vec<AnyDhtHash>,
+    },
+    /// List every hash pinned on a cell
+    ListPins {
+        /// The CellId whose pins to list
+        cell_id: Box<CellId>,
+    },
+    /// Check whether the conductor is live and ready to serve requests
+    CheckHealth,
+    /// Give the keystore its passphrase, if the conductor was started with
+    /// its keystore locked, then start the active apps' Cells.
+    /// If the keystore doesn't unlock with it, it stays locked and the
+    /// passphrase can be given again.
+    UnlockKeystore {
+        /// The passphrase which unlocks the keystore
+        passphrase: String,
+    },
+    /// Gracefully shut down the conductor. In-flight zome calls and queued
+    /// workflows are given until the deadline to finish.
+    Shutdown {
+        /// How long to wait for in-flight work, in milliseconds.
+        /// Defaults to 10 seconds.
+        deadline_ms: Option<u64>,
+    },
+    /// Make a request with a token. Interfaces configured with tokens only
+    /// accept requests wrapped in this, and a scoped token may only manage
+    /// the apps and agents in its scope.
+    WithToken {
+        /// One of the interface's tokens
+        token: String,
+        /// The request to make
+        request: Box<AdminRequest>,
+    },
 }
 
 /// Responses to messages received on an Admin interface
@@ -274,12 +739,14 @@ pub enum AdminResponse {
     AppInstalled(InstalledApp),
     /// AdminInterfaces have successfully been added
     AdminInterfacesAdded(()),
-    /// A list of all installed [Dna]s
-    ListDnas(Vec<DnaHash>),
+    /// A list of all installed [Dna]s, by base64 hash
+    ListDnas(Vec<DnaInfo>),
     /// Keystore generated a new AgentPubKey
     GenerateAgentPubKey(AgentPubKey),
-    /// Listing all the cell ids in the conductor
-    ListCellIds(Vec<CellId>),
+    /// Listing all the Cells running in the conductor
+    ListCellIds(Vec<CellIdInfo>),
+    /// The installed apps, by app id
+    AppsListed(Vec<AppInfo>),
     /// [AppInterfaceApi] successfully attached
     AppInterfaceAttached {
         /// Port of the new [AppInterfaceApi]
@@ -293,21 +760,69 @@ pub enum AdminResponse {
     AppDeactivated,
     /// State of a cell
     JsonState(String),
+    /// The ops in a cell's limbo
+    LimboDumped(Vec<LimboOpInfo>),
+    /// Network events are being recorded, or recording has stopped
+    NetworkEventsRecording,
+    /// How many recorded network events were replayed
+    NetworkEventsReplayed(usize),
+    /// How many ops were written to the snapshot
+    DhtSnapshotExported(usize),
+    /// How the snapshot import went
+    DhtSnapshotImported(SnapshotImportReport),
+    /// Each reported peer's reputation, from 1.0 (nothing against it)
+    /// falling towards 0.0
+    PeerReputations(Vec<(AgentPubKey, f64)>),
+    /// The most recent gossip rounds with each peer, oldest first
+    GossipRounds(Vec<(AgentPubKey, Vec<GossipRound>)>),
+    /// The estimated size and redundancy of a DHT
+    DhtSizeEstimated(DhtSizeEstimate),
+    /// The state of each of a cell's workflow queues
+    QueueTriggersListed(Vec<QueueInfo>),
+    /// The queue was triggered successfully
+    QueueTriggered,
+    /// How well replicated a sample of a cell's authored ops are
+    RedundancyChecked(RedundancyReport),
+    /// The hashes were fetched and pinned successfully
+    HashesPinned,
+    /// The hashes were unpinned successfully
+    HashesUnpinned,
+    /// Every hash pinned on a cell
+    PinsListed(Vec<AnyDhtHash>),
+    /// The conductor's liveness and readiness
+    HealthChecked(HealthReport),
+    /// The keystore was unlocked and the active apps' Cells started
+    KeystoreUnlocked,
+    /// The app interface's apps were changed successfully
+    AppInterfaceBound,
+    /// Which apps' signals each app interface receives
+    AppInterfaceBindingsListed(Vec<AppInterfaceBinding>),
+    /// The conductor has begun shutting down, and will refuse any further requests
+    ShuttingDown,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::conductor::Conductor;
+    use crate::conductor::{api::error::ErrorKind, Conductor};
     use anyhow::Result;
     use holochain_state::test_utils::{test_conductor_env, test_wasm_env, TestEnvironment};
     use holochain_types::{
-        app::InstallAppDnaPayload,
+        app::{
+            app_manifest::{
+                AppManifestV1, AppRoleDnaManifest, AppRoleManifest, DnaLocation, DnaVersionSpec,
+            },
+            AppManifest, InstallAppDnaPayload,
+        },
         observability,
-        test_utils::{fake_agent_pubkey_1, fake_dna_file, fake_dna_zomes, write_fake_dna_file},
+        test_utils::{
+            fake_agent_pubkey_1, fake_agent_pubkey_2, fake_dna_file, fake_dna_zomes,
+            write_fake_dna_file,
+        },
     };
     use holochain_wasm_test_utils::TestWasm;
     use matches::assert_matches;
+    use unwrap_to::unwrap_to;
     use uuid::Uuid;
 
     #[tokio::test(threaded_scheduler)]
@@ -350,8 +865,19 @@ mod test {
             AdminResponse::AppInstalled(cell_ids) if cell_ids == expected_cell_ids
         );
         let dna_list = admin_api.handle_admin_request(AdminRequest::ListDnas).await;
-        let expects = vec![dna_hash];
-        assert_matches!(dna_list, AdminResponse::ListDnas(a) if a == expects);
+        let dnas = unwrap_to!(dna_list => AdminResponse::ListDnas).clone();
+        assert_eq!(dnas.len(), 1);
+        assert_eq!(dnas[0].dna_hash, dna_hash);
+        assert_eq!(dnas[0].dna_hash_b64, dna_hash.to_string());
+        assert_eq!(dnas[0].uuid, uuid.to_string());
+        assert_eq!(dnas[0].zome_names, vec![TestWasm::Foo.into()]);
+
+        let res = admin_api
+            .handle_admin_request(AdminRequest::ListApps {
+                status_filter: Some(AppStatus::Active),
+            })
+            .await;
+        assert_matches!(res, AdminResponse::AppsListed(apps) if apps.is_empty());
 
         let res = admin_api
             .handle_admin_request(AdminRequest::ActivateApp {
@@ -365,7 +891,265 @@ mod test {
             .handle_admin_request(AdminRequest::ListCellIds)
             .await;
 
-        assert_matches!(res, AdminResponse::ListCellIds(v) if v == vec![cell_id]);
+        let cells = unwrap_to!(res => AdminResponse::ListCellIds).clone();
+        assert_eq!(cells, vec![CellIdInfo::from(cell_id.clone())]);
+        assert_eq!(
+            cells[0].agent_pub_key_b64,
+            cell_id.agent_pubkey().to_string()
+        );
+
+        let res = admin_api
+            .handle_admin_request(AdminRequest::ListApps {
+                status_filter: None,
+            })
+            .await;
+        let apps = unwrap_to!(res => AdminResponse::AppsListed).clone();
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app_id, "test");
+        assert_eq!(apps[0].status, AppStatus::Active);
+        assert_eq!(apps[0].cells.len(), 1);
+        assert_eq!(apps[0].cells[0].cell_id, cell_id);
+        assert_eq!(
+            apps[0].cells[0].dna_hash_b64,
+            cell_id.dna_hash().to_string()
+        );
+        assert!(apps[0].cells[0].running);
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn install_app_bundle() -> Result<()> {
+        observability::test_run().ok();
+        let test_env = test_conductor_env();
+        let TestEnvironment {
+            env: wasm_env,
+            tmpdir: _tmpdir,
+        } = test_wasm_env();
+        let _tmpdir = test_env.tmpdir.clone();
+        let handle = Conductor::builder().test(test_env, wasm_env).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone());
+        let uuid = Uuid::new_v4();
+        let dna = fake_dna_zomes(
+            &uuid.to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let manifest = AppManifest::V1(AppManifestV1 {
+            name: "bundled".to_string(),
+            description: None,
+            roles: vec![AppRoleManifest {
+                id: "foo".to_string(),
+                provisioning: None,
+                dna: AppRoleDnaManifest {
+                    location: DnaLocation::Bundled("foo.dna.gz".into()),
+                    properties: None,
+                    uuid: None,
+                    version: Some(DnaVersionSpec::new(vec![dna.dna_hash().clone()])),
+                },
+            }],
+        });
+        let mut resources = std::collections::BTreeMap::new();
+        resources.insert("foo.dna.gz".into(), dna.to_file_content().await?);
+        let bundle = AppBundle::new(manifest, resources);
+
+        let agent_key = fake_agent_pubkey_1();
+        let cell_id = CellId::new(dna.dna_hash().clone(), agent_key.clone());
+        let expected = InstalledApp {
+            app_id: "bundled".to_string(),
+            cell_data: vec![InstalledCell::new(cell_id, "foo".to_string())],
+        };
+        let payload = InstallAppBundlePayload {
+            app_id: None,
+            agent_key,
+            source: AppBundleSource::Bytes(bundle.pack().await?),
+            uuid: None,
+            membrane_proofs: Default::default(),
+        };
+
+        let install_response = admin_api
+            .handle_admin_request(AdminRequest::InstallAppBundle(Box::new(payload)))
+            .await;
+        assert_matches!(
+            install_response,
+            AdminResponse::AppInstalled(app) if app == expected
+        );
+
+        handle.shutdown().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
+            .await
+            .ok();
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn scoped_tokens_only_manage_their_apps() -> Result<()> {
+        observability::test_run().ok();
+        let test_env = test_conductor_env();
+        let TestEnvironment {
+            env: wasm_env,
+            tmpdir: _tmpdir,
+        } = test_wasm_env();
+        let _tmpdir = test_env.tmpdir.clone();
+        let handle = Conductor::builder().test(test_env, wasm_env).await?;
+        let shutdown = handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(handle.clone()).with_tokens(vec![
+            AdminToken {
+                token: "root".into(),
+                scope: None,
+            },
+            AdminToken {
+                token: "customer".into(),
+                scope: Some(AdminScope {
+                    app_ids: vec!["customer-app".into()],
+                    agents: vec![fake_agent_pubkey_1()],
+                }),
+            },
+        ]);
+        let request = |token: &str, request| AdminRequest::WithToken {
+            token: token.into(),
+            request: Box::new(request),
+        };
+        let unauthorized = |response| match response {
+            AdminResponse::Error(e) => e.kind() == ErrorKind::Unauthorized,
+            _ => false,
+        };
+
+        let dna = fake_dna_zomes(
+            &Uuid::new_v4().to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+        let manifest = AppManifest::V1(AppManifestV1 {
+            name: "bundled".to_string(),
+            description: None,
+            roles: vec![AppRoleManifest {
+                id: "foo".to_string(),
+                provisioning: None,
+                dna: AppRoleDnaManifest {
+                    location: DnaLocation::Bundled("foo.dna.gz".into()),
+                    properties: None,
+                    uuid: None,
+                    version: None,
+                },
+            }],
+        });
+        let mut resources = std::collections::BTreeMap::new();
+        resources.insert("foo.dna.gz".into(), dna.to_file_content().await?);
+        let bundle = AppBundle::new(manifest, resources).pack().await?;
+        let install = |app_id: &str, agent_key| {
+            AdminRequest::InstallAppBundle(Box::new(InstallAppBundlePayload {
+                app_id: Some(app_id.into()),
+                agent_key,
+                source: AppBundleSource::Bytes(bundle.clone()),
+                uuid: None,
+                membrane_proofs: Default::default(),
+            }))
+        };
+
+        // Requests without a known token are turned away
+        assert!(unauthorized(
+            admin_api.handle_admin_request(AdminRequest::ListDnas).await
+        ));
+        assert!(unauthorized(
+            admin_api
+                .handle_admin_request(request("guess", AdminRequest::ListDnas))
+                .await
+        ));
+
+        let res = admin_api
+            .handle_admin_request(request("root", install("other-app", fake_agent_pubkey_2())))
+            .await;
+        let other_cell = unwrap_to!(res => AdminResponse::AppInstalled).cell_data[0]
+            .as_id()
+            .clone();
+
+        // Naming their app doesn't let the customer install it for someone else
+        assert!(unauthorized(
+            admin_api
+                .handle_admin_request(request(
+                    "customer",
+                    install("customer-app", fake_agent_pubkey_2()),
+                ))
+                .await
+        ));
+        let res = admin_api
+            .handle_admin_request(request(
+                "customer",
+                install("customer-app", fake_agent_pubkey_1()),
+            ))
+            .await;
+        assert_matches!(res, AdminResponse::AppInstalled(_));
+
+        // The customer can manage their own app but not anyone else's,
+        // and can't do anything conductor-wide
+        let res = admin_api
+            .handle_admin_request(request(
+                "customer",
+                AdminRequest::ActivateApp {
+                    app_id: "customer-app".into(),
+                },
+            ))
+            .await;
+        assert_matches!(res, AdminResponse::AppActivated);
+        assert!(unauthorized(
+            admin_api
+                .handle_admin_request(request(
+                    "customer",
+                    AdminRequest::DeactivateApp {
+                        app_id: "other-app".into(),
+                    },
+                ))
+                .await
+        ));
+        assert!(unauthorized(
+            admin_api
+                .handle_admin_request(request(
+                    "customer",
+                    AdminRequest::DumpState {
+                        cell_id: Box::new(other_cell),
+                    },
+                ))
+                .await
+        ));
+        assert!(unauthorized(
+            admin_api
+                .handle_admin_request(request("customer", AdminRequest::ListDnas))
+                .await
+        ));
+        assert!(unauthorized(
+            admin_api
+                .handle_admin_request(request(
+                    "customer",
+                    install("another-app", fake_agent_pubkey_2()),
+                ))
+                .await
+        ));
+
+        // Listings leave out what the customer can't see
+        let res = admin_api
+            .handle_admin_request(request(
+                "customer",
+                AdminRequest::ListApps {
+                    status_filter: None,
+                },
+            ))
+            .await;
+        let apps = unwrap_to!(res => AdminResponse::AppsListed);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].app_id, "customer-app");
+        let res = admin_api
+            .handle_admin_request(request(
+                "root",
+                AdminRequest::ListApps {
+                    status_filter: None,
+                },
+            ))
+            .await;
+        assert_matches!(res, AdminResponse::AppsListed(apps) if apps.len() == 2);
 
         handle.shutdown().await;
         tokio::time::timeout(std::time::Duration::from_secs(1), shutdown)
@@ -384,11 +1168,25 @@ mod test {
             "how_many": 42,
         });
         let properties = Some(JsonProperties::new(json.clone()));
-        let result = read_parse_dna(dna_path, properties).await?;
+        let result = read_parse_dna(dna_path, properties, None).await?;
         let properties = JsonProperties::new(json);
         let mut dna = dna.dna().clone();
         dna.properties = properties.try_into().unwrap();
         assert_eq!(&dna, result.dna());
         Ok(())
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn dna_read_overrides_uuid() -> Result<()> {
+        let dna = fake_dna_file(&Uuid::new_v4().to_string());
+        let (dna_path, _tmpdir) = write_fake_dna_file(dna.clone()).await?;
+        let staging = read_parse_dna(dna_path.clone(), None, Some("staging".into())).await?;
+        let unchanged = read_parse_dna(dna_path, None, None).await?;
+        assert_eq!(staging.dna().uuid, "staging");
+        assert_eq!(staging.dna().zomes, dna.dna().zomes);
+        // Same code on a different network
+        assert_ne!(staging.dna_hash(), dna.dna_hash());
+        assert_eq!(unchanged.dna_hash(), dna.dna_hash());
+        Ok(())
+    }
 }
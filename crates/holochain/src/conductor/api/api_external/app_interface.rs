@@ -1,14 +1,26 @@
 use super::InterfaceApi;
 use crate::conductor::api::error::{ConductorApiResult, ExternalApiWireError, SerializationError};
 use crate::conductor::{
+    error::ConductorError,
     interface::error::{InterfaceError, InterfaceResult},
     ConductorHandle,
 };
+use crate::core::cancel::CancelToken;
+use crate::core::chain_audit::ChainAudit;
+use crate::core::offline::CellNetworkStatus;
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::state::source_chain::ChainCapabilities;
+use crate::core::workflow::ZomeCallInvocationResult;
+use holo_hash::AgentPubKey;
 use holochain_serialized_bytes::prelude::*;
-use holochain_types::app::{AppId, InstalledApp};
+use holochain_types::{
+    activity::ChainRange,
+    app::{AppId, InstalledApp},
+    cell::CellId,
+};
 use holochain_zome_types::ExternOutput;
 use holochain_zome_types::ZomeCallResponse;
+use std::time::Duration;
 
 /// The interface that a Conductor exposes to the outside world.
 #[async_trait::async_trait]
@@ -53,24 +65,66 @@ impl AppInterfaceApi for RealAppInterfaceApi {
         &self,
         request: AppRequest,
     ) -> ConductorApiResult<AppResponse> {
+        if self.conductor_handle.is_keystore_locked().await {
+            return Err(ConductorError::KeystoreLocked.into());
+        }
         match request {
             AppRequest::AppInfo { app_id } => Ok(AppResponse::AppInfo(
                 self.conductor_handle.get_app_info(&app_id).await?,
             )),
             AppRequest::ZomeCallInvocation(request) => {
-                match self.conductor_handle.call_zome(*request).await? {
-                    Ok(ZomeCallResponse::Ok(output)) => {
-                        Ok(AppResponse::ZomeCallInvocation(Box::new(output)))
-                    }
-                    Ok(ZomeCallResponse::Unauthorized) => Ok(AppResponse::ZomeCallUnauthorized),
-                    Err(e) => Ok(AppResponse::Error(e.into())),
-                }
+                zome_call_response(self.conductor_handle.call_zome(*request).await?)
             }
+            AppRequest::ZomeCallInvocationWithTimeout {
+                invocation,
+                timeout_ms,
+            } => {
+                let cancel = CancelToken::new();
+                cancel.cancel_after(Duration::from_millis(timeout_ms));
+                zome_call_response(
+                    self.conductor_handle
+                        .call_zome_cancellable(*invocation, cancel)
+                        .await?,
+                )
+            }
+            AppRequest::AuditSourceChain {
+                cell_id,
+                agent,
+                range,
+            } => Ok(AppResponse::SourceChainAudit(
+                self.conductor_handle
+                    .audit_source_chain(&cell_id, agent, range)
+                    .await?,
+            )),
+            AppRequest::PollAgentActivity { cell_id, agent } => Ok(AppResponse::SourceChainAudit(
+                self.conductor_handle
+                    .poll_agent_activity(&cell_id, agent)
+                    .await?,
+            )),
+            AppRequest::SetCellOffline { cell_id, offline } => Ok(AppResponse::CellNetworkStatus(
+                self.conductor_handle
+                    .set_cell_offline(&cell_id, offline)
+                    .await?,
+            )),
+            AppRequest::CellNetworkStatus { cell_id } => Ok(AppResponse::CellNetworkStatus(
+                self.conductor_handle.cell_network_status(&cell_id).await?,
+            )),
+            AppRequest::ListCapabilities { cell_id } => Ok(AppResponse::Capabilities(
+                self.conductor_handle.list_capabilities(&cell_id).await?,
+            )),
             AppRequest::Crypto(_) => unimplemented!("Crypto methods currently unimplemented"),
         }
     }
 }
 
+fn zome_call_response(result: ZomeCallInvocationResult) -> ConductorApiResult<AppResponse> {
+    match result {
+        Ok(ZomeCallResponse::Ok(output)) => Ok(AppResponse::ZomeCallInvocation(Box::new(output))),
+        Ok(ZomeCallResponse::Unauthorized) => Ok(AppResponse::ZomeCallUnauthorized),
+        Err(e) => Ok(AppResponse::Error(e.into())),
+    }
+}
+
 #[async_trait::async_trait]
 impl InterfaceApi for RealAppInterfaceApi {
     type ApiRequest = AppRequest;
@@ -108,6 +162,60 @@ pub enum AppRequest {
 
     /// Call a zome function
     ZomeCallInvocation(Box<ZomeCallInvocation>),
+
+    /// Call a zome function, cancelling it if it hasn't finished in time.
+    /// Nothing the call wrote is committed if it's cancelled.
+    ZomeCallInvocationWithTimeout {
+        /// The call to make
+        invocation: Box<ZomeCallInvocation>,
+        /// How many milliseconds the call may take
+        timeout_ms: u64,
+    },
+
+    /// Verify a range of an agent's source chain using only its headers,
+    /// fetched through a Cell's network
+    AuditSourceChain {
+        /// The Cell whose network to fetch headers through
+        cell_id: CellId,
+        /// The agent whose chain to audit
+        agent: AgentPubKey,
+        /// The range of the chain to audit
+        range: ChainRange,
+    },
+
+    /// Verify an agent's whole source chain, only fetching the headers
+    /// after those this Cell has already verified
+    PollAgentActivity {
+        /// The Cell whose network to fetch headers through
+        cell_id: CellId,
+        /// The agent whose chain to audit
+        agent: AgentPubKey,
+    },
+
+    /// Put a Cell in or out of offline mode.
+    /// While offline, zome calls only see the Cell's local state and
+    /// nothing is published. Coming back online publishes everything
+    /// authored in the meantime and refreshes the bases it touched.
+    SetCellOffline {
+        /// The Cell to put in or out of offline mode
+        cell_id: CellId,
+        /// Whether the Cell should be offline
+        offline: bool,
+    },
+
+    /// Find out whether a Cell is offline and how much it has waiting to publish
+    CellNetworkStatus {
+        /// The Cell to get the status of
+        cell_id: CellId,
+    },
+
+    /// List the cap grants this agent has committed to a Cell's source chain,
+    /// with their functions and assignees, and the cap claims it holds.
+    /// Grants and claims which have been updated or deleted aren't listed.
+    ListCapabilities {
+        /// The Cell whose source chain to read
+        cell_id: CellId,
+    },
 }
 
 /// Responses to requests received on an App interface
@@ -125,6 +233,15 @@ pub enum AppResponse {
 
     /// The zome call is unauthorized
     ZomeCallUnauthorized,
+
+    /// The response to an AuditSourceChain or PollAgentActivity request
+    SourceChainAudit(ChainAudit),
+
+    /// The response to a SetCellOffline or CellNetworkStatus request
+    CellNetworkStatus(CellNetworkStatus),
+
+    /// The response to a ListCapabilities request
+    Capabilities(ChainCapabilities),
 }
 
 #[allow(missing_docs)]
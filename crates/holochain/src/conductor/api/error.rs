@@ -12,11 +12,14 @@ use crate::{
         workflow::error::WorkflowError,
     },
 };
+use holochain_error_kind::HasErrorKind;
 use holochain_serialized_bytes::prelude::*;
 use holochain_state::error::DatabaseError;
 use holochain_types::cell::CellId;
 use thiserror::Error;
 
+pub use holochain_error_kind::{ErrorKind, ErrorReport};
+
 /// Errors occurring during a [CellConductorApi] or [InterfaceApi] call
 #[derive(Error, Debug)]
 pub enum ConductorApiError {
@@ -67,6 +70,10 @@ pub enum ConductorApiError {
     #[error("The Dna file path provided was invalid")]
     DnaReadError(String),
 
+    /// The App bundle could not be read or resolved
+    #[error("AppBundleError: {0}")]
+    AppBundleError(#[from] holochain_types::app::AppBundleError),
+
     /// KeystoreError
     #[error("KeystoreError: {0}")]
     KeystoreError(#[from] holochain_keystore::KeystoreError),
@@ -81,6 +88,31 @@ pub enum ConductorApiError {
 
     #[error(transparent)]
     SourceChainError(#[from] SourceChainError),
+
+    /// The admin token was missing or unknown, or its scope doesn't cover the request
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+}
+
+impl HasErrorKind for ConductorApiError {
+    fn error_kind(&self) -> ErrorKind {
+        use ConductorApiError::*;
+        match self {
+            CellMissing(_) => ErrorKind::NotFound,
+            ZomeCallInvocationCellMismatch { .. } => ErrorKind::Unauthorized,
+            ConductorError(e) => e.error_kind(),
+            Io(_) | KeystoreError(_) => ErrorKind::Internal,
+            SerializationError(_) => ErrorKind::Serialization,
+            DatabaseError(e) => e.error_kind(),
+            WorkspaceError(e) => e.error_kind(),
+            WorkflowError(e) => e.error_kind(),
+            DnaError(_) | DnaReadError(_) | AppBundleError(_) => ErrorKind::InvalidInput,
+            CellError(e) => e.error_kind(),
+            InterfaceError(e) => e.error_kind(),
+            SourceChainError(e) => e.error_kind(),
+            Unauthorized(_) => ErrorKind::Unauthorized,
+        }
+    }
 }
 
 /// All the serialization errors that can occur
@@ -100,57 +132,115 @@ pub type ConductorApiResult<T> = Result<T, ConductorApiError>;
 
 /// Error type that goes over the websocket wire.
 /// This intends to be application developer facing
-/// so it should be readable and relevant
+/// so it should be readable and relevant.
+/// Every variant carries the [ErrorKind] of the underlying error,
+/// so clients can react to it without parsing the message.
 #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes, Clone)]
 #[serde(rename = "snake-case", tag = "type", content = "data")]
 pub enum ExternalApiWireError {
     // TODO: B-01506 Constrain these errors so they are relevant to
     // application developers and what they would need
     // to react to using code (i.e. not just print)
-    /// Any internal error
-    InternalError(String),
+    /// Any other error from the conductor
+    InternalError(ErrorReport),
     /// The input to the api failed to Deseralize
-    Deserialization(String),
+    Deserialization(ErrorReport),
     /// The dna path provided was invalid
-    DnaReadError(String),
+    DnaReadError(ErrorReport),
     /// There was an error in the ribosome
-    RibosomeError(String),
+    RibosomeError(ErrorReport),
     /// Error activating app
-    ActivateApp(String),
+    ActivateApp(ErrorReport),
+    /// The conductor's keystore is locked, so apps can't be used until
+    /// it's unlocked over an admin interface
+    KeystoreLocked(ErrorReport),
 }
 
 impl ExternalApiWireError {
-    /// Convert the error from the display.
+    /// Convert an error with no known kind from the display.
     pub fn internal<T: std::fmt::Display>(e: T) -> Self {
         // Display format is used because
         // this version intended for users.
-        ExternalApiWireError::InternalError(e.to_string())
+        ExternalApiWireError::InternalError(ErrorReport::new(ErrorKind::Internal, e))
+    }
+
+    /// The kind of the underlying error
+    pub fn kind(&self) -> ErrorKind {
+        self.report().kind
+    }
+
+    /// The kind and message of the underlying error
+    pub fn report(&self) -> &ErrorReport {
+        match self {
+            ExternalApiWireError::InternalError(r)
+            | ExternalApiWireError::Deserialization(r)
+            | ExternalApiWireError::DnaReadError(r)
+            | ExternalApiWireError::RibosomeError(r)
+            | ExternalApiWireError::ActivateApp(r)
+            | ExternalApiWireError::KeystoreLocked(r) => r,
+        }
     }
 }
 
 impl From<ConductorApiError> for ExternalApiWireError {
     fn from(err: ConductorApiError) -> Self {
+        let report = ErrorReport::from_error(&err);
         match err {
-            ConductorApiError::DnaReadError(e) => ExternalApiWireError::DnaReadError(e),
-            e => ExternalApiWireError::internal(e),
+            ConductorApiError::DnaReadError(e) => {
+                ExternalApiWireError::DnaReadError(ErrorReport::new(report.kind, e))
+            }
+            ConductorApiError::ConductorError(ConductorError::KeystoreLocked) => {
+                ExternalApiWireError::KeystoreLocked(report)
+            }
+            _ => ExternalApiWireError::InternalError(report),
         }
     }
 }
 
 impl From<SerializationError> for ExternalApiWireError {
     fn from(e: SerializationError) -> Self {
-        ExternalApiWireError::Deserialization(format!("{:?}", e))
+        ExternalApiWireError::Deserialization(ErrorReport::new(
+            ErrorKind::Serialization,
+            format!("{:?}", e),
+        ))
     }
 }
 
 impl From<RibosomeError> for ExternalApiWireError {
     fn from(e: RibosomeError) -> Self {
-        ExternalApiWireError::RibosomeError(e.to_string())
+        ExternalApiWireError::RibosomeError(ErrorReport::from_error(&e))
     }
 }
 
 impl From<CreateAppError> for ExternalApiWireError {
     fn from(e: CreateAppError) -> Self {
-        ExternalApiWireError::ActivateApp(e.to_string())
+        ExternalApiWireError::ActivateApp(ErrorReport::from_error(&e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wire_error_keeps_the_kind() {
+        let err: ExternalApiWireError =
+            ConductorApiError::ConductorError(ConductorError::AppNotInstalled).into();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let err: ExternalApiWireError =
+            ConductorApiError::WorkflowError(Box::new(WorkflowError::CapabilityMissing)).into();
+        assert_eq!(err.kind(), ErrorKind::Unauthorized);
+
+        let err: ExternalApiWireError =
+            ConductorApiError::ConductorError(ConductorError::KeystoreLocked).into();
+        assert!(matches!(err, ExternalApiWireError::KeystoreLocked(_)));
+        assert_eq!(err.kind(), ErrorKind::Unavailable);
+
+        let err: ExternalApiWireError = ConductorApiError::DnaReadError("nope".into()).into();
+        assert_eq!(
+            err.report(),
+            &ErrorReport::new(ErrorKind::InvalidInput, "nope")
+        );
     }
 }
@@ -4,13 +4,14 @@
 use super::CellConductorApiT;
 use crate::conductor::{api::error::ConductorApiResult, entry_def_store::EntryDefBufferKey};
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::signal::Signal;
 use crate::core::workflow::ZomeCallInvocationResult;
 use async_trait::async_trait;
 use holo_hash::DnaHash;
 use holochain_keystore::KeystoreSender;
-use holochain_types::dna::DnaFile;
+use holochain_types::dna::{zome::Zome, DnaFile};
 use holochain_types::{autonomic::AutonomicCue, cell::CellId};
-use holochain_zome_types::entry_def::EntryDef;
+use holochain_zome_types::{entry_def::EntryDef, link::LinkTypes};
 use mockall::mock;
 
 // Unfortunate workaround to get mockall to work with async_trait, due to the complexity of each.
@@ -35,6 +36,10 @@ mock! {
         fn sync_get_dna(&self, dna_hash: &DnaHash) -> Option<DnaFile>;
         fn sync_get_this_dna(&self) -> Option<DnaFile>;
         fn sync_get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef>;
+        fn sync_get_link_types(&self, zome: &Zome) -> Option<LinkTypes>;
+        fn sync_add_link_types(&self, zome: Zome, link_types: LinkTypes);
+        fn sync_emit_signal(&self, signal: Signal);
+        fn sync_cell_died(&self, error: String);
     }
 
     trait Clone {
@@ -76,4 +81,16 @@ impl CellConductorApiT for MockCellConductorApi {
     async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef> {
         self.sync_get_entry_def(key)
     }
+    async fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes> {
+        self.sync_get_link_types(zome)
+    }
+    async fn add_link_types(&self, zome: Zome, link_types: LinkTypes) {
+        self.sync_add_link_types(zome, link_types)
+    }
+    async fn emit_signal(&self, signal: Signal) {
+        self.sync_emit_signal(signal)
+    }
+    async fn cell_died(&self, error: String) {
+        self.sync_cell_died(error)
+    }
 }
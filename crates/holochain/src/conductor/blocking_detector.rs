@@ -0,0 +1,100 @@
+//! Detects when something blocks the async worker threads.
+//!
+//! A task wakes up on a short, regular tick. If it wakes up much later than
+//! it asked to, some other task must have been hogging a worker thread
+//! (e.g. an LMDB transaction or wasm call which wasn't moved onto
+//! `spawn_blocking`), so we log a warning.
+//!
+//! Debug builds, including tests, go further and abort the process if the
+//! worker threads are blocked for longer than [DEBUG_FAILURE_THRESHOLD].
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::*;
+
+/// How often the detector checks in
+const TICK: Duration = Duration::from_millis(10);
+
+/// How long debug builds let the worker threads be blocked before failing.
+/// This is well above the warning threshold, so a busy machine which is
+/// merely slow to wake the detector doesn't fail.
+pub const DEBUG_FAILURE_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// A handle to a running blocking detector
+#[derive(Clone, Debug)]
+pub struct BlockingDetector {
+    warnings: Arc<AtomicUsize>,
+}
+
+impl BlockingDetector {
+    /// How many times the worker threads were found to be blocked
+    pub fn warnings(&self) -> usize {
+        self.warnings.load(Ordering::Relaxed)
+    }
+}
+
+/// Spawn a task onto the current runtime which warns whenever it is woken
+/// more than `threshold` late, and aborts the process if it's woken more
+/// than `fail_after` late.
+pub fn spawn_blocking_detector(
+    threshold: Duration,
+    fail_after: Option<Duration>,
+) -> BlockingDetector {
+    let warnings = Arc::new(AtomicUsize::new(0));
+    let detector = BlockingDetector {
+        warnings: warnings.clone(),
+    };
+    tokio::task::spawn(async move {
+        loop {
+            let start = Instant::now();
+            tokio::time::delay_for(TICK).await;
+            let late = start.elapsed().checked_sub(TICK).unwrap_or_default();
+            if fail_after
+                .map(|fail_after| late > fail_after)
+                .unwrap_or(false)
+            {
+                error!(
+                    late_ms = late.as_millis() as u64,
+                    "The async runtime was blocked. Blocking work must be moved off the worker threads"
+                );
+                eprintln!(
+                    "The async runtime was blocked for {}ms. Blocking work must be moved off the worker threads",
+                    late.as_millis()
+                );
+                // A panic would only end this task, so abort to fail the
+                // test or debug run which blocked
+                std::process::abort();
+            }
+            if late > threshold {
+                warnings.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    late_ms = late.as_millis() as u64,
+                    "The async runtime was blocked. Blocking work should be moved to spawn_blocking"
+                );
+            }
+        }
+    });
+    detector
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn detects_blocked_runtime() {
+        let detector = spawn_blocking_detector(Duration::from_millis(50), None);
+        tokio::time::delay_for(TICK * 3).await;
+        assert_eq!(detector.warnings(), 0);
+
+        // The basic scheduler only has one thread, so this blocks the detector too
+        std::thread::sleep(Duration::from_millis(200));
+        tokio::time::delay_for(TICK * 3).await;
+        assert!(detector.warnings() > 0);
+    }
+}
@@ -8,23 +8,47 @@ use super::manager::ManagedTaskAdd;
 use crate::conductor::api::error::ConductorApiError;
 use crate::conductor::api::CellConductorApiT;
 use crate::conductor::handle::ConductorHandle;
-use crate::core::queue_consumer::{spawn_queue_consumer_tasks, InitialQueueTriggers};
+use crate::conductor::zome_call_scheduler::AppZomeCalls;
+use crate::core::autonomic::AutonomicSchedule;
+use crate::core::cancel::CancelToken;
+use crate::core::clock::{Clock, Entropy};
+use crate::core::queue_consumer::{spawn_queue_consumer_tasks, InitialQueueTriggers, QueueTrigger};
+use crate::core::quota::AppQuota;
+use crate::core::redundancy::{check_redundancy, REDUNDANCY_SAMPLE_SIZE, TARGET_REDUNDANCY};
+use crate::core::ribosome::wasm_io::WasmIoLimits;
 use crate::core::ribosome::ZomeCallInvocation;
-use holochain_zome_types::zome::FunctionName;
+use crate::core::subconscious::UnresolvedDependencyPolicy;
+use holochain_zome_types::{provenance::CallProvenance, zome::FunctionName};
 
 use crate::{
     conductor::{api::CellConductorApi, cell::error::CellResult},
     core::ribosome::{guest_callback::init::InitResult, wasm_ribosome::WasmRibosome},
     core::{
+        dht_snapshot::{export_snapshot, DhtSnapshot, SnapshotImportReport},
+        handoff::{hand_off, HandoffReport},
+        offline::{reconcile, unpublished_ops, CellNetworkStatus},
+        signal::{BasisChangedSignal, Signal},
         state::{
-            dht_op_integration::IntegratedDhtOpsBuf,
+            dht_op_integration::{index_integrated_ops, AuthoredDhtOpsStore, IntegratedDhtOpsBuf},
             element_buf::ElementBuf,
+            integration_journal::recover_interrupted_integration,
             metadata::{LinkMetaKey, MetadataBuf, MetadataBufT},
-            source_chain::SourceChainBuf,
+            pins::PinsBuf,
+            publish_progress::{rehydrate_publish_progress, PublishProgressBuf},
+            source_chain::{CapGrantIndex, SourceChain, SourceChainBuf},
+            subscriptions::SubscriptionsBuf,
+            validation_receipts_db::{
+                SignedValidationReceipt, ValidationReceipt, ValidationReceiptsBuf, ValidationResult,
+            },
         },
+        validation_package::{assemble, required_validation_type},
         workflow::{
-            call_zome_workflow, error::WorkflowError, genesis_workflow::genesis_workflow,
-            incoming_dht_ops_workflow::incoming_dht_ops_workflow, initialize_zomes_workflow,
+            call_zome_workflow,
+            error::{WorkflowError, WorkflowResult},
+            genesis_workflow::genesis_workflow,
+            incoming_dht_ops_workflow::incoming_dht_ops_workflow,
+            initialize_zomes_workflow,
+            publish_dht_ops_workflow::DEFAULT_RECEIPT_BUNDLE_SIZE,
             CallZomeWorkflowArgs, CallZomeWorkspace, GenesisWorkflowArgs, GenesisWorkspace,
             InitializeZomesWorkflowArgs, ZomeCallInvocationResult,
         },
@@ -35,35 +59,53 @@ use fallible_iterator::FallibleIterator;
 use futures::future::FutureExt;
 use hash_type::AnyDht;
 use holo_hash::*;
+use holochain_error_kind::HasErrorKind;
 use holochain_keystore::Signature;
-use holochain_p2p::HolochainP2pCellT;
+use holochain_p2p::{
+    actor::PeerReport,
+    dht_arc::{DhtArc, MAX_HALF_LENGTH},
+    HolochainP2pCellT,
+};
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_state::{
-    db::GetDb,
-    env::{EnvironmentWrite, ReadManager},
+    buffer::{BufferedStore, KvBufFresh},
+    db::{GetDb, AUTHORED_DHT_OPS},
+    env::{EnvironmentWrite, ReadManager, WriteManager},
+    error::DatabaseError,
+    fresh_reader,
 };
 use holochain_types::{
+    activity::{sign_observation, AgentActivityResponse, ChainHeadResponse, ChainRange},
     autonomic::AutonomicProcess,
     cell::CellId,
-    element::{GetElementResponse, WireElement},
-    link::{GetLinksResponse, WireLinkMetaKey},
+    dht_op::DhtOpCounts,
+    element::{verify_batch, GetElementResponse, SignedHeaderHashed, WireElement},
+    link::{GetLinksResponse, WireLinkMetaKey, MAX_GET_LINKS_RESPONSE_HEADERS},
     metadata::{MetadataSet, TimedHeaderHash},
-    Timestamp,
+    validate::{ValidationPackageResponse, ValidationStatus},
+    validation_receipt::{live_store_entry_ops, store_element_op_hash},
+    HeaderHashed, Timestamp,
 };
 use holochain_zome_types::capability::CapSecret;
+use holochain_zome_types::element::SignedHeader;
 use holochain_zome_types::header::{CreateLink, DeleteLink};
+use holochain_zome_types::observation::ChainHeadObservation;
+use holochain_zome_types::validate::ValidationPackage;
 use holochain_zome_types::zome::ZomeName;
 use holochain_zome_types::ExternInput;
 use std::{
     collections::{BTreeMap, BTreeSet},
     convert::TryInto,
+    future::Future,
     hash::{Hash, Hasher},
 };
 use tokio::sync;
 use tracing::*;
 use tracing_futures::Instrument;
+use validation_package::ValidationPackageCache;
 
 mod authority;
+mod validation_package;
 
 #[allow(missing_docs)]
 pub mod error;
@@ -105,6 +147,21 @@ where
     env: EnvironmentWrite,
     holochain_p2p_cell: P2pCell,
     queue_triggers: InitialQueueTriggers,
+    /// The resources the Cell's app may use
+    quota: AppQuota,
+    /// The turns for the Cell's app to run zome calls
+    zome_calls: AppZomeCalls,
+    /// How much data may cross the wasm boundary on each call
+    wasm_io_limits: WasmIoLimits,
+    /// How many times a zome call is re-run after losing a race to commit
+    zome_call_retries: u32,
+    /// The live cap grants on the source chain, for authorizing zome calls
+    cap_grants: CapGrantIndex,
+    /// Where the Cell's workflows get the time from
+    clock: Clock,
+    /// Where zome calls get random bytes from
+    entropy: Entropy,
+    validation_package_cache: parking_lot::Mutex<ValidationPackageCache>,
 }
 
 impl Cell {
@@ -118,6 +175,14 @@ impl Cell {
         mut holochain_p2p_cell: holochain_p2p::HolochainP2pCell,
         managed_task_add_sender: sync::mpsc::Sender<ManagedTaskAdd>,
         managed_task_stop_broadcaster: sync::broadcast::Sender<()>,
+        unresolved_dependency_policy: UnresolvedDependencyPolicy,
+        quota: AppQuota,
+        zome_calls: AppZomeCalls,
+        wasm_io_limits: WasmIoLimits,
+        zome_call_retries: u32,
+        clock: Clock,
+        entropy: Entropy,
+        autonomic_schedule: AutonomicSchedule,
     ) -> CellResult<Self> {
         let conductor_api = CellConductorApi::new(conductor_handle.clone(), id.clone());
 
@@ -128,13 +193,33 @@ impl Cell {
         };
 
         if has_genesis {
+            // finish off any integration that was interrupted last time
+            // before the integration workflow can run again
+            if let Some(report) = recover_interrupted_integration(&env)? {
+                tracing::info!(?report, "Recovered interrupted integration");
+            }
+            // ops integrated before the order index existed
+            let indexed = index_integrated_ops(&env)?;
+            if indexed > 0 {
+                tracing::info!(indexed, "Indexed the order of integrated ops");
+            }
+            // pick up publishing where it was left off
+            let report = rehydrate_publish_progress(&env, DEFAULT_RECEIPT_BUNDLE_SIZE)?;
+            if report.unfinished > 0 {
+                tracing::info!(?report, "Resuming publishing of authored ops");
+            }
             holochain_p2p_cell.join().await?;
+            let cap_grants = CapGrantIndex::default();
             let queue_triggers = spawn_queue_consumer_tasks(
                 &env,
                 holochain_p2p_cell.clone(),
                 conductor_api.clone(),
                 managed_task_add_sender,
                 managed_task_stop_broadcaster,
+                unresolved_dependency_policy,
+                quota.clone(),
+                clock.clone(),
+                autonomic_schedule,
             )
             .await;
 
@@ -144,6 +229,14 @@ impl Cell {
                 env,
                 holochain_p2p_cell,
                 queue_triggers,
+                quota,
+                zome_calls,
+                wasm_io_limits,
+                zome_call_retries,
+                cap_grants,
+                clock,
+                entropy,
+                validation_package_cache: Default::default(),
             })
         } else {
             Err(CellError::CellWithoutGenesis(id))
@@ -157,6 +250,91 @@ impl Cell {
         self.queue_triggers.initialize_workflows();
     }
 
+    /// Leave the network, so the Cell stops gossiping and receiving requests.
+    /// Everything it holds is handed off to the remaining authorities first.
+    pub async fn leave(&mut self) -> CellResult<()> {
+        let everything = DhtArc::new(0, MAX_HALF_LENGTH);
+        if let Err(error) = self.hand_off(everything, DhtArc::new(0, 0)).await {
+            warn!(cell_id = ?self.id(), ?error, "Could not hand off before leaving");
+        }
+        self.leave_without_handoff().await
+    }
+
+    /// Leave the network without handing anything off, for a Cell which
+    /// will rejoin still holding everything it held, e.g. once restarted.
+    pub async fn leave_without_handoff(&mut self) -> CellResult<()> {
+        self.holochain_p2p_cell.leave().await?;
+        Ok(())
+    }
+
+    /// Hand off the ops this Cell holds with a basis in its `held` arc, but
+    /// not the `kept` arc, to the authorities closest to them.
+    /// Call this before shrinking the Cell's arc.
+    pub async fn hand_off(&self, held: DhtArc, kept: DhtArc) -> CellResult<HandoffReport> {
+        let report = hand_off(
+            self.env.clone().into(),
+            self.holochain_p2p_cell.clone(),
+            held,
+            kept,
+        )
+        .await
+        .map_err(Box::new)?;
+        if !report.unacknowledged.is_empty() {
+            warn!(
+                cell_id = ?self.id(),
+                unacknowledged = report.unacknowledged.len(),
+                "Some ops were not acknowledged by any authority during handoff"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Take a snapshot of every op this Cell has integrated as valid,
+    /// to seed another Cell with
+    pub async fn export_dht_snapshot(&self) -> CellResult<DhtSnapshot> {
+        Ok(
+            export_snapshot(self.env.clone().into(), self.dna_hash().clone())
+                .await
+                .map_err(Box::new)?,
+        )
+    }
+
+    /// Queue the ops in a snapshot for validation, as if they'd been
+    /// published to this Cell. Ops which aren't what they claim to be are
+    /// dropped before they reach the workflows.
+    pub async fn import_dht_snapshot(
+        &self,
+        snapshot: DhtSnapshot,
+    ) -> CellResult<SnapshotImportReport> {
+        if snapshot.dna_hash != *self.dna_hash() {
+            return Err(CellError::SnapshotForOtherDna {
+                expected: self.dna_hash().clone(),
+                found: snapshot.dna_hash,
+            });
+        }
+        let (ops, counterfeit) = snapshot.check_ops().await;
+        if !counterfeit.is_empty() {
+            warn!(
+                cell_id = ?self.id(),
+                counterfeit = counterfeit.len(),
+                "Dropping counterfeit ops from an imported snapshot"
+            );
+        }
+        let queued = ops.len();
+        incoming_dht_ops_workflow(
+            &self.env,
+            self.queue_triggers.sys_validation.clone(),
+            ops,
+            Vec::new(),
+        )
+        .await
+        .map_err(Box::new)?;
+        Ok(SnapshotImportReport {
+            queued,
+            counterfeit,
+        })
+    }
+
     /// Performs the Genesis workflow the Cell, ensuring that its initial
     /// elements are committed. This is a prerequisite for any other interaction
     /// with the SourceChain
@@ -165,6 +343,7 @@ impl Cell {
         conductor_handle: ConductorHandle,
         cell_env: EnvironmentWrite,
         membrane_proof: Option<SerializedBytes>,
+        clock: Clock,
     ) -> CellResult<()> {
         // get the dna
         let dna_file = conductor_handle
@@ -175,10 +354,11 @@ impl Cell {
         let conductor_api = CellConductorApi::new(conductor_handle, id.clone());
 
         // run genesis
-        let workspace = GenesisWorkspace::new(cell_env.clone().into())
+        let mut workspace = GenesisWorkspace::new(cell_env.clone().into())
             .await
             .map_err(ConductorApiError::from)
             .map_err(Box::new)?;
+        workspace.set_clock(clock);
         let args = GenesisWorkflowArgs::new(dna_file, id.agent_pubkey().clone(), membrane_proof);
 
         genesis_workflow(workspace, cell_env.clone().into(), conductor_api, args)
@@ -189,6 +369,20 @@ impl Cell {
         Ok(())
     }
 
+    /// Check that genesis left a source chain the Cell can use: all the
+    /// genesis elements are there, and the chain belongs to the Cell's agent
+    pub fn check_genesis(id: &CellId, cell_env: &EnvironmentWrite) -> CellResult<()> {
+        let source_chain = SourceChainBuf::new(cell_env.clone().into())?;
+        let problem = if !source_chain.has_genesis() {
+            "the genesis elements are missing"
+        } else if source_chain.agent_pubkey()?.as_ref() != Some(id.agent_pubkey()) {
+            "the chain belongs to another agent"
+        } else {
+            return Ok(());
+        };
+        Err(CellError::GenesisCheckFailed(id.clone(), problem.into()))
+    }
+
     fn dna_hash(&self) -> &DnaHash {
         &self.id.dna_hash()
     }
@@ -208,6 +402,56 @@ impl Cell {
         &self.holochain_p2p_cell
     }
 
+    /// Put this Cell in or out of offline mode.
+    /// Coming back online publishes everything authored while offline, and
+    /// refreshes the cache at the bases it touched in the background.
+    pub fn set_offline(&self, offline: bool) -> CellResult<CellNetworkStatus> {
+        let was_offline = self.holochain_p2p_cell.is_offline();
+        self.holochain_p2p_cell.set_offline(offline);
+        if was_offline && !offline {
+            let bases: BTreeSet<_> = unpublished_ops(&self.env.clone().into())?
+                .into_iter()
+                .collect();
+            self.queue_triggers
+                .clone()
+                .trigger(QueueTrigger::PublishDhtOps);
+            let env = self.env.clone();
+            let network = self.holochain_p2p_cell.clone();
+            tokio::task::spawn(async move {
+                if let Err(error) = reconcile(env, network, bases).await {
+                    warn!(?error, "Could not reconcile after coming back online");
+                }
+            });
+        }
+        self.network_status()
+    }
+
+    /// Whether this Cell is offline and how much it has waiting to publish
+    pub fn network_status(&self) -> CellResult<CellNetworkStatus> {
+        Ok(CellNetworkStatus {
+            offline: self.holochain_p2p_cell.is_offline(),
+            unpublished_ops: unpublished_ops(&self.env.clone().into())?.len(),
+        })
+    }
+
+    /// Unpin hashes. Their data stays in the cache, but is no longer served
+    /// to other agents unless this Cell is an authority for it.
+    pub fn unpin(&self, hashes: Vec<AnyDhtHash>) -> CellResult<()> {
+        let env_ref = self.env.guard();
+        let mut pins = PinsBuf::new(self.env.clone().into())?;
+        for hash in hashes {
+            pins.unpin(&hash)?;
+        }
+        env_ref.with_commit(|writer| pins.flush_to_txn(writer))?;
+        Ok(())
+    }
+
+    /// Every hash pinned on this Cell
+    pub fn pins(&self) -> CellResult<Vec<AnyDhtHash>> {
+        let pins = PinsBuf::new(self.env.clone().into())?;
+        Ok(fresh_reader!(self.env, |r| pins.pins(&r))?)
+    }
+
     #[instrument(skip(self, evt))]
     /// Entry point for incoming messages from the network that need to be handled
     pub async fn handle_holochain_p2p_event(
@@ -222,13 +466,16 @@ impl Cell {
                 zome_name,
                 fn_name,
                 cap,
+                provenance,
                 respond,
                 request,
                 ..
             } => {
                 async {
                     let res = self
-                        .handle_call_remote(from_agent, zome_name, fn_name, cap, request)
+                        .handle_call_remote(
+                            from_agent, zome_name, fn_name, cap, provenance, request,
+                        )
                         .await
                         .map_err(holochain_p2p::HolochainP2pError::other);
                     respond.respond(Ok(async move { res }.boxed().into()));
@@ -243,11 +490,18 @@ impl Cell {
                 request_validation_receipt,
                 dht_hash,
                 ops,
+                validation_packages,
                 ..
             } => {
                 async {
                     let res = self
-                        .handle_publish(from_agent, request_validation_receipt, dht_hash, ops)
+                        .handle_publish(
+                            from_agent,
+                            request_validation_receipt,
+                            dht_hash,
+                            ops,
+                            validation_packages,
+                        )
                         .await
                         .map_err(holochain_p2p::HolochainP2pError::other);
                     respond.respond(Ok(async move { res }.boxed().into()));
@@ -258,11 +512,12 @@ impl Cell {
             GetValidationPackage {
                 span: _span,
                 respond,
+                header_hash,
                 ..
             } => {
                 async {
                     let res = self
-                        .handle_get_validation_package()
+                        .handle_get_validation_package(header_hash)
                         .await
                         .map_err(holochain_p2p::HolochainP2pError::other);
                     respond.respond(Ok(async move { res }.boxed().into()));
@@ -320,6 +575,38 @@ impl Cell {
                 .instrument(debug_span!("cell_handle_get_links"))
                 .await;
             }
+            GetAgentActivity {
+                span: _span,
+                respond,
+                agent,
+                range,
+                ..
+            } => {
+                async {
+                    let res = self
+                        .handle_get_agent_activity(agent, range)
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_get_agent_activity"))
+                .await;
+            }
+            ObserveChainHead {
+                span: _span,
+                respond,
+                agent,
+                ..
+            } => {
+                async {
+                    let res = self
+                        .handle_observe_chain_head(agent)
+                        .await
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_observe_chain_head"))
+                .await;
+            }
             ValidationReceiptReceived {
                 span: _span,
                 respond,
@@ -353,6 +640,38 @@ impl Cell {
                 .instrument(debug_span!("cell_handle_fetch_op_hashes_for_constraints"))
                 .await;
             }
+            GetDhtOpCounts {
+                span: _span,
+                respond,
+                dht_arc,
+                since,
+                until,
+                ..
+            } => {
+                async {
+                    let res = self
+                        .handle_get_dht_op_counts(dht_arc, since, until)
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_get_dht_op_counts"))
+                .await;
+            }
+            HoldsOps {
+                span: _span,
+                respond,
+                op_hashes,
+                ..
+            } => {
+                async {
+                    let res = self
+                        .handle_holds_ops(op_hashes)
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_holds_ops"))
+                .await;
+            }
             FetchOpHashData {
                 span: _span,
                 respond,
@@ -384,30 +703,143 @@ impl Cell {
                 .instrument(debug_span!("cell_handle_sign_network_data"))
                 .await;
             }
+            Subscribe {
+                span: _span,
+                respond,
+                from_agent,
+                basis,
+                ..
+            } => {
+                async {
+                    let res = self
+                        .handle_subscribe(from_agent, basis, true)
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_subscribe"))
+                .await;
+            }
+            Unsubscribe {
+                span: _span,
+                respond,
+                from_agent,
+                basis,
+                ..
+            } => {
+                async {
+                    let res = self
+                        .handle_subscribe(from_agent, basis, false)
+                        .map_err(holochain_p2p::HolochainP2pError::other);
+                    respond.respond(Ok(async move { res }.boxed().into()));
+                }
+                .instrument(debug_span!("cell_handle_unsubscribe"))
+                .await;
+            }
+            BasisChanged {
+                span: _span,
+                respond,
+                from_agent,
+                basis,
+                op_hashes,
+                ..
+            } => {
+                self.handle_basis_changed(from_agent, basis, op_hashes)
+                    .await;
+                respond.respond(Ok(async move { Ok(()) }.boxed().into()));
+            }
+            NetworkRestarted {
+                span: _span,
+                respond,
+                reason,
+                ..
+            } => {
+                self.handle_network_restarted(reason);
+                respond.respond(Ok(async move { Ok(()) }.boxed().into()));
+            }
         }
         Ok(())
     }
 
-    #[instrument(skip(self, _request_validation_receipt, _dht_hash, ops))]
+    #[instrument(skip(self, _request_validation_receipt, _dht_hash, ops, validation_packages))]
     /// we are receiving a "publish" event from the network
     async fn handle_publish(
         &self,
-        _from_agent: AgentPubKey,
+        from_agent: AgentPubKey,
         _request_validation_receipt: bool,
         _dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        validation_packages: Vec<(HeaderHash, ValidationPackage)>,
     ) -> CellResult<()> {
-        incoming_dht_ops_workflow(&self.env, self.queue_triggers.sys_validation.clone(), ops)
-            .await
-            .map_err(Box::new)
-            .map_err(ConductorApiError::from)
-            .map_err(Box::new)?;
+        // Drop any op whose signature doesn't check out before it reaches
+        // the workflows, and count it against whoever sent it.
+        // A publish can carry many ops, so they're verified as a batch.
+        let headers: Vec<_> = ops
+            .iter()
+            .map(|(_, op)| {
+                SignedHeaderHashed::with_presigned(
+                    HeaderHashed::from_content_sync(op.header()),
+                    op.signature().clone(),
+                )
+            })
+            .collect();
+        let verified = verify_batch(&headers).await;
+        let mut valid_ops = Vec::with_capacity(ops.len());
+        let mut counterfeits = 0;
+        for ((hash, op), valid) in ops.into_iter().zip(verified) {
+            if valid {
+                valid_ops.push((hash, op));
+            } else {
+                warn!(?from_agent, ?hash, "Dropping counterfeit op");
+                counterfeits += 1;
+            }
+        }
+        if counterfeits > 0 {
+            let mut network = self.holochain_p2p_cell.clone();
+            for _ in 0..counterfeits {
+                if let Err(e) = network
+                    .report_peer(from_agent.clone(), PeerReport::ValidationFailure)
+                    .await
+                {
+                    warn!(?e, "Failed to report a peer who sent counterfeit ops");
+                }
+            }
+        }
+        let ops = valid_ops;
+        incoming_dht_ops_workflow(
+            &self.env,
+            self.queue_triggers.sys_validation.clone(),
+            ops,
+            validation_packages,
+        )
+        .await
+        .map_err(Box::new)
+        .map_err(ConductorApiError::from)
+        .map_err(Box::new)?;
         Ok(())
     }
 
-    /// a remote node is attempting to retreive a validation package
-    async fn handle_get_validation_package(&self) -> CellResult<()> {
-        unimplemented!()
+    #[instrument(skip(self))]
+    /// a remote node is asking us, as the author of a header,
+    /// for the validation package needed to validate it
+    async fn handle_get_validation_package(
+        &self,
+        header_hash: HeaderHash,
+    ) -> CellResult<ValidationPackageResponse> {
+        if let Some(package) = self.validation_package_cache.lock().get(&header_hash) {
+            return Ok(ValidationPackageResponse(Some(package)));
+        }
+        // Validators never see our private entries
+        let source_chain = SourceChain::public_only(self.env.clone().into())?;
+        let header = match source_chain.get_header(&header_hash)? {
+            Some(shh) if shh.header().author() == self.id.agent_pubkey() => shh.header().clone(),
+            _ => return Ok(ValidationPackageResponse(None)),
+        };
+        let required = required_validation_type(&header, &self.conductor_api).await;
+        let package = assemble(&source_chain, &header, required)?;
+        self.validation_package_cache
+            .lock()
+            .put(header_hash, package.clone());
+        Ok(ValidationPackageResponse(Some(package)))
     }
 
     #[instrument(skip(self, options))]
@@ -421,14 +853,23 @@ impl Cell {
         // we can just have these defaults depending on whether or not
         // the hash is an entry or header.
         // In the future we should use GetOptions to choose which get to run.
+        let with_receipts = options.with_receipts;
         let r = match *dht_hash.hash_type() {
             AnyDht::Entry => self.handle_get_entry(dht_hash.into(), options).await,
             AnyDht::Header => self.handle_get_element(dht_hash.into()).await,
         };
-        if let Err(e) = &r {
-            error!(msg = "Error handling a get", ?e, agent = ?self.id.agent_pubkey());
+        let r = match r {
+            Ok(r) if with_receipts => self.add_get_receipts(r).await,
+            r => r,
+        };
+        match r {
+            Err(e) => {
+                error!(msg = "Error handling a get", ?e, agent = ?self.id.agent_pubkey());
+                // Answering with nothing would look like we don't hold the data
+                Ok(GetElementResponse::Error(e.error_kind()))
+            }
+            r => r,
         }
-        r
     }
 
     #[instrument(skip(self, options))]
@@ -443,25 +884,108 @@ impl Cell {
 
     async fn handle_get_element(&self, hash: HeaderHash) -> CellResult<GetElementResponse> {
         // Get the vaults
-        let env_ref = self.env.guard();
-        let reader = env_ref.reader()?;
         let element_vault = ElementBuf::vault(self.env.clone().into(), false)?;
         let meta_vault = MetadataBuf::vault(self.env.clone().into())?;
 
         // Check that we have the authority to serve this request because we have
         // done the StoreElement validation
-        if !meta_vault.has_registered_store_element(&hash)? {
-            return Ok(GetElementResponse::GetHeader(None));
+        if fresh_reader!(self.env, |r| meta_vault
+            .has_registered_store_element(&r, &hash))?
+        {
+            return self.get_element_from(&element_vault, &meta_vault, hash);
+        }
+
+        // Pinned elements are served from the cache, even if we aren't an authority for them
+        if PinsBuf::new(self.env.clone().into())?.is_pinned(&hash.clone().into())? {
+            let element_cache = ElementBuf::cache(self.env.clone().into())?;
+            let meta_cache = MetadataBuf::cache(self.env.clone().into())?;
+            return self.get_element_from(&element_cache, &meta_cache, hash);
+        }
+        Ok(GetElementResponse::GetHeader(None))
+    }
+
+    /// Include our receipts for the ops a get response is made of, so the
+    /// requester can count the authorities which vouch for the data
+    async fn add_get_receipts(
+        &self,
+        response: GetElementResponse,
+    ) -> CellResult<GetElementResponse> {
+        Ok(match response {
+            GetElementResponse::GetEntryFull(Some(mut raw)) => {
+                let entry_hash = EntryHash::with_data_sync(&raw.entry);
+                let ops =
+                    live_store_entry_ops(&raw.live_headers, &raw.entry_type, &entry_hash).await;
+                raw.receipts = self.sign_receipts(ops).await?;
+                GetElementResponse::GetEntryFull(Some(raw))
+            }
+            GetElementResponse::GetEntryCollapsed(Some(mut collapsed)) => {
+                let entry_hash = EntryHash::with_data_sync(&collapsed.entry);
+                let ops = live_store_entry_ops(
+                    &collapsed.live_headers,
+                    &collapsed.entry_type,
+                    &entry_hash,
+                )
+                .await;
+                collapsed.receipts = self.sign_receipts(ops).await?;
+                GetElementResponse::GetEntryCollapsed(Some(collapsed))
+            }
+            GetElementResponse::GetHeader(Some(element)) => {
+                let header = element.signed_header().header();
+                let ops = vec![(
+                    HeaderHash::with_data_sync(header),
+                    store_element_op_hash(header),
+                )];
+                let receipts = self.sign_receipts(ops).await?;
+                GetElementResponse::GetHeader(Some(Box::new(element.with_receipts(receipts))))
+            }
+            r => r,
+        })
+    }
+
+    /// Sign a receipt for each of these ops which we hold as valid.
+    /// Data served from the cache, which we haven't validated, gets none.
+    async fn sign_receipts(
+        &self,
+        ops: Vec<(HeaderHash, DhtOpHash)>,
+    ) -> CellResult<Vec<SignedValidationReceipt>> {
+        let integrated_dht_ops = IntegratedDhtOpsBuf::new(self.env().clone().into())?;
+        let mut receipts = Vec::with_capacity(ops.len());
+        for (_, dht_op_hash) in ops {
+            match integrated_dht_ops.get(&dht_op_hash)? {
+                Some(op) if op.validation_status == ValidationStatus::Valid => (),
+                _ => continue,
+            }
+            let receipt = ValidationReceipt {
+                dht_op_hash,
+                validation_result: ValidationResult::Valid,
+                validator: self.id.agent_pubkey().clone(),
+            }
+            .sign(self.env.keystore())
+            .await
+            .map_err(DatabaseError::from)?;
+            receipts.push(receipt);
         }
+        Ok(receipts)
+    }
+
+    /// Gather the response to a get for an element from one pair of stores
+    fn get_element_from(
+        &self,
+        element_store: &ElementBuf,
+        meta_store: &MetadataBuf,
+        hash: HeaderHash,
+    ) -> CellResult<GetElementResponse> {
+        let env_ref = self.env.guard();
+        let reader = env_ref.reader()?;
 
         // Look for a delete on the header and collect it
-        let deleted = meta_vault
+        let deleted = meta_store
             .get_deletes_on_header(&reader, hash.clone())?
             .next()?;
         let deleted = match deleted {
             Some(delete_header) => {
                 let delete = delete_header.header_hash;
-                match element_vault.get_header(&delete)? {
+                match element_store.get_header(&delete)? {
                     Some(delete) => Some(delete.try_into().map_err(AuthorityDataError::from)?),
                     None => {
                         return Err(AuthorityDataError::missing_data(delete));
@@ -472,7 +996,7 @@ impl Cell {
         };
 
         // Get the actual header and return it with proof of deleted if there is any
-        let r = element_vault
+        let r = element_store
             .get_element(&hash)?
             .map(|e| WireElement::from_element(e, deleted))
             .map(Box::new);
@@ -498,7 +1022,7 @@ impl Cell {
     fn handle_get_links(
         &self,
         link_key: WireLinkMetaKey,
-        _options: holochain_p2p::event::GetLinksOptions,
+        options: holochain_p2p::event::GetLinksOptions,
     ) -> CellResult<GetLinksResponse> {
         // Get the vaults
         let env_ref = self.env.guard();
@@ -524,10 +1048,14 @@ impl Cell {
             })
             .collect::<BTreeMap<_, _>>()?;
 
+        // Only send one page, so the response stays a manageable size
+        let (page, continuation) =
+            authority::page_links(&links, options.after, MAX_GET_LINKS_RESPONSE_HEADERS);
+
         // Get the headers from the element stores
-        let mut result_adds: Vec<(CreateLink, Signature)> = Vec::with_capacity(links.len());
-        let mut result_removes: Vec<(DeleteLink, Signature)> = Vec::with_capacity(links.len());
-        for (link_add, link_removes) in links {
+        let mut result_adds: Vec<(CreateLink, Signature)> = Vec::with_capacity(page.len());
+        let mut result_removes: Vec<(DeleteLink, Signature)> = Vec::with_capacity(page.len());
+        for (link_add, link_removes) in page {
             if let Some(link_add) = element_vault.get_header(&link_add.header_hash)? {
                 for link_remove in link_removes {
                     if let Some(link_remove) = element_vault.get_header(&link_remove.header_hash)? {
@@ -552,12 +1080,137 @@ impl Cell {
         Ok(GetLinksResponse {
             link_adds: result_adds,
             link_removes: result_removes,
+            continuation,
+        })
+    }
+
+    #[instrument(skip(self))]
+    /// a remote node is asking us for the headers of an agent's source chain
+    fn handle_get_agent_activity(
+        &self,
+        agent: AgentPubKey,
+        range: ChainRange,
+    ) -> CellResult<AgentActivityResponse> {
+        let env_ref = self.env.guard();
+        let reader = env_ref.reader()?;
+        let element_vault = ElementBuf::vault(self.env.clone().into(), false)?;
+        let meta_vault = MetadataBuf::vault(self.env.clone().into())?;
+
+        let mut headers = Vec::new();
+        let mut activity = meta_vault.get_activity(&reader, agent)?;
+        while let Some(timed) = activity.next()? {
+            if let Some(shh) = element_vault.get_header(&timed.header_hash)? {
+                if range.contains(shh.header().header_seq()) {
+                    let (h, s) = shh.into_header_and_signature();
+                    headers.push(SignedHeader(h.into_content(), s));
+                }
+            }
+        }
+        headers.sort_by_key(|h| h.0.header_seq());
+        Ok(AgentActivityResponse { headers })
+    }
+
+    #[instrument(skip(self))]
+    /// a remote node is asking us to sign what we hold at the head of an
+    /// agent's source chain
+    async fn handle_observe_chain_head(&self, agent: AgentPubKey) -> CellResult<ChainHeadResponse> {
+        let range = ChainRange {
+            start_seq: 0,
+            end_seq: None,
+        };
+        let head = match self
+            .handle_get_agent_activity(agent.clone(), range)?
+            .headers
+            .pop()
+        {
+            Some(head) => head,
+            None => return Ok(ChainHeadResponse { observation: None }),
+        };
+        let observation = ChainHeadObservation {
+            agent,
+            head,
+            observer: self.id.agent_pubkey().clone(),
+            observed_at: Timestamp::now().into(),
+        };
+        let observation = sign_observation(observation, self.env.keystore())
+            .await
+            .map_err(DatabaseError::from)?;
+        Ok(ChainHeadResponse {
+            observation: Some(observation),
         })
     }
 
     /// a remote agent is sending us a validation receipt.
-    async fn handle_validation_receipt(&self, _receipt: SerializedBytes) -> CellResult<()> {
-        unimplemented!()
+    /// Receipts for ops we didn't author, or with a bad signature, are dropped.
+    async fn handle_validation_receipt(&self, receipt: SerializedBytes) -> CellResult<()> {
+        let receipt: SignedValidationReceipt = receipt.try_into()?;
+        let validator = receipt.receipt.validator.clone();
+        let dht_op_hash = receipt.receipt.dht_op_hash.clone();
+        if !receipt.verify().await.map_err(DatabaseError::from)? {
+            warn!(
+                ?validator,
+                ?dht_op_hash,
+                "Dropping a validation receipt with a bad signature"
+            );
+            return Ok(());
+        }
+        let authored_dht_ops: AuthoredDhtOpsStore = KvBufFresh::new(
+            self.env.clone().into(),
+            self.env.get_db(&*AUTHORED_DHT_OPS)?,
+        );
+        if !authored_dht_ops.contains(&dht_op_hash)? {
+            return Ok(());
+        }
+
+        let env_ref = self.env.guard();
+        let mut receipts = ValidationReceiptsBuf::new(&self.env)?;
+        let mut progress = PublishProgressBuf::new(self.env.clone().into())?;
+        if receipt.receipt.validation_result == ValidationResult::Valid {
+            progress.record_receipt(dht_op_hash, validator)?;
+        }
+        receipts.add_if_unique(receipt)?;
+        env_ref.with_commit::<DatabaseError, _, _>(|writer| {
+            receipts.flush_to_txn(writer)?;
+            progress.flush_to_txn(writer)
+        })?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    /// a remote agent wants to know, or no longer wants to know,
+    /// when we integrate ops at a basis
+    fn handle_subscribe(
+        &self,
+        from_agent: AgentPubKey,
+        basis: AnyDhtHash,
+        subscribe: bool,
+    ) -> CellResult<()> {
+        let env_ref = self.env.guard();
+        let mut subscriptions = SubscriptionsBuf::new(self.env.clone().into())?;
+        if subscribe {
+            subscriptions.subscribe(&basis, from_agent)?;
+        } else {
+            subscriptions.unsubscribe(&basis, &from_agent)?;
+        }
+        env_ref.with_commit(|writer| subscriptions.flush_to_txn(writer))?;
+        Ok(())
+    }
+
+    /// an authority we subscribed to has integrated ops at a basis,
+    /// so let the app know
+    async fn handle_basis_changed(
+        &self,
+        from_agent: AgentPubKey,
+        basis: AnyDhtHash,
+        op_hashes: Vec<DhtOpHash>,
+    ) {
+        self.conductor_api
+            .emit_signal(Signal::BasisChanged(BasisChangedSignal {
+                basis,
+                op_hashes,
+                authority: from_agent,
+            }))
+            .await;
     }
 
     #[instrument(skip(self, dht_arc, since, until))]
@@ -571,13 +1224,49 @@ impl Cell {
         let env_ref = self.env.guard();
         let reader = env_ref.reader()?;
         let integrated_dht_ops = IntegratedDhtOpsBuf::new(self.env().clone().into())?;
+        // Ordered so that gossip sees the same ops in the same order each time
         let result: Vec<DhtOpHash> = integrated_dht_ops
-            .query(&reader, Some(since), Some(until), Some(dht_arc))?
+            .query_ordered(&reader, Some(since), Some(until), Some(dht_arc))?
             .map(|(k, _)| Ok(k))
             .collect()?;
         Ok(result)
     }
 
+    #[instrument(skip(self, dht_arc, since, until))]
+    /// a remote node is asking how many ops we hold in part of the dht
+    fn handle_get_dht_op_counts(
+        &self,
+        dht_arc: holochain_p2p::dht_arc::DhtArc,
+        since: Timestamp,
+        until: Timestamp,
+    ) -> CellResult<DhtOpCounts> {
+        let env_ref = self.env.guard();
+        let reader = env_ref.reader()?;
+        let integrated_dht_ops = IntegratedDhtOpsBuf::new(self.env().clone().into())?;
+        let mut counts = DhtOpCounts::default();
+        let mut ops = integrated_dht_ops.query(&reader, Some(since), Some(until), Some(dht_arc))?;
+        while let Some((_, value)) = ops.next()? {
+            counts.add(value.op.get_type());
+        }
+        Ok(counts)
+    }
+
+    #[instrument(skip(self, op_hashes))]
+    /// a remote agent is asking which of these ops we have integrated
+    fn handle_holds_ops(
+        &self,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    ) -> CellResult<Vec<holo_hash::DhtOpHash>> {
+        let integrated_dht_ops = IntegratedDhtOpsBuf::new(self.env().clone().into())?;
+        let mut held = Vec::new();
+        for op_hash in op_hashes {
+            if integrated_dht_ops.get(&op_hash)?.is_some() {
+                held.push(op_hash);
+            }
+        }
+        Ok(held)
+    }
+
     #[instrument(skip(self, op_hashes))]
     /// The network module is requesting the content for dht ops
     async fn handle_fetch_op_hash_data(
@@ -613,13 +1302,79 @@ impl Cell {
         unimplemented!()
     }
 
+    /// the network for this cell died and has been restarted
+    fn handle_network_restarted(&self, reason: String) {
+        warn!(cell_id = ?self.id(), %reason, "Network restarted");
+        // anything published to the old network may not have reached anyone,
+        // so publish it again
+        self.queue_triggers
+            .clone()
+            .trigger(QueueTrigger::PublishDhtOps);
+    }
+
     /// When the Conductor determines that it's time to execute some [AutonomicProcess],
     /// whether scheduled or through an [AutonomicCue], this function gets called
     pub async fn handle_autonomic_process(&self, process: AutonomicProcess) -> CellResult<()> {
         match process {
-            AutonomicProcess::SlowHeal => unimplemented!(),
-            AutonomicProcess::HealthCheck => unimplemented!(),
+            AutonomicProcess::SlowHeal => {
+                // Ops waiting on dependencies get another chance to find them
+                let mut triggers = self.queue_triggers.clone();
+                triggers.trigger(QueueTrigger::SysValidation);
+                triggers.trigger(QueueTrigger::AppValidation);
+            }
+            AutonomicProcess::HealthCheck => {
+                // Nobody can be asked while offline
+                if self.holochain_p2p_cell.is_offline() {
+                    return Ok(());
+                }
+                let report = check_redundancy(
+                    self.env.clone(),
+                    &mut self.holochain_p2p_cell.clone(),
+                    REDUNDANCY_SAMPLE_SIZE,
+                    TARGET_REDUNDANCY,
+                )
+                .await
+                .map_err(Box::new)?;
+                info!(
+                    cell_id = ?self.id(),
+                    sampled = report.sampled,
+                    under_replicated = report.under_replicated,
+                    mean_holders = report.mean_holders,
+                    min_holders = ?report.min_holders,
+                    "Checked redundancy of authored ops"
+                );
+                if report.under_replicated > 0 {
+                    self.queue_triggers
+                        .clone()
+                        .trigger(QueueTrigger::PublishDhtOps);
+                }
+            }
+            AutonomicProcess::PruneCaches => {
+                let swept = self.cap_grants.sweep_expired(self.clock.now());
+                if swept > 0 {
+                    debug!(cell_id = ?self.id(), swept, "Swept expired cap grants");
+                }
+            }
+            AutonomicProcess::ResizeArc => {
+                let mut network = self.holochain_p2p_cell.clone();
+                let (current, resized) = network.resized_arc().await?;
+                if current == resized {
+                    return Ok(());
+                }
+                // What's no longer held is handed off before letting go of it,
+                // in case this Cell is its only holder. If anything isn't
+                // taken, the arc stays as it is until the next try.
+                if !resized.contains_arc(&current) {
+                    let report = self.hand_off(current, resized).await?;
+                    if !report.unacknowledged.is_empty() {
+                        return Ok(());
+                    }
+                }
+                network.set_arc(resized).await?;
+                debug!(cell_id = ?self.id(), coverage = resized.coverage(), "Resized storage arc");
+            }
         }
+        Ok(())
     }
 
     #[instrument(skip(self, from_agent, fn_name, cap, payload))]
@@ -630,8 +1385,13 @@ impl Cell {
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        provenance: Option<CallProvenance>,
         payload: SerializedBytes,
     ) -> CellResult<SerializedBytes> {
+        // Only the sending agent is authenticated, so a path which doesn't
+        // end with them can't be right
+        let caller =
+            provenance.filter(|p| p.hops.last().map_or(false, |hop| hop.agent == from_agent));
         let invocation = ZomeCallInvocation {
             cell_id: self.id.clone(),
             zome_name: zome_name.clone(),
@@ -643,36 +1403,83 @@ impl Cell {
         // double ? because
         // - ConductorApiResult
         // - ZomeCallInvocationResult
-        Ok(self.call_zome(invocation).await??.try_into()?)
+        Ok(self
+            .call_zome_from(invocation, caller, CancelToken::new())
+            .await??
+            .try_into()?)
     }
 
-    /// Function called by the Conductor
-    #[instrument(skip(self, invocation))]
+    /// Function called by the Conductor.
+    /// The call gives up early if `cancel` fires.
+    ///
+    /// When the conductor is busy the call first waits for the app's turn,
+    /// see [ZomeCallScheduler](crate::conductor::zome_call_scheduler).
+    ///
+    /// Concurrent calls to this Cell each work on their own view of the
+    /// source chain, so when two of them write, the one which commits
+    /// second finds the chain head has moved. That call is run again
+    /// from scratch, up to the configured number of retries.
+    #[instrument(skip(self, invocation, cancel))]
     pub async fn call_zome(
         &self,
         invocation: ZomeCallInvocation,
+        cancel: CancelToken,
+    ) -> CellResult<ZomeCallInvocationResult> {
+        let _turn = match cancel.or_cancel(self.zome_calls.turn()).await {
+            Ok(turn) => turn,
+            Err(cancelled) => return Ok(Err(cancelled.into())),
+        };
+        self.call_zome_from(invocation, None, cancel).await
+    }
+
+    /// Call a zome function on behalf of another zome function,
+    /// given the path which led to the caller
+    async fn call_zome_from(
+        &self,
+        invocation: ZomeCallInvocation,
+        caller: Option<CallProvenance>,
+        cancel: CancelToken,
     ) -> CellResult<ZomeCallInvocationResult> {
         // Check if init has run if not run it
         self.check_or_run_zome_init().await?;
 
         let arc = self.env();
         let keystore = arc.keystore().clone();
-        let workspace = CallZomeWorkspace::new(arc.clone().into())?;
+        let ribosome = self.get_ribosome().await?;
 
-        let args = CallZomeWorkflowArgs {
-            ribosome: self.get_ribosome().await?,
-            invocation,
-        };
-        Ok(call_zome_workflow(
-            workspace,
-            self.holochain_p2p_cell.clone(),
-            keystore,
-            arc.clone().into(),
-            args,
-            self.queue_triggers.produce_dht_ops.clone(),
-        )
-        .await
-        .map_err(Box::new)?)
+        let result = retry_if_head_moved(self.zome_call_retries, || {
+            let workspace = CallZomeWorkspace::new(arc.clone().into());
+            let clock = self.clock.clone();
+            let args = CallZomeWorkflowArgs {
+                ribosome: ribosome.clone(),
+                invocation: invocation.clone(),
+                quota: self.quota.clone(),
+                cancel: cancel.clone(),
+                cap_grants: self.cap_grants.clone(),
+                caller: caller.clone(),
+                clock: self.clock.clone(),
+                entropy: self.entropy.clone(),
+            };
+            let network = self.holochain_p2p_cell.clone();
+            let keystore = keystore.clone();
+            let writer = arc.clone().into();
+            let trigger_produce_dht_ops = self.queue_triggers.produce_dht_ops.clone();
+            async move {
+                let mut workspace = workspace?;
+                workspace.source_chain.set_clock(clock);
+                call_zome_workflow(
+                    workspace,
+                    network,
+                    keystore,
+                    writer,
+                    args,
+                    trigger_produce_dht_ops,
+                )
+                .await
+            }
+        })
+        .await;
+        Ok(result.map_err(Box::new)?)
     }
 
     /// Check if each Zome's init callback has been run, and if not, run it.
@@ -701,7 +1508,7 @@ impl Cell {
         let dna_def = dna_file.dna().clone();
 
         // Get the ribosome
-        let ribosome = WasmRibosome::new(dna_file);
+        let ribosome = WasmRibosome::new(dna_file).with_io_limits(self.wasm_io_limits);
 
         // Run the workflow
         let args = InitializeZomesWorkflowArgs { dna_def, ribosome };
@@ -739,7 +1546,7 @@ impl Cell {
     // TODO: reevaluate once Workflows are fully implemented (after B-01567)
     pub(crate) async fn get_ribosome(&self) -> CellResult<WasmRibosome> {
         match self.conductor_api.get_dna(self.dna_hash()).await {
-            Some(dna) => Ok(WasmRibosome::new(dna)),
+            Some(dna) => Ok(WasmRibosome::new(dna).with_io_limits(self.wasm_io_limits)),
             None => Err(CellError::DnaMissing),
         }
     }
@@ -750,14 +1557,34 @@ impl Cell {
         &self.env
     }
 
-    #[cfg(test)]
-    /// Get the triggers for the cell
-    /// Useful for testing when you want to
-    /// Cause workflows to trigger
+    /// Get the triggers for the cell, to inspect its queue consumers
+    /// or cause workflows to trigger
     pub(crate) fn triggers(&self) -> &InitialQueueTriggers {
         &self.queue_triggers
     }
 }
 
+/// Run a workflow, running it again from the start each time another write
+/// moves the source chain head before it can commit, at most `retries` times
+async fn retry_if_head_moved<T, F, Fut>(retries: u32, mut run: F) -> WorkflowResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = WorkflowResult<T>>,
+{
+    let mut retried = 0;
+    loop {
+        match run().await {
+            Err(e) if e.is_head_moved() && retried < retries => {
+                retried += 1;
+                debug!(
+                    retries = retried,
+                    "Chain head moved during zome call, retrying"
+                );
+            }
+            result => return result,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test;
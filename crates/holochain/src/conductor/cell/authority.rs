@@ -2,18 +2,27 @@ use super::error::{AuthorityDataError, CellResult};
 use crate::core::state::{
     element_buf::ElementBuf,
     metadata::{MetadataBuf, MetadataBufT},
+    pins::PinsBuf,
 };
 use fallible_iterator::FallibleIterator;
 
-use holo_hash::EntryHash;
+use holo_hash::{EntryHash, HeaderHash};
 use holochain_state::{env::EnvironmentWrite, fresh_reader};
 use holochain_types::{
-    element::{GetElementResponse, RawGetEntryResponse},
-    header::WireUpdateRelationship,
+    element::{CollapsedGetEntryResponse, GetElementResponse, RawGetEntryResponse},
+    header::{WireDelete, WireUpdateRelationship},
     metadata::TimedHeaderHash,
 };
-use holochain_zome_types::{element::SignedHeaderHashed, header::conversions::WrongHeaderError};
-use std::{collections::BTreeSet, convert::TryInto};
+use holochain_zome_types::{
+    element::SignedHeaderHashed,
+    header::{conversions::WrongHeaderError, EntryType},
+    Entry,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryInto,
+    ops::Bound,
+};
 use tracing::*;
 
 #[instrument(skip(state_env))]
@@ -25,6 +34,32 @@ pub async fn handle_get_entry(
     // Get the vaults
     let element_vault = ElementBuf::vault(state_env.clone().into(), false)?;
     let meta_vault = MetadataBuf::vault(state_env.clone().into())?;
+    let r = get_entry_from(&state_env, &element_vault, &meta_vault, &hash, &options)?;
+
+    // Pinned entries are served from the cache, even if we aren't an authority for them
+    if let GetElementResponse::GetEntryFull(None) | GetElementResponse::GetEntryCollapsed(None) = r
+    {
+        if PinsBuf::new(state_env.clone().into())?.is_pinned(&hash.clone().into())? {
+            let element_cache = ElementBuf::cache(state_env.clone().into())?;
+            let meta_cache = MetadataBuf::cache(state_env.clone().into())?;
+            return get_entry_from(&state_env, &element_cache, &meta_cache, &hash, &options);
+        }
+    }
+    Ok(r)
+}
+
+/// Gather the response to a get for an entry from one pair of stores
+fn get_entry_from(
+    state_env: &EnvironmentWrite,
+    element_store: &ElementBuf,
+    meta_store: &MetadataBuf,
+    hash: &EntryHash,
+    options: &holochain_p2p::event::GetOptions,
+) -> CellResult<GetElementResponse> {
+    if options.collapsed {
+        let r = get_entry_collapsed_from(state_env, element_store, meta_store, hash, options)?;
+        return Ok(GetElementResponse::GetEntryCollapsed(r.map(Box::new)));
+    }
 
     // ## Helper closures to DRY and make more readable
 
@@ -32,7 +67,7 @@ pub async fn handle_get_entry(
     // Render headers from TimedHeaderHash to SignedHeaderHash
     let render_header = |timed_header_hash: TimedHeaderHash| {
         let header_hash = timed_header_hash.header_hash;
-        let r = element_vault
+        let r = element_store
             .get_header(&header_hash)?
             .ok_or_else(|| AuthorityDataError::missing_data(header_hash))?;
         CellResult::Ok(r)
@@ -40,30 +75,14 @@ pub async fn handle_get_entry(
 
     // ### Get entry data closure
     // Get the entry from a header
-    let get_entry = |header: SignedHeaderHashed| {
-        // Does the header contain entry data?
-        let (eh, et) = header.header().entry_data().ok_or_else(|| {
-            AuthorityDataError::WrongHeaderError(WrongHeaderError(format!(
-                "Header should have entry data: {:?}",
-                header
-            )))
-        })?;
-
-        // Can we get the actual entry
-        let entry_data = element_vault
-            .get_entry(&eh)?
-            .map(|e| (e.into_content(), et.clone()))
-            // Missing the entry
-            .ok_or_else(|| AuthorityDataError::missing_data_entry(header))?;
-        CellResult::Ok(entry_data)
-    };
+    let get_entry = |header: SignedHeaderHashed| get_entry_of_header(element_store, header);
 
     // ### Gather headers closure
     // This gathers the headers and deletes we want
     let gather_headers = |reader| {
         let mut deletes = Vec::new();
         let mut updates = Vec::new();
-        let headers = meta_vault
+        let headers = meta_store
             .get_headers(&reader, hash.clone())?
             .collect::<Vec<_>>()?;
         let mut live_headers = BTreeSet::new();
@@ -72,14 +91,14 @@ pub async fn handle_get_entry(
         if options.all_live_headers_with_metadata {
             for hash in headers {
                 deletes.extend(
-                    meta_vault
+                    meta_store
                         .get_deletes_on_header(&reader, hash.header_hash.clone())?
                         .iterator(),
                 );
                 let header = render_header(hash)?;
                 live_headers.insert(header.try_into()?);
             }
-            let updates_returns = meta_vault
+            let updates_returns = meta_store
                 .get_updates(&reader, hash.clone().into())?
                 .collect::<Vec<_>>()?;
             let updates_returns = updates_returns.into_iter().map(|update| {
@@ -94,7 +113,7 @@ pub async fn handle_get_entry(
         } else {
             for hash in headers {
                 // Check for a delete
-                let is_deleted = meta_vault
+                let is_deleted = meta_store
                     .get_deletes_on_header(&reader, hash.header_hash.clone())?
                     .next()?
                     .is_some();
@@ -102,7 +121,7 @@ pub async fn handle_get_entry(
                 // If there is a delete then gather all deletes
                 if is_deleted {
                     deletes.extend(
-                        meta_vault
+                        meta_store
                             .get_deletes_on_header(&reader, hash.header_hash.clone())?
                             .iterator(),
                     );
@@ -129,7 +148,7 @@ pub async fn handle_get_entry(
     // Get the entry from the first header
 
     fresh_reader!(state_env, |reader| {
-        let first_header = meta_vault.get_headers(&reader, hash.clone())?.next()?;
+        let first_header = meta_store.get_headers(&reader, hash.clone())?.next()?;
         let entry_data = match first_header {
             Some(first_header) => {
                 let header = render_header(first_header)?;
@@ -149,6 +168,7 @@ pub async fn handle_get_entry(
                     updates,
                     entry,
                     entry_type,
+                    receipts: Vec::new(),
                 };
                 Some(Box::new(r))
             }
@@ -158,3 +178,140 @@ pub async fn handle_get_entry(
         Ok(GetElementResponse::GetEntryFull(r))
     })
 }
+
+/// Gather the same data as [get_entry_from] in a single pass over the
+/// headers on the entry. Each header's deletes are only read once and
+/// every header is rendered in one batch.
+fn get_entry_collapsed_from(
+    state_env: &EnvironmentWrite,
+    element_store: &ElementBuf,
+    meta_store: &MetadataBuf,
+    hash: &EntryHash,
+    options: &holochain_p2p::event::GetOptions,
+) -> CellResult<Option<CollapsedGetEntryResponse>> {
+    let (first_header, live_headers, deletes, updates) = fresh_reader!(state_env, |reader| {
+        let mut first_header = None;
+        let mut live_headers = Vec::new();
+        let mut deletes = Vec::new();
+        let mut headers = meta_store.get_headers(&reader, hash.clone())?;
+        while let Some(header) = headers.next()? {
+            let header_deletes = meta_store
+                .get_deletes_on_header(&reader, header.header_hash.clone())?
+                .map(|delete| Ok(delete.header_hash))
+                .collect::<Vec<_>>()?;
+            if header_deletes.is_empty() || options.all_live_headers_with_metadata {
+                live_headers.push(header.header_hash.clone());
+            }
+            deletes.extend(header_deletes);
+            first_header.get_or_insert(header.header_hash);
+        }
+        let updates = if options.all_live_headers_with_metadata {
+            meta_store
+                .get_updates(&reader, hash.clone().into())?
+                .map(|update| Ok(update.header_hash))
+                .collect::<Vec<_>>()?
+        } else {
+            Vec::new()
+        };
+        CellResult::Ok((first_header, live_headers, deletes, updates))
+    })?;
+
+    // There are no headers so we don't have the entry
+    let first_header = match first_header {
+        Some(first_header) => first_header,
+        None => return Ok(None),
+    };
+
+    let live_count = live_headers.len();
+    let delete_count = deletes.len();
+    let mut hashes: Vec<HeaderHash> = live_headers;
+    hashes.extend(deletes);
+    hashes.extend(updates);
+    hashes.push(first_header);
+    let rendered = element_store.get_headers(&hashes)?;
+    let mut rendered = rendered
+        .into_iter()
+        .zip(hashes)
+        .map(|(header, hash)| header.ok_or_else(|| AuthorityDataError::missing_data(hash)));
+
+    let mut live_headers = BTreeSet::new();
+    for header in rendered.by_ref().take(live_count) {
+        live_headers.insert(header?.try_into()?);
+    }
+    let mut deletes = Vec::with_capacity(delete_count);
+    for header in rendered.by_ref().take(delete_count) {
+        let delete: WireDelete = header?.try_into().map_err(AuthorityDataError::from)?;
+        deletes.push(delete.into());
+    }
+    let mut rendered: Vec<_> = rendered.collect::<Result<_, _>>()?;
+    let first_header = rendered.pop().expect("The first header is always rendered");
+    let updates = rendered
+        .into_iter()
+        .map(|update| {
+            let update: WireUpdateRelationship =
+                update.try_into().map_err(AuthorityDataError::from)?;
+            CellResult::Ok(update)
+        })
+        .collect::<Result<_, _>>()?;
+
+    let (entry, entry_type) = get_entry_of_header(element_store, first_header)?;
+    let r = CollapsedGetEntryResponse {
+        live_headers,
+        deletes,
+        updates,
+        entry,
+        entry_type,
+        receipts: Vec::new(),
+    };
+    debug!(handle_get_collapsed_return = ?r);
+    Ok(Some(r))
+}
+
+/// Get the entry which a header creates
+fn get_entry_of_header(
+    element_store: &ElementBuf,
+    header: SignedHeaderHashed,
+) -> CellResult<(Entry, EntryType)> {
+    // Does the header contain entry data?
+    let (eh, et) = header.header().entry_data().ok_or_else(|| {
+        AuthorityDataError::WrongHeaderError(WrongHeaderError(format!(
+            "Header should have entry data: {:?}",
+            header
+        )))
+    })?;
+
+    // Can we get the actual entry
+    let entry_data = element_store
+        .get_entry(&eh)?
+        .map(|e| (e.into_content(), et.clone()))
+        // Missing the entry
+        .ok_or_else(|| AuthorityDataError::missing_data_entry(header))?;
+    Ok(entry_data)
+}
+
+/// The links on one page of a get links response
+pub type LinksPage<'a> = Vec<(&'a TimedHeaderHash, &'a BTreeSet<TimedHeaderHash>)>;
+
+/// Take the page of `links` which starts after `after`, holding as many
+/// link adds, each with its removes, as fit in `max_headers` headers.
+/// A page always holds at least one link, however many removes it has.
+/// If any links are left over, also returns the last link add on the page
+/// as the continuation to ask for the next page with.
+pub fn page_links(
+    links: &BTreeMap<TimedHeaderHash, BTreeSet<TimedHeaderHash>>,
+    after: Option<TimedHeaderHash>,
+    max_headers: usize,
+) -> (LinksPage<'_>, Option<TimedHeaderHash>) {
+    let start = after.map_or(Bound::Unbounded, Bound::Excluded);
+    let mut page: LinksPage = Vec::new();
+    let mut headers = 0;
+    for (link_add, link_removes) in links.range((start, Bound::Unbounded)) {
+        headers += 1 + link_removes.len();
+        if !page.is_empty() && headers > max_headers {
+            let continuation = page.last().map(|(link_add, _)| (*link_add).clone());
+            return (page, continuation);
+        }
+        page.push((link_add, link_removes));
+    }
+    (page, None)
+}
@@ -2,12 +2,15 @@ use crate::{
     conductor::api::error::ConductorApiError,
     core::{
         ribosome::{error::RibosomeError, guest_callback::init::InitResult},
+        state::cascade::error::CascadeError,
         workflow::{
             error::WorkflowError, produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError,
         },
         SourceChainError,
     },
 };
+use holo_hash::DnaHash;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_p2p::HolochainP2pError;
 use holochain_state::error::DatabaseError;
 use holochain_types::{cell::CellId, header::error::HeaderError};
@@ -29,6 +32,8 @@ pub enum CellError {
     HeaderError(#[from] HeaderError),
     #[error("This cell has not had a successful genesis and cannot be created")]
     CellWithoutGenesis(CellId),
+    #[error("Genesis left the cell {0:?} unusable: {1}")]
+    GenesisCheckFailed(CellId, String),
     #[error("The cell failed to cleanup its environment because: {0}. Recommend manually deleting the database at: {1}")]
     Cleanup(String, PathBuf),
     #[error(transparent)]
@@ -47,12 +52,44 @@ pub enum CellError {
     SerializedBytesError(#[from] holochain_serialized_bytes::SerializedBytesError),
     #[error(transparent)]
     DhtOpConvertError(#[from] DhtOpConvertError),
+    #[error(transparent)]
+    CascadeError(#[from] CascadeError),
     #[error("Cell is an authority for is missing or incorrect: {0}")]
     AuthorityDataError(#[from] AuthorityDataError),
+    #[error("The snapshot holds ops for the Dna {found}, not this cell's Dna {expected}")]
+    SnapshotForOtherDna { expected: DnaHash, found: DnaHash },
     #[error("Todo")]
     Todo,
 }
 
+impl HasErrorKind for CellError {
+    fn error_kind(&self) -> ErrorKind {
+        use CellError::*;
+        match self {
+            DatabaseError(e) => e.error_kind(),
+            DnaMissing => ErrorKind::NotFound,
+            JoinError(_)
+            | HeaderError(_)
+            | CellWithoutGenesis(_)
+            | GenesisCheckFailed(_, _)
+            | Todo => ErrorKind::Internal,
+            Genesis(e) => e.error_kind(),
+            Cleanup(_, _) => ErrorKind::Storage,
+            WorkflowError(e) => e.error_kind(),
+            WorkspaceError(e) => e.error_kind(),
+            RibosomeError(e) => e.error_kind(),
+            SourceChainError(e) => e.error_kind(),
+            InitFailed(_) => ErrorKind::Validation,
+            HolochainP2pError(e) => e.error_kind(),
+            SerializedBytesError(_) => ErrorKind::Serialization,
+            DhtOpConvertError(e) => e.error_kind(),
+            CascadeError(e) => e.error_kind(),
+            AuthorityDataError(e) => e.error_kind(),
+            SnapshotForOtherDna { .. } => ErrorKind::InvalidInput,
+        }
+    }
+}
+
 pub type CellResult<T> = Result<T, CellError>;
 
 #[derive(Error, Debug)]
@@ -67,6 +104,18 @@ pub enum AuthorityDataError {
     MissingMetadata(String),
 }
 
+impl HasErrorKind for AuthorityDataError {
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            AuthorityDataError::DhtOpConvertError(e) => e.error_kind(),
+            AuthorityDataError::WrongHeaderError(_) => ErrorKind::InvalidInput,
+            AuthorityDataError::MissingData(_) | AuthorityDataError::MissingMetadata(_) => {
+                ErrorKind::NotFound
+            }
+        }
+    }
+}
+
 impl AuthorityDataError {
     pub fn missing_data<T: std::fmt::Debug>(data: T) -> CellError {
         Self::MissingData(format!("Missing header {:?}", data)).into()
@@ -1,19 +1,38 @@
 use crate::{
-    conductor::manager::spawn_task_manager,
-    core::workflow::incoming_dht_ops_workflow::IncomingDhtOpsWorkspace,
-    fixt::{DnaFileFixturator, SignatureFixturator},
+    conductor::{manager::spawn_task_manager, zome_call_scheduler::ZomeCallScheduler},
+    core::{
+        state::{source_chain::SourceChain, workspace::Workspace},
+        workflow::{
+            error::WorkflowResult, fake_genesis,
+            incoming_dht_ops_workflow::IncomingDhtOpsWorkspace, CallZomeWorkspace,
+        },
+    },
+    fixt::{DnaFileFixturator, EntryFixturator, SignatureFixturator},
 };
 use ::fixt::prelude::*;
-use holo_hash::HasHash;
+use holo_hash::{HasHash, HeaderHash};
 use holochain_p2p::actor::HolochainP2pRefToCell;
-use holochain_state::test_utils::{test_cell_env, TestEnvironment};
+use holochain_state::{
+    buffer::BufferedStore,
+    env::{EnvironmentWrite, WriteManager},
+    test_utils::{test_cell_env, TestEnvironment},
+};
 use holochain_types::{
+    cell::CellId,
     dht_op::{DhtOp, DhtOpHashed},
+    fixt::AppEntry,
     test_utils::{fake_agent_pubkey_2, fake_cell_id},
-    HeaderHashed, Timestamp,
+    EntryHashed, HeaderHashed, Timestamp,
+};
+use holochain_zome_types::{
+    entry_def::EntryVisibility,
+    header::{self, builder, AppEntryType, EntryType},
+};
+use matches::assert_matches;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
 };
-use holochain_zome_types::header;
-use std::sync::Arc;
 use tokio::sync;
 
 #[tokio::test(threaded_scheduler)]
@@ -36,9 +55,15 @@ async fn test_cell_handle_publish() {
 
     let mock_handler: crate::conductor::handle::ConductorHandle = Arc::new(mock_handler);
 
-    super::Cell::genesis(cell_id.clone(), mock_handler.clone(), env.clone(), None)
-        .await
-        .unwrap();
+    super::Cell::genesis(
+        cell_id.clone(),
+        mock_handler.clone(),
+        env.clone(),
+        None,
+        Default::default(),
+    )
+    .await
+    .unwrap();
 
     let (add_task_sender, shutdown) = spawn_task_manager();
     let (stop_tx, _) = sync::broadcast::channel(1);
@@ -50,6 +75,14 @@ async fn test_cell_handle_publish() {
         holochain_p2p_cell,
         add_task_sender,
         stop_tx.clone(),
+        Default::default(),
+        Default::default(),
+        ZomeCallScheduler::new(1, Default::default()).for_app("test".into()),
+        Default::default(),
+        0,
+        Default::default(),
+        Default::default(),
+        Default::default(),
     )
     .await
     .unwrap();
@@ -69,6 +102,7 @@ async fn test_cell_handle_publish() {
         true,
         header_hash.clone().into(),
         vec![(op_hash.clone(), op.clone())],
+        vec![],
     )
     .await
     .unwrap();
@@ -81,3 +115,166 @@ async fn test_cell_handle_publish() {
     stop_tx.send(()).unwrap();
     shutdown.await.unwrap();
 }
+
+#[tokio::test(threaded_scheduler)]
+async fn test_check_genesis() {
+    let TestEnvironment {
+        env,
+        tmpdir: _tmpdir,
+    } = test_cell_env();
+    let cell_id = fake_cell_id(1);
+
+    // Nothing on the chain yet
+    assert_matches!(
+        super::Cell::check_genesis(&cell_id, &env),
+        Err(super::CellError::GenesisCheckFailed(_, _))
+    );
+
+    let mut mock_handler = crate::conductor::handle::MockConductorHandleT::new();
+    mock_handler
+        .expect_get_dna()
+        .returning(|_| Some(fixt!(DnaFile)));
+    let mock_handler: crate::conductor::handle::ConductorHandle = Arc::new(mock_handler);
+    super::Cell::genesis(
+        cell_id.clone(),
+        mock_handler,
+        env.clone(),
+        None,
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    super::Cell::check_genesis(&cell_id, &env).unwrap();
+
+    // The chain belongs to a different agent
+    let other_cell_id = CellId::new(cell_id.dna_hash().clone(), fake_agent_pubkey_2());
+    assert_matches!(
+        super::Cell::check_genesis(&other_cell_id, &env),
+        Err(super::CellError::GenesisCheckFailed(_, _))
+    );
+}
+
+#[test]
+fn test_page_links() {
+    use holochain_types::{metadata::TimedHeaderHash, test_utils::fake_header_hash};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    let timed = |i: u8| TimedHeaderHash {
+        timestamp: Timestamp(i as i64, 0),
+        header_hash: fake_header_hash(i),
+    };
+    // Five links, the second with three removes
+    let links: BTreeMap<_, _> = (0..5)
+        .map(|i| {
+            let removes: BTreeSet<_> = match i {
+                1 => (10..13).map(timed).collect(),
+                _ => BTreeSet::new(),
+            };
+            (timed(i), removes)
+        })
+        .collect();
+
+    // The second link and its removes don't fit with the first
+    let (page, continuation) = super::authority::page_links(&links, None, 4);
+    assert_eq!(page.len(), 1);
+    assert_eq!(continuation, Some(timed(0)));
+
+    let (page, continuation) = super::authority::page_links(&links, continuation, 4);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].1.len(), 3);
+    assert_eq!(continuation, Some(timed(1)));
+
+    let (page, continuation) = super::authority::page_links(&links, continuation, 4);
+    assert_eq!(page.len(), 3);
+    assert_eq!(continuation, None);
+
+    // A link with more removes than fit is still sent on its own
+    let (page, continuation) = super::authority::page_links(&links, Some(timed(0)), 2);
+    assert_eq!(page.len(), 1);
+    assert_eq!(continuation, Some(timed(1)));
+}
+
+/// Commit a new entry to the source chain in a fresh workspace. Given a race,
+/// signal once the entry is put and wait for the go ahead before committing,
+/// so another commit can move the chain head in between.
+async fn commit_entry(
+    env: EnvironmentWrite,
+    race: Option<(sync::oneshot::Sender<()>, sync::oneshot::Receiver<()>)>,
+) -> WorkflowResult<HeaderHash> {
+    let mut workspace = CallZomeWorkspace::new(env.clone().into())?;
+    let entry = EntryFixturator::new(AppEntry).next().unwrap();
+    let header_builder = builder::Create {
+        entry_type: EntryType::App(AppEntryType::new(
+            0.into(),
+            0.into(),
+            EntryVisibility::Public,
+        )),
+        entry_hash: EntryHashed::from_content_sync(entry.clone()).into_hash(),
+    };
+    let hash = workspace
+        .source_chain
+        .put(header_builder, Some(entry))
+        .await?;
+    if let Some((put, go)) = race {
+        put.send(()).unwrap();
+        go.await.unwrap();
+    }
+    env.guard()
+        .with_commit(|writer| workspace.flush_to_txn_ref(writer))?;
+    Ok(hash)
+}
+
+/// Race two commits to a genesised chain, the first of which is held up
+/// until the second has committed. Returns the result of the first, how
+/// many times it ran, and the length of the chain afterwards.
+async fn race_commits(retries: u32) -> (WorkflowResult<HeaderHash>, u32, usize) {
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let mut source_chain = SourceChain::new(env.clone().into()).unwrap();
+    fake_genesis(&mut source_chain).await.unwrap();
+    env.guard()
+        .with_commit(|writer| source_chain.flush_to_txn(writer))
+        .unwrap();
+
+    let (put_tx, put_rx) = sync::oneshot::channel();
+    let (go_tx, go_rx) = sync::oneshot::channel();
+    let mut race = Some((put_tx, go_rx));
+    let attempts = Arc::new(AtomicU32::new(0));
+    let loser = tokio::spawn({
+        let env = env.clone();
+        let attempts = attempts.clone();
+        async move {
+            super::retry_if_head_moved(retries, || {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                commit_entry(env.clone(), race.take())
+            })
+            .await
+        }
+    });
+
+    put_rx.await.unwrap();
+    commit_entry(env.clone(), None).await.unwrap();
+    go_tx.send(()).unwrap();
+
+    let result = loser.await.unwrap();
+    let len = SourceChain::new(env.clone().into()).unwrap().len();
+    (result, attempts.load(Ordering::SeqCst), len)
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn zome_call_which_loses_a_commit_race_retries() {
+    let (result, attempts, len) = race_commits(1).await;
+    assert!(result.is_ok());
+    assert_eq!(attempts, 2);
+    // Genesis and both commits
+    assert_eq!(len, 5);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn zome_call_gives_up_once_out_of_retries() {
+    let (result, attempts, len) = race_commits(0).await;
+    assert!(result.unwrap_err().is_head_moved());
+    assert_eq!(attempts, 1);
+    // Only the winner committed
+    assert_eq!(len, 4);
+}
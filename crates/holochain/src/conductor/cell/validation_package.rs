@@ -0,0 +1,57 @@
+//! Caching the validation packages which validators request from the
+//! author of a header
+
+use holo_hash::HeaderHash;
+use holochain_zome_types::validate::ValidationPackage;
+use std::collections::{HashMap, VecDeque};
+
+/// How many assembled packages a Cell keeps
+const CACHE_SIZE: usize = 128;
+
+/// Recently assembled validation packages.
+/// The chain before a header never changes, so neither does its package.
+#[derive(Default)]
+pub(super) struct ValidationPackageCache {
+    packages: HashMap<HeaderHash, ValidationPackage>,
+    /// Oldest first, so we know what to evict
+    order: VecDeque<HeaderHash>,
+}
+
+impl ValidationPackageCache {
+    pub fn get(&self, header_hash: &HeaderHash) -> Option<ValidationPackage> {
+        self.packages.get(header_hash).cloned()
+    }
+
+    pub fn put(&mut self, header_hash: HeaderHash, package: ValidationPackage) {
+        if self.packages.insert(header_hash.clone(), package).is_none() {
+            self.order.push_back(header_hash);
+            if self.order.len() > CACHE_SIZE {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.packages.remove(&oldest);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixt::prelude::*;
+    use holo_hash::fixt::HeaderHashFixturator;
+
+    #[test]
+    fn cache_evicts_oldest() {
+        let mut cache = ValidationPackageCache::default();
+        let mut hashes = HeaderHashFixturator::new(Unpredictable);
+        let first = hashes.next().unwrap();
+        cache.put(first.clone(), ValidationPackage::new(vec![]));
+        assert!(cache.get(&first).is_some());
+
+        for hash in hashes.take(CACHE_SIZE) {
+            cache.put(hash, ValidationPackage::new(vec![]));
+        }
+        assert!(cache.get(&first).is_none());
+        assert_eq!(cache.packages.len(), CACHE_SIZE);
+    }
+}
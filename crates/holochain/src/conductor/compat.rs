@@ -1,7 +1,6 @@
 use super::{
     config::{AdminInterfaceConfig, ConductorConfig, DpkiConfig, InterfaceDriver},
     error::ConductorError,
-    state::AppInterfaceConfig,
     ConductorBuilder, ConductorHandle,
 };
 use holo_hash::*;
@@ -116,16 +115,13 @@ pub async fn load_conductor_from_legacy_config(
         return Err(Box::new(ConductorError::from(error)).into());
     }
 
-    for i in app_interfaces {
-        let AppInterfaceConfig {
-            driver: InterfaceDriver::Websocket { port },
-            cells: _,
-        } = i;
-        conductor
-            .clone()
-            .add_app_interface(port)
-            .await
-            .map_err(Box::new)?;
+    for driver in app_interfaces {
+        match driver {
+            InterfaceDriver::Websocket { port } => conductor.clone().add_app_interface(port, None),
+            InterfaceDriver::Http { port } => conductor.clone().add_http_app_interface(port),
+        }
+        .await
+        .map_err(Box::new)?;
     }
 
     Ok(conductor)
@@ -150,6 +146,7 @@ fn convert_dpki(legacy: legacy::DpkiConfig) -> DpkiConfig {
 fn convert_interface_driver(legacy: legacy::InterfaceDriver) -> Option<InterfaceDriver> {
     match legacy {
         legacy::InterfaceDriver::Websocket { port } => Some(InterfaceDriver::Websocket { port }),
+        legacy::InterfaceDriver::Http { port } => Some(InterfaceDriver::Http { port }),
         _ => None,
     }
 }
@@ -161,24 +158,22 @@ fn extract_admin_interfaces(
         .into_iter()
         .filter(|c| c.admin)
         .filter_map(|c: legacy::InterfaceConfig| {
-            convert_interface_driver(c.driver).map(|driver| AdminInterfaceConfig { driver })
+            convert_interface_driver(c.driver)
+                // Admin interfaces are only served over websockets
+                .filter(|driver| matches!(driver, InterfaceDriver::Websocket { .. }))
+                .map(|driver| AdminInterfaceConfig {
+                    driver,
+                    tokens: vec![],
+                })
         })
         .collect()
 }
 
-fn extract_app_interfaces(
-    legacy_interfaces: Vec<legacy::InterfaceConfig>,
-) -> Vec<AppInterfaceConfig> {
+fn extract_app_interfaces(legacy_interfaces: Vec<legacy::InterfaceConfig>) -> Vec<InterfaceDriver> {
     legacy_interfaces
         .into_iter()
         .filter(|c| !c.admin)
-        .filter_map(|c: legacy::InterfaceConfig| {
-            convert_interface_driver(c.driver).map(|driver| AppInterfaceConfig {
-                driver,
-                // FIXME: cells not hooked up for now since we don't use signals yet
-                cells: Vec::new(),
-            })
-        })
+        .filter_map(|c: legacy::InterfaceConfig| convert_interface_driver(c.driver))
         .collect()
 }
 
@@ -308,6 +303,7 @@ pub mod tests {
             config.admin_interfaces.unwrap()[0],
             AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port: 2222 },
+                tokens: vec![],
             }
         );
         assert!(config.dpki.is_some());
@@ -385,9 +381,9 @@ pub mod tests {
             .returning(|| Ok(vec![]));
         handle
             .expect_add_app_interface()
-            .with(predicate::eq(1111))
+            .with(predicate::eq(1111), predicate::eq(None))
             .times(1)
-            .returning(|port| Ok(port));
+            .returning(|port, _| Ok(port));
 
         let builder = Conductor::builder().with_mock_handle(handle);
         let _ = load_conductor_from_legacy_config(legacy_config, builder)
@@ -10,37 +10,61 @@
 //! users in a testing environment.
 use super::{
     api::{CellConductorApi, CellConductorApiT, RealAdminInterfaceApi, RealAppInterfaceApi},
-    config::{AdminInterfaceConfig, InterfaceDriver},
+    config::{AdminInterfaceConfig, HealthCheckConfig, InterfaceDriver, SignalWebhookConfig},
     dna_store::{DnaDefBuf, DnaStore, RealDnaStore},
     entry_def_store::{get_entry_defs, EntryDefBuf, EntryDefBufferKey},
     error::{ConductorError, CreateAppError},
     handle::ConductorHandleImpl,
+    health::{HealthEvent, HEALTH_EVENT_BUFFER_SIZE},
     interface::{
-        error::InterfaceResult,
+        error::{InterfaceError, InterfaceResult},
+        signal_routes::{AppInterfaceBinding, SignalRoutes},
         websocket::{
             spawn_admin_interface_task, spawn_app_interface_task, spawn_websocket_listener,
             SIGNAL_BUFFER_SIZE,
         },
     },
     manager::{
-        keep_alive_task, spawn_task_manager, ManagedTaskAdd, ManagedTaskHandle,
+        keep_alive_task, spawn_task_manager, ManagedTaskAdd, ManagedTaskHandle, ManagedTaskResult,
         TaskManagerRunHandle,
     },
+    passphrase_service::request_passphrase,
     paths::EnvironmentRootPath,
     state::ConductorState,
+    zome_call_scheduler::{ZomeCallScheduler, DEFAULT_MAX_CONCURRENT_ZOME_CALLS},
     CellError,
 };
 use crate::{
     conductor::{
-        api::error::ConductorApiResult, cell::Cell, config::ConductorConfig,
-        dna_store::MockDnaStore, error::ConductorResult, handle::ConductorHandle,
+        api::error::{ConductorApiResult, SerializationError},
+        cell::Cell,
+        config::ConductorConfig,
+        dna_store::MockDnaStore,
+        error::ConductorResult,
+        handle::ConductorHandle,
     },
-    core::state::{source_chain::SourceChainBuf, wasm::WasmBuf},
+    core::autonomic::AutonomicSchedule,
+    core::clock::{Clock, Entropy},
+    core::dht_snapshot::{DhtSnapshot, SnapshotImportReport},
+    core::queue_consumer::QueueTrigger,
+    core::quota::AppQuota,
+    core::ribosome::wasm_io::WasmIoLimits,
+    core::signal::CellSignal,
+    core::state::{
+        limbo_dump::{dump_limbo, LimboOpInfo},
+        source_chain::SourceChainBuf,
+        wasm::WasmBuf,
+    },
+    core::subconscious::UnresolvedDependencyPolicy,
+    core::workflow::call_zome_workflow::DEFAULT_ZOME_CALL_RETRIES,
+    core::workflow::genesis_workflow::DEFAULT_MAX_CONCURRENT_GENESIS,
 };
 use holochain_keystore::{
-    lair_keystore::spawn_lair_keystore, test_keystore::spawn_test_keystore, KeystoreSender,
-    KeystoreSenderExt,
+    lair_keystore::{spawn_lair_keystore, UnlockPassphrase},
+    test_keystore::spawn_test_keystore,
+    KeystoreSender, KeystoreSenderExt,
 };
+use holochain_p2p::{actor::GossipRound, HolochainP2pSender};
 use holochain_state::{
     buffer::BufferedStore,
     buffer::{KvStore, KvStoreT},
@@ -51,17 +75,27 @@ use holochain_state::{
     prelude::*,
 };
 use holochain_types::{
-    app::{AppId, InstalledApp, InstalledCell, MembraneProof},
+    app::{
+        AppId, AppInfo, AppRunState, AppStatus, CellInfo, DisabledAppReason, InstalledApp,
+        InstalledCell, MembraneProof, PausedAppReason,
+    },
     cell::CellId,
     dna::{wasm::DnaWasmHashed, DnaFile},
 };
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tracing::*;
 
 pub use builder::*;
-use futures::future::{self, TryFutureExt};
+use futures::{
+    future::{self, TryFutureExt},
+    stream::StreamExt,
+};
 use holo_hash::DnaHash;
 
 #[cfg(test)]
@@ -87,6 +121,19 @@ where
     _state: CellState,
 }
 
+/// How long a graceful shutdown waits for in-flight work before giving up on it
+pub const DEFAULT_SHUTDOWN_DEADLINE: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How often to check whether the Cells' queues have been flushed
+const FLUSH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// How long to wait for lair to unlock with a passphrase before deciding
+/// the passphrase is wrong
+const KEYSTORE_UNLOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How often to try starting the Cells of paused apps again
+const PAUSED_APP_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 pub type StopBroadcaster = tokio::sync::broadcast::Sender<()>;
 pub type StopReceiver = tokio::sync::broadcast::Receiver<()>;
 
@@ -110,13 +157,17 @@ where
 
     /// Set to true when `conductor.shutdown()` has been called, so that other
     /// tasks can check on the shutdown status
-    shutting_down: bool,
+    shutting_down: AtomicBool,
 
     /// The admin websocket ports this conductor has open.
     /// This exists so that we can run tests and bind to port 0, and find out
     /// the dynamically allocated port later.
     admin_websocket_ports: Vec<u16>,
 
+    /// How many admin interfaces the config asked for, so readiness
+    /// checks can tell whether they have all been bound
+    configured_admin_interfaces: usize,
+
     /// Channel on which to send info about tasks we want to manage
     managed_task_add_sender: mpsc::Sender<ManagedTaskAdd>,
 
@@ -133,11 +184,55 @@ where
     /// Access to private keys for signing and encryption.
     keystore: KeystoreSender,
 
+    /// Unlocks the keystore. While it's locked Cells aren't started
+    /// and app interfaces refuse requests.
+    unlock_passphrase: UnlockPassphrase,
+
     /// The root environment directory where all environments are created
     root_env_dir: EnvironmentRootPath,
 
     /// Handle to the network actor.
     holochain_p2p: holochain_p2p::HolochainP2pRef,
+
+    /// Signals emitted by Cells, which app interfaces and webhooks subscribe to
+    signal_broadcaster: broadcast::Sender<CellSignal>,
+
+    /// Which apps' signals each app interface receives
+    signal_routes: SignalRoutes,
+
+    /// Problems the conductor recovered from, for anyone watching its health
+    health_broadcaster: broadcast::Sender<HealthEvent>,
+
+    /// When Cells give up on ops whose dependencies can't be found
+    unresolved_dependency_policy: UnresolvedDependencyPolicy,
+
+    /// The resources each app's Cells may use. Apps without a quota are unbounded.
+    app_quotas: HashMap<AppId, AppQuota>,
+
+    /// What the last failure to start each active app's Cells left it as,
+    /// until its Cells are running
+    app_start_failures: HashMap<AppId, AppRunState>,
+
+    /// How much data may cross the wasm boundary on each call
+    wasm_io_limits: WasmIoLimits,
+
+    /// How many times a zome call is re-run after losing a race to commit
+    zome_call_retries: u32,
+
+    /// How often each Cell's autonomic processes run
+    autonomic_schedule: AutonomicSchedule,
+
+    /// How many Cells run genesis at once while an app is being installed
+    max_concurrent_genesis: usize,
+
+    /// Shares zome call execution fairly between apps
+    zome_call_scheduler: ZomeCallScheduler,
+
+    /// Where Cells get the time from
+    clock: Clock,
+
+    /// Where zome calls get random bytes from
+    entropy: Entropy,
 }
 
 impl Conductor {
@@ -179,13 +274,46 @@ where
     /// A gate to put at the top of public functions to ensure that work is not
     /// attempted after a shutdown has been issued
     pub(super) fn check_running(&self) -> ConductorResult<()> {
-        if self.shutting_down {
+        if self.shutting_down.load(Ordering::SeqCst) {
             Err(ConductorError::ShuttingDown)
         } else {
             Ok(())
         }
     }
 
+    /// Whether the keystore is still waiting for its passphrase
+    pub(super) fn is_keystore_locked(&self) -> bool {
+        self.unlock_passphrase.is_locked()
+    }
+
+    /// A gate to put at the top of functions which need the keystore
+    pub(super) fn check_keystore_unlocked(&self) -> ConductorResult<()> {
+        if self.is_keystore_locked() {
+            Err(ConductorError::KeystoreLocked)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Give the keystore its passphrase, then check lair unlocks with it.
+    /// A passphrase which lair doesn't accept in time is taken back,
+    /// leaving the keystore locked so the passphrase can be given again.
+    pub(super) async fn unlock_keystore(&self, passphrase: String) -> ConductorResult<()> {
+        if !self.is_keystore_locked() {
+            return Err(ConductorError::KeystoreAlreadyUnlocked);
+        }
+        self.unlock_passphrase.set(passphrase);
+        let unlocked =
+            tokio::time::timeout(KEYSTORE_UNLOCK_TIMEOUT, self.keystore.check_unlocked()).await;
+        let error = match unlocked {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "timed out waiting for the keystore".to_string(),
+        };
+        self.unlock_passphrase.clear();
+        Err(ConductorError::KeystoreUnlockFailed(error))
+    }
+
     pub(super) fn dna_store(&self) -> &DS {
         &self.dna_store
     }
@@ -194,8 +322,8 @@ where
         &mut self.dna_store
     }
 
-    pub(super) fn shutdown(&mut self) {
-        self.shutting_down = true;
+    pub(super) fn shutdown(&self) {
+        self.stop_accepting_requests();
         self.managed_task_stop_broadcaster
             .send(())
             .map(|_| ())
@@ -204,6 +332,58 @@ where
             })
     }
 
+    /// Refuse any further requests, without stopping anything yet
+    pub(super) fn stop_accepting_requests(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    /// Trigger every Cell's integration and publish workflows, then wait
+    /// until all of the Cells' queue consumers have finished everything
+    /// they were triggered to do, or until the deadline passes.
+    pub(super) async fn flush_cell_queues(&self, deadline: tokio::time::Instant) {
+        for item in self.cells.values() {
+            let mut triggers = item.cell.triggers().clone();
+            triggers.trigger(QueueTrigger::IntegrateDhtOps);
+            triggers.trigger(QueueTrigger::PublishDhtOps);
+        }
+        let activity = || -> usize {
+            self.cells
+                .values()
+                .map(|item| item.cell.triggers().activity())
+                .sum()
+        };
+        loop {
+            let before = activity();
+            if self
+                .cells
+                .values()
+                .all(|item| item.cell.triggers().all_idle())
+                && activity() == before
+            {
+                return;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                warn!("Cell queues were not flushed before the shutdown deadline");
+                return;
+            }
+            tokio::time::delay_for(FLUSH_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Sync every environment to disk and close it
+    pub(super) fn close_environments(&self) {
+        let envs = self
+            .cells
+            .values()
+            .map(|item| item.cell.env())
+            .chain(vec![&self.env, &self.wasm_env]);
+        for env in envs {
+            if let Err(e) = env.close() {
+                error!(path = ?env.path(), ?e, "Couldn't close environment");
+            }
+        }
+    }
+
     pub(super) fn take_shutdown_handle(&mut self) -> Option<TaskManagerRunHandle> {
         self.task_manager_run_handle.take()
     }
@@ -222,8 +402,8 @@ where
         let stop_tx = self.managed_task_stop_broadcaster.clone();
 
         // Closure to process each admin config item
-        let spawn_from_config = |AdminInterfaceConfig { driver, .. }| {
-            let admin_api = admin_api.clone();
+        let spawn_from_config = |AdminInterfaceConfig { driver, tokens }| {
+            let admin_api = admin_api.clone().with_tokens(tokens);
             let stop_tx = stop_tx.clone();
             async move {
                 match driver {
@@ -237,6 +417,9 @@ where
                         )?;
                         InterfaceResult::Ok((port, handle))
                     }
+                    driver @ InterfaceDriver::Http { .. } => {
+                        Err(InterfaceError::UnsupportedDriver(driver))
+                    }
                 }
             }
         };
@@ -285,48 +468,183 @@ where
     pub(super) async fn add_app_interface_via_handle(
         &mut self,
         port: u16,
+        app_ids: Option<Vec<AppId>>,
         handle: ConductorHandle,
     ) -> ConductorResult<u16> {
         let app_api = RealAppInterfaceApi::new(handle);
-        let (signal_broadcaster, _r) = tokio::sync::broadcast::channel(SIGNAL_BUFFER_SIZE);
+        let signal_broadcaster = self.signal_broadcaster.clone();
         let stop_rx = self.managed_task_stop_broadcaster.subscribe();
-        let (port, task) = spawn_app_interface_task(port, app_api, signal_broadcaster, stop_rx)
-            .await
-            .map_err(Box::new)?;
+        let (port, task) = spawn_app_interface_task(
+            port,
+            app_api,
+            signal_broadcaster,
+            self.signal_routes.clone(),
+            stop_rx,
+        )
+        .await
+        .map_err(Box::new)?;
+        self.signal_routes.add_interface(port, app_ids);
         // TODO: RELIABILITY: Handle this task by restating it if it fails and log the error
         self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
         Ok(port)
     }
 
+    /// Change which apps' signals the app interface on a port receives
+    pub(super) fn bind_app_interface(
+        &self,
+        port: u16,
+        app_ids: Option<Vec<AppId>>,
+    ) -> ConductorResult<()> {
+        if self.signal_routes.bind(port, app_ids) {
+            Ok(())
+        } else {
+            Err(ConductorError::AppInterfaceMissing(port))
+        }
+    }
+
+    /// Which apps' signals each app interface receives
+    pub(super) fn list_app_interface_bindings(&self) -> Vec<AppInterfaceBinding> {
+        self.signal_routes.bindings()
+    }
+
+    /// Add an App interface which serves JSON over HTTP rather than a websocket
+    #[cfg(feature = "http_interface")]
+    pub(super) async fn add_http_app_interface_via_handle(
+        &mut self,
+        port: u16,
+        handle: ConductorHandle,
+    ) -> ConductorResult<u16> {
+        let app_api = RealAppInterfaceApi::new(handle);
+        let stop_rx = self.managed_task_stop_broadcaster.subscribe();
+        let (port, task) =
+            super::interface::http::spawn_http_app_interface_task(port, app_api, stop_rx)
+                .await
+                .map_err(Box::new)?;
+        self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
+        Ok(port)
+    }
+
+    /// HTTP App interfaces aren't available without the `http_interface` feature
+    #[cfg(not(feature = "http_interface"))]
+    pub(super) async fn add_http_app_interface_via_handle(
+        &mut self,
+        port: u16,
+        _handle: ConductorHandle,
+    ) -> ConductorResult<u16> {
+        let driver = InterfaceDriver::Http { port };
+        Err(Box::new(InterfaceError::UnsupportedDriver(driver)).into())
+    }
+
+    /// Forward the signals emitted by Cells to a webhook
+    #[cfg(feature = "signal_webhooks")]
+    pub(super) async fn add_signal_webhook(
+        &mut self,
+        config: SignalWebhookConfig,
+    ) -> ConductorResult<()> {
+        let task = super::interface::webhook::spawn_signal_webhook_task(
+            config,
+            self.signal_broadcaster.subscribe(),
+            self.managed_task_stop_broadcaster.subscribe(),
+        )
+        .map_err(Box::new)?;
+        self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
+        Ok(())
+    }
+
+    /// Signal webhooks aren't available without the `signal_webhooks` feature
+    #[cfg(not(feature = "signal_webhooks"))]
+    pub(super) async fn add_signal_webhook(
+        &mut self,
+        config: SignalWebhookConfig,
+    ) -> ConductorResult<()> {
+        Err(ConductorError::ConfigError(format!(
+            "Can't forward signals to {}: this conductor was built without the `signal_webhooks` feature",
+            config.url
+        )))
+    }
+
+    /// Serve liveness and readiness probes over HTTP
+    #[cfg(feature = "http_interface")]
+    pub(super) async fn add_health_check_via_handle(
+        &mut self,
+        config: HealthCheckConfig,
+        handle: ConductorHandle,
+    ) -> ConductorResult<u16> {
+        let stop_rx = self.managed_task_stop_broadcaster.subscribe();
+        let (port, task) =
+            super::interface::health_check::spawn_health_check_task(config.port, handle, stop_rx)
+                .await
+                .map_err(Box::new)?;
+        self.manage_task(ManagedTaskAdd::dont_handle(task)).await?;
+        Ok(port)
+    }
+
+    /// HTTP health checks aren't available without the `http_interface` feature
+    #[cfg(not(feature = "http_interface"))]
+    pub(super) async fn add_health_check_via_handle(
+        &mut self,
+        config: HealthCheckConfig,
+        _handle: ConductorHandle,
+    ) -> ConductorResult<u16> {
+        let driver = InterfaceDriver::Http { port: config.port };
+        Err(Box::new(InterfaceError::UnsupportedDriver(driver)).into())
+    }
+
+    /// A sender for the signals forwarded to app interfaces and webhooks
+    pub(super) fn signal_broadcaster(&self) -> broadcast::Sender<CellSignal> {
+        self.signal_broadcaster.clone()
+    }
+
+    /// A sender for health events, which can be subscribed to
+    pub(super) fn health_broadcaster(&self) -> broadcast::Sender<HealthEvent> {
+        self.health_broadcaster.clone()
+    }
+
     /// Perform Genesis on the source chains for each of the specified CellIds.
+    /// Up to `max_concurrent_genesis` Cells run genesis at once, and each is
+    /// checked to have a usable source chain afterwards.
     ///
-    /// If genesis fails for any cell, this entire function fails, and all other
-    /// partial or complete successes are rolled back.
+    /// If genesis or the check fails for any cell, this entire function fails,
+    /// and all other partial or complete successes are rolled back.
     pub(super) async fn genesis_cells(
         &self,
         cell_ids_with_proofs: Vec<(CellId, Option<MembraneProof>)>,
         conductor_handle: ConductorHandle,
     ) -> ConductorResult<()> {
         let root_env_dir = std::path::PathBuf::from(self.root_env_dir.clone());
-        let keystore = self.keystore.clone();
 
         let cells_tasks = cell_ids_with_proofs.into_iter().map(|(cell_id, proof)| {
             let root_env_dir = root_env_dir.clone();
             let keystore = self.keystore.clone();
             let conductor_handle = conductor_handle.clone();
             let cell_id_inner = cell_id.clone();
+            let clock = self.clock.clone();
             tokio::spawn(async move {
                 let env = EnvironmentWrite::new(
                     &root_env_dir,
                     EnvironmentKind::Cell(cell_id_inner.clone()),
                     keystore.clone(),
                 )?;
-                Cell::genesis(cell_id_inner, conductor_handle, env, proof).await
+                Cell::genesis(
+                    cell_id_inner.clone(),
+                    conductor_handle,
+                    env.clone(),
+                    proof,
+                    clock,
+                )
+                .await?;
+                if let Err(e) = Cell::check_genesis(&cell_id_inner, &env) {
+                    env.remove().await?;
+                    return Err(e);
+                }
+                Ok::<_, CellError>(())
             })
             .map_err(CellError::from)
             .and_then(|result| async move { result.map(|_| cell_id) })
         });
-        let (success, errors): (Vec<_>, Vec<_>) = futures::future::join_all(cells_tasks)
+        let (success, errors): (Vec<_>, Vec<_>) = futures::stream::iter(cells_tasks)
+            .buffer_unordered(self.max_concurrent_genesis)
+            .collect::<Vec<_>>()
             .await
             .into_iter()
             .partition(Result::is_ok);
@@ -336,14 +654,7 @@ where
 
         // If there was errors, cleanup and return the errors
         if !errors.is_empty() {
-            for cell_id in success {
-                let env = EnvironmentWrite::new(
-                    &root_env_dir,
-                    EnvironmentKind::Cell(cell_id),
-                    keystore.clone(),
-                )?;
-                env.remove().await?;
-            }
+            self.remove_cell_envs(success).await?;
 
             // match needed to avoid Debug requirement on unwrap_err
             let errors = errors
@@ -361,6 +672,24 @@ where
         }
     }
 
+    /// Delete the environments of Cells which have been through genesis,
+    /// when the app they were created for couldn't be installed
+    pub(super) async fn remove_cell_envs(
+        &self,
+        cell_ids: impl IntoIterator<Item = CellId>,
+    ) -> ConductorResult<()> {
+        let root_env_dir = std::path::PathBuf::from(self.root_env_dir.clone());
+        for cell_id in cell_ids {
+            let env = EnvironmentWrite::new(
+                &root_env_dir,
+                EnvironmentKind::Cell(cell_id),
+                self.keystore.clone(),
+            )?;
+            env.remove().await?;
+        }
+        Ok(())
+    }
+
     /// Create Cells for each CellId marked active in the ConductorState db
     pub(super) async fn create_active_app_cells(
         &self,
@@ -386,6 +715,9 @@ where
 
                     // Task that creates the cells
                     async move {
+                        let quota = self.app_quotas.get(&app_id).cloned().unwrap_or_default();
+                        let zome_calls = &self.zome_call_scheduler.for_app(app_id.clone());
+
                         // Only create cells not already created
                         let cells_to_create = cell_ids
                             .filter(|cell_id| !self.cells.contains_key(cell_id))
@@ -395,6 +727,7 @@ where
                                     root_env_dir.clone(),
                                     keystore.clone(),
                                     conductor_handle.clone(),
+                                    quota.clone(),
                                 )
                             });
 
@@ -402,7 +735,7 @@ where
 
                         // Create each cell
                         let cells_tasks = cells_to_create.map(
-                            |(cell_id, dir, keystore, conductor_handle)| async move {
+                            |(cell_id, dir, keystore, conductor_handle, quota)| async move {
                                 let holochain_p2p_cell = self.holochain_p2p.to_cell(
                                     cell_id.dna_hash().clone(),
                                     cell_id.agent_pubkey().clone(),
@@ -420,6 +753,14 @@ where
                                     holochain_p2p_cell,
                                     self.managed_task_add_sender.clone(),
                                     self.managed_task_stop_broadcaster.clone(),
+                                    self.unresolved_dependency_policy.clone(),
+                                    quota,
+                                    zome_calls.clone(),
+                                    self.wasm_io_limits,
+                                    self.zome_call_retries,
+                                    self.clock.clone(),
+                                    self.entropy.clone(),
+                                    self.autonomic_schedule.clone(),
                                 )
                                 .await
                             },
@@ -455,7 +796,10 @@ where
                             Err(CreateAppError::Failed { app_id, errors })
                         } else {
                             // No errors so return the cells
-                            Ok(success.collect())
+                            let cells: Vec<_> = success.collect();
+                            self.signal_routes
+                                .add_cells(&app_id, cells.iter().map(|cell| cell.id().clone()));
+                            Ok(cells)
                         }
                     }
                 });
@@ -585,11 +929,14 @@ where
         Ok((dnas, defs))
     }
 
-    /// Remove cells from the cell map in the Conductor
-    pub(super) fn remove_cells(&mut self, cell_ids: Vec<CellId>) {
-        for cell_id in cell_ids {
-            self.cells.remove(&cell_id);
-        }
+    /// Remove cells from the cell map in the Conductor, returning the removed cells
+    pub(super) fn remove_cells(&mut self, cell_ids: Vec<CellId>) -> Vec<Cell> {
+        self.signal_routes.remove_cells(&cell_ids);
+        cell_ids
+            .into_iter()
+            .filter_map(|cell_id| self.cells.remove(&cell_id))
+            .map(|item| item.cell)
+            .collect()
     }
 
     pub(super) async fn put_wasm(
@@ -601,7 +948,11 @@ where
         let dna_def_db = environ.get_db(&*holochain_state::db::DNA_DEF)?;
         let entry_def_db = environ.get_db(&*holochain_state::db::ENTRY_DEF)?;
 
-        let zome_defs = get_entry_defs(dna.clone())?;
+        // Getting the entry defs compiles the wasm, which blocks
+        let zome_defs = {
+            let dna = dna.clone();
+            tokio::task::spawn_blocking(move || get_entry_defs(dna)).await??
+        };
 
         let mut entry_def_buf = EntryDefBuf::new(environ.clone().into(), entry_def_db)?;
 
@@ -638,6 +989,145 @@ where
         Ok(self.cells.keys().cloned().collect())
     }
 
+    /// Installed apps with the given status, or all of them, by app id
+    pub(super) async fn list_apps(
+        &self,
+        status_filter: Option<AppStatus>,
+    ) -> ConductorResult<Vec<AppInfo>> {
+        let state = self.get_state().await?;
+        let mut apps: Vec<AppInfo> = state
+            .active_apps
+            .into_iter()
+            .map(|app| (app, AppStatus::Active))
+            .chain(
+                state
+                    .inactive_apps
+                    .into_iter()
+                    .map(|app| (app, AppStatus::Inactive)),
+            )
+            .filter(|(_, status)| status_filter.map_or(true, |filter| filter == *status))
+            .map(|((app_id, cells), status)| AppInfo {
+                run_state: self.app_run_state(&app_id, status, &cells),
+                app_id,
+                status,
+                cells: cells
+                    .into_iter()
+                    .map(|cell| {
+                        let running = self.cells.contains_key(cell.as_id());
+                        let (cell_id, cell_nick) = cell.into_inner();
+                        CellInfo {
+                            cell_nick,
+                            dna_hash_b64: cell_id.dna_hash().to_string(),
+                            agent_pub_key_b64: cell_id.agent_pubkey().to_string(),
+                            cell_id,
+                            running,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        apps.sort_by(|a, b| a.app_id.cmp(&b.app_id));
+        Ok(apps)
+    }
+
+    /// Whether an app's Cells are running, and why not
+    fn app_run_state(
+        &self,
+        app_id: &AppId,
+        status: AppStatus,
+        cells: &[InstalledCell],
+    ) -> AppRunState {
+        if status == AppStatus::Inactive {
+            AppRunState::Disabled(DisabledAppReason::Deactivated)
+        } else if cells
+            .iter()
+            .all(|cell| self.cells.contains_key(cell.as_id()))
+        {
+            AppRunState::Running
+        } else if self.is_keystore_locked() {
+            AppRunState::Paused(PausedAppReason::KeystoreLocked)
+        } else {
+            self.app_start_failures
+                .get(app_id)
+                .cloned()
+                .unwrap_or(AppRunState::Paused(PausedAppReason::Starting))
+        }
+    }
+
+    /// Remember why apps failed to start, and forget the failures of
+    /// apps which are now running or have been deactivated
+    pub(super) async fn record_app_start_failures(
+        &mut self,
+        errors: &[CreateAppError],
+    ) -> ConductorResult<()> {
+        let not_running = self.apps_not_running().await?;
+        self.app_start_failures
+            .retain(|app_id, _| not_running.contains(app_id));
+        for error in errors {
+            self.app_start_failures
+                .insert(error.app_id().clone(), error.run_state());
+        }
+        Ok(())
+    }
+
+    /// Drop a Cell whose task has died and pause the apps it belongs to,
+    /// remembering why. Returns the Cell, unless it was already dropped
+    /// because another of its tasks died first, or the conductor is
+    /// shutting down and its tasks are expected to end.
+    pub(super) async fn pause_dead_cell(
+        &mut self,
+        cell_id: &CellId,
+        error: String,
+    ) -> ConductorResult<Option<Cell>> {
+        if self.check_running().is_err() {
+            return Ok(None);
+        }
+        let item = match self.cells.remove(cell_id) {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        error!(?cell_id, %error, "A Cell's task died, pausing its apps");
+        let run_state = AppRunState::Paused(PausedAppReason::Error(format!(
+            "Cell {} died: {}",
+            cell_id, error
+        )));
+        for (app_id, cells) in self.get_state().await?.active_apps {
+            if cells.iter().any(|cell| cell.as_id() == cell_id) {
+                self.app_start_failures.insert(app_id, run_state.clone());
+            }
+        }
+        Ok(Some(item.cell))
+    }
+
+    /// Active apps with Cells which haven't been created
+    pub(super) async fn apps_not_running(&self) -> ConductorResult<Vec<AppId>> {
+        Ok(self
+            .get_state()
+            .await?
+            .active_apps
+            .into_iter()
+            .filter(|(_, cells)| {
+                cells
+                    .iter()
+                    .any(|cell| !self.cells.contains_key(cell.as_id()))
+            })
+            .map(|(app_id, _)| app_id)
+            .collect())
+    }
+
+    /// How many configured admin interfaces haven't been bound
+    pub(super) fn unbound_admin_interfaces(&self) -> usize {
+        self.configured_admin_interfaces
+            .saturating_sub(self.admin_websocket_ports.len())
+    }
+
+    pub(super) fn cell_envs(&self) -> Vec<(CellId, EnvironmentWrite)> {
+        self.cells
+            .iter()
+            .map(|(id, item)| (id.clone(), item.cell.env().clone()))
+            .collect()
+    }
+
     pub(super) async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String> {
         let cell = self.cell_by_id(cell_id)?;
         let arc = cell.env();
@@ -645,6 +1135,93 @@ where
         Ok(source_chain.dump_as_json().await?)
     }
 
+    pub(super) async fn dump_cell_limbo(
+        &self,
+        cell_id: &CellId,
+    ) -> ConductorApiResult<Vec<LimboOpInfo>> {
+        let cell = self.cell_by_id(cell_id)?;
+        Ok(dump_limbo(cell.env())?)
+    }
+
+    pub(super) async fn export_dht_snapshot(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<usize> {
+        let snapshot = self.cell_by_id(cell_id)?.export_dht_snapshot().await?;
+        let count = snapshot.ops.len();
+        let data = snapshot.encode().map_err(SerializationError::from)?;
+        tokio::fs::write(path, data).await?;
+        Ok(count)
+    }
+
+    pub(super) async fn import_dht_snapshot(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<SnapshotImportReport> {
+        let cell = self.cell_by_id(cell_id)?;
+        let data = tokio::fs::read(path).await?;
+        let snapshot = DhtSnapshot::decode(data).map_err(SerializationError::from)?;
+        Ok(cell.import_dht_snapshot(snapshot).await?)
+    }
+
+    pub(super) async fn record_network_events(
+        &self,
+        cell_id: &CellId,
+        path: Option<PathBuf>,
+    ) -> ConductorApiResult<()> {
+        self.cell_by_id(cell_id)?;
+        self.holochain_p2p
+            .record_events(
+                cell_id.dna_hash().clone(),
+                cell_id.agent_pubkey().clone(),
+                path,
+            )
+            .await
+            .map_err(ConductorError::from)?;
+        Ok(())
+    }
+
+    pub(super) async fn replay_network_events(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<usize> {
+        self.cell_by_id(cell_id)?;
+        Ok(self
+            .holochain_p2p
+            .replay_events(
+                cell_id.dna_hash().clone(),
+                cell_id.agent_pubkey().clone(),
+                path,
+            )
+            .await
+            .map_err(ConductorError::from)?)
+    }
+
+    pub(super) async fn peer_reputations(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(AgentPubKey, f64)>> {
+        Ok(self
+            .holochain_p2p
+            .peer_reputations(dna_hash.clone())
+            .await
+            .map_err(ConductorError::from)?)
+    }
+
+    pub(super) async fn gossip_rounds(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(AgentPubKey, Vec<GossipRound>)>> {
+        Ok(self
+            .holochain_p2p
+            .gossip_rounds(dna_hash.clone())
+            .await
+            .map_err(ConductorError::from)?)
+    }
+
     #[cfg(test)]
     pub(super) async fn get_state_from_handle(&self) -> ConductorResult<ConductorState> {
         self.get_state().await
@@ -671,20 +1248,40 @@ where
         let (task_tx, task_manager_run_handle) = spawn_task_manager();
         let task_manager_run_handle = Some(task_manager_run_handle);
         let (stop_tx, _) = tokio::sync::broadcast::channel::<()>(1);
+        let (signal_broadcaster, _) = tokio::sync::broadcast::channel(SIGNAL_BUFFER_SIZE);
+        let (health_broadcaster, _) = tokio::sync::broadcast::channel(HEALTH_EVENT_BUFFER_SIZE);
         Ok(Self {
             env,
             wasm_env,
             state_db: KvStore::new(db),
             cells: HashMap::new(),
-            shutting_down: false,
+            shutting_down: AtomicBool::new(false),
             managed_task_add_sender: task_tx,
             managed_task_stop_broadcaster: stop_tx,
             task_manager_run_handle,
             admin_websocket_ports: Vec::new(),
+            configured_admin_interfaces: 0,
             dna_store,
             keystore,
+            unlock_passphrase: UnlockPassphrase::blank(),
             root_env_dir,
             holochain_p2p,
+            signal_broadcaster,
+            signal_routes: SignalRoutes::default(),
+            health_broadcaster,
+            unresolved_dependency_policy: Default::default(),
+            app_quotas: HashMap::new(),
+            app_start_failures: HashMap::new(),
+            wasm_io_limits: Default::default(),
+            zome_call_retries: DEFAULT_ZOME_CALL_RETRIES,
+            autonomic_schedule: Default::default(),
+            max_concurrent_genesis: DEFAULT_MAX_CONCURRENT_GENESIS,
+            zome_call_scheduler: ZomeCallScheduler::new(
+                DEFAULT_MAX_CONCURRENT_ZOME_CALLS,
+                HashMap::new(),
+            ),
+            clock: Clock::default(),
+            entropy: Entropy::default(),
         })
     }
 
@@ -728,7 +1325,11 @@ pub type ConductorStateDb = KvStore<UnitDbKey, ConductorState>;
 mod builder {
 
     use super::*;
-    use crate::conductor::{dna_store::RealDnaStore, ConductorHandle};
+    use crate::conductor::{
+        blocking_detector::{spawn_blocking_detector, DEBUG_FAILURE_THRESHOLD},
+        dna_store::RealDnaStore,
+        ConductorHandle,
+    };
     use holochain_state::{env::EnvironmentKind, test_utils::TestEnvironment};
 
     /// A configurable Builder for Conductor and sometimes ConductorHandle
@@ -737,6 +1338,8 @@ mod builder {
         config: ConductorConfig,
         dna_store: DS,
         keystore: Option<KeystoreSender>,
+        clock: Clock,
+        entropy: Entropy,
         #[cfg(test)]
         state: Option<ConductorState>,
         #[cfg(test)]
@@ -784,6 +1387,8 @@ mod builder {
 
             let _ = holochain_crypto::crypto_init_sodium();
 
+            // Only lair is protected by a passphrase
+            let mut unlock_passphrase = UnlockPassphrase::blank();
             let keystore = if let Some(keystore) = self.keystore {
                 keystore
             } else if self.config.use_dangerous_test_keystore {
@@ -799,7 +1404,13 @@ mod builder {
                     .unwrap();
                 keystore
             } else {
-                spawn_lair_keystore(None).await?
+                if let Some(service) = &self.config.passphrase_service {
+                    unlock_passphrase = match request_passphrase(service).await? {
+                        Some(passphrase) => UnlockPassphrase::new(passphrase),
+                        None => UnlockPassphrase::locked(),
+                    };
+                }
+                spawn_lair_keystore(None, unlock_passphrase.clone()).await?
             };
             let env_path = self.config.environment_path.clone();
 
@@ -816,12 +1427,16 @@ mod builder {
             let state = self.state;
 
             let Self {
-                dna_store, config, ..
+                dna_store,
+                config,
+                clock,
+                entropy,
+                ..
             } = self;
 
             let (holochain_p2p, p2p_evt) = holochain_p2p::spawn_holochain_p2p().await?;
 
-            let conductor = Conductor::new(
+            let mut conductor = Conductor::new(
                 environment,
                 wasm_environment,
                 dna_store,
@@ -830,21 +1445,64 @@ mod builder {
                 holochain_p2p,
             )
             .await?;
+            conductor.unlock_passphrase = unlock_passphrase;
 
             #[cfg(test)]
             let conductor = Self::update_fake_state(state, conductor).await?;
 
-            Self::finish(conductor, config, p2p_evt).await
+            Self::finish(conductor, config, clock, entropy, p2p_evt).await
         }
 
         async fn finish(
-            conductor: Conductor<DS>,
+            mut conductor: Conductor<DS>,
             conductor_config: ConductorConfig,
+            clock: Clock,
+            entropy: Entropy,
             p2p_evt: holochain_p2p::event::HolochainP2pEventReceiver,
         ) -> ConductorResult<ConductorHandle> {
+            conductor.clock = clock;
+            conductor.entropy = entropy;
+            conductor.unresolved_dependency_policy = conductor_config.unresolved_dependencies;
+            conductor.app_quotas = conductor_config.app_quotas;
+            conductor.wasm_io_limits = conductor_config.wasm_io_limits;
+            conductor.zome_call_retries = conductor_config
+                .zome_call_retries
+                .unwrap_or(DEFAULT_ZOME_CALL_RETRIES);
+            conductor.autonomic_schedule = conductor_config.autonomic;
+            conductor.max_concurrent_genesis = conductor_config
+                .max_concurrent_genesis
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_GENESIS)
+                .max(1);
+            conductor.zome_call_scheduler = ZomeCallScheduler::new(
+                conductor_config
+                    .max_concurrent_zome_calls
+                    .unwrap_or(DEFAULT_MAX_CONCURRENT_ZOME_CALLS),
+                conductor_config.zome_call_weights,
+            );
+            conductor.configured_admin_interfaces = conductor_config
+                .admin_interfaces
+                .as_ref()
+                .map(Vec::len)
+                .unwrap_or(0);
+
+            // Debug builds, and so tests, fail if the worker threads are blocked
+            let runtime_config = conductor_config.tokio_runtime.clone().unwrap_or_default();
+            if let Some(threshold) = runtime_config.blocking_warning_threshold() {
+                let fail_after = if cfg!(debug_assertions) {
+                    Some(DEBUG_FAILURE_THRESHOLD)
+                } else {
+                    None
+                };
+                spawn_blocking_detector(threshold, fail_after);
+            }
+
             // Get data before handle
             let keystore = conductor.keystore.clone();
             let holochain_p2p = conductor.holochain_p2p.clone();
+            // The retry task needs the handle, so it's handed to the
+            // TaskManager through its own sender once the handle exists
+            let mut managed_task_add_sender = conductor.managed_task_add_sender.clone();
+            let retry_stop_rx = conductor.managed_task_stop_broadcaster.subscribe();
 
             // Create handle
             let handle: ConductorHandle = Arc::new(ConductorHandleImpl {
@@ -853,8 +1511,19 @@ mod builder {
                 holochain_p2p,
             });
 
+            // Probes are served first so that orchestrators can see
+            // the conductor is live while it is still starting up
+            if let Some(config) = conductor_config.health_check {
+                let port = handle.clone().add_health_check(config).await?;
+                info!(port, "Serving health checks");
+            }
+
             handle.add_dnas().await?;
 
+            if handle.is_keystore_locked().await {
+                warn!("The keystore is locked, Cells will start once it's unlocked over an admin interface");
+            }
+
             let cell_startup_errors = handle.clone().setup_cells().await?;
 
             // TODO: This should probably be emitted over the admin interface
@@ -865,12 +1534,32 @@ mod builder {
                 );
             }
 
+            if conductor_config.startup_integrity_check {
+                for (cell_id, report) in handle.check_integrity().await? {
+                    if report.is_clean() {
+                        info!(?cell_id, ?report, "Vault integrity check passed");
+                    } else {
+                        warn!(?cell_id, ?report, "Quarantined corrupt vault data");
+                    }
+                }
+            }
+
             // Create admin interfaces
             if let Some(configs) = conductor_config.admin_interfaces {
                 handle.clone().add_admin_interfaces(configs).await?;
             }
 
+            for config in conductor_config.signal_webhooks {
+                handle.add_signal_webhook(config).await?;
+            }
+
             tokio::task::spawn(p2p_event_task(p2p_evt, handle.clone()));
+            let retry_task =
+                tokio::task::spawn(paused_app_retry_task(handle.clone(), retry_stop_rx));
+            managed_task_add_sender
+                .send(ManagedTaskAdd::dont_handle(retry_task))
+                .await
+                .map_err(|e| ConductorError::SubmitTaskError(format!("{}", e)))?;
 
             Ok(handle)
         }
@@ -882,6 +1571,21 @@ mod builder {
             self
         }
 
+        /// Take the time from this clock instead of the system's, so tests
+        /// can control timestamps, publish intervals and validation backoff
+        /// by advancing a clone of it
+        pub fn with_clock(mut self, clock: Clock) -> Self {
+            self.clock = clock;
+            self
+        }
+
+        /// Give zome calls random bytes from this generator instead of the
+        /// system's, so tests get the same bytes on every run
+        pub fn with_entropy(mut self, entropy: Entropy) -> Self {
+            self.entropy = entropy;
+            self
+        }
+
         #[cfg(test)]
         /// Sets some fake conductor state for tests
         pub fn fake_state(mut self, state: ConductorState) -> Self {
@@ -933,7 +1637,7 @@ mod builder {
             #[cfg(test)]
             let conductor = Self::update_fake_state(self.state, conductor).await?;
 
-            Self::finish(conductor, self.config, p2p_evt).await
+            Self::finish(conductor, self.config, self.clock, self.entropy, p2p_evt).await
         }
     }
 }
@@ -945,6 +1649,16 @@ async fn p2p_event_task(
     use tokio::stream::StreamExt;
     while let Some(evt) = p2p_evt.next().await {
         let cell_id = CellId::new(evt.dna_hash().clone(), evt.as_to_agent().clone());
+        if let holochain_p2p::event::HolochainP2pEvent::NetworkRestarted { reason, .. } = &evt {
+            // it doesn't matter if nobody is watching
+            let _ = handle
+                .health_broadcaster()
+                .await
+                .send(HealthEvent::NetworkRestarted {
+                    cell_id: cell_id.clone(),
+                    reason: reason.clone(),
+                });
+        }
         if let Err(e) = handle.dispatch_holochain_p2p_event(&cell_id, evt).await {
             tracing::error!(
                 message = "error dispatching network event",
@@ -955,13 +1669,55 @@ async fn p2p_event_task(
     tracing::warn!("p2p_event_task has ended");
 }
 
+/// Keep trying to start the Cells of paused apps, e.g. those which failed
+/// because the network couldn't be bound or whose Cells died, until the
+/// conductor shuts down
+async fn paused_app_retry_task(
+    handle: ConductorHandle,
+    mut stop: broadcast::Receiver<()>,
+) -> ManagedTaskResult {
+    loop {
+        tokio::select! {
+            _ = stop.recv() => break,
+            _ = tokio::time::delay_for(PAUSED_APP_RETRY_INTERVAL) => (),
+        }
+        if handle.check_running().await.is_err() {
+            break;
+        }
+        let any_paused = match handle.list_apps(Some(AppStatus::Active)).await {
+            Ok(apps) => apps
+                .iter()
+                .any(|app| matches!(app.run_state, AppRunState::Paused(_))),
+            Err(e) => {
+                warn!(error = ?e, "Couldn't list apps to retry the paused ones");
+                continue;
+            }
+        };
+        if any_paused {
+            match handle.clone().setup_cells().await {
+                Ok(errors) if !errors.is_empty() => {
+                    debug!(?errors, "Paused apps still failed to start")
+                }
+                Ok(_) => info!("Paused apps have started"),
+                Err(e) => warn!(error = ?e, "Couldn't retry the paused apps"),
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use super::{Conductor, ConductorState};
     use crate::conductor::dna_store::MockDnaStore;
+    use crate::test_utils::setup_app;
+    use holochain_serialized_bytes::SerializedBytes;
     use holochain_state::test_utils::{test_conductor_env, test_wasm_env, TestEnvironment};
-    use holochain_types::test_utils::fake_cell_id;
+    use holochain_types::dna::{DnaDef, DnaFile};
+    use holochain_types::test_utils::{fake_agent_pubkey_1, fake_cell_id};
+    use holochain_wasm_test_utils::TestWasm;
+    use std::convert::TryFrom;
 
     #[tokio::test(threaded_scheduler)]
     async fn can_update_state() {
@@ -1012,6 +1768,50 @@ pub mod tests {
         );
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn locked_keystore_is_unlocked_once() {
+        let TestEnvironment {
+            env: environment,
+            tmpdir,
+        } = test_conductor_env();
+        let TestEnvironment {
+            env: wasm_env,
+            tmpdir: _tmpdir,
+        } = test_wasm_env();
+        let keystore = environment.keystore().clone();
+        let (holochain_p2p, _p2p_evt) = holochain_p2p::spawn_holochain_p2p().await.unwrap();
+        let mut conductor = Conductor::new(
+            environment,
+            wasm_env,
+            MockDnaStore::new(),
+            keystore,
+            tmpdir.path().to_path_buf().into(),
+            holochain_p2p,
+        )
+        .await
+        .unwrap();
+        // test keystores aren't locked
+        assert!(!conductor.is_keystore_locked());
+
+        conductor.unlock_passphrase = UnlockPassphrase::locked();
+        assert!(conductor.is_keystore_locked());
+        assert!(matches!(
+            conductor.check_keystore_unlocked(),
+            Err(ConductorError::KeystoreLocked)
+        ));
+
+        conductor
+            .unlock_keystore("passphrase".into())
+            .await
+            .unwrap();
+        assert!(!conductor.is_keystore_locked());
+        assert!(conductor.check_keystore_unlocked().is_ok());
+        assert!(matches!(
+            conductor.unlock_keystore("passphrase".into()).await,
+            Err(ConductorError::KeystoreAlreadyUnlocked)
+        ));
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn can_set_fake_state() {
         let test_env = test_conductor_env();
@@ -1028,4 +1828,156 @@ pub mod tests {
             .unwrap();
         assert_eq!(state, conductor.get_state_from_handle().await.unwrap());
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn graceful_shutdown_refuses_requests() {
+        let test_env = test_conductor_env();
+        let _tmpdir = test_env.tmpdir.clone();
+        let TestEnvironment {
+            env: wasm_env,
+            tmpdir: _tmpdir,
+        } = test_wasm_env();
+        let conductor = ConductorBuilder::new()
+            .test(test_env, wasm_env)
+            .await
+            .unwrap();
+        conductor.check_running().await.unwrap();
+
+        conductor
+            .shutdown_gracefully(std::time::Duration::from_millis(100))
+            .await;
+        matches::assert_matches!(
+            conductor.check_running().await,
+            Err(ConductorError::ShuttingDown)
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn apps_without_cells_are_not_ready() {
+        let test_env = test_conductor_env();
+        let _tmpdir = test_env.tmpdir.clone();
+        let TestEnvironment {
+            env: wasm_env,
+            tmpdir: _tmpdir,
+        } = test_wasm_env();
+        // The Dna isn't installed, so the Cell can't be created
+        let installed_cell = InstalledCell::new(fake_cell_id(1), "handle".to_string());
+        let mut state = ConductorState::default();
+        state
+            .active_apps
+            .insert("fake app".to_string(), vec![installed_cell]);
+        let conductor = ConductorBuilder::new()
+            .fake_state(state)
+            .test(test_env, wasm_env)
+            .await
+            .unwrap();
+
+        let report = conductor.check_health().await;
+        assert!(report.live);
+        assert!(report.keystore_reachable);
+        assert_eq!(report.apps_not_running, vec!["fake app".to_string()]);
+        assert!(!report.is_ready());
+
+        // Retrying won't give the Cell a source chain
+        let apps = conductor.list_apps(None).await.unwrap();
+        matches::assert_matches!(
+            apps[0].run_state,
+            AppRunState::Disabled(DisabledAppReason::Error(_))
+        );
+
+        conductor.shutdown().await;
+        assert!(!conductor.check_health().await.live);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn dead_cells_pause_their_apps() {
+        let dna_file = DnaFile::new(
+            DnaDef {
+                name: "dead_cells_pause_their_apps".to_string(),
+                uuid: "6e1c4a0e-3b8e-4bd4-a39b-1b0a2d6a3c51".to_string(),
+                properties: SerializedBytes::try_from(()).unwrap(),
+                zomes: vec![TestWasm::Foo.into()].into(),
+            },
+            vec![TestWasm::Foo.into()],
+        )
+        .await
+        .unwrap();
+        let cell_id = CellId::new(dna_file.dna_hash().to_owned(), fake_agent_pubkey_1());
+        let installed_cell = InstalledCell::new(cell_id.clone(), "handle".into());
+
+        let mut dna_store = MockDnaStore::new();
+        dna_store.expect_get().return_const(Some(dna_file));
+        dna_store.expect_add_dnas::<Vec<_>>().return_const(());
+        dna_store.expect_add_entry_defs::<Vec<_>>().return_const(());
+
+        let (_tmpdir, _app_api, conductor) =
+            setup_app(vec![("test_app", vec![(installed_cell, None)])], dna_store).await;
+        let apps = conductor.list_apps(None).await.unwrap();
+        matches::assert_matches!(apps[0].run_state, AppRunState::Running);
+
+        conductor
+            .cell_died(&cell_id, "boom".to_string())
+            .await
+            .unwrap();
+        assert!(conductor.list_cell_ids().await.unwrap().is_empty());
+        let apps = conductor.list_apps(None).await.unwrap();
+        matches::assert_matches!(
+            &apps[0].run_state,
+            AppRunState::Paused(PausedAppReason::Error(e)) if e.contains("boom")
+        );
+        assert!(!apps[0].cells[0].running);
+
+        // Dying twice, e.g. when another of its tasks dies, is fine
+        conductor
+            .cell_died(&cell_id, "boom again".to_string())
+            .await
+            .unwrap();
+
+        // Retrying brings the Cell back
+        let errors = conductor.clone().setup_cells().await.unwrap();
+        assert!(errors.is_empty());
+        let apps = conductor.list_apps(None).await.unwrap();
+        matches::assert_matches!(apps[0].run_state, AppRunState::Running);
+
+        let shutdown = conductor.take_shutdown_handle().await.unwrap();
+        conductor.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn network_restarts_are_broadcast_as_health_events() {
+        use holochain_p2p::event::HolochainP2pEventSender;
+
+        let cell_id = fake_cell_id(1);
+        let (health_broadcaster, mut health_rx) = broadcast::channel(HEALTH_EVENT_BUFFER_SIZE);
+        let mut handle = MockConductorHandleT::new();
+        handle
+            .expect_health_broadcaster()
+            .returning(move || health_broadcaster.clone());
+        handle
+            .expect_dispatch_holochain_p2p_event()
+            .returning(|_, _| Ok(()));
+
+        let (mut p2p_send, p2p_evt) = futures::channel::mpsc::channel(1);
+        let task = tokio::task::spawn(p2p_event_task(p2p_evt, Arc::new(handle)));
+
+        // the mock handle drops the event without answering it
+        let _ = p2p_send
+            .network_restarted(
+                cell_id.dna_hash().clone(),
+                cell_id.agent_pubkey().clone(),
+                "boom".to_string(),
+            )
+            .await;
+        assert_eq!(
+            health_rx.recv().await.unwrap(),
+            HealthEvent::NetworkRestarted {
+                cell_id,
+                reason: "boom".to_string(),
+            }
+        );
+
+        drop(p2p_send);
+        task.await.unwrap();
+    }
 }
@@ -5,9 +5,12 @@ use serde::{Deserialize, Serialize};
 
 mod admin_interface_config;
 mod dpki_config;
+mod health_check_config;
+mod logger_config;
 mod network_config;
 mod passphrase_service_config;
-//mod logger_config;
+mod signal_webhook_config;
+mod tokio_runtime_config;
 //mod signal_config;
 use super::{
     error::{ConductorError, ConductorResult},
@@ -15,18 +18,29 @@ use super::{
 };
 
 pub use crate::conductor::interface::InterfaceDriver;
-pub use admin_interface_config::AdminInterfaceConfig;
+pub use crate::core::autonomic::AutonomicSchedule;
+pub use crate::core::quota::AppQuota;
+pub use crate::core::ribosome::wasm_io::WasmIoLimits;
+pub use crate::core::subconscious::UnresolvedDependencyPolicy;
+pub use admin_interface_config::{AdminInterfaceConfig, AdminScope, AdminToken};
 pub use dpki_config::DpkiConfig;
-//pub use logger_config::LoggerConfig;
+pub use health_check_config::HealthCheckConfig;
+pub use logger_config::LoggerConfig;
 pub use network_config::NetworkConfig;
 pub use passphrase_service_config::PassphraseServiceConfig;
+pub use signal_webhook_config::{SignalFilter, SignalWebhookConfig};
+pub use tokio_runtime_config::TokioRuntimeConfig;
 //pub use signal_config::SignalConfig;
-use std::path::Path;
+use holochain_types::app::AppId;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 // TODO change types from "stringly typed" to Url2
-/// All the config information for the conductor
+/// All the config information for the conductor.
+/// Unknown fields are an error, so a misspelled option isn't silently ignored.
 #[derive(Deserialize, Serialize, Default, Debug)]
 #[cfg_attr(test, derive(PartialEq))]
+#[serde(deny_unknown_fields)]
 pub struct ConductorConfig {
     /// The path to the LMDB environment for this conductor.
     /// If omitted, chooses a default path.
@@ -71,15 +85,76 @@ pub struct ConductorConfig {
 
     /// Setup admin interfaces to control this conductor through a websocket connection
     pub admin_interfaces: Option<Vec<AdminInterfaceConfig>>,
+
+    /// Re-hash and re-verify the contents of every Cell's vault on startup,
+    /// moving anything which fails into quarantine. This can take a while
+    /// for large vaults, so it is off by default.
+    #[serde(default)]
+    pub startup_integrity_check: bool,
+
+    /// Configures how logging should behave. Optional.
+    pub logger: Option<LoggerConfig>,
+
+    /// Configures the tokio runtime's threads. Optional.
+    pub tokio_runtime: Option<TokioRuntimeConfig>,
+
+    /// Serve liveness and readiness probes over HTTP. Optional.
+    #[serde(default)]
+    pub health_check: Option<HealthCheckConfig>,
+
+    /// HTTP endpoints to forward signals to, for external systems which
+    /// can't keep a websocket open to an app interface
+    #[serde(default)]
+    pub signal_webhooks: Vec<SignalWebhookConfig>,
+
+    /// When Cells give up on validating ops whose dependencies
+    /// can't be found. Defaults to a day.
+    #[serde(default)]
+    pub unresolved_dependencies: UnresolvedDependencyPolicy,
+
+    /// Bounds on the resources each installed app may use, by app id.
+    /// Apps without a quota are unbounded.
+    #[serde(default)]
+    pub app_quotas: HashMap<AppId, AppQuota>,
+
+    /// How much data may cross the wasm boundary on each call,
+    /// so a buggy zome can't exhaust the conductor's memory.
+    /// Defaults to 16MiB each way.
+    #[serde(default)]
+    pub wasm_io_limits: WasmIoLimits,
+
+    /// How many times a zome call is re-run when another call to the same
+    /// Cell moved the source chain head before it could commit.
+    /// Defaults to 3.
+    #[serde(default)]
+    pub zome_call_retries: Option<u32>,
+
+    /// How many Cells run genesis at once while an app is being installed.
+    /// Defaults to 4.
+    #[serde(default)]
+    pub max_concurrent_genesis: Option<usize>,
+
+    /// How many zome calls from app interfaces run at once. When more are
+    /// made they wait their turn, with the apps taking turns fairly.
+    /// Defaults to 32.
+    #[serde(default)]
+    pub max_concurrent_zome_calls: Option<usize>,
+
+    /// How many waiting zome calls each app may start in a row before the
+    /// next app's turn, by app id. Apps not listed get 1.
+    #[serde(default)]
+    pub zome_call_weights: HashMap<AppId, u32>,
+
+    /// How often each Cell runs its autonomic processes, such as
+    /// redundancy checks, cache pruning and arc resizing. Each interval is
+    /// in seconds, and 0 only runs the process when it is cued.
+    #[serde(default)]
+    pub autonomic: AutonomicSchedule,
     //
     //
     // /// Which signals to emit
     // TODO: it's an open question whether signal config is stateful or not, i.e. whether it belongs here.
     // pub signals: SignalConfig,
-    //
-    // /// Configures how logging should behave. Optional.
-    // TODO: it's an open question whether we want to keep any of the legacy LoggerConfig
-    // pub logger: LoggerConfig,
 }
 
 /// helper fnction function to load a `Config` from a toml string.
@@ -90,6 +165,47 @@ where
     toml::from_str::<T>(toml).map_err(ConductorError::DeserializationError)
 }
 
+/// The commented default config, with `{environment_path}` to fill in
+const DEFAULT_CONFIG_TEMPLATE: &str = include_str!("config/default_config.toml");
+
+/// Replace each `${NAME}` in a config with the value `lookup` gives for
+/// `NAME`, or each `${NAME:-fallback}` with `fallback` if it gives none.
+/// Comment lines are left alone.
+fn substitute_env_vars(
+    toml: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> ConductorResult<String> {
+    let mut substituted = Vec::new();
+    for (i, line) in toml.lines().enumerate() {
+        if line.trim_start().starts_with('#') {
+            substituted.push(line.to_string());
+            continue;
+        }
+        let error = |msg: String| ConductorError::ConfigError(format!("line {}: {}", i + 1, msg));
+        let mut out = String::with_capacity(line.len());
+        let mut rest = line;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| error("`${` is never closed with `}`".into()))?;
+            let (name, fallback) = match after[..end].find(":-") {
+                Some(split) => (&after[..split], Some(&after[split + 2..end])),
+                None => (&after[..end], None),
+            };
+            let value = lookup(name)
+                .or_else(|| fallback.map(String::from))
+                .ok_or_else(|| error(format!("environment variable `{}` is not set", name)))?;
+            out.push_str(&value);
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+        substituted.push(out);
+    }
+    Ok(substituted.join("\n"))
+}
+
 impl ConductorConfig {
     /// create a ConductorConfig struct from a toml file path
     pub fn load_toml(path: &Path) -> ConductorResult<ConductorConfig> {
@@ -99,13 +215,23 @@ impl ConductorConfig {
             }
             _ => err.into(),
         })?;
+        let config_toml = substitute_env_vars(&config_toml, |name| std::env::var(name).ok())?;
         config_from_toml(&config_toml)
     }
+
+    /// The default config as toml, with every option described and those
+    /// which are optional commented out
+    pub fn default_commented_toml() -> String {
+        let environment_path = PathBuf::from(ConductorConfig::default().environment_path);
+        let environment_path = toml::Value::String(environment_path.display().to_string());
+        DEFAULT_CONFIG_TEMPLATE.replace("{environment_path}", &environment_path.to_string())
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use holochain_types::observability::{FileLogConfig, OtlpConfig, RotationPeriod};
     use matches::assert_matches;
     use std::path::{Path, PathBuf};
     use url::Url;
@@ -128,6 +254,74 @@ pub mod tests {
         assert_matches!(result, Err(ConductorError::DeserializationError(_)));
     }
 
+    #[test]
+    fn test_config_unknown_field() {
+        let toml = r#"
+    environment_path = "/path/to/env"
+    zome_call_retrys = 5
+    "#;
+        let result: ConductorResult<ConductorConfig> = config_from_toml(toml);
+        let err = match result {
+            Err(ConductorError::DeserializationError(err)) => err,
+            other => panic!("expected an unknown field error, got {:?}", other),
+        };
+        assert!(err.to_string().contains("unknown field `zome_call_retrys`"));
+    }
+
+    #[test]
+    fn test_config_wrong_type_has_line() {
+        let toml = r#"
+    environment_path = "/path/to/env"
+    zome_call_retries = "five"
+    "#;
+        let result: ConductorResult<ConductorConfig> = config_from_toml(toml);
+        let err = match result {
+            Err(ConductorError::DeserializationError(err)) => err,
+            other => panic!("expected a type error, got {:?}", other),
+        };
+        assert_eq!(err.line_col().map(|(line, _)| line), Some(2));
+    }
+
+    #[test]
+    fn test_substitute_env_vars() {
+        let lookup = |name: &str| match name {
+            "ENV_DIR" => Some("/path/to/env".to_string()),
+            _ => None,
+        };
+        let toml = r#"environment_path = "${ENV_DIR}"
+    # port = ${UNSET}
+    zome_call_retries = ${RETRIES:-5}"#;
+        let result: ConductorConfig =
+            config_from_toml(&substitute_env_vars(toml, lookup).unwrap()).unwrap();
+        assert_eq!(
+            result.environment_path,
+            PathBuf::from("/path/to/env").into()
+        );
+        assert_eq!(result.zome_call_retries, Some(5));
+
+        let result = substitute_env_vars("a = 1\nb = \"${UNSET}\"", lookup);
+        assert_matches!(
+            result,
+            Err(ConductorError::ConfigError(msg)) if msg.starts_with("line 2:") && msg.contains("UNSET")
+        );
+    }
+
+    #[test]
+    fn test_default_commented_toml() {
+        let toml = ConductorConfig::default_commented_toml();
+        let result: ConductorConfig = config_from_toml(&toml).unwrap();
+        assert_eq!(result, ConductorConfig::default());
+
+        // Every example must still be a valid option once uncommented
+        let uncommented = toml
+            .lines()
+            .map(|line| line.trim_start_matches("# "))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let result: ConductorResult<ConductorConfig> = config_from_toml(&uncommented);
+        assert_matches!(result, Ok(_));
+    }
+
     #[test]
     fn test_config_complete_minimal_config() {
         let toml = r#"
@@ -148,6 +342,19 @@ pub mod tests {
                 dpki: None,
                 passphrase_service: Some(PassphraseServiceConfig::Cmd),
                 admin_interfaces: None,
+                startup_integrity_check: false,
+                logger: None,
+                tokio_runtime: None,
+                health_check: None,
+                signal_webhooks: vec![],
+                unresolved_dependencies: Default::default(),
+                app_quotas: HashMap::new(),
+                wasm_io_limits: Default::default(),
+                zome_call_retries: None,
+                max_concurrent_genesis: None,
+                max_concurrent_zome_calls: None,
+                zome_call_weights: HashMap::new(),
+                autonomic: Default::default(),
                 use_dangerous_test_keystore: false,
             }
         );
@@ -158,6 +365,9 @@ pub mod tests {
         let toml = r#"
     environment_path = "/path/to/env"
     use_dangerous_test_keystore = true
+    zome_call_retries = 5
+    max_concurrent_genesis = 8
+    max_concurrent_zome_calls = 16
 
     [passphrase_service]
     type = "cmd"
@@ -178,6 +388,49 @@ pub mod tests {
     driver.type = "websocket"
     driver.port = 1234
 
+    [[admin_interfaces.tokens]]
+    token = "customer-secret"
+    scope.app_ids = ["customer-app"]
+
+    [logger.file]
+    directory = "/path/to/logs"
+    max_file_size_bytes = 1000000
+    rotation = "hourly"
+
+    [logger.otlp]
+    endpoint = "collector:55680"
+    service_name = "alice"
+    sample_ratio = 0.1
+
+    [tokio_runtime]
+    core_threads = 4
+    blocking_warning_threshold_ms = 50
+
+    [health_check]
+    port = 8888
+
+    [[signal_webhooks]]
+    url = "http://localhost:8080/signals"
+    filter = "user"
+    secret = "shh"
+
+    [unresolved_dependencies]
+    abandon_after_hours = 48
+
+    [app_quotas.hosted]
+    max_chain_growth_per_hour = 100
+    max_storage_bytes = 1000000
+
+    [wasm_io_limits]
+    max_input_bytes = 1048576
+
+    [zome_call_weights]
+    hosted = 3
+
+    [autonomic]
+    health_check_interval_s = 60
+    prune_caches_interval_s = 0
+
     "#;
         let result: ConductorResult<ConductorConfig> = config_from_toml(toml);
         assert_eq!(
@@ -196,8 +449,67 @@ pub mod tests {
                 }),
                 passphrase_service: Some(PassphraseServiceConfig::Cmd),
                 admin_interfaces: Some(vec![AdminInterfaceConfig {
-                    driver: InterfaceDriver::Websocket { port: 1234 }
+                    driver: InterfaceDriver::Websocket { port: 1234 },
+                    tokens: vec![AdminToken {
+                        token: "customer-secret".into(),
+                        scope: Some(AdminScope {
+                            app_ids: vec!["customer-app".into()],
+                            agents: vec![],
+                        }),
+                    }],
                 }]),
+                startup_integrity_check: false,
+                logger: Some(LoggerConfig {
+                    file: Some(FileLogConfig {
+                        directory: PathBuf::from("/path/to/logs"),
+                        file_prefix: "holochain".into(),
+                        max_file_size_bytes: Some(1000000),
+                        rotation: RotationPeriod::Hourly,
+                        max_files: None,
+                    }),
+                    otlp: Some(OtlpConfig {
+                        endpoint: "collector:55680".into(),
+                        service_name: "alice".into(),
+                        sample_ratio: 0.1,
+                    }),
+                }),
+                tokio_runtime: Some(TokioRuntimeConfig {
+                    core_threads: Some(4),
+                    max_threads: None,
+                    blocking_warning_threshold_ms: Some(50),
+                }),
+                health_check: Some(HealthCheckConfig { port: 8888 }),
+                signal_webhooks: vec![SignalWebhookConfig {
+                    url: Url::parse("http://localhost:8080/signals").unwrap(),
+                    filter: SignalFilter::User,
+                    secret: Some("shh".into()),
+                    retries: 3,
+                }],
+                unresolved_dependencies: UnresolvedDependencyPolicy {
+                    abandon_after_hours: 48,
+                },
+                app_quotas: maplit::hashmap! {
+                    "hosted".to_string() => AppQuota {
+                        max_chain_growth_per_hour: Some(100),
+                        max_ops_published_per_hour: None,
+                        max_storage_bytes: Some(1000000),
+                    },
+                },
+                wasm_io_limits: WasmIoLimits {
+                    max_input_bytes: 1048576,
+                    ..Default::default()
+                },
+                zome_call_retries: Some(5),
+                max_concurrent_genesis: Some(8),
+                max_concurrent_zome_calls: Some(16),
+                zome_call_weights: maplit::hashmap! {
+                    "hosted".to_string() => 3,
+                },
+                autonomic: AutonomicSchedule {
+                    health_check_interval_s: 60,
+                    prune_caches_interval_s: 0,
+                    ..Default::default()
+                },
                 use_dangerous_test_keystore: true,
             }
         );
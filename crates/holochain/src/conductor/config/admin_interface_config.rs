@@ -1,6 +1,11 @@
 #![deny(missing_docs)]
 
 use crate::conductor::interface::InterfaceDriver;
+use holo_hash::AgentPubKey;
+use holochain_types::{
+    app::{AppId, AppInfo},
+    cell::CellId,
+};
 use serde::{self, Deserialize, Serialize};
 
 /// Information neeeded to spawn an Admin interface
@@ -10,7 +15,57 @@ pub struct AdminInterfaceConfig {
     /// By what means will the interface be exposed?
     /// Current only option is a local websocket running on a configurable port.
     pub driver: InterfaceDriver,
+    /// The tokens which may use this interface. If there are any, every
+    /// request must be wrapped in an `AdminRequest::WithToken` carrying
+    /// one of them. With none, anyone who can connect has full control.
+    #[serde(default)]
+    pub tokens: Vec<AdminToken>,
     // /// How long will this interface be accessible between authentications?
     // /// TODO: implement once we have authentication
     // _session_duration_seconds: Option<u32>,
 }
+
+/// A token which may use an admin interface, and what it may do there
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct AdminToken {
+    /// The secret the client sends with each request
+    pub token: String,
+    /// What the token may manage. `None` gives full control of the conductor.
+    #[serde(default)]
+    pub scope: Option<AdminScope>,
+}
+
+/// The apps and agents a scoped admin token may manage, e.g. so a hosting
+/// provider can let a customer manage their own apps and nothing else
+#[derive(Clone, Deserialize, Serialize, Debug, Default, PartialEq)]
+pub struct AdminScope {
+    /// Apps which may be activated, deactivated and inspected.
+    /// Installing an app also needs its agent to be in [Self::agents].
+    #[serde(default)]
+    pub app_ids: Vec<AppId>,
+    /// Agents whose apps and Cells may be managed, whatever the app is called
+    #[serde(default)]
+    pub agents: Vec<AgentPubKey>,
+}
+
+impl AdminScope {
+    /// Whether the scope covers an app: it's named in the scope, or every
+    /// one of its Cells belongs to an agent in the scope
+    pub fn covers_app(&self, app: &AppInfo) -> bool {
+        self.app_ids.contains(&app.app_id)
+            || (!app.cells.is_empty()
+                && app
+                    .cells
+                    .iter()
+                    .all(|cell| self.agents.contains(cell.cell_id.agent_pubkey())))
+    }
+
+    /// Whether the scope covers a Cell: it belongs to an agent in the
+    /// scope, or to one of the installed apps the scope covers
+    pub fn covers_cell(&self, apps: &[AppInfo], cell_id: &CellId) -> bool {
+        self.agents.contains(cell_id.agent_pubkey())
+            || apps.iter().any(|app| {
+                self.covers_app(app) && app.cells.iter().any(|cell| &cell.cell_id == cell_id)
+            })
+    }
+}
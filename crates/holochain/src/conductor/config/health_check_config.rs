@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// Serves `GET /live` and `GET /ready` over HTTP on localhost, for
+/// orchestration systems to probe. Needs the `http_interface` feature.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct HealthCheckConfig {
+    /// The port to listen on. 0 lets the OS choose a free port.
+    pub port: u16,
+}
@@ -1,5 +1,13 @@
+use holochain_types::observability::{FileLogConfig, OtlpConfig};
 use serde::{Deserialize, Serialize};
 
-/// FIXME: implement
-#[derive(Deserialize, Serialize, Default, Debug, PartialEq)]
-pub struct LoggerConfig {}
+/// Configures how logging should behave, beyond what's given on the command line
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq)]
+pub struct LoggerConfig {
+    /// Also write structured json logs to rotating files. Optional.
+    pub file: Option<FileLogConfig>,
+    /// Also export spans to an OpenTelemetry collector.
+    /// Needs holochain to be built with the `otlp` feature. Optional.
+    #[serde(default)]
+    pub otlp: Option<OtlpConfig>,
+}
@@ -8,6 +8,8 @@ use std::path::PathBuf;
 /// arbitrary UIs to connect to the conductor and prompt the user for a passphrase.
 /// The according `PassphraseServiceUnixSocket` will send a request message over the socket
 /// then receives bytes as passphrase until a newline is sent.
+/// The passphrase can also be read from a file or an environment variable, or be left for the
+/// `UnlockKeystore` admin call, in which case the conductor starts with its keystore locked.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum PassphraseServiceConfig {
@@ -23,6 +25,20 @@ pub enum PassphraseServiceConfig {
         /// The actual pasphrase in the mock case so we can test fail cases
         passphrase: String,
     },
+    /// Passphrase is the first line of a file, e.g. a secret mounted into a container
+    File {
+        /// Path of the file
+        path: PathBuf,
+    },
+    /// Passphrase is read from an environment variable
+    Env {
+        /// Name of the variable
+        var: String,
+    },
+    /// Passphrase is given later by the `UnlockKeystore` admin call.
+    /// Until then the admin interfaces work but Cells aren't started
+    /// and app interfaces refuse requests.
+    Admin,
 }
 
 impl Default for PassphraseServiceConfig {
@@ -0,0 +1,61 @@
+use crate::core::signal::Signal;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// Forwards the signals emitted by the conductor's Cells to an HTTP endpoint.
+/// Needs the `signal_webhooks` feature.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+pub struct SignalWebhookConfig {
+    /// The http:// url signals are POSTed to as JSON.
+    /// Put a proxy in front of endpoints which need TLS.
+    #[serde(with = "url_serde")]
+    pub url: Url,
+    /// Which signals to forward. Defaults to all of them.
+    #[serde(default)]
+    pub filter: SignalFilter,
+    /// If set, each request is signed with an HMAC-SHA256 of its body keyed
+    /// with this secret, so the endpoint can check it came from this conductor
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// How many times to retry a signal the endpoint fails to accept,
+    /// waiting twice as long before each retry. Defaults to 3.
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+/// Which kinds of signal a webhook forwards
+#[derive(Clone, Copy, Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalFilter {
+    /// Every signal
+    All,
+    /// Only trace signals
+    Trace,
+    /// Only signals emitted by zomes
+    User,
+    /// Only notifications of changes at subscribed bases
+    BasisChanged,
+}
+
+impl Default for SignalFilter {
+    fn default() -> Self {
+        SignalFilter::All
+    }
+}
+
+impl SignalFilter {
+    /// Does this signal pass the filter?
+    pub fn matches(&self, signal: &Signal) -> bool {
+        match (self, signal) {
+            (SignalFilter::All, _) => true,
+            (SignalFilter::Trace, Signal::Trace) => true,
+            (SignalFilter::User, Signal::User(_)) => true,
+            (SignalFilter::BasisChanged, Signal::BasisChanged(_)) => true,
+            _ => false,
+        }
+    }
+}
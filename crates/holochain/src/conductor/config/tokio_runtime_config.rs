@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Configures the tokio runtime the conductor runs on
+#[derive(Clone, Deserialize, Serialize, Default, Debug, PartialEq)]
+pub struct TokioRuntimeConfig {
+    /// How many worker threads drive async tasks.
+    /// Defaults to the number of cpus.
+    #[serde(default)]
+    pub core_threads: Option<usize>,
+    /// The most threads tokio will run, counting both the worker threads and
+    /// the threads used for blocking work such as LMDB transactions and
+    /// wasm calls. Defaults to tokio's own limit.
+    #[serde(default)]
+    pub max_threads: Option<usize>,
+    /// Log a warning whenever the async worker threads are blocked for
+    /// longer than this many milliseconds. Defaults to 100 in debug builds
+    /// and off in release builds. Debug builds also abort if the threads
+    /// are blocked for over a second.
+    #[serde(default)]
+    pub blocking_warning_threshold_ms: Option<u64>,
+}
+
+impl TokioRuntimeConfig {
+    /// The blocking warning threshold, falling back to the build's default
+    pub fn blocking_warning_threshold(&self) -> Option<std::time::Duration> {
+        self.blocking_warning_threshold_ms
+            .or_else(|| {
+                if cfg!(debug_assertions) {
+                    Some(100)
+                } else {
+                    None
+                }
+            })
+            .map(std::time::Duration::from_millis)
+    }
+}
@@ -9,10 +9,10 @@ use holochain_state::{
     prelude::*,
 };
 use holochain_types::{
-    dna::{DnaDef, DnaDefHashed, DnaFile},
+    dna::{zome::Zome, DnaDef, DnaDefHashed, DnaFile},
     prelude::*,
 };
-use holochain_zome_types::entry_def::EntryDef;
+use holochain_zome_types::{entry_def::EntryDef, link::LinkTypes};
 use mockall::automock;
 use std::collections::HashMap;
 use tracing::*;
@@ -22,6 +22,8 @@ use tracing::*;
 pub struct RealDnaStore {
     dnas: HashMap<DnaHash, DnaFile>,
     entry_defs: HashMap<EntryDefBufferKey, EntryDef>,
+    /// The link types each zome declares, once something has asked
+    link_types: HashMap<Zome, LinkTypes>,
 }
 
 pub struct DnaDefBuf {
@@ -41,6 +43,8 @@ pub trait DnaStore: Default + Send + Sync {
     fn list(&self) -> Vec<DnaHash>;
     fn get(&self, hash: &DnaHash) -> Option<DnaFile>;
     fn get_entry_def(&self, k: &EntryDefBufferKey) -> Option<EntryDef>;
+    fn add_link_types(&mut self, zome: Zome, link_types: LinkTypes);
+    fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes>;
 }
 
 impl DnaStore for RealDnaStore {
@@ -71,6 +75,12 @@ impl DnaStore for RealDnaStore {
     fn get_entry_def(&self, k: &EntryDefBufferKey) -> Option<EntryDef> {
         self.entry_defs.get(k).cloned()
     }
+    fn add_link_types(&mut self, zome: Zome, link_types: LinkTypes) {
+        self.link_types.insert(zome, link_types);
+    }
+    fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes> {
+        self.link_types.get(zome).cloned()
+    }
 }
 
 impl RealDnaStore {
@@ -78,6 +88,7 @@ impl RealDnaStore {
         RealDnaStore {
             dnas: HashMap::new(),
             entry_defs: HashMap::new(),
+            link_types: HashMap::new(),
         }
     }
 }
@@ -1,7 +1,11 @@
 use super::{entry_def_store::error::EntryDefStoreError, interface::error::InterfaceError};
 use crate::{conductor::cell::error::CellError, core::workflow::error::WorkflowError};
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_state::error::DatabaseError;
-use holochain_types::{app::AppId, cell::CellId};
+use holochain_types::{
+    app::{AppId, AppRunState, DisabledAppReason, PausedAppReason},
+    cell::CellId,
+};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -79,6 +83,9 @@ pub enum ConductorError {
     #[error("Tried to deactivate an app that was not active")]
     AppNotActive,
 
+    #[error("No app interface is attached on port {0}")]
+    AppInterfaceMissing(u16),
+
     #[error(transparent)]
     HolochainP2pError(#[from] holochain_p2p::HolochainP2pError),
 
@@ -87,6 +94,18 @@ pub enum ConductorError {
 
     #[error(transparent)]
     KeystoreError(#[from] holochain_keystore::KeystoreError),
+
+    #[error(transparent)]
+    JoinError(#[from] tokio::task::JoinError),
+
+    #[error("The keystore is locked, unlock it with the UnlockKeystore admin call")]
+    KeystoreLocked,
+
+    #[error("The keystore is already unlocked")]
+    KeystoreAlreadyUnlocked,
+
+    #[error("The keystore didn't unlock with the passphrase: {0}")]
+    KeystoreUnlockFailed(String),
 }
 
 #[derive(Error, Debug)]
@@ -98,6 +117,81 @@ pub enum CreateAppError {
     },
 }
 
+impl CreateAppError {
+    /// The app which failed to start
+    pub fn app_id(&self) -> &AppId {
+        match self {
+            CreateAppError::Failed { app_id, .. } => app_id,
+        }
+    }
+
+    /// What the failure leaves the app as. If every Cell failed in a way
+    /// which may clear up, the app is paused to be retried.
+    pub fn run_state(&self) -> AppRunState {
+        match self {
+            CreateAppError::Failed { errors, .. } => {
+                if errors.iter().all(|e| e.error_kind().is_retryable()) {
+                    AppRunState::Paused(PausedAppReason::Error(self.to_string()))
+                } else {
+                    AppRunState::Disabled(DisabledAppReason::Error(self.to_string()))
+                }
+            }
+        }
+    }
+}
+
+impl HasErrorKind for ConductorError {
+    fn error_kind(&self) -> ErrorKind {
+        use ConductorError::*;
+        match self {
+            InternalCellError(e) => e.error_kind(),
+            DatabaseError(e) => e.error_kind(),
+            CellNotActive | CellNotInitialized | KeystoreLocked => ErrorKind::Unavailable,
+            CellAlreadyActive
+            | AppNotActive
+            | KeystoreAlreadyUnlocked
+            | KeystoreUnlockFailed(_) => ErrorKind::InvalidInput,
+            CellMissing(_)
+            | ConfigMissing(_)
+            | WasmMissing
+            | AppNotInstalled
+            | AppInterfaceMissing(_) => ErrorKind::NotFound,
+            ConfigError(_) | DnaError(_) => ErrorKind::InvalidInput,
+            DeserializationError(_) | SerializationError(_) | SerializedBytesError(_) => {
+                ErrorKind::Serialization
+            }
+            ShuttingDown => ErrorKind::Cancelled,
+            Todo(_)
+            | SubmitTaskError(_)
+            | EntryDefStoreError(_)
+            | KeystoreError(_)
+            | JoinError(_) => ErrorKind::Internal,
+            IoError(_) => ErrorKind::Storage,
+            WorkflowError(e) => e.error_kind(),
+            InterfaceError(e) => e.error_kind(),
+            CreateAppFailed(e) => e.error_kind(),
+            GenesisFailed { errors } => first_error_kind(errors),
+            HolochainP2pError(e) => e.error_kind(),
+        }
+    }
+}
+
+impl HasErrorKind for CreateAppError {
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            CreateAppError::Failed { errors, .. } => first_error_kind(errors),
+        }
+    }
+}
+
+/// When several cells fail, report the kind of the first failure
+fn first_error_kind(errors: &[CellError]) -> ErrorKind {
+    errors
+        .first()
+        .map(HasErrorKind::error_kind)
+        .unwrap_or(ErrorKind::Internal)
+}
+
 // TODO: can this be removed?
 impl From<String> for ConductorError {
     fn from(s: String) -> Self {
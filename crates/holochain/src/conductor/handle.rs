@@ -47,25 +47,45 @@
 
 use super::{
     api::error::ConductorApiResult,
-    config::AdminInterfaceConfig,
+    config::{AdminInterfaceConfig, HealthCheckConfig, SignalWebhookConfig},
     dna_store::DnaStore,
     entry_def_store::EntryDefBufferKey,
     error::{ConductorResult, CreateAppError},
+    health::{HealthEvent, HealthReport, HEALTH_CHECK_TIMEOUT},
+    interface::signal_routes::AppInterfaceBinding,
     manager::TaskManagerRunHandle,
-    Cell, Conductor,
+    Cell, CellError, Conductor,
+};
+use crate::core::cancel::CancelToken;
+use crate::core::chain_audit::{audit_source_chain, poll_agent_activity, ChainAudit};
+use crate::core::dht_metrics::{estimate_dht_size, DhtSizeEstimate};
+use crate::core::dht_snapshot::SnapshotImportReport;
+use crate::core::offline::CellNetworkStatus;
+use crate::core::queue_consumer::{QueueInfo, QueueTrigger};
+use crate::core::redundancy::{
+    check_redundancy, RedundancyReport, REDUNDANCY_SAMPLE_SIZE, TARGET_REDUNDANCY,
 };
 use crate::core::ribosome::ZomeCallInvocation;
+use crate::core::signal::CellSignal;
+use crate::core::state::activity_cache::ActivityCacheBuf;
+use crate::core::state::integrity::{check_vault_integrity, IntegrityReport};
+use crate::core::state::limbo_dump::LimboOpInfo;
+use crate::core::state::pins::pin_hashes;
+use crate::core::state::source_chain::{ChainCapabilities, SourceChain};
 use crate::core::workflow::ZomeCallInvocationResult;
 use derive_more::From;
+use holochain_keystore::KeystoreSenderExt;
+use holochain_p2p::{actor::GossipRound, HolochainP2pError};
 use holochain_types::{
-    app::{AppId, InstalledApp, InstalledCell, MembraneProof},
+    activity::ChainRange,
+    app::{AppId, AppInfo, AppStatus, InstalledApp, InstalledCell, MembraneProof},
     autonomic::AutonomicCue,
     cell::CellId,
-    dna::DnaFile,
+    dna::{zome::Zome, DnaFile},
     prelude::*,
 };
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::{path::PathBuf, sync::Arc};
+use tokio::sync::{broadcast, RwLock};
 use tracing::*;
 
 #[cfg(test)]
@@ -73,8 +93,12 @@ use super::state::ConductorState;
 #[cfg(test)]
 use crate::core::queue_consumer::InitialQueueTriggers;
 #[cfg(test)]
-use holochain_state::env::EnvironmentWrite;
-use holochain_zome_types::entry_def::EntryDef;
+use holochain_state::{
+    buffer::BufferedStore,
+    env::{EnvironmentWrite, WriteManager},
+    error::DatabaseError,
+};
+use holochain_zome_types::{entry_def::EntryDef, link::LinkTypes};
 
 /// A handle to the Conductor that can easily be passed around and cheaply cloned
 pub type ConductorHandle = Arc<dyn ConductorHandleT>;
@@ -99,8 +123,47 @@ pub trait ConductorHandleT: Send + Sync {
         configs: Vec<AdminInterfaceConfig>,
     ) -> ConductorResult<()>;
 
-    /// Add an app interface
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16>;
+    /// Add an app interface, returning the port.
+    /// It only receives signals from the given apps, or from every app if `None`.
+    async fn add_app_interface(
+        self: Arc<Self>,
+        port: u16,
+        app_ids: Option<Vec<AppId>>,
+    ) -> ConductorResult<u16>;
+
+    /// Change which apps' signals the app interface on a port receives,
+    /// or let it receive every app's signals with `None`
+    async fn bind_app_interface(
+        &self,
+        port: u16,
+        app_ids: Option<Vec<AppId>>,
+    ) -> ConductorResult<()>;
+
+    /// Which apps' signals each app interface receives
+    async fn list_app_interface_bindings(&self) -> Vec<AppInterfaceBinding>;
+
+    /// Add an app interface which serves JSON over HTTP.
+    /// Fails unless built with the `http_interface` feature.
+    async fn add_http_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16>;
+
+    /// Forward signals emitted by Cells to a webhook.
+    /// Fails unless built with the `signal_webhooks` feature.
+    async fn add_signal_webhook(&self, config: SignalWebhookConfig) -> ConductorResult<()>;
+
+    /// Serve liveness and readiness probes over HTTP, returning the port.
+    /// Fails unless built with the `http_interface` feature.
+    async fn add_health_check(self: Arc<Self>, config: HealthCheckConfig) -> ConductorResult<u16>;
+
+    /// Check whether the conductor is live and ready
+    async fn check_health(&self) -> HealthReport;
+
+    /// A sender for events which affected the conductor's health while it
+    /// was running. Subscribe to it to watch for them.
+    async fn health_broadcaster(&self) -> broadcast::Sender<HealthEvent>;
+
+    /// A sender for signals emitted by Cells, which are forwarded to
+    /// app interfaces and webhooks
+    async fn signal_broadcaster(&self) -> broadcast::Sender<CellSignal>;
 
     /// Install a [Dna] in this Conductor
     async fn install_dna(&self, dna: DnaFile) -> ConductorResult<()>;
@@ -114,6 +177,12 @@ pub trait ConductorHandleT: Send + Sync {
     /// Get a [EntryDef] from the [EntryDefBuffer]
     async fn get_entry_def(&self, key: &EntryDefBufferKey) -> Option<EntryDef>;
 
+    /// Get the [LinkTypes] a zome declares, if they've been added
+    async fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes>;
+
+    /// Remember the [LinkTypes] a zome declares
+    async fn add_link_types(&self, zome: Zome, link_types: LinkTypes);
+
     /// Add the [DnaFile]s from the wasm and dna_def databases into memory
     async fn add_dnas(&self) -> ConductorResult<()>;
 
@@ -130,6 +199,13 @@ pub trait ConductorHandleT: Send + Sync {
         invocation: ZomeCallInvocation,
     ) -> ConductorApiResult<ZomeCallInvocationResult>;
 
+    /// Invoke a zome function on a Cell, giving up early if `cancel` fires
+    async fn call_zome_cancellable(
+        &self,
+        invocation: ZomeCallInvocation,
+        cancel: CancelToken,
+    ) -> ConductorApiResult<ZomeCallInvocationResult>;
+
     /// Cue the autonomic system to perform some action early (experimental)
     async fn autonomic_cue(&self, cue: AutonomicCue, cell_id: &CellId) -> ConductorApiResult<()>;
 
@@ -146,6 +222,15 @@ pub trait ConductorHandleT: Send + Sync {
     /// Send a signal to all managed tasks asking them to end ASAP.
     async fn shutdown(&self);
 
+    /// Shut down without losing work: stop accepting requests, give in-flight
+    /// zome calls until the deadline to finish, flush each Cell's integration
+    /// and publish queues, then stop all managed tasks and close every
+    /// environment.
+    ///
+    /// NB: peers are not persisted, as the network keeps no peer store of its
+    /// own yet. Agents are re-joined from the ConductorState on startup.
+    async fn shutdown_gracefully(&self, deadline: std::time::Duration);
+
     /// Request access to this conductor's keystore
     fn keystore(&self) -> &KeystoreSender;
 
@@ -162,9 +247,24 @@ pub trait ConductorHandleT: Send + Sync {
     ) -> ConductorResult<()>;
 
     /// Setup the cells from the database
-    /// Only creates any cells that are not already created
+    /// Only creates any cells that are not already created.
+    /// While the keystore is locked nothing is created.
     async fn setup_cells(self: Arc<Self>) -> ConductorResult<Vec<CreateAppError>>;
 
+    /// Whether the keystore is still waiting for its passphrase
+    async fn is_keystore_locked(&self) -> bool;
+
+    /// One of a Cell's tasks died while the conductor was running.
+    /// The Cell is dropped and its apps are paused, so that it's started
+    /// again along with the other paused apps.
+    async fn cell_died(&self, cell_id: &CellId, error: String) -> ConductorResult<()>;
+
+    /// Give a locked keystore its passphrase, then start the active apps' Cells
+    async fn unlock_keystore(
+        self: Arc<Self>,
+        passphrase: String,
+    ) -> ConductorResult<Vec<CreateAppError>>;
+
     /// Activate an app
     #[allow(clippy::ptr_arg)]
     async fn activate_app(&self, app_id: AppId) -> ConductorResult<()>;
@@ -176,10 +276,146 @@ pub trait ConductorHandleT: Send + Sync {
     /// List Cell Ids
     async fn list_cell_ids(&self) -> ConductorResult<Vec<CellId>>;
 
+    /// List installed apps with their Cells, optionally only those with
+    /// the given status
+    async fn list_apps(&self, status_filter: Option<AppStatus>) -> ConductorResult<Vec<AppInfo>>;
+
     /// Dump the cells state
     #[allow(clippy::ptr_arg)]
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String>;
 
+    /// List every op a Cell is holding in its validation and integration limbos
+    #[allow(clippy::ptr_arg)]
+    async fn dump_cell_limbo(&self, cell_id: &CellId) -> ConductorApiResult<Vec<LimboOpInfo>>;
+
+    /// Start recording every network event a Cell receives to the file at
+    /// `path`, or stop recording if `None`
+    #[allow(clippy::ptr_arg)]
+    async fn record_network_events(
+        &self,
+        cell_id: &CellId,
+        path: Option<PathBuf>,
+    ) -> ConductorApiResult<()>;
+
+    /// Feed a recording of network events back through a Cell's handlers,
+    /// in order, returning how many were replayed
+    #[allow(clippy::ptr_arg)]
+    async fn replay_network_events(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<usize>;
+
+    /// Write every op a Cell has integrated as valid to a snapshot file,
+    /// returning how many ops were written
+    #[allow(clippy::ptr_arg)]
+    async fn export_dht_snapshot(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<usize>;
+
+    /// Queue the ops in a snapshot file for validation in a Cell
+    #[allow(clippy::ptr_arg)]
+    async fn import_dht_snapshot(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<SnapshotImportReport>;
+
+    /// The reputation of every peer reported on a Dna's network
+    async fn peer_reputations(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(AgentPubKey, f64)>>;
+
+    /// The most recent gossip rounds with each peer on a Dna's network
+    async fn gossip_rounds(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(AgentPubKey, Vec<GossipRound>)>>;
+
+    /// Fetch the headers of an agent's source chain through a Cell's network
+    /// and verify them, without downloading any entries
+    #[allow(clippy::ptr_arg)]
+    async fn audit_source_chain(
+        &self,
+        cell_id: &CellId,
+        agent: AgentPubKey,
+        range: ChainRange,
+    ) -> ConductorApiResult<ChainAudit>;
+
+    /// Fetch the headers of an agent's source chain after those already
+    /// verified in a Cell's activity cache, verify that they carry on from
+    /// them, and add them to the cache
+    #[allow(clippy::ptr_arg)]
+    async fn poll_agent_activity(
+        &self,
+        cell_id: &CellId,
+        agent: AgentPubKey,
+    ) -> ConductorApiResult<ChainAudit>;
+
+    /// Estimate the size and redundancy of a Cell's DHT by sampling
+    /// the authorities around random locations
+    #[allow(clippy::ptr_arg)]
+    async fn estimate_dht_size(
+        &self,
+        cell_id: &CellId,
+        samples: u8,
+    ) -> ConductorApiResult<DhtSizeEstimate>;
+
+    /// Inspect the queue consumers of a Cell: which have been triggered but
+    /// not yet run, and how much work is waiting for each
+    #[allow(clippy::ptr_arg)]
+    async fn queue_info(&self, cell_id: &CellId) -> ConductorApiResult<Vec<QueueInfo>>;
+
+    /// Manually trigger one of a Cell's queue consumers
+    #[allow(clippy::ptr_arg)]
+    async fn trigger_queue(&self, cell_id: &CellId, queue: QueueTrigger) -> ConductorApiResult<()>;
+
+    /// Put a Cell in or out of offline mode.
+    /// Coming back online publishes what was authored while offline.
+    #[allow(clippy::ptr_arg)]
+    async fn set_cell_offline(
+        &self,
+        cell_id: &CellId,
+        offline: bool,
+    ) -> ConductorApiResult<CellNetworkStatus>;
+
+    /// Whether a Cell is offline and how much it has waiting to publish
+    #[allow(clippy::ptr_arg)]
+    async fn cell_network_status(&self, cell_id: &CellId) -> ConductorApiResult<CellNetworkStatus>;
+
+    /// The live cap grants and claims on a Cell's source chain
+    #[allow(clippy::ptr_arg)]
+    async fn list_capabilities(&self, cell_id: &CellId) -> ConductorApiResult<ChainCapabilities>;
+
+    /// Check how many authorities hold a sample of a Cell's authored ops,
+    /// publishing again any which are under-replicated
+    #[allow(clippy::ptr_arg)]
+    async fn check_redundancy(&self, cell_id: &CellId) -> ConductorApiResult<RedundancyReport>;
+
+    /// Pin hashes on a Cell so their data is always held and served locally.
+    /// Data the Cell doesn't hold yet is fetched first.
+    #[allow(clippy::ptr_arg)]
+    async fn pin_hashes(&self, cell_id: &CellId, hashes: Vec<AnyDhtHash>)
+        -> ConductorApiResult<()>;
+
+    /// Unpin hashes on a Cell
+    #[allow(clippy::ptr_arg)]
+    async fn unpin_hashes(
+        &self,
+        cell_id: &CellId,
+        hashes: Vec<AnyDhtHash>,
+    ) -> ConductorApiResult<()>;
+
+    /// Every hash pinned on a Cell
+    #[allow(clippy::ptr_arg)]
+    async fn list_pins(&self, cell_id: &CellId) -> ConductorApiResult<Vec<AnyDhtHash>>;
+
+    /// Re-hash and re-verify every Cell's vault, quarantining corrupt data
+    async fn check_integrity(&self) -> ConductorResult<Vec<(CellId, IntegrityReport)>>;
+
     /// Get info about an installed App, whether active or inactive
     #[allow(clippy::ptr_arg)]
     async fn get_app_info(&self, app_id: &AppId) -> ConductorResult<Option<InstalledApp>>;
@@ -226,9 +462,82 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .await
     }
 
-    async fn add_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16> {
+    async fn add_app_interface(
+        self: Arc<Self>,
+        port: u16,
+        app_ids: Option<Vec<AppId>>,
+    ) -> ConductorResult<u16> {
+        let mut lock = self.conductor.write().await;
+        lock.add_app_interface_via_handle(port, app_ids, self.clone())
+            .await
+    }
+
+    async fn bind_app_interface(
+        &self,
+        port: u16,
+        app_ids: Option<Vec<AppId>>,
+    ) -> ConductorResult<()> {
+        self.conductor
+            .read()
+            .await
+            .bind_app_interface(port, app_ids)
+    }
+
+    async fn list_app_interface_bindings(&self) -> Vec<AppInterfaceBinding> {
+        self.conductor.read().await.list_app_interface_bindings()
+    }
+
+    async fn add_http_app_interface(self: Arc<Self>, port: u16) -> ConductorResult<u16> {
+        let mut lock = self.conductor.write().await;
+        lock.add_http_app_interface_via_handle(port, self.clone())
+            .await
+    }
+
+    async fn add_signal_webhook(&self, config: SignalWebhookConfig) -> ConductorResult<()> {
+        let mut lock = self.conductor.write().await;
+        lock.add_signal_webhook(config).await
+    }
+
+    async fn add_health_check(self: Arc<Self>, config: HealthCheckConfig) -> ConductorResult<u16> {
         let mut lock = self.conductor.write().await;
-        lock.add_app_interface_via_handle(port, self.clone()).await
+        lock.add_health_check_via_handle(config, self.clone()).await
+    }
+
+    async fn check_health(&self) -> HealthReport {
+        // A conductor stuck behind its lock isn't live
+        let (apps_not_running, unbound_interfaces) = {
+            let lock = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, self.conductor.read()).await
+            {
+                Ok(lock) if lock.check_running().is_ok() => lock,
+                _ => return HealthReport::unresponsive(),
+            };
+            match lock.apps_not_running().await {
+                Ok(apps) => (apps, lock.unbound_admin_interfaces()),
+                Err(e) => {
+                    warn!(error = ?e, "Couldn't read the conductor state to check its health");
+                    return HealthReport::unresponsive();
+                }
+            }
+        };
+        let keystore_reachable =
+            tokio::time::timeout(HEALTH_CHECK_TIMEOUT, self.keystore.check_reachable())
+                .await
+                .map(|r| r.is_ok())
+                .unwrap_or(false);
+        HealthReport {
+            live: true,
+            apps_not_running,
+            unbound_interfaces,
+            keystore_reachable,
+        }
+    }
+
+    async fn health_broadcaster(&self) -> broadcast::Sender<HealthEvent> {
+        self.conductor.read().await.health_broadcaster()
+    }
+
+    async fn signal_broadcaster(&self) -> broadcast::Sender<CellSignal> {
+        self.conductor.read().await.signal_broadcaster()
     }
 
     async fn install_dna(&self, dna: DnaFile) -> ConductorResult<()> {
@@ -264,6 +573,18 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.dna_store().get_entry_def(key)
     }
 
+    async fn get_link_types(&self, zome: &Zome) -> Option<LinkTypes> {
+        self.conductor.read().await.dna_store().get_link_types(zome)
+    }
+
+    async fn add_link_types(&self, zome: Zome, link_types: LinkTypes) {
+        self.conductor
+            .write()
+            .await
+            .dna_store_mut()
+            .add_link_types(zome, link_types)
+    }
+
     #[instrument(skip(self))]
     async fn dispatch_holochain_p2p_event(
         &self,
@@ -280,21 +601,31 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
     async fn call_zome(
         &self,
         invocation: ZomeCallInvocation,
+    ) -> ConductorApiResult<ZomeCallInvocationResult> {
+        self.call_zome_cancellable(invocation, CancelToken::new())
+            .await
+    }
+
+    async fn call_zome_cancellable(
+        &self,
+        invocation: ZomeCallInvocation,
+        cancel: CancelToken,
     ) -> ConductorApiResult<ZomeCallInvocationResult> {
         // FIXME: D-01058: We are holding this read lock for
         // the entire call to call_zome and blocking
         // any writes to the conductor
         let lock = self.conductor.read().await;
+        // A request may have got past the interface just before shutdown began
+        lock.check_running()?;
         debug!(cell_id = ?invocation.cell_id);
         let cell: &Cell = lock.cell_by_id(&invocation.cell_id)?;
-        Ok(cell.call_zome(invocation).await?)
+        Ok(cell.call_zome(invocation, cancel).await?)
     }
 
     async fn autonomic_cue(&self, cue: AutonomicCue, cell_id: &CellId) -> ConductorApiResult<()> {
-        let lock = self.conductor.write().await;
+        let lock = self.conductor.read().await;
         let cell = lock.cell_by_id(cell_id)?;
-        let _ = cell.handle_autonomic_process(cue.into()).await;
-        Ok(())
+        Ok(cell.handle_autonomic_process(cue.into()).await?)
     }
 
     async fn take_shutdown_handle(&self) -> Option<TaskManagerRunHandle> {
@@ -312,6 +643,26 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.write().await.shutdown()
     }
 
+    async fn shutdown_gracefully(&self, deadline: std::time::Duration) {
+        let deadline = tokio::time::Instant::now() + deadline;
+        self.conductor.read().await.stop_accepting_requests();
+
+        // Zome calls hold a read lock for as long as they run, so once we can
+        // take the write lock there are none left in flight
+        if tokio::time::timeout_at(deadline, self.conductor.write())
+            .await
+            .is_err()
+        {
+            warn!("Zome calls were still running at the shutdown deadline");
+        }
+
+        let lock = self.conductor.read().await;
+        lock.flush_cell_queues(deadline).await;
+        lock.shutdown();
+        lock.close_environments();
+        info!("Conductor shut down gracefully");
+    }
+
     fn keystore(&self) -> &KeystoreSender {
         &self.keystore
     }
@@ -325,6 +676,8 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         app_id: AppId,
         cell_data: Vec<(InstalledCell, Option<MembraneProof>)>,
     ) -> ConductorResult<()> {
+        // genesis signs the new chains
+        self.conductor.read().await.check_keystore_unlocked()?;
         self.conductor
             .read()
             .await
@@ -337,20 +690,36 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             )
             .await?;
 
-        let cell_data = cell_data.into_iter().map(|(c, _)| c).collect();
+        let cell_data: Vec<_> = cell_data.into_iter().map(|(c, _)| c).collect();
+        let cell_ids: Vec<_> = cell_data.iter().map(|c| c.as_id().clone()).collect();
         let app = InstalledApp { app_id, cell_data };
 
         // Update the db
-        self.conductor
+        let result = self
+            .conductor
             .write()
             .await
             .add_inactive_app_to_db(app)
-            .await
+            .await;
+
+        // Don't leave the new cells behind if the app couldn't be recorded
+        if result.is_err() {
+            self.conductor
+                .read()
+                .await
+                .remove_cell_envs(cell_ids)
+                .await?;
+        }
+        result
     }
 
     async fn setup_cells(self: Arc<Self>) -> ConductorResult<Vec<CreateAppError>> {
         let cells = {
             let lock = self.conductor.read().await;
+            // the cells are started once the keystore is unlocked
+            if lock.is_keystore_locked() {
+                return Ok(Vec::new());
+            }
             lock.create_active_app_cells(self.clone())
                 .await?
                 .into_iter()
@@ -371,11 +740,46 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .filter_map(|r| r)
             .collect();
         {
-            self.conductor.write().await.initialize_cell_workflows();
+            let mut lock = self.conductor.write().await;
+            lock.initialize_cell_workflows();
+            lock.record_app_start_failures(&r).await?;
         }
         Ok(r)
     }
 
+    async fn is_keystore_locked(&self) -> bool {
+        self.conductor.read().await.is_keystore_locked()
+    }
+
+    async fn cell_died(&self, cell_id: &CellId, error: String) -> ConductorResult<()> {
+        let cell = self
+            .conductor
+            .write()
+            .await
+            .pause_dead_cell(cell_id, error)
+            .await?;
+        // Leave the network so the restarted Cell can join afresh.
+        // It keeps its store, so there's nothing to hand off.
+        if let Some(mut cell) = cell {
+            if let Err(e) = cell.leave_without_handoff().await {
+                warn!(?cell_id, error = ?e, "Dead Cell failed to leave the network");
+            }
+        }
+        Ok(())
+    }
+
+    async fn unlock_keystore(
+        self: Arc<Self>,
+        passphrase: String,
+    ) -> ConductorResult<Vec<CreateAppError>> {
+        self.conductor
+            .read()
+            .await
+            .unlock_keystore(passphrase)
+            .await?;
+        self.setup_cells().await
+    }
+
     async fn activate_app(&self, app_id: AppId) -> ConductorResult<()> {
         self.conductor
             .write()
@@ -391,10 +795,17 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
             .await
             .deactivate_app_in_db(app_id)
             .await?;
-        self.conductor
+        let cells = self
+            .conductor
             .write()
             .await
             .remove_cells(cell_ids_to_remove);
+        // Leave the network so the cells' spaces don't outlive them
+        for mut cell in cells {
+            if let Err(e) = cell.leave().await {
+                warn!(cell_id = ?cell.id(), error = ?e, "Cell failed to leave the network");
+            }
+        }
         Ok(())
     }
 
@@ -402,10 +813,229 @@ impl<DS: DnaStore + 'static> ConductorHandleT for ConductorHandleImpl<DS> {
         self.conductor.read().await.list_cell_ids().await
     }
 
+    async fn list_apps(&self, status_filter: Option<AppStatus>) -> ConductorResult<Vec<AppInfo>> {
+        self.conductor.read().await.list_apps(status_filter).await
+    }
+
     async fn dump_cell_state(&self, cell_id: &CellId) -> ConductorApiResult<String> {
         self.conductor.read().await.dump_cell_state(cell_id).await
     }
 
+    async fn dump_cell_limbo(&self, cell_id: &CellId) -> ConductorApiResult<Vec<LimboOpInfo>> {
+        self.conductor.read().await.dump_cell_limbo(cell_id).await
+    }
+
+    async fn record_network_events(
+        &self,
+        cell_id: &CellId,
+        path: Option<PathBuf>,
+    ) -> ConductorApiResult<()> {
+        self.conductor
+            .read()
+            .await
+            .record_network_events(cell_id, path)
+            .await
+    }
+
+    async fn replay_network_events(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<usize> {
+        self.conductor
+            .read()
+            .await
+            .replay_network_events(cell_id, path)
+            .await
+    }
+
+    async fn export_dht_snapshot(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<usize> {
+        self.conductor
+            .read()
+            .await
+            .export_dht_snapshot(cell_id, path)
+            .await
+    }
+
+    async fn import_dht_snapshot(
+        &self,
+        cell_id: &CellId,
+        path: PathBuf,
+    ) -> ConductorApiResult<SnapshotImportReport> {
+        self.conductor
+            .read()
+            .await
+            .import_dht_snapshot(cell_id, path)
+            .await
+    }
+
+    async fn peer_reputations(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(AgentPubKey, f64)>> {
+        self.conductor.read().await.peer_reputations(dna_hash).await
+    }
+
+    async fn gossip_rounds(
+        &self,
+        dna_hash: &DnaHash,
+    ) -> ConductorApiResult<Vec<(AgentPubKey, Vec<GossipRound>)>> {
+        self.conductor.read().await.gossip_rounds(dna_hash).await
+    }
+
+    async fn audit_source_chain(
+        &self,
+        cell_id: &CellId,
+        agent: AgentPubKey,
+        range: ChainRange,
+    ) -> ConductorApiResult<ChainAudit> {
+        // Don't hold the lock while waiting on the network
+        let mut network = {
+            let lock = self.conductor.read().await;
+            lock.cell_by_id(cell_id)?.holochain_p2p_cell().clone()
+        };
+        Ok(audit_source_chain(&mut network, agent, range).await?)
+    }
+
+    async fn poll_agent_activity(
+        &self,
+        cell_id: &CellId,
+        agent: AgentPubKey,
+    ) -> ConductorApiResult<ChainAudit> {
+        // Don't hold the lock while waiting on the network
+        let (env, mut network) = {
+            let lock = self.conductor.read().await;
+            let cell = lock.cell_by_id(cell_id)?;
+            (cell.env().clone(), cell.holochain_p2p_cell().clone())
+        };
+        let mut cache = ActivityCacheBuf::new(env.clone().into())?;
+        let cached = cache.get(&agent)?;
+        let cached_len = cached.headers.len();
+        let audit = poll_agent_activity(&mut network, agent.clone(), cached).await?;
+        cache.append(agent, audit.headers[cached_len..].to_vec())?;
+        env.guard()
+            .with_commit::<DatabaseError, _, _>(|writer| cache.flush_to_txn(writer))?;
+        Ok(audit)
+    }
+
+    async fn estimate_dht_size(
+        &self,
+        cell_id: &CellId,
+        samples: u8,
+    ) -> ConductorApiResult<DhtSizeEstimate> {
+        // Don't hold the lock while waiting on the network
+        let mut network = {
+            let lock = self.conductor.read().await;
+            lock.cell_by_id(cell_id)?.holochain_p2p_cell().clone()
+        };
+        Ok(estimate_dht_size(&mut network, samples).await?)
+    }
+
+    async fn queue_info(&self, cell_id: &CellId) -> ConductorApiResult<Vec<QueueInfo>> {
+        let (triggers, env) = {
+            let lock = self.conductor.read().await;
+            let cell = lock.cell_by_id(cell_id)?;
+            (cell.triggers().clone(), cell.env().clone())
+        };
+        Ok(triggers.queue_info(&env)?)
+    }
+
+    async fn trigger_queue(&self, cell_id: &CellId, queue: QueueTrigger) -> ConductorApiResult<()> {
+        let lock = self.conductor.read().await;
+        let cell = lock.cell_by_id(cell_id)?;
+        cell.triggers().clone().trigger(queue);
+        Ok(())
+    }
+
+    async fn set_cell_offline(
+        &self,
+        cell_id: &CellId,
+        offline: bool,
+    ) -> ConductorApiResult<CellNetworkStatus> {
+        let lock = self.conductor.read().await;
+        Ok(lock.cell_by_id(cell_id)?.set_offline(offline)?)
+    }
+
+    async fn cell_network_status(&self, cell_id: &CellId) -> ConductorApiResult<CellNetworkStatus> {
+        let lock = self.conductor.read().await;
+        Ok(lock.cell_by_id(cell_id)?.network_status()?)
+    }
+
+    async fn list_capabilities(&self, cell_id: &CellId) -> ConductorApiResult<ChainCapabilities> {
+        let env = {
+            let lock = self.conductor.read().await;
+            lock.cell_by_id(cell_id)?.env().clone()
+        };
+        Ok(SourceChain::new(env.into())?.capabilities()?)
+    }
+
+    async fn check_redundancy(&self, cell_id: &CellId) -> ConductorApiResult<RedundancyReport> {
+        // Don't hold the lock while waiting on the network
+        let (env, mut network) = {
+            let lock = self.conductor.read().await;
+            let cell = lock.cell_by_id(cell_id)?;
+            (cell.env().clone(), cell.holochain_p2p_cell().clone())
+        };
+        // Nobody can be asked while offline, so every op would look lost
+        if network.is_offline() {
+            return Err(CellError::from(HolochainP2pError::Offline).into());
+        }
+        let report = check_redundancy(env, &mut network, REDUNDANCY_SAMPLE_SIZE, TARGET_REDUNDANCY)
+            .await
+            .map_err(|e| CellError::from(Box::new(e)))?;
+        if report.under_replicated > 0 {
+            self.trigger_queue(cell_id, QueueTrigger::PublishDhtOps)
+                .await?;
+        }
+        Ok(report)
+    }
+
+    async fn pin_hashes(
+        &self,
+        cell_id: &CellId,
+        hashes: Vec<AnyDhtHash>,
+    ) -> ConductorApiResult<()> {
+        // Don't hold the lock while waiting on the network
+        let (env, network) = {
+            let lock = self.conductor.read().await;
+            let cell = lock.cell_by_id(cell_id)?;
+            (cell.env().clone(), cell.holochain_p2p_cell().clone())
+        };
+        pin_hashes(env, network, hashes)
+            .await
+            .map_err(CellError::from)?;
+        Ok(())
+    }
+
+    async fn unpin_hashes(
+        &self,
+        cell_id: &CellId,
+        hashes: Vec<AnyDhtHash>,
+    ) -> ConductorApiResult<()> {
+        let lock = self.conductor.read().await;
+        Ok(lock.cell_by_id(cell_id)?.unpin(hashes)?)
+    }
+
+    async fn list_pins(&self, cell_id: &CellId) -> ConductorApiResult<Vec<AnyDhtHash>> {
+        let lock = self.conductor.read().await;
+        Ok(lock.cell_by_id(cell_id)?.pins()?)
+    }
+
+    async fn check_integrity(&self) -> ConductorResult<Vec<(CellId, IntegrityReport)>> {
+        // Don't hold the lock while checking, this can take a while
+        let cell_envs = self.conductor.read().await.cell_envs();
+        let mut reports = Vec::with_capacity(cell_envs.len());
+        for (cell_id, env) in cell_envs {
+            let report = check_vault_integrity(env).await.map_err(CellError::from)?;
+            reports.push((cell_id, report));
+        }
+        Ok(reports)
+    }
+
     async fn get_app_info(&self, app_id: &AppId) -> ConductorResult<Option<InstalledApp>> {
         Ok(self
             .conductor
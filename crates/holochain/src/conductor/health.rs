@@ -0,0 +1,109 @@
+//! Liveness and readiness of a Conductor, for orchestration systems like
+//! systemd or Kubernetes.
+//!
+//! A Conductor is live while it responds to requests and isn't shutting
+//! down. It is ready once it is live, every active app's Cells are running,
+//! every configured admin interface is bound and the keystore answers.
+//!
+//! Health is checked with [AdminRequest::CheckHealth], or over HTTP with
+//! `GET /live` and `GET /ready` on the port given by a [HealthCheckConfig].
+//! The HTTP endpoints answer 200 or 503 with the [HealthReport] as JSON.
+//!
+//! Problems the Conductor recovers from by itself, like the network
+//! restarting, don't affect readiness. They are broadcast as [HealthEvent]s
+//! instead, see [ConductorHandleT::health_broadcaster].
+//!
+//! [AdminRequest::CheckHealth]: crate::conductor::api::AdminRequest::CheckHealth
+//! [HealthCheckConfig]: crate::conductor::config::HealthCheckConfig
+//! [ConductorHandleT::health_broadcaster]: crate::conductor::handle::ConductorHandleT::health_broadcaster
+
+use holochain_types::{app::AppId, cell::CellId};
+use serde::{Deserialize, Serialize};
+
+/// How long each check may take before it counts as failed
+pub const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How many health events are kept for subscribers which fall behind
+pub(crate) const HEALTH_EVENT_BUFFER_SIZE: usize = 16;
+
+/// Something which happened to a running Conductor's health
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HealthEvent {
+    /// The network for a Cell died and was restarted.
+    /// The Cell has already been re-joined.
+    NetworkRestarted {
+        /// The Cell whose network restarted
+        cell_id: CellId,
+        /// Why the network died
+        reason: String,
+    },
+}
+
+/// The outcome of checking a Conductor's health
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The Conductor answered within the timeout and isn't shutting down
+    pub live: bool,
+    /// Active apps which don't have all their Cells running
+    pub apps_not_running: Vec<AppId>,
+    /// How many of the configured admin interfaces aren't bound yet
+    pub unbound_interfaces: usize,
+    /// The keystore answered within the timeout
+    pub keystore_reachable: bool,
+}
+
+impl HealthReport {
+    /// The report for a Conductor which didn't answer,
+    /// so nothing else could be checked
+    pub fn unresponsive() -> Self {
+        Self {
+            live: false,
+            apps_not_running: Vec::new(),
+            unbound_interfaces: 0,
+            keystore_reachable: false,
+        }
+    }
+
+    /// Whether the Conductor is ready to serve requests
+    pub fn is_ready(&self) -> bool {
+        self.live
+            && self.apps_not_running.is_empty()
+            && self.unbound_interfaces == 0
+            && self.keystore_reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy() -> HealthReport {
+        HealthReport {
+            live: true,
+            apps_not_running: Vec::new(),
+            unbound_interfaces: 0,
+            keystore_reachable: true,
+        }
+    }
+
+    #[test]
+    fn ready_only_when_every_check_passes() {
+        assert!(healthy().is_ready());
+        assert!(!HealthReport::unresponsive().is_ready());
+        assert!(!HealthReport {
+            apps_not_running: vec!["app".into()],
+            ..healthy()
+        }
+        .is_ready());
+        assert!(!HealthReport {
+            unbound_interfaces: 1,
+            ..healthy()
+        }
+        .is_ready());
+        assert!(!HealthReport {
+            keystore_reachable: false,
+            ..healthy()
+        }
+        .is_ready());
+    }
+}
@@ -55,6 +55,15 @@ pub fn prompt_for_environment_dir(path: &Path) -> std::io::Result<bool> {
     }
 }
 
+/// Prompt the user for the passphrase which unlocks the keystore.
+/// The passphrase is read from a line of stdin, without the newline.
+pub fn prompt_for_passphrase() -> std::io::Result<String> {
+    println!("Enter the passphrase to unlock the keystore:");
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(input.trim_end_matches(&['\r', '\n'][..]).to_string())
+}
+
 /// If config_path is Some, attempt to load the config from that path, and return error if file not found
 /// If config_path is None, attempt to load config from default path, and offer to create config if file not found
 pub fn load_config_or_prompt_for_default(
@@ -85,10 +94,8 @@ fn save_default_config_toml(path: &Path) -> ConductorResult<ConductorConfig> {
         ConductorError::ConfigError(format!("Bad path for conductor config: {}", path.display()))
     })?;
     std::fs::create_dir_all(dir)?;
-    let default = ConductorConfig::default();
-    let content_toml = toml::to_string(&default)?;
-    std::fs::write(path, content_toml)?;
-    Ok(default)
+    std::fs::write(path, ConductorConfig::default_commented_toml())?;
+    Ok(ConductorConfig::default())
 }
 
 #[cfg(test)]
@@ -11,6 +11,13 @@ use serde::{Deserialize, Serialize};
 use std::convert::{TryFrom, TryInto};
 
 pub mod error;
+#[cfg(feature = "http_interface")]
+pub mod health_check;
+#[cfg(feature = "http_interface")]
+pub mod http;
+pub mod signal_routes;
+#[cfg(feature = "signal_webhooks")]
+pub mod webhook;
 pub mod websocket;
 
 /// Allows the conductor or cell to forward signals to connected clients
@@ -153,7 +160,14 @@ pub fn attach_external_conductor_api<A: AppInterfaceApi>(
 #[cfg_attr(test, derive(PartialEq))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum InterfaceDriver {
-    Websocket { port: u16 },
+    Websocket {
+        port: u16,
+    },
+    /// JSON over HTTP, for App interfaces only.
+    /// Needs the `http_interface` feature.
+    Http {
+        port: u16,
+    },
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
-use crate::conductor::error::ConductorError;
+use crate::conductor::{error::ConductorError, interface::InterfaceDriver};
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_serialized_bytes::SerializedBytesError;
 
 /// Interface Error Type
@@ -26,6 +27,11 @@ pub enum InterfaceError {
     IoTodo(#[from] std::io::Error),
     #[error("Failed to find free port")]
     PortError,
+    #[error("The {0:?} interface driver isn't supported by this interface or build")]
+    UnsupportedDriver(InterfaceDriver),
+    #[cfg(feature = "http_interface")]
+    #[error(transparent)]
+    Http(#[from] hyper::Error),
 }
 
 impl From<String> for InterfaceError {
@@ -40,5 +46,21 @@ impl From<futures::channel::mpsc::SendError> for InterfaceError {
     }
 }
 
+impl HasErrorKind for InterfaceError {
+    fn error_kind(&self) -> ErrorKind {
+        use InterfaceError::*;
+        match self {
+            SerializedBytes(_) => ErrorKind::Serialization,
+            JoinError(_) | Other(_) => ErrorKind::Internal,
+            SignalReceive(_) | SendError | IoTodo(_) | PortError => ErrorKind::Unavailable,
+            RequestHandler(e) => e.error_kind(),
+            UnexpectedMessage(_) | UnsupportedDriver(_) => ErrorKind::InvalidInput,
+            Closed => ErrorKind::Cancelled,
+            #[cfg(feature = "http_interface")]
+            Http(_) => ErrorKind::Unavailable,
+        }
+    }
+}
+
 /// Interface Result Type
 pub type InterfaceResult<T> = Result<T, InterfaceError>;
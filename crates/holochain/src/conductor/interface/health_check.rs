@@ -0,0 +1,76 @@
+//! Liveness and readiness probes served over plain HTTP.
+//!
+//! - `GET /live` answers 200 while the Conductor is live
+//! - `GET /ready` answers 200 once the Conductor is ready
+//!
+//! Otherwise they answer 503. Either way the body is the [HealthReport].
+
+use super::{error::InterfaceResult, http::json_response};
+use crate::conductor::{
+    conductor::StopReceiver,
+    health::HealthReport,
+    manager::{ManagedTaskHandle, ManagedTaskResult},
+    ConductorHandle,
+};
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::{convert::Infallible, net::SocketAddr};
+use tracing::*;
+
+/// Serve the health check endpoints
+pub async fn spawn_health_check_task(
+    port: u16,
+    handle: ConductorHandle,
+    mut stop_rx: StopReceiver,
+) -> InterfaceResult<(u16, ManagedTaskHandle)> {
+    trace!("Initializing health check endpoints");
+    let make_service = make_service_fn(move |_| {
+        let handle = handle.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_health_request(handle.clone(), request)
+            }))
+        }
+    });
+    let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], port)))?.serve(make_service);
+    trace!("LISTENING AT: {}", server.local_addr());
+    let port = server.local_addr().port();
+    let task = tokio::task::spawn(async move {
+        let server = server.with_graceful_shutdown(async move {
+            stop_rx.recv().await.ok();
+        });
+        if let Err(e) = server.await {
+            error!(
+                error = &e as &dyn std::error::Error,
+                "Health check endpoints failed"
+            );
+        }
+        ManagedTaskResult::Ok(())
+    });
+    Ok((port, task))
+}
+
+async fn handle_health_request(
+    handle: ConductorHandle,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let probe: fn(&HealthReport) -> bool = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/live") => |report| report.live,
+        (&Method::GET, "/ready") => HealthReport::is_ready,
+        _ => {
+            return Ok(json_response(
+                StatusCode::NOT_FOUND,
+                &serde_json::json!({ "error": "Not found" }),
+            ))
+        }
+    };
+    let report = handle.check_health().await;
+    let status = if probe(&report) {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    Ok(json_response(status, &report))
+}
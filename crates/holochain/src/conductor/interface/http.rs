@@ -0,0 +1,534 @@
+//! An App interface served over plain HTTP, for clients which can't keep a
+//! websocket open. Bodies are JSON, with hashes as base64 strings.
+//!
+//! - `POST /zome-call` takes a [HttpZomeCall] and returns the zome
+//!   function's output as JSON
+//! - `GET /app-info?app_id=<AppId>` returns a [HttpAppInfo], or `null` if
+//!   there's no such app
+//!
+//! Errors come back as `{ "error": ... }` with a 4xx or 5xx status.
+//! HTTP can't push signals, so they're only sent to websocket interfaces.
+//!
+//! Every request must be addressed to a loopback host on the interface's
+//! port, and if it comes from a browser, from a page on one. This stops other
+//! web pages calling zomes through the user's browser, whether by a cross
+//! site request or by rebinding their own domain to 127.0.0.1.
+//! Zome calls must also be sent as `application/json`, which browsers can't
+//! do across origins without a preflight.
+
+use super::error::{InterfaceError, InterfaceResult};
+use crate::conductor::{
+    api::{
+        error::{ErrorKind, ExternalApiWireError},
+        AppRequest, AppResponse, InterfaceApi,
+    },
+    conductor::StopReceiver,
+    manager::{ManagedTaskHandle, ManagedTaskResult},
+};
+use crate::core::ribosome::ZomeCallInvocation;
+use holo_hash::{AgentPubKey, DnaHash};
+use holochain_serialized_bytes::{SerializedBytes, UnsafeBytes};
+use holochain_types::{
+    app::{AppId, CellNick, InstalledApp},
+    cell::CellId,
+};
+use holochain_zome_types::{
+    capability::{CapSecret, CAP_SECRET_BYTES},
+    ExternInput, ExternOutput,
+};
+use hyper::{
+    body::HttpBody,
+    server::conn::AddrIncoming,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode, Uri,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::{Infallible, TryFrom},
+    net::SocketAddr,
+};
+use tracing::*;
+
+/// The largest zome call body the interface will read
+const MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// The hosts a request may be addressed to, or come from, on the
+/// interface's port
+const ALLOWED_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// A [CellId] with its hashes as base64 strings
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HttpCellId {
+    /// The Dna of the Cell
+    pub dna_hash: String,
+    /// The agent running the Cell
+    pub agent_pub_key: String,
+}
+
+/// The body of a `POST /zome-call`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HttpZomeCall {
+    /// The Cell to call into
+    pub cell_id: HttpCellId,
+    /// The zome containing the function
+    pub zome_name: String,
+    /// The function to call
+    pub fn_name: String,
+    /// The base64 capability secret authorizing the call, if any
+    #[serde(default)]
+    pub cap: Option<String>,
+    /// The function's input, which is passed to the zome as messagepack
+    pub payload: serde_json::Value,
+    /// The base64 agent key the call is made on behalf of
+    pub provenance: String,
+}
+
+/// An installed Cell in a [HttpAppInfo]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HttpInstalledCell {
+    /// The Cell's id
+    pub cell_id: HttpCellId,
+    /// The Cell's nick within the app
+    pub cell_nick: CellNick,
+}
+
+/// The response to a `GET /app-info`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HttpAppInfo {
+    /// The app's id
+    pub app_id: AppId,
+    /// The app's Cells
+    pub cell_data: Vec<HttpInstalledCell>,
+}
+
+/// Reasons a request to the HTTP interface fails
+#[derive(Debug)]
+enum HttpError {
+    NotFound,
+    BadRequest(String),
+    ForbiddenOrigin,
+    UnsupportedMediaType,
+    PayloadTooLarge,
+    Unauthorized,
+    Conductor(ExternalApiWireError),
+    Interface(InterfaceError),
+}
+
+impl HttpError {
+    fn into_response(self) -> Response<Body> {
+        let (status, error): (_, serde_json::Value) = match self {
+            HttpError::NotFound => (StatusCode::NOT_FOUND, "Not found".into()),
+            HttpError::BadRequest(e) => (StatusCode::BAD_REQUEST, e.into()),
+            HttpError::ForbiddenOrigin => {
+                (StatusCode::FORBIDDEN, "Host or origin not allowed".into())
+            }
+            HttpError::UnsupportedMediaType => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected application/json".into(),
+            ),
+            HttpError::PayloadTooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "Body too large".into()),
+            HttpError::Unauthorized => (StatusCode::FORBIDDEN, "Zome call unauthorized".into()),
+            HttpError::Conductor(e) => (
+                status_for_kind(e.kind()),
+                serde_json::to_value(e).unwrap_or_default(),
+            ),
+            HttpError::Interface(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string().into()),
+        };
+        json_response(status, &serde_json::json!({ "error": error }))
+    }
+}
+
+/// The closest HTTP status to each kind of conductor error
+fn status_for_kind(kind: ErrorKind) -> StatusCode {
+    match kind {
+        ErrorKind::NotFound => StatusCode::NOT_FOUND,
+        ErrorKind::InvalidInput | ErrorKind::Serialization => StatusCode::BAD_REQUEST,
+        ErrorKind::Unauthorized => StatusCode::FORBIDDEN,
+        ErrorKind::Validation => StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorKind::QuotaExceeded => StatusCode::TOO_MANY_REQUESTS,
+        ErrorKind::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        ErrorKind::Unavailable | ErrorKind::Cancelled => StatusCode::SERVICE_UNAVAILABLE,
+        ErrorKind::Storage | ErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<InterfaceError> for HttpError {
+    fn from(e: InterfaceError) -> Self {
+        HttpError::Interface(e)
+    }
+}
+
+impl From<&CellId> for HttpCellId {
+    fn from(cell_id: &CellId) -> Self {
+        Self {
+            dna_hash: cell_id.dna_hash().to_string(),
+            agent_pub_key: cell_id.agent_pubkey().to_string(),
+        }
+    }
+}
+
+impl TryFrom<HttpCellId> for CellId {
+    type Error = String;
+    fn try_from(cell_id: HttpCellId) -> Result<Self, Self::Error> {
+        let dna_hash = DnaHash::try_from(cell_id.dna_hash.as_str())
+            .map_err(|e| format!("Invalid dna_hash: {}", e))?;
+        let agent = AgentPubKey::try_from(cell_id.agent_pub_key.as_str())
+            .map_err(|e| format!("Invalid agent_pub_key: {}", e))?;
+        Ok(CellId::new(dna_hash, agent))
+    }
+}
+
+impl TryFrom<HttpZomeCall> for ZomeCallInvocation {
+    type Error = String;
+    fn try_from(call: HttpZomeCall) -> Result<Self, Self::Error> {
+        let cap = call.cap.map(|cap| decode_cap_secret(&cap)).transpose()?;
+        let payload = holochain_serialized_bytes::encode(&call.payload)
+            .map_err(|e| format!("Invalid payload: {}", e))?;
+        let provenance = AgentPubKey::try_from(call.provenance.as_str())
+            .map_err(|e| format!("Invalid provenance: {}", e))?;
+        Ok(ZomeCallInvocation {
+            cell_id: CellId::try_from(call.cell_id)?,
+            zome_name: call.zome_name.into(),
+            cap,
+            fn_name: call.fn_name.into(),
+            payload: ExternInput::new(SerializedBytes::from(UnsafeBytes::from(payload))),
+            provenance,
+        })
+    }
+}
+
+impl From<InstalledApp> for HttpAppInfo {
+    fn from(app: InstalledApp) -> Self {
+        Self {
+            app_id: app.app_id,
+            cell_data: app
+                .cell_data
+                .iter()
+                .map(|cell| HttpInstalledCell {
+                    cell_id: cell.as_id().into(),
+                    cell_nick: cell.as_nick().clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn decode_cap_secret(cap: &str) -> Result<CapSecret, String> {
+    let bytes = base64::decode(cap).map_err(|e| format!("Invalid cap: {}", e))?;
+    if bytes.len() != CAP_SECRET_BYTES {
+        return Err(format!("Invalid cap: expected {} bytes", CAP_SECRET_BYTES));
+    }
+    let mut secret = [0; CAP_SECRET_BYTES];
+    secret.copy_from_slice(&bytes);
+    Ok(secret.into())
+}
+
+/// Transcode a zome function's messagepack output to JSON.
+/// Binary data becomes an array of numbers.
+fn output_to_json(output: ExternOutput) -> Result<serde_json::Value, String> {
+    let bytes: SerializedBytes = output.into_inner();
+    let mut deserializer = rmp_serde::Deserializer::new(&bytes.bytes()[..]);
+    serde_transcode::transcode(&mut deserializer, serde_json::value::Serializer)
+        .map_err(|e| format!("Failed to convert the zome output to json: {}", e))
+}
+
+pub(super) fn json_response<T: Serialize>(status: StatusCode, body: &T) -> Response<Body> {
+    let mut response = Response::new(Body::from(
+        serde_json::to_vec(body).expect("Values always serialize to json"),
+    ));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        hyper::header::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// Create an App interface which serves zome calls and app info over HTTP
+pub async fn spawn_http_app_interface_task<A>(
+    port: u16,
+    api: A,
+    mut stop_rx: StopReceiver,
+) -> InterfaceResult<(u16, ManagedTaskHandle)>
+where
+    A: InterfaceApi<ApiRequest = AppRequest, ApiResponse = AppResponse>,
+{
+    trace!("Initializing HTTP App interface");
+    let incoming = AddrIncoming::bind(&SocketAddr::from(([127, 0, 0, 1], port)))?;
+    let port = incoming.local_addr().port();
+    let make_service = make_service_fn(move |_| {
+        let api = api.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_http_request(api.clone(), port, request)
+            }))
+        }
+    });
+    let server = Server::builder(incoming).serve(make_service);
+    trace!("LISTENING AT: {}", server.local_addr());
+    let task = tokio::task::spawn(async move {
+        let server = server.with_graceful_shutdown(async move {
+            stop_rx.recv().await.ok();
+        });
+        if let Err(e) = server.await {
+            error!(
+                error = &e as &dyn std::error::Error,
+                "HTTP App interface failed"
+            );
+        }
+        ManagedTaskResult::Ok(())
+    });
+    Ok((port, task))
+}
+
+async fn handle_http_request<A>(
+    api: A,
+    port: u16,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible>
+where
+    A: InterfaceApi<ApiRequest = AppRequest, ApiResponse = AppResponse>,
+{
+    let response = match check_origin(&request, port) {
+        Err(e) => Err(e),
+        Ok(()) => match (request.method(), request.uri().path()) {
+            (&Method::POST, "/zome-call") => zome_call(api, request).await,
+            (&Method::GET, "/app-info") => app_info(api, request).await,
+            _ => Err(HttpError::NotFound),
+        },
+    };
+    Ok(response.unwrap_or_else(HttpError::into_response))
+}
+
+/// Check the request is addressed to one of the [ALLOWED_HOSTS] on `port`,
+/// and if it has an Origin, that it's one of them too
+fn check_origin(request: &Request<Body>, port: u16) -> Result<(), HttpError> {
+    let allowed = |authority: &str| {
+        ALLOWED_HOSTS
+            .iter()
+            .any(|host| authority == format!("{}:{}", host, port))
+    };
+    let host = request
+        .headers()
+        .get(hyper::header::HOST)
+        .and_then(|host| host.to_str().ok());
+    if !host.map(allowed).unwrap_or(false) {
+        return Err(HttpError::ForbiddenOrigin);
+    }
+    if let Some(origin) = request.headers().get(hyper::header::ORIGIN) {
+        let origin = origin
+            .to_str()
+            .ok()
+            .and_then(|origin| origin.parse::<Uri>().ok());
+        let allowed = origin
+            .filter(|origin| origin.scheme_str() == Some("http"))
+            .and_then(|origin| origin.authority().map(|a| allowed(a.as_str())))
+            .unwrap_or(false);
+        if !allowed {
+            return Err(HttpError::ForbiddenOrigin);
+        }
+    }
+    Ok(())
+}
+
+/// Check the body is JSON, then read it, up to [MAX_BODY_BYTES]
+async fn read_json_body(request: Request<Body>) -> Result<Vec<u8>, HttpError> {
+    let is_json = request
+        .headers()
+        .get(hyper::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+        .unwrap_or(false);
+    if !is_json {
+        return Err(HttpError::UnsupportedMediaType);
+    }
+    let mut body = request.into_body();
+    if body.size_hint().lower() > MAX_BODY_BYTES as u64 {
+        return Err(HttpError::PayloadTooLarge);
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| HttpError::BadRequest(e.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(HttpError::PayloadTooLarge);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+async fn zome_call<A>(api: A, request: Request<Body>) -> Result<Response<Body>, HttpError>
+where
+    A: InterfaceApi<ApiRequest = AppRequest, ApiResponse = AppResponse>,
+{
+    let body = read_json_body(request).await?;
+    let call: HttpZomeCall =
+        serde_json::from_slice(&body).map_err(|e| HttpError::BadRequest(e.to_string()))?;
+    let invocation = ZomeCallInvocation::try_from(call).map_err(HttpError::BadRequest)?;
+    match api
+        .handle_request(Ok(AppRequest::ZomeCallInvocation(Box::new(invocation))))
+        .await?
+    {
+        AppResponse::ZomeCallInvocation(output) => {
+            let output = output_to_json(*output)
+                .map_err(|e| HttpError::Conductor(ExternalApiWireError::internal(e)))?;
+            Ok(json_response(StatusCode::OK, &output))
+        }
+        AppResponse::ZomeCallUnauthorized => Err(HttpError::Unauthorized),
+        AppResponse::Error(e) => Err(HttpError::Conductor(e)),
+        r => Err(InterfaceError::UnexpectedMessage(format!("{:?}", r)).into()),
+    }
+}
+
+async fn app_info<A>(api: A, request: Request<Body>) -> Result<Response<Body>, HttpError>
+where
+    A: InterfaceApi<ApiRequest = AppRequest, ApiResponse = AppResponse>,
+{
+    let app_id = request
+        .uri()
+        .query()
+        .and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "app_id")
+                .map(|(_, app_id)| app_id.into_owned())
+        })
+        .ok_or_else(|| HttpError::BadRequest("Missing app_id".into()))?;
+    match api
+        .handle_request(Ok(AppRequest::AppInfo { app_id }))
+        .await?
+    {
+        AppResponse::AppInfo(app) => Ok(json_response(StatusCode::OK, &app.map(HttpAppInfo::from))),
+        AppResponse::Error(e) => Err(HttpError::Conductor(e)),
+        r => Err(InterfaceError::UnexpectedMessage(format!("{:?}", r)).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixt::prelude::*;
+    use holo_hash::fixt::{AgentPubKeyFixturator, DnaHashFixturator};
+    use holochain_types::app::InstalledCell;
+
+    fn cell_id() -> CellId {
+        CellId::new(fixt!(DnaHash), fixt!(AgentPubKey))
+    }
+
+    #[test]
+    fn cell_ids_round_trip_through_base64() {
+        let cell_id = cell_id();
+        let http_cell_id = HttpCellId::from(&cell_id);
+        assert!(http_cell_id.dna_hash.starts_with("uhC0k"));
+        assert_eq!(CellId::try_from(http_cell_id).unwrap(), cell_id);
+
+        let bad = HttpCellId {
+            dna_hash: "not a hash".into(),
+            agent_pub_key: cell_id.agent_pubkey().to_string(),
+        };
+        assert!(CellId::try_from(bad).is_err());
+    }
+
+    #[test]
+    fn zome_calls_convert_from_json() {
+        let cell_id = cell_id();
+        let json = serde_json::json!({
+            "cell_id": HttpCellId::from(&cell_id),
+            "zome_name": "foo",
+            "fn_name": "bar",
+            "cap": base64::encode(&[1; CAP_SECRET_BYTES][..]),
+            "payload": { "a": [1, 2, 3] },
+            "provenance": cell_id.agent_pubkey().to_string(),
+        });
+        let call: HttpZomeCall = serde_json::from_value(json).unwrap();
+        let invocation = ZomeCallInvocation::try_from(call.clone()).unwrap();
+        assert_eq!(invocation.cell_id, cell_id);
+        assert_eq!(invocation.zome_name, "foo".into());
+        assert_eq!(invocation.cap, Some(CapSecret::from([1; CAP_SECRET_BYTES])));
+        assert_eq!(&invocation.provenance, cell_id.agent_pubkey());
+        let payload: serde_json::Value =
+            holochain_serialized_bytes::decode(invocation.payload.into_inner().bytes()).unwrap();
+        assert_eq!(payload, call.payload);
+
+        let short_cap = HttpZomeCall {
+            cap: Some(base64::encode(&[1; 4])),
+            ..call
+        };
+        assert!(ZomeCallInvocation::try_from(short_cap).is_err());
+    }
+
+    fn request(headers: &[(&str, &str)], body: Body) -> Request<Body> {
+        let mut request = Request::post("/zome-call");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.body(body).unwrap()
+    }
+
+    #[test]
+    fn only_loopback_hosts_and_origins_are_allowed() {
+        let allowed =
+            |headers: &[(&str, &str)]| check_origin(&request(headers, Body::empty()), 8888).is_ok();
+        assert!(allowed(&[("host", "localhost:8888")]));
+        assert!(allowed(&[
+            ("host", "127.0.0.1:8888"),
+            ("origin", "http://localhost:8888")
+        ]));
+        // no host, or a rebound domain
+        assert!(!allowed(&[]));
+        assert!(!allowed(&[("host", "evil.com:8888")]));
+        assert!(!allowed(&[("host", "localhost:9999")]));
+        // a page on another site
+        assert!(!allowed(&[
+            ("host", "localhost:8888"),
+            ("origin", "https://evil.com")
+        ]));
+        assert!(!allowed(&[("host", "localhost:8888"), ("origin", "null")]));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn zome_call_bodies_must_be_small_json() {
+        let json = request(
+            &[("content-type", "application/json; charset=utf-8")],
+            "{}".into(),
+        );
+        assert_eq!(read_json_body(json).await.unwrap(), b"{}".to_vec());
+        let text = request(&[("content-type", "text/plain")], "{}".into());
+        assert!(matches!(
+            read_json_body(text).await,
+            Err(HttpError::UnsupportedMediaType)
+        ));
+        let huge = request(
+            &[("content-type", "application/json")],
+            vec![0; MAX_BODY_BYTES + 1].into(),
+        );
+        assert!(matches!(
+            read_json_body(huge).await,
+            Err(HttpError::PayloadTooLarge)
+        ));
+    }
+
+    #[test]
+    fn binary_output_becomes_json_arrays() {
+        // Hashes serialize as messagepack binary
+        let dna_hash = fixt!(DnaHash);
+        let bytes = holochain_serialized_bytes::encode(&dna_hash).unwrap();
+        let output = ExternOutput::new(SerializedBytes::from(UnsafeBytes::from(bytes)));
+        let json = output_to_json(output).unwrap();
+        assert_eq!(json["hash"], serde_json::json!(dna_hash.get_full_bytes()));
+    }
+
+    #[test]
+    fn app_info_uses_base64_hashes() {
+        let cell_id = cell_id();
+        let app = InstalledApp {
+            app_id: "app".into(),
+            cell_data: vec![InstalledCell::new(cell_id.clone(), "nick".into())],
+        };
+        let json = serde_json::to_value(HttpAppInfo::from(app)).unwrap();
+        assert_eq!(
+            json["cell_data"][0]["cell_id"]["agent_pub_key"],
+            serde_json::Value::String(cell_id.agent_pubkey().to_string())
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Which apps' signals each app interface receives.
+//!
+//! An app interface attached for particular apps only receives the signals
+//! emitted by those apps' Cells. One attached without any apps receives
+//! every signal. Bindings are recorded by port when an interface is
+//! attached, and can be listed and changed over the admin interface.
+
+use holochain_types::{app::AppId, cell::CellId};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// The apps an app interface receives signals from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AppInterfaceBinding {
+    /// The port the app interface is attached on
+    pub port: u16,
+    /// The apps whose signals it receives, or `None` for every app
+    pub app_ids: Option<Vec<AppId>>,
+}
+
+/// The routing table shared by the Conductor and its app interfaces
+#[derive(Clone, Default)]
+pub struct SignalRoutes(Arc<RwLock<RoutingTable>>);
+
+#[derive(Default)]
+struct RoutingTable {
+    /// The apps each app interface receives signals from, by port.
+    /// `None` receives signals from every app.
+    bindings: HashMap<u16, Option<HashSet<AppId>>>,
+    /// The app each running Cell belongs to
+    cell_apps: HashMap<CellId, AppId>,
+}
+
+impl SignalRoutes {
+    /// Record the apps a newly attached app interface receives signals from
+    pub fn add_interface(&self, port: u16, app_ids: Option<Vec<AppId>>) {
+        self.0
+            .write()
+            .bindings
+            .insert(port, app_ids.map(|ids| ids.into_iter().collect()));
+    }
+
+    /// Change the apps an app interface receives signals from.
+    /// Returns false if no app interface is attached on the port.
+    pub fn bind(&self, port: u16, app_ids: Option<Vec<AppId>>) -> bool {
+        match self.0.write().bindings.get_mut(&port) {
+            Some(binding) => {
+                *binding = app_ids.map(|ids| ids.into_iter().collect());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every app interface's binding, ordered by port
+    pub fn bindings(&self) -> Vec<AppInterfaceBinding> {
+        let mut bindings: Vec<_> = self
+            .0
+            .read()
+            .bindings
+            .iter()
+            .map(|(port, app_ids)| AppInterfaceBinding {
+                port: *port,
+                app_ids: app_ids.as_ref().map(|ids| {
+                    let mut ids: Vec<_> = ids.iter().cloned().collect();
+                    ids.sort();
+                    ids
+                }),
+            })
+            .collect();
+        bindings.sort_by_key(|binding| binding.port);
+        bindings
+    }
+
+    /// Record which app some newly running Cells belong to
+    #[allow(clippy::ptr_arg)]
+    pub fn add_cells(&self, app_id: &AppId, cell_ids: impl IntoIterator<Item = CellId>) {
+        let mut table = self.0.write();
+        for cell_id in cell_ids {
+            table.cell_apps.insert(cell_id, app_id.clone());
+        }
+    }
+
+    /// Forget Cells which have stopped running
+    pub fn remove_cells(&self, cell_ids: &[CellId]) {
+        let mut table = self.0.write();
+        for cell_id in cell_ids {
+            table.cell_apps.remove(cell_id);
+        }
+    }
+
+    /// Should the app interface on this port receive a signal from this Cell?
+    pub fn routes_to(&self, port: u16, cell_id: &CellId) -> bool {
+        let table = self.0.read();
+        match table.bindings.get(&port) {
+            Some(None) => true,
+            Some(Some(app_ids)) => table
+                .cell_apps
+                .get(cell_id)
+                .map(|app_id| app_ids.contains(app_id))
+                .unwrap_or(false),
+            // Not attached yet, so nobody can be listening
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_types::test_utils::fake_cell_id;
+
+    #[test]
+    fn interfaces_only_get_signals_from_their_apps() {
+        let routes = SignalRoutes::default();
+        let cell_a = fake_cell_id(1);
+        let cell_b = fake_cell_id(2);
+        routes.add_cells(&"a".to_string(), vec![cell_a.clone()]);
+        routes.add_cells(&"b".to_string(), vec![cell_b.clone()]);
+
+        routes.add_interface(1000, None);
+        routes.add_interface(1001, Some(vec!["a".to_string()]));
+
+        assert!(routes.routes_to(1000, &cell_a));
+        assert!(routes.routes_to(1000, &cell_b));
+        assert!(routes.routes_to(1001, &cell_a));
+        assert!(!routes.routes_to(1001, &cell_b));
+        assert!(!routes.routes_to(1002, &cell_a));
+
+        // Rebinding takes effect straight away
+        assert!(routes.bind(1001, Some(vec!["b".to_string()])));
+        assert!(!routes.routes_to(1001, &cell_a));
+        assert!(routes.routes_to(1001, &cell_b));
+        assert!(!routes.bind(1002, None));
+
+        // Cells which have stopped don't belong to any app
+        routes.remove_cells(&[cell_b.clone()]);
+        assert!(!routes.routes_to(1001, &cell_b));
+
+        assert_eq!(
+            routes.bindings(),
+            vec![
+                AppInterfaceBinding {
+                    port: 1000,
+                    app_ids: None
+                },
+                AppInterfaceBinding {
+                    port: 1001,
+                    app_ids: Some(vec!["b".to_string()])
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Forwards signals emitted by the conductor's Cells to HTTP endpoints, so
+//! external systems can react to them without keeping a websocket open.
+//!
+//! Each signal which passes a webhook's filter is POSTed to it as JSON,
+//! one at a time and in order. If the webhook has a secret, the
+//! [SIGNATURE_HEADER] carries `sha256=<hex HMAC-SHA256 of the body>`.
+
+use super::error::{InterfaceError, InterfaceResult};
+use crate::conductor::{
+    conductor::StopReceiver,
+    config::SignalWebhookConfig,
+    manager::{ManagedTaskHandle, ManagedTaskResult},
+};
+use crate::core::signal::CellSignal;
+use hyper::{client::HttpConnector, header::CONTENT_TYPE, Body, Client, Request, Uri};
+use ring::hmac;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::*;
+
+/// The header carrying a signed request's signature
+pub const SIGNATURE_HEADER: &str = "x-holochain-signature";
+
+/// How long to wait before the first retry of a failed delivery
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Spawn a task which forwards signals to a webhook until the conductor stops
+pub fn spawn_signal_webhook_task(
+    config: SignalWebhookConfig,
+    mut signal_rx: broadcast::Receiver<CellSignal>,
+    mut stop_rx: StopReceiver,
+) -> InterfaceResult<ManagedTaskHandle> {
+    let uri: Uri = config
+        .url
+        .as_str()
+        .parse()
+        .map_err(|e| InterfaceError::Other(format!("Invalid webhook url: {}", e)))?;
+    let key = config
+        .secret
+        .as_ref()
+        .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()));
+    let client = Client::new();
+    trace!(url = %uri, "Forwarding signals to webhook");
+    Ok(tokio::task::spawn(async move {
+        loop {
+            let signal = tokio::select! {
+                _ = stop_rx.recv() => break,
+                signal = signal_rx.recv() => match signal {
+                    Ok(CellSignal { signal, .. }) => signal,
+                    Err(broadcast::RecvError::Lagged(skipped)) => {
+                        warn!(url = %uri, skipped, "Signal webhook fell behind and dropped signals");
+                        continue;
+                    }
+                    Err(broadcast::RecvError::Closed) => break,
+                },
+            };
+            if !config.filter.matches(&signal) {
+                continue;
+            }
+            match serde_json::to_vec(&signal) {
+                Ok(body) => deliver(&client, &uri, key.as_ref(), body, config.retries).await,
+                Err(e) => error!(?e, "Failed to serialize a signal for a webhook"),
+            }
+        }
+        ManagedTaskResult::Ok(())
+    }))
+}
+
+/// POST a signal to the webhook, retrying with backoff until it's accepted
+async fn deliver(
+    client: &Client<HttpConnector>,
+    uri: &Uri,
+    key: Option<&hmac::Key>,
+    body: Vec<u8>,
+    retries: u32,
+) {
+    let signature = key.map(|key| sign(key, &body));
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            tokio::time::delay_for(retry_delay(attempt - 1)).await;
+        }
+        let mut request = Request::post(uri.clone()).header(CONTENT_TYPE, "application/json");
+        if let Some(signature) = &signature {
+            request = request.header(SIGNATURE_HEADER, signature.as_str());
+        }
+        let request = request
+            .body(Body::from(body.clone()))
+            .expect("Webhook requests are always valid");
+        match client.request(request).await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => {
+                warn!(url = %uri, attempt, status = %response.status(), "Webhook rejected a signal")
+            }
+            Err(e) => warn!(url = %uri, attempt, ?e, "Failed to send a signal to a webhook"),
+        }
+    }
+    error!(url = %uri, retries, "Gave up sending a signal to a webhook");
+}
+
+/// The signature header value for a request body
+fn sign(key: &hmac::Key, body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(hmac::sign(key, body).as_ref()))
+}
+
+/// How long to wait before a retry, counting from zero
+fn retry_delay(retry: u32) -> Duration {
+    INITIAL_RETRY_DELAY * 2u32.saturating_pow(retry.min(6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conductor::config::SignalFilter;
+    use crate::core::signal::{Signal, UserSignal};
+
+    #[test]
+    fn signatures_are_hex_hmac_sha256() {
+        // RFC 4231 test case 2
+        let key = hmac::Key::new(hmac::HMAC_SHA256, b"Jefe");
+        assert_eq!(
+            sign(&key, b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn retries_back_off() {
+        assert_eq!(retry_delay(0), Duration::from_millis(500));
+        assert_eq!(retry_delay(2), Duration::from_secs(2));
+        assert_eq!(retry_delay(100), retry_delay(6));
+    }
+
+    #[test]
+    fn filters_match_signal_kinds() {
+        let user = Signal::User(UserSignal);
+        assert!(SignalFilter::All.matches(&Signal::Trace));
+        assert!(SignalFilter::All.matches(&user));
+        assert!(SignalFilter::User.matches(&user));
+        assert!(!SignalFilter::User.matches(&Signal::Trace));
+        assert!(!SignalFilter::Trace.matches(&user));
+        assert!(!SignalFilter::BasisChanged.matches(&user));
+    }
+}
@@ -1,10 +1,11 @@
 use super::error::{InterfaceError, InterfaceResult};
+use super::signal_routes::SignalRoutes;
 use crate::conductor::{
     conductor::StopReceiver,
     interface::*,
     manager::{ManagedTaskHandle, ManagedTaskResult},
 };
-use crate::core::signal::Signal;
+use crate::core::signal::CellSignal;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_websocket::{
     websocket_bind, WebsocketConfig, WebsocketListener, WebsocketMessage, WebsocketReceiver,
@@ -94,11 +95,13 @@ pub fn spawn_admin_interface_task<A: InterfaceApi>(
 }
 
 /// Create an App Interface, which includes the ability to receive signals
-/// from Cells via a broadcast channel
+/// from Cells via a broadcast channel. Only the signals the [SignalRoutes]
+/// route to this interface's port are sent to its clients.
 pub async fn spawn_app_interface_task<A: InterfaceApi>(
     port: u16,
     api: A,
-    signal_broadcaster: broadcast::Sender<Signal>,
+    signal_broadcaster: broadcast::Sender<CellSignal>,
+    signal_routes: SignalRoutes,
     mut stop_rx: StopReceiver,
 ) -> InterfaceResult<(u16, ManagedTaskHandle)> {
     trace!("Initializing App interface");
@@ -123,6 +126,8 @@ pub async fn spawn_app_interface_task<A: InterfaceApi>(
                     recv_socket,
                     signal_rx,
                     send_socket,
+                    port,
+                    signal_routes.clone(),
                 )));
             };
 
@@ -181,8 +186,10 @@ async fn recv_incoming_admin_msgs<A: InterfaceApi>(api: A, mut recv_socket: Webs
 async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
     api: A,
     mut recv_socket: WebsocketReceiver,
-    mut signal_rx: broadcast::Receiver<Signal>,
+    mut signal_rx: broadcast::Receiver<CellSignal>,
     mut signal_tx: WebsocketSender,
+    port: u16,
+    signal_routes: SignalRoutes,
 ) -> InterfaceResult<()> {
     trace!("CONNECTION: {}", recv_socket.remote_addr());
 
@@ -192,10 +199,13 @@ async fn recv_incoming_msgs_and_outgoing_signals<A: InterfaceApi>(
             // across the interface
             signal = signal_rx.next() => {
                 if let Some(signal) = signal {
-                    let bytes = SerializedBytes::try_from(
-                        signal.map_err(InterfaceError::SignalReceive)?,
-                    )?;
-                    signal_tx.signal(bytes).await?;
+                    let CellSignal { cell_id, signal } =
+                        signal.map_err(InterfaceError::SignalReceive)?;
+                    // Skip signals from apps this interface isn't for
+                    if signal_routes.routes_to(port, &cell_id) {
+                        let bytes = SerializedBytes::try_from(signal)?;
+                        signal_tx.signal(bytes).await?;
+                    }
                 } else {
                     debug!("Closing interface: signal stream empty");
                     break;
@@ -238,9 +248,13 @@ where
 pub mod test {
     use super::*;
     use crate::conductor::{
-        api::{error::ExternalApiWireError, AdminRequest, AdminResponse, RealAdminInterfaceApi},
+        api::{
+            error::ExternalApiWireError, AdminInterfaceApi, AdminRequest, AdminResponse,
+            RealAdminInterfaceApi,
+        },
         conductor::ConductorBuilder,
         dna_store::MockDnaStore,
+        interface::signal_routes::AppInterfaceBinding,
         state::ConductorState,
         Conductor, ConductorHandle,
     };
@@ -605,11 +619,14 @@ pub mod test {
         let (_tmpdir, conductor_handle) = setup_admin().await;
         let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
         let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
-        let msg = AdminRequest::AttachAppInterface { port: None };
+        let msg = AdminRequest::AttachAppInterface {
+            port: None,
+            app_ids: None,
+        };
         let msg = msg.try_into().unwrap();
         let respond = |bytes: SerializedBytes| {
             let response: AdminResponse = bytes.try_into().unwrap();
-            assert_matches!(response, AdminResponse::AppInterfaceAttached{ .. });
+            assert_matches!(response, AdminResponse::AppInterfaceAttached { .. });
             async { Ok(()) }.boxed()
         };
         let respond = Box::new(respond);
@@ -619,6 +636,60 @@ pub mod test {
         shutdown.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn app_interface_bindings() {
+        observability::test_run().ok();
+        let (_tmpdir, conductor_handle) = setup_admin().await;
+        let shutdown = conductor_handle.take_shutdown_handle().await.unwrap();
+        let admin_api = RealAdminInterfaceApi::new(conductor_handle.clone());
+        let app_ids = Some(vec!["app".to_string()]);
+        let port = match admin_api
+            .handle_admin_request(AdminRequest::AttachAppInterface {
+                port: None,
+                app_ids: app_ids.clone(),
+            })
+            .await
+        {
+            AdminResponse::AppInterfaceAttached { port } => port,
+            r => panic!("Unexpected response {:?}", r),
+        };
+        let response = admin_api
+            .handle_admin_request(AdminRequest::ListAppInterfaceBindings)
+            .await;
+        assert_matches!(
+            response,
+            AdminResponse::AppInterfaceBindingsListed(bindings)
+                if bindings == vec![AppInterfaceBinding { port, app_ids }]
+        );
+
+        let response = admin_api
+            .handle_admin_request(AdminRequest::BindAppInterface {
+                port,
+                app_ids: None,
+            })
+            .await;
+        assert_matches!(response, AdminResponse::AppInterfaceBound);
+        assert_eq!(
+            conductor_handle.list_app_interface_bindings().await,
+            vec![AppInterfaceBinding {
+                port,
+                app_ids: None
+            }]
+        );
+
+        // Nothing is attached on this port
+        let response = admin_api
+            .handle_admin_request(AdminRequest::BindAppInterface {
+                port: port.wrapping_add(1),
+                app_ids: None,
+            })
+            .await;
+        assert_matches!(response, AdminResponse::Error(_));
+
+        conductor_handle.shutdown().await;
+        shutdown.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn dump_state() {
         observability::test_run().ok();
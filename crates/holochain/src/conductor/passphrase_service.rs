@@ -0,0 +1,115 @@
+//! Getting the passphrase which unlocks the keystore, from wherever the
+//! [PassphraseServiceConfig] says it comes from.
+
+use crate::conductor::{
+    config::PassphraseServiceConfig,
+    error::{ConductorError, ConductorResult},
+    interactive,
+};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// The message sent over a unix socket to ask the UI for the passphrase
+const UNIX_SOCKET_REQUEST: &[u8] = b"request_passphrase\n";
+
+/// Get the passphrase at startup.
+///
+/// Returns None if the passphrase is to be given later by the
+/// `UnlockKeystore` admin call, leaving the keystore locked.
+pub async fn request_passphrase(
+    config: &PassphraseServiceConfig,
+) -> ConductorResult<Option<String>> {
+    let passphrase = match config {
+        PassphraseServiceConfig::Cmd => {
+            tokio::task::spawn_blocking(interactive::prompt_for_passphrase).await??
+        }
+        PassphraseServiceConfig::UnixSocket { path } => {
+            let mut stream = tokio::net::UnixStream::connect(path).await?;
+            stream.write_all(UNIX_SOCKET_REQUEST).await?;
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).await?;
+            first_line(&line)
+        }
+        PassphraseServiceConfig::Mock { passphrase } => passphrase.clone(),
+        PassphraseServiceConfig::File { path } => {
+            first_line(&tokio::fs::read_to_string(path).await?)
+        }
+        PassphraseServiceConfig::Env { var } => std::env::var(var).map_err(|e| {
+            ConductorError::ConfigError(format!(
+                "Couldn't read the keystore passphrase from ${}: {}",
+                var, e
+            ))
+        })?,
+        PassphraseServiceConfig::Admin => return Ok(None),
+    };
+    Ok(Some(passphrase))
+}
+
+/// The passphrase is everything up to the first newline
+fn first_line(s: &str) -> String {
+    s.lines().next().unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn passphrase_sources() {
+        let mock = PassphraseServiceConfig::Mock {
+            passphrase: "mock".to_string(),
+        };
+        assert_eq!(
+            request_passphrase(&mock).await.unwrap(),
+            Some("mock".into())
+        );
+
+        let tmp = TempDir::new("passphrase").unwrap();
+        let path = tmp.path().join("passphrase");
+        std::fs::write(&path, "from a file\n").unwrap();
+        let file = PassphraseServiceConfig::File { path };
+        assert_eq!(
+            request_passphrase(&file).await.unwrap(),
+            Some("from a file".into())
+        );
+
+        let var = "HOLOCHAIN_TEST_KEYSTORE_PASSPHRASE".to_string();
+        std::env::set_var(&var, "from the env");
+        let env = PassphraseServiceConfig::Env { var: var.clone() };
+        assert_eq!(
+            request_passphrase(&env).await.unwrap(),
+            Some("from the env".into())
+        );
+        std::env::remove_var(&var);
+        assert!(request_passphrase(&env).await.is_err());
+
+        // left for the admin call
+        assert_eq!(
+            request_passphrase(&PassphraseServiceConfig::Admin)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn passphrase_over_a_unix_socket() {
+        let tmp = TempDir::new("passphrase").unwrap();
+        let path = tmp.path().join("passphrase.sock");
+        let mut listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let ui = tokio::task::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = tokio::io::split(stream);
+            let mut request = String::new();
+            BufReader::new(read).read_line(&mut request).await.unwrap();
+            assert_eq!(request.as_bytes(), UNIX_SOCKET_REQUEST);
+            write.write_all(b"from the ui\n").await.unwrap();
+        });
+        let socket = PassphraseServiceConfig::UnixSocket { path };
+        assert_eq!(
+            request_passphrase(&socket).await.unwrap(),
+            Some("from the ui".into())
+        );
+        ui.await.unwrap();
+    }
+}
@@ -0,0 +1,136 @@
+//! Helpers for spinning up throwaway conductors for local development.
+//!
+//! Each sandbox conductor gets its own directory containing a generated
+//! `conductor-config.toml` and LMDB environment, uses the test keystore,
+//! and listens on OS-assigned admin and app ports. This is what backs the
+//! `hc-sandbox` binary.
+
+use super::{
+    config::{AdminInterfaceConfig, ConductorConfig, InterfaceDriver},
+    error::{ConductorError, ConductorResult},
+    paths::EnvironmentRootPath,
+    Conductor, ConductorHandle,
+};
+use holo_hash::AgentPubKey;
+use holochain_keystore::KeystoreSenderExt;
+use holochain_types::{
+    app::{AppId, InstalledCell},
+    cell::CellId,
+    dna::DnaFile,
+};
+use std::path::{Path, PathBuf};
+
+const CONFIG_FILENAME: &str = "conductor-config.toml";
+const ENVIRONMENT_DIRECTORY: &str = "databases";
+
+/// A running sandbox conductor, with the details needed to connect to it
+pub struct SandboxConductor {
+    /// Handle to the running conductor
+    pub handle: ConductorHandle,
+    /// The directory holding this conductor's config and databases
+    pub root: PathBuf,
+    /// The port of the conductor's admin interface
+    pub admin_port: u16,
+    /// The port of the conductor's app interface
+    pub app_port: u16,
+    /// The agent which was generated for this conductor
+    pub agent_key: AgentPubKey,
+    /// The Cell created for the sandbox app
+    pub cell_id: CellId,
+}
+
+/// Build the config used by sandbox conductors rooted at `root`
+pub fn sandbox_config(root: &Path) -> ConductorConfig {
+    ConductorConfig {
+        environment_path: EnvironmentRootPath::from(root.join(ENVIRONMENT_DIRECTORY)),
+        use_dangerous_test_keystore: true,
+        admin_interfaces: Some(vec![AdminInterfaceConfig {
+            driver: InterfaceDriver::Websocket { port: 0 },
+            tokens: vec![],
+        }]),
+        ..Default::default()
+    }
+}
+
+/// Write a sandbox config into `root`, start a conductor from it,
+/// generate an agent, install and activate `dna` as a single-cell app,
+/// and attach an app interface.
+pub async fn spawn_sandbox_conductor(
+    root: PathBuf,
+    dna: DnaFile,
+    app_id: AppId,
+) -> ConductorResult<SandboxConductor> {
+    let config = sandbox_config(&root);
+    std::fs::create_dir_all(config.environment_path.as_ref())?;
+    std::fs::write(root.join(CONFIG_FILENAME), toml::to_string_pretty(&config)?)?;
+
+    let handle = Conductor::builder().config(config).build().await?;
+    let admin_port = handle
+        .get_arbitrary_admin_websocket_port()
+        .await
+        .ok_or_else(|| ConductorError::ConfigError("No admin interface was started".into()))?;
+
+    let agent_key = handle
+        .keystore()
+        .clone()
+        .generate_sign_keypair_from_pure_entropy()
+        .await?;
+    let cell_id = CellId::new(dna.dna_hash().clone(), agent_key.clone());
+    handle.install_dna(dna).await?;
+    handle
+        .clone()
+        .install_app(
+            app_id.clone(),
+            vec![(InstalledCell::new(cell_id.clone(), app_id.clone()), None)],
+        )
+        .await?;
+    handle.activate_app(app_id).await?;
+    if let Some(error) = handle.clone().setup_cells().await?.into_iter().next() {
+        return Err(error.into());
+    }
+    let app_port = handle.clone().add_app_interface(0, None).await?;
+
+    Ok(SandboxConductor {
+        handle,
+        root,
+        admin_port,
+        app_port,
+        agent_key,
+        cell_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_types::test_utils::fake_dna_zomes;
+    use holochain_wasm_test_utils::TestWasm;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn spawn_two_sandboxes() {
+        let tmpdir = tempdir::TempDir::new("sandbox").unwrap();
+        let dna = fake_dna_zomes(
+            &uuid::Uuid::new_v4().to_string(),
+            vec![(TestWasm::Foo.into(), TestWasm::Foo.into())],
+        );
+
+        let mut sandboxes = Vec::new();
+        for i in 0..2 {
+            let root = tmpdir.path().join(format!("conductor-{}", i));
+            sandboxes.push(
+                spawn_sandbox_conductor(root, dna.clone(), "sandbox".into())
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        assert_ne!(sandboxes[0].admin_port, sandboxes[1].admin_port);
+        assert_ne!(sandboxes[0].agent_key, sandboxes[1].agent_key);
+        for sandbox in sandboxes {
+            assert!(sandbox.root.join(CONFIG_FILENAME).is_file());
+            let cells = sandbox.handle.list_cell_ids().await.unwrap();
+            assert_eq!(cells, vec![sandbox.cell_id.clone()]);
+            sandbox.handle.shutdown().await;
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Shares zome call execution fairly between the apps on a conductor.
+//!
+//! Only so many zome calls from app interfaces run at once. When the
+//! conductor is busy, further calls wait their turn in a queue per app,
+//! and the apps take turns in weighted round robin: an app with a weight
+//! of 3 gets up to three calls started for each one started for an app
+//! with a weight of 1. So one chatty app's UI can't starve the others,
+//! however many calls it queues up.
+//!
+//! Zome calls made from within a zome call, and calls from remote agents,
+//! don't wait for a turn.
+
+use holochain_types::app::AppId;
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tokio::sync::oneshot;
+
+/// How many zome calls run at once if the config doesn't say
+pub const DEFAULT_MAX_CONCURRENT_ZOME_CALLS: usize = 32;
+
+/// Hands out turns to run zome calls. Clones share the same queues.
+#[derive(Clone)]
+pub struct ZomeCallScheduler(Arc<Mutex<Schedule>>);
+
+struct Schedule {
+    /// How many calls may run at once
+    max_running: usize,
+    /// How many calls are running
+    running: usize,
+    /// How many turns in a row each app gets, by app id.
+    /// Apps not listed get one.
+    weights: HashMap<AppId, u32>,
+    /// The apps with calls waiting, in the order they take turns
+    turns: VecDeque<AppId>,
+    /// The calls waiting for each app
+    waiting: HashMap<AppId, AppQueue>,
+}
+
+struct AppQueue {
+    waiters: VecDeque<oneshot::Sender<ZomeCallTurn>>,
+    /// How many more calls the app may start before the next app's go
+    credit: u32,
+}
+
+/// The turns for one app's zome calls
+#[derive(Clone)]
+pub struct AppZomeCalls {
+    scheduler: ZomeCallScheduler,
+    app_id: AppId,
+}
+
+/// A zome call may run while this is held.
+/// Dropping it gives the turn to the next call waiting.
+pub struct ZomeCallTurn(Option<ZomeCallScheduler>);
+
+impl ZomeCallScheduler {
+    /// Run at most `max_running` calls at once, sharing them out
+    /// between apps by their weights
+    pub fn new(max_running: usize, weights: HashMap<AppId, u32>) -> Self {
+        Self(Arc::new(Mutex::new(Schedule {
+            max_running: max_running.max(1),
+            running: 0,
+            weights,
+            turns: VecDeque::new(),
+            waiting: HashMap::new(),
+        })))
+    }
+
+    /// The turns for an app's zome calls
+    pub fn for_app(&self, app_id: AppId) -> AppZomeCalls {
+        AppZomeCalls {
+            scheduler: self.clone(),
+            app_id,
+        }
+    }
+
+    /// Wait for a turn to run one of an app's calls
+    pub async fn turn(&self, app_id: &AppId) -> ZomeCallTurn {
+        let rx = {
+            let mut schedule = self.0.lock();
+            if schedule.turns.is_empty() && schedule.running < schedule.max_running {
+                schedule.running += 1;
+                return ZomeCallTurn(Some(self.clone()));
+            }
+            let (tx, rx) = oneshot::channel();
+            schedule.enqueue(app_id, tx);
+            rx
+        };
+        // Turns are only handed out to receivers which are still waiting,
+        // and the scheduler is kept alive by this handle
+        rx.await
+            .expect("The scheduler never drops a waiter without a turn")
+    }
+
+    /// Give a finished call's turn to the next call waiting
+    fn release(&self) {
+        let mut schedule = self.0.lock();
+        while let Some(tx) = schedule.next_waiter() {
+            match tx.send(ZomeCallTurn(Some(self.clone()))) {
+                Ok(()) => return,
+                // The caller gave up waiting, so the turn goes to the next
+                // call rather than being released again when it's dropped
+                Err(mut turn) => {
+                    turn.0.take();
+                }
+            }
+        }
+        schedule.running -= 1;
+    }
+}
+
+impl Schedule {
+    fn enqueue(&mut self, app_id: &AppId, tx: oneshot::Sender<ZomeCallTurn>) {
+        if !self.waiting.contains_key(app_id) {
+            let credit = self.weight(app_id);
+            self.waiting.insert(
+                app_id.clone(),
+                AppQueue {
+                    waiters: VecDeque::new(),
+                    credit,
+                },
+            );
+            self.turns.push_back(app_id.clone());
+        }
+        if let Some(queue) = self.waiting.get_mut(app_id) {
+            queue.waiters.push_back(tx);
+        }
+    }
+
+    fn weight(&self, app_id: &AppId) -> u32 {
+        self.weights.get(app_id).copied().unwrap_or(1).max(1)
+    }
+
+    /// The next call to start, moving on to the next app once
+    /// this one has had as many turns as its weight
+    fn next_waiter(&mut self) -> Option<oneshot::Sender<ZomeCallTurn>> {
+        let app_id = self.turns.front()?.clone();
+        let weight = self.weight(&app_id);
+        let queue = self.waiting.get_mut(&app_id)?;
+        let tx = queue.waiters.pop_front();
+        queue.credit = queue.credit.saturating_sub(1);
+        if queue.waiters.is_empty() {
+            self.waiting.remove(&app_id);
+            self.turns.pop_front();
+        } else if queue.credit == 0 {
+            queue.credit = weight;
+            self.turns.rotate_left(1);
+        }
+        tx
+    }
+}
+
+impl AppZomeCalls {
+    /// Wait for a turn to run one of this app's calls
+    pub async fn turn(&self) -> ZomeCallTurn {
+        self.scheduler.turn(&self.app_id).await
+    }
+}
+
+impl Drop for ZomeCallTurn {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.0.take() {
+            scheduler.release();
+        }
+    }
+}
+
+impl std::fmt::Debug for ZomeCallScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let schedule = self.0.lock();
+        f.debug_struct("ZomeCallScheduler")
+            .field("max_running", &schedule.max_running)
+            .field("running", &schedule.running)
+            .field("waiting_apps", &schedule.turns.len())
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for AppZomeCalls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AppZomeCalls").field(&self.app_id).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    /// Start waiting for a turn for each app in order, then let one call
+    /// finish at a time and see which app goes next
+    async fn order_of_turns(scheduler: &ZomeCallScheduler, calls: &[&str]) -> Vec<String> {
+        let running = scheduler.turn(&"busy".to_string()).await;
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        for app in calls {
+            let scheduler = scheduler.clone();
+            let tx = tx.clone();
+            let app = app.to_string();
+            // Make sure each call is queued before the next
+            let (queued_tx, queued_rx) = oneshot::channel();
+            tokio::spawn(async move {
+                let turn = {
+                    let turn = scheduler.turn(&app);
+                    futures::pin_mut!(turn);
+                    assert!((&mut turn).now_or_never().is_none());
+                    queued_tx.send(()).ok();
+                    turn.await
+                };
+                tx.send((app, turn)).ok();
+            });
+            queued_rx.await.unwrap();
+        }
+        drop(running);
+        let mut order = Vec::new();
+        for _ in calls {
+            let (app, turn) = rx.recv().await.unwrap();
+            order.push(app);
+            drop(turn);
+        }
+        order
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn apps_take_turns_by_weight() {
+        let scheduler = ZomeCallScheduler::new(1, HashMap::new());
+        let order = order_of_turns(&scheduler, &["chatty", "chatty", "chatty", "quiet"]).await;
+        assert_eq!(order, vec!["chatty", "quiet", "chatty", "chatty"]);
+
+        let mut weights = HashMap::new();
+        weights.insert("chatty".to_string(), 2);
+        let scheduler = ZomeCallScheduler::new(1, weights);
+        let order = order_of_turns(
+            &scheduler,
+            &["chatty", "chatty", "chatty", "quiet", "quiet"],
+        )
+        .await;
+        assert_eq!(order, vec!["chatty", "chatty", "quiet", "chatty", "quiet"]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn calls_run_straight_away_when_not_busy() {
+        let scheduler = ZomeCallScheduler::new(2, HashMap::new());
+        let app = "app".to_string();
+        let first = scheduler.turn(&app).await;
+        let _second = scheduler.turn(&app).await;
+        assert!(scheduler.turn(&app).now_or_never().is_none());
+
+        // A caller which gives up waiting doesn't hold on to a turn
+        drop(first);
+        assert_eq!(scheduler.0.lock().running, 1);
+        let _third = scheduler.turn(&app).now_or_never().unwrap();
+        assert_eq!(scheduler.0.lock().running, 2);
+    }
+}
@@ -2,14 +2,26 @@
 
 #![deny(missing_docs)]
 
+pub mod autonomic;
+pub mod cancel;
+pub mod chain_audit;
+pub mod clock;
+pub mod dht_metrics;
+pub mod dht_snapshot;
+pub mod handoff;
 pub mod net;
 pub mod nucleus;
+pub mod offline;
 pub mod queue_consumer;
+pub mod quota;
+pub mod redundancy;
 #[allow(missing_docs)]
 pub mod ribosome;
 #[allow(missing_docs)]
 pub mod signal;
 pub mod state;
+pub mod subconscious;
+pub mod validation_package;
 #[allow(missing_docs)]
 pub mod workflow;
 
@@ -0,0 +1,156 @@
+//! The schedule on which each Cell's [AutonomicProcess]es run.
+//!
+//! Every Cell has a task which cues its autonomic processes as their
+//! intervals come round, see [queue_consumer]. Each interval is
+//! counted from when the process last ran, so a slow run, or a Cell which
+//! was busy, pushes the next run back rather than causing a burst of runs.
+//!
+//! [queue_consumer]: crate::core::queue_consumer
+
+use super::{redundancy::REDUNDANCY_CHECK_INTERVAL, state::source_chain::EXPIRY_SWEEP_INTERVAL};
+use holochain_types::autonomic::AutonomicProcess;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How often slow healing runs, if not configured
+const DEFAULT_SLOW_HEAL_INTERVAL_S: u64 = 10 * 60;
+
+/// How often the storage arc is resized, if not configured
+const DEFAULT_RESIZE_ARC_INTERVAL_S: u64 = 15 * 60;
+
+/// How often each autonomic process runs, in seconds.
+/// A process whose interval is 0 only runs when it is cued.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutonomicSchedule {
+    /// Retry validation of ops which are waiting on missing dependencies.
+    /// Defaults to 10 minutes.
+    pub slow_heal_interval_s: u64,
+    /// Check how widely authored ops are held, republishing any which are
+    /// under-replicated. Defaults to 5 minutes.
+    pub health_check_interval_s: u64,
+    /// Drop stale entries from the Cell's in-memory caches.
+    /// Defaults to 1 minute.
+    pub prune_caches_interval_s: u64,
+    /// Resize the Cell's storage arc to reach its nearest neighbors.
+    /// Defaults to 15 minutes.
+    pub resize_arc_interval_s: u64,
+}
+
+impl Default for AutonomicSchedule {
+    fn default() -> Self {
+        Self {
+            slow_heal_interval_s: DEFAULT_SLOW_HEAL_INTERVAL_S,
+            health_check_interval_s: REDUNDANCY_CHECK_INTERVAL.as_secs(),
+            prune_caches_interval_s: EXPIRY_SWEEP_INTERVAL.as_secs(),
+            resize_arc_interval_s: DEFAULT_RESIZE_ARC_INTERVAL_S,
+        }
+    }
+}
+
+impl AutonomicSchedule {
+    /// The processes which run on a timer, with how often each runs
+    pub fn intervals(&self) -> Vec<(AutonomicProcess, Duration)> {
+        vec![
+            (AutonomicProcess::SlowHeal, self.slow_heal_interval_s),
+            (AutonomicProcess::HealthCheck, self.health_check_interval_s),
+            (AutonomicProcess::PruneCaches, self.prune_caches_interval_s),
+            (AutonomicProcess::ResizeArc, self.resize_arc_interval_s),
+        ]
+        .into_iter()
+        .filter(|(_, interval)| *interval > 0)
+        .map(|(process, interval)| (process, Duration::from_secs(interval)))
+        .collect()
+    }
+}
+
+/// When each scheduled process is next due
+#[derive(Clone, Debug)]
+pub struct Timetable(Vec<(AutonomicProcess, Duration, Instant)>);
+
+impl Timetable {
+    /// Each scheduled process is first due one interval after `now`
+    pub fn new(schedule: &AutonomicSchedule, now: Instant) -> Self {
+        Self(
+            schedule
+                .intervals()
+                .into_iter()
+                .map(|(process, interval)| (process, interval, now + interval))
+                .collect(),
+        )
+    }
+
+    /// When the next process is due, or None if nothing is scheduled
+    pub fn next_due(&self) -> Option<Instant> {
+        self.0.iter().map(|(_, _, due)| *due).min()
+    }
+
+    /// Take the processes which are due by `now`,
+    /// scheduling each to run again one interval after `now`
+    pub fn take_due(&mut self, now: Instant) -> Vec<AutonomicProcess> {
+        self.0
+            .iter_mut()
+            .filter(|(_, _, due)| *due <= now)
+            .map(|(process, interval, due)| {
+                *due = now + *interval;
+                *process
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_intervals_are_not_scheduled() {
+        let schedule = AutonomicSchedule {
+            slow_heal_interval_s: 0,
+            health_check_interval_s: 0,
+            prune_caches_interval_s: 30,
+            resize_arc_interval_s: 0,
+        };
+        assert_eq!(
+            schedule.intervals(),
+            vec![(AutonomicProcess::PruneCaches, Duration::from_secs(30))]
+        );
+    }
+
+    #[test]
+    fn timetable_takes_due_processes_and_reschedules_them() {
+        let schedule = AutonomicSchedule {
+            slow_heal_interval_s: 10,
+            health_check_interval_s: 30,
+            prune_caches_interval_s: 0,
+            resize_arc_interval_s: 0,
+        };
+        let start = Instant::now();
+        let mut timetable = Timetable::new(&schedule, start);
+        assert_eq!(timetable.next_due(), Some(start + Duration::from_secs(10)));
+        assert!(timetable.take_due(start).is_empty());
+
+        // Running late doesn't cause a burst of catch up runs
+        let late = start + Duration::from_secs(25);
+        assert_eq!(timetable.take_due(late), vec![AutonomicProcess::SlowHeal]);
+        assert_eq!(timetable.next_due(), Some(start + Duration::from_secs(30)));
+
+        let later = start + Duration::from_secs(35);
+        assert_eq!(
+            timetable.take_due(later),
+            vec![AutonomicProcess::SlowHeal, AutonomicProcess::HealthCheck]
+        );
+        assert_eq!(timetable.next_due(), Some(later + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn nothing_scheduled() {
+        let schedule = AutonomicSchedule {
+            slow_heal_interval_s: 0,
+            health_check_interval_s: 0,
+            prune_caches_interval_s: 0,
+            resize_arc_interval_s: 0,
+        };
+        assert_eq!(Timetable::new(&schedule, Instant::now()).next_due(), None);
+    }
+}
@@ -0,0 +1,152 @@
+//! Cancelling a zome call which is already running.
+//!
+//! A [CancelToken] is handed down through the call zome workflow, the
+//! host functions and the network fetches they make. Wasm can't be
+//! interrupted in the middle of an instruction, so every host function
+//! call is a checkpoint: once the token has fired the next host function
+//! fails, the guest unwinds and the workflow returns without committing,
+//! which releases the workspace lock. Network fetches which are in flight
+//! are abandoned straight away.
+
+use holochain_error_kind::{ErrorKind, HasErrorKind};
+use parking_lot::Mutex;
+use std::{future::Future, sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::sync::watch;
+
+/// Why a call was stopped before it finished
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum Cancelled {
+    /// Whoever made the call gave up on it
+    #[error("The call was cancelled")]
+    Requested,
+    /// The call ran for longer than its timeout
+    #[error("The call timed out")]
+    TimedOut,
+}
+
+impl HasErrorKind for Cancelled {
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            Cancelled::Requested => ErrorKind::Cancelled,
+            Cancelled::TimedOut => ErrorKind::Timeout,
+        }
+    }
+}
+
+/// Fires at most once to stop a call. Clones share the same state.
+#[derive(Clone)]
+pub struct CancelToken(Arc<Inner>);
+
+struct Inner {
+    reason: Mutex<Option<Cancelled>>,
+    tx: watch::Sender<bool>,
+    rx: watch::Receiver<bool>,
+}
+
+impl CancelToken {
+    /// A token which hasn't fired
+    pub fn new() -> Self {
+        let (tx, rx) = watch::channel(false);
+        Self(Arc::new(Inner {
+            reason: Mutex::new(None),
+            tx,
+            rx,
+        }))
+    }
+
+    /// Stop the call. Only the first reason given is kept.
+    pub fn cancel(&self, reason: Cancelled) {
+        let mut current = self.0.reason.lock();
+        if current.is_none() {
+            *current = Some(reason);
+            // The token holds a receiver, so this can't fail
+            self.0.tx.broadcast(true).ok();
+        }
+    }
+
+    /// Fire with [Cancelled::TimedOut] after `timeout`,
+    /// unless every clone of the token has been dropped by then
+    pub fn cancel_after(&self, timeout: Duration) {
+        let weak = Arc::downgrade(&self.0);
+        tokio::spawn(async move {
+            tokio::time::delay_for(timeout).await;
+            if let Some(inner) = weak.upgrade() {
+                CancelToken(inner).cancel(Cancelled::TimedOut);
+            }
+        });
+    }
+
+    /// Why the token fired, if it has
+    pub fn reason(&self) -> Option<Cancelled> {
+        *self.0.reason.lock()
+    }
+
+    /// Fail if the token has fired
+    pub fn check(&self) -> Result<(), Cancelled> {
+        match self.reason() {
+            Some(reason) => Err(reason),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait until the token fires
+    pub async fn cancelled(&self) -> Cancelled {
+        let mut rx = self.0.rx.clone();
+        while let Some(fired) = rx.recv().await {
+            if fired {
+                break;
+            }
+        }
+        self.reason().unwrap_or(Cancelled::Requested)
+    }
+
+    /// Run a future, abandoning it if the token fires first
+    pub fn or_cancel<F: Future>(&self, f: F) -> impl Future<Output = Result<F::Output, Cancelled>> {
+        let token = self.clone();
+        async move {
+            token.check()?;
+            tokio::select! {
+                output = f => Ok(output),
+                reason = token.cancelled() => Err(reason),
+            }
+        }
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CancelToken").field(&self.reason()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn first_reason_wins() {
+        let token = CancelToken::new();
+        assert_eq!(token.check(), Ok(()));
+        token.clone().cancel(Cancelled::Requested);
+        token.cancel(Cancelled::TimedOut);
+        assert_eq!(token.check(), Err(Cancelled::Requested));
+        assert_eq!(token.cancelled().await, Cancelled::Requested);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn timeout_abandons_a_future() {
+        let token = CancelToken::new();
+        token.cancel_after(Duration::from_millis(10));
+        let never = futures::future::pending::<()>();
+        assert_eq!(token.or_cancel(never).await, Err(Cancelled::TimedOut));
+        assert_eq!(token.or_cancel(async { 1 }).await, Err(Cancelled::TimedOut));
+        assert_eq!(CancelToken::new().or_cancel(async { 1 }).await, Ok(1));
+    }
+}
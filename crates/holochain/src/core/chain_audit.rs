@@ -0,0 +1,386 @@
+//! Header-only audits of source chains.
+//!
+//! A light client can check an agent's source chain without downloading any
+//! entries: the signed headers are fetched from the agent's activity
+//! authorities, and then checked locally to be signed by the agent and to
+//! form an unbroken hash chain.
+//!
+//! Polling an agent's activity keeps the verified headers in a local cache,
+//! so only the headers after the cache's watermark are fetched each time.
+
+use super::{
+    state::activity_cache::CachedActivity,
+    sys_validate::{
+        check_prev_header, check_prev_seq, check_prev_timestamp, verify_header_signature,
+    },
+};
+use holo_hash::{AgentPubKey, HeaderHash};
+use holochain_p2p::{
+    actor::{GetActivityOptions, HolochainP2pResult, PeerReport},
+    HolochainP2pCellT,
+};
+use holochain_serialized_bytes::prelude::*;
+use holochain_types::activity::{AgentActivityResponse, ChainRange};
+use holochain_zome_types::{element::SignedHeader, header::Header};
+use std::collections::{BTreeMap, HashSet};
+
+/// The first problem found in an audited source chain.
+/// Each carries the sequence number of the header it was found at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChainFault {
+    /// No authority sent a header at this position
+    Missing(u32),
+    /// Headers were sent for this position, but none were signed by the agent
+    InvalidSignature(u32),
+    /// The header doesn't reference the hash of the header before it
+    BrokenLink(u32),
+    /// The agent signed more than one header at this position
+    Fork(u32),
+    /// The header isn't a valid successor to the header before it
+    Invalid(u32, String),
+}
+
+/// The result of auditing a range of an agent's source chain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct ChainAudit {
+    /// The agent whose chain was audited
+    pub agent: AgentPubKey,
+    /// The verified headers, in sequence order.
+    /// If there is a fault, these are the headers before it.
+    pub headers: Vec<SignedHeader>,
+    /// The first fault found, if any
+    pub fault: Option<ChainFault>,
+}
+
+impl ChainAudit {
+    /// Whether the whole range was verified
+    pub fn is_valid(&self) -> bool {
+        self.fault.is_none()
+    }
+}
+
+/// Fetch the signed headers of an agent's source chain in `range` and verify
+/// them. Authorities who send headers that aren't signed by the agent are
+/// reported to the network.
+///
+/// With an open ended range the audit stops at the highest header any
+/// authority sent, so an authority withholding the head of the chain can't
+/// be detected.
+pub async fn audit_source_chain<N: HolochainP2pCellT>(
+    network: &mut N,
+    agent: AgentPubKey,
+    range: ChainRange,
+) -> HolochainP2pResult<ChainAudit> {
+    let responses = network
+        .get_agent_activity(agent.clone(), range, GetActivityOptions::default())
+        .await?;
+    let (audit, bad_peers) = audit_headers(agent, range, responses, None).await;
+    for peer in bad_peers {
+        network.report_peer(peer, PeerReport::BadResponse).await?;
+    }
+    Ok(audit)
+}
+
+/// Fetch only the headers of an agent's source chain after the watermark
+/// of the `cached` headers, and verify that they carry on from them.
+/// The audit covers the whole chain: the cached headers followed by any
+/// new headers which were verified.
+///
+/// Finding nothing new after the watermark isn't a fault.
+pub async fn poll_agent_activity<N: HolochainP2pCellT>(
+    network: &mut N,
+    agent: AgentPubKey,
+    cached: CachedActivity,
+) -> HolochainP2pResult<ChainAudit> {
+    let range = ChainRange {
+        start_seq: cached.next_seq(),
+        end_seq: None,
+    };
+    let responses = network
+        .get_agent_activity(agent.clone(), range, GetActivityOptions::default())
+        .await?;
+    let (mut audit, bad_peers) =
+        audit_headers(agent, range, responses, cached.headers.last()).await;
+    for peer in bad_peers {
+        network.report_peer(peer, PeerReport::BadResponse).await?;
+    }
+    if audit.headers.is_empty() && audit.fault == Some(ChainFault::Missing(range.start_seq)) {
+        audit.fault = None;
+    }
+    let mut headers = cached.headers;
+    headers.append(&mut audit.headers);
+    audit.headers = headers;
+    Ok(audit)
+}
+
+/// Verify the headers sent by authorities, returning the audit and the
+/// authorities who sent forged headers.
+/// If the range carries on from an already verified header then
+/// the first header must follow on from it.
+async fn audit_headers(
+    agent: AgentPubKey,
+    range: ChainRange,
+    responses: Vec<(AgentPubKey, AgentActivityResponse)>,
+    after: Option<&SignedHeader>,
+) -> (ChainAudit, HashSet<AgentPubKey>) {
+    // Distinct, correctly signed headers at each position
+    let mut signed: BTreeMap<u32, BTreeMap<HeaderHash, SignedHeader>> = BTreeMap::new();
+    let mut forged = HashSet::new();
+    let mut bad_peers = HashSet::new();
+    for (peer, response) in responses {
+        for sh in response.headers {
+            let seq = sh.0.header_seq();
+            if !range.contains(seq) {
+                continue;
+            }
+            if sh.0.author() != &agent || verify_header_signature(&sh.1, &sh.0).await.is_err() {
+                forged.insert(seq);
+                bad_peers.insert(peer.clone());
+                continue;
+            }
+            signed
+                .entry(seq)
+                .or_default()
+                .insert(HeaderHash::with_data_sync(&sh.0), sh);
+        }
+    }
+
+    let end_seq = range
+        .end_seq
+        .or_else(|| signed.keys().chain(forged.iter()).max().copied())
+        .unwrap_or(range.start_seq);
+
+    let mut headers: Vec<SignedHeader> = Vec::new();
+    let mut prev: Option<(HeaderHash, Header)> =
+        after.map(|sh| (HeaderHash::with_data_sync(&sh.0), sh.0.clone()));
+    let mut fault = None;
+    for seq in range.start_seq..=end_seq {
+        let (hash, sh) = match signed.remove(&seq) {
+            Some(at_seq) if at_seq.len() > 1 => {
+                fault = Some(ChainFault::Fork(seq));
+                break;
+            }
+            Some(at_seq) => at_seq.into_iter().next().expect("Entries are never empty"),
+            None if forged.contains(&seq) => {
+                fault = Some(ChainFault::InvalidSignature(seq));
+                break;
+            }
+            None => {
+                fault = Some(ChainFault::Missing(seq));
+                break;
+            }
+        };
+        if let Err(e) = check_prev_header(&sh.0) {
+            fault = Some(ChainFault::Invalid(seq, e.to_string()));
+            break;
+        }
+        if let Some((prev_hash, prev)) = &prev {
+            if sh.0.prev_header() != Some(prev_hash) {
+                fault = Some(ChainFault::BrokenLink(seq));
+                break;
+            }
+            if let Err(e) =
+                check_prev_seq(&sh.0, prev).and_then(|_| check_prev_timestamp(&sh.0, prev))
+            {
+                fault = Some(ChainFault::Invalid(seq, e.to_string()));
+                break;
+            }
+        }
+        prev = Some((hash, sh.0.clone()));
+        headers.push(sh);
+    }
+
+    (
+        ChainAudit {
+            agent,
+            headers,
+            fault,
+        },
+        bad_peers,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{AgentPubKeyFixturator, DnaHashFixturator, EntryHashFixturator};
+    use holochain_keystore::AgentPubKeyExt;
+    use holochain_state::test_utils::test_keystore;
+    use holochain_types::{
+        fixt::{AppEntryTypeFixturator, SignatureFixturator},
+        test_utils::{fake_agent_pubkey_1, fake_agent_pubkey_2},
+        Timestamp,
+    };
+    use holochain_zome_types::{
+        entry_def::EntryVisibility,
+        header::{Create, Dna, EntryType, Header},
+    };
+
+    fn dna() -> Header {
+        Header::Dna(Dna {
+            author: fake_agent_pubkey_1(),
+            timestamp: Timestamp(0, 0).into(),
+            hash: fixt!(DnaHash),
+        })
+    }
+
+    fn create(prev: &Header) -> Header {
+        let header_seq = prev.header_seq() + 1;
+        Header::Create(Create {
+            author: fake_agent_pubkey_1(),
+            timestamp: Timestamp(header_seq as i64, 0).into(),
+            header_seq,
+            prev_header: HeaderHash::with_data_sync(prev),
+            entry_type: EntryType::App(
+                AppEntryTypeFixturator::new(EntryVisibility::Public)
+                    .next()
+                    .unwrap(),
+            ),
+            entry_hash: fixt!(EntryHash),
+        })
+    }
+
+    async fn sign(header: Header) -> SignedHeader {
+        let signature = header
+            .author()
+            .sign(&test_keystore(), &header)
+            .await
+            .unwrap();
+        SignedHeader(header, signature)
+    }
+
+    /// A signed chain of `len` headers
+    async fn chain(len: usize) -> Vec<SignedHeader> {
+        let mut headers = vec![dna()];
+        while headers.len() < len {
+            let next = create(headers.last().unwrap());
+            headers.push(next);
+        }
+        let mut signed = Vec::new();
+        for header in headers {
+            signed.push(sign(header).await);
+        }
+        signed
+    }
+
+    fn from(peer: AgentPubKey, headers: Vec<SignedHeader>) -> (AgentPubKey, AgentActivityResponse) {
+        (peer, AgentActivityResponse { headers })
+    }
+
+    fn range(start_seq: u32, end_seq: Option<u32>) -> ChainRange {
+        ChainRange { start_seq, end_seq }
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn verifies_honest_chains() {
+        let agent = fake_agent_pubkey_1();
+        let peer = fake_agent_pubkey_2();
+        let headers = chain(4).await;
+
+        let (audit, bad_peers) = audit_headers(
+            agent.clone(),
+            range(0, None),
+            vec![from(peer.clone(), headers.clone())],
+            None,
+        )
+        .await;
+        assert!(audit.is_valid());
+        assert_eq!(audit.headers, headers);
+        assert!(bad_peers.is_empty());
+
+        // A range can start part way along the chain,
+        // and responses from different authorities are merged
+        let (audit, _) = audit_headers(
+            agent.clone(),
+            range(1, Some(3)),
+            vec![
+                from(peer.clone(), headers[1..3].to_vec()),
+                from(fixt!(AgentPubKey), headers[2..].to_vec()),
+            ],
+            None,
+        )
+        .await;
+        assert!(audit.is_valid());
+        assert_eq!(audit.headers, headers[1..].to_vec());
+
+        // Headers that weren't sent can't be verified
+        let (audit, _) = audit_headers(
+            agent,
+            range(0, Some(5)),
+            vec![from(peer, headers.clone())],
+            None,
+        )
+        .await;
+        assert_eq!(audit.fault, Some(ChainFault::Missing(4)));
+        assert_eq!(audit.headers, headers);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn new_headers_carry_on_from_the_watermark() {
+        let agent = fake_agent_pubkey_1();
+        let peer = fake_agent_pubkey_2();
+        let headers = chain(4).await;
+
+        let (audit, _) = audit_headers(
+            agent.clone(),
+            range(2, None),
+            vec![from(peer.clone(), headers[2..].to_vec())],
+            Some(&headers[1]),
+        )
+        .await;
+        assert!(audit.is_valid());
+        assert_eq!(audit.headers, headers[2..].to_vec());
+
+        // The first new header must follow the last verified one
+        let (audit, _) = audit_headers(
+            agent,
+            range(2, None),
+            vec![from(peer, headers[2..].to_vec())],
+            Some(&headers[0]),
+        )
+        .await;
+        assert_eq!(audit.fault, Some(ChainFault::BrokenLink(2)));
+        assert!(audit.headers.is_empty());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn finds_faults() {
+        let agent = fake_agent_pubkey_1();
+        let peer = fake_agent_pubkey_2();
+        let headers = chain(3).await;
+        let audit = |headers: Vec<SignedHeader>| {
+            audit_headers(
+                agent.clone(),
+                range(0, None),
+                vec![from(peer.clone(), headers)],
+                None,
+            )
+        };
+
+        // A forged signature
+        let mut forged = headers.clone();
+        forged[1].1 = fixt!(Signature);
+        let (result, bad_peers) = audit(forged).await;
+        assert_eq!(result.fault, Some(ChainFault::InvalidSignature(1)));
+        assert_eq!(result.headers, headers[..1].to_vec());
+        assert!(bad_peers.contains(&peer));
+
+        // Two headers signed at the same position
+        let mut forked = headers.clone();
+        forked.push(sign(create(&headers[1].0)).await);
+        let (result, bad_peers) = audit(forked).await;
+        assert_eq!(result.fault, Some(ChainFault::Fork(2)));
+        assert!(bad_peers.is_empty());
+
+        // A header which doesn't follow the one before
+        let mut broken = headers.clone();
+        let mut wrong_prev = create(&headers[1].0);
+        if let Header::Create(c) = &mut wrong_prev {
+            c.prev_header = HeaderHash::with_data_sync(&headers[0].0);
+        }
+        broken[2] = sign(wrong_prev).await;
+        let (result, _) = audit(broken).await;
+        assert_eq!(result.fault, Some(ChainFault::BrokenLink(2)));
+    }
+}
@@ -0,0 +1,129 @@
+//! Where a conductor gets the time and randomness from.
+//!
+//! Normally both come from the system. Tests can build a conductor with a
+//! [Clock] which only moves when it's told to, and with [Entropy] from a
+//! seeded generator, so header timestamps, publish intervals, validation
+//! backoff and random bytes are the same on every run. The test keeps a
+//! clone of the clock and advances it instead of sleeping.
+//!
+//! Timers such as call timeouts still run on tokio's clock.
+
+use holochain_types::Timestamp;
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::{sync::Arc, time::Duration};
+
+/// The current time. Clones share the same time.
+#[derive(Clone, Default)]
+pub struct Clock(Option<Arc<Mutex<Timestamp>>>);
+
+impl Clock {
+    /// The system clock
+    pub fn system() -> Self {
+        Self(None)
+    }
+
+    /// A clock which starts at this time and only moves when advanced or set
+    pub fn fixed(start: Timestamp) -> Self {
+        Self(Some(Arc::new(Mutex::new(start))))
+    }
+
+    /// The current time
+    pub fn now(&self) -> Timestamp {
+        match &self.0 {
+            Some(now) => *now.lock(),
+            None => Timestamp::now(),
+        }
+    }
+
+    /// Move a fixed clock forward. The system clock can't be moved.
+    pub fn advance(&self, by: Duration) {
+        if let Some(now) = &self.0 {
+            let mut now = now.lock();
+            let t: chrono::DateTime<chrono::Utc> = (&*now).into();
+            *now = (t + chrono::Duration::from_std(by).expect("Duration out of range")).into();
+        }
+    }
+
+    /// Set a fixed clock to this time. The system clock can't be set.
+    pub fn set(&self, to: Timestamp) {
+        if let Some(now) = &self.0 {
+            *now.lock() = to;
+        }
+    }
+}
+
+impl std::fmt::Debug for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(now) => f.debug_tuple("Clock::Fixed").field(&*now.lock()).finish(),
+            None => f.write_str("Clock::System"),
+        }
+    }
+}
+
+/// A source of random bytes. Clones share the same generator.
+#[derive(Clone, Default)]
+pub struct Entropy(Option<Arc<Mutex<StdRng>>>);
+
+impl Entropy {
+    /// The system's entropy
+    pub fn system() -> Self {
+        Self(None)
+    }
+
+    /// A generator which gives the same bytes in the same order for a seed
+    pub fn seeded(seed: u64) -> Self {
+        Self(Some(Arc::new(Mutex::new(StdRng::seed_from_u64(seed)))))
+    }
+
+    /// Whether the bytes come from a seeded generator
+    pub fn is_seeded(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Fill a buffer with random bytes
+    pub fn fill_bytes(&self, buf: &mut [u8]) {
+        match &self.0 {
+            Some(rng) => rng.lock().fill_bytes(buf),
+            None => rand::thread_rng().fill_bytes(buf),
+        }
+    }
+}
+
+impl std::fmt::Debug for Entropy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(_) => f.write_str("Entropy::Seeded"),
+            None => f.write_str("Entropy::System"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock_only_moves_when_told() {
+        let clock = Clock::fixed(Timestamp(1000, 0));
+        let shared = clock.clone();
+        assert_eq!(clock.now(), Timestamp(1000, 0));
+        shared.advance(Duration::from_millis(1500));
+        assert_eq!(clock.now(), Timestamp(1001, 500_000_000));
+        shared.set(Timestamp(5, 0));
+        assert_eq!(clock.now(), Timestamp(5, 0));
+    }
+
+    #[test]
+    fn seeded_entropy_repeats() {
+        let (mut a, mut b) = ([0; 32], [0; 32]);
+        Entropy::seeded(42).fill_bytes(&mut a);
+        Entropy::seeded(42).fill_bytes(&mut b);
+        assert_eq!(a, b);
+        let entropy = Entropy::seeded(42);
+        entropy.clone().fill_bytes(&mut b);
+        entropy.fill_bytes(&mut b);
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,173 @@
+//! Network-wide statistics, estimated by sampling authorities.
+//!
+//! Each sample picks a random location and asks the authorities near it how
+//! many ops they hold in an arc around it. Scaling that up to the whole DHT
+//! estimates its size, and comparing how much each authority holds estimates
+//! how many copies of each op there are.
+
+use holo_hash::{AgentPubKey, AnyDhtHash, EntryHash};
+use holochain_p2p::{
+    actor::{GetDhtOpCountsOptions, HolochainP2pResult},
+    dht_arc::MAX_HALF_LENGTH,
+    HolochainP2pCellT,
+};
+use holochain_serialized_bytes::prelude::*;
+use holochain_types::{
+    dht_op::{DhtOpCounts, DhtOpType},
+    Timestamp,
+};
+use std::collections::HashMap;
+
+/// Each sample covers a sixteenth of the DHT
+const SAMPLE_HALF_LENGTH: u32 = MAX_HALF_LENGTH / 16;
+
+/// An estimate of the size of a DHT, from sampling its authorities
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct DhtSizeEstimate {
+    /// The estimated number of distinct ops of each type
+    pub op_counts: HashMap<DhtOpType, u64>,
+    /// The estimated number of distinct ops of all types
+    pub total_ops: u64,
+    /// The average number of authorities found holding each op.
+    /// Only authorities that responded are counted, so this is a lower bound.
+    pub redundancy: f64,
+    /// How many of the samples got any response
+    pub samples: usize,
+}
+
+/// Estimate the size and redundancy of the DHT by asking the authorities
+/// around `samples` random locations how many ops they hold
+pub async fn estimate_dht_size<N: HolochainP2pCellT>(
+    network: &mut N,
+    samples: u8,
+) -> HolochainP2pResult<DhtSizeEstimate> {
+    let since = Timestamp(0, 0);
+    let until = Timestamp::now();
+    let mut responses = Vec::with_capacity(samples as usize);
+    for _ in 0..samples {
+        let location: Vec<u8> = (0..36).map(|_| rand::random()).collect();
+        let basis: AnyDhtHash = EntryHash::from_raw_bytes(location).into();
+        responses.push(
+            network
+                .get_dht_op_counts(
+                    basis,
+                    SAMPLE_HALF_LENGTH,
+                    since,
+                    until,
+                    GetDhtOpCountsOptions::default(),
+                )
+                .await?,
+        );
+    }
+    Ok(estimate(SAMPLE_HALF_LENGTH, responses))
+}
+
+/// Combine the responses to each sample of an arc of `half_length`.
+/// The authority holding the most ops in a sample is taken to hold all of
+/// them, so the others hold copies of some of its ops.
+fn estimate(half_length: u32, samples: Vec<Vec<(AgentPubKey, DhtOpCounts)>>) -> DhtSizeEstimate {
+    let arc_length = (half_length as u64 * 2).saturating_sub(1).max(1);
+    let scale = (1u64 << 32) as f64 / arc_length.min(1 << 32) as f64;
+
+    let mut answered = 0;
+    let mut type_totals: HashMap<DhtOpType, u64> = HashMap::new();
+    let mut total = 0;
+    let mut copies = 0.0;
+    let mut held_samples = 0;
+    for responses in samples {
+        if responses.is_empty() {
+            continue;
+        }
+        answered += 1;
+        let mut type_maxes: HashMap<DhtOpType, u64> = HashMap::new();
+        for (_, counts) in &responses {
+            for (op_type, count) in &counts.counts {
+                let max = type_maxes.entry(*op_type).or_default();
+                *max = (*max).max(*count);
+            }
+        }
+        for (op_type, max) in type_maxes {
+            *type_totals.entry(op_type).or_default() += max;
+        }
+        let held: Vec<u64> = responses.iter().map(|(_, c)| c.total()).collect();
+        let most = held.iter().copied().max().unwrap_or_default();
+        if most > 0 {
+            total += most;
+            copies += held.iter().sum::<u64>() as f64 / most as f64;
+            held_samples += 1;
+        }
+    }
+
+    let extrapolate = |count: u64| {
+        if answered == 0 {
+            0
+        } else {
+            (count as f64 / answered as f64 * scale).round() as u64
+        }
+    };
+    DhtSizeEstimate {
+        op_counts: type_totals
+            .into_iter()
+            .map(|(op_type, count)| (op_type, extrapolate(count)))
+            .collect(),
+        total_ops: extrapolate(total),
+        redundancy: if held_samples == 0 {
+            0.0
+        } else {
+            copies / held_samples as f64
+        },
+        samples: answered,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::AgentPubKeyFixturator;
+
+    fn counts(counts: &[(DhtOpType, u64)]) -> (AgentPubKey, DhtOpCounts) {
+        (
+            fixt!(AgentPubKey),
+            DhtOpCounts {
+                counts: counts.iter().copied().collect(),
+            },
+        )
+    }
+
+    #[test]
+    fn extrapolates_samples() {
+        // Samples of a quarter of the DHT
+        let half_length = MAX_HALF_LENGTH / 4;
+        let samples = vec![
+            // Two authorities hold everything, one holds half
+            vec![
+                counts(&[(DhtOpType::StoreEntry, 6), (DhtOpType::StoreElement, 4)]),
+                counts(&[(DhtOpType::StoreEntry, 6), (DhtOpType::StoreElement, 4)]),
+                counts(&[(DhtOpType::StoreEntry, 3), (DhtOpType::StoreElement, 2)]),
+            ],
+            // No one answered
+            vec![],
+            vec![
+                counts(&[(DhtOpType::StoreEntry, 10), (DhtOpType::StoreElement, 10)]),
+                counts(&[(DhtOpType::StoreEntry, 10), (DhtOpType::StoreElement, 10)]),
+            ],
+        ];
+        let estimate = estimate(half_length, samples);
+        assert_eq!(estimate.samples, 2);
+        // An average of 15 ops in each quarter
+        assert_eq!(estimate.total_ops, 60);
+        assert_eq!(estimate.op_counts[&DhtOpType::StoreEntry], 32);
+        assert_eq!(estimate.op_counts[&DhtOpType::StoreElement], 28);
+        assert!((estimate.redundancy - 2.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn no_responses() {
+        let estimate = estimate(SAMPLE_HALF_LENGTH, vec![vec![], vec![]]);
+        assert_eq!(estimate.samples, 0);
+        assert_eq!(estimate.total_ops, 0);
+        assert!(estimate.op_counts.is_empty());
+        assert!(estimate.redundancy.abs() < 1e-9);
+    }
+}
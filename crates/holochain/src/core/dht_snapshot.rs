@@ -0,0 +1,159 @@
+//! Exporting the ops a cell holds to a snapshot, and importing a snapshot
+//! into another cell.
+//!
+//! A new network for an old Dna can take days to gossip its history from
+//! the few nodes which still hold it. Instead, a node which holds the data
+//! can export every op it has integrated as valid, and a fresh cell on the
+//! new network can import the snapshot in one go. Imported ops aren't
+//! trusted: they go through the same validation as ops published by any
+//! other node, so a snapshot can only bring in data which is valid anyway.
+
+use super::{
+    state::{dht_op_integration::IntegratedDhtOpsBuf, element_buf::ElementBuf},
+    sys_validate::verify_header_signature,
+    workflow::{error::WorkflowResult, produce_dht_ops_workflow::dht_op_light::light_to_op},
+};
+use holo_hash::{DhtOpHash, DnaHash};
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{env::EnvironmentRead, fresh_reader};
+use holochain_types::{
+    dht_op::{DhtOp, DhtOpHashed},
+    validate::ValidationStatus,
+    Timestamp,
+};
+
+/// Every op a cell had integrated as valid, ordered by when their headers
+/// were authored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct DhtSnapshot {
+    /// The Dna the ops belong to
+    pub dna_hash: DnaHash,
+    /// When the snapshot was exported
+    pub exported_at: Timestamp,
+    /// The ops, with their hashes
+    pub ops: Vec<(DhtOpHash, DhtOp)>,
+}
+
+impl DhtSnapshot {
+    /// Encode the snapshot to be written to an archive
+    pub fn encode(self) -> Result<Vec<u8>, SerializedBytesError> {
+        Ok(UnsafeBytes::from(SerializedBytes::try_from(self)?).into())
+    }
+
+    /// Decode a snapshot read from an archive
+    pub fn decode(data: Vec<u8>) -> Result<Self, SerializedBytesError> {
+        SerializedBytes::from(UnsafeBytes::from(data)).try_into()
+    }
+
+    /// Split the ops into those which are what they claim to be, and the
+    /// hashes of those which aren't: either the hash doesn't match the op
+    /// or the header's signature doesn't check out
+    pub async fn check_ops(self) -> (Vec<(DhtOpHash, DhtOp)>, Vec<DhtOpHash>) {
+        let mut ops = Vec::with_capacity(self.ops.len());
+        let mut counterfeit = Vec::new();
+        for (hash, op) in self.ops {
+            let (op, actual_hash) = DhtOpHashed::from_content_sync(op).into_inner();
+            if actual_hash == hash
+                && verify_header_signature(op.signature(), &op.header())
+                    .await
+                    .is_ok()
+            {
+                ops.push((hash, op));
+            } else {
+                counterfeit.push(hash);
+            }
+        }
+        (ops, counterfeit)
+    }
+}
+
+/// How an import went
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct SnapshotImportReport {
+    /// How many ops were queued for validation.
+    /// Ops the cell already held are counted, but not validated again.
+    pub queued: usize,
+    /// The ops which weren't what they claimed to be, and were dropped
+    pub counterfeit: Vec<DhtOpHash>,
+}
+
+/// Take a snapshot of every op integrated as valid in a cell's environment
+pub async fn export_snapshot(
+    env: EnvironmentRead,
+    dna_hash: DnaHash,
+) -> WorkflowResult<DhtSnapshot> {
+    let integrated_dht_ops = IntegratedDhtOpsBuf::new(env.clone())?;
+    let element_vault = ElementBuf::vault(env.clone(), false)?;
+    let held = fresh_reader!(env, |r| {
+        integrated_dht_ops
+            .query_ordered(&r, None, None, None)?
+            .collect::<Vec<_>>()
+    })?;
+
+    let mut ops = Vec::with_capacity(held.len());
+    for (op_hash, value) in held {
+        if value.validation_status != ValidationStatus::Valid {
+            continue;
+        }
+        ops.push((op_hash, light_to_op(value.op, &element_vault).await?));
+    }
+    Ok(DhtSnapshot {
+        dna_hash,
+        exported_at: Timestamp::now(),
+        ops,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{state::source_chain::SourceChain, workflow::fake_genesis};
+    use ::fixt::prelude::*;
+    use fallible_iterator::FallibleIterator;
+    use holo_hash::fixt::DnaHashFixturator;
+    use holochain_state::test_utils::test_cell_env;
+    use holochain_types::fixt::SignatureFixturator;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn snapshots_round_trip_and_catch_counterfeits() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut source_chain = SourceChain::new(env.clone().into()).unwrap();
+        fake_genesis(&mut source_chain).await.unwrap();
+
+        // The genesis headers' activity ops are properly signed
+        let ops: Vec<_> = source_chain
+            .iter_back()
+            .map(|shh| {
+                let op =
+                    DhtOp::RegisterAgentActivity(shh.signature().clone(), shh.header().clone());
+                let (op, hash) = DhtOpHashed::from_content_sync(op).into_inner();
+                Ok((hash, op))
+            })
+            .collect()
+            .unwrap();
+        assert_eq!(ops.len(), 3);
+
+        let snapshot = DhtSnapshot {
+            dna_hash: fixt!(DnaHash),
+            exported_at: Timestamp::now(),
+            ops: ops.clone(),
+        };
+        let decoded = DhtSnapshot::decode(snapshot.clone().encode().unwrap()).unwrap();
+        assert_eq!(decoded, snapshot);
+
+        let (checked, counterfeit) = decoded.check_ops().await;
+        assert_eq!(checked, ops);
+        assert!(counterfeit.is_empty());
+
+        // A forged signature and a mislabelled op are both dropped
+        let mut forged = snapshot;
+        if let DhtOp::RegisterAgentActivity(signature, _) = &mut forged.ops[0].1 {
+            *signature = fixt!(Signature);
+        }
+        forged.ops[1].0 = ops[0].0.clone();
+        let (checked, counterfeit) = forged.check_ops().await;
+        assert_eq!(checked, ops[2..].to_vec());
+        assert_eq!(counterfeit, vec![ops[0].0.clone(), ops[0].0.clone()]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Handing off the ops a cell holds before it stops being an authority for them.
+//!
+//! When a cell leaves the network, or its arc shrinks, it may be the only
+//! node holding some of the data in the range it gives up. So before letting
+//! go, every op it has integrated with a basis in that range is pushed to the
+//! authorities now closest to the basis, waiting for them to acknowledge it.
+//! Ops are pushed several at a time, and the whole handoff has one deadline,
+//! so a cell holding many ops doesn't take ages to leave.
+
+use super::{
+    state::{dht_op_integration::IntegratedDhtOpsBuf, element_buf::ElementBuf},
+    workflow::{error::WorkflowResult, produce_dht_ops_workflow::dht_op_light::light_to_op},
+};
+use fallible_iterator::FallibleIterator;
+use futures::stream::StreamExt;
+use holo_hash::DhtOpHash;
+use holochain_p2p::{dht_arc::DhtArc, HolochainP2pCell, HolochainP2pCellT};
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{env::EnvironmentRead, error::DatabaseResult, fresh_reader};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How long to wait for the authorities to acknowledge all the ops
+pub const HANDOFF_TIMEOUT_MS: u64 = 30_000;
+
+/// How many ops are pushed at once
+const HANDOFF_CONCURRENCY: usize = 32;
+
+/// How a handoff went
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct HandoffReport {
+    /// How many ops at least one authority acknowledged
+    pub handed_off: usize,
+    /// The ops no authority acknowledged in time.
+    /// If this cell was their only holder they may be lost.
+    pub unacknowledged: Vec<DhtOpHash>,
+}
+
+/// Push every integrated op with a basis in the `held` arc, but not the
+/// `kept` arc, to the authorities closest to that basis
+pub async fn hand_off(
+    env: EnvironmentRead,
+    network: HolochainP2pCell,
+    held: DhtArc,
+    kept: DhtArc,
+) -> WorkflowResult<HandoffReport> {
+    let integrated_dht_ops = IntegratedDhtOpsBuf::new(env.clone())?;
+    let element_vault = ElementBuf::vault(env.clone(), false)?;
+    let held: Vec<_> = fresh_reader!(env, |r| {
+        let held = integrated_dht_ops
+            .query(&r, None, None, Some(held))?
+            .filter(|(_, value)| Ok(!kept.contains(value.op.dht_basis().get_loc())))
+            .collect()?;
+        DatabaseResult::Ok(held)
+    })?;
+
+    let mut ops = Vec::with_capacity(held.len());
+    for (op_hash, value) in held {
+        let basis = value.op.dht_basis().clone();
+        let op = light_to_op(value.op, &element_vault).await?;
+        ops.push((op_hash, basis, op));
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(HANDOFF_TIMEOUT_MS);
+    let pushed: Vec<_> = futures::stream::iter(ops)
+        .map(|(op_hash, basis, op)| {
+            let mut network = network.clone();
+            async move {
+                // Ops still waiting their turn at the deadline count as unacknowledged
+                let timeout_ms = deadline
+                    .saturating_duration_since(Instant::now())
+                    .as_millis();
+                let acked = tokio::time::timeout_at(
+                    deadline,
+                    network.hand_off(basis, op_hash.clone(), op, Some(timeout_ms as u64)),
+                )
+                .await
+                .unwrap_or_else(|_| Ok(Vec::new()));
+                (op_hash, acked)
+            }
+        })
+        .buffer_unordered(HANDOFF_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut report = HandoffReport::default();
+    for (op_hash, acked) in pushed {
+        if acked?.is_empty() {
+            report.unacknowledged.push(op_hash);
+        } else {
+            report.handed_off += 1;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,143 @@
+//! Offline mode, for cells which should keep working without the network.
+//!
+//! While a cell is offline its network answers every query as if no authority
+//! responded, so zome calls only see what the cell holds locally, and the
+//! publish workflow leaves authored ops waiting in the authored store.
+//! When the cell comes back online the waiting ops are published and the
+//! metadata at every basis they touch is fetched again, so the cache picks up
+//! anything other agents did there in the meantime.
+
+use super::state::{
+    cascade::{error::CascadeResult, Cascade},
+    dht_op_integration::AuthoredDhtOpsStore,
+    element_buf::ElementBuf,
+    metadata::MetadataBuf,
+    publish_progress::PublishProgressBuf,
+    workspace::WorkspaceResult,
+};
+use fallible_iterator::FallibleIterator;
+use holo_hash::{AnyDhtHash, DhtOpHash};
+use holochain_p2p::HolochainP2pCell;
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::{BufferedStore, KvBufFresh},
+    db::AUTHORED_DHT_OPS,
+    env::{EnvironmentRead, EnvironmentWrite, WriteManager},
+    error::DatabaseError,
+    fresh_reader,
+    prelude::*,
+};
+use std::collections::BTreeSet;
+use tracing::*;
+
+/// Whether a cell is offline and how much it has waiting to publish
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct CellNetworkStatus {
+    /// Whether the cell is in offline mode
+    pub offline: bool,
+    /// How many authored ops have never been published
+    pub unpublished_ops: usize,
+}
+
+/// The basis of every authored op which has never been published,
+/// one for each op
+pub fn unpublished_ops(env: &EnvironmentRead) -> WorkspaceResult<Vec<AnyDhtHash>> {
+    let authored_dht_ops: AuthoredDhtOpsStore =
+        KvBufFresh::new(env.clone(), env.get_db(&*AUTHORED_DHT_OPS)?);
+    let progress = PublishProgressBuf::new(env.clone())?;
+    fresh_reader!(env, |r| {
+        let published: BTreeSet<_> = progress
+            .iter_all(&r)?
+            .into_iter()
+            .filter(|(_, p)| p.last_publish_time.is_some())
+            .map(|(op_hash, _)| op_hash)
+            .collect();
+        let bases = authored_dht_ops
+            .iter(&r)?
+            .filter(|(k, _)| Ok(!published.contains(&DhtOpHash::with_pre_hashed(k.to_vec()))))
+            .map(|(_, v)| Ok(v.op.dht_basis().clone()))
+            .collect()?;
+        WorkspaceResult::Ok(bases)
+    })
+}
+
+/// Fetch the latest metadata at each basis into the cache.
+/// A basis which can't be fetched is skipped.
+pub async fn reconcile(
+    env: EnvironmentWrite,
+    network: HolochainP2pCell,
+    bases: BTreeSet<AnyDhtHash>,
+) -> CascadeResult<()> {
+    let env_ref = env.guard();
+    let element_vault = ElementBuf::vault(env.clone().into(), false)?;
+    let meta_vault = MetadataBuf::vault(env.clone().into())?;
+    let mut element_cache = ElementBuf::cache(env.clone().into())?;
+    let mut meta_cache = MetadataBuf::cache(env.clone().into())?;
+    let mut cascade = Cascade::new(
+        env.clone().into(),
+        &element_vault,
+        &meta_vault,
+        &mut element_cache,
+        &mut meta_cache,
+        network,
+    );
+    for basis in bases {
+        if let Err(error) = cascade.refresh_basis(basis.clone()).await {
+            warn!(?error, ?basis, "Could not reconcile a basis");
+        }
+    }
+    env_ref.with_commit::<DatabaseError, _, _>(|writer| {
+        element_cache.flush_to_txn(writer)?;
+        meta_cache.flush_to_txn(writer)
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::dht_op_integration::AuthoredDhtOpsValue;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{DhtOpHashFixturator, EntryHashFixturator, HeaderHashFixturator};
+    use holochain_state::test_utils::test_cell_env;
+    use holochain_types::{dht_op::DhtOpLight, Timestamp};
+
+    #[tokio::test(threaded_scheduler)]
+    async fn only_unpublished_ops_are_counted() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let basis = fixt!(EntryHash);
+        let op = |basis: &holo_hash::EntryHash| {
+            AuthoredDhtOpsValue::from_light(DhtOpLight::StoreEntry(
+                fixt!(HeaderHash),
+                basis.clone(),
+                basis.clone().into(),
+            ))
+        };
+
+        {
+            let mut authored_dht_ops: AuthoredDhtOpsStore =
+                KvBufFresh::new(env.clone().into(), env.get_db(&*AUTHORED_DHT_OPS).unwrap());
+            let mut progress = PublishProgressBuf::new(env.clone().into()).unwrap();
+            authored_dht_ops.put(fixt!(DhtOpHash), op(&basis)).unwrap();
+            authored_dht_ops.put(fixt!(DhtOpHash), op(&basis)).unwrap();
+            let published = fixt!(DhtOpHash);
+            authored_dht_ops
+                .put(published.clone(), op(&fixt!(EntryHash)))
+                .unwrap();
+            progress
+                .record_publish(published, Timestamp::now())
+                .unwrap();
+            env_ref
+                .with_commit::<DatabaseError, _, _>(|writer| {
+                    authored_dht_ops.flush_to_txn(writer)?;
+                    progress.flush_to_txn(writer)
+                })
+                .unwrap();
+        }
+
+        let unpublished = unpublished_ops(&env.clone().into()).unwrap();
+        assert_eq!(unpublished, vec![basis.clone().into(), basis.into()]);
+    }
+}
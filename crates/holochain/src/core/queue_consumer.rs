@@ -18,24 +18,40 @@
 //! | ProduceDhtOps  | ChainSequence    | Auth'd + IntQ †  | DhtOpIntegr.   |
 //! |                 **integration, common to both paths**                 |
 //! | DhtOpIntegr.   | IntegrationLimbo | IntegratedDhtOps | Publish        |
-//! | Publish        | AuthoredDhtOps   | *n/a*            | *n/a*          |
+//! | Publish        | AuthoredDhtOps   | PublishProgress  | *n/a*          |
+//! | Redundancy ‡   | AuthoredDhtOps   | PublishProgress  | Publish        |
 //!
 //! († Auth'd + IntQ is short for: AuthoredDhtOps + IntegrationLimbo)
+//! (‡ Redundancy is checked by the autonomic HealthCheck process, which the
+//! autonomic consumer cues on a timer rather than being triggered)
 //!
 //! Implicitly, every workflow also writes to its own source queue, i.e. to
 //! remove the item it has just processed.
 
-use std::sync::{Arc, Once};
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Once,
+    },
+};
 
 use derive_more::{Constructor, Display, From};
+use fallible_iterator::FallibleIterator;
 use futures::future::Either;
+use holo_hash::DhtOpHash;
 use holochain_state::{
-    env::{EnvironmentWrite, WriteManager},
-    prelude::Writer,
+    buffer::KvBufFresh,
+    db::{AUTHORED_DHT_OPS, INTEGRATION_LIMBO},
+    env::EnvironmentWrite,
+    fresh_reader,
+    prelude::*,
 };
 use tokio::sync::{self, mpsc};
 
 // TODO: move these to workflow mod
+mod autonomic_consumer;
+use autonomic_consumer::*;
 mod integrate_dht_ops_consumer;
 use integrate_dht_ops_consumer::*;
 mod sys_validation_consumer;
@@ -45,8 +61,22 @@ use app_validation_consumer::*;
 mod produce_dht_ops_consumer;
 use produce_dht_ops_consumer::*;
 mod publish_dht_ops_consumer;
-use super::state::workspace::WorkspaceError;
-use crate::conductor::{api::CellConductorApiT, manager::ManagedTaskAdd};
+use super::autonomic::AutonomicSchedule;
+use super::clock::Clock;
+use super::quota::AppQuota;
+use super::state::{
+    chain_sequence::ChainSequenceBuf,
+    dht_op_integration::{AuthoredDhtOpsStore, IntegrationLimboStore},
+    publish_progress::PublishProgressBuf,
+    validation_db::{ValidationLimboStatus, ValidationLimboStore},
+    workspace::{WorkspaceError, WorkspaceResult},
+};
+use super::subconscious::UnresolvedDependencyPolicy;
+use super::workflow::publish_dht_ops_workflow::DEFAULT_RECEIPT_BUNDLE_SIZE;
+use crate::conductor::{
+    api::CellConductorApiT,
+    manager::{ManagedTaskAdd, ManagedTaskHandle},
+};
 use holochain_p2p::HolochainP2pCell;
 use publish_dht_ops_consumer::*;
 
@@ -61,30 +91,60 @@ pub async fn spawn_queue_consumer_tasks(
     conductor_api: impl CellConductorApiT + 'static,
     mut task_sender: sync::mpsc::Sender<ManagedTaskAdd>,
     stop: sync::broadcast::Sender<()>,
+    unresolved_dependency_policy: UnresolvedDependencyPolicy,
+    quota: AppQuota,
+    clock: Clock,
+    autonomic_schedule: AutonomicSchedule,
 ) -> InitialQueueTriggers {
     // Publish
-    let (tx_publish, handle) =
-        spawn_publish_dht_ops_consumer(env.clone(), stop.subscribe(), cell_network.clone());
+    let (tx_publish, handle) = spawn_publish_dht_ops_consumer(
+        env.clone(),
+        stop.subscribe(),
+        cell_network.clone(),
+        conductor_api.clone(),
+        quota,
+        clock.clone(),
+    );
     task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
+        .send(manage_consumer(handle, &conductor_api))
+        .await
+        .expect("Failed to manage workflow handle");
+
+    // Autonomic processes
+    let handle =
+        spawn_autonomic_consumer(stop.subscribe(), conductor_api.clone(), autonomic_schedule);
+    task_sender
+        .send(manage_consumer(handle, &conductor_api))
         .await
         .expect("Failed to manage workflow handle");
 
     let (create_tx_sys, get_tx_sys) = tokio::sync::oneshot::channel();
 
     // Integration
-    let (tx_integration, handle) =
-        spawn_integrate_dht_ops_consumer(env.clone(), stop.subscribe(), get_tx_sys);
+    let (tx_integration, handle) = spawn_integrate_dht_ops_consumer(
+        env.clone(),
+        stop.subscribe(),
+        get_tx_sys,
+        cell_network.clone(),
+        conductor_api.clone(),
+    );
     task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
+        .send(manage_consumer(handle, &conductor_api))
         .await
         .expect("Failed to manage workflow handle");
 
     // App validation
-    let (tx_app, handle) =
-        spawn_app_validation_consumer(env.clone(), stop.subscribe(), tx_integration.clone());
+    let (tx_app, handle) = spawn_app_validation_consumer(
+        env.clone(),
+        stop.subscribe(),
+        tx_integration.clone(),
+        cell_network.clone(),
+        conductor_api.clone(),
+        unresolved_dependency_policy.clone(),
+        clock.clone(),
+    );
     task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
+        .send(manage_consumer(handle, &conductor_api))
         .await
         .expect("Failed to manage workflow handle");
 
@@ -94,10 +154,12 @@ pub async fn spawn_queue_consumer_tasks(
         stop.subscribe(),
         tx_app.clone(),
         cell_network,
-        conductor_api,
+        conductor_api.clone(),
+        unresolved_dependency_policy,
+        clock,
     );
     task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
+        .send(manage_consumer(handle, &conductor_api))
         .await
         .expect("Failed to manage workflow handle");
     if create_tx_sys.send(tx_sys.clone()).is_err() {
@@ -108,13 +170,36 @@ pub async fn spawn_queue_consumer_tasks(
     let (tx_produce, handle) =
         spawn_produce_dht_ops_consumer(env.clone(), stop.subscribe(), tx_publish.clone());
     task_sender
-        .send(ManagedTaskAdd::dont_handle(handle))
+        .send(manage_consumer(handle, &conductor_api))
         .await
         .expect("Failed to manage workflow handle");
 
     InitialQueueTriggers::new(tx_sys, tx_produce, tx_publish, tx_app, tx_integration)
 }
 
+/// Hand a queue consumer's task to the TaskManager. A consumer which dies
+/// while the conductor is running leaves its Cell unable to do its work,
+/// so the conductor is told the Cell has died.
+fn manage_consumer(
+    handle: ManagedTaskHandle,
+    conductor_api: &(impl CellConductorApiT + 'static),
+) -> ManagedTaskAdd {
+    let conductor_api = conductor_api.clone();
+    ManagedTaskAdd::new(
+        handle,
+        Box::new(move |result| match result {
+            Ok(()) => None,
+            Err(e) => {
+                let conductor_api = conductor_api.clone();
+                Some(ManagedTaskAdd::dont_handle(tokio::spawn(async move {
+                    conductor_api.cell_died(e.to_string()).await;
+                    Ok(())
+                })))
+            }
+        }),
+    )
+}
+
 #[derive(Clone)]
 /// The entry points for kicking off a chain reaction of queue activity
 pub struct InitialQueueTriggers {
@@ -163,13 +248,175 @@ impl InitialQueueTriggers {
             })
         }
     }
+
+    /// Manually nudge one of the queue consumers, regardless of whether
+    /// there is known to be work waiting for it.
+    pub fn trigger(&mut self, queue: QueueTrigger) {
+        match queue {
+            QueueTrigger::SysValidation => self.sys_validation.trigger(),
+            QueueTrigger::AppValidation => self.app_validation.trigger(),
+            QueueTrigger::IntegrateDhtOps => self.integrate_dht_ops.trigger(),
+            QueueTrigger::ProduceDhtOps => self.produce_dht_ops.trigger(),
+            QueueTrigger::PublishDhtOps => self.publish_dht_ops.trigger(),
+        }
+    }
+
+    /// Whether every queue consumer has finished the work it picked up
+    /// and has no trigger waiting for it
+    pub fn all_idle(&self) -> bool {
+        self.all().iter().all(|trigger| trigger.is_idle())
+    }
+
+    /// How many times the queue consumers have been triggered or started a
+    /// run. A consumer can trigger another just before it goes idle, so two
+    /// looks at whether they're all idle are only comparable if this hasn't
+    /// changed in between.
+    pub fn activity(&self) -> usize {
+        self.all().iter().map(|trigger| trigger.activity()).sum()
+    }
+
+    fn all(&self) -> [&TriggerSender; 5] {
+        [
+            &self.sys_validation,
+            &self.app_validation,
+            &self.integrate_dht_ops,
+            &self.produce_dht_ops,
+            &self.publish_dht_ops,
+        ]
+    }
+
+    /// Report, for each queue consumer, whether it has a trigger it hasn't
+    /// picked up yet and how many items are waiting in its source queue.
+    pub fn queue_info(&self, env: &EnvironmentWrite) -> WorkspaceResult<Vec<QueueInfo>> {
+        let depths = QueueDepths::new(env)?;
+        Ok(vec![
+            QueueInfo::new(
+                QueueTrigger::SysValidation,
+                self.sys_validation.is_pending(),
+                depths.sys_validation,
+            ),
+            QueueInfo::new(
+                QueueTrigger::AppValidation,
+                self.app_validation.is_pending(),
+                depths.app_validation,
+            ),
+            QueueInfo::new(
+                QueueTrigger::IntegrateDhtOps,
+                self.integrate_dht_ops.is_pending(),
+                depths.integrate_dht_ops,
+            ),
+            QueueInfo::new(
+                QueueTrigger::ProduceDhtOps,
+                self.produce_dht_ops.is_pending(),
+                depths.produce_dht_ops,
+            ),
+            QueueInfo::new(
+                QueueTrigger::PublishDhtOps,
+                self.publish_dht_ops.is_pending(),
+                depths.publish_dht_ops,
+            ),
+        ])
+    }
+}
+
+/// Names the queue consumers, for inspecting and triggering them from
+/// outside the Cell, i.e. over the admin interface
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueTrigger {
+    /// The SysValidation workflow
+    SysValidation,
+    /// The AppValidation workflow
+    AppValidation,
+    /// The DhtOpIntegration workflow
+    IntegrateDhtOps,
+    /// The ProduceDhtOps workflow
+    ProduceDhtOps,
+    /// The Publish workflow
+    PublishDhtOps,
+}
+
+/// A snapshot of a single queue consumer
+#[derive(Clone, Debug, PartialEq, Eq, Constructor, serde::Serialize, serde::Deserialize)]
+pub struct QueueInfo {
+    /// Which consumer this is
+    pub queue: QueueTrigger,
+    /// The consumer has been triggered but hasn't started running yet
+    pub trigger_pending: bool,
+    /// How many items in the source queue are waiting on this consumer
+    pub depth: usize,
+}
+
+/// The number of items waiting in each source queue
+#[derive(Default)]
+struct QueueDepths {
+    sys_validation: usize,
+    app_validation: usize,
+    integrate_dht_ops: usize,
+    produce_dht_ops: usize,
+    publish_dht_ops: usize,
+}
+
+impl QueueDepths {
+    fn new(env: &EnvironmentWrite) -> WorkspaceResult<Self> {
+        let validation_limbo = ValidationLimboStore::new(env.clone().into())?;
+        let integration_limbo: IntegrationLimboStore =
+            KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO)?);
+        let authored_dht_ops: AuthoredDhtOpsStore =
+            KvBufFresh::new(env.clone().into(), env.get_db(&*AUTHORED_DHT_OPS)?);
+        let progress = PublishProgressBuf::new(env.clone().into())?;
+        let chain_sequence = ChainSequenceBuf::new(env.clone().into())?;
+
+        fresh_reader!(env, |r| {
+            let mut depths = Self::default();
+            validation_limbo.iter(&r)?.for_each(|(_, vlv)| {
+                match vlv.status {
+                    ValidationLimboStatus::Pending | ValidationLimboStatus::AwaitingSysDeps(_) => {
+                        depths.sys_validation += 1
+                    }
+                    ValidationLimboStatus::SysValidated
+                    | ValidationLimboStatus::AwaitingAppDeps(_)
+                    | ValidationLimboStatus::PendingValidation => depths.app_validation += 1,
+                }
+                Ok(())
+            })?;
+            depths.integrate_dht_ops = integration_limbo.iter(&r)?.count()?;
+            // Ops are waiting to be published until they have the receipts
+            // their entry def requires, same as in the publish workflow
+            let finished: HashSet<_> = progress
+                .iter_all(&r)?
+                .into_iter()
+                .filter(|(_, p)| p.is_saturated(DEFAULT_RECEIPT_BUNDLE_SIZE))
+                .map(|(op_hash, _)| op_hash)
+                .collect();
+            depths.publish_dht_ops = authored_dht_ops
+                .iter(&r)?
+                .filter(|(k, _)| Ok(!finished.contains(&DhtOpHash::with_pre_hashed(k.to_vec()))))
+                .count()?;
+            depths.produce_dht_ops = chain_sequence
+                .get_items_with_incomplete_dht_ops(&r)?
+                .count()?;
+            WorkspaceResult::Ok(depths)
+        })
+    }
 }
 /// The means of nudging a queue consumer to tell it to look for more work
 #[derive(Clone)]
-pub struct TriggerSender(mpsc::Sender<()>);
+pub struct TriggerSender(mpsc::Sender<()>, Arc<TriggerState>);
 
 /// The receiving end of a queue trigger channel
-pub struct TriggerReceiver(mpsc::Receiver<()>);
+pub struct TriggerReceiver(mpsc::Receiver<()>, Arc<TriggerState>);
+
+/// What a queue consumer is up to, shared by both ends of its trigger
+#[derive(Default)]
+struct TriggerState {
+    /// Triggered, but the consumer hasn't picked up the trigger yet
+    pending: AtomicBool,
+    /// The consumer is running the work it last picked up
+    running: AtomicBool,
+    /// Counts triggers and runs started
+    activity: AtomicUsize,
+}
 
 impl TriggerSender {
     /// Create a new channel for waking a consumer
@@ -178,32 +425,62 @@ impl TriggerSender {
     /// inconsistency from the perspective of any particular CPU thread
     pub fn new() -> (TriggerSender, TriggerReceiver) {
         let (tx, rx) = mpsc::channel(num_cpus::get());
-        (TriggerSender(tx), TriggerReceiver(rx))
+        let state = Arc::new(TriggerState::default());
+        (TriggerSender(tx, state.clone()), TriggerReceiver(rx, state))
     }
 
     /// Lazily nudge the consumer task, ignoring the case where the consumer
     /// already has a pending trigger signal
     pub fn trigger(&mut self) {
+        // Marked pending before sending, so the consumer can't pick up the
+        // trigger and clear the flag before it's set
+        self.1.pending.store(true, Ordering::SeqCst);
         match self.0.try_send(()) {
             Err(mpsc::error::TrySendError::Closed(_)) => {
+                self.1.pending.store(false, Ordering::SeqCst);
                 tracing::warn!(
                     "Queue consumer trigger was sent while Cell is shutting down: ignoring."
                 );
             }
-            Err(mpsc::error::TrySendError::Full(_)) => (),
-            Ok(()) => (),
+            Err(mpsc::error::TrySendError::Full(_)) | Ok(()) => {
+                self.1.activity.fetch_add(1, Ordering::SeqCst);
+            }
         };
     }
+
+    /// The consumer has been triggered but hasn't picked up the trigger yet
+    pub fn is_pending(&self) -> bool {
+        self.1.pending.load(Ordering::SeqCst)
+    }
+
+    /// The consumer has no trigger waiting for it and has finished its last
+    /// run, i.e. it is back waiting for a trigger
+    pub fn is_idle(&self) -> bool {
+        !self.is_pending() && !self.1.running.load(Ordering::SeqCst)
+    }
+
+    /// How many times the consumer has been triggered or started a run
+    pub fn activity(&self) -> usize {
+        self.1.activity.load(Ordering::SeqCst)
+    }
 }
 
 impl TriggerReceiver {
     /// Listen for one or more items to come through, draining the channel
     /// each time. Bubble up errors on empty channel.
+    ///
+    /// Listening again means the consumer has finished its last run.
     pub async fn listen(&mut self) -> Result<(), QueueTriggerClosedError> {
         use tokio::sync::mpsc::error::TryRecvError;
 
+        self.1.running.store(false, Ordering::SeqCst);
         // wait for next item
         if self.0.recv().await.is_some() {
+            // running before not pending, so the consumer never looks idle
+            // while it has work
+            self.1.running.store(true, Ordering::SeqCst);
+            self.1.activity.fetch_add(1, Ordering::SeqCst);
+            self.1.pending.store(false, Ordering::SeqCst);
             // drain the channel
             loop {
                 match self.0.try_recv() {
@@ -278,3 +555,39 @@ async fn next_job_or_exit(
         Job::Run
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn trigger_is_pending_until_picked_up() {
+        let (mut tx, mut rx) = TriggerSender::new();
+        assert!(!tx.is_pending());
+        tx.trigger();
+        tx.trigger();
+        assert!(tx.is_pending());
+        rx.listen().await.unwrap();
+        assert!(!tx.is_pending());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn consumer_is_busy_until_it_listens_again() {
+        let (mut tx, mut rx) = TriggerSender::new();
+        assert!(tx.is_idle());
+        tx.trigger();
+        assert!(!tx.is_idle());
+
+        // Picked up, but still running
+        rx.listen().await.unwrap();
+        assert!(!tx.is_pending());
+        assert!(!tx.is_idle());
+        assert_eq!(tx.activity(), 2);
+
+        // Back waiting for the next trigger
+        let next = tokio::time::timeout(std::time::Duration::from_millis(10), rx.listen()).await;
+        assert!(next.is_err());
+        assert!(tx.is_idle());
+        assert_eq!(tx.activity(), 2);
+    }
+}
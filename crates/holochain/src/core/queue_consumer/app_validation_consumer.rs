@@ -3,7 +3,11 @@
 use super::*;
 use crate::{
     conductor::manager::ManagedTaskResult,
-    core::workflow::app_validation_workflow::{app_validation_workflow, AppValidationWorkspace},
+    core::{
+        clock::Clock,
+        subconscious::UnresolvedDependencyPolicy,
+        workflow::app_validation_workflow::{app_validation_workflow, AppValidationWorkspace},
+    },
 };
 use holochain_state::env::EnvironmentWrite;
 
@@ -11,11 +15,15 @@ use tokio::task::JoinHandle;
 use tracing::*;
 
 /// Spawn the QueueConsumer for AppValidation workflow
-#[instrument(skip(env, stop, trigger_integration))]
+#[instrument(skip(env, stop, trigger_integration, network, conductor_api, policy, clock))]
 pub fn spawn_app_validation_consumer(
     env: EnvironmentWrite,
     mut stop: sync::broadcast::Receiver<()>,
     mut trigger_integration: TriggerSender,
+    network: HolochainP2pCell,
+    conductor_api: impl CellConductorApiT + 'static,
+    policy: UnresolvedDependencyPolicy,
+    clock: Clock,
 ) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
     let (tx, mut rx) = TriggerSender::new();
     let mut trigger_self = tx.clone();
@@ -30,12 +38,19 @@ pub fn spawn_app_validation_consumer(
             }
 
             // Run the workflow
-            let workspace = AppValidationWorkspace::new(env.clone().into())
+            let mut workspace = AppValidationWorkspace::new(env.clone().into())
                 .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete =
-                app_validation_workflow(workspace, env.clone().into(), &mut trigger_integration)
-                    .await
-                    .expect("Error running Workflow")
+            workspace.clock = clock.clone();
+            if let WorkComplete::Incomplete = app_validation_workflow(
+                workspace,
+                env.clone().into(),
+                &mut trigger_integration,
+                network.clone(),
+                &conductor_api,
+                &policy,
+            )
+            .await
+            .expect("Error running Workflow")
             {
                 trigger_self.trigger()
             };
@@ -0,0 +1,46 @@
+//! The periodic task which cues each of a Cell's autonomic processes
+//! as its interval comes round
+
+use super::*;
+use crate::{
+    conductor::manager::ManagedTaskResult,
+    core::autonomic::{AutonomicSchedule, Timetable},
+};
+use holochain_types::autonomic::AutonomicCue;
+use std::time::Instant;
+use tokio::task::JoinHandle;
+use tracing::*;
+
+/// Spawn the task which cues the Cell's autonomic processes on the
+/// given schedule, until the Cell shuts down
+#[instrument(skip(stop, conductor_api))]
+pub fn spawn_autonomic_consumer(
+    mut stop: sync::broadcast::Receiver<()>,
+    conductor_api: impl CellConductorApiT + 'static,
+    schedule: AutonomicSchedule,
+) -> JoinHandle<ManagedTaskResult> {
+    tokio::spawn(async move {
+        let mut timetable = Timetable::new(&schedule, Instant::now());
+        while let Some(due) = timetable.next_due() {
+            // Wait for the next process to come due or exit
+            let tick = tokio::time::delay_until(due.into());
+            let kill = stop.recv();
+            tokio::pin!(tick);
+            tokio::pin!(kill);
+            if let Either::Right(_) = futures::future::select(tick, kill).await {
+                tracing::warn!("Cell is shutting down: stopping autonomic task.");
+                break;
+            }
+
+            for process in timetable.take_due(Instant::now()) {
+                if let Err(error) = conductor_api
+                    .autonomic_cue(AutonomicCue::Run(process))
+                    .await
+                {
+                    warn!(?error, ?process, "Autonomic process failed");
+                }
+            }
+        }
+        Ok(())
+    })
+}
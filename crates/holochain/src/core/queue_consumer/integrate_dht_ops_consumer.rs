@@ -3,22 +3,28 @@
 use super::*;
 
 use crate::{
-    conductor::manager::ManagedTaskResult,
-    core::workflow::integrate_dht_ops_workflow::{
-        integrate_dht_ops_workflow, IntegrateDhtOpsWorkspace,
+    conductor::{api::CellConductorApiT, manager::ManagedTaskResult},
+    core::{
+        state::integration_journal::begin_integration,
+        workflow::integrate_dht_ops_workflow::{
+            integrate_dht_ops_workflow, IntegrateDhtOpsWorkspace,
+        },
     },
 };
+use holochain_p2p::HolochainP2pCell;
 use holochain_state::env::EnvironmentWrite;
 
 use tokio::task::JoinHandle;
 use tracing::*;
 
 /// Spawn the QueueConsumer for DhtOpIntegration workflow
-#[instrument(skip(env, stop, trigger_sys))]
+#[instrument(skip(env, stop, trigger_sys, network, conductor_api))]
 pub fn spawn_integrate_dht_ops_consumer(
     env: EnvironmentWrite,
     mut stop: sync::broadcast::Receiver<()>,
     trigger_sys: sync::oneshot::Receiver<TriggerSender>,
+    network: HolochainP2pCell,
+    conductor_api: impl CellConductorApiT + 'static,
 ) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
     let (tx, mut rx) = TriggerSender::new();
     let mut trigger_self = tx.clone();
@@ -33,13 +39,21 @@ pub fn spawn_integrate_dht_ops_consumer(
                 break;
             }
 
+            // Journal what we're about to integrate, in case we're interrupted
+            begin_integration(&env).expect("Could not journal integration");
+
             // Run the workflow
             let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into())
                 .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete =
-                integrate_dht_ops_workflow(workspace, env.clone().into(), &mut trigger_sys)
-                    .await
-                    .expect("Error running Workflow")
+            if let WorkComplete::Incomplete = integrate_dht_ops_workflow(
+                workspace,
+                env.clone().into(),
+                &mut trigger_sys,
+                &network,
+                &conductor_api,
+            )
+            .await
+            .expect("Error running Workflow")
             {
                 trigger_self.trigger()
             };
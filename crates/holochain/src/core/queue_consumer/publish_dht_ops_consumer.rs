@@ -3,8 +3,12 @@
 use super::*;
 
 use crate::{
-    conductor::manager::ManagedTaskResult,
-    core::workflow::publish_dht_ops_workflow::{publish_dht_ops_workflow, PublishDhtOpsWorkspace},
+    conductor::{api::CellConductorApiT, manager::ManagedTaskResult},
+    core::{
+        clock::Clock,
+        quota::AppQuota,
+        workflow::publish_dht_ops_workflow::{publish_dht_ops_workflow, PublishDhtOpsWorkspace},
+    },
 };
 use holochain_state::env::EnvironmentWrite;
 
@@ -12,11 +16,14 @@ use tokio::task::JoinHandle;
 use tracing::*;
 
 /// Spawn the QueueConsumer for Publish workflow
-#[instrument(skip(env, stop, cell_network))]
+#[instrument(skip(env, stop, cell_network, conductor_api, quota, clock))]
 pub fn spawn_publish_dht_ops_consumer(
     env: EnvironmentWrite,
     mut stop: sync::broadcast::Receiver<()>,
     mut cell_network: HolochainP2pCell,
+    conductor_api: impl CellConductorApiT + 'static,
+    quota: AppQuota,
+    clock: Clock,
 ) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
     let (tx, mut rx) = TriggerSender::new();
     let mut trigger_self = tx.clone();
@@ -31,12 +38,18 @@ pub fn spawn_publish_dht_ops_consumer(
             }
 
             // Run the workflow
-            let workspace = PublishDhtOpsWorkspace::new(env.clone().into())
+            let mut workspace = PublishDhtOpsWorkspace::new(env.clone().into())
                 .expect("Could not create Workspace");
-            if let WorkComplete::Incomplete =
-                publish_dht_ops_workflow(workspace, env.clone().into(), &mut cell_network)
-                    .await
-                    .expect("Error running Workflow")
+            workspace.set_clock(clock.clone());
+            if let WorkComplete::Incomplete = publish_dht_ops_workflow(
+                workspace,
+                env.clone().into(),
+                &mut cell_network,
+                &conductor_api,
+                &quota,
+            )
+            .await
+            .expect("Error running Workflow")
             {
                 trigger_self.trigger()
             };
@@ -3,23 +3,40 @@
 use super::*;
 use crate::{
     conductor::manager::ManagedTaskResult,
-    core::workflow::sys_validation_workflow::{sys_validation_workflow, SysValidationWorkspace},
+    core::{
+        clock::Clock,
+        subconscious::UnresolvedDependencyPolicy,
+        workflow::sys_validation_workflow::{
+            dependency_fetch::DependencyFetcher, sys_validation_workflow, SysValidationWorkspace,
+        },
+    },
 };
 use holochain_state::env::EnvironmentWrite;
 use tokio::task::JoinHandle;
 use tracing::*;
 
 /// Spawn the QueueConsumer for SysValidation workflow
-#[instrument(skip(env, stop, trigger_app_validation, network, conductor_api))]
+#[instrument(skip(
+    env,
+    stop,
+    trigger_app_validation,
+    network,
+    conductor_api,
+    policy,
+    clock
+))]
 pub fn spawn_sys_validation_consumer(
     env: EnvironmentWrite,
     mut stop: sync::broadcast::Receiver<()>,
     mut trigger_app_validation: TriggerSender,
     network: HolochainP2pCell,
     conductor_api: impl CellConductorApiT + 'static,
+    policy: UnresolvedDependencyPolicy,
+    clock: Clock,
 ) -> (TriggerSender, JoinHandle<ManagedTaskResult>) {
     let (tx, mut rx) = TriggerSender::new();
     let mut trigger_self = tx.clone();
+    let dependency_fetcher = DependencyFetcher::new(env.clone(), network.clone(), tx.clone());
     let handle = tokio::spawn(async move {
         loop {
             // Wait for next job
@@ -31,14 +48,17 @@ pub fn spawn_sys_validation_consumer(
             }
 
             // Run the workflow
-            let workspace = SysValidationWorkspace::new(env.clone().into())
+            let mut workspace = SysValidationWorkspace::new(env.clone().into())
                 .expect("Could not create Workspace");
+            workspace.clock = clock.clone();
             if let WorkComplete::Incomplete = sys_validation_workflow(
                 workspace,
                 env.clone().into(),
                 &mut trigger_app_validation,
                 network.clone(),
                 conductor_api.clone(),
+                &dependency_fetcher,
+                &policy,
             )
             .await
             .expect("Error running Workflow")
@@ -0,0 +1,201 @@
+//! Bounds on the resources each installed app may use, so that shared
+//! hosting providers can run many apps side by side.
+//!
+//! An [AppQuota] is set per app in the conductor config and applies to
+//! each of the app's Cells on its own. Source chain growth and storage are
+//! checked by the host functions which write to the source chain, so a zome
+//! call over quota fails with a [QuotaError]. Ops over the publish quota
+//! aren't lost: they wait in the authored store until the hour has passed.
+
+use crate::core::state::source_chain::{SourceChainBuf, SourceChainError};
+use fallible_iterator::FallibleIterator;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
+use holochain_state::error::DatabaseError;
+use holochain_types::Timestamp;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The window the hourly quotas are counted over
+const QUOTA_WINDOW_SECS: i64 = 60 * 60;
+
+/// The most each of an app's Cells may use.
+/// Anything left unset is unlimited.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppQuota {
+    /// Most headers a source chain may grow by in an hour.
+    /// Genesis headers count too.
+    #[serde(default)]
+    pub max_chain_growth_per_hour: Option<u32>,
+    /// Most distinct ops a Cell may publish in an hour
+    #[serde(default)]
+    pub max_ops_published_per_hour: Option<u32>,
+    /// Most bytes a Cell's environment may take up on disk
+    #[serde(default)]
+    pub max_storage_bytes: Option<u64>,
+}
+
+/// An app tried to use more than its quota allows
+#[derive(Error, Debug)]
+pub enum QuotaError {
+    /// The source chain has grown too quickly
+    #[error("Quota exceeded: the source chain grew by {grown} headers in the last hour, the app may only add {max} an hour")]
+    ChainGrowth {
+        /// Headers added in the last hour
+        grown: u32,
+        /// The app's quota
+        max: u32,
+    },
+    /// The Cell is storing too much
+    #[error("Quota exceeded: the cell is storing {used} bytes, the app may only store {max}")]
+    Storage {
+        /// Bytes the Cell's environment takes up
+        used: u64,
+        /// The app's quota
+        max: u64,
+    },
+    /// The source chain couldn't be read to check the quota
+    #[error(transparent)]
+    SourceChainError(#[from] SourceChainError),
+    /// The environment couldn't be read to check the quota
+    #[error(transparent)]
+    DatabaseError(#[from] DatabaseError),
+}
+
+impl HasErrorKind for QuotaError {
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            QuotaError::ChainGrowth { .. } | QuotaError::Storage { .. } => ErrorKind::QuotaExceeded,
+            QuotaError::SourceChainError(e) => e.error_kind(),
+            QuotaError::DatabaseError(e) => e.error_kind(),
+        }
+    }
+}
+
+impl AppQuota {
+    /// Check another header may be written to this source chain
+    pub fn check_write(&self, source_chain: &SourceChainBuf) -> Result<(), QuotaError> {
+        self.check_writes(source_chain, 1)
+    }
+
+    /// Check `count` more headers may be written to this source chain,
+    /// so a batch is either written whole or not at all
+    pub fn check_writes(
+        &self,
+        source_chain: &SourceChainBuf,
+        count: u32,
+    ) -> Result<(), QuotaError> {
+        if let Some(max) = self.max_chain_growth_per_hour {
+            let grown = headers_since(source_chain, hour_ago(Timestamp::now()))?;
+            if grown.saturating_add(count) > max {
+                return Err(QuotaError::ChainGrowth { grown, max });
+            }
+        }
+        if let Some(max) = self.max_storage_bytes {
+            let used = source_chain.env().used_bytes()?;
+            if used >= max {
+                return Err(QuotaError::Storage { used, max });
+            }
+        }
+        Ok(())
+    }
+
+    /// How many more ops may be published this hour,
+    /// or `None` if publishing is unlimited
+    pub fn publish_allowance(&self, published_in_last_hour: usize) -> Option<usize> {
+        self.max_ops_published_per_hour
+            .map(|max| (max as usize).saturating_sub(published_in_last_hour))
+    }
+}
+
+/// The start of the hour the quotas are counted over
+pub fn hour_ago(now: Timestamp) -> Timestamp {
+    Timestamp(now.0 - QUOTA_WINDOW_SECS, now.1)
+}
+
+/// How many headers on the chain, including any written in this call,
+/// were authored at or after `since`
+fn headers_since(source_chain: &SourceChainBuf, since: Timestamp) -> Result<u32, QuotaError> {
+    let since: holochain_zome_types::timestamp::Timestamp = since.into();
+    Ok(source_chain
+        .iter_back()
+        .take_while(|header| Ok(header.header().timestamp() >= since))
+        .count()? as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{state::source_chain::SourceChain, workflow::fake_genesis};
+    use holochain_state::test_utils::test_cell_env;
+    use matches::assert_matches;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn chain_growth_counts_recent_headers() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut source_chain = SourceChain::new(env.clone().into()).unwrap();
+        // Genesis writes three headers
+        fake_genesis(&mut source_chain).await.unwrap();
+
+        let quota = AppQuota {
+            max_chain_growth_per_hour: Some(3),
+            ..Default::default()
+        };
+        assert_matches!(
+            quota.check_write(&source_chain),
+            Err(QuotaError::ChainGrowth { grown: 3, max: 3 })
+        );
+        let quota = AppQuota {
+            max_chain_growth_per_hour: Some(4),
+            ..Default::default()
+        };
+        assert_matches!(quota.check_write(&source_chain), Ok(()));
+        assert_matches!(AppQuota::default().check_write(&source_chain), Ok(()));
+
+        // A batch must fit whole
+        assert_matches!(quota.check_writes(&source_chain, 1), Ok(()));
+        assert_matches!(
+            quota.check_writes(&source_chain, 2),
+            Err(QuotaError::ChainGrowth { grown: 3, max: 4 })
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn storage_is_measured_on_disk() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let source_chain = SourceChain::new(env.clone().into()).unwrap();
+        let quota = AppQuota {
+            max_storage_bytes: Some(1),
+            ..Default::default()
+        };
+        assert_matches!(
+            quota.check_write(&source_chain),
+            Err(QuotaError::Storage { max: 1, .. })
+        );
+    }
+
+    #[test]
+    fn publish_allowance_counts_down() {
+        let quota = AppQuota {
+            max_ops_published_per_hour: Some(10),
+            ..Default::default()
+        };
+        assert_eq!(quota.publish_allowance(0), Some(10));
+        assert_eq!(quota.publish_allowance(7), Some(3));
+        assert_eq!(quota.publish_allowance(12), Some(0));
+        assert_eq!(AppQuota::default().publish_allowance(1000), None);
+    }
+
+    #[test]
+    fn quotas_default_to_unlimited() {
+        let quota: AppQuota = toml::from_str("max_storage_bytes = 1024").unwrap();
+        assert_eq!(
+            quota,
+            AppQuota {
+                max_storage_bytes: Some(1024),
+                ..Default::default()
+            }
+        );
+    }
+}
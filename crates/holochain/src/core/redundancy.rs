@@ -0,0 +1,235 @@
+//! Keeping authored ops replicated across enough authorities.
+//!
+//! The publish workflow stops publishing an op once it has collected enough
+//! validation receipts, but the authorities which sent them may leave or lose
+//! the data later. So every so often an authoring cell samples the ops it
+//! has finished publishing and asks the authorities for each basis which of
+//! them they still hold. Any op held by fewer than the target number of
+//! authorities only keeps the receipts of those which hold it, which puts it
+//! back in the publish workflow's queue.
+
+use super::{
+    state::{dht_op_integration::AuthoredDhtOpsStore, publish_progress::PublishProgressBuf},
+    workflow::{error::WorkflowResult, publish_dht_ops_workflow::DEFAULT_RECEIPT_BUNDLE_SIZE},
+};
+use fallible_iterator::FallibleIterator;
+use holo_hash::{AgentPubKey, AnyDhtHash, DhtOpHash};
+use holochain_p2p::{actor::HoldsOpsOptions, HolochainP2pCellT};
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::{BufferedStore, KvBufFresh},
+    db::AUTHORED_DHT_OPS,
+    env::{EnvironmentWrite, WriteManager},
+    error::DatabaseResult,
+    fresh_reader,
+    prelude::*,
+};
+use rand::seq::IteratorRandom;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+/// How often each cell checks the redundancy of its authored ops, by default
+pub const REDUNDANCY_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// How many authored ops each check samples
+pub const REDUNDANCY_SAMPLE_SIZE: usize = 20;
+
+/// How many authorities should hold each authored op
+pub const TARGET_REDUNDANCY: u32 = DEFAULT_RECEIPT_BUNDLE_SIZE;
+
+/// How a redundancy check went, as a measure of the network's health
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct RedundancyReport {
+    /// How many authored ops were checked
+    pub sampled: usize,
+    /// How many of them were held by fewer authorities than the target,
+    /// and so will be published again
+    pub under_replicated: usize,
+    /// The average number of authorities found holding each checked op
+    pub mean_holders: f64,
+    /// The fewest authorities found holding any checked op
+    pub min_holders: Option<usize>,
+}
+
+/// Ask the authorities how many of them hold a sample of the ops this cell
+/// has finished publishing, and queue any held by fewer than `target`
+/// to be published again
+pub async fn check_redundancy<N: HolochainP2pCellT>(
+    env: EnvironmentWrite,
+    network: &mut N,
+    samples: usize,
+    target: u32,
+) -> WorkflowResult<RedundancyReport> {
+    let authored_dht_ops: AuthoredDhtOpsStore =
+        KvBufFresh::new(env.clone().into(), env.get_db(&*AUTHORED_DHT_OPS)?);
+    let mut progress = PublishProgressBuf::new(env.clone().into())?;
+
+    // Only ops which have collected enough receipts, the rest are still
+    // being published anyway
+    let sample: Vec<_> = fresh_reader!(env, |r| {
+        let finished: BTreeSet<_> = progress
+            .iter_all(&r)?
+            .into_iter()
+            .filter(|(_, p)| p.receipt_count() >= target)
+            .map(|(op_hash, _)| op_hash)
+            .collect();
+        let published: Vec<_> = authored_dht_ops
+            .iter(&r)?
+            .map(|(k, v)| Ok((DhtOpHash::with_pre_hashed(k.to_vec()), v)))
+            .filter(|(op_hash, _)| Ok(finished.contains(op_hash)))
+            .collect()?;
+        DatabaseResult::Ok(
+            published
+                .into_iter()
+                .choose_multiple(&mut rand::thread_rng(), samples),
+        )
+    })?;
+
+    let mut by_basis: BTreeMap<AnyDhtHash, Vec<DhtOpHash>> = BTreeMap::new();
+    for (op_hash, value) in &sample {
+        by_basis
+            .entry(value.op.dht_basis().clone())
+            .or_default()
+            .push(op_hash.clone());
+    }
+    let mut holders = HashMap::new();
+    for (basis, op_hashes) in by_basis {
+        let responses = network
+            .holds_ops(basis, op_hashes, HoldsOpsOptions::default())
+            .await?;
+        holders.extend(holders_of(responses));
+    }
+
+    let mut report = RedundancyReport {
+        sampled: sample.len(),
+        ..Default::default()
+    };
+    let mut total_holders = 0;
+    for (op_hash, _) in sample {
+        let held_by = holders.remove(&op_hash).unwrap_or_default();
+        let count = held_by.len();
+        total_holders += count;
+        report.min_holders = Some(report.min_holders.map_or(count, |m| m.min(count)));
+        if (count as u32) < target {
+            report.under_replicated += 1;
+            progress.set_receipts_from(op_hash, held_by)?;
+        }
+    }
+    if report.sampled > 0 {
+        report.mean_holders = total_holders as f64 / report.sampled as f64;
+    }
+
+    env.guard()
+        .with_commit(|writer| progress.flush_to_txn(writer))?;
+    Ok(report)
+}
+
+/// The distinct authorities which hold each op
+fn holders_of(
+    responses: Vec<(AgentPubKey, Vec<DhtOpHash>)>,
+) -> HashMap<DhtOpHash, BTreeSet<AgentPubKey>> {
+    let mut holders: HashMap<_, BTreeSet<_>> = HashMap::new();
+    for (agent, held) in responses {
+        for op_hash in held {
+            holders.entry(op_hash).or_default().insert(agent.clone());
+        }
+    }
+    holders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::dht_op_integration::AuthoredDhtOpsValue;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{AgentPubKeyFixturator, DhtOpHashFixturator, HeaderHashFixturator};
+    use holochain_p2p::MockHolochainP2pCellT;
+    use holochain_state::{error::DatabaseError, test_utils::test_cell_env};
+    use holochain_types::dht_op::DhtOpLight;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn under_replicated_ops_are_queued_for_publish() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let alice = fixt!(AgentPubKey);
+        let bob = fixt!(AgentPubKey);
+        let well_held = fixt!(DhtOpHash);
+        let poorly_held = fixt!(DhtOpHash);
+        let still_publishing = fixt!(DhtOpHash);
+
+        {
+            let mut authored_dht_ops: AuthoredDhtOpsStore =
+                KvBufFresh::new(env.clone().into(), env.get_db(&*AUTHORED_DHT_OPS).unwrap());
+            let mut progress = PublishProgressBuf::new(env.clone().into()).unwrap();
+            for (op_hash, validators) in vec![
+                (&well_held, vec![&alice, &bob]),
+                (&poorly_held, vec![&alice, &bob]),
+                (&still_publishing, vec![&alice]),
+            ] {
+                let value = AuthoredDhtOpsValue::from_light(DhtOpLight::RegisterAgentActivity(
+                    fixt!(HeaderHash),
+                    fixt!(HeaderHash).into(),
+                ));
+                authored_dht_ops.put(op_hash.clone(), value).unwrap();
+                for validator in validators {
+                    progress
+                        .record_receipt(op_hash.clone(), validator.clone())
+                        .unwrap();
+                }
+            }
+            env_ref
+                .with_commit::<DatabaseError, _, _>(|writer| {
+                    authored_dht_ops.flush_to_txn(writer)?;
+                    progress.flush_to_txn(writer)
+                })
+                .unwrap();
+        }
+
+        let mut network = MockHolochainP2pCellT::new();
+        {
+            let (alice, bob) = (alice.clone(), bob.clone());
+            let well_held = well_held.clone();
+            network
+                .expect_holds_ops()
+                .returning(move |_, op_hashes, _| {
+                    // Ops still being published are never asked about
+                    assert!(!op_hashes.contains(&still_publishing));
+                    Ok(vec![
+                        (alice.clone(), op_hashes.clone()),
+                        (
+                            bob.clone(),
+                            op_hashes.into_iter().filter(|h| *h == well_held).collect(),
+                        ),
+                    ])
+                });
+        }
+
+        let report = check_redundancy(env.clone(), &mut network, 10, 2)
+            .await
+            .unwrap();
+        assert_eq!(report.sampled, 2);
+        assert_eq!(report.under_replicated, 1);
+        assert_eq!(report.min_holders, Some(1));
+        assert_eq!(report.mean_holders, 1.5);
+
+        let progress = PublishProgressBuf::new(env.clone().into()).unwrap();
+        assert_eq!(progress.get(&well_held).unwrap().receipt_count(), 2);
+        // Only the receipt from the authority which still holds it is kept
+        assert_eq!(
+            progress.get(&poorly_held).unwrap().receipts_from,
+            vec![alice].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn agents_are_counted_once_per_op() {
+        let alice = fixt!(AgentPubKey);
+        let op_hash = fixt!(DhtOpHash);
+        let holders = holders_of(vec![
+            (alice.clone(), vec![op_hash.clone(), op_hash.clone()]),
+            (alice, vec![op_hash.clone()]),
+        ]);
+        assert_eq!(holders.get(&op_hash).map(|h| h.len()), Some(1));
+    }
+}
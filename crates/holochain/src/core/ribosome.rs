@@ -10,13 +10,23 @@
 pub mod error;
 pub mod guest_callback;
 pub mod host_fn;
+pub mod wasm_io;
 pub mod wasm_ribosome;
 
+use crate::core::cancel::CancelToken;
+use crate::core::clock::{Clock, Entropy};
+use crate::core::quota::AppQuota;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsResult;
+use crate::core::ribosome::guest_callback::index_op::IndexOpHostAccess;
+use crate::core::ribosome::guest_callback::index_op::IndexOpInvocation;
+use crate::core::ribosome::guest_callback::index_op::IndexOpResult;
 use crate::core::ribosome::guest_callback::init::InitInvocation;
 use crate::core::ribosome::guest_callback::init::InitResult;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesHostAccess;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesInvocation;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesResult;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentInvocation;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentResult;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitInvocation;
@@ -29,6 +39,7 @@ use crate::core::ribosome::guest_callback::validate_link_add::ValidateCreateLink
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageInvocation;
 use crate::core::ribosome::guest_callback::validation_package::ValidationPackageResult;
 use crate::core::ribosome::guest_callback::CallIterator;
+use crate::core::state::source_chain::CapGrantIndex;
 use crate::core::workflow::CallZomeWorkspaceLock;
 use crate::fixt::ExternInputFixturator;
 use crate::fixt::FunctionNameFixturator;
@@ -53,6 +64,8 @@ use holochain_types::fixt::CapSecretFixturator;
 use holochain_types::fixt::CellIdFixturator;
 use holochain_wasm_test_utils::TestWasm;
 use holochain_zome_types::capability::CapGrant;
+use holochain_zome_types::provenance::CallProvenance;
+use holochain_zome_types::validate::ValidationPackage;
 use holochain_zome_types::zome::FunctionName;
 use holochain_zome_types::zome::ZomeName;
 use holochain_zome_types::ExternOutput;
@@ -60,6 +73,7 @@ use holochain_zome_types::ZomeCallResponse;
 use holochain_zome_types::{capability::CapSecret, header::ZomeId, ExternInput};
 use mockall::automock;
 use std::iter::Iterator;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct CallContext {
@@ -90,6 +104,8 @@ pub enum HostAccess {
     ValidateCreateLink(ValidateCreateLinkHostAccess),
     Init(InitHostAccess),
     EntryDefs(EntryDefsHostAccess),
+    LinkTypes(LinkTypesHostAccess),
+    IndexOp(IndexOpHostAccess),
     MigrateAgent(MigrateAgentHostAccess),
     ValidationPackage(ValidationPackageHostAccess),
     PostCommit(PostCommitHostAccess),
@@ -105,6 +121,8 @@ impl From<&HostAccess> for HostFnAccess {
             }
             HostAccess::Init(init_host_access) => init_host_access.into(),
             HostAccess::EntryDefs(entry_defs_host_access) => entry_defs_host_access.into(),
+            HostAccess::LinkTypes(link_types_host_access) => link_types_host_access.into(),
+            HostAccess::IndexOp(index_op_host_access) => index_op_host_access.into(),
             HostAccess::MigrateAgent(migrate_agent_host_access) => migrate_agent_host_access.into(),
             HostAccess::ValidationPackage(validation_package_host_access) => {
                 validation_package_host_access.into()
@@ -152,6 +170,63 @@ impl HostAccess {
             ),
         }
     }
+
+    /// Get the validation package of the element being validated.
+    /// An entry def which doesn't ask for a package gets an empty one.
+    /// Errors outside of validate callbacks, which have no package.
+    pub fn validation_package(&self) -> RibosomeResult<Arc<ValidationPackage>> {
+        match self {
+            Self::Validate(ValidateHostAccess {
+                validation_package, ..
+            }) => Ok(validation_package
+                .clone()
+                .unwrap_or_else(|| Arc::new(ValidationPackage::new(Vec::new())))),
+            _ => Err(RibosomeError::NotValidating),
+        }
+    }
+
+    /// Get the token which cancels this call.
+    /// Only zome calls can be cancelled, so for anything else it never fires.
+    pub fn cancel(&self) -> CancelToken {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { cancel, .. }) => cancel.clone(),
+            _ => CancelToken::new(),
+        }
+    }
+
+    /// Get the clock. Only zome calls can be given one, the rest use the system's.
+    pub fn clock(&self) -> Clock {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { clock, .. }) => clock.clone(),
+            _ => Clock::system(),
+        }
+    }
+
+    /// Get the entropy. Only zome calls can be given one, the rest use the system's.
+    pub fn entropy(&self) -> Entropy {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { entropy, .. }) => entropy.clone(),
+            _ => Entropy::system(),
+        }
+    }
+
+    /// Get the app's quota. Only zome calls are bound by it.
+    pub fn quota(&self) -> Option<&AppQuota> {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess { quota, .. }) => Some(quota),
+            _ => None,
+        }
+    }
+
+    /// Get the path of calls which led to this one. Only zome calls have one.
+    pub fn call_provenance(&self) -> Option<&CallProvenance> {
+        match self {
+            Self::ZomeCall(ZomeCallHostAccess {
+                call_provenance, ..
+            }) => Some(call_provenance),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -225,11 +300,13 @@ impl ZomeCallInvocation {
     /// - we need to find a live (committed and not deleted) cap grant that matches the secret
     /// - if the live cap grant is for the current author the call is ALWAYS authorized ELSE
     /// - the live cap grant needs to include the invocation's provenance AND zome/function name
+    /// - the live cap grant must not have expired
     #[allow(clippy::extra_unused_lifetimes)]
     pub fn is_authorized<'a>(&self, host_access: &ZomeCallHostAccess) -> RibosomeResult<bool> {
         let check_function = (self.zome_name.clone(), self.fn_name.clone());
         let check_agent = self.provenance.clone();
         let check_secret = self.cap;
+        let now = host_access.clock.now();
 
         tokio_safe_block_on::tokio_safe_block_forever_on(async move {
             let maybe_grant: Option<CapGrant> = host_access
@@ -237,7 +314,13 @@ impl ZomeCallInvocation {
                 .read()
                 .await
                 .source_chain
-                .valid_cap_grant(&check_function, &check_agent, check_secret.as_ref())?;
+                .valid_cap_grant_indexed(
+                    &host_access.cap_grants,
+                    &check_function,
+                    &check_agent,
+                    check_secret.as_ref(),
+                    now,
+                )?;
 
             Ok(maybe_grant.is_some())
         })
@@ -356,6 +439,16 @@ pub struct ZomeCallHostAccess {
     pub workspace: CallZomeWorkspaceLock,
     pub keystore: KeystoreSender,
     pub network: HolochainP2pCell,
+    pub quota: AppQuota,
+    pub cancel: CancelToken,
+    /// The Cell's live cap grants, for authorizing the call
+    pub cap_grants: CapGrantIndex,
+    /// The path of calls which led to this one, ending with this one
+    pub call_provenance: CallProvenance,
+    /// Where the call gets the time from
+    pub clock: Clock,
+    /// Where the call gets random bytes from
+    pub entropy: Entropy,
 }
 
 impl From<ZomeCallHostAccess> for HostAccess {
@@ -420,6 +513,18 @@ pub trait RibosomeT: Sized + std::fmt::Debug {
         invocation: EntryDefsInvocation,
     ) -> RibosomeResult<EntryDefsResult>;
 
+    fn run_link_types(
+        &self,
+        access: LinkTypesHostAccess,
+        invocation: LinkTypesInvocation,
+    ) -> RibosomeResult<LinkTypesResult>;
+
+    fn run_index_op(
+        &self,
+        access: IndexOpHostAccess,
+        invocation: IndexOpInvocation,
+    ) -> RibosomeResult<IndexOpResult>;
+
     fn run_validation_package(
         &self,
         access: ValidationPackageHostAccess,
@@ -1,9 +1,15 @@
 #![deny(missing_docs)]
 //! Errors occurring during a [Ribosome] call
 
-use crate::core::state::{cascade::error::CascadeError, source_chain::SourceChainError};
-use holo_hash::AnyDhtHash;
+use crate::core::{
+    cancel::Cancelled,
+    quota::QuotaError,
+    ribosome::wasm_io::WasmIoError,
+    state::{cascade::error::CascadeError, source_chain::SourceChainError},
+};
+use holo_hash::{AnyDhtHash, EntryHash, HeaderHash};
 use holochain_crypto::CryptoError;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_serialized_bytes::prelude::SerializedBytesError;
 use holochain_types::dna::error::DnaError;
 use holochain_wasmer_host::prelude::WasmError;
@@ -40,6 +46,19 @@ pub enum RibosomeError {
     #[error("An error with entry defs: {0}")]
     EntryDefs(ZomeName, String),
 
+    /// a problem with link types
+    #[error("An error with link types: {0}")]
+    LinkTypes(ZomeName, String),
+
+    /// a zome's index_op callback failed
+    #[error("An error indexing an op in zome {0}: {1}")]
+    IndexOp(ZomeName, String),
+
+    /// a host fn which only makes sense in a validate callback was called
+    /// from somewhere else
+    #[error("The validation package can only be read from a validate callback")]
+    NotValidating,
+
     /// a mandatory dependency for an element doesn't exist
     /// for example a remove link ribosome call needs to find the add link in order to infer the
     /// correct base and this dependent relationship exists before even subconscious validation
@@ -47,6 +66,11 @@ pub enum RibosomeError {
     #[error("A mandatory element is missing, dht hash: {0}")]
     ElementDeps(AnyDhtHash),
 
+    /// an update targeted an entry which has several live headers which
+    /// haven't been updated yet, so the update has to pick one of them
+    #[error("Update of entry {0} is ambiguous between headers {1:?}")]
+    UpdateConflict(EntryHash, Vec<HeaderHash>),
+
     /// ident
     #[error(transparent)]
     CryptoError(#[from] CryptoError),
@@ -63,6 +87,18 @@ pub enum RibosomeError {
     #[error(transparent)]
     SourceChainError(#[from] SourceChainError),
 
+    /// The app has used up its quota
+    #[error(transparent)]
+    QuotaError(#[from] QuotaError),
+
+    /// Data crossing the wasm boundary was over the limit
+    #[error(transparent)]
+    WasmIoError(#[from] WasmIoError),
+
+    /// The call was cancelled or timed out
+    #[error(transparent)]
+    Cancelled(#[from] Cancelled),
+
     /// ident
     #[error(transparent)]
     BlockOnError(#[from] BlockOnError),
@@ -76,5 +112,29 @@ pub enum RibosomeError {
     P2pError(#[from] holochain_p2p::HolochainP2pError),
 }
 
+impl HasErrorKind for RibosomeError {
+    fn error_kind(&self) -> ErrorKind {
+        use RibosomeError::*;
+        match self {
+            DnaError(_) | WasmError(_) | CryptoError(_) | JoinError(_) => ErrorKind::Internal,
+            SerializationError(_) => ErrorKind::Serialization,
+            ZomeNotExists(_) | ZomeFnNotExists(_, _) | ElementDeps(_) => ErrorKind::NotFound,
+            EntryDefs(_, _)
+            | LinkTypes(_, _)
+            | IndexOp(_, _)
+            | NotValidating
+            | UpdateConflict(_, _) => ErrorKind::InvalidInput,
+            DatabaseError(e) => e.error_kind(),
+            CascadeError(e) => e.error_kind(),
+            SourceChainError(e) => e.error_kind(),
+            QuotaError(e) => e.error_kind(),
+            WasmIoError(e) => e.error_kind(),
+            Cancelled(e) => e.error_kind(),
+            BlockOnError(_) => ErrorKind::Timeout,
+            P2pError(e) => e.error_kind(),
+        }
+    }
+}
+
 /// Type alias
 pub type RibosomeResult<T> = Result<T, RibosomeError>;
@@ -1,5 +1,7 @@
 pub mod entry_defs;
+pub mod index_op;
 pub mod init;
+pub mod link_types;
 pub mod migrate_agent;
 pub mod post_commit;
 pub mod validate;
@@ -277,12 +277,16 @@ mod slow_tests {
                         visibility: EntryVisibility::Public,
                         crdt_type: CrdtType,
                         required_validations: 5.into(),
+                        required_validation_type: Default::default(),
+                        references: vec![],
                     },
                     EntryDef {
                         id: "comment".into(),
                         visibility: EntryVisibility::Private,
                         crdt_type: CrdtType,
                         required_validations: 5.into(),
+                        required_validation_type: Default::default(),
+                        references: vec![],
                     },
                 ]
                 .into();
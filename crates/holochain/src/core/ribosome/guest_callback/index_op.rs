@@ -0,0 +1,175 @@
+use crate::core::ribosome::FnComponents;
+use crate::core::ribosome::HostAccess;
+use crate::core::ribosome::Invocation;
+use crate::core::ribosome::ZomesToInvoke;
+use derive_more::Constructor;
+use holochain_serialized_bytes::prelude::*;
+use holochain_types::dna::zome::HostFnAccess;
+use holochain_zome_types::element::Element;
+use holochain_zome_types::index::IndexOpCallbackResult;
+use holochain_zome_types::index::IndexRecord;
+use holochain_zome_types::zome::ZomeName;
+use holochain_zome_types::ExternInput;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct IndexOpInvocation {
+    /// The zome which defines the entry
+    pub zome_name: ZomeName,
+    /// The element being integrated, which always has its entry
+    pub element: Arc<Element>,
+}
+
+impl IndexOpInvocation {
+    pub fn new(zome_name: ZomeName, element: Element) -> Self {
+        Self {
+            zome_name,
+            element: Arc::new(element),
+        }
+    }
+}
+
+#[derive(Clone, Constructor)]
+pub struct IndexOpHostAccess;
+
+impl From<IndexOpHostAccess> for HostAccess {
+    fn from(index_op_host_access: IndexOpHostAccess) -> Self {
+        Self::IndexOp(index_op_host_access)
+    }
+}
+
+impl From<&IndexOpHostAccess> for HostFnAccess {
+    fn from(_: &IndexOpHostAccess) -> Self {
+        // Indexing must be deterministic so every authority agrees
+        Self::none()
+    }
+}
+
+impl Invocation for IndexOpInvocation {
+    fn zomes(&self) -> ZomesToInvoke {
+        // like validation, entries are only indexed by the zome that defines them
+        ZomesToInvoke::One(self.zome_name.clone())
+    }
+    fn fn_components(&self) -> FnComponents {
+        vec!["index_op".into()].into()
+    }
+    fn host_input(self) -> Result<ExternInput, SerializedBytesError> {
+        Ok(ExternInput::new((*self.element).clone().try_into()?))
+    }
+}
+
+impl TryFrom<IndexOpInvocation> for ExternInput {
+    type Error = SerializedBytesError;
+    fn try_from(index_op_invocation: IndexOpInvocation) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            (*index_op_invocation.element).clone().try_into()?,
+        ))
+    }
+}
+
+/// the aggregate result of the index_op callbacks
+/// zomes that don't implement the callback emit no records
+#[derive(PartialEq, Debug, Clone)]
+pub enum IndexOpResult {
+    Indexes(Vec<(ZomeName, IndexRecord)>),
+    Err(ZomeName, String),
+}
+
+impl From<Vec<(ZomeName, IndexOpCallbackResult)>> for IndexOpResult {
+    fn from(callback_results: Vec<(ZomeName, IndexOpCallbackResult)>) -> Self {
+        callback_results
+            .into_iter()
+            .fold(Self::Indexes(vec![]), |acc, x| match x {
+                // err overrides everything
+                (zome_name, IndexOpCallbackResult::Err(fail_string)) => {
+                    Self::Err(zome_name, fail_string)
+                }
+                // records accumulate
+                (zome_name, IndexOpCallbackResult::Indexes(records)) => match acc {
+                    Self::Indexes(mut all) => {
+                        all.extend(records.into_iter().map(|r| (zome_name.clone(), r)));
+                        Self::Indexes(all)
+                    }
+                    Self::Err(_, _) => acc,
+                },
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{IndexOpHostAccess, IndexOpResult};
+    use crate::core::ribosome::Invocation;
+    use crate::core::ribosome::ZomesToInvoke;
+    use crate::fixt::IndexOpInvocationFixturator;
+    use crate::fixt::ZomeNameFixturator;
+    use ::fixt::prelude::*;
+    use holochain_serialized_bytes::prelude::*;
+    use holochain_types::dna::zome::HostFnAccess;
+    use holochain_zome_types::index::IndexOpCallbackResult;
+    use holochain_zome_types::index::IndexRecord;
+    use holochain_zome_types::ExternInput;
+
+    #[test]
+    fn index_op_callback_result_fold() {
+        let mut zome_name_fixturator = ZomeNameFixturator::new(fixt::Unpredictable);
+        let zome_name = zome_name_fixturator.next().unwrap();
+        let record = IndexRecord::new("by_name", "alice", SerializedBytes::try_from(()).unwrap());
+
+        // no records
+        assert_eq!(IndexOpResult::Indexes(vec![]), vec![].into());
+
+        // records are tagged with their zome
+        assert_eq!(
+            IndexOpResult::Indexes(vec![(zome_name.clone(), record.clone())]),
+            vec![(
+                zome_name.clone(),
+                IndexOpCallbackResult::Indexes(vec![record.clone()])
+            )]
+            .into(),
+        );
+
+        // an err overrides records in any position
+        let zome_name_err = zome_name_fixturator.next().unwrap();
+        let result: IndexOpResult = vec![
+            (
+                zome_name_err.clone(),
+                IndexOpCallbackResult::Err("bad".into()),
+            ),
+            (zome_name, IndexOpCallbackResult::Indexes(vec![record])),
+        ]
+        .into();
+        assert_eq!(result, IndexOpResult::Err(zome_name_err, "bad".into()));
+    }
+
+    #[test]
+    fn index_op_host_access() {
+        assert_eq!(HostFnAccess::from(&IndexOpHostAccess), HostFnAccess::none());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn index_op_invocation_zomes() {
+        let index_op_invocation = IndexOpInvocationFixturator::new(fixt::Unpredictable)
+            .next()
+            .unwrap();
+        let zome_name = index_op_invocation.zome_name.clone();
+        assert_eq!(ZomesToInvoke::One(zome_name), index_op_invocation.zomes(),);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn index_op_invocation_host_input() {
+        let index_op_invocation = IndexOpInvocationFixturator::new(fixt::Unpredictable)
+            .next()
+            .unwrap();
+
+        let host_input = index_op_invocation.clone().host_input().unwrap();
+
+        assert_eq!(
+            host_input,
+            ExternInput::new(
+                SerializedBytes::try_from((*index_op_invocation.element).clone()).unwrap()
+            ),
+        );
+    }
+}
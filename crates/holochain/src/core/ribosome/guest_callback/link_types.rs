@@ -0,0 +1,229 @@
+use crate::core::ribosome::FnComponents;
+use crate::core::ribosome::Invocation;
+use crate::core::ribosome::{HostAccess, ZomesToInvoke};
+use derive_more::Constructor;
+use holochain_serialized_bytes::prelude::*;
+use holochain_types::dna::zome::HostFnAccess;
+use holochain_zome_types::link::LinkTypes;
+use holochain_zome_types::link::LinkTypesCallbackResult;
+use holochain_zome_types::zome::ZomeName;
+use holochain_zome_types::ExternInput;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+pub struct LinkTypesInvocation;
+
+impl LinkTypesInvocation {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[derive(Clone, Constructor)]
+pub struct LinkTypesHostAccess;
+
+impl From<&HostAccess> for LinkTypesHostAccess {
+    fn from(_: &HostAccess) -> Self {
+        Self
+    }
+}
+
+impl From<LinkTypesHostAccess> for HostAccess {
+    fn from(link_types_host_access: LinkTypesHostAccess) -> Self {
+        Self::LinkTypes(link_types_host_access)
+    }
+}
+
+impl From<&LinkTypesHostAccess> for HostFnAccess {
+    fn from(_: &LinkTypesHostAccess) -> Self {
+        Self::none()
+    }
+}
+
+impl Invocation for LinkTypesInvocation {
+    fn zomes(&self) -> ZomesToInvoke {
+        ZomesToInvoke::All
+    }
+    fn fn_components(&self) -> FnComponents {
+        vec!["link_types".into()].into()
+    }
+    fn host_input(self) -> Result<ExternInput, SerializedBytesError> {
+        Ok(ExternInput::new(().try_into()?))
+    }
+}
+
+impl TryFrom<LinkTypesInvocation> for ExternInput {
+    type Error = SerializedBytesError;
+    fn try_from(_: LinkTypesInvocation) -> Result<Self, Self::Error> {
+        Ok(Self::new(().try_into()?))
+    }
+}
+
+/// the aggregate result of _all_ link types callbacks
+/// zomes that don't implement the callback have no declared link types
+#[derive(PartialEq, Debug, Clone)]
+pub enum LinkTypesResult {
+    /// simple mapping between zome and link types
+    Defs(BTreeMap<ZomeName, LinkTypes>),
+    Err(ZomeName, String),
+}
+
+impl From<Vec<(ZomeName, LinkTypesCallbackResult)>> for LinkTypesResult {
+    fn from(callback_results: Vec<(ZomeName, LinkTypesCallbackResult)>) -> Self {
+        callback_results.into_iter().fold(
+            LinkTypesResult::Defs(BTreeMap::new()),
+            |acc, x| match x {
+                // err overrides everything
+                (zome_name, LinkTypesCallbackResult::Err(fail_string)) => {
+                    Self::Err(zome_name, fail_string)
+                }
+                // passing callback allows the acc to carry forward
+                (zome_name, LinkTypesCallbackResult::Defs(defs)) => match acc {
+                    Self::Defs(mut btreemap) => {
+                        btreemap.insert(zome_name, defs);
+                        Self::Defs(btreemap)
+                    }
+                    Self::Err(_, _) => acc,
+                },
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::{LinkTypesHostAccess, LinkTypesResult};
+    use crate::core::ribosome::Invocation;
+    use crate::core::ribosome::ZomesToInvoke;
+    use crate::fixt::LinkTypesInvocationFixturator;
+    use crate::fixt::ZomeNameFixturator;
+    use ::fixt::prelude::*;
+    use holochain_serialized_bytes::prelude::*;
+    use holochain_types::dna::zome::HostFnAccess;
+    use holochain_zome_types::link::LinkTypes;
+    use holochain_zome_types::link::LinkTypesCallbackResult;
+    use holochain_zome_types::ExternInput;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn link_types_callback_result_fold() {
+        let mut zome_name_fixturator = ZomeNameFixturator::new(fixt::Unpredictable);
+
+        // zero defs
+        assert_eq!(LinkTypesResult::Defs(BTreeMap::new()), vec![].into());
+
+        // one defs
+        let zome_name = zome_name_fixturator.next().unwrap();
+        let link_types: LinkTypes = vec!["follows".into()].into();
+        assert_eq!(
+            LinkTypesResult::Defs({
+                let mut tree = BTreeMap::new();
+                tree.insert(zome_name.clone(), link_types.clone());
+                tree
+            }),
+            vec![(
+                zome_name.clone(),
+                LinkTypesCallbackResult::Defs(link_types.clone())
+            )]
+            .into(),
+        );
+
+        // an err overrides defs in any position
+        let zome_name_err = zome_name_fixturator.next().unwrap();
+        let result: LinkTypesResult = vec![
+            (zome_name, LinkTypesCallbackResult::Defs(link_types)),
+            (
+                zome_name_err.clone(),
+                LinkTypesCallbackResult::Err("bad".into()),
+            ),
+        ]
+        .into();
+        assert_eq!(result, LinkTypesResult::Err(zome_name_err, "bad".into()));
+    }
+
+    #[test]
+    fn link_types_host_access() {
+        assert_eq!(
+            HostFnAccess::from(&LinkTypesHostAccess),
+            HostFnAccess::none()
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn link_types_invocation_zomes() {
+        let link_types_invocation = LinkTypesInvocationFixturator::new(fixt::Unpredictable)
+            .next()
+            .unwrap();
+        assert_eq!(ZomesToInvoke::All, link_types_invocation.zomes(),);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn link_types_invocation_host_input() {
+        let link_types_invocation = LinkTypesInvocationFixturator::new(fixt::Unpredictable)
+            .next()
+            .unwrap();
+
+        let host_input = link_types_invocation.clone().host_input().unwrap();
+
+        assert_eq!(
+            host_input,
+            ExternInput::new(SerializedBytes::try_from(()).unwrap()),
+        );
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "slow_tests")]
+mod slow_tests {
+    use crate::core::ribosome::guest_callback::link_types::LinkTypesHostAccess;
+    use crate::core::ribosome::guest_callback::link_types::LinkTypesResult;
+    use crate::core::ribosome::RibosomeT;
+    use crate::fixt::curve::Zomes;
+    use crate::fixt::LinkTypesInvocationFixturator;
+    use crate::fixt::WasmRibosomeFixturator;
+    use holochain_wasm_test_utils::TestWasm;
+    use holochain_zome_types::link::LinkTypes;
+    use holochain_zome_types::zome::ZomeName;
+    use std::collections::BTreeMap;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_link_types_unimplemented() {
+        let ribosome = WasmRibosomeFixturator::new(Zomes(vec![TestWasm::Foo]))
+            .next()
+            .unwrap();
+        let link_types_invocation = LinkTypesInvocationFixturator::new(fixt::Empty)
+            .next()
+            .unwrap();
+
+        let result = ribosome
+            .run_link_types(LinkTypesHostAccess, link_types_invocation)
+            .unwrap();
+        assert_eq!(result, LinkTypesResult::Defs(BTreeMap::new()),);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_link_types_implemented() {
+        let ribosome = WasmRibosomeFixturator::new(Zomes(vec![TestWasm::Link]))
+            .next()
+            .unwrap();
+        let link_types_invocation = LinkTypesInvocationFixturator::new(fixt::Empty)
+            .next()
+            .unwrap();
+
+        let result = ribosome
+            .run_link_types(LinkTypesHostAccess, link_types_invocation)
+            .unwrap();
+        assert_eq!(
+            result,
+            LinkTypesResult::Defs({
+                let mut tree = BTreeMap::new();
+                let zome_name: ZomeName = "link".into();
+                let link_types: LinkTypes = vec!["follows".into()].into();
+                tree.insert(zome_name, link_types);
+                tree
+            }),
+        );
+    }
+}
@@ -2,12 +2,13 @@ use crate::core::ribosome::FnComponents;
 use crate::core::ribosome::HostAccess;
 use crate::core::ribosome::Invocation;
 use crate::core::ribosome::ZomesToInvoke;
-use derive_more::Constructor;
 use holo_hash::EntryHash;
 use holochain_serialized_bytes::prelude::*;
 use holochain_types::dna::zome::HostFnAccess;
 use holochain_zome_types::entry::Entry;
 use holochain_zome_types::validate::ValidateCallbackResult;
+use holochain_zome_types::validate::ValidateData;
+use holochain_zome_types::validate::ValidationPackage;
 use holochain_zome_types::zome::ZomeName;
 use holochain_zome_types::ExternInput;
 use std::sync::Arc;
@@ -20,6 +21,8 @@ pub struct ValidateInvocation {
     // we can SerializedBytes off an Entry reference
     // lifetimes on invocations are a pain
     pub entry: Arc<Entry>,
+    /// The author's earlier elements, if the entry def asks for them
+    pub validation_package: Option<Arc<ValidationPackage>>,
 }
 
 impl ValidateInvocation {
@@ -27,12 +30,30 @@ impl ValidateInvocation {
         Self {
             zome_name,
             entry: Arc::new(entry),
+            validation_package: None,
         }
     }
+
+    /// The data the guest validates
+    fn validate_data(&self) -> ValidateData {
+        ValidateData {
+            entry: (*self.entry).clone(),
+            validation_package: self.validation_package.as_deref().cloned(),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ValidateHostAccess {
+    /// The author's earlier elements, for the validation package host fns
+    pub validation_package: Option<Arc<ValidationPackage>>,
 }
 
-#[derive(Clone, Constructor)]
-pub struct ValidateHostAccess;
+impl ValidateHostAccess {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
 impl From<ValidateHostAccess> for HostAccess {
     fn from(validate_host_access: ValidateHostAccess) -> Self {
@@ -67,14 +88,14 @@ impl Invocation for ValidateInvocation {
         .into()
     }
     fn host_input(self) -> Result<ExternInput, SerializedBytesError> {
-        Ok(ExternInput::new((&*self.entry).try_into()?))
+        Ok(ExternInput::new(self.validate_data().try_into()?))
     }
 }
 
 impl TryFrom<ValidateInvocation> for ExternInput {
     type Error = SerializedBytesError;
     fn try_from(validate_invocation: ValidateInvocation) -> Result<Self, Self::Error> {
-        Ok(Self::new((&*validate_invocation.entry).try_into()?))
+        Ok(Self::new(validate_invocation.validate_data().try_into()?))
     }
 }
 
@@ -126,6 +147,7 @@ mod test {
     use holochain_types::{dna::zome::HostFnAccess, fixt::*};
     use holochain_zome_types::entry::Entry;
     use holochain_zome_types::validate::ValidateCallbackResult;
+    use holochain_zome_types::validate::ValidateData;
     use holochain_zome_types::ExternInput;
     use rand::seq::SliceRandom;
     use std::sync::Arc;
@@ -254,7 +276,13 @@ mod test {
 
         assert_eq!(
             host_input,
-            ExternInput::new(SerializedBytes::try_from(&*validate_invocation.entry).unwrap()),
+            ExternInput::new(
+                SerializedBytes::try_from(ValidateData {
+                    entry: (*validate_invocation.entry).clone(),
+                    validation_package: None,
+                })
+                .unwrap()
+            ),
         );
     }
 }
@@ -290,7 +318,7 @@ mod slow_tests {
         validate_invocation.zome_name = TestWasm::Foo.into();
 
         let result = ribosome
-            .run_validate(ValidateHostAccess, validate_invocation)
+            .run_validate(ValidateHostAccess::new(), validate_invocation)
             .unwrap();
         assert_eq!(result, ValidateResult::Valid,);
     }
@@ -306,7 +334,7 @@ mod slow_tests {
         validate_invocation.zome_name = TestWasm::ValidateValid.into();
 
         let result = ribosome
-            .run_validate(ValidateHostAccess, validate_invocation)
+            .run_validate(ValidateHostAccess::new(), validate_invocation)
             .unwrap();
         assert_eq!(result, ValidateResult::Valid,);
     }
@@ -322,7 +350,7 @@ mod slow_tests {
         validate_invocation.zome_name = TestWasm::ValidateInvalid.into();
 
         let result = ribosome
-            .run_validate(ValidateHostAccess, validate_invocation)
+            .run_validate(ValidateHostAccess::new(), validate_invocation)
             .unwrap();
         assert_eq!(result, ValidateResult::Invalid("esoteric edge case".into()),);
     }
@@ -346,7 +374,7 @@ mod slow_tests {
         validate_invocation.entry = Arc::new(entry);
 
         let result = ribosome
-            .run_validate(ValidateHostAccess, validate_invocation)
+            .run_validate(ValidateHostAccess::new(), validate_invocation)
             .unwrap();
         assert_eq!(result, ValidateResult::Invalid("esoteric edge case".into()));
     }
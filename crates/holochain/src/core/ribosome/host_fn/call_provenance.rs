@@ -0,0 +1,50 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_zome_types::CallProvenanceInput;
+use holochain_zome_types::CallProvenanceOutput;
+use std::sync::Arc;
+
+/// The path of calls which led to the running zome function, ending with it.
+/// Callbacks have no path.
+pub fn call_provenance(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    _input: CallProvenanceInput,
+) -> RibosomeResult<CallProvenanceOutput> {
+    Ok(CallProvenanceOutput::new(
+        call_context.host_access().call_provenance().cloned(),
+    ))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::call_provenance;
+    use crate::core::ribosome::CallContext;
+    use crate::fixt::CallProvenanceFixturator;
+    use crate::fixt::WasmRibosomeFixturator;
+    use crate::fixt::ZomeCallHostAccessFixturator;
+    use crate::fixt::ZomeNameFixturator;
+    use ::fixt::prelude::*;
+    use holochain_zome_types::CallProvenanceInput;
+    use std::sync::Arc;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn call_provenance_is_the_zome_calls() {
+        let ribosome = WasmRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+            .next()
+            .unwrap();
+        let provenance = fixt!(CallProvenance);
+        let mut host_access = fixt!(ZomeCallHostAccess);
+        host_access.call_provenance = provenance.clone();
+        let call_context = CallContext::new(fixt!(ZomeName), host_access.into());
+
+        let output = call_provenance(
+            Arc::new(ribosome),
+            Arc::new(call_context),
+            CallProvenanceInput::new(()),
+        )
+        .unwrap();
+        assert_eq!(output.into_inner(), Some(provenance));
+    }
+}
@@ -13,21 +13,25 @@ pub fn call_remote(
     call_context: Arc<CallContext>,
     input: CallRemoteInput,
 ) -> RibosomeResult<CallRemoteOutput> {
+    let cancel = call_context.host_access.cancel();
     // it is the network's responsibility to handle timeouts and return an Err result in that case
-    let result: ZomeCallResponse = tokio_safe_block_on::tokio_safe_block_forever_on(async move {
-        let mut network = call_context.host_access().network().clone();
-        let call_remote = input.into_inner();
-        network
-            .call_remote(
-                call_remote.to_agent(),
-                call_remote.zome_name(),
-                call_remote.fn_name(),
-                call_remote.cap(),
-                call_remote.request(),
-            )
-            .await
-    })?
-    .try_into()?;
+    let result: ZomeCallResponse =
+        tokio_safe_block_on::tokio_safe_block_forever_on(cancel.or_cancel(async move {
+            let mut network = call_context.host_access().network().clone();
+            let provenance = call_context.host_access().call_provenance().cloned();
+            let call_remote = input.into_inner();
+            network
+                .call_remote(
+                    call_remote.to_agent(),
+                    call_remote.zome_name(),
+                    call_remote.fn_name(),
+                    call_remote.cap(),
+                    provenance,
+                    call_remote.request(),
+                )
+                .await
+        }))??
+        .try_into()?;
 
     Ok(CallRemoteOutput::new(result))
 }
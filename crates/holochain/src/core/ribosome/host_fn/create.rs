@@ -51,10 +51,8 @@ pub fn create<'a>(
     };
 
     // build a header for the entry being committed
-    let header_builder = builder::Create {
-        entry_type,
-        entry_hash,
-    };
+    let header_builder = builder::Create::for_entry(entry_type, entry_hash, &entry)
+        .map_err(SourceChainError::from)?;
     let host_access = call_context.host_access();
 
     // return the hash of the committed entry
@@ -65,6 +63,9 @@ pub fn create<'a>(
         let mut guard = host_access.workspace().write().await;
         let workspace: &mut CallZomeWorkspace = &mut guard;
         let source_chain = &mut workspace.source_chain;
+        if let Some(quota) = host_access.quota() {
+            quota.check_write(source_chain)?;
+        }
         // push the header and the entry into the source chain
         let header_hash = source_chain.put(header_builder, Some(entry)).await?;
         // fetch the element we just added so we can integrate its DhtOps
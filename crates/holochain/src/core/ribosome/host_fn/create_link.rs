@@ -1,13 +1,19 @@
+use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesInvocation;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesResult;
+use crate::core::state::source_chain::SourceChainError;
 use crate::core::workflow::integrate_dht_ops_workflow::integrate_to_cache;
 use crate::core::{
     ribosome::{CallContext, RibosomeT},
     workflow::CallZomeWorkspace,
-    SourceChainResult,
 };
 use holochain_zome_types::header::builder;
+use holochain_zome_types::header::LinkTypeIndex;
+use holochain_zome_types::link::LinkTypeId;
 use holochain_zome_types::CreateLinkInput;
 use holochain_zome_types::CreateLinkOutput;
+use std::convert::TryFrom;
 use std::sync::Arc;
 
 #[allow(clippy::extra_unused_lifetimes)]
@@ -16,18 +22,32 @@ pub fn create_link<'a>(
     call_context: Arc<CallContext>,
     input: CreateLinkInput,
 ) -> RibosomeResult<CreateLinkOutput> {
-    let (base_address, target_address, tag) = input.into_inner();
+    let (base_address, target_address, link_type_id, tag) = input.into_inner();
 
     // extract the zome position
     let zome_id = ribosome.zome_name_to_id(&call_context.zome_name)?;
 
+    // extract the link type position if this is a typed link
+    let link_type = match link_type_id {
+        Some(link_type_id) => Some(extract_link_type(
+            ribosome,
+            call_context.clone(),
+            link_type_id,
+        )?),
+        None => None,
+    };
+
     // Construct the link add
-    let header_builder = builder::CreateLink::new(base_address, target_address, zome_id, tag);
+    let header_builder =
+        builder::CreateLink::new(base_address, target_address, zome_id, link_type, tag);
 
     let header_hash =
         tokio_safe_block_on::tokio_safe_block_forever_on(tokio::task::spawn(async move {
             let mut guard = call_context.host_access.workspace().write().await;
             let workspace: &mut CallZomeWorkspace = &mut guard;
+            if let Some(quota) = call_context.host_access.quota() {
+                quota.check_write(&workspace.source_chain)?;
+            }
             // push the header into the source chain
             let header_hash = workspace.source_chain.put(header_builder, None).await?;
             let element = workspace
@@ -40,8 +60,9 @@ pub fn create_link<'a>(
                 &mut workspace.cache_meta,
             )
             .await
-            .map_err(Box::new)?;
-            SourceChainResult::Ok(header_hash)
+            .map_err(Box::new)
+            .map_err(SourceChainError::from)?;
+            RibosomeResult::Ok(header_hash)
         }))??;
 
     // return the hash of the committed link
@@ -51,4 +72,37 @@ pub fn create_link<'a>(
     Ok(CreateLinkOutput::new(header_hash))
 }
 
+pub fn extract_link_type(
+    ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    link_type_id: LinkTypeId,
+) -> RibosomeResult<LinkTypeIndex> {
+    let position =
+        match ribosome.run_link_types((&call_context.host_access).into(), LinkTypesInvocation)? {
+            // the ribosome returned some link types
+            LinkTypesResult::Defs(defs) => defs
+                .get(&call_context.zome_name)
+                // convert the link type id string into a numeric position in the link types
+                .and_then(|link_types| link_types.link_type_id_position(&link_type_id)),
+            LinkTypesResult::Err(zome_name, error) => {
+                return Err(RibosomeError::LinkTypes(zome_name, error))
+            }
+        };
+    let position = position.ok_or_else(|| {
+        RibosomeError::LinkTypes(
+            call_context.zome_name.clone(),
+            format!("link type not found for {:?}", link_type_id),
+        )
+    })?;
+    // link types are indexed by a byte
+    u8::try_from(position)
+        .map(LinkTypeIndex::from)
+        .map_err(|_| {
+            RibosomeError::LinkTypes(
+                call_context.zome_name.clone(),
+                format!("too many link types to index {:?}", link_type_id),
+            )
+        })
+}
+
 // we rely on the tests for get_links and get_link_details
@@ -0,0 +1,85 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::host_fn::create_link::extract_link_type;
+use crate::core::state::source_chain::SourceChainError;
+use crate::core::workflow::integrate_dht_ops_workflow::integrate_to_cache;
+use crate::core::{
+    ribosome::{CallContext, RibosomeT},
+    workflow::CallZomeWorkspace,
+};
+use holochain_zome_types::header::builder;
+use holochain_zome_types::CreateLinksInput;
+use holochain_zome_types::CreateLinksOutput;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[allow(clippy::extra_unused_lifetimes)]
+pub fn create_links<'a>(
+    ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: CreateLinksInput,
+) -> RibosomeResult<CreateLinksOutput> {
+    // extract the zome position
+    let zome_id = ribosome.zome_name_to_id(&call_context.zome_name)?;
+
+    // construct every link add before writing any of them,
+    // looking up each link type once however many links use it
+    let mut link_types = HashMap::new();
+    let mut header_builders = Vec::new();
+    for link in input.into_inner() {
+        let (base_address, target_address, link_type_id, tag) = link.into_inner();
+        let link_type = match link_type_id {
+            Some(link_type_id) => match link_types.get(&link_type_id) {
+                Some(link_type) => Some(*link_type),
+                None => {
+                    let link_type = extract_link_type(
+                        ribosome.clone(),
+                        call_context.clone(),
+                        link_type_id.clone(),
+                    )?;
+                    link_types.insert(link_type_id, link_type);
+                    Some(link_type)
+                }
+            },
+            None => None,
+        };
+        header_builders.push(builder::CreateLink::new(
+            base_address,
+            target_address,
+            zome_id,
+            link_type,
+            tag,
+        ));
+    }
+
+    let header_hashes =
+        tokio_safe_block_on::tokio_safe_block_forever_on(tokio::task::spawn(async move {
+            let mut guard = call_context.host_access.workspace().write().await;
+            let workspace: &mut CallZomeWorkspace = &mut guard;
+            if let Some(quota) = call_context.host_access.quota() {
+                quota.check_writes(&workspace.source_chain, header_builders.len() as u32)?;
+            }
+            // push the headers into the source chain in one pass
+            let mut header_hashes = Vec::with_capacity(header_builders.len());
+            for header_builder in header_builders {
+                let header_hash = workspace.source_chain.put(header_builder, None).await?;
+                let element = workspace
+                    .source_chain
+                    .get_element(&header_hash)?
+                    .expect("Element we just put in SourceChain must be gettable");
+                integrate_to_cache(
+                    &element,
+                    workspace.source_chain.elements(),
+                    &mut workspace.cache_meta,
+                )
+                .await
+                .map_err(Box::new)
+                .map_err(SourceChainError::from)?;
+                header_hashes.push(header_hash);
+            }
+            RibosomeResult::Ok(header_hashes)
+        }))??;
+
+    // return the hashes of the committed links, in the order they were given
+    // as with create_link, if validation fails the whole call is rolled back
+    Ok(CreateLinksOutput::new(header_hashes))
+}
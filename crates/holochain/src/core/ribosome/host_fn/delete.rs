@@ -29,6 +29,9 @@ pub fn delete<'a>(
         let mut guard = host_access.workspace().write().await;
         let workspace: &mut CallZomeWorkspace = &mut guard;
         let source_chain = &mut workspace.source_chain;
+        if let Some(quota) = host_access.quota() {
+            quota.check_write(source_chain)?;
+        }
         let header_builder = builder::Delete {
             deletes_address,
             deletes_entry_address,
@@ -2,11 +2,10 @@ use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::RibosomeT;
-use crate::core::state::cascade::error::CascadeResult;
 use crate::core::workflow::call_zome_workflow::CallZomeWorkspace;
 use crate::core::{workflow::integrate_dht_ops_workflow::integrate_to_cache, SourceChainError};
-use holochain_p2p::actor::GetOptions;
-use holochain_types::element::SignedHeaderHashed;
+use holo_hash::{EntryHash, HeaderHash};
+use holochain_p2p::{actor::GetOptions, HolochainP2pCell};
 use holochain_zome_types::header::builder;
 use holochain_zome_types::DeleteLinkInput;
 use holochain_zome_types::DeleteLinkOutput;
@@ -21,48 +20,15 @@ pub fn delete_link<'a>(
 ) -> RibosomeResult<DeleteLinkOutput> {
     let link_add_address = input.into_inner();
 
-    // get the base address from the add link header
-    // don't allow the wasm developer to get this wrong
-    // it is never valid to have divergent base address for add/remove links
-    // the subconscious will validate the base address match but we need to fetch it here to
-    // include it in the remove link header
     let network = call_context.host_access.network().clone();
     let address = link_add_address.clone();
     let call_context_2 = call_context.clone();
 
     // handle timeouts at the network layer
-    let maybe_add_link: Option<SignedHeaderHashed> =
-        tokio_safe_block_on::tokio_safe_block_forever_on(async move {
-            CascadeResult::Ok(
-                call_context_2
-                    .clone()
-                    .host_access
-                    .workspace()
-                    .write()
-                    .await
-                    .cascade(network)
-                    .dht_get(address.into(), GetOptions::default())
-                    .await?
-                    .map(|el| el.into_inner().0),
-            )
-        })?;
-
-    let base_address = match maybe_add_link {
-        Some(add_link_signed_header_hash) => {
-            match add_link_signed_header_hash.header() {
-                Header::CreateLink(link_add_header) => Ok(link_add_header.base_address.clone()),
-                // the add link header hash provided was found but didn't point to an AddLink
-                // header (it is something else) so we cannot proceed
-                _ => Err(RibosomeError::ElementDeps(link_add_address.clone().into())),
-            }
-        }
-        // the add link header hash could not be found
-        // it's unlikely that a wasm call would have a valid add link header hash from "somewhere"
-        // that isn't also discoverable in either the cache or DHT, but it _is_ possible so we have
-        // to fail in that case (e.g. the local cache could have GC'd at the same moment the
-        // network connection dropped out)
-        None => Err(RibosomeError::ElementDeps(link_add_address.clone().into())),
-    }?;
+    let base_address = tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let mut guard = call_context_2.host_access.workspace().write().await;
+        link_base_address(&mut guard, network, address).await
+    })?;
 
     let workspace_lock = call_context.host_access.workspace();
 
@@ -73,6 +39,9 @@ pub fn delete_link<'a>(
         let mut guard = workspace_lock.write().await;
         let workspace: &mut CallZomeWorkspace = &mut guard;
         let source_chain = &mut workspace.source_chain;
+        if let Some(quota) = call_context.host_access.quota() {
+            quota.check_write(source_chain)?;
+        }
         let header_builder = builder::DeleteLink {
             link_add_address,
             base_address,
@@ -93,6 +62,40 @@ pub fn delete_link<'a>(
     })
 }
 
+/// Get the base address from the add link header.
+/// Don't allow the wasm developer to get this wrong:
+/// it is never valid to have divergent base address for add/remove links.
+/// The subconscious will validate the base address match but we need to fetch it here to
+/// include it in the remove link header.
+pub(crate) async fn link_base_address(
+    workspace: &mut CallZomeWorkspace,
+    network: HolochainP2pCell,
+    link_add_address: HeaderHash,
+) -> RibosomeResult<EntryHash> {
+    let maybe_add_link = workspace
+        .cascade(network)
+        .dht_get(link_add_address.clone().into(), GetOptions::default())
+        .await?
+        .map(|el| el.into_inner().0);
+
+    match maybe_add_link {
+        Some(add_link_signed_header_hash) => {
+            match add_link_signed_header_hash.header() {
+                Header::CreateLink(link_add_header) => Ok(link_add_header.base_address.clone()),
+                // the add link header hash provided was found but didn't point to an AddLink
+                // header (it is something else) so we cannot proceed
+                _ => Err(RibosomeError::ElementDeps(link_add_address.into())),
+            }
+        }
+        // the add link header hash could not be found
+        // it's unlikely that a wasm call would have a valid add link header hash from "somewhere"
+        // that isn't also discoverable in either the cache or DHT, but it _is_ possible so we have
+        // to fail in that case (e.g. the local cache could have GC'd at the same moment the
+        // network connection dropped out)
+        None => Err(RibosomeError::ElementDeps(link_add_address.into())),
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "slow_tests")]
 pub mod slow_tests {
@@ -0,0 +1,112 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::host_fn::delete_link::link_base_address;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use crate::core::workflow::call_zome_workflow::CallZomeWorkspace;
+use crate::core::{workflow::integrate_dht_ops_workflow::integrate_to_cache, SourceChainError};
+use holochain_zome_types::header::builder;
+use holochain_zome_types::DeleteLinksInput;
+use holochain_zome_types::DeleteLinksOutput;
+use std::sync::Arc;
+
+#[allow(clippy::extra_unused_lifetimes)]
+pub fn delete_links<'a>(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: DeleteLinksInput,
+) -> RibosomeResult<DeleteLinksOutput> {
+    let link_add_addresses = input.into_inner();
+    let network = call_context.host_access.network().clone();
+    let workspace_lock = call_context.host_access.workspace();
+
+    // handle timeouts at the network layer
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let mut guard = workspace_lock.write().await;
+        let workspace: &mut CallZomeWorkspace = &mut guard;
+
+        // find the base of every link before writing any of the deletes,
+        // so one missing link add leaves the chain untouched
+        let mut header_builders = Vec::with_capacity(link_add_addresses.len());
+        for link_add_address in link_add_addresses {
+            let base_address =
+                link_base_address(workspace, network.clone(), link_add_address.clone()).await?;
+            header_builders.push(builder::DeleteLink {
+                link_add_address,
+                base_address,
+            });
+        }
+
+        if let Some(quota) = call_context.host_access.quota() {
+            quota.check_writes(&workspace.source_chain, header_builders.len() as u32)?;
+        }
+        // add the DeleteLinks to the source chain in one pass
+        let mut header_hashes = Vec::with_capacity(header_builders.len());
+        for header_builder in header_builders {
+            let header_hash = workspace.source_chain.put(header_builder, None).await?;
+            let element = workspace
+                .source_chain
+                .get_element(&header_hash)?
+                .expect("Element we just put in SourceChain must be gettable");
+            integrate_to_cache(
+                &element,
+                workspace.source_chain.elements(),
+                &mut workspace.cache_meta,
+            )
+            .await
+            .map_err(Box::new)
+            .map_err(SourceChainError::from)?;
+            header_hashes.push(header_hash);
+        }
+        Ok(DeleteLinksOutput::new(header_hashes))
+    })
+}
+
+#[cfg(test)]
+#[cfg(feature = "slow_tests")]
+pub mod slow_tests {
+
+    use crate::fixt::ZomeCallHostAccessFixturator;
+    use ::fixt::prelude::*;
+    use holo_hash::HeaderHash;
+    use holochain_wasm_test_utils::TestWasm;
+    use holochain_zome_types::link::Links;
+    use holochain_zome_types::DeleteLinksInput;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn ribosome_create_and_delete_links_in_bulk() {
+        let test_env = holochain_state::test_utils::test_cell_env();
+        let env = test_env.env();
+
+        let mut workspace =
+            crate::core::workflow::CallZomeWorkspace::new(env.clone().into()).unwrap();
+
+        // commits fail validation if we don't do genesis
+        crate::core::workflow::fake_genesis(&mut workspace.source_chain)
+            .await
+            .unwrap();
+
+        let workspace_lock = crate::core::workflow::CallZomeWorkspaceLock::new(workspace);
+        let mut host_access = fixt!(ZomeCallHostAccess);
+        host_access.workspace = workspace_lock;
+
+        // three links in one call, one header each
+        let link_headers: Vec<HeaderHash> =
+            crate::call_test_ribosome!(host_access, TestWasm::Link, "create_links", ());
+        assert_eq!(link_headers.len(), 3);
+
+        let links: Links = crate::call_test_ribosome!(host_access, TestWasm::Link, "get_links", ());
+        assert_eq!(links.into_inner().len(), 3);
+
+        // delete two of them in one call
+        let deletes: Vec<HeaderHash> = crate::call_test_ribosome!(
+            host_access,
+            TestWasm::Link,
+            "delete_links",
+            DeleteLinksInput::new(link_headers[..2].to_vec())
+        );
+        assert_eq!(deletes.len(), 2);
+
+        let links: Links = crate::call_test_ribosome!(host_access, TestWasm::Link, "get_links", ());
+        assert_eq!(links.into_inner().len(), 1);
+    }
+}
@@ -1,14 +1,116 @@
+use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
+use crate::core::ribosome::guest_callback::entry_defs::EntryDefsResult;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::RibosomeT;
+use holochain_zome_types::entry_def::EntryDefsQuery;
+use holochain_zome_types::zome::ZomeName;
 use holochain_zome_types::EntryTypePropertiesInput;
 use holochain_zome_types::EntryTypePropertiesOutput;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+/// The entry defs registered by the zomes in the DNA, so a zome can
+/// adapt to the entry types it's running alongside.
 pub fn entry_type_properties(
-    _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
-    _input: EntryTypePropertiesInput,
+    ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: EntryTypePropertiesInput,
 ) -> RibosomeResult<EntryTypePropertiesOutput> {
-    unimplemented!();
+    let mut defs =
+        match ribosome.run_entry_defs((&call_context.host_access).into(), EntryDefsInvocation)? {
+            EntryDefsResult::Defs(defs) => defs,
+            EntryDefsResult::Err(zome_name, msg) => {
+                return Err(RibosomeError::EntryDefs(zome_name, msg))
+            }
+        };
+    let zome_name = match input.into_inner() {
+        EntryDefsQuery::AllZomes => return Ok(EntryTypePropertiesOutput::new(defs)),
+        EntryDefsQuery::ThisZome => call_context.zome_name(),
+        EntryDefsQuery::Zome(zome_name) => zome_name,
+    };
+    ribosome
+        .dna_file()
+        .dna()
+        .get_zome(&zome_name)
+        .map_err(|_| RibosomeError::ZomeNotExists(zome_name.clone()))?;
+    // zomes without an entry_defs callback have no defs
+    let zome_defs = defs.remove(&zome_name).unwrap_or_else(|| vec![].into());
+    let mut only: BTreeMap<ZomeName, _> = BTreeMap::new();
+    only.insert(zome_name, zome_defs);
+    Ok(EntryTypePropertiesOutput::new(only))
+}
+
+#[cfg(test)]
+#[cfg(feature = "slow_tests")]
+pub mod slow_tests {
+    use super::entry_type_properties;
+    use crate::core::ribosome::error::RibosomeError;
+    use crate::core::ribosome::CallContext;
+    use crate::fixt::curve::Zomes;
+    use crate::fixt::WasmRibosomeFixturator;
+    use crate::fixt::ZomeCallHostAccessFixturator;
+    use ::fixt::prelude::*;
+    use holochain_wasm_test_utils::TestWasm;
+    use holochain_zome_types::entry_def::EntryDefId;
+    use holochain_zome_types::entry_def::EntryDefsQuery;
+    use holochain_zome_types::entry_def::EntryVisibility;
+    use holochain_zome_types::zome::ZomeName;
+    use holochain_zome_types::EntryTypePropertiesInput;
+    use std::sync::Arc;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn entry_type_properties_by_zome() {
+        let ribosome = Arc::new(
+            WasmRibosomeFixturator::new(Zomes(vec![TestWasm::EntryDefs, TestWasm::Foo]))
+                .next()
+                .unwrap(),
+        );
+        let entry_defs_zome: ZomeName = TestWasm::EntryDefs.into();
+        let foo_zome: ZomeName = TestWasm::Foo.into();
+        let call_context = Arc::new(CallContext::new(
+            entry_defs_zome.clone(),
+            fixt!(ZomeCallHostAccess).into(),
+        ));
+        let query = |query| {
+            entry_type_properties(
+                ribosome.clone(),
+                call_context.clone(),
+                EntryTypePropertiesInput::new(query),
+            )
+            .map(|output| output.into_inner())
+        };
+
+        // the calling zome's own defs
+        let defs = query(EntryDefsQuery::ThisZome).unwrap();
+        assert_eq!(defs.len(), 1);
+        let own = defs[&entry_defs_zome]
+            .iter()
+            .map(|def| (def.id.clone(), def.visibility))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            own,
+            vec![
+                (EntryDefId::from("post"), EntryVisibility::Public),
+                (EntryDefId::from("comment"), EntryVisibility::Private),
+            ]
+        );
+
+        // a zome without any entry types has no defs
+        let defs = query(EntryDefsQuery::Zome(foo_zome.clone())).unwrap();
+        assert_eq!(defs[&foo_zome].iter().count(), 0);
+
+        // every zome which defines entry types
+        let defs = query(EntryDefsQuery::AllZomes).unwrap();
+        assert_eq!(defs.keys().collect::<Vec<_>>(), vec![&entry_defs_zome]);
+
+        // a zome not in the dna
+        match query(EntryDefsQuery::Zome("missing".into())) {
+            Err(RibosomeError::ZomeNotExists(zome_name)) => {
+                assert_eq!(zome_name, "missing".into())
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
 }
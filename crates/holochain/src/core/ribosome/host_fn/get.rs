@@ -14,9 +14,10 @@ pub fn get<'a>(
 
     // Get the network from the context
     let network = call_context.host_access.network().clone();
+    let cancel = call_context.host_access.cancel();
 
     // timeouts must be handled by the network
-    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+    tokio_safe_block_on::tokio_safe_block_forever_on(cancel.or_cancel(async move {
         let maybe_element = call_context
             .host_access
             .workspace()
@@ -27,7 +28,7 @@ pub fn get<'a>(
             .await?;
 
         Ok(GetOutput::new(maybe_element))
-    })
+    }))?
 }
 
 // we are relying on the create tests to show the commit/get round trip
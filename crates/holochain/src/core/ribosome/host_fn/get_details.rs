@@ -1,31 +1,142 @@
 use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::guest_callback::entry_defs::{
+    EntryDefsHostAccess, EntryDefsInvocation, EntryDefsResult,
+};
 use crate::core::ribosome::{CallContext, RibosomeT};
-use holochain_zome_types::{GetDetailsInput, GetDetailsOutput};
+use holo_hash::AnyDhtHash;
+use holochain_types::entry::entry_references;
+use holochain_zome_types::{
+    entry::Entry,
+    header::{EntryType, Header},
+    metadata::Details,
+    GetDetailsInput, GetDetailsOutput,
+};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
+/// The most references followed for a single get_details, at any depth.
+const MAX_FOLLOWED_REFERENCES: usize = 100;
+
+/// The reference fields declared for each app entry type,
+/// keyed by zome id and entry def index
+type ReferenceFields = BTreeMap<(u8, u8), Vec<String>>;
+
 #[allow(clippy::extra_unused_lifetimes)]
 pub fn get_details<'a>(
-    _ribosome: Arc<impl RibosomeT>,
+    ribosome: Arc<impl RibosomeT>,
     call_context: Arc<CallContext>,
     input: GetDetailsInput,
 ) -> RibosomeResult<GetDetailsOutput> {
     let (hash, options) = input.into_inner();
+    let depth = options.follow_references;
+    let reference_fields = if depth > 0 {
+        reference_fields(ribosome.as_ref())?
+    } else {
+        ReferenceFields::new()
+    };
 
     // Get the network from the context
     let network = call_context.host_access.network().clone();
+    let cancel = call_context.host_access.cancel();
 
     // timeouts must be handled by the network
-    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
-        let maybe_details = call_context
-            .host_access
-            .workspace()
-            .write()
-            .await
-            .cascade(network)
-            .get_details(hash, options.into())
-            .await?;
+    tokio_safe_block_on::tokio_safe_block_forever_on(cancel.or_cancel(async move {
+        let mut workspace = call_context.host_access.workspace().write().await;
+        let mut cascade = workspace.cascade(network);
+        let mut maybe_details = cascade.get_details(hash.clone(), options.into()).await?;
+        if depth == 0 {
+            return Ok(GetDetailsOutput::new(maybe_details));
+        }
+
+        // Walk the references breadth first, getting each element once
+        let mut next = match &maybe_details {
+            Some(Details::Element(details)) => references(
+                &reference_fields,
+                details.element.header().entry_type(),
+                details.element.entry().as_option(),
+            ),
+            Some(Details::Entry(details)) => references(
+                &reference_fields,
+                details.headers.first().and_then(Header::entry_type),
+                Some(&details.entry),
+            ),
+            None => Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        seen.insert(hash);
+        let mut followed = Vec::new();
+        for _ in 0..depth {
+            let mut found = Vec::new();
+            for hash in next {
+                if seen.len() > MAX_FOLLOWED_REFERENCES {
+                    break;
+                }
+                if !seen.insert(hash.clone()) {
+                    continue;
+                }
+                if let Some(element) = cascade.dht_get(hash, Default::default()).await? {
+                    found.push(element);
+                }
+            }
+            next = found
+                .iter()
+                .flat_map(|element| {
+                    references(
+                        &reference_fields,
+                        element.header().entry_type(),
+                        element.entry().as_option(),
+                    )
+                })
+                .collect();
+            followed.extend(found);
+        }
+
+        match &mut maybe_details {
+            Some(Details::Element(details)) => details.references = followed,
+            Some(Details::Entry(details)) => details.references = followed,
+            None => (),
+        }
         Ok(GetDetailsOutput::new(maybe_details))
-    })
+    }))?
+}
+
+/// Find the fields each app entry type declares as references
+fn reference_fields(ribosome: &impl RibosomeT) -> RibosomeResult<ReferenceFields> {
+    let mut fields = ReferenceFields::new();
+    if let EntryDefsResult::Defs(mut defs) =
+        ribosome.run_entry_defs(EntryDefsHostAccess, EntryDefsInvocation)?
+    {
+        for (zome_id, (zome_name, _)) in ribosome.dna_file().dna().zomes.iter().enumerate() {
+            let entry_defs = match defs.remove(zome_name) {
+                Some(entry_defs) => entry_defs,
+                None => continue,
+            };
+            for (id, entry_def) in entry_defs.into_iter().enumerate() {
+                if !entry_def.references.is_empty() {
+                    fields.insert((zome_id as u8, id as u8), entry_def.references);
+                }
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// The hashes an entry references through its declared fields
+fn references(
+    reference_fields: &ReferenceFields,
+    entry_type: Option<&EntryType>,
+    entry: Option<&Entry>,
+) -> Vec<AnyDhtHash> {
+    match (entry_type, entry) {
+        (Some(EntryType::App(app_entry_type)), Some(entry)) => reference_fields
+            .get(&(
+                u8::from(app_entry_type.zome_id()),
+                u8::from(app_entry_type.id()),
+            ))
+            .map(|fields| entry_references(entry, fields))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,42 @@
+use super::get_links_in_range::{live_links_in_range, ordered_link_type};
+use crate::core::ribosome::error::RibosomeError;
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::{CallContext, RibosomeT};
+use holochain_zome_types::GetLinkAggregateInput;
+use holochain_zome_types::GetLinkAggregateOutput;
+use std::sync::Arc;
+
+/// Aggregate the tag values of the links of an ordered link type on a base,
+/// from the ordered index this cell keeps as an authority.
+pub fn get_link_aggregate(
+    ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: GetLinkAggregateInput,
+) -> RibosomeResult<GetLinkAggregateOutput> {
+    let (base_address, link_type_id, op) = input.into_inner();
+
+    // Get zome id
+    let zome_id = ribosome.zome_name_to_id(&call_context.zome_name)?;
+
+    let (link_type, tag_order) = ordered_link_type(ribosome, &call_context, &link_type_id)?;
+    let zome_name = call_context.zome_name.clone();
+    let links = live_links_in_range(
+        call_context,
+        base_address,
+        zome_id,
+        link_type,
+        tag_order,
+        None,
+        None,
+    )?;
+    match op.aggregate(tag_order, links.iter().map(|link| &link.tag)) {
+        Some(aggregate) => Ok(GetLinkAggregateOutput::new(aggregate)),
+        None => Err(RibosomeError::LinkTypes(
+            zome_name,
+            format!(
+                "{:?} can't be applied to link type {:?} ordered by {:?}",
+                op, link_type_id, tag_order
+            ),
+        )),
+    }
+}
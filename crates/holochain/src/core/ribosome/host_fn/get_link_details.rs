@@ -22,8 +22,9 @@ pub fn get_link_details<'a>(
 
     // Get the network from the context
     let network = call_context.host_access.network().clone();
+    let cancel = call_context.host_access.cancel();
 
-    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+    tokio_safe_block_on::tokio_safe_block_forever_on(cancel.or_cancel(async move {
         // Create the key
         let key = match tag.as_ref() {
             Some(tag) => LinkMetaKey::BaseZomeTag(&base_address, zome_id, tag),
@@ -43,7 +44,7 @@ pub fn get_link_details<'a>(
         );
 
         Ok(GetLinkDetailsOutput::new(link_details))
-    })
+    }))?
 }
 
 #[cfg(test)]
@@ -0,0 +1,117 @@
+use crate::core::ribosome::error::RibosomeError;
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesInvocation;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesResult;
+use crate::core::{
+    ribosome::{CallContext, RibosomeT},
+    state::metadata::{LinkTagIndexBuf, MetadataBuf, MetadataBufT},
+};
+use fallible_iterator::FallibleIterator;
+use holo_hash::EntryHash;
+use holochain_state::{error::DatabaseResult, fresh_reader};
+use holochain_zome_types::header::{LinkTypeIndex, ZomeId};
+use holochain_zome_types::link::{Link, LinkTag, LinkTagOrder, LinkTypeId};
+use holochain_zome_types::GetLinksInRangeInput;
+use holochain_zome_types::GetLinksInRangeOutput;
+use std::sync::Arc;
+
+/// Get the links of an ordered link type on a base with tags in a range,
+/// from the ordered index this cell keeps as an authority.
+pub fn get_links_in_range(
+    ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: GetLinksInRangeInput,
+) -> RibosomeResult<GetLinksInRangeOutput> {
+    let (base_address, link_type_id, start, end) = input.into_inner();
+
+    // Get zome id
+    let zome_id = ribosome.zome_name_to_id(&call_context.zome_name)?;
+
+    let (link_type, tag_order) = ordered_link_type(ribosome, &call_context, &link_type_id)?;
+    let links = live_links_in_range(
+        call_context,
+        base_address,
+        zome_id,
+        link_type,
+        tag_order,
+        start,
+        end,
+    )?;
+    Ok(GetLinksInRangeOutput::new(links.into()))
+}
+
+/// Find the index and tag order of one of the calling zome's ordered link types
+pub fn ordered_link_type(
+    ribosome: Arc<impl RibosomeT>,
+    call_context: &CallContext,
+    link_type_id: &LinkTypeId,
+) -> RibosomeResult<(LinkTypeIndex, LinkTagOrder)> {
+    let link_type = match ribosome
+        .run_link_types((&call_context.host_access).into(), LinkTypesInvocation)?
+    {
+        LinkTypesResult::Defs(defs) => defs.get(&call_context.zome_name).and_then(|link_types| {
+            let index = link_types.link_type_id_position(link_type_id)?;
+            Some((index, link_types.tag_order(index)))
+        }),
+        _ => None,
+    };
+    match link_type {
+        Some((index, Some(tag_order))) => Ok((LinkTypeIndex::from(index as u8), tag_order)),
+        Some((_, None)) => Err(RibosomeError::LinkTypes(
+            call_context.zome_name.clone(),
+            format!("link type {:?} does not declare a tag order", link_type_id),
+        )),
+        None => Err(RibosomeError::LinkTypes(
+            call_context.zome_name.clone(),
+            format!("link type not found for {:?}", link_type_id),
+        )),
+    }
+}
+
+/// Read the links of the calling zome in a range of the ordered index,
+/// leaving out any which have been deleted
+pub fn live_links_in_range(
+    call_context: Arc<CallContext>,
+    base_address: EntryHash,
+    zome_id: ZomeId,
+    link_type: LinkTypeIndex,
+    tag_order: LinkTagOrder,
+    start: Option<LinkTag>,
+    end: Option<LinkTag>,
+) -> RibosomeResult<Vec<Link>> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let env = call_context
+            .host_access
+            .workspace()
+            .read()
+            .await
+            .source_chain
+            .env()
+            .clone();
+        let link_tags = LinkTagIndexBuf::vault(env.clone())?;
+        let meta = MetadataBuf::vault(env.clone())?;
+        let links = fresh_reader!(env, |r| {
+            let mut links = Vec::new();
+            for link in link_tags.get_links_in_range(
+                &r,
+                &base_address,
+                zome_id,
+                link_type,
+                tag_order,
+                start.as_ref(),
+                end.as_ref(),
+            )? {
+                // Deleted links are still in the index
+                if meta
+                    .get_link_removes_on_link_add(&r, link.link_add_hash.clone())?
+                    .next()?
+                    .is_none()
+                {
+                    links.push(link.into_link());
+                }
+            }
+            DatabaseResult::Ok(links)
+        })?;
+        Ok(links)
+    })
+}
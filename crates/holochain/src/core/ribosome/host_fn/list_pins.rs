@@ -0,0 +1,20 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::{CallContext, RibosomeT};
+use holochain_state::fresh_reader;
+use holochain_zome_types::ListPinsInput;
+use holochain_zome_types::ListPinsOutput;
+use std::sync::Arc;
+
+/// Every hash pinned on this cell, whether by a zome or an admin
+pub fn list_pins(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    _input: ListPinsInput,
+) -> RibosomeResult<ListPinsOutput> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let workspace = call_context.host_access.workspace().read().await;
+        let env = workspace.source_chain.env().clone();
+        let pins = fresh_reader!(env, |r| workspace.pins.pins(&r))?;
+        Ok(ListPinsOutput::new(pins))
+    })
+}
@@ -0,0 +1,35 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::{CallContext, RibosomeT};
+use holochain_p2p::{actor::GetActivityOptions, HolochainP2pCellT};
+use holochain_types::activity::verify_observation;
+use holochain_zome_types::ObserveChainHeadInput;
+use holochain_zome_types::ObserveChainHeadOutput;
+use std::sync::Arc;
+
+/// Ask an agent's activity authorities what they see at the head of its
+/// chain. Observations of another agent, or with bad signatures, are dropped.
+pub fn observe_chain_head(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: ObserveChainHeadInput,
+) -> RibosomeResult<ObserveChainHeadOutput> {
+    let agent = input.into_inner();
+    let mut network = call_context.host_access.network().clone();
+    let cancel = call_context.host_access.cancel();
+
+    // timeouts must be handled by the network
+    tokio_safe_block_on::tokio_safe_block_forever_on(cancel.or_cancel(async move {
+        let observations = network
+            .observe_chain_head(agent.clone(), GetActivityOptions::default())
+            .await?;
+        let mut verified = Vec::with_capacity(observations.len());
+        for observation in observations {
+            if observation.observation.agent == agent
+                && verify_observation(&observation).await.unwrap_or(false)
+            {
+                verified.push(observation);
+            }
+        }
+        Ok(ObserveChainHeadOutput::new(verified))
+    }))?
+}
@@ -0,0 +1,25 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::{CallContext, RibosomeT};
+use holochain_zome_types::PinInput;
+use holochain_zome_types::PinOutput;
+use std::sync::Arc;
+
+/// Pin a hash so its data is always held by this cell,
+/// fetching it first if it isn't held yet.
+pub fn pin(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: PinInput,
+) -> RibosomeResult<PinOutput> {
+    let hash = input.into_inner();
+    let network = call_context.host_access.network().clone();
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let mut workspace = call_context.host_access.workspace().write().await;
+        workspace
+            .cascade(network)
+            .dht_get(hash.clone(), Default::default())
+            .await?;
+        workspace.pins.pin(hash)?;
+        Ok(PinOutput::new(()))
+    })
+}
@@ -0,0 +1,35 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use crate::core::state::app_index::AppIndexBuf;
+use holochain_state::fresh_reader;
+use holochain_zome_types::index::IndexHits;
+use holochain_zome_types::QueryIndexInput;
+use holochain_zome_types::QueryIndexOutput;
+use std::sync::Arc;
+
+/// Search one of the calling zome's indexes held by this cell.
+/// Only entries this cell holds as an authority have been indexed.
+pub fn query_index(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: QueryIndexInput,
+) -> RibosomeResult<QueryIndexOutput> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let env = call_context
+            .host_access
+            .workspace()
+            .read()
+            .await
+            .source_chain
+            .env()
+            .clone();
+        let app_index = AppIndexBuf::new(env.clone())?;
+        let hits = fresh_reader!(env, |r| app_index.query(
+            &r,
+            &call_context.zome_name,
+            input.inner_ref()
+        ))?;
+        Ok(QueryIndexOutput::new(IndexHits(hits)))
+    })
+}
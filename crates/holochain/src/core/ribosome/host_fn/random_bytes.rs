@@ -10,12 +10,20 @@ use holochain_zome_types::RandomBytesInput;
 use holochain_zome_types::RandomBytesOutput;
 use std::sync::Arc;
 
-/// return n crypto secure random bytes from the standard holochain crypto lib
+/// return n crypto secure random bytes from the standard holochain crypto lib,
+/// or from the call's generator if a test has seeded one
 pub fn random_bytes(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
+    call_context: Arc<CallContext>,
     input: RandomBytesInput,
 ) -> RibosomeResult<RandomBytesOutput> {
+    let entropy = call_context.host_access().entropy();
+    if entropy.is_seeded() {
+        let mut random_bytes = vec![0; input.into_inner() as usize];
+        entropy.fill_bytes(&mut random_bytes);
+        return Ok(RandomBytesOutput::new(Bytes::from(random_bytes)));
+    }
+
     let _ = crypto_init_sodium();
     let mut buf: DynCryptoBytes = crypto_secure_buffer(input.into_inner() as _)?;
 
@@ -33,9 +41,12 @@ pub fn random_bytes(
 pub mod wasm_test {
     use crate::core::ribosome::host_fn::random_bytes::random_bytes;
 
+    use crate::core::clock::Entropy;
+    use crate::core::ribosome::CallContext;
     use crate::fixt::CallContextFixturator;
     use crate::fixt::WasmRibosomeFixturator;
     use crate::fixt::ZomeCallHostAccessFixturator;
+    use crate::fixt::ZomeNameFixturator;
     use ::fixt::prelude::*;
     use holochain_wasm_test_utils::TestWasm;
     use holochain_zome_types::RandomBytesInput;
@@ -63,6 +74,27 @@ pub mod wasm_test {
         assert_ne!(&[0; LEN], output.into_inner().as_ref(),);
     }
 
+    #[tokio::test(threaded_scheduler)]
+    /// a seeded generator gives the same bytes every time
+    async fn seeded_random_bytes_test() {
+        let bytes = || {
+            let ribosome = WasmRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+                .next()
+                .unwrap();
+            let mut host_access = fixt!(ZomeCallHostAccess);
+            host_access.entropy = Entropy::seeded(7);
+            let call_context = CallContext::new(fixt!(ZomeName), host_access.into());
+            random_bytes(
+                Arc::new(ribosome),
+                Arc::new(call_context),
+                RandomBytesInput::new(10),
+            )
+            .unwrap()
+            .into_inner()
+        };
+        assert_eq!(bytes(), bytes());
+    }
+
     #[tokio::test(threaded_scheduler)]
     /// we can get some random data out of the fn via. a wasm call
     async fn ribosome_random_bytes_test() {
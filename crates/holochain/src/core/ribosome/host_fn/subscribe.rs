@@ -0,0 +1,21 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_p2p::HolochainP2pCellT;
+use holochain_zome_types::SubscribeInput;
+use holochain_zome_types::SubscribeOutput;
+use std::sync::Arc;
+
+/// Ask the authorities for a basis to tell this agent whenever ops are integrated there.
+/// Changes arrive at the agent's cell and are emitted as `BasisChanged` signals.
+pub fn subscribe(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: SubscribeInput,
+) -> RibosomeResult<SubscribeOutput> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let mut network = call_context.host_access().network().clone();
+        network.subscribe(input.into_inner()).await?;
+        Ok(SubscribeOutput::new(()))
+    })
+}
@@ -1,19 +1,21 @@
 use crate::core::ribosome::error::RibosomeResult;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::RibosomeT;
+use holochain_types::Timestamp;
 use holochain_zome_types::SysTimeInput;
 use holochain_zome_types::SysTimeOutput;
+use std::convert::TryInto;
 use std::sync::Arc;
 
+/// The time since the UNIX epoch, from the call's clock
 pub fn sys_time(
     _ribosome: Arc<impl RibosomeT>,
-    _call_context: Arc<CallContext>,
+    call_context: Arc<CallContext>,
     _input: SysTimeInput,
 ) -> RibosomeResult<SysTimeOutput> {
-    let start = std::time::SystemTime::now();
-    let since_the_epoch = start
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("Time went backwards");
+    let Timestamp(secs, nanos) = call_context.host_access().clock().now();
+    let since_the_epoch =
+        std::time::Duration::new(secs.try_into().expect("Time went backwards"), nanos);
     Ok(SysTimeOutput::new(since_the_epoch))
 }
 
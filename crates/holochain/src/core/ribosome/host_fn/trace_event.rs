@@ -0,0 +1,74 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_zome_types::trace::TraceEventKind;
+use holochain_zome_types::TraceEventInput;
+use holochain_zome_types::TraceEventOutput;
+use std::sync::Arc;
+use tracing::*;
+
+/// Record a zome's event or counter on the span of the call which emitted it.
+/// Counters are recorded with their increment for the collector to total.
+pub fn trace_event(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: TraceEventInput,
+) -> RibosomeResult<TraceEventOutput> {
+    let event = input.into_inner();
+    let zome = call_context.zome_name();
+    let fields = event
+        .fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match event.kind {
+        TraceEventKind::Event => {
+            info!(target: "zome_trace", %zome, event = %event.name, %fields)
+        }
+        TraceEventKind::Counter(increment) => {
+            info!(target: "zome_trace", %zome, counter = %event.name, increment, %fields)
+        }
+    }
+    Ok(TraceEventOutput::new(()))
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::trace_event;
+    use crate::core::ribosome::CallContext;
+    use crate::fixt::WasmRibosomeFixturator;
+    use crate::fixt::ZomeCallHostAccessFixturator;
+    use crate::fixt::ZomeNameFixturator;
+    use ::fixt::prelude::*;
+    use holochain_zome_types::trace::TraceEvent;
+    use holochain_zome_types::TraceEventInput;
+    use holochain_zome_types::TraceEventOutput;
+    use std::sync::Arc;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn trace_events_and_counters() {
+        let ribosome = Arc::new(
+            WasmRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+                .next()
+                .unwrap(),
+        );
+        let call_context = Arc::new(CallContext::new(
+            fixt!(ZomeName),
+            fixt!(ZomeCallHostAccess).into(),
+        ));
+
+        for event in vec![
+            TraceEvent::event("cache_miss").with_field("key", "foo"),
+            TraceEvent::counter("entries_scanned", 12).with_field("index", "by_date"),
+        ] {
+            let output = trace_event(
+                ribosome.clone(),
+                call_context.clone(),
+                TraceEventInput::new(event),
+            )
+            .unwrap();
+            assert_eq!(output, TraceEventOutput::new(()));
+        }
+    }
+}
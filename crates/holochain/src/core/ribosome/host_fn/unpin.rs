@@ -0,0 +1,23 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::{CallContext, RibosomeT};
+use holochain_zome_types::UnpinInput;
+use holochain_zome_types::UnpinOutput;
+use std::sync::Arc;
+
+/// Unpin a hash. Its data stays in the cache, but may be dropped.
+pub fn unpin(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: UnpinInput,
+) -> RibosomeResult<UnpinOutput> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        call_context
+            .host_access
+            .workspace()
+            .write()
+            .await
+            .pins
+            .unpin(input.inner_ref())?;
+        Ok(UnpinOutput::new(()))
+    })
+}
@@ -0,0 +1,20 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_p2p::HolochainP2pCellT;
+use holochain_zome_types::UnsubscribeInput;
+use holochain_zome_types::UnsubscribeOutput;
+use std::sync::Arc;
+
+/// Ask the authorities for a basis to stop telling this agent about changes there
+pub fn unsubscribe(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: UnsubscribeInput,
+) -> RibosomeResult<UnsubscribeOutput> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let mut network = call_context.host_access().network().clone();
+        network.unsubscribe(input.into_inner()).await?;
+        Ok(UnsubscribeOutput::new(()))
+    })
+}
@@ -1,16 +1,18 @@
 use super::{create::extract_entry_def, delete::get_original_address};
-use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::error::{RibosomeError, RibosomeResult};
 use crate::core::ribosome::CallContext;
 use crate::core::{
     ribosome::RibosomeT,
     workflow::{integrate_dht_ops_workflow::integrate_to_cache, CallZomeWorkspace},
     SourceChainError,
 };
-use holo_hash::HasHash;
+use holo_hash::{EntryHash, HasHash, HeaderHash};
+use holochain_p2p::actor::GetOptions;
 use holochain_zome_types::entry_def::EntryDefId;
+use holochain_zome_types::metadata::{Details, EntryDetails};
 use holochain_zome_types::UpdateInput;
 use holochain_zome_types::{
-    header::{builder, AppEntryType, EntryType},
+    header::{builder, AppEntryType, EntryType, UpdateTarget},
     UpdateOutput,
 };
 use std::sync::Arc;
@@ -22,7 +24,7 @@ pub fn update<'a>(
     input: UpdateInput,
 ) -> RibosomeResult<UpdateOutput> {
     // destructure the args out into an app type def id and entry
-    let (entry_def_id, entry, original_header_address) = input.into_inner();
+    let (entry_def_id, entry, target) = input.into_inner();
 
     // build the entry hash
     let async_entry = entry.clone();
@@ -45,27 +47,41 @@ pub fn update<'a>(
         EntryDefId::CapClaim => EntryType::CapClaim,
     };
 
-    let original_entry_address =
-        get_original_address(call_context.clone(), original_header_address.clone())?;
+    let (original_header_address, original_entry_address) = match target {
+        UpdateTarget::Header(original_header_address) => {
+            let original_entry_address =
+                get_original_address(call_context.clone(), original_header_address.clone())?;
+            (original_header_address, original_entry_address)
+        }
+        UpdateTarget::Entry(original_entry_address) => (
+            get_latest_live_header(call_context.clone(), original_entry_address.clone())?,
+            original_entry_address,
+        ),
+    };
 
     // build a header for the entry being updated
-    let header_builder = builder::Update {
+    let header_builder = builder::Update::for_entry(
+        original_entry_address,
+        original_header_address,
         entry_type,
         entry_hash,
-        original_header_address,
-        original_entry_address,
-    };
+        &entry,
+    )
+    .map_err(SourceChainError::from)?;
 
-    let workspace_lock = call_context.host_access.workspace();
+    let host_access = call_context.host_access();
 
     // return the hash of the updated entry
     // note that validation is handled by the workflow
     // if the validation fails this update will be rolled back by virtue of the lmdb transaction
     // being atomic
     tokio_safe_block_on::tokio_safe_block_forever_on(async move {
-        let mut guard = workspace_lock.write().await;
+        let mut guard = host_access.workspace().write().await;
         let workspace: &mut CallZomeWorkspace = &mut guard;
         let source_chain = &mut workspace.source_chain;
+        if let Some(quota) = host_access.quota() {
+            quota.check_write(source_chain)?;
+        }
         // push the header and the entry into the source chain
         let header_hash = source_chain.put(header_builder, Some(entry)).await?;
         // fetch the element we just added so we can integrate its DhtOps
@@ -84,4 +100,137 @@ pub fn update<'a>(
     })
 }
 
+#[allow(clippy::extra_unused_lifetimes)]
+pub(crate) fn get_latest_live_header<'a>(
+    call_context: Arc<CallContext>,
+    entry_hash: EntryHash,
+) -> RibosomeResult<HeaderHash> {
+    let network = call_context.host_access.network().clone();
+    let workspace_lock = call_context.host_access.workspace();
+
+    let details = tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        let mut workspace = workspace_lock.write().await;
+        let mut cascade = workspace.cascade(network);
+        cascade
+            .get_details(entry_hash.clone().into(), GetOptions::default())
+            .await
+    })?;
+    match details {
+        Some(Details::Entry(details)) => latest_live_header(entry_hash, &details),
+        _ => Err(RibosomeError::ElementDeps(entry_hash.into())),
+    }
+}
+
+/// The header an update of an entry replaces: its live header which hasn't
+/// been updated yet. If all of them have been, the newest is updated again,
+/// as if the update had targeted it. Several live headers which haven't
+/// been updated is a conflict.
+fn latest_live_header(entry_hash: EntryHash, details: &EntryDetails) -> RibosomeResult<HeaderHash> {
+    let live: Vec<_> = details
+        .headers
+        .iter()
+        .map(|header| (HeaderHash::with_data_sync(header), header.timestamp()))
+        .filter(|(hash, _)| {
+            !details
+                .deletes
+                .iter()
+                .any(|delete| &delete.deletes_address == hash)
+        })
+        .collect();
+    let heads: Vec<_> = live
+        .iter()
+        .map(|(hash, _)| hash)
+        .filter(|hash| {
+            !details
+                .updates
+                .iter()
+                .any(|update| &update.original_header_address == *hash)
+        })
+        .cloned()
+        .collect();
+    match heads.len() {
+        0 => live
+            .into_iter()
+            .max_by_key(|(_, timestamp)| *timestamp)
+            .map(|(hash, _)| hash)
+            .ok_or_else(|| RibosomeError::ElementDeps(entry_hash.into())),
+        1 => Ok(heads.into_iter().next().unwrap()),
+        _ => Err(RibosomeError::UpdateConflict(entry_hash, heads)),
+    }
+}
+
 // relying on tests for get_details
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{AgentPubKeyFixturator, EntryHashFixturator};
+    use holochain_types::fixt::{CreateFixturator, DeleteFixturator, UpdateFixturator};
+    use holochain_zome_types::{header::Header, metadata::EntryDhtStatus, Entry};
+
+    #[test]
+    fn latest_live_header_resolution() {
+        let entry_hash = fixt!(EntryHash);
+        let mut details = EntryDetails {
+            entry: Entry::Agent(fixt!(AgentPubKey)),
+            headers: Vec::new(),
+            deletes: Vec::new(),
+            updates: Vec::new(),
+            entry_dht_status: EntryDhtStatus::Dead,
+            references: Vec::new(),
+        };
+        let header = |seconds| {
+            let mut create = fixt!(Create);
+            create.entry_hash = entry_hash.clone();
+            create.timestamp = holochain_zome_types::timestamp::Timestamp(seconds, 0);
+            Header::Create(create)
+        };
+        let (older, newer) = (header(1), header(2));
+        let (older_hash, newer_hash) = (
+            HeaderHash::with_data_sync(&older),
+            HeaderHash::with_data_sync(&newer),
+        );
+
+        // Nothing to update
+        assert!(matches!(
+            latest_live_header(entry_hash.clone(), &details),
+            Err(RibosomeError::ElementDeps(_))
+        ));
+
+        // Two live headers which haven't been updated conflict
+        details.headers = vec![older.clone(), newer.clone()];
+        assert!(matches!(
+            latest_live_header(entry_hash.clone(), &details),
+            Err(RibosomeError::UpdateConflict(_, heads)) if heads.len() == 2
+        ));
+
+        // Unless one of them is deleted
+        let mut delete = fixt!(Delete);
+        delete.deletes_address = newer_hash.clone();
+        details.deletes = vec![delete];
+        assert_eq!(
+            latest_live_header(entry_hash.clone(), &details).unwrap(),
+            older_hash
+        );
+
+        // Or updated
+        details.deletes.clear();
+        let mut update = fixt!(Update);
+        update.original_header_address = older_hash;
+        details.updates = vec![update.clone()];
+        assert_eq!(
+            latest_live_header(entry_hash.clone(), &details).unwrap(),
+            newer_hash
+        );
+
+        // When every live header has been updated the newest is updated again
+        let mut update_newer = update;
+        update_newer.original_header_address = newer_hash.clone();
+        details.updates.push(update_newer);
+        assert_eq!(
+            latest_live_header(entry_hash, &details).unwrap(),
+            newer_hash
+        );
+    }
+}
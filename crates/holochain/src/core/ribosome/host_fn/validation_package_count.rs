@@ -0,0 +1,109 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_zome_types::ValidationPackageCountInput;
+use holochain_zome_types::ValidationPackageCountOutput;
+use std::sync::Arc;
+
+/// Count the elements of the validation package which match a filter, so
+/// rate limits don't need the package deserialized and walked in wasm
+pub fn validation_package_count(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: ValidationPackageCountInput,
+) -> RibosomeResult<ValidationPackageCountOutput> {
+    let package = call_context.host_access.validation_package()?;
+    let count = package.count(input.inner_ref());
+    Ok(ValidationPackageCountOutput::new(count as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ribosome::error::RibosomeError;
+    use crate::core::ribosome::guest_callback::validate::ValidateHostAccess;
+    use crate::core::ribosome::HostAccess;
+    use crate::fixt::{WasmRibosomeFixturator, ZomeCallHostAccessFixturator};
+    use ::fixt::prelude::*;
+    use holochain_types::{fixt::*, HeaderHashed};
+    use holochain_zome_types::{
+        element::{Element, SignedHeaderHashed},
+        header::{Header, HeaderType},
+        query::ChainQueryFilter,
+        timestamp::Timestamp,
+        validate::ValidationPackage,
+    };
+
+    fn element(header: Header) -> Element {
+        Element::new(
+            SignedHeaderHashed::with_presigned(
+                HeaderHashed::from_content_sync(header),
+                fixt!(Signature),
+            ),
+            None,
+        )
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn counts_matching_elements_when_validating() {
+        let ribosome = Arc::new(
+            WasmRibosomeFixturator::new(crate::fixt::curve::Zomes(vec![]))
+                .next()
+                .unwrap(),
+        );
+        let mut elements = Vec::new();
+        for t in 0..3 {
+            let mut create = fixt!(Create);
+            create.timestamp = Timestamp(t, 0);
+            elements.push(element(create.into()));
+        }
+        let mut link = fixt!(CreateLink);
+        link.timestamp = Timestamp(1, 0);
+        elements.push(element(link.into()));
+        let host_access = ValidateHostAccess {
+            validation_package: Some(Arc::new(ValidationPackage::new(elements))),
+        };
+        let call_context = Arc::new(CallContext::new(
+            "foo".into(),
+            HostAccess::Validate(host_access),
+        ));
+
+        let filter = ChainQueryFilter::new()
+            .header_type(HeaderType::Create)
+            .timestamp_range(Timestamp(1, 0)..Timestamp(3, 0));
+        let output = validation_package_count(
+            ribosome.clone(),
+            call_context,
+            ValidationPackageCountInput::new(filter.clone()),
+        )
+        .unwrap();
+        assert_eq!(output.into_inner(), 2);
+
+        // An entry def without a package has nothing to count
+        let call_context = Arc::new(CallContext::new(
+            "foo".into(),
+            HostAccess::Validate(ValidateHostAccess::new()),
+        ));
+        let output = validation_package_count(
+            ribosome.clone(),
+            call_context,
+            ValidationPackageCountInput::new(filter.clone()),
+        )
+        .unwrap();
+        assert_eq!(output.into_inner(), 0);
+
+        // Outside of validation there is no package at all
+        let call_context = Arc::new(CallContext::new(
+            "foo".into(),
+            HostAccess::ZomeCall(fixt!(ZomeCallHostAccess)),
+        ));
+        assert!(matches!(
+            validation_package_count(
+                ribosome,
+                call_context,
+                ValidationPackageCountInput::new(filter)
+            ),
+            Err(RibosomeError::NotValidating)
+        ));
+    }
+}
@@ -0,0 +1,19 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_zome_types::element::ElementVec;
+use holochain_zome_types::ValidationPackageQueryInput;
+use holochain_zome_types::ValidationPackageQueryOutput;
+use std::sync::Arc;
+
+/// The elements of the validation package which match a filter,
+/// e.g. those authored in a time range
+pub fn validation_package_query(
+    _ribosome: Arc<impl RibosomeT>,
+    call_context: Arc<CallContext>,
+    input: ValidationPackageQueryInput,
+) -> RibosomeResult<ValidationPackageQueryOutput> {
+    let package = call_context.host_access.validation_package()?;
+    let elements = package.query(input.inner_ref());
+    Ok(ValidationPackageQueryOutput::new(ElementVec(elements)))
+}
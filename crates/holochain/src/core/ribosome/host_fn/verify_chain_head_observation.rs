@@ -0,0 +1,22 @@
+use crate::core::ribosome::error::RibosomeResult;
+use crate::core::ribosome::CallContext;
+use crate::core::ribosome::RibosomeT;
+use holochain_state::error::DatabaseError;
+use holochain_types::activity::verify_observation;
+use holochain_zome_types::VerifyChainHeadObservationInput;
+use holochain_zome_types::VerifyChainHeadObservationOutput;
+use std::sync::Arc;
+
+/// Check an observation held as evidence, e.g. while validating a dispute
+pub fn verify_chain_head_observation(
+    _ribosome: Arc<impl RibosomeT>,
+    _call_context: Arc<CallContext>,
+    input: VerifyChainHeadObservationInput,
+) -> RibosomeResult<VerifyChainHeadObservationOutput> {
+    let observation = input.into_inner();
+    let valid = tokio_safe_block_on::tokio_safe_block_forever_on(async move {
+        verify_observation(&observation).await
+    })
+    .map_err(DatabaseError::from)?;
+    Ok(VerifyChainHeadObservationOutput::new(valid))
+}
@@ -0,0 +1,129 @@
+//! Limits on the size of data crossing the wasm boundary, so that a buggy
+//! zome can't exhaust the conductor's memory.
+//!
+//! "Input" is anything the host hands to the guest: the [ExternInput] of an
+//! extern call, and the results of host functions. "Output" is anything the
+//! guest hands back: the [ExternOutput] of an extern call. Inputs are
+//! checked before the guest allocates memory for them.
+//!
+//! [ExternInput]: holochain_zome_types::ExternInput
+//! [ExternOutput]: holochain_zome_types::ExternOutput
+
+use holochain_error_kind::{ErrorKind, HasErrorKind};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// 16MiB, which is plenty for any sane entry
+const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// The most bytes which may cross the wasm boundary in each direction
+/// on a single call
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WasmIoLimits {
+    /// Most bytes the host may hand to the guest at once
+    #[serde(default = "default_max_bytes")]
+    pub max_input_bytes: usize,
+    /// Most bytes the guest may hand back to the host at once
+    #[serde(default = "default_max_bytes")]
+    pub max_output_bytes: usize,
+}
+
+fn default_max_bytes() -> usize {
+    DEFAULT_MAX_BYTES
+}
+
+impl Default for WasmIoLimits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: DEFAULT_MAX_BYTES,
+            max_output_bytes: DEFAULT_MAX_BYTES,
+        }
+    }
+}
+
+/// Data crossing the wasm boundary was over the limit
+#[derive(Error, Debug, PartialEq)]
+pub enum WasmIoError {
+    /// The host tried to hand the guest too much data
+    #[error("Input of {size} bytes to the wasm guest is over the limit of {limit} bytes")]
+    InputTooLarge {
+        /// Bytes the host tried to hand over
+        size: usize,
+        /// The configured limit
+        limit: usize,
+    },
+    /// The guest handed back too much data
+    #[error("Output of {size} bytes from the wasm guest is over the limit of {limit} bytes")]
+    OutputTooLarge {
+        /// Bytes the guest handed back
+        size: usize,
+        /// The configured limit
+        limit: usize,
+    },
+}
+
+impl HasErrorKind for WasmIoError {
+    fn error_kind(&self) -> ErrorKind {
+        ErrorKind::InvalidInput
+    }
+}
+
+impl WasmIoLimits {
+    /// Check `size` bytes may be handed to the guest
+    pub fn check_input(&self, size: usize) -> Result<(), WasmIoError> {
+        if size > self.max_input_bytes {
+            return Err(WasmIoError::InputTooLarge {
+                size,
+                limit: self.max_input_bytes,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check `size` bytes may be handed back by the guest
+    pub fn check_output(&self, size: usize) -> Result<(), WasmIoError> {
+        if size > self.max_output_bytes {
+            return Err(WasmIoError::OutputTooLarge {
+                size,
+                limit: self.max_output_bytes,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_are_inclusive() {
+        let limits = WasmIoLimits {
+            max_input_bytes: 10,
+            max_output_bytes: 20,
+        };
+        assert_eq!(limits.check_input(10), Ok(()));
+        assert_eq!(
+            limits.check_input(11),
+            Err(WasmIoError::InputTooLarge {
+                size: 11,
+                limit: 10
+            })
+        );
+        assert_eq!(limits.check_output(20), Ok(()));
+        assert_eq!(
+            limits.check_output(21),
+            Err(WasmIoError::OutputTooLarge {
+                size: 21,
+                limit: 20
+            })
+        );
+    }
+
+    #[test]
+    fn unset_limits_use_the_default() {
+        let limits: WasmIoLimits = toml::from_str("max_input_bytes = 1024").unwrap();
+        assert_eq!(limits.max_input_bytes, 1024);
+        assert_eq!(limits.max_output_bytes, DEFAULT_MAX_BYTES);
+    }
+}
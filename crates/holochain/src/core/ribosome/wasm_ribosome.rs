@@ -10,8 +10,14 @@ use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::error::RibosomeResult;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsResult;
+use crate::core::ribosome::guest_callback::index_op::IndexOpHostAccess;
+use crate::core::ribosome::guest_callback::index_op::IndexOpInvocation;
+use crate::core::ribosome::guest_callback::index_op::IndexOpResult;
 use crate::core::ribosome::guest_callback::init::InitInvocation;
 use crate::core::ribosome::guest_callback::init::InitResult;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesHostAccess;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesInvocation;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesResult;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentInvocation;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentResult;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitInvocation;
@@ -26,39 +32,58 @@ use crate::core::ribosome::guest_callback::validation_package::ValidationPackage
 use crate::core::ribosome::guest_callback::CallIterator;
 use crate::core::ribosome::host_fn::agent_info::agent_info;
 use crate::core::ribosome::host_fn::call::call;
+use crate::core::ribosome::host_fn::call_provenance::call_provenance;
 use crate::core::ribosome::host_fn::call_remote::call_remote;
 use crate::core::ribosome::host_fn::capability_claims::capability_claims;
 use crate::core::ribosome::host_fn::capability_grants::capability_grants;
 use crate::core::ribosome::host_fn::capability_info::capability_info;
 use crate::core::ribosome::host_fn::create::create;
 use crate::core::ribosome::host_fn::create_link::create_link;
+use crate::core::ribosome::host_fn::create_links::create_links;
 use crate::core::ribosome::host_fn::debug::debug;
 use crate::core::ribosome::host_fn::decrypt::decrypt;
 use crate::core::ribosome::host_fn::delete::delete;
 use crate::core::ribosome::host_fn::delete_link::delete_link;
+use crate::core::ribosome::host_fn::delete_links::delete_links;
 use crate::core::ribosome::host_fn::emit_signal::emit_signal;
 use crate::core::ribosome::host_fn::encrypt::encrypt;
+use crate::core::ribosome::host_fn::entry_type_properties::entry_type_properties;
 use crate::core::ribosome::host_fn::get::get;
 use crate::core::ribosome::host_fn::get_details::get_details;
+use crate::core::ribosome::host_fn::get_link_aggregate::get_link_aggregate;
 use crate::core::ribosome::host_fn::get_link_details::get_link_details;
 use crate::core::ribosome::host_fn::get_links::get_links;
+use crate::core::ribosome::host_fn::get_links_in_range::get_links_in_range;
 use crate::core::ribosome::host_fn::hash_entry::hash_entry;
 use crate::core::ribosome::host_fn::keystore::keystore;
+use crate::core::ribosome::host_fn::list_pins::list_pins;
+use crate::core::ribosome::host_fn::observe_chain_head::observe_chain_head;
+use crate::core::ribosome::host_fn::pin::pin;
 use crate::core::ribosome::host_fn::property::property;
 use crate::core::ribosome::host_fn::query::query;
+use crate::core::ribosome::host_fn::query_index::query_index;
 use crate::core::ribosome::host_fn::random_bytes::random_bytes;
 use crate::core::ribosome::host_fn::schedule::schedule;
 use crate::core::ribosome::host_fn::show_env::show_env;
 use crate::core::ribosome::host_fn::sign::sign;
+use crate::core::ribosome::host_fn::subscribe::subscribe;
 use crate::core::ribosome::host_fn::sys_time::sys_time;
+use crate::core::ribosome::host_fn::trace_event::trace_event;
+use crate::core::ribosome::host_fn::unpin::unpin;
 use crate::core::ribosome::host_fn::unreachable::unreachable;
+use crate::core::ribosome::host_fn::unsubscribe::unsubscribe;
 use crate::core::ribosome::host_fn::update::update;
+use crate::core::ribosome::host_fn::validation_package_count::validation_package_count;
+use crate::core::ribosome::host_fn::validation_package_query::validation_package_query;
+use crate::core::ribosome::host_fn::verify_chain_head_observation::verify_chain_head_observation;
 use crate::core::ribosome::host_fn::zome_info::zome_info;
+use crate::core::ribosome::wasm_io::WasmIoLimits;
 use crate::core::ribosome::CallContext;
 use crate::core::ribosome::Invocation;
 use crate::core::ribosome::RibosomeT;
 use crate::core::ribosome::ZomeCallInvocation;
 use crate::core::ribosome::ZomesToInvoke;
+use crate::core::workflow::call_zome_workflow::call_zome_workspace_lock::guest_call;
 use fallible_iterator::FallibleIterator;
 use holochain_types::dna::DnaError;
 use holochain_types::dna::{
@@ -67,7 +92,9 @@ use holochain_types::dna::{
 };
 use holochain_wasmer_host::prelude::*;
 use holochain_zome_types::entry_def::EntryDefsCallbackResult;
+use holochain_zome_types::index::IndexOpCallbackResult;
 use holochain_zome_types::init::InitCallbackResult;
+use holochain_zome_types::link::LinkTypesCallbackResult;
 use holochain_zome_types::migrate_agent::MigrateAgentCallbackResult;
 use holochain_zome_types::post_commit::PostCommitCallbackResult;
 use holochain_zome_types::validate::ValidateCallbackResult;
@@ -92,12 +119,23 @@ pub struct WasmRibosome {
     //      - is already in the wasm cache, and only include the DnaDef portion
     //      - here in the ribosome.
     pub dna_file: DnaFile,
+    /// How much data may cross the wasm boundary on each call
+    pub io_limits: WasmIoLimits,
 }
 
 impl WasmRibosome {
     /// Create a new instance
     pub fn new(dna_file: DnaFile) -> Self {
-        Self { dna_file }
+        Self {
+            dna_file,
+            io_limits: WasmIoLimits::default(),
+        }
+    }
+
+    /// Limit how much data may cross the wasm boundary on each call
+    pub fn with_io_limits(mut self, io_limits: WasmIoLimits) -> Self {
+        self.io_limits = io_limits;
+        self
     }
 
     pub fn module(&self, call_context: CallContext) -> RibosomeResult<Module> {
@@ -146,6 +184,12 @@ impl WasmRibosome {
                 let closure_self_arc = std::sync::Arc::clone(&self_arc);
                 let closure_call_context_arc = std::sync::Arc::clone(&call_context_arc);
                 move |ctx: &mut Ctx, guest_allocation_ptr: GuestPtr| -> Result<Len, WasmError> {
+                    // every host function call is a checkpoint for cancellation
+                    closure_call_context_arc
+                        .host_access
+                        .cancel()
+                        .check()
+                        .map_err(|e| WasmError::Zome(e.to_string()))?;
                     let input = $crate::holochain_wasmer_host::guest::from_guest_ptr(
                         ctx,
                         guest_allocation_ptr,
@@ -160,6 +204,11 @@ impl WasmRibosome {
                         )
                         .map_err(|e| WasmError::Zome(format!("{:?}", e)))?
                         .try_into()?;
+                    // the guest allocates for the output so check it first
+                    closure_self_arc
+                        .io_limits
+                        .check_input(output_sb.bytes().len())
+                        .map_err(|e| WasmError::Zome(e.to_string()))?;
 
                     Ok($crate::holochain_wasmer_host::import::set_context_data(
                         ctx, output_sb,
@@ -179,7 +228,21 @@ impl WasmRibosome {
         // imported host functions for core
         ns.insert("__debug", func!(invoke_host_function!(debug)));
         ns.insert("__hash_entry", func!(invoke_host_function!(hash_entry)));
+        ns.insert("__trace_event", func!(invoke_host_function!(trace_event)));
         ns.insert("__unreachable", func!(invoke_host_function!(unreachable)));
+        // these error outside of validate callbacks, which have no package
+        ns.insert(
+            "__validation_package_count",
+            func!(invoke_host_function!(validation_package_count)),
+        );
+        ns.insert(
+            "__validation_package_query",
+            func!(invoke_host_function!(validation_package_query)),
+        );
+        ns.insert(
+            "__verify_chain_head_observation",
+            func!(invoke_host_function!(verify_chain_head_observation)),
+        );
 
         if let HostFnAccess {
             keystore: Permission::Allow,
@@ -204,9 +267,17 @@ impl WasmRibosome {
         {
             ns.insert("__zome_info", func!(invoke_host_function!(zome_info)));
             ns.insert("__property", func!(invoke_host_function!(property)));
+            ns.insert(
+                "__entry_type_properties",
+                func!(invoke_host_function!(entry_type_properties)),
+            );
         } else {
             ns.insert("__zome_info", func!(invoke_host_function!(unreachable)));
             ns.insert("__property", func!(invoke_host_function!(unreachable)));
+            ns.insert(
+                "__entry_type_properties",
+                func!(invoke_host_function!(unreachable)),
+            );
         }
 
         if let HostFnAccess {
@@ -229,6 +300,10 @@ impl WasmRibosome {
         } = host_fn_access
         {
             ns.insert("__agent_info", func!(invoke_host_function!(agent_info)));
+            ns.insert(
+                "__call_provenance",
+                func!(invoke_host_function!(call_provenance)),
+            );
             ns.insert(
                 "__capability_claims",
                 func!(invoke_host_function!(capability_claims)),
@@ -243,6 +318,10 @@ impl WasmRibosome {
             );
         } else {
             ns.insert("__agent_info", func!(invoke_host_function!(unreachable)));
+            ns.insert(
+                "__call_provenance",
+                func!(invoke_host_function!(unreachable)),
+            );
             ns.insert(
                 "__capability_claims",
                 func!(invoke_host_function!(unreachable)),
@@ -265,20 +344,48 @@ impl WasmRibosome {
             ns.insert("__get", func!(invoke_host_function!(get)));
             ns.insert("__get_details", func!(invoke_host_function!(get_details)));
             ns.insert("__get_links", func!(invoke_host_function!(get_links)));
+            ns.insert(
+                "__get_links_in_range",
+                func!(invoke_host_function!(get_links_in_range)),
+            );
             ns.insert(
                 "__get_link_details",
                 func!(invoke_host_function!(get_link_details)),
             );
+            ns.insert(
+                "__get_link_aggregate",
+                func!(invoke_host_function!(get_link_aggregate)),
+            );
             ns.insert("__query", func!(invoke_host_function!(query)));
+            ns.insert("__query_index", func!(invoke_host_function!(query_index)));
+            ns.insert("__list_pins", func!(invoke_host_function!(list_pins)));
+            ns.insert(
+                "__observe_chain_head",
+                func!(invoke_host_function!(observe_chain_head)),
+            );
         } else {
             ns.insert("__get", func!(invoke_host_function!(unreachable)));
             ns.insert("__get_details", func!(invoke_host_function!(unreachable)));
             ns.insert("__get_links", func!(invoke_host_function!(unreachable)));
+            ns.insert(
+                "__get_links_in_range",
+                func!(invoke_host_function!(unreachable)),
+            );
             ns.insert(
                 "__get_link_details",
                 func!(invoke_host_function!(unreachable)),
             );
+            ns.insert(
+                "__get_link_aggregate",
+                func!(invoke_host_function!(unreachable)),
+            );
             ns.insert("__query", func!(invoke_host_function!(unreachable)));
+            ns.insert("__query_index", func!(invoke_host_function!(unreachable)));
+            ns.insert("__list_pins", func!(invoke_host_function!(unreachable)));
+            ns.insert(
+                "__observe_chain_head",
+                func!(invoke_host_function!(unreachable)),
+            );
         }
 
         if let HostFnAccess {
@@ -287,8 +394,12 @@ impl WasmRibosome {
         } = host_fn_access
         {
             ns.insert("__call_remote", func!(invoke_host_function!(call_remote)));
+            ns.insert("__subscribe", func!(invoke_host_function!(subscribe)));
+            ns.insert("__unsubscribe", func!(invoke_host_function!(unsubscribe)));
         } else {
             ns.insert("__call_remote", func!(invoke_host_function!(unreachable)));
+            ns.insert("__subscribe", func!(invoke_host_function!(unreachable)));
+            ns.insert("__unsubscribe", func!(invoke_host_function!(unreachable)));
         }
 
         if let HostFnAccess {
@@ -300,19 +411,27 @@ impl WasmRibosome {
             ns.insert("__create", func!(invoke_host_function!(create)));
             ns.insert("__emit_signal", func!(invoke_host_function!(emit_signal)));
             ns.insert("__create_link", func!(invoke_host_function!(create_link)));
+            ns.insert("__create_links", func!(invoke_host_function!(create_links)));
             ns.insert("__delete_link", func!(invoke_host_function!(delete_link)));
+            ns.insert("__delete_links", func!(invoke_host_function!(delete_links)));
             ns.insert("__update", func!(invoke_host_function!(update)));
             ns.insert("__delete", func!(invoke_host_function!(delete)));
             ns.insert("__schedule", func!(invoke_host_function!(schedule)));
+            ns.insert("__pin", func!(invoke_host_function!(pin)));
+            ns.insert("__unpin", func!(invoke_host_function!(unpin)));
         } else {
             ns.insert("__call", func!(invoke_host_function!(unreachable)));
             ns.insert("__create", func!(invoke_host_function!(unreachable)));
             ns.insert("__emit_signal", func!(invoke_host_function!(unreachable)));
             ns.insert("__create_link", func!(invoke_host_function!(unreachable)));
+            ns.insert("__create_links", func!(invoke_host_function!(unreachable)));
             ns.insert("__delete_link", func!(invoke_host_function!(unreachable)));
+            ns.insert("__delete_links", func!(invoke_host_function!(unreachable)));
             ns.insert("__update", func!(invoke_host_function!(unreachable)));
             ns.insert("__delete", func!(invoke_host_function!(unreachable)));
             ns.insert("__schedule", func!(invoke_host_function!(unreachable)));
+            ns.insert("__pin", func!(invoke_host_function!(unreachable)));
+            ns.insert("__unpin", func!(invoke_host_function!(unreachable)));
         }
         imports.register("env", ns);
 
@@ -384,29 +503,37 @@ impl RibosomeT for WasmRibosome {
             zome_name: zome_name.clone(),
             host_access,
         };
-        let module = self.module(call_context.clone())?;
+        // compiling and running wasm blocks, so it is moved off the async
+        // worker threads if it isn't already on a blocking thread
+        tokio::task::block_in_place(move || {
+            let module = self.module(call_context.clone())?;
 
-        if module.info().exports.contains_key(to_call.as_ref()) {
-            // there is a callback to_call and it is implemented in the wasm
-            // it is important to fully instantiate this (e.g. don't try to use the module above)
-            // because it builds guards against memory leaks and handles imports correctly
-            let mut instance = self.instance(call_context)?;
+            if module.info().exports.contains_key(to_call.as_ref()) {
+                // there is a callback to_call and it is implemented in the wasm
+                // it is important to fully instantiate this (e.g. don't try to use the module above)
+                // because it builds guards against memory leaks and handles imports correctly
+                let mut instance = self.instance(call_context)?;
 
-            let result: ExternOutput = holochain_wasmer_host::guest::call(
-                &mut instance,
-                to_call.as_ref(),
                 // be aware of this clone!
                 // the whole invocation is cloned!
                 // @todo - is this a problem for large payloads like entries?
-                invocation.to_owned().host_input()?,
-            )?;
-
-            Ok(Some(result))
-        } else {
-            // the func doesn't exist
-            // the callback is not implemented
-            Ok(None)
-        }
+                let input = invocation.to_owned().host_input()?;
+                // check before the guest allocates for the input
+                self.io_limits.check_input(input.size())?;
+
+                // the host fns the guest calls are part of this call
+                let result: ExternOutput = guest_call(|| {
+                    holochain_wasmer_host::guest::call(&mut instance, to_call.as_ref(), input)
+                })?;
+                self.io_limits.check_output(result.size())?;
+
+                Ok(Some(result))
+            } else {
+                // the func doesn't exist
+                // the callback is not implemented
+                Ok(None)
+            }
+        })
     }
 
     fn call_iterator<R: RibosomeT, I: crate::core::ribosome::Invocation>(
@@ -476,6 +603,22 @@ impl RibosomeT for WasmRibosome {
         do_callback!(self, access, invocation, EntryDefsCallbackResult)
     }
 
+    fn run_link_types(
+        &self,
+        access: LinkTypesHostAccess,
+        invocation: LinkTypesInvocation,
+    ) -> RibosomeResult<LinkTypesResult> {
+        do_callback!(self, access, invocation, LinkTypesCallbackResult)
+    }
+
+    fn run_index_op(
+        &self,
+        access: IndexOpHostAccess,
+        invocation: IndexOpInvocation,
+    ) -> RibosomeResult<IndexOpResult> {
+        do_callback!(self, access, invocation, IndexOpCallbackResult)
+    }
+
     fn run_migrate_agent(
         &self,
         access: MigrateAgentHostAccess,
@@ -1,4 +1,6 @@
+use holo_hash::{AgentPubKey, AnyDhtHash, DhtOpHash};
 use holochain_serialized_bytes::prelude::*;
+use holochain_types::cell::CellId;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, SerializedBytes)]
@@ -6,7 +8,31 @@ pub enum Signal {
     Trace,
     // Consistency(ConsistencySignal<String>),
     User(UserSignal),
+    /// Ops were integrated at a basis the Cell is subscribed to
+    BasisChanged(BasisChangedSignal),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, SerializedBytes)]
 pub struct UserSignal;
+
+/// Sent by an authority for a basis to the Cells subscribed to it,
+/// whenever it integrates ops there
+#[derive(Clone, Debug, Serialize, Deserialize, SerializedBytes)]
+pub struct BasisChangedSignal {
+    /// The entry, or base of links, which changed
+    pub basis: AnyDhtHash,
+    /// The ops the authority integrated
+    pub op_hashes: Vec<DhtOpHash>,
+    /// The authority which sent the notification
+    pub authority: AgentPubKey,
+}
+
+/// A [Signal] along with the Cell which emitted it,
+/// so it can be routed to the interfaces of that Cell's app
+#[derive(Clone, Debug)]
+pub struct CellSignal {
+    /// The Cell which emitted the signal
+    pub cell_id: CellId,
+    /// The signal itself, as sent to clients
+    pub signal: Signal,
+}
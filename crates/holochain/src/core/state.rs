@@ -32,6 +32,8 @@
 //! source: https://textik.com/#d7907793784e17e9
 //! ```
 
+pub mod activity_cache;
+pub mod app_index;
 #[allow(missing_docs)]
 pub mod cascade;
 #[allow(missing_docs)]
@@ -39,9 +41,15 @@ pub mod chain_sequence;
 pub mod dht_op_integration;
 #[allow(missing_docs)]
 pub mod element_buf;
+pub mod integration_journal;
+pub mod integrity;
+pub mod limbo_dump;
 pub mod metadata;
+pub mod pins;
+pub mod publish_progress;
 #[allow(missing_docs)]
 pub mod source_chain;
+pub mod subscriptions;
 pub mod validation_db;
 pub mod validation_receipts_db;
 #[allow(missing_docs)]
@@ -0,0 +1,128 @@
+//! A local cache of other agents' verified source chain headers.
+//!
+//! Each agent's headers are kept from the start of their chain up to a high
+//! watermark: the sequence number of the last header which has been
+//! verified. Polling an agent's activity then only needs to ask authorities
+//! for the headers after the watermark, and new headers are only appended
+//! once they have been verified to carry on from it.
+
+use holo_hash::AgentPubKey;
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh,
+    db::AGENT_ACTIVITY_CACHE,
+    error::{DatabaseError, DatabaseResult},
+    prelude::{BufferedStore, EnvironmentRead, GetDb, Writer},
+};
+use holochain_zome_types::element::SignedHeader;
+
+/// Database type for the AgentActivityCache
+pub type ActivityCacheStore = KvBufFresh<AgentPubKey, CachedActivity>;
+
+/// The verified headers of an agent's chain, in sequence order
+/// from the start of the chain
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct CachedActivity {
+    /// The headers up to and including the watermark
+    pub headers: Vec<SignedHeader>,
+}
+
+impl CachedActivity {
+    /// The sequence number of the last verified header
+    pub fn watermark(&self) -> Option<u32> {
+        self.headers.last().map(|sh| sh.0.header_seq())
+    }
+
+    /// The sequence number of the first header which isn't cached yet
+    pub fn next_seq(&self) -> u32 {
+        self.watermark().map_or(0, |seq| seq + 1)
+    }
+}
+
+/// Buffer for the verified activity of other agents
+pub struct ActivityCacheBuf {
+    store: ActivityCacheStore,
+}
+
+impl ActivityCacheBuf {
+    /// Create a new buffer for the AgentActivityCache database
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*AGENT_ACTIVITY_CACHE)?;
+        Ok(Self {
+            store: ActivityCacheStore::new(env, db),
+        })
+    }
+
+    /// The cached activity of an agent, which is empty if nothing
+    /// has been verified yet
+    pub fn get(&self, agent: &AgentPubKey) -> DatabaseResult<CachedActivity> {
+        Ok(self.store.get(agent)?.unwrap_or_default())
+    }
+
+    /// Append headers which have been verified to carry on from the watermark
+    pub fn append(&mut self, agent: AgentPubKey, headers: Vec<SignedHeader>) -> DatabaseResult<()> {
+        if headers.is_empty() {
+            return Ok(());
+        }
+        let mut cached = self.get(&agent)?;
+        debug_assert_eq!(headers[0].0.header_seq(), cached.next_seq());
+        cached.headers.extend(headers);
+        self.store.put(agent, cached)
+    }
+
+    /// Forget everything cached for an agent, so the next poll
+    /// starts again from the start of their chain
+    pub fn clear(&mut self, agent: AgentPubKey) -> DatabaseResult<()> {
+        self.store.delete(agent)
+    }
+}
+
+impl BufferedStore for ActivityCacheBuf {
+    type Error = DatabaseError;
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> Result<(), Self::Error> {
+        self.store.flush_to_txn_ref(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::AgentPubKeyFixturator;
+    use holochain_state::{env::WriteManager, test_utils::test_cell_env};
+    use holochain_types::fixt::{CreateFixturator, DnaFixturator, SignatureFixturator};
+    use holochain_zome_types::header::Header;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn appends_after_the_watermark() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let agent = fixt!(AgentPubKey);
+        let dna = SignedHeader(Header::Dna(fixt!(Dna)), fixt!(Signature));
+        let mut create = fixt!(Create);
+        create.header_seq = 1;
+        let create = SignedHeader(Header::Create(create), fixt!(Signature));
+
+        {
+            let mut buf = ActivityCacheBuf::new(env.clone().into()).unwrap();
+            assert_eq!(buf.get(&agent).unwrap().watermark(), None);
+            assert_eq!(buf.get(&agent).unwrap().next_seq(), 0);
+            buf.append(agent.clone(), vec![dna.clone()]).unwrap();
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+        {
+            let mut buf = ActivityCacheBuf::new(env.clone().into()).unwrap();
+            assert_eq!(buf.get(&agent).unwrap().watermark(), Some(0));
+            buf.append(agent.clone(), vec![create.clone()]).unwrap();
+            let cached = buf.get(&agent).unwrap();
+            assert_eq!(cached.watermark(), Some(1));
+            assert_eq!(cached.headers, vec![dna, create]);
+
+            buf.clear(agent.clone()).unwrap();
+            assert_eq!(buf.get(&agent).unwrap(), CachedActivity::default());
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! The records which zomes emit from their `index_op` callback.
+//!
+//! Each record is stored under a key made of the zome name, the index name,
+//! the record's key and the hash of the header it was emitted for. Every part
+//! but the last is escaped and terminated so that the store's byte order is
+//! the same as ordering by (zome, index, key), which lets a key range be read
+//! with a single scan. See [holochain_zome_types::index].
+
+use super::metadata::BytesKey;
+use fallible_iterator::FallibleIterator;
+use holo_hash::HeaderHash;
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh,
+    db::APP_INDEX,
+    error::{DatabaseError, DatabaseResult},
+    prelude::{BufferedStore, EnvironmentRead, GetDb, Readable, Writer},
+};
+use holochain_zome_types::{
+    index::{IndexHit, IndexKey, IndexName, IndexQuery, IndexRecord},
+    zome::ZomeName,
+};
+
+/// Database type for the AppIndex
+pub type AppIndexStore = KvBufFresh<BytesKey, AppIndexValue>;
+
+/// The value stored for each index record
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AppIndexValue {
+    /// The record's key, kept unescaped for range checks
+    pub key: IndexKey,
+    /// The record's value
+    pub value: SerializedBytes,
+    /// The header of the entry the record was emitted for
+    pub header_hash: HeaderHash,
+}
+
+/// Buffer for putting and searching the records of app defined indexes
+pub struct AppIndexBuf {
+    store: AppIndexStore,
+}
+
+impl AppIndexBuf {
+    /// Create a new buffer for the AppIndex database
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*APP_INDEX)?;
+        Ok(Self {
+            store: AppIndexStore::new(env, db),
+        })
+    }
+
+    /// Add a record emitted by a zome for the entry created by `header_hash`.
+    /// Putting the same record again overwrites it.
+    pub fn put(
+        &mut self,
+        zome_name: &ZomeName,
+        record: IndexRecord,
+        header_hash: HeaderHash,
+    ) -> DatabaseResult<()> {
+        let mut key = index_prefix(zome_name, &record.index);
+        push_escaped(&mut key, &record.key.0);
+        key.extend_from_slice(header_hash.get_full_bytes());
+        self.store.put(
+            BytesKey(key),
+            AppIndexValue {
+                key: record.key,
+                value: record.value,
+                header_hash,
+            },
+        )
+    }
+
+    /// Search one of a zome's indexes, returning records in key order
+    pub fn query<R: Readable>(
+        &self,
+        r: &R,
+        zome_name: &ZomeName,
+        query: &IndexQuery,
+    ) -> DatabaseResult<Vec<IndexHit>> {
+        let prefix = index_prefix(zome_name, &query.index);
+        let mut start = prefix.clone();
+        if let Some(key) = &query.start {
+            push_escaped(&mut start, &key.0);
+        }
+        let limit = query.limit.map(|l| l as usize).unwrap_or(usize::MAX);
+        self.store
+            .iter_from(r, BytesKey(start))?
+            .take_while(|(k, v)| {
+                Ok(k.starts_with(&prefix)
+                    && query.end.as_ref().map(|end| &v.key < end).unwrap_or(true))
+            })
+            .take(limit)
+            .map(|(_, v)| {
+                Ok(IndexHit {
+                    key: v.key,
+                    value: v.value,
+                    header_hash: v.header_hash,
+                })
+            })
+            .collect()
+    }
+}
+
+impl BufferedStore for AppIndexBuf {
+    type Error = DatabaseError;
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> Result<(), Self::Error> {
+        self.store.flush_to_txn_ref(writer)
+    }
+}
+
+/// The part of the key shared by every record in an index
+fn index_prefix(zome_name: &ZomeName, index: &IndexName) -> Vec<u8> {
+    let mut prefix = Vec::new();
+    push_escaped(&mut prefix, zome_name.0.as_bytes());
+    push_escaped(&mut prefix, index.0.as_bytes());
+    prefix
+}
+
+/// Append bytes so that no encoding is a prefix of another
+/// and encodings sort in the same order as the bytes.
+/// Zeros become `0, 0xFF` and the end is marked by `0, 0`.
+fn push_escaped(buf: &mut Vec<u8>, bytes: &[u8]) {
+    for b in bytes {
+        buf.push(*b);
+        if *b == 0 {
+            buf.push(0xFF);
+        }
+    }
+    buf.extend_from_slice(&[0, 0]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::HeaderHashFixturator;
+    use holochain_state::{
+        env::{ReadManager, WriteManager},
+        test_utils::test_cell_env,
+    };
+
+    fn record(index: &str, key: IndexKey) -> IndexRecord {
+        IndexRecord::new(index, key, SerializedBytes::try_from(()).unwrap())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn query_by_key_and_range() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let zome: ZomeName = "people".into();
+        let other_zome: ZomeName = "places".into();
+
+        {
+            let mut buf = AppIndexBuf::new(env.clone().into()).unwrap();
+            // Keys which are prefixes of each other, or contain zeros,
+            // must still come back in key order
+            for key in vec![
+                IndexKey::from("bo"),
+                IndexKey::from("bob"),
+                IndexKey::from(vec![b'b', 0]),
+                IndexKey::from("alice"),
+            ] {
+                buf.put(&zome, record("by_name", key), fixt!(HeaderHash))
+                    .unwrap();
+            }
+            for age in vec![40, 7, 18, 65] {
+                buf.put(
+                    &zome,
+                    record("by_age", IndexKey::from_u64(age)),
+                    fixt!(HeaderHash),
+                )
+                .unwrap();
+            }
+            buf.put(
+                &other_zome,
+                record("by_name", "bob".into()),
+                fixt!(HeaderHash),
+            )
+            .unwrap();
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+
+        let reader = env_ref.reader().unwrap();
+        let buf = AppIndexBuf::new(env.clone().into()).unwrap();
+        let keys = |query: IndexQuery| -> Vec<IndexKey> {
+            buf.query(&reader, &zome, &query)
+                .unwrap()
+                .into_iter()
+                .map(|hit| hit.key)
+                .collect()
+        };
+
+        assert_eq!(
+            keys(IndexQuery::all("by_name")),
+            vec![
+                IndexKey::from("alice"),
+                IndexKey::from(vec![b'b', 0]),
+                IndexKey::from("bo"),
+                IndexKey::from("bob"),
+            ]
+        );
+        assert_eq!(
+            keys(IndexQuery::exact("by_name", "bo")),
+            vec![IndexKey::from("bo")]
+        );
+        assert_eq!(
+            keys(IndexQuery::range(
+                "by_age",
+                IndexKey::from_u64(18),
+                IndexKey::from_u64(65)
+            )),
+            vec![IndexKey::from_u64(18), IndexKey::from_u64(40)]
+        );
+        assert_eq!(
+            keys(IndexQuery::all("by_age").limit(1)),
+            vec![IndexKey::from_u64(7)]
+        );
+        assert!(keys(IndexQuery::all("by_place")).is_empty());
+        assert_eq!(
+            buf.query(&reader, &other_zome, &IndexQuery::all("by_name"))
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
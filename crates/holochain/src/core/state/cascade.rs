@@ -6,7 +6,8 @@
 
 use super::{
     element_buf::ElementBuf,
-    metadata::{LinkMetaKey, MetadataBuf, MetadataBufT, SysMetaVal},
+    metadata::{LinkMetaKey, LinkMetaVal, MetadataBuf, MetadataBufT, SysMetaVal},
+    source_chain::SourceChainBuf,
 };
 use crate::core::workflow::{
     integrate_dht_ops_workflow::integrate_single_metadata,
@@ -16,11 +17,11 @@ use error::CascadeResult;
 use fallible_iterator::FallibleIterator;
 use holo_hash::{
     hash_type::{self, AnyDht},
-    AnyDhtHash, EntryHash, HasHash, HeaderHash,
+    AgentPubKey, AnyDhtHash, EntryHash, HasHash, HeaderHash,
 };
 use holochain_p2p::HolochainP2pCellT;
 use holochain_p2p::{
-    actor::{GetLinksOptions, GetMetaOptions, GetOptions},
+    actor::{GetLinksOptions, GetMetaOptions, GetOptions, PeerReport},
     HolochainP2pCell,
 };
 use holochain_state::{error::DatabaseResult, fresh_reader, prelude::*};
@@ -33,6 +34,7 @@ use holochain_types::{
     entry::option_entry_hashed,
     link::{GetLinksResponse, WireLinkMetaKey},
     metadata::{EntryDhtStatus, MetadataSet, TimedHeaderHash},
+    validation_receipt::{live_store_entry_ops, store_element_op_hash, vouching_validators},
     EntryHashed, HeaderHashed,
 };
 use holochain_zome_types::header::{CreateLink, DeleteLink};
@@ -50,6 +52,7 @@ use std::{
 };
 use tracing::*;
 use tracing_futures::Instrument;
+use verify::Rejection;
 
 #[cfg(test)]
 mod network_tests;
@@ -57,6 +60,11 @@ mod network_tests;
 mod test;
 
 pub mod error;
+pub mod verify;
+
+/// How many more times to ask for data when every authority which
+/// answered failed with an error
+const AUTHORITY_ERROR_RETRIES: usize = 2;
 
 pub struct Cascade<'a, Network = HolochainP2pCell, MetaVault = MetadataBuf, MetaCache = MetadataBuf>
 where
@@ -72,6 +80,19 @@ where
 
     env: EnvironmentRead,
     network: Network,
+
+    /// The authorities which have vouched for each header
+    /// with a receipt in their responses
+    vouched: BTreeMap<HeaderHash, BTreeSet<AgentPubKey>>,
+
+    /// The hashes authorities have told us they don't hold,
+    /// so we don't ask for them again
+    not_held: BTreeSet<AnyDhtHash>,
+
+    /// The agent's own chain. Its links are included in link queries even
+    /// before they're integrated, and gets find what's been committed to
+    /// its scratch space in this zome call before asking the network.
+    authored: Option<&'a SourceChainBuf>,
 }
 
 #[derive(Debug)]
@@ -113,9 +134,19 @@ where
             element_cache,
             meta_cache,
             network,
+            vouched: BTreeMap::new(),
+            not_held: BTreeSet::new(),
+            authored: None,
         }
     }
 
+    /// Include the links on this source chain in link queries, unless the
+    /// query's options opt out, and the elements in its scratch space in gets
+    pub fn with_authored(mut self, source_chain: &'a SourceChainBuf) -> Self {
+        self.authored = Some(source_chain);
+        self
+    }
+
     async fn update_stores(&mut self, element: Element) -> CascadeResult<()> {
         let op_lights = produce_op_lights_from_elements(vec![&element]).await?;
         let (shh, e) = element.into_inner();
@@ -144,13 +175,38 @@ where
         hash: HeaderHash,
         options: GetOptions,
     ) -> CascadeResult<()> {
-        let results = self.network.get(hash.into(), options).await?;
+        let requested: AnyDhtHash = hash.clone().into();
+        if self.not_held.contains(&requested) {
+            return Ok(());
+        }
+        let with_receipts = options.min_validations > 0;
+        let results = self.network_get(requested.clone(), options).await?;
+        if !results.is_empty()
+            && results
+                .iter()
+                .all(|(_, r)| matches!(r, GetElementResponse::GetHeader(None)))
+        {
+            self.not_held.insert(requested.clone());
+        }
         // Search through the returns for the first delete
-        for response in results.into_iter() {
+        for (peer, response) in results.into_iter() {
             match response {
                 // Has header
                 GetElementResponse::GetHeader(Some(we)) => {
+                    let validators = if with_receipts {
+                        let op_hash = store_element_op_hash(we.signed_header().header());
+                        vouching_validators(we.receipts(), &op_hash).await
+                    } else {
+                        BTreeSet::new()
+                    };
                     let (element, delete) = we.into_element_and_delete().await;
+                    if let Err(reason) =
+                        Self::verify_header_response(&hash, &element, &delete).await
+                    {
+                        self.reject(peer, &requested, reason).await;
+                        continue;
+                    }
+                    self.record_vouches(element.header_address().clone(), validators);
                     self.update_stores(element).await?;
 
                     if let Some(delete) = delete {
@@ -174,53 +230,121 @@ where
     async fn fetch_element_via_entry(
         &mut self,
         hash: EntryHash,
-        options: GetOptions,
+        mut options: GetOptions,
     ) -> CascadeResult<()> {
+        let requested: AnyDhtHash = hash.clone().into();
+        if self.not_held.contains(&requested) {
+            return Ok(());
+        }
+        // Authorities can assemble collapsed responses in a single pass
+        options.collapsed = true;
         let results = self
-            .network
-            .get(hash.clone().into(), options.clone())
+            .network_get(requested.clone(), options.clone())
             .instrument(debug_span!("fetch_element_via_entry::network_get"))
             .await?;
+        if !results.is_empty()
+            && results.iter().all(|(_, r)| {
+                matches!(
+                    r,
+                    GetElementResponse::GetEntryFull(None)
+                        | GetElementResponse::GetEntryCollapsed(None)
+                )
+            })
+        {
+            self.not_held.insert(requested.clone());
+        }
 
-        for response in results {
-            match response {
-                GetElementResponse::GetEntryFull(Some(raw)) => {
-                    let RawGetEntryResponse {
-                        live_headers,
-                        deletes,
-                        entry,
-                        entry_type,
-                        updates,
-                    } = *raw;
-                    let elements =
-                        ElementGroup::from_wire_elements(live_headers, entry_type, entry).await?;
-                    let entry_hash = elements.entry_hash().clone();
-                    self.update_stores_with_element_group(elements).await?;
-                    for delete in deletes {
-                        let element = delete.into_element().await;
-                        self.update_stores(element).await?;
-                    }
-                    for update in updates {
-                        let element = update.into_element(entry_hash.clone()).await;
-                        self.update_stores(element).await?;
-                    }
+        for (peer, response) in results {
+            let raw = match response {
+                GetElementResponse::GetEntryFull(Some(raw)) => *raw,
+                GetElementResponse::GetEntryCollapsed(Some(collapsed)) => {
+                    collapsed.into_raw(hash.clone())
                 }
                 // Authority didn't have any headers for this entry
-                GetElementResponse::GetEntryFull(None) => (),
+                GetElementResponse::GetEntryFull(None)
+                | GetElementResponse::GetEntryCollapsed(None) => continue,
                 r @ GetElementResponse::GetHeader(_) => {
                     error!(
                         msg = "Got an invalid response to fetch element via entry",
                         ?r
                     );
+                    continue;
                 }
                 r => unimplemented!("{:?} is unimplemented for fetching via entry", r),
+            };
+            let RawGetEntryResponse {
+                live_headers,
+                deletes,
+                entry,
+                entry_type,
+                updates,
+                receipts,
+            } = raw;
+            let mut vouches = Vec::new();
+            if options.min_validations > 0 {
+                for (header_hash, op_hash) in
+                    live_store_entry_ops(&live_headers, &entry_type, &hash).await
+                {
+                    vouches.push((header_hash, vouching_validators(&receipts, &op_hash).await));
+                }
+            }
+            let elements =
+                ElementGroup::from_wire_elements(live_headers, entry_type, entry).await?;
+            let mut deletes_and_updates = Vec::with_capacity(deletes.len() + updates.len());
+            for delete in deletes {
+                deletes_and_updates.push(delete.into_element().await);
+            }
+            for update in updates {
+                deletes_and_updates.push(update.into_element(hash.clone()).await);
+            }
+            if let Err(reason) =
+                Self::verify_entry_response(&hash, &elements, &deletes_and_updates).await
+            {
+                self.reject(peer, &requested, reason).await;
+                continue;
+            }
+            for (header_hash, validators) in vouches {
+                self.record_vouches(header_hash, validators);
+            }
+            self.update_stores_with_element_group(elements).await?;
+            for element in deletes_and_updates {
+                self.update_stores(element).await?;
             }
         }
         Ok(())
     }
 
-    // TODO: Remove when used
-    #[allow(dead_code)]
+    /// Get from the network, asking again while every authority which
+    /// answers fails with an error. Failed responses are left out, and
+    /// the authorities which sent them are reported so we're less likely
+    /// to ask them next time.
+    async fn network_get(
+        &mut self,
+        requested: AnyDhtHash,
+        options: GetOptions,
+    ) -> CascadeResult<Vec<(AgentPubKey, GetElementResponse)>> {
+        let mut retries = 0;
+        loop {
+            let results = self.network.get(requested.clone(), options.clone()).await?;
+            let mut failed = false;
+            let mut answers = Vec::with_capacity(results.len());
+            for (peer, response) in results {
+                match response {
+                    GetElementResponse::Error(kind) => {
+                        debug!(?peer, ?requested, %kind, "Authority failed to answer a get");
+                        failed = true;
+                        self.report(peer, PeerReport::Error).await;
+                    }
+                    r => answers.push((peer, r)),
+                }
+            }
+            if !failed || !answers.is_empty() || retries == AUTHORITY_ERROR_RETRIES {
+                return Ok(answers);
+            }
+            retries += 1;
+        }
+    }
+
     async fn fetch_meta(
         &mut self,
         basis: AnyDhtHash,
@@ -260,48 +384,190 @@ where
     async fn fetch_links(
         &mut self,
         link_key: WireLinkMetaKey,
-        options: GetLinksOptions,
+        mut options: GetLinksOptions,
     ) -> CascadeResult<()> {
         debug!("in get links");
-        let results = self.network.get_links(link_key, options).await?;
-        for links in results {
-            let GetLinksResponse {
-                link_adds,
-                link_removes,
-            } = links;
-
-            for (link_add, signature) in link_adds {
-                debug!(?link_add);
-                let element = Element::new(
-                    SignedHeaderHashed::from_content_sync(SignedHeader(link_add.into(), signature)),
-                    None,
-                );
-                self.update_stores(element).await?;
+        let requested = link_key.basis();
+        let mut fetched = 0;
+        loop {
+            let results = self
+                .network
+                .get_links(link_key.clone(), options.clone())
+                .await?;
+            // Carry on from the earliest continuation, so no authority's links are skipped
+            let mut next: Option<TimedHeaderHash> = None;
+            for (peer, links) in results {
+                let GetLinksResponse {
+                    link_adds,
+                    link_removes,
+                    continuation,
+                } = links;
+                let page_len = link_adds.len();
+
+                let mut elements = Vec::with_capacity(link_adds.len() + link_removes.len());
+                for (link_add, signature) in link_adds {
+                    debug!(?link_add);
+                    elements.push(Element::new(
+                        SignedHeaderHashed::from_content_sync(SignedHeader(
+                            link_add.into(),
+                            signature,
+                        )),
+                        None,
+                    ));
+                }
+                for (link_remove, signature) in link_removes {
+                    debug!(?link_remove);
+                    elements.push(Element::new(
+                        SignedHeaderHashed::from_content_sync(SignedHeader(
+                            link_remove.into(),
+                            signature,
+                        )),
+                        None,
+                    ));
+                }
+                if let Err(reason) = Self::verify_links_response(&requested, &elements).await {
+                    self.reject(peer, &requested, reason).await;
+                    continue;
+                }
+                for element in elements {
+                    self.update_stores(element).await?;
+                }
+                fetched += page_len;
+                if let Some(continuation) = continuation {
+                    next = Some(match next {
+                        Some(next) => next.min(continuation),
+                        None => continuation,
+                    });
+                }
             }
-            for (link_remove, signature) in link_removes {
-                debug!(?link_remove);
-                let element = Element::new(
-                    SignedHeaderHashed::from_content_sync(SignedHeader(
-                        link_remove.into(),
-                        signature,
-                    )),
-                    None,
-                );
-                self.update_stores(element).await?;
+
+            // Stop once every page has been fetched, or the caller has enough.
+            // A continuation which doesn't move on would never end, so stop there too.
+            let moves_on = match (&next, &options.after) {
+                (Some(next), Some(after)) => next > after,
+                (next, _) => next.is_some(),
+            };
+            if !moves_on || options.max_links.map_or(false, |max| fetched >= max) {
+                return Ok(());
+            }
+            options.after = next;
+        }
+    }
+
+    /// Count a rejected response, and lower the reputation of the peer who
+    /// sent it so we're less likely to ask them again
+    /// Count these validators as vouching for a header
+    fn record_vouches(&mut self, header_hash: HeaderHash, validators: BTreeSet<AgentPubKey>) {
+        if !validators.is_empty() {
+            self.vouched
+                .entry(header_hash)
+                .or_default()
+                .extend(validators);
+        }
+    }
+
+    /// Whether at least `min_validations` authorities have vouched
+    /// for a header in the responses this cascade has seen
+    fn is_vouched_for(&self, header_hash: &HeaderHash, min_validations: u8) -> bool {
+        min_validations == 0
+            || self.vouched.get(header_hash).map_or(0, BTreeSet::len) >= min_validations as usize
+    }
+
+    async fn reject(&mut self, peer: AgentPubKey, requested: &AnyDhtHash, reason: Rejection) {
+        verify::reject(&peer, requested, reason);
+        self.report(peer, PeerReport::BadResponse).await;
+    }
+
+    async fn report(&mut self, peer: AgentPubKey, report: PeerReport) {
+        if let Err(e) = self.network.report_peer(peer, report).await {
+            warn!(?e, ?report, "Failed to report a peer");
+        }
+    }
+
+    /// An element fetched by header must be the requested one, and a delete
+    /// sent alongside it must delete it
+    async fn verify_header_response(
+        requested: &HeaderHash,
+        element: &Element,
+        delete: &Option<Element>,
+    ) -> Result<(), Rejection> {
+        if element.header_address() != requested {
+            return Err(Rejection::UnexpectedData);
+        }
+        verify::verify_element(element).await?;
+        if let Some(delete) = delete {
+            match delete.header() {
+                Header::Delete(Delete {
+                    deletes_address, ..
+                }) if deletes_address == requested => (),
+                _ => return Err(Rejection::UnexpectedData),
+            }
+            verify::verify_element(delete).await?;
+        }
+        Ok(())
+    }
+
+    /// Elements fetched by entry must be for the requested entry, as must
+    /// any deletes and updates of them
+    async fn verify_entry_response(
+        requested: &EntryHash,
+        elements: &ElementGroup<'_>,
+        deletes_and_updates: &[Element],
+    ) -> Result<(), Rejection> {
+        verify::verify_element_group(elements, requested).await?;
+        for element in deletes_and_updates {
+            match element.header() {
+                Header::Delete(Delete {
+                    deletes_entry_address,
+                    ..
+                }) if deletes_entry_address == requested => (),
+                // The original entry of an update is filled in locally
+                Header::Update(_) => (),
+                _ => return Err(Rejection::UnexpectedData),
             }
+            verify::verify_element(element).await?;
         }
         Ok(())
     }
 
-    fn get_element_local_raw(&self, hash: &HeaderHash) -> CascadeResult<Option<Element>> {
-        let r = match self.element_vault.get_element(hash)? {
-            None => self.element_cache.get_element(hash)?,
-            r => r,
+    /// Links must be on the requested base
+    async fn verify_links_response(
+        requested: &AnyDhtHash,
+        elements: &[Element],
+    ) -> Result<(), Rejection> {
+        for element in elements {
+            let base_address = match element.header() {
+                Header::CreateLink(CreateLink { base_address, .. })
+                | Header::DeleteLink(DeleteLink { base_address, .. }) => base_address,
+                _ => return Err(Rejection::UnexpectedData),
+            };
+            if AnyDhtHash::from(base_address.clone()) != *requested {
+                return Err(Rejection::UnexpectedData);
+            }
+            verify::verify_element(element).await?;
+        }
+        Ok(())
+    }
+
+    /// The local reads take a reader, so that a single query reads the
+    /// elements and the metadata which says they're valid from the same
+    /// snapshot. Reading them with separate readers lets a write land in
+    /// between, e.g. showing a header in the metadata which is missing
+    /// from the element store.
+    fn get_element_local_raw<R: Readable>(
+        &self,
+        r: &R,
+        hash: &HeaderHash,
+    ) -> CascadeResult<Option<Element>> {
+        let el = match self.element_vault.get_element_with_reader(r, hash)? {
+            None => self.element_cache.get_element_with_reader(r, hash)?,
+            el => el,
         };
         // Check we have a valid reason to return this element
-        match r {
+        match el {
             Some(el)
                 if self.valid_element(
+                    r,
                     el.header_address(),
                     el.header().entry_data().map(|(h, _)| h),
                 )? =>
@@ -313,17 +579,19 @@ where
     }
 
     /// Gets the first element we can find for this entry locally
-    fn get_element_local_raw_via_entry(&self, hash: &EntryHash) -> CascadeResult<Option<Element>> {
+    fn get_element_local_raw_via_entry<R: Readable>(
+        &self,
+        r: &R,
+        hash: &EntryHash,
+    ) -> CascadeResult<Option<Element>> {
         // Get all the headers we know about.
         let mut headers: BTreeSet<TimedHeaderHash> =
-            fresh_reader!(self.meta_cache.env(), |r| self
-                .meta_cache
-                .get_headers(&r, hash.clone())?
-                .collect())?;
-        headers.extend(fresh_reader!(self.meta_cache.env(), |r| self
-            .meta_vault
-            .get_headers(&r, hash.clone())?
-            .collect::<Vec<_>>())?);
+            self.meta_cache.get_headers(r, hash.clone())?.collect()?;
+        headers.extend(
+            self.meta_vault
+                .get_headers(r, hash.clone())?
+                .collect::<Vec<_>>()?,
+        );
 
         // We might not actually be holding some of these
         // so we need to search until we find one.
@@ -331,7 +599,7 @@ where
         // so iterate in reverse
         for header in headers.into_iter().rev() {
             // Return the first element we are actually holding
-            if let Some(el) = self.get_element_local_raw(&header.header_hash)? {
+            if let Some(el) = self.get_element_local_raw(r, &header.header_hash)? {
                 return Ok(Some(el));
             }
         }
@@ -339,36 +607,36 @@ where
         Ok(None)
     }
 
-    fn get_entry_local_raw(&self, hash: &EntryHash) -> CascadeResult<Option<EntryHashed>> {
-        let r = match self.element_vault.get_entry(hash)? {
-            None => self.element_cache.get_entry(hash)?,
-            r => r,
+    fn get_entry_local_raw<R: Readable>(
+        &self,
+        r: &R,
+        hash: &EntryHash,
+    ) -> CascadeResult<Option<EntryHashed>> {
+        let e = match self.element_vault.get_entry_with_reader(r, hash)? {
+            None => self.element_cache.get_entry_with_reader(r, hash)?,
+            e => e,
         };
         // Check we have a valid reason to return this element
-        match r {
-            Some(e) if self.valid_entry(e.as_hash())? => Ok(Some(e)),
+        match e {
+            Some(e) if self.valid_entry(r, e.as_hash())? => Ok(Some(e)),
             _ => Ok(None),
         }
     }
 
-    fn get_header_local_raw(&self, hash: &HeaderHash) -> CascadeResult<Option<HeaderHashed>> {
-        Ok(self
-            .get_header_local_raw_with_sig(hash)?
-            .map(|h| h.into_header_and_signature().0))
-    }
-
-    fn get_header_local_raw_with_sig(
+    fn get_header_local_raw_with_sig<R: Readable>(
         &self,
+        r: &R,
         hash: &HeaderHash,
     ) -> CascadeResult<Option<SignedHeaderHashed>> {
-        let r = match self.element_vault.get_header(hash)? {
-            None => self.element_cache.get_header(hash)?,
-            r => r,
+        let h = match self.element_vault.get_header_with_reader(r, hash)? {
+            None => self.element_cache.get_header_with_reader(r, hash)?,
+            h => h,
         };
         // Check we have a valid reason to return this element
-        match r {
+        match h {
             Some(h)
                 if self.valid_element(
+                    r,
                     h.header_address(),
                     h.header().entry_data().map(|(h, _)| h),
                 )? =>
@@ -379,79 +647,150 @@ where
         }
     }
 
-    fn render_headers<T, F>(&self, headers: Vec<TimedHeaderHash>, f: F) -> CascadeResult<Vec<T>>
+    /// Get many headers from the vault, falling back to the cache.
+    /// Headers we don't hold, or have no valid reason to return, are None.
+    fn get_headers_local_raw<R: Readable>(
+        &self,
+        r: &R,
+        hashes: &[HeaderHash],
+    ) -> CascadeResult<Vec<Option<SignedHeaderHashed>>> {
+        let mut headers = self.element_vault.get_headers_with_reader(r, hashes)?;
+        let missing = missing_hashes(hashes, &headers);
+        fill_missing(
+            &mut headers,
+            self.element_cache.get_headers_with_reader(r, &missing)?,
+        );
+        headers
+            .into_iter()
+            .map(|h| match h {
+                Some(h)
+                    if self.valid_element(
+                        r,
+                        h.header_address(),
+                        h.header().entry_data().map(|(h, _)| h),
+                    )? =>
+                {
+                    Ok(Some(h))
+                }
+                _ => Ok(None),
+            })
+            .collect()
+    }
+
+    /// Get many elements from the vault, falling back to the cache.
+    /// Elements we don't hold, or have no valid reason to return, are None.
+    fn get_elements_local_raw<R: Readable>(
+        &self,
+        r: &R,
+        hashes: &[HeaderHash],
+    ) -> CascadeResult<Vec<Option<Element>>> {
+        let mut elements = self.element_vault.get_elements_with_reader(r, hashes)?;
+        let missing = missing_hashes(hashes, &elements);
+        fill_missing(
+            &mut elements,
+            self.element_cache.get_elements_with_reader(r, &missing)?,
+        );
+        elements
+            .into_iter()
+            .map(|el| match el {
+                Some(el)
+                    if self.valid_element(
+                        r,
+                        el.header_address(),
+                        el.header().entry_data().map(|(h, _)| h),
+                    )? =>
+                {
+                    Ok(Some(el))
+                }
+                _ => Ok(None),
+            })
+            .collect()
+    }
+
+    fn render_headers<R: Readable, T, F>(
+        &self,
+        r: &R,
+        headers: Vec<TimedHeaderHash>,
+        f: F,
+    ) -> CascadeResult<Vec<T>>
     where
         F: Fn(Header) -> DhtOpConvertResult<T>,
     {
-        let mut result = Vec::with_capacity(headers.len());
-        for h in headers {
-            let hash = h.header_hash;
-            let h = self.get_header_local_raw(&hash)?;
-            match h {
-                Some(h) => result.push(f(HeaderHashed::into_content(h))?),
-                None => continue,
-            }
+        let hashes: Vec<HeaderHash> = headers.into_iter().map(|h| h.header_hash).collect();
+        let mut result = Vec::with_capacity(hashes.len());
+        let headers = self.get_headers_local_raw(r, &hashes)?;
+        for h in headers.into_iter().flatten() {
+            let h = h.into_header_and_signature().0;
+            result.push(f(HeaderHashed::into_content(h))?);
         }
         Ok(result)
     }
 
     async fn create_entry_details(&self, hash: EntryHash) -> CascadeResult<Option<EntryDetails>> {
-        match self.get_entry_local_raw(&hash)? {
-            Some(entry) => fresh_reader!(self.env, |r| {
-                let entry_dht_status = self.meta_cache.get_dht_status(&r, &hash)?;
-                let headers = self
-                    .meta_cache
-                    .get_headers(&r, hash.clone())?
-                    .collect::<Vec<_>>()?;
-                let headers = self.render_headers(headers, Ok)?;
-                let deletes = self
-                    .meta_cache
-                    .get_deletes_on_entry(&r, hash.clone())?
-                    .collect::<Vec<_>>()?;
-                let deletes = self.render_headers(deletes, |h| Ok(Delete::try_from(h)?))?;
-                let updates = self
-                    .meta_cache
-                    .get_updates(&r, hash.into())?
-                    .collect::<Vec<_>>()?;
-                let updates = self.render_headers(updates, |h| Ok(Update::try_from(h)?))?;
-                Ok(Some(EntryDetails {
-                    entry: entry.into_content(),
-                    headers,
-                    deletes,
-                    updates,
-                    entry_dht_status,
-                }))
-            }),
-            None => Ok(None),
-        }
+        // Read the entry and its metadata from the same snapshot
+        fresh_reader!(self.env, |r| {
+            let entry = match self.get_entry_local_raw(&r, &hash)? {
+                Some(entry) => entry,
+                None => return Ok(None),
+            };
+            let entry_dht_status = self.meta_cache.get_dht_status(&r, &hash)?;
+            let headers = self
+                .meta_cache
+                .get_headers(&r, hash.clone())?
+                .collect::<Vec<_>>()?;
+            let headers = self.render_headers(&r, headers, Ok)?;
+            let deletes = self
+                .meta_cache
+                .get_deletes_on_entry(&r, hash.clone())?
+                .collect::<Vec<_>>()?;
+            let deletes = self.render_headers(&r, deletes, |h| Ok(Delete::try_from(h)?))?;
+            let updates = self
+                .meta_cache
+                .get_updates(&r, hash.into())?
+                .collect::<Vec<_>>()?;
+            let updates = self.render_headers(&r, updates, |h| Ok(Update::try_from(h)?))?;
+            Ok(Some(EntryDetails {
+                entry: entry.into_content(),
+                headers,
+                deletes,
+                updates,
+                entry_dht_status,
+                references: Vec::new(),
+            }))
+        })
     }
 
     fn create_element_details(&self, hash: HeaderHash) -> CascadeResult<Option<ElementDetails>> {
-        match self.get_element_local_raw(&hash)? {
-            Some(element) => {
-                let hash = element.header_address().clone();
-                let deletes = fresh_reader!(self.env, |r| self
-                    .meta_cache
-                    .get_deletes_on_header(&r, hash)?
-                    .collect::<Vec<_>>())?;
-                let deletes = self.render_headers(deletes, |h| Ok(Delete::try_from(h)?))?;
-                Ok(Some(ElementDetails { element, deletes }))
-            }
-            None => Ok(None),
-        }
+        // Read the element and its metadata from the same snapshot
+        fresh_reader!(self.env, |r| {
+            let element = match self.get_element_local_raw(&r, &hash)? {
+                Some(element) => element,
+                None => return Ok(None),
+            };
+            let deletes = self
+                .meta_cache
+                .get_deletes_on_header(&r, element.header_address().clone())?
+                .collect::<Vec<_>>()?;
+            let deletes = self.render_headers(&r, deletes, |h| Ok(Delete::try_from(h)?))?;
+            Ok(Some(ElementDetails {
+                element,
+                deletes,
+                references: Vec::new(),
+            }))
+        })
     }
 
-    fn valid_header(&self, hash: &HeaderHash) -> CascadeResult<bool> {
-        Ok(self.meta_vault.has_registered_store_element(&hash)?
-            || self.meta_cache.has_registered_store_element(&hash)?)
+    fn valid_header<R: Readable>(&self, r: &R, hash: &HeaderHash) -> CascadeResult<bool> {
+        Ok(self.meta_vault.has_registered_store_element(r, &hash)?
+            || self.meta_cache.has_registered_store_element(r, &hash)?)
     }
 
-    fn valid_entry(&self, hash: &EntryHash) -> CascadeResult<bool> {
-        if self.meta_cache.has_any_registered_store_entry(hash)? {
+    fn valid_entry<R: Readable>(&self, r: &R, hash: &EntryHash) -> CascadeResult<bool> {
+        if self.meta_cache.has_any_registered_store_entry(r, hash)? {
             // Found a entry header in the cache
             return Ok(true);
         }
-        if self.meta_vault.has_any_registered_store_entry(hash)? {
+        if self.meta_vault.has_any_registered_store_entry(r, hash)? {
             // Found a entry header in the vault
             return Ok(true);
         }
@@ -459,25 +798,26 @@ where
     }
 
     /// Check if we have a valid reason to return an element from the cascade
-    fn valid_element(
+    fn valid_element<R: Readable>(
         &self,
+        r: &R,
         header_hash: &HeaderHash,
         entry_hash: Option<&EntryHash>,
     ) -> CascadeResult<bool> {
-        if self.valid_header(&header_hash)? {
+        if self.valid_header(r, &header_hash)? {
             return Ok(true);
         }
         if let Some(eh) = entry_hash {
             if self
                 .meta_cache
-                .has_registered_store_entry(eh, header_hash)?
+                .has_registered_store_entry(r, eh, header_hash)?
             {
                 // Found a entry header in the cache
                 return Ok(true);
             }
             if self
                 .meta_vault
-                .has_registered_store_entry(eh, header_hash)?
+                .has_registered_store_entry(r, eh, header_hash)?
             {
                 // Found a entry header in the vault
                 return Ok(true);
@@ -498,7 +838,8 @@ where
             .await?;
 
         // Get the entry and metadata
-        self.create_entry_details(entry_hash).await
+        let details = self.create_entry_details(entry_hash.clone()).await?;
+        self.merge_scratch_entry_details(&entry_hash, details)
     }
 
     #[instrument(skip(self, options))]
@@ -520,25 +861,12 @@ where
                 EntryDhtStatus::Live => {
                     let oldest_live_header = self
                         .meta_cache
-                        .get_headers(&r, entry_hash)?
-                        .filter_map(|header| {
-                            if self
-                                .meta_cache
-                                .get_deletes_on_header(&r, header.header_hash.clone())?
-                                .next()?
-                                .is_none()
-                            {
-                                Ok(Some(header))
-                            } else {
-                                Ok(None)
-                            }
-                        })
-                        .min()?
+                        .get_oldest_live_header(&r, &entry_hash)?
                         .expect("Status is live but no headers?");
 
                     // We have an oldest live header now get the element
                     CascadeResult::Ok(
-                        self.get_element_local_raw(&oldest_live_header.header_hash)?
+                        self.get_element_local_raw(&r, &oldest_live_header.header_hash)?
                             .map(Search::Found)
                             // It's not local so check the network
                             .unwrap_or(Search::Continue(oldest_live_header.header_hash)),
@@ -555,12 +883,18 @@ where
         })?;
 
         // Network
-        match oldest_live_element {
-            Search::Found(element) => Ok(Some(element)),
+        let element = match oldest_live_element {
+            Search::Found(element) => Some(element),
             Search::Continue(oldest_live_header) => {
-                self.dht_get_header(oldest_live_header, options).await
+                self.dht_get_header(oldest_live_header, options).await?
             }
-            Search::NotInCascade => Ok(None),
+            Search::NotInCascade => None,
+        };
+
+        // Scratch
+        match element {
+            Some(element) => Ok(Some(element)),
+            None => self.get_element_scratch_via_entry(&entry_hash),
         }
     }
 
@@ -571,12 +905,34 @@ where
         options: GetOptions,
     ) -> CascadeResult<Option<ElementDetails>> {
         debug!("in get header details");
+        // Scratch
+        let scratch = self.scratch()?;
+        let deletes = scratch_deletes_on_header(&scratch, &header_hash);
+        if let Some(element) = scratch
+            .into_iter()
+            .find(|el| el.header_address() == &header_hash)
+        {
+            return Ok(Some(ElementDetails {
+                element,
+                deletes,
+                references: Vec::new(),
+            }));
+        }
+
         // Network
         self.fetch_element_via_header(header_hash.clone(), options)
             .await?;
 
-        // Get the element and the metadata
-        self.create_element_details(header_hash)
+        // Get the element and the metadata, with any deletes from this call
+        let mut details = self.create_element_details(header_hash)?;
+        if let Some(details) = &mut details {
+            for delete in deletes {
+                if !details.deletes.contains(&delete) {
+                    details.deletes.push(delete);
+                }
+            }
+        }
+        Ok(details)
     }
 
     #[instrument(skip(self, options))]
@@ -590,6 +946,19 @@ where
         options: GetOptions,
     ) -> CascadeResult<Option<Element>> {
         debug!("in get header");
+        // Scratch
+        let scratch = self.scratch()?;
+        if !scratch_deletes_on_header(&scratch, &header_hash).is_empty() {
+            return Ok(None);
+        }
+        if let Some(element) = scratch
+            .into_iter()
+            .find(|el| el.header_address() == &header_hash)
+        {
+            // No authority can know about it yet
+            return Ok(Some(element));
+        }
+
         let found_local_delete = fresh_reader!(self.env, |r| {
             let in_cache = || {
                 DatabaseResult::Ok({
@@ -625,7 +994,7 @@ where
                 .is_none();
 
             if is_live {
-                self.get_element_local_raw(&header_hash)
+                self.get_element_local_raw(&r, &header_hash)
             } else {
                 Ok(None)
             }
@@ -642,11 +1011,11 @@ where
         hash: EntryHash,
         options: GetOptions,
     ) -> CascadeResult<Option<EntryHashed>> {
-        match self.get_entry_local_raw(&hash)? {
+        match fresh_reader!(self.env, |r| self.get_entry_local_raw(&r, &hash))? {
             Some(e) => Ok(Some(e)),
             None => {
                 self.fetch_element_via_entry(hash.clone(), options).await?;
-                self.get_entry_local_raw(&hash)
+                fresh_reader!(self.env, |r| self.get_entry_local_raw(&r, &hash))
             }
         }
     }
@@ -662,11 +1031,11 @@ where
         hash: HeaderHash,
         options: GetOptions,
     ) -> CascadeResult<Option<SignedHeaderHashed>> {
-        match self.get_header_local_raw_with_sig(&hash)? {
+        match fresh_reader!(self.env, |r| self.get_header_local_raw_with_sig(&r, &hash))? {
             Some(h) => Ok(Some(h)),
             None => {
                 self.fetch_element_via_header(hash.clone(), options).await?;
-                self.get_header_local_raw_with_sig(&hash)
+                fresh_reader!(self.env, |r| self.get_header_local_raw_with_sig(&r, &hash))
             }
         }
     }
@@ -687,21 +1056,24 @@ where
         match *hash.hash_type() {
             AnyDht::Entry => {
                 let hash = hash.into();
-                match self.get_element_local_raw_via_entry(&hash)? {
+                match fresh_reader!(self.env, |r| self
+                    .get_element_local_raw_via_entry(&r, &hash))?
+                {
                     Some(e) => Ok(Some(e)),
                     None => {
                         self.fetch_element_via_entry(hash.clone(), options).await?;
-                        self.get_element_local_raw_via_entry(&hash)
+                        fresh_reader!(self.env, |r| self
+                            .get_element_local_raw_via_entry(&r, &hash))
                     }
                 }
             }
             AnyDht::Header => {
                 let hash = hash.into();
-                match self.get_element_local_raw(&hash)? {
+                match fresh_reader!(self.env, |r| self.get_element_local_raw(&r, &hash))? {
                     Some(e) => Ok(Some(e)),
                     None => {
                         self.fetch_element_via_header(hash.clone(), options).await?;
-                        self.get_element_local_raw(&hash)
+                        fresh_reader!(self.env, |r| self.get_element_local_raw(&r, &hash))
                     }
                 }
             }
@@ -713,15 +1085,19 @@ where
     /// and returns what is in the cache.
     /// This gives you the latest possible picture of the current dht state.
     /// Data from your zome call is also added to the cache.
+    /// If the options ask for a minimum number of validations, data which
+    /// fewer authorities have vouched for is treated as missing.
     pub async fn dht_get(
         &mut self,
         hash: AnyDhtHash,
         options: GetOptions,
     ) -> CascadeResult<Option<Element>> {
-        match *hash.hash_type() {
-            AnyDht::Entry => self.dht_get_entry(hash.into(), options).await,
-            AnyDht::Header => self.dht_get_header(hash.into(), options).await,
-        }
+        let min_validations = options.min_validations;
+        let element = match *hash.hash_type() {
+            AnyDht::Entry => self.dht_get_entry(hash.into(), options).await?,
+            AnyDht::Header => self.dht_get_header(hash.into(), options).await?,
+        };
+        Ok(element.filter(|e| self.is_vouched_for(e.header_address(), min_validations)))
     }
 
     #[instrument(skip(self))]
@@ -743,6 +1119,17 @@ where
         }
     }
 
+    /// Fetch the latest metadata at a basis into the cache,
+    /// including the links on it if it's an entry.
+    pub async fn refresh_basis(&mut self, basis: AnyDhtHash) -> CascadeResult<()> {
+        self.fetch_meta(basis.clone(), Default::default()).await?;
+        if let AnyDht::Entry = *basis.hash_type() {
+            self.fetch_links(WireLinkMetaKey::Base(basis.into()), Default::default())
+                .await?;
+        }
+        Ok(())
+    }
+
     #[instrument(skip(self, key, options))]
     /// Gets an links from the cas or cache depending on it's metadata
     // The default behavior is to skip deleted or replaced entries.
@@ -752,17 +1139,47 @@ where
         key: &'link LinkMetaKey<'link>,
         options: GetLinksOptions,
     ) -> CascadeResult<Vec<Link>> {
+        let include_authored = options.include_authored;
         // Update the cache from the network
         self.fetch_links(key.into(), options).await?;
 
         fresh_reader!(self.env, |r| {
             // Meta Cache
             // Return any links from the meta cache that don't have removes.
-            Ok(self
+            let mut links: Vec<(HeaderHash, Link)> = self
                 .meta_cache
                 .get_live_links(&r, key)?
-                .map(|l| Ok(l.into_link()))
-                .collect()?)
+                .map(|l| Ok((l.link_add_hash.clone(), l.into_link())))
+                .collect()?;
+            if include_authored {
+                // Add the links we've authored which aren't removed,
+                // either by us or in the cache
+                let (link_adds, link_removes) = self.authored_links(key)?;
+                for (link_add_hash, link_add) in link_adds {
+                    let link_add_hash = link_add_hash.header_hash;
+                    let removed = self
+                        .meta_cache
+                        .get_link_removes_on_link_add(&r, link_add_hash.clone())?
+                        .next()?
+                        .is_some();
+                    if !removed && !links.iter().any(|(h, _)| *h == link_add_hash) {
+                        let link = LinkMetaVal::new(
+                            link_add_hash.clone(),
+                            link_add.target_address,
+                            link_add.timestamp.into(),
+                            link_add.zome_id,
+                            link_add.tag,
+                        );
+                        links.push((link_add_hash, link.into_link()));
+                    }
+                }
+                links.retain(|(h, _)| {
+                    !link_removes
+                        .iter()
+                        .any(|link_remove| link_remove.link_add_address == *h)
+                });
+            }
+            Ok(links.into_iter().map(|(_, link)| link).collect())
         })
     }
 
@@ -774,12 +1191,16 @@ where
         key: &'link LinkMetaKey<'link>,
         options: GetLinksOptions,
     ) -> CascadeResult<Vec<(CreateLink, Vec<DeleteLink>)>> {
+        let include_authored = options.include_authored;
         // Update the cache from the network
         self.fetch_links(key.into(), options).await?;
 
-        // Get the links and collect the CreateLink / DeleteLink hashes by time.
-        let links = fresh_reader!(self.env, |r| {
-            self.meta_cache
+        // Get the links and collect the CreateLink / DeleteLink hashes by time,
+        // then get their headers from the element stores in the same read,
+        // each link add followed by its link removes
+        let (links, elements) = fresh_reader!(self.env, |r| {
+            let links = self
+                .meta_cache
                 .get_links_all(&r, key)?
                 .map(|link_add| {
                     // Collect the link removes on this link add
@@ -795,24 +1216,188 @@ where
                     // Return all link removes with this link add
                     Ok((link_add, link_removes))
                 })
-                .collect::<BTreeMap<_, _>>()
+                .collect::<BTreeMap<_, _>>()?;
+            let hashes: Vec<HeaderHash> = links
+                .iter()
+                .flat_map(|(link_add, link_removes)| {
+                    std::iter::once(link_add).chain(link_removes.iter())
+                })
+                .map(|h| h.header_hash.clone())
+                .collect();
+            let elements = self.get_elements_local_raw(&r, &hashes)?;
+            CascadeResult::Ok((links, elements))
         })?;
-        // Get the headers from the element stores
-        let mut result: Vec<(CreateLink, _)> = Vec::with_capacity(links.len());
-        for (link_add, link_removes) in links {
-            if let Some(link_add) = self.get_element_local_raw(&link_add.header_hash)? {
-                let mut r: Vec<DeleteLink> = Vec::with_capacity(link_removes.len());
-                for link_remove in link_removes {
-                    if let Some(link_remove) =
-                        self.get_element_local_raw(&link_remove.header_hash)?
+        let mut elements = elements.into_iter();
+        let mut result: BTreeMap<TimedHeaderHash, (CreateLink, Vec<DeleteLink>)> = BTreeMap::new();
+        for (link_add_hash, link_removes) in links {
+            let link_add = elements.next().flatten();
+            let mut r: Vec<DeleteLink> = Vec::with_capacity(link_removes.len());
+            for link_remove in elements.by_ref().take(link_removes.len()).flatten() {
+                r.push(link_remove.try_into()?);
+            }
+            if let Some(link_add) = link_add {
+                result.insert(link_add_hash, (link_add.try_into()?, r));
+            }
+        }
+        if include_authored {
+            // Add the links and link removes we've authored
+            let (link_adds, link_removes) = self.authored_links(key)?;
+            for (link_add_hash, link_add) in link_adds {
+                result
+                    .entry(link_add_hash)
+                    .or_insert_with(|| (link_add, Vec::new()));
+            }
+            for (link_add_hash, (_, r)) in result.iter_mut() {
+                for link_remove in &link_removes {
+                    if link_remove.link_add_address == link_add_hash.header_hash
+                        && !r.contains(link_remove)
                     {
-                        r.push(link_remove.try_into()?);
+                        r.push(link_remove.clone());
                     }
                 }
-                result.push((link_add.try_into()?, r));
             }
         }
-        Ok(result)
+        Ok(result.into_iter().map(|(_, details)| details).collect())
+    }
+
+    /// The elements committed to the agent's scratch space in this zome
+    /// call, newest first
+    fn scratch(&self) -> CascadeResult<Vec<Element>> {
+        match self.authored {
+            Some(source_chain) => Ok(source_chain.scratch_elements()?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The oldest element committed in this zome call for an entry,
+    /// which hasn't since been deleted in the same call
+    fn get_element_scratch_via_entry(&self, hash: &EntryHash) -> CascadeResult<Option<Element>> {
+        let scratch = self.scratch()?;
+        Ok(scratch
+            .iter()
+            .rev()
+            .find(|el| {
+                el.header().entry_hash() == Some(hash)
+                    && scratch_deletes_on_header(&scratch, el.header_address()).is_empty()
+            })
+            .cloned())
+    }
+
+    /// Add the headers, deletes and updates committed in this zome call to
+    /// an entry's details. If the entry was only committed in this call
+    /// there are no details yet, so they're made from the scratch space.
+    fn merge_scratch_entry_details(
+        &self,
+        hash: &EntryHash,
+        details: Option<EntryDetails>,
+    ) -> CascadeResult<Option<EntryDetails>> {
+        let scratch = self.scratch()?;
+        let mut details = match details {
+            Some(details) => details,
+            None => {
+                let entry = scratch.iter().find_map(|el| match el.entry().as_option() {
+                    Some(entry) if el.header().entry_hash() == Some(hash) => Some(entry.clone()),
+                    _ => None,
+                });
+                match entry {
+                    Some(entry) => EntryDetails {
+                        entry,
+                        headers: Vec::new(),
+                        deletes: Vec::new(),
+                        updates: Vec::new(),
+                        entry_dht_status: EntryDhtStatus::Dead,
+                        references: Vec::new(),
+                    },
+                    None => return Ok(None),
+                }
+            }
+        };
+        // Oldest first, like the details from the cache
+        for el in scratch.iter().rev() {
+            match el.header() {
+                Header::Delete(delete)
+                    if &delete.deletes_entry_address == hash
+                        && !details.deletes.contains(delete) =>
+                {
+                    details.deletes.push(delete.clone())
+                }
+                Header::Update(update)
+                    if &update.original_entry_address == hash
+                        && !details.updates.contains(update) =>
+                {
+                    details.updates.push(update.clone())
+                }
+                header
+                    if header.entry_hash() == Some(hash) && !details.headers.contains(header) =>
+                {
+                    if scratch_deletes_on_header(&scratch, el.header_address()).is_empty() {
+                        details.entry_dht_status = EntryDhtStatus::Live;
+                    }
+                    details.headers.push(header.clone())
+                }
+                _ => (),
+            }
+        }
+        Ok(Some(details))
+    }
+
+    /// The links matching a key which this agent has authored, and every
+    /// link remove it has authored. They're read from the source chain, so
+    /// they include anything committed earlier in the same zome call.
+    fn authored_links(
+        &self,
+        key: &LinkMetaKey,
+    ) -> CascadeResult<(Vec<(TimedHeaderHash, CreateLink)>, Vec<DeleteLink>)> {
+        let mut link_adds = Vec::new();
+        let mut link_removes = Vec::new();
+        if let Some(source_chain) = self.authored {
+            let mut iter = source_chain.iter_back();
+            while let Some(shh) = iter.next()? {
+                let (header, header_hash) = shh.into_header_and_signature().0.into_inner();
+                match header {
+                    Header::CreateLink(link_add) if key.matches(&link_add, &header_hash) => {
+                        let link_add_hash = TimedHeaderHash {
+                            timestamp: link_add.timestamp.into(),
+                            header_hash,
+                        };
+                        link_adds.push((link_add_hash, link_add));
+                    }
+                    Header::DeleteLink(link_remove) => link_removes.push(link_remove),
+                    _ => (),
+                }
+            }
+        }
+        Ok((link_adds, link_removes))
+    }
+}
+
+/// The deletes of a header among elements committed in a zome call
+fn scratch_deletes_on_header(scratch: &[Element], hash: &HeaderHash) -> Vec<Delete> {
+    scratch
+        .iter()
+        .filter_map(|el| match el.header() {
+            Header::Delete(delete) if &delete.deletes_address == hash => Some(delete.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The hashes whose lookups found nothing
+fn missing_hashes<T>(hashes: &[HeaderHash], found: &[Option<T>]) -> Vec<HeaderHash> {
+    hashes
+        .iter()
+        .zip(found)
+        .filter(|(_, found)| found.is_none())
+        .map(|(hash, _)| hash.clone())
+        .collect()
+}
+
+/// Fill the gaps in one batch lookup with the results of another,
+/// made for just the hashes which were missing from the first
+fn fill_missing<T>(found: &mut [Option<T>], fallback: Vec<Option<T>>) {
+    let mut fallback = fallback.into_iter();
+    for slot in found.iter_mut().filter(|slot| slot.is_none()) {
+        *slot = fallback.next().flatten();
     }
 }
 
@@ -2,6 +2,7 @@ use crate::core::{
     workflow::produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError, SourceChainError,
 };
 use holo_hash::AnyDhtHash;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_p2p::HolochainP2pError;
 use holochain_serialized_bytes::SerializedBytesError;
 use holochain_state::error::DatabaseError;
@@ -39,4 +40,21 @@ pub enum CascadeError {
     WrongHeaderError(#[from] WrongHeaderError),
 }
 
+impl HasErrorKind for CascadeError {
+    fn error_kind(&self) -> ErrorKind {
+        use CascadeError::*;
+        match self {
+            DatabaseError(e) => e.error_kind(),
+            ElementGroupError(_) | DhtOpError(_) => ErrorKind::Internal,
+            DhtOpConvertError(e) => e.error_kind(),
+            // An authority sent us something which doesn't check out
+            InvalidResponse(_) => ErrorKind::Validation,
+            SourceChainError(e) => e.error_kind(),
+            NetworkError(e) => e.error_kind(),
+            SerializedBytesError(_) => ErrorKind::Serialization,
+            WrongHeaderError(_) => ErrorKind::InvalidInput,
+        }
+    }
+}
+
 pub type CascadeResult<T> = Result<T, CascadeError>;
@@ -1,13 +1,20 @@
+use super::verify::{peer_penalty, rejection_stats, Rejection};
+use super::Cascade;
 use crate::{
     conductor::{dna_store::MockDnaStore, interface::websocket::test::setup_app},
     core::{
         state::{
             element_buf::ElementBuf,
-            metadata::{MetadataBuf, MetadataBufT},
+            metadata::{LinkMetaKey, MetadataBuf, MetadataBufT},
         },
-        workflow::{integrate_dht_ops_workflow::integrate_to_cache, CallZomeWorkspace},
+        workflow::{
+            fake_genesis, integrate_dht_ops_workflow::integrate_to_cache, CallZomeWorkspace,
+        },
+    },
+    test_utils::{
+        byzantine::{Byzantine, ByzantineAuthority},
+        fake_unique_element, test_network,
     },
-    test_utils::test_network,
 };
 use ::fixt::prelude::*;
 use fallible_iterator::FallibleIterator;
@@ -15,9 +22,11 @@ use futures::future::{Either, FutureExt};
 use ghost_actor::GhostControlSender;
 use hdk3::prelude::EntryVisibility;
 use holo_hash::{
+    fixt::HeaderHashFixturator,
     hash_type::{self, AnyDht},
     AnyDhtHash, EntryHash, HasHash, HeaderHash,
 };
+use holochain_error_kind::ErrorKind;
 use holochain_p2p::{
     actor::{GetLinksOptions, GetMetaOptions, GetOptions},
     HolochainP2pCell, HolochainP2pRef,
@@ -44,7 +53,7 @@ use holochain_wasm_test_utils::TestWasm;
 use holochain_zome_types::{
     element::SignedHeaderHashed,
     header::*,
-    link::Link,
+    link::{Link, LinkTag},
     metadata::{Details, EntryDhtStatus},
 };
 use maplit::btreeset;
@@ -213,6 +222,8 @@ async fn get_from_another_agent() {
         race_timeout_ms: None,
         follow_redirects: false,
         all_live_headers_with_metadata: false,
+        collapsed: false,
+        min_validations: 0,
     };
 
     // Bob store element
@@ -381,7 +392,7 @@ async fn get_links_from_another_agent() {
     )
     .await;
 
-    let link_options = GetLinksOptions { timeout_ms: None };
+    let link_options = GetLinksOptions::default();
 
     // Bob store links
     let base = Post("Bananas are good for you".into());
@@ -494,6 +505,302 @@ async fn get_links_from_another_agent() {
     shutdown.await.unwrap();
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn rejects_forged_elements() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let keystore = holochain_state::test_utils::test_keystore();
+
+    for (behaviour, rejection) in vec![
+        (Byzantine::ForgedSignature, Rejection::InvalidSignature),
+        (Byzantine::WrongEntry, Rejection::EntryHashMismatch),
+    ] {
+        let (shh, entry) =
+            fake_unique_element(&keystore, fake_agent_pubkey_1(), EntryVisibility::Public)
+                .await
+                .unwrap();
+        let element = Element::new(shh, Some(entry.into_content()));
+        let hash = element.header_address().clone();
+        let authority = ByzantineAuthority::new(behaviour).with_element(element);
+        let peer = authority.agent().clone();
+        let rejected_before = rejection_stats()
+            .by_reason
+            .get(&rejection)
+            .copied()
+            .unwrap_or_default();
+
+        let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+        let (network, shutdown) = authority.run().await;
+        workspace
+            .cascade(network)
+            .fetch_element_via_header(hash.clone(), Default::default())
+            .await
+            .unwrap();
+
+        assert_eq!(workspace.cache_cas.get_element(&hash).unwrap(), None);
+        assert_eq!(peer_penalty(&peer), 1);
+        assert!(rejection_stats().by_reason[&rejection] > rejected_before);
+
+        shutdown.clean().await;
+    }
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn remembers_not_held_but_not_failures() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let hash = fixt!(HeaderHash);
+    let requested: AnyDhtHash = hash.clone().into();
+
+    // An authority which fails doesn't tell us anything
+    let authority = ByzantineAuthority::new(Byzantine::Failing(ErrorKind::Storage));
+    let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+    let (network, shutdown) = authority.run().await;
+    let mut cascade = workspace.cascade(network);
+    cascade
+        .fetch_element_via_header(hash.clone(), Default::default())
+        .await
+        .unwrap();
+    assert!(!cascade.not_held.contains(&requested));
+    shutdown.clean().await;
+
+    // An authority which answers that it doesn't hold the element does
+    let authority = ByzantineAuthority::new(Byzantine::Honest);
+    let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+    let (network, shutdown) = authority.run().await;
+    let mut cascade = workspace.cascade(network);
+    cascade
+        .fetch_element_via_header(hash.clone(), Default::default())
+        .await
+        .unwrap();
+    assert!(cascade.not_held.contains(&requested));
+    shutdown.clean().await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn get_links_includes_authored() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let base = fixt!(EntryHash);
+    let kept = fixt!(EntryHash);
+    let removed = fixt!(EntryHash);
+
+    // Link twice from the base and remove the second link, without publishing
+    let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+    fake_genesis(&mut workspace.source_chain).await.unwrap();
+    for target in vec![kept.clone(), removed.clone()] {
+        let header_builder =
+            builder::CreateLink::new(base.clone(), target, 0.into(), None, LinkTag::new("a"));
+        workspace
+            .source_chain
+            .put(header_builder, None)
+            .await
+            .unwrap();
+    }
+    let link_add_address = workspace.source_chain.chain_head().unwrap().clone();
+    let header_builder = builder::DeleteLink {
+        link_add_address,
+        base_address: base.clone(),
+    };
+    workspace
+        .source_chain
+        .put(header_builder, None)
+        .await
+        .unwrap();
+
+    // The authority doesn't know about any of them yet
+    let key = LinkMetaKey::Base(&base);
+    let (network, shutdown) = ByzantineAuthority::new(Byzantine::Honest).run().await;
+    let mut cascade = workspace.cascade(network);
+    let links = cascade
+        .dht_get_links(&key, GetLinksOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        links.into_iter().map(|l| l.target).collect::<Vec<_>>(),
+        vec![kept]
+    );
+    let details = cascade
+        .get_link_details(&key, GetLinksOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(details.len(), 2);
+    assert_eq!(details.iter().filter(|(_, r)| r.is_empty()).count(), 1);
+
+    // Unless we opt out
+    let options = GetLinksOptions {
+        include_authored: false,
+        ..Default::default()
+    };
+    let links = cascade.dht_get_links(&key, options).await.unwrap();
+    assert!(links.is_empty());
+    shutdown.clean().await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn get_includes_scratch() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let entry = EntryHashed::from_content_sync(EntryFixturator::new(AppEntry).next().unwrap());
+    let entry_hash = entry.as_hash().clone();
+    let entry_type = EntryType::App(AppEntryType::new(
+        0.into(),
+        0.into(),
+        EntryVisibility::Public,
+    ));
+
+    // Create an entry without publishing it or integrating it to the cache
+    let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+    fake_genesis(&mut workspace.source_chain).await.unwrap();
+    let header_builder = builder::Create {
+        entry_type,
+        entry_hash: entry_hash.clone(),
+    };
+    let header_hash = workspace
+        .source_chain
+        .put(header_builder, Some(entry.into_content()))
+        .await
+        .unwrap();
+
+    let (network, shutdown) = ByzantineAuthority::new(Byzantine::Honest).run().await;
+    {
+        let mut cascade = workspace.cascade(network.clone());
+        let element = cascade
+            .dht_get(header_hash.clone().into(), Default::default())
+            .await
+            .unwrap()
+            .expect("Committed in this call");
+        assert_eq!(element.header_address(), &header_hash);
+        let element = cascade
+            .dht_get(entry_hash.clone().into(), Default::default())
+            .await
+            .unwrap()
+            .expect("Committed in this call");
+        assert_eq!(element.header_address(), &header_hash);
+        let details = cascade
+            .get_details(entry_hash.clone().into(), Default::default())
+            .await
+            .unwrap();
+        let details = unwrap_to!(details.unwrap() => Details::Entry).clone();
+        assert_eq!(details.headers.len(), 1);
+        assert_eq!(details.entry_dht_status, EntryDhtStatus::Live);
+    }
+
+    // Delete it in the same call
+    let header_builder = builder::Delete {
+        deletes_address: header_hash.clone(),
+        deletes_entry_address: entry_hash.clone(),
+    };
+    workspace
+        .source_chain
+        .put(header_builder, None)
+        .await
+        .unwrap();
+
+    let mut cascade = workspace.cascade(network);
+    assert!(cascade
+        .dht_get(header_hash.clone().into(), Default::default())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(cascade
+        .dht_get(entry_hash.clone().into(), Default::default())
+        .await
+        .unwrap()
+        .is_none());
+    let details = cascade
+        .get_details(header_hash.into(), Default::default())
+        .await
+        .unwrap();
+    let details = unwrap_to!(details.unwrap() => Details::Element).clone();
+    assert_eq!(details.deletes.len(), 1);
+    let details = cascade
+        .get_details(entry_hash.into(), Default::default())
+        .await
+        .unwrap();
+    let details = unwrap_to!(details.unwrap() => Details::Entry).clone();
+    assert_eq!(details.deletes.len(), 1);
+    assert_eq!(details.entry_dht_status, EntryDhtStatus::Dead);
+    shutdown.clean().await;
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn local_reads_take_elements_and_metadata_from_one_snapshot() {
+    observability::test_run().ok();
+    let test_env = test_cell_env();
+    let env = test_env.env();
+    let env_ref = env.guard();
+    let keystore = env.keystore().clone();
+    let (element, entry) =
+        fake_unique_element(&keystore, fake_agent_pubkey_1(), EntryVisibility::Public)
+            .await
+            .unwrap();
+    let header_hash = element.header_address().clone();
+    let header = element.header().clone();
+
+    // The element lands before the metadata which says it's valid
+    env_ref
+        .with_commit(|writer| {
+            let mut element_vault = ElementBuf::vault(env.clone().into(), true)?;
+            element_vault.put(element, Some(entry))?;
+            element_vault.flush_to_txn(writer)
+        })
+        .unwrap();
+    let before_meta = env_ref.reader().unwrap();
+    env_ref
+        .with_commit(|writer| {
+            let mut meta_vault = MetadataBuf::vault(env.clone().into())?;
+            meta_vault.register_element_header(&header)?;
+            meta_vault.flush_to_txn(writer)
+        })
+        .unwrap();
+
+    let element_vault = ElementBuf::vault(env.clone().into(), true).unwrap();
+    let meta_vault = MetadataBuf::vault(env.clone().into()).unwrap();
+    let mut element_cache = ElementBuf::cache(env.clone().into()).unwrap();
+    let mut meta_cache = MetadataBuf::cache(env.clone().into()).unwrap();
+    let (_network, _recv, cell_network) = test_network(None, None).await;
+    let cascade = Cascade::new(
+        env.clone().into(),
+        &element_vault,
+        &meta_vault,
+        &mut element_cache,
+        &mut meta_cache,
+        cell_network,
+    );
+
+    // Both are in the stores now, but the earlier snapshot only has the
+    // element, so it isn't known to be valid
+    let hashes = [header_hash.clone()];
+    let elements = cascade
+        .get_elements_local_raw(&before_meta, &hashes)
+        .unwrap();
+    assert!(elements[0].is_none());
+    let headers = cascade
+        .get_headers_local_raw(&before_meta, &hashes)
+        .unwrap();
+    assert!(headers[0].is_none());
+
+    // A later snapshot has both
+    let after_meta = env_ref.reader().unwrap();
+    let elements = cascade
+        .get_elements_local_raw(&after_meta, &hashes)
+        .unwrap();
+    assert_eq!(
+        elements[0].as_ref().map(|el| el.header_address()),
+        Some(&header_hash)
+    );
+    let headers = cascade.get_headers_local_raw(&after_meta, &hashes).unwrap();
+    assert_eq!(
+        headers[0].as_ref().map(|h| h.header_address()),
+        Some(&header_hash)
+    );
+}
+
 struct Shutdown {
     handle: JoinHandle<()>,
     kill: oneshot::Sender<()>,
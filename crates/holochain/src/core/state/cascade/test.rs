@@ -109,7 +109,7 @@ async fn live_local_return() -> SourceChainResult<()> {
         ..
     } = setup_env(env.clone().into())?;
     source_chain
-        .put_raw(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
+        .put_raw_unchecked(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
         .await?;
     let address = jimbo_entry.as_hash();
 
@@ -156,7 +156,7 @@ async fn dead_local_none() -> SourceChainResult<()> {
         ..
     } = setup_env(env.clone().into())?;
     source_chain
-        .put_raw(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
+        .put_raw_unchecked(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
         .await?;
     let address = jimbo_entry.as_hash();
 
@@ -289,10 +289,10 @@ async fn links_local_return() -> SourceChainResult<()> {
         mut mock_meta_cache,
     } = setup_env(env.clone().into())?;
     source_chain
-        .put_raw(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
+        .put_raw_unchecked(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
         .await?;
     source_chain
-        .put_raw(jessy_header.clone(), Some(jessy_entry.as_content().clone()))
+        .put_raw_unchecked(jessy_header.clone(), Some(jessy_entry.as_content().clone()))
         .await?;
     let base = jimbo_entry.as_hash().clone();
     let target = jessy_entry.as_hash().clone();
@@ -364,10 +364,10 @@ async fn links_cache_return() -> SourceChainResult<()> {
         mut mock_meta_cache,
     } = setup_env(env.clone().into())?;
     source_chain
-        .put_raw(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
+        .put_raw_unchecked(jimbo_header.clone(), Some(jimbo_entry.as_content().clone()))
         .await?;
     source_chain
-        .put_raw(jessy_header.clone(), Some(jessy_entry.as_content().clone()))
+        .put_raw_unchecked(jessy_header.clone(), Some(jessy_entry.as_content().clone()))
         .await?;
     let base = jimbo_entry.as_hash().clone();
     let target = jessy_entry.as_hash().clone();
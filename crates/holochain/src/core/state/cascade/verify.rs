@@ -0,0 +1,119 @@
+//! Verification of data received from authorities.
+//!
+//! Nothing an authority sends is cached until it has been checked against
+//! what was asked for, and its signatures and hashes have been checked
+//! against its content. Each rejected response is counted, and the peer who
+//! sent it is penalized.
+
+use holo_hash::{AgentPubKey, AnyDhtHash, EntryHash};
+use holochain_keystore::AgentPubKeyExt;
+use holochain_types::element::{verify_batch, Element, ElementGroup, SignedHeaderHashed};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::*;
+
+lazy_static::lazy_static! {
+    static ref REJECTIONS: Mutex<RejectionStats> = Mutex::new(RejectionStats::default());
+}
+
+/// Why a response from an authority was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rejection {
+    /// The response was for something other than what was requested
+    UnexpectedData,
+    /// A header's signature isn't valid for its author
+    InvalidSignature,
+    /// An entry doesn't match the entry hash in its header
+    EntryHashMismatch,
+}
+
+/// Rejected responses from authorities, since the conductor started
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RejectionStats {
+    /// The number of responses rejected for each reason
+    pub by_reason: HashMap<Rejection, u64>,
+    /// The number of responses rejected from each peer
+    pub penalties: HashMap<AgentPubKey, u64>,
+}
+
+/// Check a header's signature is valid for its author
+pub async fn verify_signed_header(shh: &SignedHeaderHashed) -> Result<(), Rejection> {
+    let header = shh.header();
+    // Malformed signatures are errors rather than failed verifications,
+    // but are just as much the authority's fault
+    match header
+        .author()
+        .verify_signature(shh.signature(), header)
+        .await
+    {
+        Ok(true) => Ok(()),
+        _ => Err(Rejection::InvalidSignature),
+    }
+}
+
+/// Check an element's header signature, and that its entry (if any) is the
+/// one named in the header.
+/// The header hash needn't be checked as it's always computed locally from
+/// the header received.
+pub async fn verify_element(element: &Element) -> Result<(), Rejection> {
+    verify_signed_header(element.signed_header()).await?;
+    if let (Some((entry_hash, _)), Some(entry)) =
+        (element.header().entry_data(), element.entry().as_option())
+    {
+        if *entry_hash != EntryHash::with_data_sync(entry) {
+            return Err(Rejection::EntryHashMismatch);
+        }
+    }
+    Ok(())
+}
+
+/// Check an element group is for the requested entry, and each header's
+/// signature. The signatures are verified as a batch.
+/// Headers are rebuilt from the wire with the hash of the entry received, so
+/// an entry swapped in transit shows up here as invalid signatures.
+pub async fn verify_element_group(
+    elements: &ElementGroup<'_>,
+    requested: &EntryHash,
+) -> Result<(), Rejection> {
+    if elements.entry_hash() != requested {
+        return Err(Rejection::EntryHashMismatch);
+    }
+    if verify_batch(elements.signed_headers())
+        .await
+        .into_iter()
+        .all(|valid| valid)
+    {
+        Ok(())
+    } else {
+        Err(Rejection::InvalidSignature)
+    }
+}
+
+/// Count a rejected response and penalize the peer who sent it
+pub fn reject(peer: &AgentPubKey, requested: &AnyDhtHash, reason: Rejection) {
+    warn!(
+        ?peer,
+        ?requested,
+        ?reason,
+        "Rejected a response from an authority"
+    );
+    let mut stats = REJECTIONS.lock().expect("Rejection stats poisoned");
+    *stats.by_reason.entry(reason).or_default() += 1;
+    *stats.penalties.entry(peer.clone()).or_default() += 1;
+}
+
+/// A snapshot of all rejected responses so far
+pub fn rejection_stats() -> RejectionStats {
+    REJECTIONS.lock().expect("Rejection stats poisoned").clone()
+}
+
+/// How many responses have been rejected from this peer
+pub fn peer_penalty(peer: &AgentPubKey) -> u64 {
+    REJECTIONS
+        .lock()
+        .expect("Rejection stats poisoned")
+        .penalties
+        .get(peer)
+        .copied()
+        .unwrap_or_default()
+}
@@ -82,6 +82,12 @@ impl ChainSequenceBuf {
         self.current_head.as_ref()
     }
 
+    /// The chain head as it was when this buffer was created, before
+    /// any headers were put in the scratch space
+    pub fn persisted_head(&self) -> Option<&HeaderHash> {
+        self.persisted_head.as_ref()
+    }
+
     /// empty if len is 0
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -99,6 +105,25 @@ impl ChainSequenceBuf {
             .map(|seq_item| seq_item.map(|si| si.header_address))
     }
 
+    /// The persisted headers from an index onwards, in chain order with their
+    /// indices. Headers still in the scratch space are left out.
+    pub fn persisted_from<R: Readable>(
+        &self,
+        r: &R,
+        from: u32,
+    ) -> DatabaseResult<Vec<(u32, HeaderHash)>> {
+        self.buf
+            .store()
+            .iter_from(r, from.into())?
+            .map(|(key, item)| {
+                Ok((
+                    IntKey::from_key_bytes_or_friendly_panic(key).into(),
+                    item.header_address,
+                ))
+            })
+            .collect()
+    }
+
     /// Add a header to the chain, setting all other values automatically.
     /// This is intentionally the only way to modify this database.
     #[instrument(skip(self))]
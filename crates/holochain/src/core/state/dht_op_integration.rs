@@ -1,19 +1,26 @@
 //! Various types for the databases involved in the DhtOp integration workflow
 
+use super::{element_buf::ElementBuf, metadata::BytesKey};
 use fallible_iterator::FallibleIterator;
 use holo_hash::*;
 use holochain_p2p::dht_arc::DhtArc;
 use holochain_serialized_bytes::prelude::*;
 use holochain_state::{
-    buffer::KvBufFresh,
-    db::INTEGRATED_DHT_OPS,
+    buffer::{KvBufFresh, KvBufUsed},
+    db::{INTEGRATED_DHT_OPS, INTEGRATED_DHT_OPS_ORDER},
+    env::EnvironmentWrite,
     error::{DatabaseError, DatabaseResult},
-    prelude::{BufferedStore, EnvironmentRead, GetDb, Readable},
+    fresh_reader,
+    prelude::{BufferedStore, EnvironmentRead, GetDb, Readable, WriteManager},
+};
+use holochain_types::{
+    dht_op::DhtOpLight, timestamp::TimestampKey, validate::ValidationStatus, Timestamp,
 };
-use holochain_types::{dht_op::DhtOpLight, validate::ValidationStatus, Timestamp};
 
 /// Database type for AuthoredDhtOps
-/// Buffer for accessing [DhtOp]s that you authored and finding the amount of validation receipts
+/// Buffer for accessing [DhtOp]s that you authored.
+/// How far each has got with publishing is in the
+/// [PublishProgress](super::publish_progress::PublishProgress) database.
 pub type AuthoredDhtOpsStore = KvBufFresh<AuthoredDhtOpsKey, AuthoredDhtOpsValue>;
 
 /// The key type for the AuthoredDhtOps db: a DhtOpHash
@@ -24,20 +31,12 @@ pub type AuthoredDhtOpsKey = DhtOpHash;
 pub struct AuthoredDhtOpsValue {
     /// Signatures and hashes of the op
     pub op: DhtOpLight,
-    /// Validation receipts received
-    pub receipt_count: u32,
-    /// Time last published, None if never published
-    pub last_publish_time: Option<Timestamp>,
 }
 
 impl AuthoredDhtOpsValue {
-    /// Create a new value from a DhtOpLight with no receipts and no timestamp
+    /// Create a new value from a DhtOpLight
     pub fn from_light(op: DhtOpLight) -> Self {
-        Self {
-            op,
-            receipt_count: 0,
-            last_publish_time: None,
-        }
+        Self { op }
     }
 }
 
@@ -48,9 +47,14 @@ pub type IntegrationLimboStore = KvBufFresh<IntegrationLimboKey, IntegrationLimb
 /// [DhtOp]s that have already been integrated
 pub type IntegratedDhtOpsStore = KvBufFresh<DhtOpHash, IntegratedDhtOpsValue>;
 
+/// Database type for IntegratedDhtOpsOrder: the hash of each integrated
+/// [DhtOp] keyed by its [IntegratedOpOrder]
+pub type IntegratedDhtOpsOrderStore = KvBufFresh<BytesKey, DhtOpHash>;
+
 /// Buffer that adds query logic to the IntegratedDhtOpsStore
 pub struct IntegratedDhtOpsBuf {
     store: IntegratedDhtOpsStore,
+    order: IntegratedDhtOpsOrderStore,
 }
 
 impl std::ops::Deref for IntegratedDhtOpsBuf {
@@ -72,7 +76,8 @@ impl BufferedStore for IntegratedDhtOpsBuf {
         &mut self,
         writer: &mut holochain_state::prelude::Writer,
     ) -> Result<(), Self::Error> {
-        self.store.flush_to_txn_ref(writer)
+        self.store.flush_to_txn_ref(writer)?;
+        self.order.flush_to_txn_ref(writer)
     }
 }
 
@@ -103,8 +108,10 @@ impl IntegratedDhtOpsBuf {
     /// Create a new buffer for the IntegratedDhtOpsStore
     pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
         let db = env.get_db(&*INTEGRATED_DHT_OPS).unwrap();
+        let order = env.get_db(&*INTEGRATED_DHT_OPS_ORDER)?;
         Ok(Self {
-            store: IntegratedDhtOpsStore::new(env, db),
+            store: IntegratedDhtOpsStore::new(env.clone(), db),
+            order: IntegratedDhtOpsOrderStore::new(env, order),
         })
     }
 
@@ -113,6 +120,21 @@ impl IntegratedDhtOpsBuf {
         self.store.get(op_hash)
     }
 
+    /// Add an integrated op, indexed by when its header was authored
+    pub fn integrate(
+        &mut self,
+        op_hash: DhtOpHash,
+        value: IntegratedDhtOpsValue,
+        header_timestamp: Option<Timestamp>,
+    ) -> DatabaseResult<()> {
+        let order = IntegratedOpOrder {
+            header_timestamp,
+            op_hash: op_hash.clone(),
+        };
+        self.order.put(order.into(), op_hash.clone())?;
+        self.store.put(op_hash, value)
+    }
+
     /// Get ops that match optional queries:
     /// - from a time (Inclusive)
     /// - to a time (Exclusive)
@@ -152,11 +174,114 @@ impl IntegratedDhtOpsBuf {
                 }),
         ))
     }
+
+    /// The same as [IntegratedDhtOpsBuf::query] but in the order of
+    /// [IntegratedOpOrder]. The store itself is keyed by hash so a plain
+    /// query comes back in hash order, this walks the order index instead.
+    pub fn query_ordered<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        from: Option<Timestamp>,
+        to: Option<Timestamp>,
+        dht_arc: Option<DhtArc>,
+    ) -> DatabaseResult<
+        Box<
+            dyn FallibleIterator<Item = (DhtOpHash, IntegratedDhtOpsValue), Error = DatabaseError>
+                + 'r,
+        >,
+    > {
+        Ok(Box::new(
+            self.order
+                .iter(r)?
+                .filter_map(move |(_, hash)| {
+                    Ok(KvBufUsed::get(&*self.store, r, &hash)?.map(|v| (hash, v)))
+                })
+                .filter(move |(_, v)| {
+                    Ok(from.map(|time| v.when_integrated >= time).unwrap_or(true)
+                        && to.map(|time| v.when_integrated < time).unwrap_or(true)
+                        && dht_arc
+                            .map(|dht_arc| dht_arc.contains(v.op.dht_basis().get_loc()))
+                            .unwrap_or(true))
+                }),
+        ))
+    }
+
+    #[cfg(test)]
+    pub fn clear_all(
+        &mut self,
+        writer: &mut holochain_state::prelude::Writer,
+    ) -> DatabaseResult<()> {
+        self.store.clear_all(writer)?;
+        self.order.clear_all(writer)
+    }
+}
+
+/// Fill in the order index of an environment from before it existed.
+/// If the index is empty every integrated op is indexed, with its header
+/// looked up in the vault, then in the rejected store, for its timestamp.
+/// Returns how many ops were indexed.
+pub fn index_integrated_ops(env: &EnvironmentWrite) -> DatabaseResult<usize> {
+    let mut buf = IntegratedDhtOpsBuf::new(env.clone().into())?;
+    let vault = ElementBuf::vault(env.clone().into(), false)?;
+    let rejected = ElementBuf::rejected(env.clone().into())?;
+    let unordered = fresh_reader!(env, |r| {
+        if buf.order.iter(&r)?.next()?.is_some() {
+            return DatabaseResult::Ok(Vec::new());
+        }
+        buf.store
+            .iter(&r)?
+            .map(|(k, v)| {
+                let header_hash = v.op.header_hash();
+                let header = match vault.get_header_with_reader(&r, header_hash)? {
+                    Some(header) => Some(header),
+                    None => rejected.get_header_with_reader(&r, header_hash)?,
+                };
+                Ok(IntegratedOpOrder {
+                    header_timestamp: header.map(|h| h.header().timestamp().into()),
+                    op_hash: DhtOpHash::with_pre_hashed(k.to_vec()),
+                })
+            })
+            .collect::<Vec<_>>()
+    })?;
+    let count = unordered.len();
+    for order in unordered {
+        let op_hash = order.op_hash.clone();
+        buf.order.put(order.into(), op_hash)?;
+    }
+    if count > 0 {
+        env.guard()
+            .with_commit(|writer| buf.order.flush_to_txn_ref(writer))?;
+    }
+    Ok(count)
+}
+
+/// The order [IntegratedDhtOpsBuf::query_ordered] gives ops out in:
+/// by when their header was authored, then by op hash.
+/// Both come from the op itself, unlike when it was integrated, so every
+/// node holding the same ops puts them in the same order.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IntegratedOpOrder {
+    /// The timestamp of the op's header.
+    /// None if the header isn't held, which sorts first.
+    pub header_timestamp: Option<Timestamp>,
+    /// The op's hash, for ops whose headers were authored at the same time
+    pub op_hash: DhtOpHash,
+}
+
+impl From<IntegratedOpOrder> for BytesKey {
+    fn from(order: IntegratedOpOrder) -> Self {
+        let mut key = match order.header_timestamp {
+            None => vec![0],
+            Some(timestamp) => [&[1], TimestampKey::from(timestamp).as_ref()].concat(),
+        };
+        key.extend_from_slice(order.op_hash.as_ref());
+        BytesKey(key)
+    }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::fixt::AnyDhtHashFixturator;
+    use crate::fixt::{AnyDhtHashFixturator, SignatureFixturator};
     use ::fixt::prelude::*;
     use chrono::{Duration, Utc};
     use holo_hash::fixt::{DhtOpHashFixturator, HeaderHashFixturator};
@@ -165,6 +290,8 @@ mod tests {
         buffer::BufferedStore,
         env::{ReadManager, WriteManager},
     };
+    use holochain_types::{element::SignedHeaderHashed, fixt::CreateFixturator, HeaderHashed};
+    use holochain_zome_types::Header;
     use pretty_assertions::assert_eq;
 
     #[tokio::test(threaded_scheduler)]
@@ -286,4 +413,97 @@ mod tests {
             assert_eq!(r.len(), 3);
         }
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn nodes_holding_the_same_ops_order_them_the_same() {
+        let now = Utc::now();
+        // Pairs of headers authored at the same time,
+        // so some ops are ordered by their hash
+        let mut creates = CreateFixturator::new(Unpredictable);
+        let headers: Vec<_> = (0..6)
+            .map(|i| {
+                let mut create = creates.next().unwrap();
+                create.timestamp = (now - Duration::hours(i / 2)).into();
+                SignedHeaderHashed::with_presigned(
+                    HeaderHashed::from_content_sync(Header::Create(create)),
+                    fixt!(Signature),
+                )
+            })
+            .collect();
+        let mut op_hashes = DhtOpHashFixturator::new(Unpredictable);
+        let mut basis = AnyDhtHashFixturator::new(Unpredictable);
+        let ops: Vec<_> = headers
+            .iter()
+            .map(|header| {
+                (
+                    op_hashes.next().unwrap(),
+                    DhtOpLight::RegisterAgentActivity(
+                        header.as_hash().clone(),
+                        basis.next().unwrap(),
+                    ),
+                )
+            })
+            .collect();
+
+        // Each node integrates the ops in a different order.
+        // The second has them integrated before the order index existed.
+        let mut orders = Vec::new();
+        for (backfill, ops) in vec![
+            (false, ops.clone()),
+            (true, ops.iter().rev().cloned().collect()),
+        ] {
+            let test_env = test_cell_env();
+            let env = test_env.env();
+            let env_ref = env.guard();
+            let mut elements = ElementBuf::vault(env.clone().into(), true).unwrap();
+            let mut buf = IntegratedDhtOpsBuf::new(env.clone().into()).unwrap();
+            for header in &headers {
+                elements.put(header.clone(), None).unwrap();
+            }
+            for (i, (hash, op)) in ops.into_iter().enumerate() {
+                let header_timestamp = elements
+                    .get_header(op.header_hash())
+                    .unwrap()
+                    .map(|h| h.header().timestamp().into());
+                let value = IntegratedDhtOpsValue {
+                    validation_status: ValidationStatus::Valid,
+                    op,
+                    when_integrated: (now + Duration::seconds(i as i64)).into(),
+                };
+                if backfill {
+                    buf.put(hash, value).unwrap();
+                } else {
+                    buf.integrate(hash, value, header_timestamp).unwrap();
+                }
+            }
+            env_ref
+                .with_commit(|writer| {
+                    elements.flush_to_txn_ref(writer)?;
+                    buf.flush_to_txn_ref(writer)
+                })
+                .unwrap();
+            let indexed = index_integrated_ops(&env).unwrap();
+            assert_eq!(indexed, if backfill { headers.len() } else { 0 });
+
+            let reader = env_ref.reader().unwrap();
+            let elements = ElementBuf::vault(env.clone().into(), true).unwrap();
+            let buf = IntegratedDhtOpsBuf::new(env.clone().into()).unwrap();
+            let order: Vec<_> = buf
+                .query_ordered(&reader, None, None, None)
+                .unwrap()
+                .map(|(hash, value)| {
+                    let header = elements.get_header(value.op.header_hash()).unwrap();
+                    Ok((header.unwrap().header().timestamp(), hash))
+                })
+                .collect()
+                .unwrap();
+            orders.push(order);
+        }
+
+        assert_eq!(orders[0], orders[1]);
+        assert_eq!(orders[0].len(), headers.len());
+        for pair in orders[0].windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+    }
 }
@@ -10,13 +10,14 @@
 use crate::core::state::source_chain::SourceChainResult;
 use holo_hash::{EntryHash, HasHash, HeaderHash};
 use holochain_state::{
-    buffer::CasBufFreshSync,
+    buffer::{CasBufFreshSync, CasBufUsedSync},
     db::{
         GetDb, ELEMENT_CACHE_ENTRIES, ELEMENT_CACHE_HEADERS, ELEMENT_VAULT_HEADERS,
         ELEMENT_VAULT_PRIVATE_ENTRIES, ELEMENT_VAULT_PUBLIC_ENTRIES,
     },
     error::{DatabaseError, DatabaseResult},
     exports::SingleStore,
+    fresh_reader,
     prelude::*,
 };
 use holochain_types::{
@@ -87,6 +88,29 @@ impl ElementBuf<RejectedPrefix> {
     }
 }
 
+impl ElementBuf<QuarantinePrefix> {
+    /// Create a element buf for elements which failed an integrity check.
+    /// Data here is stored under the hash it was found at, which by definition
+    /// does not match its content, so it must not be read back with `get`.
+    /// This reuses the database but is the data is completely separate.
+    pub fn quarantine(env: EnvironmentRead) -> DatabaseResult<Self> {
+        ElementBuf::new_vault(env, true)
+    }
+
+    /// Quarantine a header under the hash it was originally stored at
+    pub fn put_corrupt_header(&mut self, header_hash: HeaderHash, signed_header: SignedHeader) {
+        self.headers.put_unchecked(header_hash, signed_header);
+    }
+
+    /// Quarantine an entry under the hash it was originally stored at
+    pub fn put_corrupt_entry(&mut self, entry_hash: EntryHash, entry: Entry, private: bool) {
+        match (private, self.private_entries.as_mut()) {
+            (true, Some(db)) => db.put_unchecked(entry_hash, entry),
+            _ => self.public_entries.put_unchecked(entry_hash, entry),
+        }
+    }
+}
+
 impl<P> ElementBuf<P>
 where
     P: PrefixType,
@@ -126,11 +150,45 @@ where
     /// First attempt to get from the public entry DB. If not present, and
     /// private DB access is specified, attempt to get as a private entry.
     pub fn get_entry(&self, entry_hash: &EntryHash) -> DatabaseResult<Option<EntryHashed>> {
-        match self.public_entries.get(entry_hash)? {
+        fresh_reader!(self.headers.env(), |r| self
+            .get_entry_with_reader(&r, entry_hash))
+    }
+
+    /// Get many entries in a single read, in the same order as their
+    /// addresses. Entries which can't be found are None.
+    pub fn get_entries(
+        &self,
+        entry_hashes: &[EntryHash],
+    ) -> DatabaseResult<Vec<Option<EntryHashed>>> {
+        fresh_reader!(self.headers.env(), |r| self
+            .get_entries_with_reader(&r, entry_hashes))
+    }
+
+    /// Get many entries with an existing reader, in the same order as their
+    /// addresses. Entries which can't be found are None.
+    pub fn get_entries_with_reader<R: Readable>(
+        &self,
+        r: &R,
+        entry_hashes: &[EntryHash],
+    ) -> DatabaseResult<Vec<Option<EntryHashed>>> {
+        entry_hashes
+            .iter()
+            .map(|entry_hash| self.get_entry_with_reader(r, entry_hash))
+            .collect()
+    }
+
+    /// Get an entry with an existing reader, e.g. to read it from the same
+    /// snapshot as some metadata
+    pub fn get_entry_with_reader<R: Readable>(
+        &self,
+        r: &R,
+        entry_hash: &EntryHash,
+    ) -> DatabaseResult<Option<EntryHashed>> {
+        match CasBufUsedSync::get(&*self.public_entries, r, entry_hash)? {
             Some(entry) => Ok(Some(entry)),
             None => {
                 if let Some(ref db) = (self).private_entries {
-                    db.get(entry_hash)
+                    CasBufUsedSync::get(&**db, r, entry_hash)
                 } else {
                     Ok(None)
                 }
@@ -162,6 +220,38 @@ where
         Ok(self.headers.get(header_address)?.map(Into::into))
     }
 
+    /// Get many headers in a single read, in the same order as their
+    /// addresses. Headers which can't be found are None.
+    pub fn get_headers(
+        &self,
+        header_addresses: &[HeaderHash],
+    ) -> DatabaseResult<Vec<Option<SignedHeaderHashed>>> {
+        fresh_reader!(self.headers.env(), |r| self
+            .get_headers_with_reader(&r, header_addresses))
+    }
+
+    /// Get many headers with an existing reader, in the same order as their
+    /// addresses. Headers which can't be found are None.
+    pub fn get_headers_with_reader<R: Readable>(
+        &self,
+        r: &R,
+        header_addresses: &[HeaderHash],
+    ) -> DatabaseResult<Vec<Option<SignedHeaderHashed>>> {
+        header_addresses
+            .iter()
+            .map(|header_address| self.get_header_with_reader(r, header_address))
+            .collect()
+    }
+
+    /// Get a header with an existing reader
+    pub fn get_header_with_reader<R: Readable>(
+        &self,
+        r: &R,
+        header_address: &HeaderHash,
+    ) -> DatabaseResult<Option<SignedHeaderHashed>> {
+        Ok(CasBufUsedSync::get(&*self.headers, r, header_address)?.map(Into::into))
+    }
+
     /// Get the Entry out of Header if it exists.
     ///
     /// If the header contains no entry data, return None
@@ -169,17 +259,23 @@ where
     /// - if it is a public entry, but the entry cannot be found, return error
     /// - if it is a private entry and cannot be found, return error
     /// - if it is a private entry but the private DB is disabled, return None
-    fn get_entry_from_header(&self, header: &Header) -> SourceChainResult<Option<Entry>> {
+    fn get_entry_from_header<R: Readable>(
+        &self,
+        r: &R,
+        header: &Header,
+    ) -> SourceChainResult<Option<Entry>> {
         Ok(match header.entry_data() {
             None => None,
             Some((entry_hash, entry_type)) => {
                 match entry_type.visibility() {
                     // if the header references an entry and the database is
                     // available, it better have been stored!
-                    EntryVisibility::Public => self.public_entries.get(entry_hash)?,
+                    EntryVisibility::Public => {
+                        CasBufUsedSync::get(&*self.public_entries, r, entry_hash)?
+                    }
                     EntryVisibility::Private => {
                         if let Some(ref db) = self.private_entries {
-                            db.get(entry_hash)?
+                            CasBufUsedSync::get(&**db, r, entry_hash)?
                         } else {
                             // If the private DB is disabled, just return None
                             None
@@ -193,8 +289,42 @@ where
 
     /// given a header address return the full chain element for that address
     pub fn get_element(&self, header_address: &HeaderHash) -> SourceChainResult<Option<Element>> {
-        if let Some(signed_header) = self.get_header(header_address)? {
-            let maybe_entry = self.get_entry_from_header(signed_header.header())?;
+        fresh_reader!(self.headers.env(), |r| self
+            .get_element_with_reader(&r, header_address))
+    }
+
+    /// Get many elements in a single read, in the same order as their
+    /// header addresses. Elements which can't be found are None.
+    pub fn get_elements(
+        &self,
+        header_addresses: &[HeaderHash],
+    ) -> SourceChainResult<Vec<Option<Element>>> {
+        fresh_reader!(self.headers.env(), |r| self
+            .get_elements_with_reader(&r, header_addresses))
+    }
+
+    /// Get many elements with an existing reader, e.g. to read them from the
+    /// same snapshot as the metadata which refers to them, in the same order
+    /// as their header addresses. Elements which can't be found are None.
+    pub fn get_elements_with_reader<R: Readable>(
+        &self,
+        r: &R,
+        header_addresses: &[HeaderHash],
+    ) -> SourceChainResult<Vec<Option<Element>>> {
+        header_addresses
+            .iter()
+            .map(|header_address| self.get_element_with_reader(r, header_address))
+            .collect()
+    }
+
+    /// Get an element with an existing reader
+    pub fn get_element_with_reader<R: Readable>(
+        &self,
+        r: &R,
+        header_address: &HeaderHash,
+    ) -> SourceChainResult<Option<Element>> {
+        if let Some(signed_header) = self.get_header_with_reader(r, header_address)? {
+            let maybe_entry = self.get_entry_from_header(r, signed_header.header())?;
             Ok(Some(Element::new(signed_header, maybe_entry)))
         } else {
             Ok(None)
@@ -253,11 +383,16 @@ where
     pub fn delete(&mut self, header_hash: HeaderHash, entry_hash: Option<EntryHash>) {
         self.headers.delete(header_hash);
         if let Some(entry_hash) = entry_hash {
-            if let Some(db) = self.private_entries.as_mut() {
-                db.delete(entry_hash.clone())
-            }
-            self.public_entries.delete(entry_hash);
+            self.delete_entry(entry_hash);
+        }
+    }
+
+    /// Delete an entry without touching any headers
+    pub fn delete_entry(&mut self, entry_hash: EntryHash) {
+        if let Some(db) = self.private_entries.as_mut() {
+            db.delete(entry_hash.clone())
         }
+        self.public_entries.delete(entry_hash);
     }
 
     /// Removes a delete if there was one previously added
@@ -418,4 +553,91 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn batch_gets_keep_order() -> anyhow::Result<()> {
+        let keystore = spawn_test_keystore().await?;
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+
+        let agent_key = AgentPubKey::new_from_pure_entropy(&keystore).await?;
+        let (header_1, entry_1) =
+            fake_unique_element(&keystore, agent_key.clone(), EntryVisibility::Public).await?;
+        let (header_2, entry_2) =
+            fake_unique_element(&keystore, agent_key.clone(), EntryVisibility::Private).await?;
+        let header_hash_1 = header_1.header_address().clone();
+        let header_hash_2 = header_2.header_address().clone();
+
+        env.with_commit(|txn| {
+            let mut store = ElementBuf::vault(arc.clone().into(), true)?;
+            store.put(header_1, Some(entry_1.clone()))?;
+            store.put(header_2, Some(entry_2.clone()))?;
+            store.flush_to_txn(txn)
+        })?;
+
+        let store = ElementBuf::vault(arc.clone().into(), true)?;
+        let missing_entry = EntryHash::from_raw_bytes(vec![0; 36]);
+        let entries = store.get_entries(&[
+            entry_2.as_hash().clone(),
+            missing_entry,
+            entry_1.as_hash().clone(),
+        ])?;
+        assert_eq!(entries, vec![Some(entry_2), None, Some(entry_1.clone())]);
+
+        let missing_header = HeaderHash::from_raw_bytes(vec![0; 36]);
+        let elements =
+            store.get_elements(&[header_hash_2.clone(), missing_header, header_hash_1.clone()])?;
+        let addresses: Vec<_> = elements
+            .iter()
+            .map(|el| el.as_ref().map(|el| el.header_address().clone()))
+            .collect();
+        assert_eq!(
+            addresses,
+            vec![Some(header_hash_2), None, Some(header_hash_1)]
+        );
+        assert_eq!(
+            elements[2]
+                .as_ref()
+                .and_then(|el| el.entry().as_option().cloned()),
+            Some(entry_1.into_content())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn batch_gets_with_reader_read_its_snapshot() -> anyhow::Result<()> {
+        let keystore = spawn_test_keystore().await?;
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+
+        let agent_key = AgentPubKey::new_from_pure_entropy(&keystore).await?;
+        let (header, entry) =
+            fake_unique_element(&keystore, agent_key.clone(), EntryVisibility::Public).await?;
+        let header_hash = header.header_address().clone();
+        let entry_hash = entry.as_hash().clone();
+
+        // A reader opened before the write doesn't see it
+        let before = env.reader()?;
+        env.with_commit(|txn| {
+            let mut store = ElementBuf::vault(arc.clone().into(), true)?;
+            store.put(header, Some(entry))?;
+            store.flush_to_txn(txn)
+        })?;
+
+        let store = ElementBuf::vault(arc.clone().into(), true)?;
+        let hashes = [header_hash];
+        assert!(store.get_elements_with_reader(&before, &hashes)?[0].is_none());
+        assert!(store.get_headers_with_reader(&before, &hashes)?[0].is_none());
+        assert!(store.get_entries_with_reader(&before, &[entry_hash.clone()])?[0].is_none());
+
+        let after = env.reader()?;
+        assert!(store.get_elements_with_reader(&after, &hashes)?[0].is_some());
+        assert!(store.get_headers_with_reader(&after, &hashes)?[0].is_some());
+        assert!(store.get_entries_with_reader(&after, &[entry_hash])?[0].is_some());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,206 @@
+//! A write-ahead journal for the integration workflow.
+//!
+//! Before a batch of ops is integrated, the hashes of every op waiting in the
+//! integration limbo are recorded as an [IntegrationIntent] in their own
+//! transaction. The intent is cleared in the same transaction which writes
+//! the batch's results, so an intent which is still present on startup means
+//! the batch was interrupted. [recover_interrupted_integration] then brings
+//! each journaled op to a consistent state: either fully integrated (and no
+//! longer in limbo), or back in limbo waiting to be integrated again.
+
+use super::{
+    dht_op_integration::{IntegratedDhtOpsStore, IntegrationLimboStore},
+    workspace::WorkspaceResult,
+};
+use fallible_iterator::FallibleIterator;
+use holo_hash::DhtOpHash;
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh,
+    db::{INTEGRATED_DHT_OPS, INTEGRATION_JOURNAL, INTEGRATION_LIMBO},
+    env::{EnvironmentRead, EnvironmentWrite},
+    error::DatabaseResult,
+    fresh_reader,
+    prelude::*,
+};
+use holochain_types::Timestamp;
+use tracing::*;
+
+/// Database type for the IntegrationJournal: at most one intent at a time
+pub type IntegrationJournalStore = KvBufFresh<UnitDbKey, IntegrationIntent>;
+
+/// The ops a run of the integration workflow set out to integrate
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct IntegrationIntent {
+    /// The hashes of the ops in the integration limbo when the run began
+    pub op_hashes: Vec<DhtOpHash>,
+    /// When the run began
+    pub started: Timestamp,
+}
+
+/// What recovery did with each op from an interrupted run
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IntegrationRecoveryReport {
+    /// Ops which were integrated but still in limbo, now removed from limbo
+    pub completed: Vec<DhtOpHash>,
+    /// Ops which were never integrated and remain in limbo to be retried
+    pub rolled_back: Vec<DhtOpHash>,
+    /// Ops which were in neither the limbo nor the integrated store
+    pub missing: Vec<DhtOpHash>,
+}
+
+/// Open the journal store
+pub fn integration_journal(env: EnvironmentRead) -> DatabaseResult<IntegrationJournalStore> {
+    Ok(KvBufFresh::new(
+        env.clone(),
+        env.get_db(&*INTEGRATION_JOURNAL)?,
+    ))
+}
+
+/// Record an intent covering everything currently in the integration limbo.
+/// Nothing is recorded if the limbo is empty.
+pub fn begin_integration(env: &EnvironmentWrite) -> WorkspaceResult<Option<IntegrationIntent>> {
+    let integration_limbo: IntegrationLimboStore =
+        KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO)?);
+    let op_hashes: Vec<DhtOpHash> = fresh_reader!(env, |r| integration_limbo
+        .iter(&r)?
+        .map(|(k, _)| Ok(DhtOpHash::with_pre_hashed(k.to_vec())))
+        .collect())?;
+    if op_hashes.is_empty() {
+        return Ok(None);
+    }
+    let intent = IntegrationIntent {
+        op_hashes,
+        started: Timestamp::now(),
+    };
+    let mut journal = integration_journal(env.clone().into())?;
+    journal.put(UnitDbKey, intent.clone())?;
+    env.guard()
+        .with_commit(|writer| journal.flush_to_txn_ref(writer))?;
+    Ok(Some(intent))
+}
+
+/// Resolve any intent left behind by an integration run which never
+/// committed. Must be called before the integration workflow starts.
+pub fn recover_interrupted_integration(
+    env: &EnvironmentWrite,
+) -> WorkspaceResult<Option<IntegrationRecoveryReport>> {
+    let mut journal = integration_journal(env.clone().into())?;
+    let intent = match journal.get(&UnitDbKey)? {
+        Some(intent) => intent,
+        None => return Ok(None),
+    };
+    warn!(
+        num_ops = intent.op_hashes.len(),
+        started = ?intent.started,
+        "Recovering an interrupted integration run"
+    );
+
+    let mut integration_limbo: IntegrationLimboStore =
+        KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO)?);
+    let integrated_dht_ops: IntegratedDhtOpsStore =
+        KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATED_DHT_OPS)?);
+    let mut report = IntegrationRecoveryReport::default();
+    for hash in intent.op_hashes {
+        let in_limbo = integration_limbo.contains(&hash)?;
+        let integrated = integrated_dht_ops.contains(&hash)?;
+        match (integrated, in_limbo) {
+            (true, true) => {
+                integration_limbo.delete(hash.clone())?;
+                report.completed.push(hash);
+            }
+            // Integrated by the run, or by a later one
+            (true, false) => (),
+            (false, true) => report.rolled_back.push(hash),
+            (false, false) => {
+                warn!(op_hash = ?hash, "Journaled op is neither in limbo nor integrated");
+                report.missing.push(hash);
+            }
+        }
+    }
+
+    journal.delete(UnitDbKey)?;
+    env.guard().with_commit(|writer| {
+        integration_limbo.flush_to_txn_ref(writer)?;
+        journal.flush_to_txn_ref(writer)?;
+        WorkspaceResult::Ok(())
+    })?;
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::dht_op_integration::{IntegratedDhtOpsValue, IntegrationLimboValue};
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::*;
+    use holochain_state::test_utils::test_cell_env;
+    use holochain_types::{dht_op::DhtOpLight, validate::ValidationStatus};
+
+    #[tokio::test(threaded_scheduler)]
+    async fn recovers_interrupted_run() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let op = DhtOpLight::RegisterAgentActivity(fixt!(HeaderHash), fixt!(AnyDhtHash));
+        let half_done = fixt!(DhtOpHash);
+        let not_started = fixt!(DhtOpHash);
+
+        assert_eq!(begin_integration(&env).unwrap(), None);
+
+        {
+            let mut integration_limbo: IntegrationLimboStore =
+                KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO).unwrap());
+            for hash in vec![half_done.clone(), not_started.clone()] {
+                integration_limbo
+                    .put(
+                        hash,
+                        IntegrationLimboValue {
+                            validation_status: ValidationStatus::Valid,
+                            op: op.clone(),
+                        },
+                    )
+                    .unwrap();
+            }
+            env.guard()
+                .with_commit(|writer| integration_limbo.flush_to_txn_ref(writer))
+                .unwrap();
+        }
+
+        let intent = begin_integration(&env).unwrap().unwrap();
+        assert_eq!(intent.op_hashes.len(), 2);
+
+        // Simulate a run which integrated one op but didn't clean up after it
+        {
+            let mut integrated: IntegratedDhtOpsStore = KvBufFresh::new(
+                env.clone().into(),
+                env.get_db(&*INTEGRATED_DHT_OPS).unwrap(),
+            );
+            integrated
+                .put(
+                    half_done.clone(),
+                    IntegratedDhtOpsValue {
+                        validation_status: ValidationStatus::Valid,
+                        op: op.clone(),
+                        when_integrated: Timestamp::now(),
+                    },
+                )
+                .unwrap();
+            env.guard()
+                .with_commit(|writer| integrated.flush_to_txn_ref(writer))
+                .unwrap();
+        }
+
+        let report = recover_interrupted_integration(&env).unwrap().unwrap();
+        assert_eq!(report.completed, vec![half_done.clone()]);
+        assert_eq!(report.rolled_back, vec![not_started.clone()]);
+        assert!(report.missing.is_empty());
+
+        let integration_limbo: IntegrationLimboStore =
+            KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO).unwrap());
+        assert!(!integration_limbo.contains(&half_done).unwrap());
+        assert!(integration_limbo.contains(&not_started).unwrap());
+
+        // The intent is cleared, so there's nothing left to recover
+        assert_eq!(recover_interrupted_integration(&env).unwrap(), None);
+    }
+}
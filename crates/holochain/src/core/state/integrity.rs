@@ -0,0 +1,234 @@
+//! An integrity pass over a Cell's element vault.
+//!
+//! Every stored header and entry is re-hashed and compared against the key
+//! it is stored under, and every header signature is re-verified against
+//! its author. Anything which fails is moved out of the vault and into the
+//! quarantine, so that corrupted data can't be served to other agents.
+//! Whatever the vault's metadata registered for a quarantined header is
+//! forgotten with it, so nothing points at a header the vault no longer has.
+//! Finally every entry's DHT status is rebuilt from the vault's metadata.
+//!
+//! NB: records which can't be deserialized at all will still trip the
+//! database's fatal corruption check, as they always have.
+
+use super::{element_buf::ElementBuf, metadata::MetadataBuf, source_chain::SourceChainResult};
+use holo_hash::{EntryHash, HasHash, HeaderHash, HoloHashed};
+use holochain_keystore::AgentPubKeyExt;
+use holochain_state::{env::EnvironmentWrite, fresh_reader, prelude::*};
+use holochain_zome_types::{element::SignedHeader, Entry};
+use tracing::*;
+
+/// The outcome of an integrity pass over a Cell's vault
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct IntegrityReport {
+    /// How many headers were checked
+    pub headers_checked: usize,
+    /// How many entries were checked
+    pub entries_checked: usize,
+    /// Records which failed the check and were quarantined
+    pub quarantined: Vec<CorruptRecord>,
+    /// How many entries had their DHT status corrected
+    pub statuses_rebuilt: usize,
+}
+
+/// A record which failed an integrity check
+#[derive(Debug, Clone, PartialEq)]
+pub enum CorruptRecord {
+    /// The header does not hash to the key it was stored under
+    HeaderHash(HeaderHash),
+    /// The header's signature is not valid for its author
+    HeaderSignature(HeaderHash),
+    /// The entry does not hash to the key it was stored under
+    EntryHash(EntryHash),
+}
+
+impl IntegrityReport {
+    /// True if nothing was quarantined
+    pub fn is_clean(&self) -> bool {
+        self.quarantined.is_empty()
+    }
+}
+
+/// Check every header and entry in the vault, quarantining corrupt records.
+pub async fn check_vault_integrity(env: EnvironmentWrite) -> SourceChainResult<IntegrityReport> {
+    let mut vault = ElementBuf::vault(env.clone().into(), true)?;
+    let mut quarantine = ElementBuf::quarantine(env.clone().into())?;
+    let mut meta_vault = MetadataBuf::vault(env.clone().into())?;
+    let mut report = IntegrityReport::default();
+
+    // Collect everything first so we aren't holding a reader across awaits
+    let (headers, public_entries, private_entries) = fresh_reader!(env, |r| {
+        let headers: Vec<(HeaderHash, SignedHeader)> =
+            vault.headers().iter_unchecked(&r)?.collect()?;
+        let public_entries: Vec<(EntryHash, Entry)> =
+            vault.public_entries().iter_unchecked(&r)?.collect()?;
+        let private_entries: Vec<(EntryHash, Entry)> = match vault.private_entries() {
+            Some(db) => db.iter_unchecked(&r)?.collect()?,
+            None => Vec::new(),
+        };
+        SourceChainResult::Ok((headers, public_entries, private_entries))
+    })?;
+
+    for (hash, signed_header) in headers {
+        report.headers_checked += 1;
+        let actual = HoloHashed::from_content_sync(signed_header.clone());
+        let corrupt = if actual.as_hash() != &hash {
+            Some(CorruptRecord::HeaderHash(hash.clone()))
+        } else if !signed_header
+            .header()
+            .author()
+            .verify_signature(signed_header.signature(), signed_header.header())
+            .await?
+        {
+            Some(CorruptRecord::HeaderSignature(hash.clone()))
+        } else {
+            None
+        };
+        if let Some(corrupt) = corrupt {
+            warn!(?corrupt, "Quarantining corrupt header");
+            vault.delete(hash.clone(), None);
+            meta_vault.forget_header(hash.clone(), signed_header.header())?;
+            quarantine.put_corrupt_header(hash, signed_header);
+            report.quarantined.push(corrupt);
+        }
+    }
+
+    let entries = public_entries
+        .into_iter()
+        .map(|e| (e, false))
+        .chain(private_entries.into_iter().map(|e| (e, true)));
+    for ((hash, entry), private) in entries {
+        report.entries_checked += 1;
+        let actual = HoloHashed::from_content_sync(entry.clone());
+        if actual.as_hash() != &hash {
+            let corrupt = CorruptRecord::EntryHash(hash.clone());
+            warn!(?corrupt, "Quarantining corrupt entry");
+            vault.delete_entry(hash.clone());
+            quarantine.put_corrupt_entry(hash, entry, private);
+            report.quarantined.push(corrupt);
+        }
+    }
+
+    // Statuses are derived from the metadata, so recompute them
+    // in case they have drifted from it
+    report.statuses_rebuilt = meta_vault.rebuild_entry_dht_statuses(&vault)?;
+
+    env.guard().with_commit(|writer| {
+        vault.flush_to_txn_ref(writer)?;
+        quarantine.flush_to_txn_ref(writer)?;
+        meta_vault.flush_to_txn_ref(writer)?;
+        SourceChainResult::Ok(())
+    })?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::metadata::MetadataBufT;
+    use crate::fixt::SignatureFixturator;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::AgentPubKeyFixturator;
+    use holochain_state::{
+        buffer::CasBufFreshSync, db::ELEMENT_VAULT_PUBLIC_ENTRIES, fresh_reader_test,
+        test_utils::test_cell_env,
+    };
+    use holochain_types::{
+        element::SignedHeaderHashed, fixt::CreateFixturator, header::NewEntryHeader,
+        metadata::EntryDhtStatus, EntryHashed,
+    };
+    use holochain_zome_types::header::{EntryType, Header};
+
+    #[tokio::test(threaded_scheduler)]
+    async fn quarantines_bad_signatures_and_mismatched_hashes() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut agents = AgentPubKeyFixturator::new(Unpredictable);
+
+        // An entry and a header with a bad signature, stored properly
+        let entry_hashed = EntryHashed::from_content_sync(Entry::Agent(agents.next().unwrap()));
+        let mut create = fixt!(Create);
+        create.entry_hash = entry_hashed.as_hash().clone();
+        create.entry_type = EntryType::AgentPubKey;
+        let header = HoloHashed::from_content_sync(Header::Create(create.clone()));
+        let bad_sig_hash = header.as_hash().clone();
+        let shh = SignedHeaderHashed::with_presigned(header, fixt!(Signature));
+
+        // An entry stored under the wrong hash
+        let other_entry = Entry::Agent(agents.next().unwrap());
+        let wrong_hash =
+            EntryHashed::from_content_sync(Entry::Agent(agents.next().unwrap())).into_hash();
+
+        {
+            let mut vault = ElementBuf::vault(env.clone().into(), true).unwrap();
+            let mut meta_vault = MetadataBuf::vault(env.clone().into()).unwrap();
+            meta_vault
+                .register_header(NewEntryHeader::Create(create.clone()))
+                .unwrap();
+            meta_vault.register_element_header(shh.header()).unwrap();
+            meta_vault.register_activity(shh.header().clone()).unwrap();
+            vault.put(shh, Some(entry_hashed.clone())).unwrap();
+            let mut entries: CasBufFreshSync<Entry> = CasBufFreshSync::new(
+                env.clone().into(),
+                env.get_db(&*ELEMENT_VAULT_PUBLIC_ENTRIES).unwrap(),
+            );
+            entries.put(HoloHashed::with_pre_hashed(other_entry, wrong_hash.clone()));
+            env.guard()
+                .with_commit(|writer| {
+                    vault.flush_to_txn_ref(writer)?;
+                    meta_vault.flush_to_txn_ref(writer)?;
+                    entries.flush_to_txn_ref(writer)
+                })
+                .unwrap();
+        }
+
+        let report = check_vault_integrity(env.clone()).await.unwrap();
+        assert_eq!(report.headers_checked, 1);
+        assert_eq!(report.entries_checked, 2);
+        assert_eq!(
+            report.quarantined,
+            vec![
+                CorruptRecord::HeaderSignature(bad_sig_hash.clone()),
+                CorruptRecord::EntryHash(wrong_hash.clone()),
+            ]
+        );
+
+        let vault = ElementBuf::vault(env.clone().into(), true).unwrap();
+        assert!(!vault.contains_header(&bad_sig_hash).unwrap());
+        assert!(!vault.contains_entry(&wrong_hash).unwrap());
+        assert!(vault.contains_entry(entry_hashed.as_hash()).unwrap());
+        let quarantine = ElementBuf::quarantine(env.clone().into()).unwrap();
+        assert!(quarantine.contains_header(&bad_sig_hash).unwrap());
+        assert!(quarantine.contains_entry(&wrong_hash).unwrap());
+
+        // The metadata no longer points at the quarantined header
+        let meta_vault = MetadataBuf::vault(env.clone().into()).unwrap();
+        fresh_reader_test!(env, |r| {
+            assert!(meta_vault
+                .get_headers(&r, entry_hashed.as_hash().clone())
+                .unwrap()
+                .next()
+                .unwrap()
+                .is_none());
+            assert!(meta_vault
+                .get_activity(&r, create.author.clone())
+                .unwrap()
+                .next()
+                .unwrap()
+                .is_none());
+            assert!(!meta_vault
+                .has_registered_store_element(&r, &bad_sig_hash)
+                .unwrap());
+            assert_eq!(
+                meta_vault
+                    .get_dht_status(&r, entry_hashed.as_hash())
+                    .unwrap(),
+                EntryDhtStatus::Dead
+            );
+        });
+
+        // A second pass finds nothing left to quarantine
+        let report = check_vault_integrity(env.clone()).await.unwrap();
+        assert!(report.is_clean());
+    }
+}
@@ -0,0 +1,213 @@
+//! A snapshot of every op a Cell is still holding in limbo, i.e. which has
+//! been received or authored but not yet integrated, along with enough
+//! detail to see why it's stuck.
+
+use super::{
+    dht_op_integration::IntegrationLimboStore,
+    validation_db::{ValidationLimboStatus, ValidationLimboStore},
+    workspace::WorkspaceResult,
+};
+use crate::core::workflow::sys_validation_workflow::types::DepType;
+use fallible_iterator::FallibleIterator;
+use holo_hash::{AnyDhtHash, DhtOpHash, HeaderHash};
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh, db::INTEGRATION_LIMBO, env::EnvironmentWrite, fresh_reader, prelude::*,
+};
+use holochain_types::{
+    dht_op::{DhtOpLight, DhtOpType},
+    validate::ValidationStatus,
+    Timestamp,
+};
+
+/// Where in the pipeline an op in limbo is waiting
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub enum LimboStage {
+    /// Waiting to be sys validated
+    SysValidation,
+    /// Waiting to be app validated
+    AppValidation,
+    /// Validated, but relied on dependencies which haven't finished validating
+    AwaitingDependencyValidation,
+    /// Validated and waiting to be integrated
+    Integration,
+}
+
+/// The details of a single op in limbo
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct LimboOpInfo {
+    /// The hash of the op
+    pub op_hash: DhtOpHash,
+    /// What type of op it is
+    pub op_type: DhtOpType,
+    /// The header the op was produced from
+    pub header_hash: HeaderHash,
+    /// The basis hash the op is held at
+    pub basis: AnyDhtHash,
+    /// Which stage it's waiting at
+    pub stage: LimboStage,
+    /// The dependency that must be found before validation can continue
+    pub awaiting: Option<AnyDhtHash>,
+    /// Dependencies which were used in validation but hadn't yet
+    /// finished their own validation
+    pub pending_dependencies: Vec<DepType>,
+    /// The outcome of validation, once known
+    pub validation_status: Option<ValidationStatus>,
+    /// How many times validation has been attempted
+    pub num_tries: u32,
+    /// When the op entered the validation limbo
+    pub time_added: Option<Timestamp>,
+    /// When validation was last attempted
+    pub last_try: Option<Timestamp>,
+    /// How many seconds since the op entered the validation limbo
+    pub age_secs: Option<i64>,
+}
+
+impl LimboOpInfo {
+    fn new(op_hash: DhtOpHash, op: &DhtOpLight, stage: LimboStage) -> Self {
+        Self {
+            op_hash,
+            op_type: op.get_type(),
+            header_hash: op.header_hash().clone(),
+            basis: op.dht_basis().clone(),
+            stage,
+            awaiting: None,
+            pending_dependencies: Vec::new(),
+            validation_status: None,
+            num_tries: 0,
+            time_added: None,
+            last_try: None,
+            age_secs: None,
+        }
+    }
+}
+
+/// List every op in the validation and integration limbos
+pub fn dump_limbo(env: &EnvironmentWrite) -> WorkspaceResult<Vec<LimboOpInfo>> {
+    let validation_limbo = ValidationLimboStore::new(env.clone().into())?;
+    let integration_limbo: IntegrationLimboStore =
+        KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO)?);
+    let now = chrono::Utc::now();
+
+    fresh_reader!(env, |r| {
+        let mut ops: Vec<LimboOpInfo> = validation_limbo
+            .iter(&r)?
+            .map(|(k, vlv)| {
+                let (stage, awaiting) = match vlv.status {
+                    ValidationLimboStatus::Pending => (LimboStage::SysValidation, None),
+                    ValidationLimboStatus::AwaitingSysDeps(dep) => {
+                        (LimboStage::SysValidation, Some(dep))
+                    }
+                    ValidationLimboStatus::SysValidated => (LimboStage::AppValidation, None),
+                    ValidationLimboStatus::AwaitingAppDeps(dep) => {
+                        (LimboStage::AppValidation, Some(dep))
+                    }
+                    ValidationLimboStatus::PendingValidation => {
+                        (LimboStage::AwaitingDependencyValidation, None)
+                    }
+                };
+                let age = now.signed_duration_since(vlv.time_added.clone().into());
+                Ok(LimboOpInfo {
+                    awaiting,
+                    pending_dependencies: vlv.pending_dependencies.pending,
+                    num_tries: vlv.num_tries,
+                    time_added: Some(vlv.time_added),
+                    last_try: vlv.last_try,
+                    age_secs: Some(age.num_seconds()),
+                    ..LimboOpInfo::new(DhtOpHash::with_pre_hashed(k.to_vec()), &vlv.op, stage)
+                })
+            })
+            .collect()?;
+        integration_limbo.iter(&r)?.for_each(|(k, ilv)| {
+            ops.push(LimboOpInfo {
+                validation_status: Some(ilv.validation_status),
+                ..LimboOpInfo::new(
+                    DhtOpHash::with_pre_hashed(k.to_vec()),
+                    &ilv.op,
+                    LimboStage::Integration,
+                )
+            });
+            Ok(())
+        })?;
+        WorkspaceResult::Ok(ops)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        state::{dht_op_integration::IntegrationLimboValue, validation_db::ValidationLimboValue},
+        workflow::sys_validation_workflow::types::PendingDependencies,
+    };
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::*;
+    use holochain_state::test_utils::test_cell_env;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn dump_both_limbos() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let dep = fixt!(AnyDhtHash);
+        let basis = fixt!(AnyDhtHash);
+        let validating_op = DhtOpLight::RegisterAgentActivity(fixt!(HeaderHash), basis.clone());
+        let integrating_op = DhtOpLight::RegisterAddLink(fixt!(HeaderHash), basis.clone());
+        let validating_hash = fixt!(DhtOpHash);
+        let integrating_hash = fixt!(DhtOpHash);
+
+        {
+            let mut validation_limbo = ValidationLimboStore::new(env.clone().into()).unwrap();
+            let mut integration_limbo: IntegrationLimboStore =
+                KvBufFresh::new(env.clone().into(), env.get_db(&*INTEGRATION_LIMBO).unwrap());
+            validation_limbo
+                .put(
+                    validating_hash.clone(),
+                    ValidationLimboValue {
+                        status: ValidationLimboStatus::AwaitingSysDeps(dep.clone()),
+                        pending_dependencies: PendingDependencies::new(),
+                        op: validating_op.clone(),
+                        basis: basis.clone(),
+                        time_added: Timestamp::now(),
+                        last_try: Some(Timestamp::now()),
+                        num_tries: 3,
+                        validation_package: None,
+                    },
+                )
+                .unwrap();
+            integration_limbo
+                .put(
+                    integrating_hash.clone(),
+                    IntegrationLimboValue {
+                        validation_status: ValidationStatus::Valid,
+                        op: integrating_op,
+                    },
+                )
+                .unwrap();
+            env.guard()
+                .with_commit(|writer| {
+                    validation_limbo.flush_to_txn_ref(writer)?;
+                    integration_limbo.flush_to_txn_ref(writer)
+                })
+                .unwrap();
+        }
+
+        let ops = dump_limbo(&env).unwrap();
+        assert_eq!(ops.len(), 2);
+
+        let validating = &ops[0];
+        assert_eq!(validating.op_hash, validating_hash);
+        assert_eq!(validating.op_type, DhtOpType::RegisterAgentActivity);
+        assert_eq!(validating.basis, basis);
+        assert_eq!(validating.stage, LimboStage::SysValidation);
+        assert_eq!(validating.awaiting, Some(dep));
+        assert_eq!(validating.num_tries, 3);
+        assert!(validating.age_secs.unwrap() >= 0);
+
+        let integrating = &ops[1];
+        assert_eq!(integrating.op_hash, integrating_hash);
+        assert_eq!(integrating.op_type, DhtOpType::RegisterAddLink);
+        assert_eq!(integrating.stage, LimboStage::Integration);
+        assert_eq!(integrating.validation_status, Some(ValidationStatus::Valid));
+        assert_eq!(integrating.age_secs, None);
+    }
+}
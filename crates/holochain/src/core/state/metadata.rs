@@ -4,6 +4,7 @@
 //!
 //! [Entry]: holochain_types::Entry
 
+use super::element_buf::ElementBuf;
 use fallible_iterator::FallibleIterator;
 use holo_hash::HasHash;
 use holo_hash::{AgentPubKey, AnyDhtHash, EntryHash, HeaderHash};
@@ -26,7 +27,9 @@ use holochain_zome_types::{link::LinkTag, Header};
 use std::fmt::Debug;
 use tracing::*;
 
+pub use dht_status::*;
 pub use keys::*;
+pub use link_tags::*;
 pub use sys_meta::*;
 
 #[cfg(test)]
@@ -34,7 +37,9 @@ pub use mock::MockMetadataBuf;
 #[cfg(test)]
 use mockall::mock;
 
+mod dht_status;
 mod keys;
+mod link_tags;
 #[cfg(test)]
 pub mod links_test;
 mod sys_meta;
@@ -127,6 +132,10 @@ where
     /// Deregister a [Header::Delete] on the Header of an Entry
     fn deregister_delete(&mut self, delete: header::Delete) -> DatabaseResult<()>;
 
+    /// Put an [Entry] in conflict because its author forked their chain
+    /// while creating it
+    fn register_conflict(&mut self, entry_hash: EntryHash) -> DatabaseResult<()>;
+
     /// Returns all the [HeaderHash]es of headers that created this [Entry]
     fn get_headers<'r, R: Readable>(
         &'r self,
@@ -169,6 +178,14 @@ where
         entry_hash: &EntryHash,
     ) -> DatabaseResult<EntryDhtStatus>;
 
+    /// Returns the oldest header on an [Entry] which hasn't been deleted.
+    /// This is kept up to date alongside the [EntryDhtStatus].
+    fn get_oldest_live_header<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        entry_hash: &EntryHash,
+    ) -> DatabaseResult<Option<TimedHeaderHash>>;
+
     /// Finds the redirect path and returns the final [Entry]
     fn get_canonical_entry_hash(&self, entry_hash: EntryHash) -> DatabaseResult<EntryHash>;
 
@@ -183,17 +200,26 @@ where
     ) -> DatabaseResult<Box<dyn FallibleIterator<Item = TimedHeaderHash, Error = DatabaseError> + '_>>;
 
     /// Finds if there is a StoreElement for this header
-    fn has_registered_store_element(&self, hash: &HeaderHash) -> DatabaseResult<bool>;
+    fn has_registered_store_element<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        hash: &HeaderHash,
+    ) -> DatabaseResult<bool>;
 
     /// Finds if there is a StoreEntry for this header
-    fn has_registered_store_entry(
-        &self,
+    fn has_registered_store_entry<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
         entry_hash: &EntryHash,
         header_hash: &HeaderHash,
     ) -> DatabaseResult<bool>;
 
     /// Finds if there is a StoreEntry for this entry
-    fn has_any_registered_store_entry(&self, hash: &EntryHash) -> DatabaseResult<bool>;
+    fn has_any_registered_store_entry<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        hash: &EntryHash,
+    ) -> DatabaseResult<bool>;
 
     /// Get the environment for creating readers
     fn env(&self) -> &EnvironmentRead;
@@ -311,29 +337,302 @@ where
         Ok(())
     }
 
+    /// Remove everything registered for a header which has been quarantined.
+    /// `hash` is the hash the header was stored under, which for a corrupt
+    /// header isn't the hash of its content, so nothing is re-hashed here.
+    /// The statuses of any entries it touched are recomputed without it.
+    pub fn forget_header(&mut self, hash: HeaderHash, header: &Header) -> DatabaseResult<()> {
+        let timed = TimedHeaderHash {
+            timestamp: header.timestamp().into(),
+            header_hash: hash.clone(),
+        };
+        self.misc_meta
+            .delete(MiscMetaKey::StoreElement(hash.clone()).into())?;
+        self.system_meta.delete(
+            SysMetaKey::from(header.author().clone()).into(),
+            SysMetaVal::Activity(timed.clone()),
+        );
+        match header {
+            Header::Create(create) => {
+                self.system_meta.delete(
+                    SysMetaKey::from(create.entry_hash.clone()).into(),
+                    SysMetaVal::NewEntry(timed),
+                );
+                self.update_entry_dht_status(create.entry_hash.clone())?;
+            }
+            Header::Update(update) => {
+                self.system_meta.delete(
+                    SysMetaKey::from(update.entry_hash.clone()).into(),
+                    SysMetaVal::NewEntry(timed.clone()),
+                );
+                self.system_meta.delete(
+                    SysMetaKey::from(update.original_entry_address.clone()).into(),
+                    SysMetaVal::Update(timed),
+                );
+                self.update_entry_dht_status(update.entry_hash.clone())?;
+            }
+            Header::Delete(delete) => {
+                self.system_meta.delete(
+                    SysMetaKey::from(delete.deletes_address.clone()).into(),
+                    SysMetaVal::Delete(timed.clone()),
+                );
+                self.system_meta.delete(
+                    SysMetaKey::from(delete.deletes_entry_address.clone()).into(),
+                    SysMetaVal::Delete(timed),
+                );
+                self.update_entry_dht_status(delete.deletes_entry_address.clone())?;
+            }
+            Header::CreateLink(link_add) => {
+                let key = LinkMetaKey::from((link_add, &hash));
+                self.links_meta.delete(key.into())?;
+            }
+            Header::DeleteLink(link_remove) => {
+                self.system_meta.delete(
+                    SysMetaKey::from(link_remove.link_add_address.clone()).into(),
+                    SysMetaVal::DeleteLink(timed),
+                );
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Recompute an entry's liveness from its headers and deletes.
+    /// The oldest live header is stored alongside the status so
+    /// they are always flushed together.
     #[instrument(skip(self))]
     fn update_entry_dht_status(&mut self, basis: EntryHash) -> DatabaseResult<()> {
-        let status = fresh_reader!(self.env, |r| self.get_headers(&r, basis.clone())?.find_map(
-            |header| {
+        let oldest_live_header = fresh_reader!(self.env, |r| self.oldest_live_header(&r, &basis))?;
+        let mut events = Vec::with_capacity(2);
+        events.extend(validation_event::<P>());
+        let key: PrefixBytesKey<P> = MiscMetaKey::OldestLiveHeader(basis.clone()).into();
+        match oldest_live_header {
+            Some(header) => {
+                trace!("found live header");
+                events.push(EntryStatusEvent::HeaderRestored);
+                self.misc_meta
+                    .put(key, MiscMetaValue::OldestLiveHeader(header))?;
+            }
+            None => {
+                // No evidence of life found
+                trace!("found no live header");
+                events.push(EntryStatusEvent::AllHeadersDeleted);
+                self.misc_meta.delete(key)?;
+            }
+        }
+        self.transition_entry_dht_status(basis, events)
+    }
+
+    /// The oldest of an entry's headers which hasn't been deleted
+    fn oldest_live_header<R: Readable>(
+        &self,
+        r: &R,
+        basis: &EntryHash,
+    ) -> DatabaseResult<Option<TimedHeaderHash>> {
+        self.get_headers(r, basis.clone())?
+            .filter_map(|header| {
                 if self
-                    .get_deletes_on_header(&r, header.header_hash)?
+                    .get_deletes_on_header(r, header.header_hash.clone())?
                     .next()?
                     .is_none()
                 {
-                    trace!("found live header");
-                    Ok(Some(EntryDhtStatus::Live))
+                    Ok(Some(header))
                 } else {
-                    trace!("found dead header");
                     Ok(None)
                 }
+            })
+            .min()
+    }
+
+    /// Work out an entry's status from scratch, starting from Pending,
+    /// rather than moving on from the status stored for it.
+    /// Forks are found by looking the entry's headers up in `elements`.
+    fn compute_entry_dht_status<R: Readable>(
+        &self,
+        r: &R,
+        elements: &ElementBuf<P>,
+        basis: &EntryHash,
+    ) -> DatabaseResult<(EntryDhtStatus, Option<TimedHeaderHash>)> {
+        let oldest_live_header = self.oldest_live_header(r, basis)?;
+        let mut seen = std::collections::HashSet::new();
+        let mut forked = false;
+        for hash in self
+            .get_headers(r, basis.clone())?
+            .map(|h| Ok(h.header_hash))
+            .collect::<Vec<_>>()?
+        {
+            if let Some(header) = elements.get_header(&hash)? {
+                let header = header.header();
+                forked |= !seen.insert((header.author().clone(), header.header_seq()));
             }
-        ))?
-        // No evidence of life found so entry is marked dead
-        .unwrap_or(EntryDhtStatus::Dead);
-        self.misc_meta.put(
-            MiscMetaKey::EntryStatus(basis).into(),
-            MiscMetaValue::EntryStatus(status),
-        )
+        }
+        let mut events = Vec::with_capacity(3);
+        events.extend(validation_event::<P>());
+        if forked {
+            events.push(EntryStatusEvent::Forked);
+        }
+        events.push(if oldest_live_header.is_some() {
+            EntryStatusEvent::HeaderRestored
+        } else {
+            EntryStatusEvent::AllHeadersDeleted
+        });
+        let status = events.into_iter().fold(EntryDhtStatus::Pending, transition);
+        Ok((status, oldest_live_header))
+    }
+
+    /// Recompute the status and oldest live header of every entry
+    /// which has a status, from the headers, updates and deletes stored
+    /// for it and the headers in `elements`. Each status is worked out
+    /// afresh, so even a final status which has drifted from the data it
+    /// was computed from is corrected. This is for recovering from such
+    /// drift. Only statuses which change are written back.
+    /// Returns how many entries were changed.
+    pub fn rebuild_entry_dht_statuses(
+        &mut self,
+        elements: &ElementBuf<P>,
+    ) -> DatabaseResult<usize> {
+        let entry_hashes: Vec<EntryHash> = fresh_reader!(self.env, |r| self
+            .misc_meta
+            .iter_all_key_matches(&r, PrefixBytesKey::new(std::iter::empty()))?
+            .filter_map(|(k, v)| {
+                Ok(match v {
+                    MiscMetaValue::EntryStatus(_) => {
+                        let key = PrefixBytesKey::<P>::from_key_bytes_or_friendly_panic(k);
+                        match MiscMetaKey::from(BytesKey::from(key.without_prefix())) {
+                            MiscMetaKey::EntryStatus(entry_hash) => Some(entry_hash),
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                })
+            })
+            .collect())?;
+        let changed = fresh_reader!(self.env, |r| {
+            let mut changed = Vec::new();
+            for entry_hash in entry_hashes {
+                let (status, oldest_live_header) =
+                    self.compute_entry_dht_status(&r, elements, &entry_hash)?;
+                let stored_status = self
+                    .misc_meta
+                    .get(&r, &MiscMetaKey::EntryStatus(entry_hash.clone()).into())?
+                    .map(MiscMetaValue::entry_status);
+                if stored_status != Some(status)
+                    || self.get_oldest_live_header(&r, &entry_hash)? != oldest_live_header
+                {
+                    changed.push((entry_hash, status, oldest_live_header));
+                }
+            }
+            DatabaseResult::Ok(changed)
+        })?;
+        let count = changed.len();
+        for (entry_hash, status, oldest_live_header) in changed {
+            let key: PrefixBytesKey<P> = MiscMetaKey::OldestLiveHeader(entry_hash.clone()).into();
+            match oldest_live_header {
+                Some(header) => self
+                    .misc_meta
+                    .put(key, MiscMetaValue::OldestLiveHeader(header))?,
+                None => self.misc_meta.delete(key)?,
+            }
+            self.misc_meta.put(
+                MiscMetaKey::EntryStatus(entry_hash).into(),
+                MiscMetaValue::EntryStatus(status),
+            )?;
+        }
+        Ok(count)
+    }
+
+    /// Move an entry's status on through each event in turn.
+    /// Entries without a status yet start out [EntryDhtStatus::Pending].
+    fn transition_entry_dht_status(
+        &mut self,
+        basis: EntryHash,
+        events: impl IntoIterator<Item = EntryStatusEvent>,
+    ) -> DatabaseResult<()> {
+        let key: PrefixBytesKey<P> = MiscMetaKey::EntryStatus(basis).into();
+        let current = fresh_reader!(self.env, |r| self.misc_meta.get(&r, &key))?
+            .map(MiscMetaValue::entry_status)
+            .unwrap_or(EntryDhtStatus::Pending);
+        let status = events.into_iter().fold(current, transition);
+        self.misc_meta.put(key, MiscMetaValue::EntryStatus(status))
+    }
+
+    /// Compact the deleted links on a base into [LinkTombstone]s, once
+    /// there are at least `min_deleted` of them. Each link's metadata and
+    /// the DeleteLinks on it are dropped, leaving only their hashes.
+    /// Without this a base with a lot of churn keeps every link it has
+    /// ever had. Returns how many links were compacted.
+    pub fn compact_link_tombstones(
+        &mut self,
+        base: &EntryHash,
+        min_deleted: usize,
+    ) -> DatabaseResult<usize> {
+        let key = LinkMetaKey::Base(base);
+        let deleted = fresh_reader!(self.env, |r| {
+            self.links_meta
+                .iter_all_key_matches(&r, (&key).into())?
+                .filter_map(|(k, link)| {
+                    let removes = self
+                        .get_link_removes_on_link_add(&r, link.link_add_hash.clone())?
+                        .collect::<Vec<_>>()?;
+                    Ok(if removes.is_empty() {
+                        None
+                    } else {
+                        Some((k.to_vec(), link.link_add_hash, removes))
+                    })
+                })
+                .collect::<Vec<_>>()
+        })?;
+        if deleted.is_empty() || deleted.len() < min_deleted {
+            return Ok(0);
+        }
+        let count = deleted.len();
+        for (k, link_add_hash, removes) in deleted {
+            self.links_meta
+                .delete(PrefixBytesKey::from_key_bytes_or_friendly_panic(&k))?;
+            for remove in &removes {
+                self.system_meta.delete(
+                    SysMetaKey::from(link_add_hash.clone()).into(),
+                    SysMetaVal::DeleteLink(remove.clone()),
+                );
+            }
+            self.misc_meta.put(
+                MiscMetaKey::LinkTombstone(base.clone(), link_add_hash.clone()).into(),
+                MiscMetaValue::LinkTombstone(LinkTombstone {
+                    link_add_hash,
+                    link_remove_hashes: removes.into_iter().map(|r| r.header_hash).collect(),
+                }),
+            )?;
+        }
+        Ok(count)
+    }
+
+    /// What's left of a link on a base if it has been compacted
+    pub fn get_link_tombstone<R: Readable>(
+        &self,
+        r: &R,
+        base: &EntryHash,
+        link_add_hash: &HeaderHash,
+    ) -> DatabaseResult<Option<LinkTombstone>> {
+        Ok(self
+            .misc_meta
+            .get(
+                r,
+                &MiscMetaKey::LinkTombstone(base.clone(), link_add_hash.clone()).into(),
+            )?
+            .map(MiscMetaValue::link_tombstone))
+    }
+
+    /// Whether a link was compacted, so integrating its headers again
+    /// mustn't bring it back
+    fn is_link_compacted(
+        &self,
+        base: &EntryHash,
+        link_add_hash: &HeaderHash,
+    ) -> DatabaseResult<bool> {
+        fresh_reader!(self.env, |r| self.misc_meta.contains(
+            &r,
+            &MiscMetaKey::LinkTombstone(base.clone(), link_add_hash.clone()).into()
+        ))
     }
 
     #[cfg(test)]
@@ -387,6 +686,9 @@ where
         // Register the add link onto the base
         let link_add_hash =
             HeaderHashed::from_content_sync(Header::CreateLink(link_add.clone())).into_hash();
+        if self.is_link_compacted(&link_add.base_address, &link_add_hash)? {
+            return Ok(());
+        }
 
         // Put the link add to the links table
         let key = LinkMetaKey::from((&link_add, &link_add_hash));
@@ -411,6 +713,9 @@ where
 
     fn delete_link(&mut self, link_remove: DeleteLink) -> DatabaseResult<()> {
         let link_add_address = link_remove.link_add_address.clone();
+        if self.is_link_compacted(&link_remove.base_address, &link_add_address)? {
+            return Ok(());
+        }
         // Register the link remove address to the link add address
         let link_remove = HeaderHashed::from_content_sync(Header::DeleteLink(link_remove));
         let sys_val = SysMetaVal::DeleteLink(link_remove.into());
@@ -500,6 +805,10 @@ where
         self.update_entry_dht_status(entry_hash)
     }
 
+    fn register_conflict(&mut self, entry_hash: EntryHash) -> DatabaseResult<()> {
+        self.transition_entry_dht_status(entry_hash, Some(EntryStatusEvent::Forked))
+    }
+
     fn register_activity(&mut self, header: Header) -> DatabaseResult<()> {
         let author = header.author().clone();
         self.register_header_on_basis(author, EntryHeader::Activity(header))
@@ -620,6 +929,17 @@ where
             .unwrap_or(EntryDhtStatus::Dead))
     }
 
+    fn get_oldest_live_header<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        entry_hash: &EntryHash,
+    ) -> DatabaseResult<Option<TimedHeaderHash>> {
+        Ok(self
+            .misc_meta
+            .get(r, &MiscMetaKey::OldestLiveHeader(entry_hash.clone()).into())?
+            .map(MiscMetaValue::oldest_live_header))
+    }
+
     fn get_canonical_entry_hash(&self, _entry_hash: EntryHash) -> DatabaseResult<EntryHash> {
         todo!()
     }
@@ -648,27 +968,31 @@ where
         ))
     }
 
-    fn has_registered_store_element(&self, hash: &HeaderHash) -> DatabaseResult<bool> {
-        fresh_reader!(self.env, |r| self
-            .misc_meta
-            .contains(&r, &MiscMetaKey::StoreElement(hash.clone()).into()))
+    fn has_registered_store_element<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        hash: &HeaderHash,
+    ) -> DatabaseResult<bool> {
+        self.misc_meta
+            .contains(r, &MiscMetaKey::StoreElement(hash.clone()).into())
     }
 
-    fn has_registered_store_entry(
-        &self,
+    fn has_registered_store_entry<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
         entry_hash: &EntryHash,
         header_hash: &HeaderHash,
     ) -> DatabaseResult<bool> {
-        fresh_reader!(self.env, |r| self
-            .get_headers(&r, entry_hash.clone())?
-            .any(|h| Ok(h.header_hash == *header_hash)))
+        self.get_headers(r, entry_hash.clone())?
+            .any(|h| Ok(h.header_hash == *header_hash))
     }
 
-    fn has_any_registered_store_entry(&self, hash: &EntryHash) -> DatabaseResult<bool> {
-        fresh_reader!(self.env, |r| Ok(self
-            .get_headers(&r, hash.clone())?
-            .next()?
-            .is_some()))
+    fn has_any_registered_store_entry<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+        hash: &EntryHash,
+    ) -> DatabaseResult<bool> {
+        Ok(self.get_headers(r, hash.clone())?.next()?.is_some())
     }
 
     fn env(&self) -> &EnvironmentRead {
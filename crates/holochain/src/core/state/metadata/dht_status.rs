@@ -0,0 +1,120 @@
+//! The lifecycle of an [Entry]'s [EntryDhtStatus].
+//!
+//! An entry starts out Pending. Validation moves it to Live, Rejected or
+//! Abandoned. A Live entry is Dead once all its headers are deleted, and
+//! Live again if a header comes back. If its author forked their chain
+//! while creating it, the entry is in Conflict.
+//! Rejected, Abandoned and Conflict are final.
+//!
+//! [Entry]: holochain_types::Entry
+
+use holochain_state::prelude::*;
+use holochain_types::metadata::EntryDhtStatus;
+
+/// Something that happened to an [Entry] which can change its [EntryDhtStatus]
+///
+/// [Entry]: holochain_types::Entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStatusEvent {
+    /// A header creating the entry passed validation
+    Validated,
+    /// A header creating the entry failed validation
+    Rejected,
+    /// Validation of the entry was given up on
+    Abandoned,
+    /// Every header creating the entry has been deleted
+    AllHeadersDeleted,
+    /// A header creating the entry is no longer deleted
+    HeaderRestored,
+    /// The author created the entry on more than one fork of their chain
+    Forked,
+}
+
+/// The status an entry moves to after an event.
+/// Events which can't happen to an entry with this status leave it unchanged.
+pub fn transition(status: EntryDhtStatus, event: EntryStatusEvent) -> EntryDhtStatus {
+    use EntryDhtStatus::*;
+    match (status, event) {
+        (Pending, EntryStatusEvent::Validated) => Live,
+        (Pending, EntryStatusEvent::Rejected) => Rejected,
+        (Pending, EntryStatusEvent::Abandoned) => Abandoned,
+        (Live, EntryStatusEvent::AllHeadersDeleted) => Dead,
+        (Dead, EntryStatusEvent::HeaderRestored) => Live,
+        (Live, EntryStatusEvent::Forked) | (Dead, EntryStatusEvent::Forked) => Conflict,
+        (status, _) => status,
+    }
+}
+
+/// The validation every entry in a metadata store with this prefix has
+/// been through. Data that hasn't finished validation has none.
+pub fn validation_event<P: PrefixType>() -> Option<EntryStatusEvent> {
+    if P::PREFIX == IntegratedPrefix::PREFIX {
+        Some(EntryStatusEvent::Validated)
+    } else if P::PREFIX == RejectedPrefix::PREFIX {
+        Some(EntryStatusEvent::Rejected)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use EntryDhtStatus::*;
+    use EntryStatusEvent::*;
+
+    #[test]
+    fn validation_decides_pending_entries() {
+        assert_eq!(transition(Pending, Validated), Live);
+        assert_eq!(
+            transition(Pending, EntryStatusEvent::Rejected),
+            EntryDhtStatus::Rejected
+        );
+        assert_eq!(
+            transition(Pending, EntryStatusEvent::Abandoned),
+            EntryDhtStatus::Abandoned
+        );
+        // Deletes can't apply before the entry is valid
+        assert_eq!(transition(Pending, AllHeadersDeleted), Pending);
+        assert_eq!(transition(Pending, Forked), Pending);
+    }
+
+    #[test]
+    fn deletes_kill_live_entries() {
+        assert_eq!(transition(Live, AllHeadersDeleted), Dead);
+        assert_eq!(transition(Dead, HeaderRestored), Live);
+        assert_eq!(transition(Live, HeaderRestored), Live);
+        assert_eq!(transition(Dead, AllHeadersDeleted), Dead);
+        // Validation only happens once
+        assert_eq!(transition(Dead, Validated), Dead);
+        assert_eq!(transition(Live, EntryStatusEvent::Rejected), Live);
+    }
+
+    #[test]
+    fn forks_conflict() {
+        assert_eq!(transition(Live, Forked), Conflict);
+        assert_eq!(transition(Dead, Forked), Conflict);
+        assert_eq!(transition(Conflict, HeaderRestored), Conflict);
+        assert_eq!(transition(Conflict, AllHeadersDeleted), Conflict);
+    }
+
+    #[test]
+    fn judgements_are_final() {
+        for status in &[EntryDhtStatus::Rejected, EntryDhtStatus::Abandoned] {
+            for event in &[Validated, AllHeadersDeleted, HeaderRestored, Forked] {
+                assert_eq!(transition(*status, *event), *status);
+            }
+        }
+    }
+
+    #[test]
+    fn prefixes_carry_their_validation() {
+        assert_eq!(validation_event::<IntegratedPrefix>(), Some(Validated));
+        assert_eq!(
+            validation_event::<RejectedPrefix>(),
+            Some(EntryStatusEvent::Rejected)
+        );
+        assert_eq!(validation_event::<PendingPrefix>(), None);
+        assert_eq!(validation_event::<JudgedPrefix>(), None);
+    }
+}
@@ -68,6 +68,11 @@ pub(super) enum MiscMetaKey {
     EntryStatus(EntryHash),
     /// We have integrated a StoreElement for this key
     StoreElement(HeaderHash),
+    /// The oldest header on an entry which hasn't been deleted
+    OldestLiveHeader(EntryHash),
+    /// A deleted link on a base which has been compacted,
+    /// keyed by the base and the link's [CreateLink] hash
+    LinkTombstone(EntryHash, HeaderHash),
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -78,6 +83,21 @@ pub(super) enum MiscMetaValue {
     EntryStatus(EntryDhtStatus),
     /// We have integrated a StoreElement for this key
     StoreElement(()),
+    /// The oldest header on an entry which hasn't been deleted
+    OldestLiveHeader(TimedHeaderHash),
+    /// A deleted link on a base which has been compacted
+    LinkTombstone(LinkTombstone),
+}
+
+/// What's kept of a deleted link once it's compacted: the hashes of the
+/// [CreateLink] and the [DeleteLink]s on it, so they can still be checked
+/// against the headers, but nothing else
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct LinkTombstone {
+    /// Hash of the [CreateLink] [Header] that created the link
+    pub link_add_hash: HeaderHash,
+    /// Hashes of the [DeleteLink] [Header]s that deleted it
+    pub link_remove_hashes: Vec<HeaderHash>,
 }
 
 /// Subset of headers for the sys meta db
@@ -150,6 +170,13 @@ impl<'a> LinkMetaKey<'a> {
             Base(b) | BaseZome(b, _) | BaseZomeTag(b, _, _) | Full(b, _, _, _) => b,
         }
     }
+
+    /// Whether a link matches this key in the same way a query by the key
+    /// matches links in the database, i.e. tags match by prefix
+    pub fn matches(&self, link_add: &CreateLink, link_add_hash: &HeaderHash) -> bool {
+        let link_key = BytesKey::from(LinkMetaKey::from((link_add, link_add_hash)));
+        link_key.0.starts_with(&BytesKey::from(self).0)
+    }
 }
 
 impl MiscMetaValue {
@@ -160,6 +187,20 @@ impl MiscMetaValue {
         }
     }
 
+    pub(super) fn oldest_live_header(self) -> TimedHeaderHash {
+        match self {
+            MiscMetaValue::OldestLiveHeader(h) => h,
+            _ => unreachable!("Tried to go from {:?} to {:?}", self, "oldest_live_header"),
+        }
+    }
+
+    pub(super) fn link_tombstone(self) -> LinkTombstone {
+        match self {
+            MiscMetaValue::LinkTombstone(t) => t,
+            _ => unreachable!("Tried to go from {:?} to {:?}", self, "link_tombstone"),
+        }
+    }
+
     pub(super) fn new_store_element() -> Self {
         Self::StoreElement(())
     }
@@ -0,0 +1,186 @@
+//! An ordered index of the links of link types which declare a [LinkTagOrder].
+//!
+//! The links meta db is keyed by the raw tag bytes, which only supports
+//! prefix matches. Here each link is also keyed by its tag's sort key so a
+//! range of values, like all links between two timestamps, is a single scan.
+//!
+//! Only valid integrated links are indexed. Removes are kept in the
+//! [MetadataBuf] and must be checked by the caller.
+
+use super::*;
+use holochain_state::{buffer::KvBufFresh, db::META_VAULT_LINK_TAGS};
+use holochain_zome_types::{header::LinkTypeIndex, link::LinkTagOrder};
+
+/// Database type for the ordered link tags
+pub type LinkTagStore = KvBufFresh<BytesKey, LinkMetaVal>;
+
+/// Buffer for the ordered index of typed links
+pub struct LinkTagIndexBuf {
+    store: LinkTagStore,
+}
+
+impl LinkTagIndexBuf {
+    /// Create a new buffer for the ordered link tags database of the vault
+    pub fn vault(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*META_VAULT_LINK_TAGS)?;
+        Ok(Self {
+            store: LinkTagStore::new(env, db),
+        })
+    }
+
+    /// Index a typed link by the value at the start of its tag.
+    /// Links whose tag doesn't hold a value of this order are skipped.
+    pub fn add_link(
+        &mut self,
+        link_add: CreateLink,
+        link_add_hash: HeaderHash,
+        tag_order: LinkTagOrder,
+    ) -> DatabaseResult<()> {
+        let link_type = match link_add.link_type {
+            Some(link_type) => link_type,
+            None => return Ok(()),
+        };
+        let sort_key = match tag_order.sort_key(&link_add.tag) {
+            Some(sort_key) => sort_key,
+            None => return Ok(()),
+        };
+        let mut key = prefix(&link_add.base_address, link_add.zome_id, link_type);
+        key.extend_from_slice(&sort_key);
+        key.extend_from_slice(link_add_hash.as_ref());
+        self.store.put(
+            BytesKey(key),
+            LinkMetaVal {
+                link_add_hash,
+                target: link_add.target_address,
+                timestamp: link_add.timestamp.into(),
+                zome_id: link_add.zome_id,
+                tag: link_add.tag,
+            },
+        )
+    }
+
+    /// Get the links of a type on a base with tags from `start` up to
+    /// but not including `end`, in the order of their tags.
+    /// Bounds which don't hold a value of this order are ignored.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_links_in_range<R: Readable>(
+        &self,
+        r: &R,
+        base: &EntryHash,
+        zome_id: ZomeId,
+        link_type: LinkTypeIndex,
+        tag_order: LinkTagOrder,
+        start: Option<&LinkTag>,
+        end: Option<&LinkTag>,
+    ) -> DatabaseResult<Vec<LinkMetaVal>> {
+        let prefix = prefix(base, zome_id, link_type);
+        let mut from = prefix.clone();
+        if let Some(start) = start.and_then(|start| tag_order.sort_key(start)) {
+            from.extend_from_slice(&start);
+        }
+        let end = end.and_then(|end| tag_order.sort_key(end));
+        let value_start = prefix.len();
+        let value_end = value_start + tag_order.value_len();
+        self.store
+            .iter_from(r, BytesKey(from))?
+            .take_while(|(k, _)| {
+                Ok(k.starts_with(&prefix)
+                    && end
+                        .as_ref()
+                        .map(|end| &k[value_start..value_end] < end.as_slice())
+                        .unwrap_or(true))
+            })
+            .map(|(_, v)| Ok(v))
+            .collect()
+    }
+}
+
+impl BufferedStore for LinkTagIndexBuf {
+    type Error = DatabaseError;
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> DatabaseResult<()> {
+        self.store.flush_to_txn_ref(writer)
+    }
+}
+
+/// The part of the key shared by every link of a type on a base
+fn prefix(base: &EntryHash, zome_id: ZomeId, link_type: LinkTypeIndex) -> Vec<u8> {
+    [base.as_ref(), &[u8::from(zome_id)], &[u8::from(link_type)]].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixt::CreateLinkFixturator;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{EntryHashFixturator, HeaderHashFixturator};
+    use holochain_state::{
+        env::{ReadManager, WriteManager},
+        test_utils::test_cell_env,
+    };
+
+    #[tokio::test(threaded_scheduler)]
+    async fn get_links_in_range() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let base = fixt!(EntryHash);
+        let order = LinkTagOrder::I64;
+
+        let mut link_add = fixt!(CreateLink);
+        link_add.base_address = base.clone();
+        link_add.zome_id = 0.into();
+        link_add.link_type = Some(0.into());
+        {
+            let mut buf = LinkTagIndexBuf::vault(env.clone().into()).unwrap();
+            for n in vec![30, -5, 10, 0, 20] {
+                let mut link_add = link_add.clone();
+                link_add.tag = LinkTag::from_i64(n);
+                buf.add_link(link_add, fixt!(HeaderHash), order).unwrap();
+            }
+            // Another type on the same base
+            let mut other_type = link_add.clone();
+            other_type.link_type = Some(1.into());
+            other_type.tag = LinkTag::from_i64(10);
+            buf.add_link(other_type, fixt!(HeaderHash), order).unwrap();
+            // A tag without a value isn't indexed
+            let mut short_tag = link_add.clone();
+            short_tag.tag = LinkTag::new(vec![1]);
+            buf.add_link(short_tag, fixt!(HeaderHash), order).unwrap();
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+
+        let reader = env_ref.reader().unwrap();
+        let buf = LinkTagIndexBuf::vault(env.clone().into()).unwrap();
+        let tags = |start: Option<i64>, end: Option<i64>| -> Vec<LinkTag> {
+            buf.get_links_in_range(
+                &reader,
+                &base,
+                0.into(),
+                0.into(),
+                order,
+                start.map(LinkTag::from_i64).as_ref(),
+                end.map(LinkTag::from_i64).as_ref(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|link| link.tag)
+            .collect()
+        };
+
+        assert_eq!(
+            tags(None, None),
+            vec![-5, 0, 10, 20, 30]
+                .into_iter()
+                .map(LinkTag::from_i64)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            tags(Some(0), Some(20)),
+            vec![LinkTag::from_i64(0), LinkTag::from_i64(10)]
+        );
+        assert_eq!(tags(Some(25), None), vec![LinkTag::from_i64(30)]);
+        assert!(tags(Some(40), None).is_empty());
+    }
+}
@@ -902,3 +902,74 @@ async fn links_on_same_tag() {
         );
     }
 }
+
+#[tokio::test(threaded_scheduler)]
+async fn compacts_deleted_links() {
+    let test_env = test_cell_env();
+    let arc = test_env.env();
+    let env = arc.guard();
+
+    let mut td = fixtures(arc.clone(), 4).await;
+    let base_hash = td[0].base_hash.clone();
+    let base_hash = &base_hash;
+    for d in td.iter_mut() {
+        d.base_hash = base_hash.clone();
+        d.link_add.base_address = base_hash.clone();
+        let link_add_hash =
+            HeaderHashed::from_content_sync(Header::CreateLink(d.link_add.clone())).into_hash();
+        d.expected_link.link_add_hash = link_add_hash.clone();
+        d.link_remove.link_add_address = link_add_hash;
+        d.link_remove.base_address = base_hash.clone();
+    }
+    {
+        let mut meta_buf = MetadataBuf::vault(arc.clone().into()).unwrap();
+        for d in td.iter() {
+            d.add_link(&mut meta_buf).await;
+        }
+        td[0].delete_link(&mut meta_buf).await;
+        td[1].delete_link(&mut meta_buf).await;
+        // Not enough deleted links yet
+        assert_eq!(meta_buf.compact_link_tombstones(base_hash, 3).unwrap(), 0);
+        env.with_commit(|writer| meta_buf.flush_to_txn(writer))
+            .unwrap();
+    }
+    {
+        let mut meta_buf = MetadataBuf::vault(arc.clone().into()).unwrap();
+        assert_eq!(meta_buf.compact_link_tombstones(base_hash, 2).unwrap(), 2);
+        env.with_commit(|writer| meta_buf.flush_to_txn(writer))
+            .unwrap();
+    }
+
+    let mut meta_buf = MetadataBuf::vault(arc.clone().into()).unwrap();
+    TestData::only_these_on_base(&td[2..], here!("compacted"), &meta_buf);
+    let all = fresh_reader_test!(arc, |r| meta_buf
+        .get_links_all(&r, &LinkMetaKey::Base(base_hash))
+        .unwrap()
+        .collect::<Vec<_>>()
+        .unwrap());
+    assert_eq!(all.len(), 2);
+
+    // Only the hashes of the deleted links are kept
+    for d in &td[..2] {
+        let tombstone = fresh_reader_test!(arc, |r| meta_buf
+            .get_link_tombstone(&r, base_hash, &d.expected_link.link_add_hash)
+            .unwrap());
+        let expected = LinkTombstone {
+            link_add_hash: d.expected_link.link_add_hash.clone(),
+            link_remove_hashes: vec![HeaderHash::with_data_sync(&Header::DeleteLink(
+                d.link_remove.clone(),
+            ))],
+        };
+        assert_eq!(tombstone, Some(expected));
+    }
+    for d in &td[2..] {
+        let tombstone = fresh_reader_test!(arc, |r| meta_buf
+            .get_link_tombstone(&r, base_hash, &d.expected_link.link_add_hash)
+            .unwrap());
+        assert_eq!(tombstone, None);
+    }
+
+    // Integrating a compacted link again doesn't bring it back
+    td[0].add_link(&mut meta_buf).await;
+    TestData::only_these_on_base(&td[2..], here!("not brought back"), &meta_buf);
+}
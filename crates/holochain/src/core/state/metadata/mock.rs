@@ -29,11 +29,13 @@ mock! {
         ) -> DatabaseResult<()>;
         fn sync_deregister_update(&mut self, update: header::Update) -> DatabaseResult<()>;
         fn sync_deregister_delete(&mut self, delete: header::Delete) -> DatabaseResult<()>;
+        fn sync_register_conflict(&mut self, entry_hash: EntryHash) -> DatabaseResult<()>;
         fn register_raw_on_entry(&mut self, entry_hash: EntryHash, value: SysMetaVal) -> DatabaseResult<()>;
         fn register_raw_on_header(&mut self, header_hash: HeaderHash, value: SysMetaVal);
         fn sync_deregister_add_link(&mut self, link_add: CreateLink) -> DatabaseResult<()>;
         fn sync_deregister_delete_link(&mut self, link_remove: DeleteLink) -> DatabaseResult<()>;
         fn get_dht_status(&self, entry_hash: &EntryHash) -> DatabaseResult<EntryDhtStatus>;
+        fn get_oldest_live_header(&self, entry_hash: &EntryHash) -> DatabaseResult<Option<TimedHeaderHash>>;
         fn get_canonical_entry_hash(&self, entry_hash: EntryHash) -> DatabaseResult<EntryHash>;
         fn get_canonical_header_hash(&self, header_hash: HeaderHash) -> DatabaseResult<HeaderHash>;
         fn get_headers(
@@ -99,6 +101,14 @@ impl MetadataBufT for MockMetadataBuf {
         MockMetadataBuf::get_dht_status(&self, entry_hash)
     }
 
+    fn get_oldest_live_header<'r, R: Readable>(
+        &'r self,
+        _r: &'r R,
+        entry_hash: &EntryHash,
+    ) -> DatabaseResult<Option<TimedHeaderHash>> {
+        MockMetadataBuf::get_oldest_live_header(&self, entry_hash)
+    }
+
     fn get_canonical_header_hash(&self, header_hash: HeaderHash) -> DatabaseResult<HeaderHash> {
         self.get_canonical_header_hash(header_hash)
     }
@@ -203,6 +213,10 @@ impl MetadataBufT for MockMetadataBuf {
         self.sync_deregister_delete(delete)
     }
 
+    fn register_conflict(&mut self, entry_hash: EntryHash) -> DatabaseResult<()> {
+        self.sync_register_conflict(entry_hash)
+    }
+
     fn deregister_add_link(&mut self, link_add: CreateLink) -> DatabaseResult<()> {
         self.sync_deregister_add_link(link_add)
     }
@@ -223,18 +237,27 @@ impl MetadataBufT for MockMetadataBuf {
     fn register_raw_on_header(&mut self, header_hash: HeaderHash, value: SysMetaVal) {
         self.register_raw_on_header(header_hash, value)
     }
-    fn has_registered_store_element(&self, hash: &HeaderHash) -> DatabaseResult<bool> {
-        self.has_registered_store_element(hash)
+    fn has_registered_store_element<'r, R: Readable>(
+        &'r self,
+        _r: &'r R,
+        hash: &HeaderHash,
+    ) -> DatabaseResult<bool> {
+        MockMetadataBuf::has_registered_store_element(&self, hash)
     }
-    fn has_registered_store_entry(
-        &self,
+    fn has_registered_store_entry<'r, R: Readable>(
+        &'r self,
+        _r: &'r R,
         entry_hash: &EntryHash,
         header_hash: &HeaderHash,
     ) -> DatabaseResult<bool> {
-        self.has_registered_store_entry(entry_hash, header_hash)
+        MockMetadataBuf::has_registered_store_entry(&self, entry_hash, header_hash)
     }
-    fn has_any_registered_store_entry(&self, hash: &EntryHash) -> DatabaseResult<bool> {
-        self.has_any_registered_store_entry(hash)
+    fn has_any_registered_store_entry<'r, R: Readable>(
+        &'r self,
+        _r: &'r R,
+        hash: &EntryHash,
+    ) -> DatabaseResult<bool> {
+        MockMetadataBuf::has_any_registered_store_entry(&self, hash)
     }
 
     fn env(&self) -> &EnvironmentRead {
@@ -7,9 +7,13 @@ pub enum MetaGetStatus<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::core::state::metadata::{
-        EntryDhtStatus, MetadataBuf, MetadataBufT, TimedHeaderHash,
+    use crate::core::state::{
+        element_buf::ElementBuf,
+        metadata::{
+            EntryDhtStatus, MetadataBuf, MetadataBufT, MiscMetaKey, MiscMetaValue, TimedHeaderHash,
+        },
     };
+    use crate::fixt::SignatureFixturator;
     use ::fixt::prelude::*;
     use fallible_iterator::FallibleIterator;
     use header::Create;
@@ -17,6 +21,7 @@ mod tests {
     use holo_hash::*;
     use holochain_state::{prelude::*, test_utils::test_cell_env};
     use holochain_types::{
+        element::SignedHeaderHashed,
         fixt::{AppEntryTypeFixturator, HeaderBuilderCommonFixturator},
         header::NewEntryHeader,
         HeaderHashed,
@@ -744,4 +749,194 @@ mod tests {
             .unwrap();
         assert_eq!(status, EntryDhtStatus::Dead);
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn entry_dht_status_follows_validation() {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+        let mut fx = TestFixtures::new();
+        let entry_hash = fx.entry_hash();
+        let mut entry_creates = Vec::new();
+        let mut entry_deletes = Vec::new();
+        let mut entry_updates = Vec::new();
+        let mut delete_updates = Vec::new();
+
+        create_data(
+            &mut entry_creates,
+            &mut entry_deletes,
+            &mut entry_updates,
+            &mut delete_updates,
+            &entry_hash,
+            &mut fx,
+        )
+        .await;
+
+        let reader = env.reader().unwrap();
+        let mut pending = MetadataBuf::pending(arc.clone().into()).unwrap();
+        let mut rejected = MetadataBuf::rejected(arc.clone().into()).unwrap();
+        let mut vault = MetadataBuf::vault(arc.clone().into()).unwrap();
+        for header in entry_creates {
+            pending.register_header(header.clone()).unwrap();
+            rejected.register_header(header.clone()).unwrap();
+            vault.register_header(header).unwrap();
+        }
+        assert_eq!(
+            pending.get_dht_status(&reader, &entry_hash).unwrap(),
+            EntryDhtStatus::Pending
+        );
+        assert_eq!(
+            rejected.get_dht_status(&reader, &entry_hash).unwrap(),
+            EntryDhtStatus::Rejected
+        );
+        assert_eq!(
+            vault.get_dht_status(&reader, &entry_hash).unwrap(),
+            EntryDhtStatus::Live
+        );
+
+        // Conflicts outlast deletes
+        vault.register_conflict(entry_hash.clone()).unwrap();
+        for delete in entry_deletes {
+            vault.register_delete(delete).unwrap();
+        }
+        assert_eq!(
+            vault.get_dht_status(&reader, &entry_hash).unwrap(),
+            EntryDhtStatus::Conflict
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn oldest_live_header_is_kept_with_status() {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+        let mut fx = TestFixtures::new();
+        let entry_hash = fx.entry_hash();
+        let mut entry_creates = Vec::new();
+        let mut entry_deletes = Vec::new();
+        let mut entry_updates = Vec::new();
+        let mut delete_updates = Vec::new();
+
+        create_data(
+            &mut entry_creates,
+            &mut entry_deletes,
+            &mut entry_updates,
+            &mut delete_updates,
+            &entry_hash,
+            &mut fx,
+        )
+        .await;
+
+        let reader = env.reader().unwrap();
+        let mut meta_buf = MetadataBuf::vault(arc.clone().into()).unwrap();
+        for header in entry_creates {
+            meta_buf.register_header(header).unwrap();
+        }
+        let expected = meta_buf
+            .get_headers(&reader, entry_hash.clone())
+            .unwrap()
+            .min()
+            .unwrap();
+        assert!(expected.is_some());
+        assert_eq!(
+            meta_buf
+                .get_oldest_live_header(&reader, &entry_hash)
+                .unwrap(),
+            expected
+        );
+
+        // The rebuild finds the same answer, so changes nothing
+        let elements = ElementBuf::vault(arc.clone().into(), true).unwrap();
+        assert_eq!(meta_buf.rebuild_entry_dht_statuses(&elements).unwrap(), 0);
+        assert_eq!(
+            meta_buf
+                .get_oldest_live_header(&reader, &entry_hash)
+                .unwrap(),
+            expected
+        );
+
+        for delete in entry_deletes {
+            meta_buf.register_delete(delete).unwrap();
+        }
+        assert_eq!(
+            meta_buf
+                .get_oldest_live_header(&reader, &entry_hash)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            meta_buf.get_dht_status(&reader, &entry_hash).unwrap(),
+            EntryDhtStatus::Dead
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn rebuild_recomputes_final_statuses() {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let env = arc.guard();
+        let mut fx = TestFixtures::new();
+        let mut elements = ElementBuf::vault(arc.clone().into(), true).unwrap();
+        let mut meta_buf = MetadataBuf::vault(arc.clone().into()).unwrap();
+
+        // An entry whose author created it on two forks of their chain
+        let forked_hash = fx.entry_hash();
+        let common = fx.common();
+        let forked_common = HeaderBuilderCommon {
+            author: common.author.clone(),
+            timestamp: common.timestamp.clone(),
+            header_seq: common.header_seq,
+            prev_header: fx.header_hash(),
+        };
+        // And an entry with a single header
+        let live_hash = fx.entry_hash();
+        let live_common = fx.common();
+        for (entry_hash, common) in vec![
+            (forked_hash.clone(), common),
+            (forked_hash.clone(), forked_common),
+            (live_hash.clone(), live_common),
+        ] {
+            let create = builder::Create {
+                entry_hash,
+                entry_type: fx.entry_type(),
+            }
+            .build(common);
+            let header = HeaderHashed::from_content_sync(create.clone().into());
+            elements
+                .put(
+                    SignedHeaderHashed::with_presigned(header, fixt!(Signature)),
+                    None,
+                )
+                .unwrap();
+            meta_buf
+                .register_header(NewEntryHeader::Create(create))
+                .unwrap();
+        }
+        // The live entry's status has drifted to one which is final
+        meta_buf
+            .misc_meta
+            .put(
+                MiscMetaKey::EntryStatus(live_hash.clone()).into(),
+                MiscMetaValue::EntryStatus(EntryDhtStatus::Rejected),
+            )
+            .unwrap();
+
+        let reader = env.reader().unwrap();
+        assert_eq!(
+            meta_buf.get_dht_status(&reader, &forked_hash).unwrap(),
+            EntryDhtStatus::Live
+        );
+        assert_eq!(meta_buf.rebuild_entry_dht_statuses(&elements).unwrap(), 2);
+        assert_eq!(
+            meta_buf.get_dht_status(&reader, &forked_hash).unwrap(),
+            EntryDhtStatus::Conflict
+        );
+        assert_eq!(
+            meta_buf.get_dht_status(&reader, &live_hash).unwrap(),
+            EntryDhtStatus::Live
+        );
+
+        // Nothing is left to change
+        assert_eq!(meta_buf.rebuild_entry_dht_statuses(&elements).unwrap(), 0);
+    }
 }
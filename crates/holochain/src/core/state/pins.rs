@@ -0,0 +1,170 @@
+//! The hashes an app or admin has pinned, so their data is always held locally.
+//!
+//! Pinning a hash fetches its data into the cache. Pinned data must never be
+//! dropped from the cache, and the cell serves it to other agents as if it
+//! were an authority for it, even if the hash is outside its arc.
+
+use super::{
+    cascade::{error::CascadeResult, Cascade},
+    element_buf::ElementBuf,
+    metadata::{BytesKey, MetadataBuf},
+};
+use fallible_iterator::FallibleIterator;
+use holo_hash::AnyDhtHash;
+use holochain_p2p::HolochainP2pCell;
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh,
+    db::PINS,
+    env::{EnvironmentWrite, WriteManager},
+    error::{DatabaseError, DatabaseResult},
+    prelude::{BufferedStore, EnvironmentRead, GetDb, Readable, Writer},
+};
+use holochain_types::Timestamp;
+
+/// Database type for the Pins
+pub type PinsStore = KvBufFresh<BytesKey, PinValue>;
+
+/// The value stored for each pin
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PinValue {
+    /// The pinned hash
+    pub hash: AnyDhtHash,
+    /// When it was pinned
+    pub since: Timestamp,
+}
+
+/// Buffer for the hashes pinned on this cell
+pub struct PinsBuf {
+    store: PinsStore,
+}
+
+impl PinsBuf {
+    /// Create a new buffer for the Pins database
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*PINS)?;
+        Ok(Self {
+            store: PinsStore::new(env, db),
+        })
+    }
+
+    /// Pin a hash. Pinning it again just updates when it was pinned.
+    pub fn pin(&mut self, hash: AnyDhtHash) -> DatabaseResult<()> {
+        self.store.put(
+            key(&hash),
+            PinValue {
+                hash,
+                since: Timestamp::now(),
+            },
+        )
+    }
+
+    /// Unpin a hash, if it's pinned
+    pub fn unpin(&mut self, hash: &AnyDhtHash) -> DatabaseResult<()> {
+        self.store.delete(key(hash))
+    }
+
+    /// Whether a hash is pinned
+    pub fn is_pinned(&self, hash: &AnyDhtHash) -> DatabaseResult<bool> {
+        self.store.contains(&key(hash))
+    }
+
+    /// Every pinned hash
+    pub fn pins<R: Readable>(&self, r: &R) -> DatabaseResult<Vec<AnyDhtHash>> {
+        self.store.iter(r)?.map(|(_, v)| Ok(v.hash)).collect()
+    }
+}
+
+impl BufferedStore for PinsBuf {
+    type Error = DatabaseError;
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> Result<(), Self::Error> {
+        self.store.flush_to_txn_ref(writer)
+    }
+}
+
+/// Pin hashes, first fetching any data that isn't held yet into the cache
+pub async fn pin_hashes(
+    env: EnvironmentWrite,
+    network: HolochainP2pCell,
+    hashes: Vec<AnyDhtHash>,
+) -> CascadeResult<()> {
+    let env_ref = env.guard();
+    let element_vault = ElementBuf::vault(env.clone().into(), false)?;
+    let meta_vault = MetadataBuf::vault(env.clone().into())?;
+    let mut element_cache = ElementBuf::cache(env.clone().into())?;
+    let mut meta_cache = MetadataBuf::cache(env.clone().into())?;
+    let mut pins = PinsBuf::new(env.clone().into())?;
+    let mut cascade = Cascade::new(
+        env.clone().into(),
+        &element_vault,
+        &meta_vault,
+        &mut element_cache,
+        &mut meta_cache,
+        network,
+    );
+    for hash in hashes {
+        cascade.dht_get(hash.clone(), Default::default()).await?;
+        pins.pin(hash)?;
+    }
+    env_ref.with_commit::<DatabaseError, _, _>(|writer| {
+        element_cache.flush_to_txn(writer)?;
+        meta_cache.flush_to_txn(writer)?;
+        pins.flush_to_txn(writer)
+    })?;
+    Ok(())
+}
+
+fn key(hash: &AnyDhtHash) -> BytesKey {
+    BytesKey(hash.get_full_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{EntryHashFixturator, HeaderHashFixturator};
+    use holochain_state::{env::ReadManager, test_utils::test_cell_env};
+
+    #[tokio::test(threaded_scheduler)]
+    async fn pin_and_unpin() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let entry: AnyDhtHash = fixt!(EntryHash).into();
+        let header: AnyDhtHash = fixt!(HeaderHash).into();
+
+        {
+            let mut buf = PinsBuf::new(env.clone().into()).unwrap();
+            buf.pin(entry.clone()).unwrap();
+            buf.pin(header.clone()).unwrap();
+            // Pinning twice is one pin
+            buf.pin(entry.clone()).unwrap();
+            // Pins are seen before they're flushed
+            assert!(buf.is_pinned(&entry).unwrap());
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+        {
+            let reader = env_ref.reader().unwrap();
+            let buf = PinsBuf::new(env.clone().into()).unwrap();
+            let mut pins = buf.pins(&reader).unwrap();
+            pins.sort();
+            let mut expected = vec![entry.clone(), header.clone()];
+            expected.sort();
+            assert_eq!(pins, expected);
+        }
+        {
+            let mut buf = PinsBuf::new(env.clone().into()).unwrap();
+            buf.unpin(&entry).unwrap();
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+        let reader = env_ref.reader().unwrap();
+        let buf = PinsBuf::new(env.clone().into()).unwrap();
+        assert!(!buf.is_pinned(&entry).unwrap());
+        assert!(buf.is_pinned(&header).unwrap());
+        assert_eq!(buf.pins(&reader).unwrap(), vec![header]);
+    }
+}
@@ -0,0 +1,277 @@
+//! How far the publish workflow has got with each authored op.
+//!
+//! For every op it authored a cell records when the op was last published
+//! and which validators have sent a receipt for it. This lives in its own
+//! database, so a restarted conductor picks up where publishing left off:
+//! ops which already have enough receipts aren't published again, and ops
+//! published a moment before the restart wait out the usual interval.
+//!
+//! Receipts are recorded by validator, so a validator which sends the same
+//! receipt twice, or again after a restart, is only counted once.
+//! An op with no record hasn't been published yet.
+
+use super::{dht_op_integration::AuthoredDhtOpsStore, workspace::WorkspaceResult};
+use fallible_iterator::FallibleIterator;
+use holo_hash::{AgentPubKey, DhtOpHash};
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh,
+    db::{AUTHORED_DHT_OPS, PUBLISH_PROGRESS},
+    env::{EnvironmentRead, EnvironmentWrite, WriteManager},
+    error::{DatabaseError, DatabaseResult},
+    fresh_reader,
+    prelude::{BufferedStore, GetDb, Readable, Writer},
+};
+use holochain_types::Timestamp;
+use std::collections::BTreeSet;
+use tracing::*;
+
+/// Database type for the PublishProgress
+pub type PublishProgressStore = KvBufFresh<DhtOpHash, PublishProgress>;
+
+/// How far publishing an authored op has got
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct PublishProgress {
+    /// The validators which have sent a receipt for the op
+    pub receipts_from: BTreeSet<AgentPubKey>,
+    /// Time last published, None if never published
+    pub last_publish_time: Option<Timestamp>,
+    /// How many receipts the op needs, from its entry def's required
+    /// validations. None until the op is first published.
+    #[serde(default)]
+    pub required_receipts: Option<u32>,
+}
+
+impl PublishProgress {
+    /// How many validators have sent a receipt
+    pub fn receipt_count(&self) -> u32 {
+        self.receipts_from.len() as u32
+    }
+
+    /// Whether enough validators have sent a receipt, needing `default`
+    /// if the op's required validations aren't known yet
+    pub fn is_saturated(&self, default: u32) -> bool {
+        self.receipt_count() >= self.required_receipts.unwrap_or(default)
+    }
+}
+
+/// Buffer for the publish progress of this cell's authored ops
+pub struct PublishProgressBuf {
+    store: PublishProgressStore,
+}
+
+impl PublishProgressBuf {
+    /// Create a new buffer for the PublishProgress database
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*PUBLISH_PROGRESS)?;
+        Ok(Self {
+            store: PublishProgressStore::new(env, db),
+        })
+    }
+
+    /// The progress of an op, which is empty if it hasn't been published
+    pub fn get(&self, op_hash: &DhtOpHash) -> DatabaseResult<PublishProgress> {
+        Ok(self.store.get(op_hash)?.unwrap_or_default())
+    }
+
+    /// Record that an op was published at this time
+    pub fn record_publish(&mut self, op_hash: DhtOpHash, at: Timestamp) -> DatabaseResult<()> {
+        let mut progress = self.get(&op_hash)?;
+        progress.last_publish_time = Some(at);
+        self.store.put(op_hash, progress)
+    }
+
+    /// Record how many receipts an op needs
+    pub fn set_required_receipts(
+        &mut self,
+        op_hash: DhtOpHash,
+        required_receipts: u32,
+    ) -> DatabaseResult<()> {
+        let mut progress = self.get(&op_hash)?;
+        progress.required_receipts = Some(required_receipts);
+        self.store.put(op_hash, progress)
+    }
+
+    /// Record a receipt for an op from this validator.
+    /// Returns false if the validator had already sent one.
+    pub fn record_receipt(
+        &mut self,
+        op_hash: DhtOpHash,
+        validator: AgentPubKey,
+    ) -> DatabaseResult<bool> {
+        let mut progress = self.get(&op_hash)?;
+        if !progress.receipts_from.insert(validator) {
+            return Ok(false);
+        }
+        self.store.put(op_hash, progress)?;
+        Ok(true)
+    }
+
+    /// Replace the validators counted for an op, i.e. with the authorities
+    /// which were found to still hold it
+    pub fn set_receipts_from(
+        &mut self,
+        op_hash: DhtOpHash,
+        receipts_from: BTreeSet<AgentPubKey>,
+    ) -> DatabaseResult<()> {
+        let mut progress = self.get(&op_hash)?;
+        progress.receipts_from = receipts_from;
+        self.store.put(op_hash, progress)
+    }
+
+    /// Every op which has a record
+    pub fn iter_all<R: Readable>(
+        &self,
+        r: &R,
+    ) -> DatabaseResult<Vec<(DhtOpHash, PublishProgress)>> {
+        self.store
+            .iter(r)?
+            .map(|(k, v)| Ok((DhtOpHash::with_pre_hashed(k.to_vec()), v)))
+            .collect()
+    }
+
+    fn delete(&mut self, op_hash: DhtOpHash) -> DatabaseResult<()> {
+        self.store.delete(op_hash)
+    }
+}
+
+impl BufferedStore for PublishProgressBuf {
+    type Error = DatabaseError;
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> Result<(), Self::Error> {
+        self.store.flush_to_txn_ref(writer)
+    }
+}
+
+/// Where publishing stood when a cell started
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PublishRehydrationReport {
+    /// Authored ops which still need more receipts
+    pub unfinished: usize,
+    /// Authored ops which have enough receipts
+    pub finished: usize,
+    /// Records for ops which are no longer authored, now removed
+    pub orphaned: usize,
+}
+
+/// Load the publish progress left by the last run, before the publish
+/// workflow starts, and drop any records for ops which are no longer in
+/// the authored store. Ops whose required receipts aren't known yet
+/// need `default_target` receipts.
+pub fn rehydrate_publish_progress(
+    env: &EnvironmentWrite,
+    default_target: u32,
+) -> WorkspaceResult<PublishRehydrationReport> {
+    let authored_dht_ops: AuthoredDhtOpsStore =
+        KvBufFresh::new(env.clone().into(), env.get_db(&*AUTHORED_DHT_OPS)?);
+    let mut progress = PublishProgressBuf::new(env.clone().into())?;
+    let mut report = PublishRehydrationReport::default();
+
+    let (authored, recorded) = fresh_reader!(env, |r| {
+        let authored: BTreeSet<DhtOpHash> = authored_dht_ops
+            .iter(&r)?
+            .map(|(k, _)| Ok(DhtOpHash::with_pre_hashed(k.to_vec())))
+            .collect()?;
+        DatabaseResult::Ok((authored, progress.iter_all(&r)?))
+    })?;
+    for (op_hash, p) in recorded {
+        if !authored.contains(&op_hash) {
+            progress.delete(op_hash)?;
+            report.orphaned += 1;
+        } else if p.is_saturated(default_target) {
+            report.finished += 1;
+        }
+    }
+    report.unfinished = authored.len() - report.finished;
+
+    if report.orphaned > 0 {
+        env.guard()
+            .with_commit(|writer| progress.flush_to_txn(writer))?;
+    }
+    debug!(?report, "Rehydrated publish progress");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::dht_op_integration::AuthoredDhtOpsValue;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{AgentPubKeyFixturator, DhtOpHashFixturator, HeaderHashFixturator};
+    use holochain_state::{env::ReadManager, test_utils::test_cell_env};
+    use holochain_types::dht_op::DhtOpLight;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn progress_survives_a_restart() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let alice = fixt!(AgentPubKey);
+        let bob = fixt!(AgentPubKey);
+        let finished = fixt!(DhtOpHash);
+        let unfinished = fixt!(DhtOpHash);
+        let never_published = fixt!(DhtOpHash);
+        let orphan = fixt!(DhtOpHash);
+
+        {
+            let mut authored_dht_ops: AuthoredDhtOpsStore =
+                KvBufFresh::new(env.clone().into(), env.get_db(&*AUTHORED_DHT_OPS).unwrap());
+            for op_hash in vec![&finished, &unfinished, &never_published] {
+                let value = AuthoredDhtOpsValue::from_light(DhtOpLight::RegisterAgentActivity(
+                    fixt!(HeaderHash),
+                    fixt!(HeaderHash).into(),
+                ));
+                authored_dht_ops.put(op_hash.clone(), value).unwrap();
+            }
+            let mut progress = PublishProgressBuf::new(env.clone().into()).unwrap();
+            for op_hash in vec![&finished, &unfinished, &orphan] {
+                progress
+                    .record_publish(op_hash.clone(), Timestamp(1, 0))
+                    .unwrap();
+            }
+            assert!(progress
+                .record_receipt(finished.clone(), alice.clone())
+                .unwrap());
+            assert!(progress
+                .record_receipt(finished.clone(), bob.clone())
+                .unwrap());
+            // The same validator only counts once
+            assert!(!progress
+                .record_receipt(finished.clone(), bob.clone())
+                .unwrap());
+            assert!(progress
+                .record_receipt(unfinished.clone(), alice.clone())
+                .unwrap());
+            // An entry def can ask for more receipts than the default
+            progress
+                .set_required_receipts(unfinished.clone(), 3)
+                .unwrap();
+            env_ref
+                .with_commit::<DatabaseError, _, _>(|writer| {
+                    authored_dht_ops.flush_to_txn(writer)?;
+                    progress.flush_to_txn(writer)
+                })
+                .unwrap();
+        }
+
+        let report = rehydrate_publish_progress(&env, 2).unwrap();
+        assert_eq!(
+            report,
+            PublishRehydrationReport {
+                unfinished: 2,
+                finished: 1,
+                orphaned: 1,
+            }
+        );
+
+        let reader = env_ref.reader().unwrap();
+        let progress = PublishProgressBuf::new(env.clone().into()).unwrap();
+        assert_eq!(progress.get(&finished).unwrap().receipt_count(), 2);
+        assert_eq!(
+            progress.get(&unfinished).unwrap().last_publish_time,
+            Some(Timestamp(1, 0))
+        );
+        assert!(!progress.get(&unfinished).unwrap().is_saturated(1));
+        assert_eq!(progress.get(&never_published).unwrap(), Default::default());
+        assert_eq!(progress.iter_all(&reader).unwrap().len(), 2);
+    }
+}
@@ -3,22 +3,24 @@
 //! which would return Option in the SourceChainBuf, like getting the source chain head, or the AgentPubKey,
 //! cannot fail, so the function return types reflect that.
 
+pub use cap_grant_index::{CapGrantIndex, EXPIRY_SWEEP_INTERVAL};
 pub use error::*;
 use fallible_iterator::FallibleIterator;
 use holo_hash::*;
-use holochain_state::{buffer::BufferedStore, error::DatabaseResult, fresh_reader, prelude::*};
+use holochain_state::{buffer::BufferedStore, error::DatabaseResult, prelude::*};
 use holochain_types::{prelude::*, EntryHashed};
 use holochain_zome_types::{
-    capability::{CapAccess, CapGrant, CapSecret, GrantedFunction},
+    capability::{CapClaim, CapGrant, CapSecret, GrantedFunction, ZomeCallCapGrant},
     element::Element,
     entry::{CapClaimEntry, Entry},
-    header::{builder, EntryType, Header, HeaderBuilder, HeaderBuilderCommon, HeaderInner},
+    header::{builder, EntryType, Header, HeaderBuilder, HeaderInner},
     query::ChainQueryFilter,
 };
 use shrinkwraprs::Shrinkwrap;
 pub use source_chain_buffer::*;
 use std::collections::HashSet;
 
+mod cap_grant_index;
 mod error;
 mod source_chain_buffer;
 
@@ -28,6 +30,16 @@ mod source_chain_buffer;
 #[shrinkwrap(mutable)]
 pub struct SourceChain(pub SourceChainBuf);
 
+/// The cap grants and claims committed to a source chain which haven't
+/// since been updated or deleted, oldest first
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ChainCapabilities {
+    /// Each grant with the header which created or last updated it
+    pub grants: Vec<(HeaderHash, ZomeCallCapGrant)>,
+    /// Each claim with the header which created or last updated it
+    pub claims: Vec<(HeaderHash, CapClaim)>,
+}
+
 impl SourceChain {
     pub fn agent_pubkey(&self) -> SourceChainResult<AgentPubKey> {
         self.0
@@ -59,12 +71,10 @@ impl SourceChain {
         header_builder: B,
         maybe_entry: Option<Entry>,
     ) -> SourceChainResult<HeaderHash> {
-        let common = HeaderBuilderCommon {
-            author: self.agent_pubkey()?,
-            timestamp: Timestamp::now().into(),
-            header_seq: self.len() as u32,
-            prev_header: self.chain_head()?.to_owned(),
-        };
+        let prev = self
+            .get_header(self.chain_head()?)?
+            .ok_or(SourceChainError::MissingHead)?;
+        let common = self.next_header_common(prev.header_hashed())?;
         let header = header_builder.build(common).into();
         self.put_raw(header, maybe_entry).await
     }
@@ -76,10 +86,7 @@ impl SourceChain {
     ) -> SourceChainResult<HeaderHash> {
         let (entry, entry_hash) =
             EntryHashed::from_content_sync(Entry::CapClaim(claim_entry)).into_inner();
-        let header_builder = builder::Create {
-            entry_type: EntryType::CapClaim,
-            entry_hash,
-        };
+        let header_builder = builder::Create::for_entry(EntryType::CapClaim, entry_hash, &entry)?;
         self.put(header_builder, Some(entry)).await
     }
 
@@ -89,8 +96,8 @@ impl SourceChain {
     ///
     /// Else the secret and assignees of a grant will be checked and may be returned.
     ///
-    /// @todo this is not particularly fast, there are several ways to speed this up in the future
-    /// such as indexing secrets and prefixing cap grants in lmdb for direct lookup
+    /// This reads every grant on the chain, so a Cell authorizing calls
+    /// should keep a [CapGrantIndex] and use [SourceChain::valid_cap_grant_indexed].
     ///
     /// NB: [B-01676] the entry must be persisted for this to work. Once we have a
     /// proper capability index DB, OR a proper iterator that respects the
@@ -100,145 +107,37 @@ impl SourceChain {
         check_function: &GrantedFunction,
         check_agent: &AgentPubKey,
         check_secret: Option<&CapSecret>,
+    ) -> SourceChainResult<Option<CapGrant>> {
+        self.valid_cap_grant_indexed(
+            &CapGrantIndex::default(),
+            check_function,
+            check_agent,
+            check_secret,
+            Timestamp::now(),
+        )
+    }
+
+    /// Same as [SourceChain::valid_cap_grant], but looks up grants in an index
+    /// which is kept between calls and only reads the headers added since
+    /// it was last used. Grants which have expired by `now` aren't valid.
+    pub fn valid_cap_grant_indexed(
+        &self,
+        index: &CapGrantIndex,
+        check_function: &GrantedFunction,
+        check_agent: &AgentPubKey,
+        check_secret: Option<&CapSecret>,
+        now: Timestamp,
     ) -> SourceChainResult<Option<CapGrant>> {
         // most calls for most apps are going to be the local agent calling itself locally
-        // for this case we want to short circuit without iterating the whole source chain
+        // for this case we want to short circuit without looking up any grants
         let author_grant = CapGrant::from(self.agent_pubkey()?);
         if author_grant.is_valid(check_function, check_agent, check_secret) {
             return Ok(Some(author_grant));
         }
 
-        // if we are here then the caller is not the current agent so we need to search the source
-        // chain to see if there is a local grant that is valid for the provided secret/agent
-        // combination
-        let committed_valid_grant = fresh_reader!(self.env(), |r| {
-            let (references, headers): (
-                HashSet<HeaderHash>,
-                Vec<HoloHashed<holochain_zome_types::element::SignedHeader>>,
-            ) = self
-                .0
-                .headers()
-                .iter_fail(&r)?
-                .filter(|header| {
-                    Ok(match header.as_content().header() {
-                        Header::Create(create) => match create.entry_type {
-                            EntryType::CapGrant => true,
-                            // filter out authorship and everything else
-                            _ => false,
-                        },
-                        Header::Update(update) => match update.entry_type {
-                            EntryType::CapGrant => true,
-                            // filter out authorship and everything else
-                            _ => false,
-                        },
-                        Header::Delete(_) => true,
-                        // no other headers are relevant
-                        _ => false,
-                    })
-                })
-                // extract all the header references
-                // if a header is referenced by an update/delete then it is no longer valid
-                // with all the references in a bucket we can use it to filter out entries below
-                .fold(
-                    (HashSet::new(), vec![]),
-                    |(mut references, mut headers), header| {
-                        match header.as_content().header() {
-                            Header::Update(update) => {
-                                references.insert(update.original_header_address.clone());
-                            }
-                            Header::Delete(delete) => {
-                                references.insert(delete.deletes_address.clone());
-                            }
-                            _ => {}
-                        }
-                        // this is a best-effort attempt to avoid putting things we already know as
-                        // referenced into the returned vec
-                        // it isn't comprehensive because it relies on ordering but it's an easy
-                        // and relatively safe optimisation to not do further processing here
-                        if !references.contains(header.as_hash()) {
-                            headers.push(header);
-                        }
-
-                        Ok((references, headers))
-                    },
-                )?;
-
-            // second pass over the headers to make sure that all referenced headers are removed
-            // this makes the process reliable even if the iterators don't follow the chain order
-            let live_cap_grants: HashSet<_> = headers
-                .iter()
-                .filter(|header| !references.contains(header.as_hash()))
-                .filter_map(|header| match header.as_content().header() {
-                    Header::Create(create) => Some(create.entry_hash.clone()),
-                    Header::Update(update) => Some(update.entry_hash.clone()),
-                    _ => None,
-                })
-                .collect();
-
-            self
-            .0
-            .elements()
-            .private_entries()
-            .expect(
-                "SourceChainBuf must have access to private entries in order to access CapGrants",
-            )
-            .iter_fail(&r)?
-            // ensure we respect the header filtering we already did above
-            .filter(|entry| {
-                Ok(live_cap_grants.contains(entry.as_hash()))
-            })
-            .filter_map(|entry| Ok(entry.as_cap_grant()))
-            // filter down to only the grants for this function
-            .filter(|grant| {
-                Ok(grant.is_valid(check_function, check_agent, check_secret))
-            })
-            // if there are still multiple grants, fold them down based on specificity
-            // authorship > assigned > transferable > unrestricted
-            .fold(None, |mut acc, grant| {
-                acc = match &grant {
-                    CapGrant::RemoteAgent(zome_call_cap_grant) => {
-                        match &zome_call_cap_grant.access {
-                            CapAccess::Assigned { .. } => match &acc {
-                                Some(CapGrant::RemoteAgent(acc_zome_call_cap_grant)) => {
-                                    match acc_zome_call_cap_grant.access {
-                                        // an assigned acc takes precedence
-                                        CapAccess::Assigned { .. } => acc,
-                                        // current grant takes precedence over all other accs
-                                        _ => Some(grant),
-                                    }
-                                }
-                                None => Some(grant),
-                                // authorship should be short circuit and filtered
-                                _ => unreachable!(),
-                            },
-                            CapAccess::Transferable { .. } => match &acc {
-                                Some(CapGrant::RemoteAgent(acc_zome_call_cap_grant)) => {
-                                    match acc_zome_call_cap_grant.access {
-                                        // an assigned acc takes precedence
-                                        CapAccess::Assigned { .. } => acc,
-                                        // transferable acc takes precedence
-                                        CapAccess::Transferable { .. } => acc,
-                                        // current grant takes preference over other accs
-                                        _ => Some(grant),
-                                    }
-                                }
-                                None => Some(grant),
-                                // authorship should be short circuited and filtered by now
-                                _ => unreachable!(),
-                            }
-                            CapAccess::Unrestricted => match acc {
-                                Some(_) => acc,
-                                None => Some(grant),
-                            }
-                        }
-                    },
-                    // ChainAuthor should have short circuited and be filtered out already
-                    _ => unreachable!(),
-                };
-                Ok(acc)
-            })
-        })?;
-        Ok(committed_valid_grant)
+        // if we are here then the caller is not the current agent so we need to find a
+        // live grant that is valid for the provided secret/agent combination
+        index.valid_cap_grant(&self.0, check_function, check_agent, check_secret, now)
     }
 
     // @todo bring all this back when we want to administer cap claims better
@@ -287,6 +186,38 @@ impl SourceChain {
     //     }
     // }
 
+    /// Every grant and claim on the chain which is still live,
+    /// for managing the permissions this agent has given and been given
+    pub fn capabilities(&self) -> SourceChainResult<ChainCapabilities> {
+        let elements = self.query(&ChainQueryFilter::new().include_entries(true))?;
+        // Newest first, so a grant or claim is seen after anything which ends it
+        let mut ended = HashSet::new();
+        let mut capabilities = ChainCapabilities::default();
+        for element in elements {
+            match element.header() {
+                Header::Update(update) => {
+                    ended.insert(update.original_header_address.clone());
+                }
+                Header::Delete(delete) => {
+                    ended.insert(delete.deletes_address.clone());
+                }
+                _ => {}
+            }
+            if ended.contains(element.header_address()) {
+                continue;
+            }
+            let header_address = element.header_address().clone();
+            match element.into_inner().1.into_option() {
+                Some(Entry::CapGrant(grant)) => capabilities.grants.push((header_address, grant)),
+                Some(Entry::CapClaim(claim)) => capabilities.claims.push((header_address, claim)),
+                _ => {}
+            }
+        }
+        capabilities.grants.reverse();
+        capabilities.claims.reverse();
+        Ok(capabilities)
+    }
+
     /// Query Headers in the source chain.
     /// This returns a Vec rather than an iterator because it is intended to be
     /// used by the `query` host function, which crosses the wasm boundary
@@ -489,6 +420,202 @@ pub mod tests {
         Ok(())
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_cap_grant_index_catches_up() -> SourceChainResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let secret = Some(CapSecretFixturator::new(Unpredictable).next().unwrap());
+        let function: GrantedFunction = ("foo".into(), "bar".into());
+        let mut functions: GrantedFunctions = HashSet::new();
+        functions.insert(function.clone());
+        let grant = ZomeCallCapGrant::new("tag".into(), secret.unwrap().into(), functions);
+        let mut agents = AgentPubKeyFixturator::new(Predictable);
+        let alice = agents.next().unwrap();
+        let bob = agents.next().unwrap();
+        {
+            let mut store = SourceChainBuf::new(env.clone().into())?;
+            store.genesis(fake_dna_hash(1), alice.clone(), None).await?;
+            env.guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+
+        // The same index is used throughout, as a Cell would
+        let index = CapGrantIndex::default();
+        assert_eq!(
+            SourceChain::new(env.clone().into())?.valid_cap_grant_indexed(
+                &index,
+                &function,
+                &bob,
+                secret.as_ref(),
+                Timestamp::now(),
+            )?,
+            None
+        );
+
+        let (header_address, entry_address) = {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            let (entry, entry_hash) =
+                EntryHashed::from_content_sync(Entry::CapGrant(grant.clone())).into_inner();
+            let header_builder = builder::Create {
+                entry_type: EntryType::CapGrant,
+                entry_hash: entry_hash.clone(),
+            };
+            let header = chain.put(header_builder, Some(entry)).await?;
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+            (header, entry_hash)
+        };
+        assert_eq!(
+            SourceChain::new(env.clone().into())?.valid_cap_grant_indexed(
+                &index,
+                &function,
+                &bob,
+                secret.as_ref(),
+                Timestamp::now(),
+            )?,
+            Some(grant.into())
+        );
+
+        {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            let header_builder = builder::Delete {
+                deletes_address: header_address,
+                deletes_entry_address: entry_address,
+            };
+            chain.put(header_builder, None).await?;
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+        }
+        assert_eq!(
+            SourceChain::new(env.clone().into())?.valid_cap_grant_indexed(
+                &index,
+                &function,
+                &bob,
+                secret.as_ref(),
+                Timestamp::now(),
+            )?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_cap_grant_expires() -> SourceChainResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let secret = Some(CapSecretFixturator::new(Unpredictable).next().unwrap());
+        let function: GrantedFunction = ("foo".into(), "bar".into());
+        let mut functions: GrantedFunctions = HashSet::new();
+        functions.insert(function.clone());
+        let expires = Timestamp(1_000, 0);
+        let grant = ZomeCallCapGrant::new("tag".into(), secret.unwrap().into(), functions)
+            .expiring_at(expires.into());
+        let mut agents = AgentPubKeyFixturator::new(Predictable);
+        let alice = agents.next().unwrap();
+        let bob = agents.next().unwrap();
+        {
+            let mut store = SourceChainBuf::new(env.clone().into())?;
+            store.genesis(fake_dna_hash(1), alice.clone(), None).await?;
+            env.guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+        {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            let (entry, entry_hash) =
+                EntryHashed::from_content_sync(Entry::CapGrant(grant.clone())).into_inner();
+            let header_builder = builder::Create {
+                entry_type: EntryType::CapGrant,
+                entry_hash,
+            };
+            chain.put(header_builder, Some(entry)).await?;
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+        }
+
+        let index = CapGrantIndex::default();
+        let check = |now| {
+            SourceChain::new(env.clone().into())?.valid_cap_grant_indexed(
+                &index,
+                &function,
+                &bob,
+                secret.as_ref(),
+                now,
+            )
+        };
+        assert_eq!(check(Timestamp(999, 0))?, Some(grant.into()));
+        // expired even before the sweep
+        assert_eq!(check(expires)?, None);
+
+        assert_eq!(index.sweep_expired(Timestamp(999, 0)), 0);
+        assert_eq!(index.sweep_expired(expires), 1);
+        assert_eq!(index.sweep_expired(expires), 0);
+        assert_eq!(check(Timestamp(999, 0))?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_capabilities_are_live_grants_and_claims() -> SourceChainResult<()> {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let secret = CapSecretFixturator::new(Unpredictable).next().unwrap();
+        let mut functions: GrantedFunctions = HashSet::new();
+        functions.insert(("foo".into(), "bar".into()));
+        let grant = ZomeCallCapGrant::new("tag".into(), secret.into(), functions.clone());
+        let deleted_grant =
+            ZomeCallCapGrant::new("deleted".into(), CapAccess::Unrestricted, functions);
+        let mut agents = AgentPubKeyFixturator::new(Predictable);
+        let alice = agents.next().unwrap();
+        let bob = agents.next().unwrap();
+        let claim = CapClaim::new("tag".into(), bob, secret);
+        {
+            let mut store = SourceChainBuf::new(env.clone().into())?;
+            store.genesis(fake_dna_hash(1), alice.clone(), None).await?;
+            env.guard()
+                .with_commit(|writer| store.flush_to_txn(writer))?;
+        }
+
+        let (grant_header, deleted_header) = {
+            let mut chain = SourceChain::new(env.clone().into())?;
+            let mut headers = Vec::new();
+            for grant in vec![grant.clone(), deleted_grant] {
+                let (entry, entry_hash) =
+                    EntryHashed::from_content_sync(Entry::CapGrant(grant)).into_inner();
+                let header_builder = builder::Create {
+                    entry_type: EntryType::CapGrant,
+                    entry_hash: entry_hash.clone(),
+                };
+                headers.push((chain.put(header_builder, Some(entry)).await?, entry_hash));
+            }
+            chain.put_cap_claim(claim.clone()).await?;
+            let (deletes_address, deletes_entry_address) = headers.pop().unwrap();
+            chain
+                .put(
+                    builder::Delete {
+                        deletes_address: deletes_address.clone(),
+                        deletes_entry_address,
+                    },
+                    None,
+                )
+                .await?;
+            env.guard()
+                .with_commit(|writer| chain.flush_to_txn(writer))?;
+            (headers.pop().unwrap().0, deletes_address)
+        };
+
+        let capabilities = SourceChain::new(env.clone().into())?.capabilities()?;
+        assert_eq!(capabilities.grants, vec![(grant_header, grant)]);
+        assert!(capabilities
+            .grants
+            .iter()
+            .all(|(h, _)| *h != deleted_header));
+        assert_eq!(capabilities.claims.len(), 1);
+        assert_eq!(capabilities.claims[0].1, claim);
+
+        Ok(())
+    }
+
     // @todo bring all this back when we want to administer cap claims better
     // #[tokio::test(threaded_scheduler)]
     // async fn test_get_cap_claim() -> SourceChainResult<()> {
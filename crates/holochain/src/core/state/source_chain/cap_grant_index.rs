@@ -0,0 +1,228 @@
+//! An in-memory index of the live cap grants on a source chain.
+//!
+//! Authorizing a zome call from another agent means finding a live grant
+//! which matches the caller and their secret. Rather than walking the whole
+//! chain for every call, each Cell keeps its live grants indexed by secret
+//! and by assignee. The index remembers how much of the chain it has seen,
+//! and before each lookup it reads only the headers committed since, so
+//! grants which were created, updated or deleted are picked up without
+//! rescanning anything.
+//!
+//! Grants with an expiry stop matching once it has passed. The Cell's
+//! autonomic cache pruning also deletes them from the index, so short-lived
+//! grants don't pile up in it. The chain is never reread, so they don't
+//! come back.
+
+use super::{SourceChainBuf, SourceChainResult};
+use holo_hash::{AgentPubKey, HeaderHash};
+use holochain_state::fresh_reader;
+use holochain_types::{element::Element, Timestamp};
+use holochain_zome_types::{
+    capability::{CapAccess, CapGrant, CapSecret, GrantedFunction, ZomeCallCapGrant},
+    entry::Entry,
+    header::{EntryType, Header},
+};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+/// How often expired grants are swept out of the index, by default
+pub const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The live cap grants on a Cell's source chain. Clones share the same index.
+#[derive(Clone, Default)]
+pub struct CapGrantIndex(Arc<Mutex<LiveGrants>>);
+
+#[derive(Default)]
+struct LiveGrants {
+    /// How many headers of the chain have been indexed
+    indexed: u32,
+    /// Each live grant by the header which created or last updated it
+    grants: HashMap<HeaderHash, ZomeCallCapGrant>,
+    /// The unrestricted grants
+    unrestricted: HashSet<HeaderHash>,
+    /// The transferable grants for each secret
+    by_secret: HashMap<CapSecret, HashSet<HeaderHash>>,
+    /// The assigned grants for each assignee
+    by_assignee: HashMap<AgentPubKey, HashSet<HeaderHash>>,
+}
+
+impl CapGrantIndex {
+    /// Find the live grant which lets this agent call this function with this
+    /// secret, catching up with the chain first.
+    ///
+    /// If more than one grant matches, the most specific is returned:
+    /// assigned, then transferable, then unrestricted.
+    /// Grants which have expired by `now` don't match.
+    /// The chain author isn't checked here, see [SourceChain::valid_cap_grant].
+    ///
+    /// [SourceChain::valid_cap_grant]: super::SourceChain::valid_cap_grant
+    pub fn valid_cap_grant(
+        &self,
+        chain: &SourceChainBuf,
+        check_function: &GrantedFunction,
+        check_agent: &AgentPubKey,
+        check_secret: Option<&CapSecret>,
+        now: Timestamp,
+    ) -> SourceChainResult<Option<CapGrant>> {
+        let mut live = self.0.lock();
+        live.catch_up(chain)?;
+        Ok(live.find(check_function, check_agent, check_secret, now))
+    }
+
+    /// Delete the grants which have expired by `now` from the index.
+    /// Returns how many were deleted.
+    pub fn sweep_expired(&self, now: Timestamp) -> usize {
+        self.0.lock().sweep_expired(now)
+    }
+}
+
+impl std::fmt::Debug for CapGrantIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let live = self.0.lock();
+        f.debug_struct("CapGrantIndex")
+            .field("indexed", &live.indexed)
+            .field("grants", &live.grants.len())
+            .finish()
+    }
+}
+
+impl LiveGrants {
+    /// Index every header persisted since the last catch up
+    fn catch_up(&mut self, chain: &SourceChainBuf) -> SourceChainResult<()> {
+        let headers = fresh_reader!(chain.env(), |r| chain
+            .sequence()
+            .persisted_from(&r, self.indexed))?;
+        for (i, header_address) in headers {
+            if let Some(element) = chain.get_element(&header_address)? {
+                self.apply(&element);
+            }
+            self.indexed = i + 1;
+        }
+        Ok(())
+    }
+
+    /// A header which updates or deletes a grant ends it
+    fn apply(&mut self, element: &Element) {
+        match element.header() {
+            Header::Create(create) if create.entry_type == EntryType::CapGrant => {
+                self.insert(element);
+            }
+            Header::Update(update) => {
+                self.remove(&update.original_header_address);
+                if update.entry_type == EntryType::CapGrant {
+                    self.insert(element);
+                }
+            }
+            Header::Delete(delete) => self.remove(&delete.deletes_address),
+            _ => {}
+        }
+    }
+
+    fn insert(&mut self, element: &Element) {
+        let grant = match element.entry().as_option() {
+            Some(Entry::CapGrant(grant)) => grant.clone(),
+            // Grants are private, so the entry should always be here
+            _ => return,
+        };
+        let header_address = element.header_address().clone();
+        match &grant.access {
+            CapAccess::Unrestricted => {
+                self.unrestricted.insert(header_address.clone());
+            }
+            CapAccess::Transferable { secret } => {
+                self.by_secret
+                    .entry(*secret)
+                    .or_default()
+                    .insert(header_address.clone());
+            }
+            CapAccess::Assigned { assignees, .. } => {
+                for assignee in assignees {
+                    self.by_assignee
+                        .entry(assignee.clone())
+                        .or_default()
+                        .insert(header_address.clone());
+                }
+            }
+        }
+        self.grants.insert(header_address, grant);
+    }
+
+    fn remove(&mut self, header_address: &HeaderHash) {
+        let grant = match self.grants.remove(header_address) {
+            Some(grant) => grant,
+            None => return,
+        };
+        self.unindex(header_address, &grant);
+    }
+
+    /// Drop a grant from the lookups
+    fn unindex(&mut self, header_address: &HeaderHash, grant: &ZomeCallCapGrant) {
+        match &grant.access {
+            CapAccess::Unrestricted => {
+                self.unrestricted.remove(header_address);
+            }
+            CapAccess::Transferable { secret } => {
+                remove_from(&mut self.by_secret, secret, header_address)
+            }
+            CapAccess::Assigned { assignees, .. } => {
+                for assignee in assignees {
+                    remove_from(&mut self.by_assignee, assignee, header_address);
+                }
+            }
+        }
+    }
+
+    fn sweep_expired(&mut self, now: Timestamp) -> usize {
+        let now = now.into();
+        let expired: Vec<_> = self
+            .grants
+            .iter()
+            .filter(|(_, grant)| grant.is_expired(now))
+            .map(|(header_address, _)| header_address.clone())
+            .collect();
+        for header_address in &expired {
+            if let Some(grant) = self.grants.remove(header_address) {
+                self.unindex(header_address, &grant);
+            }
+        }
+        expired.len()
+    }
+
+    fn find(
+        &self,
+        check_function: &GrantedFunction,
+        check_agent: &AgentPubKey,
+        check_secret: Option<&CapSecret>,
+        now: Timestamp,
+    ) -> Option<CapGrant> {
+        let now = now.into();
+        let assigned = self.by_assignee.get(check_agent);
+        let transferable = check_secret.and_then(|secret| self.by_secret.get(secret));
+        assigned
+            .into_iter()
+            .chain(transferable)
+            .chain(Some(&self.unrestricted))
+            .flatten()
+            .filter_map(|header_address| self.grants.get(header_address))
+            .filter(|grant| !grant.is_expired(now))
+            .map(|grant| CapGrant::from(grant.clone()))
+            .find(|grant| grant.is_valid(check_function, check_agent, check_secret))
+    }
+}
+
+fn remove_from<K: std::hash::Hash + Eq>(
+    index: &mut HashMap<K, HashSet<HeaderHash>>,
+    key: &K,
+    header_address: &HeaderHash,
+) {
+    if let Some(headers) = index.get_mut(key) {
+        headers.remove(header_address);
+        if headers.is_empty() {
+            index.remove(key);
+        }
+    }
+}
@@ -1,9 +1,11 @@
 use crate::core::workflow::produce_dht_ops_workflow::dht_op_light::error::DhtOpConvertError;
 use holo_hash::EntryHash;
 use holo_hash::HeaderHash;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_serialized_bytes::prelude::*;
 use holochain_state::error::DatabaseError;
 use holochain_types::dht_op::error::DhtOpError;
+use holochain_zome_types::header::builder::HeaderBuilderError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -60,6 +62,23 @@ pub enum SourceChainError {
     #[error(transparent)]
     DhtOpConvertError(#[from] Box<DhtOpConvertError>),
 
+    /// A header was built which would break the rules of the chain
+    #[error(transparent)]
+    HeaderBuilderError(#[from] HeaderBuilderError),
+
+    /// A header was written which doesn't follow on from the chain head
+    #[error("Header doesn't extend the chain head. Expected prev_header {expected_prev_header:?} and header_seq {expected_header_seq}, got {prev_header:?} and {header_seq}")]
+    HeaderNotAtHead {
+        /// The current chain head
+        expected_prev_header: Option<HeaderHash>,
+        /// The header's prev_header
+        prev_header: Option<HeaderHash>,
+        /// The current chain length
+        expected_header_seq: u32,
+        /// The header's header_seq
+        header_seq: u32,
+    },
+
     #[error("Required the scratch space to be empty but contained values")]
     ScratchNotFresh,
 
@@ -75,6 +94,30 @@ impl From<serde_json::Error> for SourceChainError {
     }
 }
 
+impl HasErrorKind for SourceChainError {
+    fn error_kind(&self) -> ErrorKind {
+        use SourceChainError::*;
+        match self {
+            ChainEmpty | ScratchNotFresh | KeystoreError(_) | DhtOpError(_) => ErrorKind::Internal,
+            // Headers are built from the chain head, so this is a bug
+            HeaderNotAtHead { .. } => ErrorKind::Internal,
+            // Another write got in first, so trying again may succeed
+            HeadMoved(_, _) => ErrorKind::Unavailable,
+            InvalidStructure(_) | MissingHead | MalformedEntry(_) => ErrorKind::Storage,
+            SerializationError(_) | SerdeJsonError(_) => ErrorKind::Serialization,
+            DatabaseError(e) => e.error_kind(),
+            InvalidSignature
+            | InvalidPreviousHeader(_)
+            | InvalidCommit(_)
+            | InvalidCreateLink(_)
+            | HeaderBuilderError(_) => ErrorKind::Validation,
+            BlockOnError(_) => ErrorKind::Timeout,
+            DhtOpConvertError(e) => e.error_kind(),
+            ElementMissing(_) => ErrorKind::NotFound,
+        }
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ChainInvalidReason {
     #[error("A valid chain always begins with a Dna entry, followed by an Agent entry.")]
@@ -1,4 +1,5 @@
 use super::ChainInvalidReason;
+use crate::core::clock::Clock;
 use crate::core::state::{
     chain_sequence::ChainSequenceBuf,
     element_buf::{ElementBuf, HeaderCas},
@@ -13,13 +14,18 @@ use holochain_types::{
     prelude::*,
     HeaderHashed,
 };
-use holochain_zome_types::{header, Entry, Header};
+use holochain_zome_types::{
+    header::{self, builder, HeaderBuilder, HeaderBuilderCommon},
+    Entry, Header,
+};
 use tracing::*;
 
 pub struct SourceChainBuf {
     elements: ElementBuf,
     sequence: ChainSequenceBuf,
     keystore: KeystoreSender,
+    /// Where new headers get their timestamps from
+    clock: Clock,
 
     env: EnvironmentRead,
 }
@@ -30,6 +36,7 @@ impl SourceChainBuf {
             elements: ElementBuf::vault(env.clone(), true)?,
             sequence: ChainSequenceBuf::new(env.clone())?,
             keystore: env.keystore().clone(),
+            clock: Clock::default(),
             env,
         })
     }
@@ -39,6 +46,7 @@ impl SourceChainBuf {
             elements: ElementBuf::vault(env.clone(), false)?,
             sequence: ChainSequenceBuf::new(env.clone())?,
             keystore: env.keystore().clone(),
+            clock: Clock::default(),
             env,
         })
     }
@@ -47,6 +55,28 @@ impl SourceChainBuf {
         &self.env
     }
 
+    /// Where new headers get their timestamps from
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// The common fields for the header after `prev`, timestamped now.
+    /// If the clock has gone back since `prev` was written, the header gets
+    /// `prev`'s timestamp instead, because a chain's timestamps can't go back.
+    pub fn next_header_common(
+        &self,
+        prev: &HeaderHashed,
+    ) -> SourceChainResult<HeaderBuilderCommon> {
+        let now: holochain_zome_types::timestamp::Timestamp = self.clock.now().into();
+        let timestamp = std::cmp::max(now, prev.as_content().timestamp());
+        Ok(HeaderBuilderCommon::next(prev, timestamp)?)
+    }
+
+    /// Timestamp new headers with this clock instead of the system's
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+    }
+
     // add a cache test only method that allows this to
     // be used with the cache database for testing
     // FIXME This should only be cfg(test) but that doesn't work with integration tests
@@ -55,6 +85,7 @@ impl SourceChainBuf {
             elements: ElementBuf::cache(env.clone())?,
             sequence: ChainSequenceBuf::new(env.clone())?,
             keystore: env.keystore().clone(),
+            clock: Clock::default(),
             env,
         })
     }
@@ -132,11 +163,25 @@ impl SourceChainBuf {
         &self.sequence
     }
 
-    /// Add a Element to the source chain, using a fully-formed Header
+    /// Add an Element to the source chain, using a fully-formed Header.
+    /// The header must extend the current chain head, so a Dna header
+    /// is only accepted on an empty chain.
     pub async fn put_raw(
         &mut self,
         header: Header,
         maybe_entry: Option<Entry>,
+    ) -> SourceChainResult<HeaderHash> {
+        self.check_extends_head(&header)?;
+        self.put_raw_unchecked(header, maybe_entry).await
+    }
+
+    // add a test only method for writing headers which don't
+    // extend the chain head, e.g. other agents' headers in the cache
+    // FIXME This should only be cfg(test) but that doesn't work with integration tests
+    pub async fn put_raw_unchecked(
+        &mut self,
+        header: Header,
+        maybe_entry: Option<Entry>,
     ) -> SourceChainResult<HeaderHash> {
         let header = HeaderHashed::from_content_sync(header);
         let header_address = header.as_hash().to_owned();
@@ -158,6 +203,24 @@ impl SourceChainBuf {
         Ok(header_address)
     }
 
+    /// Check the header's prev_header is the chain head
+    /// and its header_seq is the chain length
+    fn check_extends_head(&self, header: &Header) -> SourceChainResult<()> {
+        let expected_prev_header = self.chain_head();
+        let expected_header_seq = self.len() as u32;
+        if header.prev_header() != expected_prev_header
+            || header.header_seq() != expected_header_seq
+        {
+            return Err(SourceChainError::HeaderNotAtHead {
+                expected_prev_header: expected_prev_header.cloned(),
+                prev_header: header.prev_header().cloned(),
+                expected_header_seq,
+                header_seq: header.header_seq(),
+            });
+        }
+        Ok(())
+    }
+
     pub fn headers(&self) -> &HeaderCas<IntegratedPrefix> {
         &self.elements.headers()
     }
@@ -189,6 +252,23 @@ impl SourceChainBuf {
         SourceChainBackwardIterator::new(self)
     }
 
+    /// The elements put in the scratch space, newest first.
+    /// Nobody else can see them until the chain is flushed.
+    pub fn scratch_elements(&self) -> SourceChainResult<Vec<Element>> {
+        let persisted_head = self.sequence.persisted_head();
+        let mut iter = self.iter_back();
+        let mut elements = Vec::new();
+        while let Some(shh) = iter.next()? {
+            if Some(shh.header_address()) == persisted_head {
+                break;
+            }
+            if let Some(element) = self.get_element(shh.header_address())? {
+                elements.push(element);
+            }
+        }
+        Ok(elements)
+    }
+
     /// dump the entire source chain as a pretty-printed json string
     pub async fn dump_as_json(&self) -> Result<String, SourceChainError> {
         #[derive(Serialize, Deserialize)]
@@ -243,32 +323,29 @@ impl SourceChainBuf {
         // create a DNA chain element and add it directly to the store
         let dna_header = Header::Dna(header::Dna {
             author: agent_pubkey.clone(),
-            timestamp: Timestamp::now().into(),
+            timestamp: self.clock.now().into(),
             hash: dna_hash,
         });
-        let dna_header_address = self.put_raw(dna_header, None).await?;
+        let dna_header_address = self.put_raw(dna_header.clone(), None).await?;
+        let dna_header = HeaderHashed::with_pre_hashed(dna_header, dna_header_address);
 
         // create the agent validation entry and add it directly to the store
-        let agent_validation_header = Header::AgentValidationPkg(header::AgentValidationPkg {
-            author: agent_pubkey.clone(),
-            timestamp: Timestamp::now().into(),
-            header_seq: 1,
-            prev_header: dna_header_address,
-            membrane_proof,
-        });
-        let avh_addr = self.put_raw(agent_validation_header, None).await?;
+        let agent_validation_header: Header = builder::AgentValidationPkg::new(membrane_proof)
+            .build(self.next_header_common(&dna_header)?)
+            .into();
+        let avh_addr = self.put_raw(agent_validation_header.clone(), None).await?;
+        let agent_validation_header =
+            HeaderHashed::with_pre_hashed(agent_validation_header, avh_addr);
 
         // create a agent chain element and add it directly to the store
-        let agent_header = Header::Create(header::Create {
-            author: agent_pubkey.clone(),
-            timestamp: Timestamp::now().into(),
-            header_seq: 2,
-            prev_header: avh_addr,
-            entry_type: header::EntryType::AgentPubKey,
-            entry_hash: agent_pubkey.clone().into(),
-        });
-        self.put_raw(agent_header, Some(Entry::Agent(agent_pubkey)))
-            .await?;
+        let agent_entry = Entry::Agent(agent_pubkey.clone());
+        let agent_header = builder::Create::for_entry(
+            header::EntryType::AgentPubKey,
+            agent_pubkey.into(),
+            &agent_entry,
+        )?
+        .build(self.next_header_common(&agent_validation_header)?);
+        self.put_raw(agent_header.into(), Some(agent_entry)).await?;
 
         Ok(())
     }
@@ -324,7 +401,8 @@ impl<'a> FallibleIterator for SourceChainBackwardIterator<'a> {
 pub mod tests {
 
     use super::SourceChainBuf;
-    use crate::core::state::source_chain::SourceChainResult;
+    use crate::core::clock::Clock;
+    use crate::core::state::source_chain::{SourceChainError, SourceChainResult};
     use fallible_iterator::FallibleIterator;
     use holochain_state::{prelude::*, test_utils::test_cell_env};
     use holochain_types::{
@@ -333,6 +411,7 @@ pub mod tests {
         HeaderHashed,
     };
     use holochain_zome_types::{header, Entry, Header};
+    use matches::assert_matches;
 
     fn fixtures() -> (
         AgentPubKey,
@@ -506,4 +585,79 @@ pub mod tests {
         assert_eq!(signed_header.as_hash(), hashed.as_hash());
         assert_eq!(signed_header.as_hash(), signed_header.header_address());
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn put_rejects_headers_which_dont_extend_the_head() {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let mut store = SourceChainBuf::new(arc.clone().into()).unwrap();
+
+        let (_, dna_header, _, agent_header, agent_entry) = fixtures();
+        let dna_header = dna_header.into_content();
+
+        // The agent header can't come first
+        let err = store
+            .put_raw(agent_header.as_content().clone(), agent_entry.clone())
+            .await
+            .unwrap_err();
+        assert_matches!(err, SourceChainError::HeaderNotAtHead { .. });
+
+        store.put_raw(dna_header.clone(), None).await.unwrap();
+
+        // A second Dna header doesn't extend the head
+        let err = store.put_raw(dna_header, None).await.unwrap_err();
+        assert_matches!(
+            err,
+            SourceChainError::HeaderNotAtHead {
+                prev_header: None,
+                expected_header_seq: 1,
+                header_seq: 0,
+                ..
+            }
+        );
+
+        // Right prev_header but wrong header_seq
+        let mut bad_seq = agent_header.as_content().clone();
+        if let Header::Create(create) = &mut bad_seq {
+            create.header_seq = 2;
+        }
+        let err = store
+            .put_raw(bad_seq, agent_entry.clone())
+            .await
+            .unwrap_err();
+        assert_matches!(
+            err,
+            SourceChainError::HeaderNotAtHead {
+                expected_header_seq: 1,
+                header_seq: 2,
+                ..
+            }
+        );
+
+        store
+            .put_raw(agent_header.as_content().clone(), agent_entry)
+            .await
+            .unwrap();
+        assert_eq!(store.chain_head(), Some(agent_header.as_hash()));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn genesis_headers_are_stamped_by_the_clock() {
+        let test_env = test_cell_env();
+        let arc = test_env.env();
+        let mut store = SourceChainBuf::new(arc.clone().into()).unwrap();
+        let clock = Clock::fixed(Timestamp(1000, 0));
+        store.set_clock(clock);
+
+        let agent_pubkey = fake_agent_pubkey_1();
+        store
+            .genesis(fake_dna_file("a").dna_hash().clone(), agent_pubkey, None)
+            .await
+            .unwrap();
+        let head = store
+            .get_element(store.chain_head().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(head.header().timestamp(), Timestamp(1000, 0).into());
+    }
 }
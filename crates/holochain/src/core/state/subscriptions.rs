@@ -0,0 +1,150 @@
+//! The remote agents who have asked this cell, as an authority, to tell them
+//! when ops are integrated at a basis.
+//!
+//! Each subscription is stored under the basis followed by the subscriber,
+//! so all the subscribers for a basis are read with a single scan.
+//! Subscriptions last until the subscriber unsubscribes.
+
+use super::metadata::BytesKey;
+use fallible_iterator::FallibleIterator;
+use holo_hash::{AgentPubKey, AnyDhtHash};
+use holochain_serialized_bytes::prelude::*;
+use holochain_state::{
+    buffer::KvBufFresh,
+    db::SUBSCRIPTIONS,
+    error::{DatabaseError, DatabaseResult},
+    prelude::{BufferedStore, EnvironmentRead, GetDb, Readable, Writer},
+};
+use holochain_types::Timestamp;
+
+/// Database type for the Subscriptions
+pub type SubscriptionsStore = KvBufFresh<BytesKey, SubscriptionValue>;
+
+/// The value stored for each subscription
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SubscriptionValue {
+    /// The agent to notify
+    pub subscriber: AgentPubKey,
+    /// When the agent subscribed
+    pub since: Timestamp,
+}
+
+/// Buffer for the subscriptions to bases this cell is an authority for
+pub struct SubscriptionsBuf {
+    store: SubscriptionsStore,
+}
+
+impl SubscriptionsBuf {
+    /// Create a new buffer for the Subscriptions database
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*SUBSCRIPTIONS)?;
+        Ok(Self {
+            store: SubscriptionsStore::new(env, db),
+        })
+    }
+
+    /// Subscribe an agent to a basis.
+    /// Subscribing again just updates when it subscribed.
+    pub fn subscribe(&mut self, basis: &AnyDhtHash, subscriber: AgentPubKey) -> DatabaseResult<()> {
+        self.store.put(
+            key(basis, &subscriber),
+            SubscriptionValue {
+                subscriber,
+                since: Timestamp::now(),
+            },
+        )
+    }
+
+    /// Remove an agent's subscription to a basis, if it has one
+    pub fn unsubscribe(
+        &mut self,
+        basis: &AnyDhtHash,
+        subscriber: &AgentPubKey,
+    ) -> DatabaseResult<()> {
+        self.store.delete(key(basis, subscriber))
+    }
+
+    /// The agents subscribed to a basis
+    pub fn subscribers<R: Readable>(
+        &self,
+        r: &R,
+        basis: &AnyDhtHash,
+    ) -> DatabaseResult<Vec<AgentPubKey>> {
+        let prefix = basis.get_full_bytes().to_vec();
+        self.store
+            .iter_from(r, BytesKey(prefix.clone()))?
+            .take_while(|(k, _)| Ok(k.starts_with(&prefix)))
+            .map(|(_, v)| Ok(v.subscriber))
+            .collect()
+    }
+}
+
+impl BufferedStore for SubscriptionsBuf {
+    type Error = DatabaseError;
+    fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> Result<(), Self::Error> {
+        self.store.flush_to_txn_ref(writer)
+    }
+}
+
+/// Hashes are all the same length, so no basis is a prefix of another
+fn key(basis: &AnyDhtHash, subscriber: &AgentPubKey) -> BytesKey {
+    BytesKey([basis.get_full_bytes(), subscriber.get_full_bytes()].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{AgentPubKeyFixturator, EntryHashFixturator};
+    use holochain_state::{
+        env::{ReadManager, WriteManager},
+        test_utils::test_cell_env,
+    };
+
+    #[tokio::test(threaded_scheduler)]
+    async fn subscribe_and_unsubscribe() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let env_ref = env.guard();
+        let basis: AnyDhtHash = fixt!(EntryHash).into();
+        let other_basis: AnyDhtHash = fixt!(EntryHash).into();
+        let alice = fixt!(AgentPubKey);
+        let bob = fixt!(AgentPubKey);
+
+        {
+            let mut buf = SubscriptionsBuf::new(env.clone().into()).unwrap();
+            buf.subscribe(&basis, alice.clone()).unwrap();
+            buf.subscribe(&basis, bob.clone()).unwrap();
+            buf.subscribe(&other_basis, bob.clone()).unwrap();
+            // Subscribing twice is one subscription
+            buf.subscribe(&basis, alice.clone()).unwrap();
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+        {
+            let reader = env_ref.reader().unwrap();
+            let buf = SubscriptionsBuf::new(env.clone().into()).unwrap();
+            let mut subscribers = buf.subscribers(&reader, &basis).unwrap();
+            subscribers.sort();
+            let mut expected = vec![alice.clone(), bob.clone()];
+            expected.sort();
+            assert_eq!(subscribers, expected);
+            assert_eq!(
+                buf.subscribers(&reader, &other_basis).unwrap(),
+                vec![bob.clone()]
+            );
+        }
+        {
+            let mut buf = SubscriptionsBuf::new(env.clone().into()).unwrap();
+            buf.unsubscribe(&basis, &bob).unwrap();
+            env_ref
+                .with_commit(|writer| buf.flush_to_txn(writer))
+                .unwrap();
+        }
+        let reader = env_ref.reader().unwrap();
+        let buf = SubscriptionsBuf::new(env.clone().into()).unwrap();
+        assert_eq!(buf.subscribers(&reader, &basis).unwrap(), vec![alice]);
+        assert_eq!(buf.subscribers(&reader, &other_basis).unwrap(), vec![bob]);
+    }
+}
@@ -5,11 +5,12 @@ use holo_hash::{AnyDhtHash, DhtOpHash};
 use holochain_serialized_bytes::prelude::*;
 use holochain_state::{
     buffer::KvBufFresh,
-    db::VALIDATION_LIMBO,
+    db::{ABANDONED_DHT_OPS, VALIDATION_LIMBO},
     error::DatabaseResult,
     prelude::{EnvironmentRead, GetDb},
 };
-use holochain_types::{dht_op::DhtOpLight, Timestamp};
+use holochain_types::{dht_op::DhtOpLight, validate::ValidationStatus, Timestamp};
+use holochain_zome_types::validate::ValidationPackage;
 use shrinkwraprs::Shrinkwrap;
 
 #[derive(Shrinkwrap)]
@@ -21,7 +22,7 @@ pub struct ValidationLimboStore(pub KvBufFresh<ValidationLimboKey, ValidationLim
 pub type ValidationLimboKey = DhtOpHash;
 
 /// A type for storing in databases that only need the hashes.
-#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ValidationLimboValue {
     /// Status of this op in the limbo
     pub status: ValidationLimboStatus,
@@ -40,6 +41,10 @@ pub struct ValidationLimboValue {
     pub last_try: Option<Timestamp>,
     /// Number of times we have tried to validate the op
     pub num_tries: u32,
+    /// The validation package the author published with the op, if its
+    /// entry def asks for one
+    #[serde(default)]
+    pub validation_package: Option<ValidationPackage>,
 }
 
 /// The status of a [DhtOp] in limbo
@@ -65,3 +70,52 @@ impl ValidationLimboStore {
         Ok(Self(KvBufFresh::new(env, db)))
     }
 }
+
+#[derive(Shrinkwrap)]
+#[shrinkwrap(mutable)]
+/// The database recording why ops were abandoned
+pub struct AbandonedDhtOpsStore(pub KvBufFresh<DhtOpHash, AbandonedDhtOpsValue>);
+
+/// What we know about an op the subconscious gave up on
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AbandonedDhtOpsValue {
+    /// The op that was abandoned
+    pub op: DhtOpLight,
+    /// Why it was abandoned
+    pub reason: AbandonedReason,
+    /// The dependencies it was still waiting to see validated
+    pub pending_dependencies: PendingDependencies,
+    /// When it was abandoned
+    pub when_abandoned: Timestamp,
+}
+
+/// Why the subconscious will never again try to validate an op
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum AbandonedReason {
+    /// The op's dependencies were still missing when it had waited
+    /// longer than the [UnresolvedDependencyPolicy] allows
+    ///
+    /// [UnresolvedDependencyPolicy]: crate::core::subconscious::UnresolvedDependencyPolicy
+    DependencyTimedOut {
+        /// The dependency being fetched, if it was waiting on one
+        awaiting: Option<AnyDhtHash>,
+        /// How long the op had been in limbo
+        waited_secs: i64,
+    },
+    /// A dependency which can't be substituted by any other element
+    /// was itself rejected or abandoned
+    DependencyInvalid {
+        /// The dependency's op
+        dependency: DhtOpHash,
+        /// How the dependency was judged
+        status: ValidationStatus,
+    },
+}
+
+impl AbandonedDhtOpsStore {
+    /// Create a new Abandoned DhtOps db
+    pub fn new(env: EnvironmentRead) -> DatabaseResult<Self> {
+        let db = env.get_db(&*ABANDONED_DHT_OPS)?;
+        Ok(Self(KvBufFresh::new(env, db)))
+    }
+}
@@ -1,84 +1,16 @@
 //! Module for items related to aggregating validation_receipts
 
 use fallible_iterator::FallibleIterator;
-use holo_hash::{AgentPubKey, DhtOpHash};
-use holochain_keystore::{AgentPubKeyExt, KeystoreSender, Signature};
-use holochain_serialized_bytes::prelude::*;
+use holo_hash::DhtOpHash;
 use holochain_state::{
     buffer::{BufferedStore, KvvBufUsed},
     db::GetDb,
     error::{DatabaseError, DatabaseResult},
     prelude::{Readable, Writer},
 };
-
-/// The result of a DhtOp Validation.
-#[derive(
-    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
-)]
-#[serde(tag = "type")]
-pub enum ValidationResult {
-    /// Successful validation.
-    Valid,
-    // TODO - fill out with additional options, which may (or may not) have content
-    // Abandoned { .. },
-    // Warrant { .. },
-}
-
-/// Validation receipt content - to be signed.
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    serde::Serialize,
-    serde::Deserialize,
-    SerializedBytes,
-)]
-pub struct ValidationReceipt {
-    /// the op this validation receipt is for.
-    pub dht_op_hash: DhtOpHash,
-
-    /// the result of this validation.
-    pub validation_result: ValidationResult,
-
-    /// the remote validator which is signing this receipt.
-    pub validator: AgentPubKey,
-}
-
-impl ValidationReceipt {
-    /// Sign this validation receipt.
-    pub async fn sign(self, keystore: &KeystoreSender) -> DatabaseResult<SignedValidationReceipt> {
-        let signature = self.validator.sign(keystore, self.clone()).await?;
-        Ok(SignedValidationReceipt {
-            receipt: self,
-            validator_signature: signature,
-        })
-    }
-}
-
-/// A full, signed validation receipt.
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    serde::Serialize,
-    serde::Deserialize,
-    SerializedBytes,
-)]
-pub struct SignedValidationReceipt {
-    /// the content of the validation receipt.
-    pub receipt: ValidationReceipt,
-
-    /// the signature of the remote validator.
-    pub validator_signature: Signature,
-}
+pub use holochain_types::validation_receipt::{
+    SignedValidationReceipt, ValidationReceipt, ValidationResult,
+};
 
 /// The database/buffer for aggregating validation_receipts sent by remote
 /// nodes in charge of storage thereof.
@@ -148,7 +80,7 @@ impl BufferedStore for ValidationReceiptsBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use holochain_keystore::KeystoreSenderExt;
+    use holochain_keystore::{KeystoreSender, KeystoreSenderExt};
     use holochain_state::{env::ReadManager, prelude::*};
     use holochain_types::test_utils::fake_dht_op_hash;
 
@@ -4,6 +4,7 @@
 //! Every Workflow has an associated Workspace type.
 
 use super::source_chain::SourceChainError;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_state::{error::DatabaseError, prelude::Writer};
 use thiserror::Error;
 
@@ -17,6 +18,15 @@ pub enum WorkspaceError {
     SourceChainError(#[from] SourceChainError),
 }
 
+impl HasErrorKind for WorkspaceError {
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            WorkspaceError::DatabaseError(e) => e.error_kind(),
+            WorkspaceError::SourceChainError(e) => e.error_kind(),
+        }
+    }
+}
+
 #[allow(missing_docs)]
 pub type WorkspaceResult<T> = Result<T, WorkspaceError>;
 
@@ -0,0 +1,162 @@
+//! The subconscious decides what becomes of ops whose validation
+//! dependencies can't be resolved.
+//!
+//! Validation callbacks answer `UnresolvedDependencies` without knowing how
+//! long we've been waiting or what became of the dependencies. An op stays
+//! Pending and is retried while its dependencies might still turn up. It is
+//! Abandoned once it has waited longer than the [UnresolvedDependencyPolicy]
+//! allows, or as soon as a dependency it can't do without is itself rejected
+//! or abandoned. The reason is kept in the [AbandonedDhtOpsStore].
+//!
+//! [AbandonedDhtOpsStore]: crate::core::state::validation_db::AbandonedDhtOpsStore
+
+use crate::core::{
+    state::validation_db::{AbandonedReason, ValidationLimboValue},
+    workflow::sys_validation_workflow::types::DepType,
+};
+use holo_hash::AnyDhtHash;
+use holochain_types::{validate::ValidationStatus, Timestamp};
+use serde::{Deserialize, Serialize};
+
+/// How long an op may wait for missing dependencies before it is abandoned
+const DEFAULT_ABANDON_AFTER_HOURS: u64 = 24;
+
+/// When to give up on ops with unresolved dependencies
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UnresolvedDependencyPolicy {
+    /// Abandon ops whose dependencies are still unresolved
+    /// this many hours after the op arrived
+    pub abandon_after_hours: u64,
+}
+
+/// What to do with an op which is missing dependencies
+#[derive(Clone, Debug, PartialEq)]
+pub enum DependencyJudgement {
+    /// Keep the op in limbo and try again later
+    Pending,
+    /// Never try to validate the op again
+    Abandoned(AbandonedReason),
+}
+
+impl Default for UnresolvedDependencyPolicy {
+    fn default() -> Self {
+        Self {
+            abandon_after_hours: DEFAULT_ABANDON_AFTER_HOURS,
+        }
+    }
+}
+
+impl UnresolvedDependencyPolicy {
+    /// Judge an op whose dependencies are still missing.
+    /// `awaiting` is the dependency being fetched, if there is one.
+    pub fn judge_missing(
+        &self,
+        vlv: &ValidationLimboValue,
+        awaiting: Option<AnyDhtHash>,
+        now: Timestamp,
+    ) -> DependencyJudgement {
+        let waited = chrono::DateTime::<chrono::Utc>::from(now)
+            .signed_duration_since(chrono::DateTime::<chrono::Utc>::from(&vlv.time_added));
+        if waited.num_hours() >= self.abandon_after_hours as i64 {
+            DependencyJudgement::Abandoned(AbandonedReason::DependencyTimedOut {
+                awaiting,
+                waited_secs: waited.num_seconds(),
+            })
+        } else {
+            DependencyJudgement::Pending
+        }
+    }
+
+    /// Judge an op one of whose dependencies was rejected or abandoned.
+    /// Only a fixed element makes the op impossible to validate;
+    /// any other element for the same entry could still stand in
+    /// for the dependency.
+    pub fn judge_invalid(dependency: &DepType, status: ValidationStatus) -> DependencyJudgement {
+        match (dependency, status) {
+            (_, ValidationStatus::Valid) | (DepType::AnyElement(_), _) => {
+                DependencyJudgement::Pending
+            }
+            (DepType::FixedElement(dependency), status) => {
+                DependencyJudgement::Abandoned(AbandonedReason::DependencyInvalid {
+                    dependency: dependency.clone(),
+                    status,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        state::validation_db::ValidationLimboStatus,
+        workflow::sys_validation_workflow::types::PendingDependencies,
+    };
+    use fixt::prelude::*;
+    use holo_hash::fixt::{AnyDhtHashFixturator, DhtOpHashFixturator, HeaderHashFixturator};
+    use holochain_types::dht_op::DhtOpLight;
+
+    fn vlv_added_at(time_added: Timestamp) -> ValidationLimboValue {
+        ValidationLimboValue {
+            status: ValidationLimboStatus::Pending,
+            pending_dependencies: PendingDependencies::new(),
+            op: DhtOpLight::RegisterAgentActivity(fixt!(HeaderHash), fixt!(AnyDhtHash)),
+            basis: fixt!(AnyDhtHash),
+            time_added,
+            last_try: None,
+            num_tries: 0,
+            validation_package: None,
+        }
+    }
+
+    #[test]
+    fn missing_deps_stay_pending_until_the_threshold() {
+        let policy = UnresolvedDependencyPolicy {
+            abandon_after_hours: 2,
+        };
+        let vlv = vlv_added_at(Timestamp(0, 0));
+        let awaiting = fixt!(AnyDhtHash);
+
+        assert_eq!(
+            policy.judge_missing(&vlv, Some(awaiting.clone()), Timestamp(60 * 60, 0)),
+            DependencyJudgement::Pending
+        );
+        assert_eq!(
+            policy.judge_missing(&vlv, Some(awaiting.clone()), Timestamp(2 * 60 * 60, 0)),
+            DependencyJudgement::Abandoned(AbandonedReason::DependencyTimedOut {
+                awaiting: Some(awaiting),
+                waited_secs: 2 * 60 * 60,
+            })
+        );
+    }
+
+    #[test]
+    fn only_fixed_invalid_deps_abandon() {
+        let dep = fixt!(DhtOpHash);
+        assert_eq!(
+            UnresolvedDependencyPolicy::judge_invalid(
+                &DepType::FixedElement(dep.clone()),
+                ValidationStatus::Rejected
+            ),
+            DependencyJudgement::Abandoned(AbandonedReason::DependencyInvalid {
+                dependency: dep.clone(),
+                status: ValidationStatus::Rejected,
+            })
+        );
+        assert_eq!(
+            UnresolvedDependencyPolicy::judge_invalid(
+                &DepType::AnyElement(dep.clone()),
+                ValidationStatus::Abandoned
+            ),
+            DependencyJudgement::Pending
+        );
+        assert_eq!(
+            UnresolvedDependencyPolicy::judge_invalid(
+                &DepType::FixedElement(dep),
+                ValidationStatus::Valid
+            ),
+            DependencyJudgement::Pending
+        );
+    }
+}
@@ -12,6 +12,12 @@ use crate::conductor::{
     api::CellConductorApiT,
     entry_def_store::{get_entry_defs, EntryDefBufferKey},
 };
+use crate::core::ribosome::{
+    error::RibosomeError,
+    guest_callback::link_types::{LinkTypesHostAccess, LinkTypesInvocation, LinkTypesResult},
+    wasm_ribosome::WasmRibosome,
+    RibosomeT,
+};
 use fallible_iterator::FallibleIterator;
 use holochain_keystore::{AgentPubKeyExt, Signature};
 use holochain_state::{fresh_reader, prelude::PrefixType};
@@ -374,6 +380,76 @@ pub fn check_tag_size(tag: &LinkTag) -> SysValidationResult<()> {
     }
 }
 
+/// Check the link type of a typed link is declared by the zome
+/// and that the tag holds a value if the link type is ordered.
+/// Untyped links are always valid here.
+pub async fn check_link_type(
+    link_add: &CreateLink,
+    conductor_api: &impl CellConductorApiT,
+) -> SysValidationResult<()> {
+    let link_type = match link_add.link_type {
+        Some(link_type) => link_type,
+        None => return Ok(()),
+    };
+    let zome_index = u8::from(link_add.zome_id) as usize;
+    let link_type_index = u8::from(link_type) as usize;
+    // We want to be careful about holding locks open to the conductor api
+    // so calls are made in blocks
+    let dna_file = { conductor_api.get_this_dna().await };
+    let dna_file =
+        dna_file.ok_or_else(|| SysValidationError::DnaMissing(conductor_api.cell_id().clone()))?;
+
+    // Check if the zome is found
+    let zome = dna_file
+        .dna()
+        .zomes
+        .get(zome_index)
+        .ok_or_else(|| ValidationOutcome::LinkType(link_add.zome_id, link_type))?
+        .1
+        .clone();
+
+    // Try to get the link types from the dna store
+    let link_types = { conductor_api.get_link_types(&zome).await };
+
+    // If they're not there run the ribosome and add every zome's
+    let link_types = match link_types {
+        Some(link_types) => link_types,
+        None => {
+            let zomes = dna_file.dna().zomes.clone();
+            let ribosome = WasmRibosome::new(dna_file);
+            let mut defs =
+                match ribosome.run_link_types(LinkTypesHostAccess, LinkTypesInvocation)? {
+                    LinkTypesResult::Defs(defs) => defs,
+                    LinkTypesResult::Err(zome_name, error) => {
+                        return Err(RibosomeError::LinkTypes(zome_name, error).into())
+                    }
+                };
+            let mut found = None;
+            for (zome_name, each_zome) in zomes {
+                // Zomes without the callback declare no link types
+                let link_types = defs
+                    .remove(&zome_name)
+                    .unwrap_or_else(|| Vec::new().into());
+                if each_zome == zome {
+                    found = Some(link_types.clone());
+                }
+                conductor_api.add_link_types(each_zome, link_types).await;
+            }
+            found.unwrap_or_else(|| Vec::new().into())
+        }
+    };
+    if link_type_index >= link_types.len() {
+        return Err(ValidationOutcome::LinkType(link_add.zome_id, link_type).into());
+    }
+
+    match link_types.tag_order(link_type_index) {
+        Some(tag_order) if tag_order.sort_key(&link_add.tag).is_none() => {
+            Err(ValidationOutcome::LinkTagOrder(link_add.zome_id, link_type, tag_order).into())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Check a Update's entry type is the same for
 /// original and new entry.
 pub fn check_update_reference(
@@ -1,14 +1,16 @@
 use super::SourceChainError;
 use crate::{
     conductor::entry_def_store::error::EntryDefStoreError,
-    core::state::cascade::error::CascadeError,
+    core::{ribosome::error::RibosomeError, state::cascade::error::CascadeError},
 };
 use holo_hash::{AnyDhtHash, HeaderHash};
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_keystore::{KeystoreError, Signature};
 use holochain_state::error::DatabaseError;
 use holochain_types::cell::CellId;
 use holochain_zome_types::{
-    header::{AppEntryType, EntryType},
+    header::{AppEntryType, EntryType, LinkTypeIndex, ZomeId},
+    link::LinkTagOrder,
     Header,
 };
 use thiserror::Error;
@@ -32,6 +34,8 @@ pub enum SysValidationError {
     #[error(transparent)]
     KeystoreError(#[from] KeystoreError),
     #[error(transparent)]
+    RibosomeError(#[from] RibosomeError),
+    #[error(transparent)]
     SourceChainError(#[from] SourceChainError),
     #[error("Dna is missing for this cell {0:?}. Cannot validate without dna.")]
     DnaMissing(CellId),
@@ -39,6 +43,21 @@ pub enum SysValidationError {
     ValidationOutcome(#[from] ValidationOutcome),
 }
 
+impl HasErrorKind for SysValidationError {
+    fn error_kind(&self) -> ErrorKind {
+        use SysValidationError::*;
+        match self {
+            CascadeError(e) => e.error_kind(),
+            DatabaseError(e) => e.error_kind(),
+            EntryDefStoreError(_) | KeystoreError(_) => ErrorKind::Internal,
+            RibosomeError(e) => e.error_kind(),
+            SourceChainError(e) => e.error_kind(),
+            DnaMissing(_) => ErrorKind::NotFound,
+            ValidationOutcome(_) => ErrorKind::Validation,
+        }
+    }
+}
+
 pub type SysValidationResult<T> = Result<T, SysValidationError>;
 
 // TODO: use try guard crate to refactor this so it's not an "Error"
@@ -60,6 +79,10 @@ pub enum ValidationOutcome {
     EntryType,
     #[error("The app entry type {0:?} visibility didn't match the zome")]
     EntryVisibility(AppEntryType),
+    #[error("The link type {1:?} is not declared by the zome {0}")]
+    LinkType(ZomeId, LinkTypeIndex),
+    #[error("The link tag doesn't hold a {2:?} value for the ordered link type {1:?} of zome {0}")]
+    LinkTagOrder(ZomeId, LinkTypeIndex, LinkTagOrder),
     #[error("The link tag size {0} was bigger then the MAX_TAG_SIZE {1}")]
     TagTooLarge(usize, usize),
     #[error("The header {0:?} was expected to be a link add header")]
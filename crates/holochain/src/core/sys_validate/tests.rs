@@ -15,9 +15,13 @@ use holochain_types::{
     Timestamp,
 };
 use holochain_wasm_test_utils::TestWasm;
-use holochain_zome_types::{header::InitZomesComplete, Header};
+use holochain_zome_types::{header::InitZomesComplete, link::LinkTagOrder, Header};
 use matches::assert_matches;
-use std::convert::{TryFrom, TryInto};
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    sync::{Arc, Mutex},
+};
 
 async fn test_gen(ts: Timestamp, seq: u32, prev: HeaderHash) -> Element {
     let keystore = holochain_state::test_utils::test_keystore();
@@ -397,6 +401,82 @@ async fn check_link_tag_size_test() {
     );
 }
 
+#[tokio::test(threaded_scheduler)]
+async fn check_link_type_test() {
+    observability::test_run().ok();
+    // Setup test data
+    let dna_file = DnaFile::new(
+        DnaDef {
+            name: "link_type_test".to_string(),
+            uuid: "e3a6d1c2-4f0c-4b36-9f2d-0b8e6b0a7c51".to_string(),
+            properties: SerializedBytes::try_from(()).unwrap(),
+            zomes: vec![TestWasm::Link.into()].into(),
+        },
+        vec![TestWasm::Link.into()],
+    )
+    .await
+    .unwrap();
+
+    // Setup mock conductor, whose dna store starts without any link types
+    let mut conductor_api = MockCellConductorApi::new();
+    conductor_api.expect_cell_id().return_const(fixt!(CellId));
+    conductor_api
+        .expect_sync_get_this_dna()
+        .return_const(Some(dna_file));
+    let link_types = Arc::new(Mutex::new(HashMap::new()));
+    conductor_api.expect_sync_get_link_types().returning({
+        let link_types = link_types.clone();
+        move |zome| link_types.lock().unwrap().get(zome).cloned()
+    });
+    // The zome's callback only has to be run once
+    conductor_api
+        .expect_sync_add_link_types()
+        .times(1)
+        .returning(move |zome, defs| {
+            link_types.lock().unwrap().insert(zome, defs);
+        });
+
+    let mut link_add = fixt!(CreateLink);
+    link_add.zome_id = 0.into();
+
+    // ## Untyped links are always fine
+    link_add.link_type = None;
+    assert_matches!(check_link_type(&link_add, &conductor_api).await, Ok(()));
+
+    // ## Declared link type
+    link_add.link_type = Some(0.into());
+    assert_matches!(check_link_type(&link_add, &conductor_api).await, Ok(()));
+
+    // ## Ordered link type with a value in the tag
+    link_add.link_type = Some(1.into());
+    link_add.tag = LinkTag::from_timestamp(Timestamp(1, 0).into());
+    assert_matches!(check_link_type(&link_add, &conductor_api).await, Ok(()));
+
+    // ## Ordered link type without a value in the tag
+    link_add.tag = LinkTag::new(vec![1, 2, 3]);
+    assert_matches!(
+        check_link_type(&link_add, &conductor_api).await,
+        Err(SysValidationError::ValidationOutcome(
+            ValidationOutcome::LinkTagOrder(_, _, LinkTagOrder::Timestamp)
+        ))
+    );
+
+    // ## Undeclared link type
+    link_add.link_type = Some(2.into());
+    assert_matches!(
+        check_link_type(&link_add, &conductor_api).await,
+        Err(SysValidationError::ValidationOutcome(ValidationOutcome::LinkType(_, _)))
+    );
+
+    // ## ZomeId out of range
+    link_add.zome_id = 1.into();
+    link_add.link_type = Some(0.into());
+    assert_matches!(
+        check_link_type(&link_add, &conductor_api).await,
+        Err(SysValidationError::ValidationOutcome(ValidationOutcome::LinkType(_, _)))
+    );
+}
+
 #[tokio::test(threaded_scheduler)]
 async fn check_app_entry_type_test() {
     observability::test_run().ok();
@@ -482,3 +562,49 @@ async fn check_entry_not_private_test() {
         ))
     );
 }
+
+mod fuzz {
+    use super::*;
+    use holochain_types::prop::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// The pure checks must return an outcome, never panic, for any
+        /// header, however malformed
+        #[test]
+        fn header_checks_never_panic(header in any_header(), prev_header in any_header()) {
+            let _ = check_prev_header(&header);
+            let _ = check_prev_timestamp(&header, &prev_header);
+            let _ = check_new_entry_header(&header);
+
+            let seq_ok = header.header_seq() > 0
+                && prev_header.header_seq() == header.header_seq() - 1;
+            prop_assert_eq!(check_prev_seq(&header, &prev_header).is_ok(), seq_ok);
+        }
+
+        #[test]
+        fn entry_checks_never_panic(entry_type in entry_type(), entry in entry()) {
+            let _ = check_entry_type(&entry_type, &entry);
+            let _ = check_entry_size(&entry);
+        }
+
+        #[test]
+        fn tag_size_is_enforced(tag in link_tag()) {
+            prop_assert_eq!(check_tag_size(&tag).is_ok(), tag.0.len() < MAX_TAG_SIZE);
+        }
+
+        #[test]
+        fn entry_hash_only_matches_its_entry(entry in entry(), other in entry_hash()) {
+            let hash = EntryHash::with_data_sync(&entry);
+            prop_assert!(tokio_safe_block_on::tokio_safe_block_forever_on(
+                check_entry_hash(&hash, &entry)
+            )
+            .is_ok());
+            prop_assume!(other != hash);
+            prop_assert!(tokio_safe_block_on::tokio_safe_block_forever_on(
+                check_entry_hash(&other, &entry)
+            )
+            .is_err());
+        }
+    }
+}
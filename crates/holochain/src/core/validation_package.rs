@@ -0,0 +1,425 @@
+//! Validation packages: the earlier elements from an author's source chain
+//! which validators need to validate one of the author's headers.
+//!
+//! Each entry def says which elements its validation needs with a
+//! [RequiredValidationType]. Authors assemble packages from their own
+//! chain, to validate their commits and to attach when publishing.
+
+use crate::conductor::{api::CellConductorApiT, entry_def_store::EntryDefBufferKey};
+use crate::core::{
+    ribosome::{
+        error::RibosomeResult,
+        guest_callback::entry_defs::{EntryDefsHostAccess, EntryDefsInvocation, EntryDefsResult},
+        RibosomeT,
+    },
+    state::source_chain::{SourceChain, SourceChainResult},
+};
+use holo_hash::{EntryHash, HeaderHash};
+use holochain_keystore::AgentPubKeyExt;
+use holochain_zome_types::{
+    element::{Element, ElementEntry},
+    entry_def::{EntryDef, EntryVisibility},
+    header::{AppEntryType, EntryType},
+    query::ChainQueryFilter,
+    validate::{RequiredValidationType, ValidationPackage},
+    Header,
+};
+
+/// Which validation package a header needs, from its entry def in the
+/// Cell's entry def store. Only app entries can ask for a package.
+pub async fn required_validation_type(
+    header: &Header,
+    conductor_api: &impl CellConductorApiT,
+) -> RequiredValidationType {
+    entry_def_of(header, conductor_api)
+        .await
+        .map(|entry_def| entry_def.required_validation_type)
+        .unwrap_or_default()
+}
+
+/// The entry def of a header's app entry, from the Cell's entry def store.
+/// None if the header has no app entry, or its entry def can't be found.
+pub async fn entry_def_of(
+    header: &Header,
+    conductor_api: &impl CellConductorApiT,
+) -> Option<EntryDef> {
+    let entry_type = app_entry_type(header)?;
+    let zome_index = u8::from(entry_type.zome_id()) as usize;
+    // Sys validation rejects headers for missing zomes
+    let (_, zome) = conductor_api
+        .get_this_dna()
+        .await
+        .and_then(|dna_file| dna_file.dna().zomes.get(zome_index).cloned())?;
+    let key = EntryDefBufferKey::new(zome, entry_type.id());
+    conductor_api.get_entry_def(&key).await
+}
+
+/// The same as [required_validation_type] but asking the zome's
+/// `entry_defs` callback directly, for when there's no conductor api
+pub fn required_validation_type_from_ribosome(
+    ribosome: &impl RibosomeT,
+    header: &Header,
+) -> RibosomeResult<RequiredValidationType> {
+    let entry_type = match app_entry_type(header) {
+        Some(entry_type) => entry_type,
+        None => return Ok(RequiredValidationType::Element),
+    };
+    let zome_name = match ribosome
+        .dna_file()
+        .dna()
+        .zomes
+        .get(u8::from(entry_type.zome_id()) as usize)
+    {
+        Some((zome_name, _)) => zome_name.clone(),
+        None => return Ok(RequiredValidationType::Element),
+    };
+    Ok(
+        match ribosome.run_entry_defs(EntryDefsHostAccess, EntryDefsInvocation)? {
+            EntryDefsResult::Defs(mut defs) => defs
+                .remove(&zome_name)
+                .and_then(|entry_defs| {
+                    entry_defs
+                        .into_iter()
+                        .nth(u8::from(entry_type.id()) as usize)
+                })
+                .map(|entry_def| entry_def.required_validation_type)
+                .unwrap_or_default(),
+            EntryDefsResult::Err(_, _) => RequiredValidationType::Element,
+        },
+    )
+}
+
+/// Collect the elements before this header which its validators need,
+/// in chain order.
+/// A sub chain package still holds every header before this one, so
+/// validators can check nothing was left out, but only the elements of the
+/// header's entry type carry their entries.
+pub fn assemble(
+    source_chain: &SourceChain,
+    header: &Header,
+    required: RequiredValidationType,
+) -> SourceChainResult<ValidationPackage> {
+    let query = ChainQueryFilter::new()
+        .sequence_range(0..header.header_seq())
+        .include_entries(true);
+    let mut elements = match required {
+        RequiredValidationType::Element => Vec::new(),
+        RequiredValidationType::SubChain => source_chain
+            .query(&query)?
+            .into_iter()
+            .map(|el| {
+                if el.header().entry_type() == header.entry_type() {
+                    el
+                } else {
+                    Element::new(el.signed_header().clone(), None)
+                }
+            })
+            .collect(),
+        RequiredValidationType::Full => source_chain.query(&query)?,
+    };
+    // Queries walk the chain backwards
+    elements.reverse();
+    Ok(ValidationPackage::new(elements))
+}
+
+/// Whether a package [verify]s as the author's holds everything the
+/// required validation type asks for: every header before this one, with
+/// the entries of all of them for a full package, or of those of the
+/// header's entry type for a sub chain. Private entries may be hidden.
+pub fn is_complete(
+    package: &ValidationPackage,
+    header: &Header,
+    required: RequiredValidationType,
+) -> bool {
+    let has_entry = |el: &Element| match (el.entry(), el.header().entry_type()) {
+        (ElementEntry::Present(_), _) | (ElementEntry::NotApplicable, None) => true,
+        (ElementEntry::Hidden, Some(entry_type)) => {
+            entry_type.visibility() == &EntryVisibility::Private
+        }
+        _ => false,
+    };
+    match required {
+        RequiredValidationType::Element => true,
+        // Verified packages have rising seqs below the header's,
+        // so there's one element for each only if there are this many
+        RequiredValidationType::SubChain => {
+            package.0.len() == header.header_seq() as usize
+                && package
+                    .0
+                    .iter()
+                    .filter(|el| el.header().entry_type() == header.entry_type())
+                    .all(has_entry)
+        }
+        RequiredValidationType::Full => {
+            package.0.len() == header.header_seq() as usize && package.0.iter().all(has_entry)
+        }
+    }
+}
+
+/// The part of a complete package the app's callbacks see: a sub chain
+/// package narrowed down to the elements of the header's entry type
+pub fn for_validation(
+    package: ValidationPackage,
+    header: &Header,
+    required: RequiredValidationType,
+) -> ValidationPackage {
+    match required {
+        RequiredValidationType::SubChain => ValidationPackage::new(
+            package
+                .0
+                .into_iter()
+                .filter(|el| el.header().entry_type() == header.entry_type())
+                .collect(),
+        ),
+        _ => package,
+    }
+}
+
+/// Check a package received with a header really is from the header's
+/// author: every element is signed by the author, hashes to the address it
+/// claims, and sits in the author's chain before the header. Elements which
+/// are next to each other in the chain must link up.
+pub async fn verify(package: &ValidationPackage, header: &Header) -> SourceChainResult<bool> {
+    let author = header.author();
+    let mut prev: Option<(u32, &HeaderHash)> = None;
+    for element in package.0.iter() {
+        let el_header = element.header();
+        let seq = el_header.header_seq();
+        if el_header.author() != author
+            || seq >= header.header_seq()
+            || *element.header_address() != HeaderHash::with_data_sync(el_header)
+        {
+            return Ok(false);
+        }
+        match prev {
+            Some((prev_seq, _)) if seq <= prev_seq => return Ok(false),
+            Some((prev_seq, prev_hash)) if seq == prev_seq + 1 => {
+                if el_header.prev_header() != Some(prev_hash) {
+                    return Ok(false);
+                }
+            }
+            _ => (),
+        }
+        if let (ElementEntry::Present(entry), Some((entry_hash, _))) =
+            (element.entry(), el_header.entry_data())
+        {
+            if *entry_hash != EntryHash::with_data_sync(entry) {
+                return Ok(false);
+            }
+        }
+        if !author
+            .verify_signature(element.signature(), el_header)
+            .await?
+        {
+            return Ok(false);
+        }
+        prev = Some((seq, element.header_address()));
+    }
+    Ok(match prev {
+        Some((prev_seq, prev_hash)) if prev_seq + 1 == header.header_seq() => {
+            header.prev_header() == Some(prev_hash)
+        }
+        _ => true,
+    })
+}
+
+fn app_entry_type(header: &Header) -> Option<&AppEntryType> {
+    match header.entry_type() {
+        Some(EntryType::App(entry_type)) => Some(entry_type),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{state::source_chain::SourceChain, workflow::fake_genesis};
+    use holochain_serialized_bytes::{SerializedBytes, UnsafeBytes};
+    use holochain_state::test_utils::test_cell_env;
+    use holochain_types::{header::builder, EntryHashed};
+    use holochain_zome_types::{element::SignedHeaderHashed, signature::Signature, Entry};
+    use matches::assert_matches;
+    use std::convert::TryInto;
+
+    fn app_entry(n: u8) -> (Entry, EntryHash) {
+        EntryHashed::from_content_sync(Entry::App(
+            SerializedBytes::from(UnsafeBytes::from(vec![n]))
+                .try_into()
+                .unwrap(),
+        ))
+        .into_inner()
+    }
+
+    fn entry_type(id: u8) -> EntryType {
+        EntryType::App(AppEntryType::new(
+            id.into(),
+            0.into(),
+            EntryVisibility::Public,
+        ))
+    }
+
+    /// Genesis, then app entries of types 0, 1, 0, then the header being
+    /// validated, of type 0. Chains made from different `content` fork
+    /// after genesis.
+    async fn chain_with_header(chain: &mut SourceChain, content: u8) -> Header {
+        fake_genesis(chain).await.unwrap();
+        let mut last = None;
+        for (n, id) in [0u8, 1, 0, 0].iter().enumerate() {
+            let (entry, entry_hash) = app_entry(content + n as u8);
+            let builder = builder::Create {
+                entry_type: entry_type(*id),
+                entry_hash,
+            };
+            last = Some(chain.put(builder, Some(entry)).await.unwrap());
+        }
+        chain
+            .get_header(&last.unwrap())
+            .unwrap()
+            .unwrap()
+            .header()
+            .clone()
+    }
+
+    fn seqs(package: &ValidationPackage) -> Vec<u32> {
+        package
+            .0
+            .iter()
+            .map(|el| el.header().header_seq())
+            .collect()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn packages_hold_what_the_entry_def_asks_for() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut chain = SourceChain::new(env.clone().into()).unwrap();
+        let header = chain_with_header(&mut chain, 0).await;
+        assert_eq!(header.header_seq(), 6);
+
+        let package = assemble(&chain, &header, RequiredValidationType::Element).unwrap();
+        assert!(package.0.is_empty());
+
+        let package = assemble(&chain, &header, RequiredValidationType::SubChain).unwrap();
+        assert_eq!(seqs(&package), vec![0, 1, 2, 3, 4, 5]);
+        let with_entries = package
+            .0
+            .iter()
+            .filter(|el| el.entry().as_option().is_some())
+            .map(|el| el.header().header_seq())
+            .collect::<Vec<_>>();
+        assert_eq!(with_entries, vec![3, 5]);
+        let package = for_validation(package, &header, RequiredValidationType::SubChain);
+        assert_eq!(seqs(&package), vec![3, 5]);
+
+        let package = assemble(&chain, &header, RequiredValidationType::Full).unwrap();
+        assert_eq!(seqs(&package), vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn only_the_authors_own_chain_verifies() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut chain = SourceChain::new(env.clone().into()).unwrap();
+        let header = chain_with_header(&mut chain, 0).await;
+
+        let full = assemble(&chain, &header, RequiredValidationType::Full).unwrap();
+        assert_matches!(verify(&full, &header).await, Ok(true));
+        let sub_chain = assemble(&chain, &header, RequiredValidationType::SubChain).unwrap();
+        assert_matches!(verify(&sub_chain, &header).await, Ok(true));
+
+        // out of order
+        let mut package = full.clone();
+        package.0.swap(1, 2);
+        assert_matches!(verify(&package, &header).await, Ok(false));
+
+        // an element from a fork of the author's chain
+        let fork_env = test_cell_env();
+        let mut fork = SourceChain::new(fork_env.env().into()).unwrap();
+        let fork_header = chain_with_header(&mut fork, 10).await;
+        let forked = assemble(&fork, &fork_header, RequiredValidationType::Full).unwrap();
+        let mut package = full.clone();
+        package.0[4] = forked.0[4].clone();
+        assert_matches!(verify(&package, &header).await, Ok(false));
+        // which can't link up to the header either
+        let package = ValidationPackage::new(vec![forked.0[5].clone()]);
+        assert_matches!(verify(&package, &header).await, Ok(false));
+
+        // a forged signature
+        let mut package = full.clone();
+        let (signed_header, entry) = package.0.remove(0).into_inner();
+        let (header_hashed, _) = signed_header.into_header_and_signature();
+        package.0.insert(
+            0,
+            Element::new(
+                SignedHeaderHashed::with_presigned(header_hashed, Signature(vec![0; 64])),
+                entry.into_option(),
+            ),
+        );
+        assert_matches!(verify(&package, &header).await, Ok(false));
+
+        // the chain of another header from further back
+        let earlier = full.0[3].header().clone();
+        assert_matches!(verify(&full, &earlier).await, Ok(false));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn packages_must_hold_everything_asked_for() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut chain = SourceChain::new(env.clone().into()).unwrap();
+        let header = chain_with_header(&mut chain, 0).await;
+
+        let full = assemble(&chain, &header, RequiredValidationType::Full).unwrap();
+        let sub_chain = assemble(&chain, &header, RequiredValidationType::SubChain).unwrap();
+        let empty = ValidationPackage::new(Vec::new());
+        assert!(is_complete(
+            &empty,
+            &header,
+            RequiredValidationType::Element
+        ));
+        assert!(!is_complete(
+            &empty,
+            &header,
+            RequiredValidationType::SubChain
+        ));
+        assert!(is_complete(&full, &header, RequiredValidationType::Full));
+        assert!(is_complete(
+            &full,
+            &header,
+            RequiredValidationType::SubChain
+        ));
+        assert!(is_complete(
+            &sub_chain,
+            &header,
+            RequiredValidationType::SubChain
+        ));
+        // the entries of other types were left out
+        assert!(!is_complete(
+            &sub_chain,
+            &header,
+            RequiredValidationType::Full
+        ));
+
+        // an element of the header's type left out still links up,
+        // as only the earliest elements are gone
+        let mut package = sub_chain.clone();
+        package.0.drain(..4);
+        assert_matches!(verify(&package, &header).await, Ok(true));
+        assert!(!is_complete(
+            &package,
+            &header,
+            RequiredValidationType::SubChain
+        ));
+
+        // an entry of the header's type withheld
+        let mut package = sub_chain.clone();
+        let shh = package.0[3].signed_header().clone();
+        package.0[3] = Element::new(shh, None);
+        assert_matches!(verify(&package, &header).await, Ok(true));
+        assert!(!is_complete(
+            &package,
+            &header,
+            RequiredValidationType::SubChain
+        ));
+    }
+}
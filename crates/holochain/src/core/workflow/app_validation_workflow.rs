@@ -8,20 +8,33 @@ use super::{
         integrate_single_metadata,
     },
     produce_dht_ops_workflow::dht_op_light::light_to_op,
-    sys_validation_workflow::types::DepType,
 };
+use crate::conductor::api::CellConductorApiT;
 use crate::core::{
+    clock::Clock,
     queue_consumer::{OneshotWriter, TriggerSender, WorkComplete},
+    ribosome::{
+        error::RibosomeResult,
+        guest_callback::validate::{ValidateHostAccess, ValidateInvocation, ValidateResult},
+        wasm_ribosome::WasmRibosome,
+        RibosomeT,
+    },
     state::{
         dht_op_integration::{IntegratedDhtOpsStore, IntegrationLimboStore, IntegrationLimboValue},
         element_buf::ElementBuf,
         metadata::MetadataBuf,
-        validation_db::{ValidationLimboStatus, ValidationLimboStore, ValidationLimboValue},
+        validation_db::{
+            AbandonedDhtOpsStore, AbandonedDhtOpsValue, AbandonedReason, ValidationLimboStatus,
+            ValidationLimboStore, ValidationLimboValue,
+        },
         workspace::{Workspace, WorkspaceResult},
     },
+    subconscious::{DependencyJudgement, UnresolvedDependencyPolicy},
+    validation_package::{self, required_validation_type_from_ribosome},
 };
 use fallible_iterator::FallibleIterator;
-use holo_hash::DhtOpHash;
+use holo_hash::{AnyDhtHash, DhtOpHash, HeaderHash};
+use holochain_p2p::{HolochainP2pCell, HolochainP2pCellT};
 use holochain_state::{
     buffer::{BufferedStore, KvBufFresh},
     db::{INTEGRATED_DHT_OPS, INTEGRATION_LIMBO},
@@ -29,18 +42,37 @@ use holochain_state::{
     fresh_reader,
     prelude::*,
 };
-use holochain_types::{dht_op::DhtOp, dht_op::DhtOpLight, validate::ValidationStatus, Timestamp};
+use holochain_types::{
+    dht_op::DhtOp,
+    dht_op::DhtOpLight,
+    validate::{ValidationPackageResponse, ValidationStatus},
+};
+use holochain_zome_types::{
+    header::EntryType,
+    validate::{RequiredValidationType, ValidationPackage},
+    Entry, Header,
+};
+use std::sync::Arc;
 use tracing::*;
 
-#[instrument(skip(workspace, writer, trigger_integration))]
+#[cfg(test)]
+mod tests;
+
+#[instrument(skip(workspace, writer, trigger_integration, network, conductor_api, policy))]
 pub async fn app_validation_workflow(
     mut workspace: AppValidationWorkspace,
     writer: OneshotWriter,
     trigger_integration: &mut TriggerSender,
+    mut network: HolochainP2pCell,
+    conductor_api: &impl CellConductorApiT,
+    policy: &UnresolvedDependencyPolicy,
 ) -> WorkflowResult<WorkComplete> {
-    warn!("unimplemented passthrough");
+    // The app's validate callbacks are in the dna
+    let ribosome = conductor_api.get_this_dna().await.map(WasmRibosome::new);
 
-    let complete = app_validation_workflow_inner(&mut workspace).await?;
+    let complete =
+        app_validation_workflow_inner(&mut workspace, ribosome.as_ref(), &mut network, policy)
+            .await?;
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
     // commit the workspace
@@ -51,8 +83,12 @@ pub async fn app_validation_workflow(
 
     Ok(complete)
 }
+
 async fn app_validation_workflow_inner(
     workspace: &mut AppValidationWorkspace,
+    ribosome: Option<&impl RibosomeT>,
+    network: &mut impl HolochainP2pCellT,
+    policy: &UnresolvedDependencyPolicy,
 ) -> WorkflowResult<WorkComplete> {
     let env = workspace.validation_limbo.env().clone();
     let (ops, mut awaiting_ops): (Vec<ValidationLimboValue>, Vec<ValidationLimboValue>) =
@@ -77,23 +113,16 @@ async fn app_validation_workflow_inner(
     debug!(?ops, ?awaiting_ops);
     for mut vlv in ops {
         match &vlv.status {
+            // Try again in case the dependency has arrived
             ValidationLimboStatus::AwaitingAppDeps(_) => {
-                let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
-                let hash = DhtOpHash::with_data_sync(&op);
-                workspace.put_val_limbo(hash, vlv)?;
+                app_validate_op(workspace, ribosome, network, policy, vlv).await?
             }
             ValidationLimboStatus::SysValidated => {
                 if vlv.pending_dependencies.pending_dependencies() {
                     vlv.status = ValidationLimboStatus::PendingValidation;
                     awaiting_ops.push(vlv);
                 } else {
-                    let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
-                    let hash = DhtOpHash::with_data_sync(&op);
-                    let iv = IntegrationLimboValue {
-                        validation_status: ValidationStatus::Valid,
-                        op: vlv.op,
-                    };
-                    workspace.put_int_limbo(hash, iv, op)?;
+                    app_validate_op(workspace, ribosome, network, policy, vlv).await?
                 }
             }
             _ => unreachable!("Should not contain any other status"),
@@ -111,13 +140,18 @@ async fn app_validation_workflow_inner(
         if let Some(iv) = iv {
             return Ok(Some(iv.validation_status));
         }
+        // Abandoned ops are never integrated
+        if workspace.abandoned_dht_ops.get(dep)?.is_some() {
+            return Ok(Some(ValidationStatus::Abandoned));
+        }
         Ok(None)
     }
     // Check awaiting proof that might be able to be progressed now.
     // Including any awaiting proof from this run.
     'op_loop: for mut vlv in awaiting_ops {
         let mut still_awaiting = Vec::new();
-        for dep in vlv.pending_dependencies.pending.drain(..) {
+        // Keep the deps on the op in case it's abandoned
+        for dep in vlv.pending_dependencies.pending.clone() {
             match check_dep_status(dep.as_ref(), &workspace)? {
                 Some(status) => {
                     match status {
@@ -125,24 +159,20 @@ async fn app_validation_workflow_inner(
                             // Discarding dep because we have proof it's integrated and valid
                         }
                         ValidationStatus::Rejected | ValidationStatus::Abandoned => {
-                            match dep {
-                                DepType::FixedElement(_) => {
-                                    // Mark this op as invalid and integrate it.
-                                    // There is no reason to check the other deps as it is rejected.
+                            match UnresolvedDependencyPolicy::judge_invalid(&dep, status) {
+                                DependencyJudgement::Abandoned(reason) => {
+                                    // This op can never be validated so abandon it.
+                                    // There is no reason to check the other deps.
                                     let op =
                                         light_to_op(vlv.op.clone(), &workspace.element_pending)
                                             .await?;
                                     let hash = DhtOpHash::with_data_sync(&op);
-                                    let iv = IntegrationLimboValue {
-                                        validation_status: status,
-                                        op: vlv.op,
-                                    };
-                                    workspace.put_int_limbo(hash, iv, op)?;
+                                    workspace.abandon(hash, vlv, op, reason)?;
 
                                     // Continue to the next op
                                     continue 'op_loop;
                                 }
-                                DepType::AnyElement(_) => {
+                                DependencyJudgement::Pending => {
                                     // The dependency is any element with for an entry
                                     // So we can't say that it is invalid because there could
                                     // always be a valid entry.
@@ -159,26 +189,197 @@ async fn app_validation_workflow_inner(
                 }
             }
         }
-        let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
-        let hash = DhtOpHash::with_data_sync(&op);
         if !still_awaiting.is_empty() {
+            let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
+            let hash = DhtOpHash::with_data_sync(&op);
             vlv.pending_dependencies.pending = still_awaiting;
-            workspace.put_val_limbo(hash, vlv)?;
+            match policy.judge_missing(&vlv, None, workspace.clock.now()) {
+                DependencyJudgement::Pending => workspace.put_val_limbo(hash, vlv)?,
+                DependencyJudgement::Abandoned(reason) => {
+                    workspace.abandon(hash, vlv, op, reason)?
+                }
+            }
         } else {
-            let iv = IntegrationLimboValue {
-                validation_status: ValidationStatus::Valid,
-                op: vlv.op,
-            };
-            workspace.put_int_limbo(hash, iv, op)?;
+            app_validate_op(workspace, ribosome, network, policy, vlv).await?
         }
     }
     Ok(WorkComplete::Complete)
 }
 
+/// Run the app's validation of an op whose sys dependencies are all met,
+/// then send it on to integration or leave it waiting on what the app
+/// says it depends on.
+/// Ops whose entry def asks for a validation package wait until they have
+/// a complete one, from the author if they didn't arrive with it.
+async fn app_validate_op(
+    workspace: &mut AppValidationWorkspace,
+    ribosome: Option<&impl RibosomeT>,
+    network: &mut impl HolochainP2pCellT,
+    policy: &UnresolvedDependencyPolicy,
+    mut vlv: ValidationLimboValue,
+) -> WorkflowResult<()> {
+    let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
+    let hash = DhtOpHash::with_data_sync(&op);
+    let ribosome = match ribosome {
+        Some(ribosome) => ribosome,
+        None => {
+            warn!(
+                ?hash,
+                "The dna is missing so the op can't be app validated yet"
+            );
+            return workspace.put_val_limbo(hash, vlv);
+        }
+    };
+    let header = op.header();
+    let required = match validated_entry(&op) {
+        Some(_) => match required_validation_type_from_ribosome(ribosome, &header) {
+            Ok(required) => required,
+            Err(error) => {
+                warn!(?error, ?hash, "Could not find the op's entry def");
+                return workspace.put_val_limbo(hash, vlv);
+            }
+        },
+        None => RequiredValidationType::Element,
+    };
+    let validation_package = match required {
+        RequiredValidationType::Element => None,
+        required => {
+            let package = match vlv.validation_package.take() {
+                Some(package) if validation_package::is_complete(&package, &header, required) => {
+                    Some(package)
+                }
+                _ => fetch_validation_package(network, &header, required).await,
+            };
+            match package {
+                Some(package) => {
+                    vlv.validation_package = Some(package.clone());
+                    Some(validation_package::for_validation(
+                        package, &header, required,
+                    ))
+                }
+                None => {
+                    let dep: AnyDhtHash = HeaderHash::with_data_sync(&header).into();
+                    vlv.status = ValidationLimboStatus::AwaitingAppDeps(dep.clone());
+                    return match policy.judge_missing(&vlv, Some(dep), workspace.clock.now()) {
+                        DependencyJudgement::Pending => workspace.put_val_limbo(hash, vlv),
+                        DependencyJudgement::Abandoned(reason) => {
+                            workspace.abandon(hash, vlv, op, reason)
+                        }
+                    };
+                }
+            }
+        }
+    };
+    let validation_status = match run_validate(ribosome, &op, validation_package) {
+        Ok(ValidateResult::Valid) => ValidationStatus::Valid,
+        Ok(ValidateResult::Invalid(reason)) => {
+            debug!(?hash, %reason, "Op is invalid");
+            ValidationStatus::Rejected
+        }
+        Ok(ValidateResult::UnresolvedDependencies(deps)) => {
+            let dep: Option<AnyDhtHash> = deps.into_iter().next().map(Into::into);
+            if let Some(dep) = &dep {
+                vlv.status = ValidationLimboStatus::AwaitingAppDeps(dep.clone());
+            }
+            return match policy.judge_missing(&vlv, dep, workspace.clock.now()) {
+                DependencyJudgement::Pending => workspace.put_val_limbo(hash, vlv),
+                DependencyJudgement::Abandoned(reason) => workspace.abandon(hash, vlv, op, reason),
+            };
+        }
+        Err(error) => {
+            warn!(?error, ?hash, "Could not run the validate callback");
+            return workspace.put_val_limbo(hash, vlv);
+        }
+    };
+    let iv = IntegrationLimboValue {
+        validation_status,
+        op: vlv.op,
+    };
+    workspace.put_int_limbo(hash, iv, op)
+}
+
+/// Ask a header's author for the validation package it needs.
+/// None if the author can't be reached, or doesn't send a complete package
+/// of its own chain.
+async fn fetch_validation_package(
+    network: &mut impl HolochainP2pCellT,
+    header: &Header,
+    required: RequiredValidationType,
+) -> Option<ValidationPackage> {
+    let header_hash = HeaderHash::with_data_sync(header);
+    let package = match network
+        .get_validation_package(header.author().clone(), header_hash.clone())
+        .await
+    {
+        Ok(ValidationPackageResponse(package)) => package?,
+        Err(error) => {
+            debug!(?error, ?header_hash, "Could not get the validation package");
+            return None;
+        }
+    };
+    match validation_package::verify(&package, header).await {
+        Ok(true) if validation_package::is_complete(&package, header, required) => Some(package),
+        _ => {
+            warn!(?header_hash, "The author sent a bad validation package");
+            None
+        }
+    }
+}
+
+/// The header and entry the app validates for an op.
+/// Ops without an entry have nothing for the app to validate.
+fn validated_entry(op: &DhtOp) -> Option<(Header, &Entry)> {
+    match op {
+        DhtOp::StoreEntry(_, header, entry) => Some((header.clone().into(), &**entry)),
+        DhtOp::StoreElement(_, header, Some(entry)) => Some((header.clone(), &**entry)),
+        _ => None,
+    }
+}
+
+/// Run the validate callback of the zome which defines an op's app entry,
+/// with the validation package its entry def asks for.
+/// Ops without an app entry have nothing for the app to validate.
+fn run_validate(
+    ribosome: &impl RibosomeT,
+    op: &DhtOp,
+    validation_package: Option<ValidationPackage>,
+) -> RibosomeResult<ValidateResult> {
+    let (header, entry) = match validated_entry(op) {
+        Some(header_and_entry) => header_and_entry,
+        None => return Ok(ValidateResult::Valid),
+    };
+    let zome_id = match header.entry_type() {
+        Some(EntryType::App(app_entry_type)) => app_entry_type.zome_id(),
+        _ => return Ok(ValidateResult::Valid),
+    };
+    let zome_name = match ribosome
+        .dna_file()
+        .dna()
+        .zomes
+        .get(u8::from(zome_id) as usize)
+    {
+        Some((zome_name, _)) => zome_name.clone(),
+        // Sys validation has already rejected entries for missing zomes
+        None => return Ok(ValidateResult::Valid),
+    };
+    let validation_package = validation_package.map(Arc::new);
+    ribosome.run_validate(
+        ValidateHostAccess {
+            validation_package: validation_package.clone(),
+        },
+        ValidateInvocation {
+            zome_name,
+            entry: Arc::new(entry.clone()),
+            validation_package,
+        },
+    )
+}
+
 pub struct AppValidationWorkspace {
     pub integrated_dht_ops: IntegratedDhtOpsStore,
     pub integration_limbo: IntegrationLimboStore,
     pub validation_limbo: ValidationLimboStore,
+    pub abandoned_dht_ops: AbandonedDhtOpsStore,
     // Integrated data
     pub element_vault: ElementBuf,
     pub meta_vault: MetadataBuf,
@@ -193,6 +394,8 @@ pub struct AppValidationWorkspace {
     pub meta_cache: MetadataBuf,
     // Ops to disintegrate
     pub to_disintegrate_pending: Vec<DhtOpLight>,
+    /// Where tries and abandonments get their timestamps from
+    pub clock: Clock,
 }
 
 impl AppValidationWorkspace {
@@ -203,6 +406,7 @@ impl AppValidationWorkspace {
         let integration_limbo = KvBufFresh::new(env.clone(), db);
 
         let validation_limbo = ValidationLimboStore::new(env.clone())?;
+        let abandoned_dht_ops = AbandonedDhtOpsStore::new(env.clone())?;
 
         let element_vault = ElementBuf::vault(env.clone(), false)?;
         let meta_vault = MetadataBuf::vault(env.clone())?;
@@ -219,6 +423,7 @@ impl AppValidationWorkspace {
             integrated_dht_ops,
             integration_limbo,
             validation_limbo,
+            abandoned_dht_ops,
             element_vault,
             meta_vault,
             element_pending,
@@ -228,6 +433,7 @@ impl AppValidationWorkspace {
             element_cache,
             meta_cache,
             to_disintegrate_pending: Vec::new(),
+            clock: Clock::default(),
         })
     }
 
@@ -236,7 +442,7 @@ impl AppValidationWorkspace {
         hash: DhtOpHash,
         mut vlv: ValidationLimboValue,
     ) -> WorkflowResult<()> {
-        vlv.last_try = Some(Timestamp::now());
+        vlv.last_try = Some(self.clock.now());
         vlv.num_tries += 1;
         self.validation_limbo.put(hash, vlv)?;
         Ok(())
@@ -257,6 +463,31 @@ impl AppValidationWorkspace {
         Ok(())
     }
 
+    /// Give up on validating an op and record why
+    fn abandon(
+        &mut self,
+        hash: DhtOpHash,
+        vlv: ValidationLimboValue,
+        op: DhtOp,
+        reason: AbandonedReason,
+    ) -> WorkflowResult<()> {
+        debug!(?hash, ?reason, "Abandoning op");
+        self.abandoned_dht_ops.put(
+            hash.clone(),
+            AbandonedDhtOpsValue {
+                op: vlv.op.clone(),
+                reason,
+                pending_dependencies: vlv.pending_dependencies,
+                when_abandoned: self.clock.now(),
+            },
+        )?;
+        let iv = IntegrationLimboValue {
+            validation_status: ValidationStatus::Abandoned,
+            op: vlv.op,
+        };
+        self.put_int_limbo(hash, iv, op)
+    }
+
     #[tracing::instrument(skip(self, writer))]
     /// We need to cancel any deletes for the pending data
     /// where the ops still in validation limbo reference that data
@@ -274,10 +505,10 @@ impl AppValidationWorkspace {
 
 impl Workspace for AppValidationWorkspace {
     fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> WorkspaceResult<()> {
-        warn!("unimplemented passthrough");
         self.update_element_stores(writer)?;
         self.validation_limbo.0.flush_to_txn_ref(writer)?;
         self.integration_limbo.flush_to_txn_ref(writer)?;
+        self.abandoned_dht_ops.0.flush_to_txn_ref(writer)?;
         self.element_pending.flush_to_txn_ref(writer)?;
         self.meta_pending.flush_to_txn_ref(writer)?;
         self.element_judged.flush_to_txn_ref(writer)?;
@@ -0,0 +1,230 @@
+use super::*;
+use crate::{
+    core::{
+        ribosome::{guest_callback::entry_defs::EntryDefsResult, MockRibosomeT},
+        state::source_chain::SourceChain,
+        validation_package,
+        workflow::{fake_genesis, incoming_dht_ops_workflow::incoming_dht_ops_workflow},
+    },
+    fixt::*,
+};
+use ::fixt::prelude::*;
+use holochain_p2p::MockHolochainP2pCellT;
+use holochain_serialized_bytes::{SerializedBytes, UnsafeBytes};
+use holochain_state::{
+    env::EnvironmentWrite,
+    test_utils::{test_cell_env, TestEnvironment},
+};
+use holochain_types::{fixt::*, header::builder, EntryHashed};
+use holochain_zome_types::{
+    entry_def::EntryVisibility, header::AppEntryType, validate::RequiredValidationType,
+};
+use std::{collections::BTreeMap, convert::TryInto, sync::Mutex};
+
+/// An app entry published, with its validation package if `attached`,
+/// which has been received and sys validated
+async fn sys_validated_op(
+    env: &EnvironmentWrite,
+    attached: bool,
+) -> (DhtOpHash, HeaderHash, ValidationPackage) {
+    let author_env = test_cell_env();
+    let mut chain = SourceChain::new(author_env.env().into()).unwrap();
+    fake_genesis(&mut chain).await.unwrap();
+    let (entry, entry_hash) = EntryHashed::from_content_sync(Entry::App(
+        SerializedBytes::from(UnsafeBytes::from(vec![0]))
+            .try_into()
+            .unwrap(),
+    ))
+    .into_inner();
+    let builder = builder::Create {
+        entry_type: EntryType::App(AppEntryType::new(
+            0.into(),
+            0.into(),
+            EntryVisibility::Public,
+        )),
+        entry_hash,
+    };
+    let header_hash = chain.put(builder, Some(entry.clone())).await.unwrap();
+    let (header, signature) = chain
+        .get_header(&header_hash)
+        .unwrap()
+        .unwrap()
+        .into_header_and_signature();
+    let header = header.into_content();
+    let package =
+        validation_package::assemble(&chain, &header, RequiredValidationType::Full).unwrap();
+
+    let op = DhtOp::StoreElement(signature, header, Some(Box::new(entry)));
+    let hash = DhtOpHash::with_data_sync(&op);
+    let (sys_validation_trigger, _rx) = TriggerSender::new();
+    incoming_dht_ops_workflow(
+        env,
+        sys_validation_trigger,
+        vec![(hash.clone(), op)],
+        if attached {
+            vec![(header_hash.clone(), package.clone())]
+        } else {
+            Vec::new()
+        },
+    )
+    .await
+    .unwrap();
+
+    let mut validation_limbo = ValidationLimboStore::new(env.clone().into()).unwrap();
+    let mut vlv = validation_limbo.get(&hash).unwrap().unwrap();
+    vlv.status = ValidationLimboStatus::SysValidated;
+    validation_limbo.put(hash.clone(), vlv).unwrap();
+    env.guard()
+        .with_commit(|writer| validation_limbo.0.flush_to_txn_ref(writer))
+        .unwrap();
+    (hash, header_hash, package)
+}
+
+/// A ribosome whose only zome's entries need a full validation package,
+/// and are validated with `result`, keeping the packages it was given
+fn ribosome_returning(
+    result: ValidateResult,
+) -> (
+    MockRibosomeT,
+    Arc<Mutex<Vec<Option<Arc<ValidationPackage>>>>>,
+) {
+    let mut dna_file = DnaFileFixturator::new(Empty).next().unwrap();
+    dna_file.dna.zomes.clear();
+    let zome_name = fixt!(ZomeName);
+    dna_file.dna.zomes.push((zome_name.clone(), fixt!(Zome)));
+    let mut entry_def = fixt!(EntryDef);
+    entry_def.required_validation_type = RequiredValidationType::Full;
+    let mut entry_defs = BTreeMap::new();
+    entry_defs.insert(zome_name, vec![entry_def].into());
+    let packages = Arc::new(Mutex::new(Vec::new()));
+    let mut ribosome = MockRibosomeT::new();
+    ribosome.expect_dna_file().return_const(dna_file);
+    ribosome
+        .expect_run_entry_defs()
+        .returning(move |_, _| Ok(EntryDefsResult::Defs(entry_defs.clone())));
+    ribosome.expect_run_validate().returning({
+        let packages = packages.clone();
+        move |_, invocation| {
+            packages
+                .lock()
+                .unwrap()
+                .push(invocation.validation_package.clone());
+            Ok(result.clone())
+        }
+    });
+    (ribosome, packages)
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn app_validation_sees_the_package_the_op_arrived_with() {
+    let TestEnvironment { env, tmpdir: _t } = test_cell_env();
+    let (hash, _, package) = sys_validated_op(&env, true).await;
+    let (ribosome, packages) = ribosome_returning(ValidateResult::Invalid("nope".into()));
+    // The author isn't asked for a package we already have
+    let mut network = MockHolochainP2pCellT::new();
+
+    let mut workspace = AppValidationWorkspace::new(env.clone().into()).unwrap();
+    app_validation_workflow_inner(
+        &mut workspace,
+        Some(&ribosome),
+        &mut network,
+        &UnresolvedDependencyPolicy::default(),
+    )
+    .await
+    .unwrap();
+
+    let iv = workspace.integration_limbo.get(&hash).unwrap().unwrap();
+    assert_eq!(iv.validation_status, ValidationStatus::Rejected);
+    assert_eq!(*packages.lock().unwrap(), vec![Some(Arc::new(package))]);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn unresolved_app_dependencies_are_waited_for() {
+    let TestEnvironment { env, tmpdir: _t } = test_cell_env();
+    let (hash, _, _) = sys_validated_op(&env, true).await;
+    let dep = fixt!(EntryHash);
+    let (ribosome, _) =
+        ribosome_returning(ValidateResult::UnresolvedDependencies(vec![dep.clone()]));
+    let mut network = MockHolochainP2pCellT::new();
+
+    let mut workspace = AppValidationWorkspace::new(env.clone().into()).unwrap();
+    app_validation_workflow_inner(
+        &mut workspace,
+        Some(&ribosome),
+        &mut network,
+        &UnresolvedDependencyPolicy::default(),
+    )
+    .await
+    .unwrap();
+
+    assert!(workspace.integration_limbo.get(&hash).unwrap().is_none());
+    let vlv = workspace.validation_limbo.get(&hash).unwrap().unwrap();
+    assert_eq!(
+        vlv.status,
+        ValidationLimboStatus::AwaitingAppDeps(dep.into())
+    );
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn missing_packages_are_fetched_from_the_author() {
+    let TestEnvironment { env, tmpdir: _t } = test_cell_env();
+    let (hash, header_hash, package) = sys_validated_op(&env, false).await;
+    let (ribosome, packages) = ribosome_returning(ValidateResult::Valid);
+    let mut network = MockHolochainP2pCellT::new();
+    {
+        let package = package.clone();
+        network
+            .expect_get_validation_package()
+            .times(1)
+            .returning(move |_, requested| {
+                assert_eq!(requested, header_hash);
+                Ok(ValidationPackageResponse(Some(package.clone())))
+            });
+    }
+
+    let mut workspace = AppValidationWorkspace::new(env.clone().into()).unwrap();
+    app_validation_workflow_inner(
+        &mut workspace,
+        Some(&ribosome),
+        &mut network,
+        &UnresolvedDependencyPolicy::default(),
+    )
+    .await
+    .unwrap();
+
+    let iv = workspace.integration_limbo.get(&hash).unwrap().unwrap();
+    assert_eq!(iv.validation_status, ValidationStatus::Valid);
+    assert_eq!(*packages.lock().unwrap(), vec![Some(Arc::new(package))]);
+}
+
+#[tokio::test(threaded_scheduler)]
+async fn ops_without_their_package_are_not_validated() {
+    let TestEnvironment { env, tmpdir: _t } = test_cell_env();
+    let (hash, header_hash, package) = sys_validated_op(&env, false).await;
+    let (ribosome, packages) = ribosome_returning(ValidateResult::Valid);
+    let mut network = MockHolochainP2pCellT::new();
+    // An incomplete package is as good as none
+    let mut incomplete = package;
+    incomplete.0.remove(0);
+    network
+        .expect_get_validation_package()
+        .returning(move |_, _| Ok(ValidationPackageResponse(Some(incomplete.clone()))));
+
+    let mut workspace = AppValidationWorkspace::new(env.clone().into()).unwrap();
+    app_validation_workflow_inner(
+        &mut workspace,
+        Some(&ribosome),
+        &mut network,
+        &UnresolvedDependencyPolicy::default(),
+    )
+    .await
+    .unwrap();
+
+    assert!(packages.lock().unwrap().is_empty());
+    assert!(workspace.integration_limbo.get(&hash).unwrap().is_none());
+    let vlv = workspace.validation_limbo.get(&hash).unwrap().unwrap();
+    assert_eq!(
+        vlv.status,
+        ValidationLimboStatus::AwaitingAppDeps(header_hash.into())
+    );
+}
@@ -1,4 +1,7 @@
 use super::error::{WorkflowError, WorkflowResult};
+use crate::core::cancel::CancelToken;
+use crate::core::clock::{Clock, Entropy};
+use crate::core::quota::AppQuota;
 use crate::core::ribosome::error::RibosomeError;
 use crate::core::ribosome::guest_callback::validate::ValidateInvocation;
 use crate::core::ribosome::guest_callback::validate::{ValidateHostAccess, ValidateResult};
@@ -7,15 +10,16 @@ use crate::core::ribosome::guest_callback::validate_link_add::ValidateCreateLink
 use crate::core::ribosome::guest_callback::validate_link_add::ValidateCreateLinkResult;
 use crate::core::ribosome::ZomeCallInvocation;
 use crate::core::ribosome::{error::RibosomeResult, RibosomeT, ZomeCallHostAccess};
+use crate::core::state::source_chain::CapGrantIndex;
 use crate::core::state::source_chain::SourceChainError;
 use crate::core::state::workspace::Workspace;
 use crate::core::{
     queue_consumer::{OneshotWriter, TriggerSender},
     state::{
-        cascade::Cascade, element_buf::ElementBuf, metadata::MetadataBuf,
+        cascade::Cascade, element_buf::ElementBuf, metadata::MetadataBuf, pins::PinsBuf,
         source_chain::SourceChain, workspace::WorkspaceResult,
     },
-    sys_validate_element,
+    sys_validate_element, validation_package,
 };
 pub use call_zome_workspace_lock::CallZomeWorkspaceLock;
 use fallible_iterator::FallibleIterator;
@@ -26,24 +30,43 @@ use holochain_state::prelude::*;
 use holochain_types::element::Element;
 use holochain_zome_types::entry::GetOptions;
 use holochain_zome_types::header::Header;
+use holochain_zome_types::provenance::{CallHop, CallProvenance};
+use holochain_zome_types::validate::RequiredValidationType;
 use holochain_zome_types::ZomeCallResponse;
 use std::sync::Arc;
 use tracing::instrument;
 
 pub mod call_zome_workspace_lock;
 
+/// How many times a zome call is re-run by default when another call
+/// to the same Cell moved the chain head before it could commit
+pub const DEFAULT_ZOME_CALL_RETRIES: u32 = 3;
+
 /// Placeholder for the return value of a zome invocation
 /// TODO: do we want this to be the same as ZomeCallInvocationRESPONSE?
 pub type ZomeCallInvocationResult = RibosomeResult<ZomeCallResponse>;
 
 #[derive(Debug)]
-pub struct CallZomeWorkflowArgs<Ribosome: RibosomeT> {
+pub struct CallZomeWorkflowArgs<Ribosome: RibosomeT + Send + 'static> {
     pub ribosome: Ribosome,
     pub invocation: ZomeCallInvocation,
+    /// The quota of the app the Cell belongs to
+    pub quota: AppQuota,
+    /// Stops the call early, e.g. when the caller's timeout is up
+    pub cancel: CancelToken,
+    /// The Cell's live cap grants
+    pub cap_grants: CapGrantIndex,
+    /// The path which led to the calling zome function,
+    /// if this call was made by one
+    pub caller: Option<CallProvenance>,
+    /// Where the call gets the time from
+    pub clock: Clock,
+    /// Where the call gets random bytes from
+    pub entropy: Entropy,
 }
 
 #[instrument(skip(workspace, network, keystore, writer, args, trigger_produce_dht_ops))]
-pub async fn call_zome_workflow<'env, Ribosome: RibosomeT>(
+pub async fn call_zome_workflow<'env, Ribosome: RibosomeT + Send + 'static>(
     workspace: CallZomeWorkspace,
     network: HolochainP2pCell,
     keystore: KeystoreSender,
@@ -52,23 +75,28 @@ pub async fn call_zome_workflow<'env, Ribosome: RibosomeT>(
     mut trigger_produce_dht_ops: TriggerSender,
 ) -> WorkflowResult<ZomeCallInvocationResult> {
     let workspace_lock = CallZomeWorkspaceLock::new(workspace);
+    let cancel = args.cancel.clone();
     let result = call_zome_workflow_inner(workspace_lock.clone(), network, keystore, args).await?;
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
+    // A cancelled call leaves nothing behind
+    cancel.check().map_err(RibosomeError::from)?;
+
     // commit the workspace
     {
         let mut guard = workspace_lock.write().await;
         let workspace = &mut guard;
         writer.with_writer(|writer| Ok(workspace.flush_to_txn_ref(writer)?))?;
     }
+    tracing::debug!(lock = ?workspace_lock.stats(), "workspace lock contention");
 
     trigger_produce_dht_ops.trigger();
 
     Ok(result)
 }
 
-async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
+async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT + Send + 'static>(
     workspace_lock: CallZomeWorkspaceLock,
     network: HolochainP2pCell,
     keystore: KeystoreSender,
@@ -77,6 +105,12 @@ async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
     let CallZomeWorkflowArgs {
         ribosome,
         invocation,
+        quota,
+        cancel,
+        cap_grants,
+        caller,
+        clock,
+        entropy,
     } = args;
 
     let zome_name = invocation.zome_name.clone();
@@ -91,14 +125,40 @@ async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
 
     let agent_key = invocation.provenance.clone();
 
+    let mut call_provenance = caller.unwrap_or_else(|| CallProvenance::new(agent_key.clone()));
+    call_provenance.hops.push(CallHop {
+        agent: invocation.cell_id.agent_pubkey().clone(),
+        dna_hash: invocation.cell_id.dna_hash().clone(),
+        zome_name: zome_name.clone(),
+        fn_name: invocation.fn_name.clone(),
+    });
+
     tracing::trace!(line = line!());
     // Create the unsafe sourcechain for use with wasm closure
-    let result = {
-        let host_access =
-            ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network.clone());
-        ribosome.call_zome_function(host_access, invocation)
+    let (ribosome, result) = {
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network.clone(),
+            quota,
+            cancel.clone(),
+            cap_grants,
+            call_provenance,
+            clock,
+            entropy,
+        );
+        // Running the wasm blocks, so keep it off the async worker threads
+        tokio::task::spawn_blocking(move || {
+            let result = ribosome.call_zome_function(host_access, invocation);
+            (ribosome, result)
+        })
+        .await
+        .map_err(RibosomeError::from)?
     };
     tracing::trace!(line = line!());
+    // The guest may have failed at a cancellation checkpoint,
+    // so report the cancellation rather than how the guest failed
+    cancel.check().map_err(RibosomeError::from)?;
 
     let to_app_validate = {
         let workspace = workspace_lock.read().await;
@@ -146,10 +206,40 @@ async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
         to_app_validate
     };
 
+    // Assemble the validation packages the entry defs ask for
+    // before the cascade borrows the workspace
+    let validation_packages = {
+        let workspace = workspace_lock.read().await;
+        to_app_validate
+            .iter()
+            .map(|element| {
+                let header = element.header();
+                Ok(
+                    match validation_package::required_validation_type_from_ribosome(
+                        &ribosome, header,
+                    )? {
+                        RequiredValidationType::Element => None,
+                        required => Some(Arc::new(validation_package::for_validation(
+                            validation_package::assemble(
+                                &workspace.source_chain,
+                                header,
+                                required,
+                            )?,
+                            header,
+                            required,
+                        ))),
+                    },
+                )
+            })
+            .collect::<WorkflowResult<Vec<_>>>()?
+    };
+
     {
         let mut workspace = workspace_lock.write().await;
         let mut cascade = workspace.cascade(network);
-        for chain_element in to_app_validate {
+        for (chain_element, validation_package) in
+            to_app_validate.into_iter().zip(validation_packages)
+        {
             // @todo have app validate in its own workflow
             if let Header::CreateLink(link_add) = chain_element.header() {
                 let validate: ValidateCreateLinkResult = ribosome.run_validate_link_add(
@@ -160,7 +250,7 @@ async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
                             let base_address: AnyDhtHash = link_add.base_address.clone().into();
                             #[allow(clippy::eval_order_dependence)]
                             cascade
-                                .dht_get(base_address.clone(), GetOptions.into())
+                                .dht_get(base_address.clone(), GetOptions::default().into())
                                 .await
                                 .map_err(RibosomeError::from)?
                                 .ok_or_else(|| RibosomeError::ElementDeps(base_address.clone()))?
@@ -173,7 +263,7 @@ async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
                             let target_address: AnyDhtHash = link_add.target_address.clone().into();
                             #[allow(clippy::eval_order_dependence)]
                             cascade
-                                .dht_get(target_address.clone(), GetOptions.into())
+                                .dht_get(target_address.clone(), GetOptions::default().into())
                                 .await
                                 .map_err(RibosomeError::from)?
                                 .ok_or_else(|| RibosomeError::ElementDeps(target_address.clone()))?
@@ -195,10 +285,13 @@ async fn call_zome_workflow_inner<'env, Ribosome: RibosomeT>(
 
             if let holochain_types::element::ElementEntry::Present(entry) = chain_element.entry() {
                 let validate: ValidateResult = ribosome.run_validate(
-                    ValidateHostAccess,
+                    ValidateHostAccess {
+                        validation_package: validation_package.clone(),
+                    },
                     ValidateInvocation {
                         zome_name: zome_name.clone(),
                         entry: Arc::new(entry.clone()),
+                        validation_package,
                     },
                 )?;
                 match validate {
@@ -229,6 +322,7 @@ pub struct CallZomeWorkspace {
     pub meta: MetadataBuf,
     pub cache_cas: ElementBuf,
     pub cache_meta: MetadataBuf,
+    pub pins: PinsBuf,
 }
 
 impl<'a> CallZomeWorkspace {
@@ -236,13 +330,15 @@ impl<'a> CallZomeWorkspace {
         let source_chain = SourceChain::new(env.clone())?;
         let cache_cas = ElementBuf::cache(env.clone())?;
         let meta = MetadataBuf::vault(env.clone())?;
-        let cache_meta = MetadataBuf::cache(env)?;
+        let cache_meta = MetadataBuf::cache(env.clone())?;
+        let pins = PinsBuf::new(env)?;
 
         Ok(CallZomeWorkspace {
             source_chain,
             meta,
             cache_cas,
             cache_meta,
+            pins,
         })
     }
 
@@ -255,6 +351,7 @@ impl<'a> CallZomeWorkspace {
             &mut self.cache_meta,
             network,
         )
+        .with_authored(&self.source_chain)
     }
 }
 
@@ -264,6 +361,7 @@ impl Workspace for CallZomeWorkspace {
         self.meta.flush_to_txn_ref(writer)?;
         self.cache_cas.flush_to_txn_ref(writer)?;
         self.cache_meta.flush_to_txn_ref(writer)?;
+        self.pins.flush_to_txn_ref(writer)?;
         Ok(())
     }
 }
@@ -272,6 +370,7 @@ impl Workspace for CallZomeWorkspace {
 pub mod tests {
     use super::*;
     use crate::core::{
+        cancel::Cancelled,
         ribosome::MockRibosomeT,
         workflow::{error::WorkflowError, genesis_workflow::tests::fake_genesis},
     };
@@ -292,7 +391,7 @@ pub mod tests {
         a: u32,
     }
 
-    async fn run_call_zome<'env, Ribosome: RibosomeT + Send + Sync + 'env>(
+    async fn run_call_zome<Ribosome: RibosomeT + Send + Sync + 'static>(
         workspace: CallZomeWorkspace,
         ribosome: Ribosome,
         invocation: ZomeCallInvocation,
@@ -302,6 +401,12 @@ pub mod tests {
         let args = CallZomeWorkflowArgs {
             invocation,
             ribosome,
+            quota: Default::default(),
+            cancel: CancelToken::new(),
+            cap_grants: Default::default(),
+            caller: None,
+            clock: Default::default(),
+            entropy: Default::default(),
         };
         call_zome_workflow_inner(workspace.into(), network, keystore, args).await
     }
@@ -340,6 +445,48 @@ pub mod tests {
         assert_matches!(error, WorkflowError::CapabilityMissing);
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn cancelled_call_reports_cancellation() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
+        fake_genesis(&mut workspace.source_chain).await.unwrap();
+
+        // The caller gives up while the wasm is running
+        let mut ribosome = MockRibosomeT::new();
+        ribosome
+            .expect_call_zome_function()
+            .returning(|host_access, _invocation| {
+                host_access.cancel.cancel(Cancelled::Requested);
+                Ok(ZomeCallResponse::Unauthorized)
+            });
+        let invocation = crate::core::ribosome::ZomeCallInvocationFixturator::new(fixt::Empty)
+            .next()
+            .unwrap();
+        let args = CallZomeWorkflowArgs {
+            invocation,
+            ribosome,
+            quota: Default::default(),
+            cancel: CancelToken::new(),
+            cap_grants: Default::default(),
+            caller: None,
+            clock: Default::default(),
+            entropy: Default::default(),
+        };
+        let error = call_zome_workflow_inner(
+            workspace.into(),
+            fixt!(HolochainP2pCell),
+            fixt!(KeystoreSender),
+            args,
+        )
+        .await
+        .unwrap_err();
+        assert_matches!(
+            error,
+            WorkflowError::RibosomeError(RibosomeError::Cancelled(Cancelled::Requested))
+        );
+    }
+
     // TODO: B-01553: Finish these tests when capabilities land
     // 1.1 If there is a secret, we look up our private CAS and see if it matches any secret for a
     // Capability Grant entry that we have stored. If it does, check that this Capability Grant is
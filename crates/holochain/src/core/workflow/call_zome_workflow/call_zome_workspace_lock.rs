@@ -1,14 +1,157 @@
+//! The lock through which a zome call and its host fns share the workspace.
+//!
+//! The lock isn't re-entrant, so a host fn which takes it while the call
+//! already holds it waits forever. To make that easy to spot the lock keeps
+//! track of where it's held, and by which [guest_call], and how long waits
+//! and holds take. In debug builds a guest call which asks for the lock
+//! while it already holds it panics, naming where the lock is held, instead
+//! of hanging silently. Other waits, however long, are left to finish.
+
 #![allow(clippy::mutex_atomic)]
 use super::*;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use parking_lot::Mutex;
+use std::{
+    cell::Cell,
+    future::Future,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+thread_local! {
+    /// The guest call running on this thread, if any
+    static GUEST_CALL: Cell<Option<u64>> = Cell::new(None);
+}
+
+static NEXT_GUEST_CALL: AtomicU64 = AtomicU64::new(0);
+
+/// Run a call into the guest. The guest blocks the thread it runs on, and
+/// the host fns it calls block the same thread, so every lock taken on the
+/// thread until it returns is taken by this call. A guest call made while
+/// another is running on the thread is part of the outer call.
+pub fn guest_call<R>(f: impl FnOnce() -> R) -> R {
+    if GUEST_CALL.with(Cell::get).is_some() {
+        return f();
+    }
+    GUEST_CALL.with(|call| call.set(Some(NEXT_GUEST_CALL.fetch_add(1, Ordering::Relaxed))));
+    let _ended = GuestCallEnded;
+    f()
+}
+
+/// Clears the thread's guest call when dropped, even if the call panics
+struct GuestCallEnded;
+
+impl Drop for GuestCallEnded {
+    fn drop(&mut self) {
+        GUEST_CALL.with(|call| call.set(None));
+    }
+}
 
-#[derive(Clone, shrinkwraprs::Shrinkwrap)]
-pub struct CallZomeWorkspaceLock(Arc<RwLock<CallZomeWorkspace>>);
+#[derive(Clone)]
+pub struct CallZomeWorkspaceLock {
+    lock: Arc<RwLock<CallZomeWorkspace>>,
+    metrics: Arc<LockMetrics>,
+}
+
+/// How contended a workspace lock has been
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WorkspaceLockStats {
+    /// How many times the lock has been taken
+    pub acquired: u64,
+    /// How many are waiting for the lock right now
+    pub waiting: usize,
+    /// The most that have waited for the lock at once
+    pub max_waiting: usize,
+    /// The total time spent waiting for the lock
+    pub total_wait: Duration,
+    /// The total time the lock has been held
+    pub total_hold: Duration,
+    /// The longest the lock has been held at once
+    pub max_hold: Duration,
+}
 
 impl CallZomeWorkspaceLock {
     pub fn new(workspace: CallZomeWorkspace) -> Self {
-        Self(Arc::new(RwLock::new(workspace)))
+        Self {
+            lock: Arc::new(RwLock::new(workspace)),
+            metrics: Arc::new(LockMetrics::default()),
+        }
+    }
+
+    /// Share the workspace, waiting until no one is writing to it
+    #[track_caller]
+    pub fn read(&self) -> impl Future<Output = WorkspaceReadGuard<'_>> {
+        let location = Location::caller();
+        async move {
+            let (guard, held) = self.acquire(self.lock.read(), location, false).await;
+            WorkspaceReadGuard { guard, _held: held }
+        }
+    }
+
+    /// Take the workspace to write to, waiting until no one else has it
+    #[track_caller]
+    pub fn write(&self) -> impl Future<Output = WorkspaceWriteGuard<'_>> {
+        let location = Location::caller();
+        async move {
+            let (guard, held) = self.acquire(self.lock.write(), location, true).await;
+            WorkspaceWriteGuard { guard, _held: held }
+        }
+    }
+
+    /// How contended the lock has been so far
+    pub fn stats(&self) -> WorkspaceLockStats {
+        let m = &self.metrics;
+        WorkspaceLockStats {
+            acquired: m.acquired.load(Ordering::Relaxed),
+            waiting: m.waiting.load(Ordering::Relaxed),
+            max_waiting: m.max_waiting.load(Ordering::Relaxed),
+            total_wait: Duration::from_micros(m.wait_micros.load(Ordering::Relaxed)),
+            total_hold: Duration::from_micros(m.hold_micros.load(Ordering::Relaxed)),
+            max_hold: Duration::from_micros(m.max_hold_micros.load(Ordering::Relaxed)),
+        }
+    }
+
+    async fn acquire<G>(
+        &self,
+        guard: impl Future<Output = G>,
+        location: &'static Location<'static>,
+        write: bool,
+    ) -> (G, HeldLock<'_>) {
+        let m = &self.metrics;
+        let guest_call = GUEST_CALL.with(Cell::get);
+        if cfg!(debug_assertions) && guest_call.is_some() {
+            if let Some(diagnostic) = m.deadlock_diagnostic(location, write, guest_call) {
+                panic!("{}", diagnostic);
+            }
+        }
+        let start = Instant::now();
+        let waiting = Waiting::new(m);
+        let guard = guard.await;
+        drop(waiting);
+        m.acquired.fetch_add(1, Ordering::Relaxed);
+        m.wait_micros
+            .fetch_add(start.elapsed().as_micros() as u64, Ordering::Relaxed);
+        let id = m.next_holder.fetch_add(1, Ordering::Relaxed);
+        m.holders.lock().push(Holder {
+            id,
+            location,
+            write,
+            guest_call,
+            since: Instant::now(),
+        });
+        (
+            guard,
+            HeldLock {
+                metrics: m,
+                id,
+                since: Instant::now(),
+            },
+        )
     }
 }
 
@@ -17,3 +160,230 @@ impl From<CallZomeWorkspace> for CallZomeWorkspaceLock {
         Self::new(w)
     }
 }
+
+/// Shared access to the workspace, which is given back when dropped
+pub struct WorkspaceReadGuard<'a> {
+    guard: RwLockReadGuard<'a, CallZomeWorkspace>,
+    _held: HeldLock<'a>,
+}
+
+impl<'a> Deref for WorkspaceReadGuard<'a> {
+    type Target = CallZomeWorkspace;
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+/// Write access to the workspace, which is given back when dropped
+pub struct WorkspaceWriteGuard<'a> {
+    guard: RwLockWriteGuard<'a, CallZomeWorkspace>,
+    _held: HeldLock<'a>,
+}
+
+impl<'a> Deref for WorkspaceWriteGuard<'a> {
+    type Target = CallZomeWorkspace;
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<'a> DerefMut for WorkspaceWriteGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+/// Counters shared by every clone of a lock
+#[derive(Default)]
+struct LockMetrics {
+    acquired: AtomicU64,
+    waiting: AtomicUsize,
+    max_waiting: AtomicUsize,
+    wait_micros: AtomicU64,
+    hold_micros: AtomicU64,
+    max_hold_micros: AtomicU64,
+    next_holder: AtomicU64,
+    /// Where the lock is held right now
+    holders: Mutex<Vec<Holder>>,
+}
+
+struct Holder {
+    id: u64,
+    location: &'static Location<'static>,
+    write: bool,
+    /// The guest call which took the lock, if it was taken by one
+    guest_call: Option<u64>,
+    since: Instant,
+}
+
+impl LockMetrics {
+    /// Describe the deadlock if `guest_call` asking for the lock would wait
+    /// on itself: it already holds the lock, and either hold is a write.
+    /// Holds by anything else are waited on as usual.
+    fn deadlock_diagnostic(
+        &self,
+        location: &'static Location<'static>,
+        write: bool,
+        guest_call: Option<u64>,
+    ) -> Option<String> {
+        let holders = self.holders.lock();
+        let held = holders
+            .iter()
+            .find(|h| h.guest_call == guest_call && (write || h.write))?;
+        Some(format!(
+            "The call zome workspace {} lock was asked for at {} by the guest call which \
+            already holds the {} lock taken at {}, {:?} ago. The lock isn't re-entrant, so \
+            this would wait forever. This is most likely a host fn taking it while the call \
+            already holds it.",
+            if write { "write" } else { "read" },
+            location,
+            if held.write { "write" } else { "read" },
+            held.location,
+            held.since.elapsed()
+        ))
+    }
+}
+
+/// Counts a wait for the lock until dropped, so a wait which is given up
+/// on, because its future was dropped, stops being counted too
+struct Waiting<'a>(&'a LockMetrics);
+
+impl<'a> Waiting<'a> {
+    fn new(metrics: &'a LockMetrics) -> Self {
+        let waiting = metrics.waiting.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics.max_waiting.fetch_max(waiting, Ordering::Relaxed);
+        Self(metrics)
+    }
+}
+
+impl<'a> Drop for Waiting<'a> {
+    fn drop(&mut self) {
+        self.0.waiting.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Records how long the lock was held when dropped
+struct HeldLock<'a> {
+    metrics: &'a LockMetrics,
+    id: u64,
+    since: Instant,
+}
+
+impl<'a> Drop for HeldLock<'a> {
+    fn drop(&mut self) {
+        let held = self.since.elapsed().as_micros() as u64;
+        self.metrics.hold_micros.fetch_add(held, Ordering::Relaxed);
+        self.metrics
+            .max_hold_micros
+            .fetch_max(held, Ordering::Relaxed);
+        self.metrics.holders.lock().retain(|h| h.id != self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_state::test_utils::test_cell_env;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn counts_waits_and_holds() {
+        let test_env = test_cell_env();
+        let lock =
+            CallZomeWorkspaceLock::new(CallZomeWorkspace::new(test_env.env().into()).unwrap());
+        {
+            let _a = lock.read().await;
+            let _b = lock.read().await;
+            assert_eq!(lock.metrics.holders.lock().len(), 2);
+        }
+        let writer = lock.write().await;
+        let waiter = {
+            let lock = lock.clone();
+            tokio::spawn(async move {
+                let _w = lock.write().await;
+            })
+        };
+        tokio::time::delay_for(Duration::from_millis(20)).await;
+        assert_eq!(lock.stats().waiting, 1);
+        drop(writer);
+        waiter.await.unwrap();
+
+        let stats = lock.stats();
+        assert_eq!(stats.acquired, 4);
+        assert_eq!(stats.waiting, 0);
+        assert_eq!(stats.max_waiting, 1);
+        assert!(stats.max_hold >= Duration::from_millis(20));
+        assert!(lock.metrics.holders.lock().is_empty());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn abandoned_waits_stop_counting() {
+        let test_env = test_cell_env();
+        let lock =
+            CallZomeWorkspaceLock::new(CallZomeWorkspace::new(test_env.env().into()).unwrap());
+        let writer = lock.write().await;
+        let res = tokio::time::timeout(Duration::from_millis(20), lock.read()).await;
+        assert!(res.is_err());
+        assert_eq!(lock.stats().waiting, 0);
+        assert_eq!(lock.stats().max_waiting, 1);
+        drop(writer);
+        assert_eq!(lock.stats().acquired, 1);
+    }
+
+    #[test]
+    fn diagnostic_names_the_holder() {
+        let metrics = LockMetrics::default();
+        let here = Location::caller();
+        metrics.holders.lock().push(Holder {
+            id: 0,
+            location: here,
+            write: true,
+            guest_call: Some(1),
+            since: Instant::now(),
+        });
+        // Another guest call just waits
+        assert_eq!(metrics.deadlock_diagnostic(here, false, Some(2)), None);
+        let diagnostic = metrics.deadlock_diagnostic(here, false, Some(1)).unwrap();
+        assert!(diagnostic.contains(&format!("read lock was asked for at {}", here)));
+        assert!(diagnostic.contains(&format!("write lock taken at {}", here)));
+    }
+
+    #[test]
+    fn shared_reads_by_one_guest_call_are_fine() {
+        let metrics = LockMetrics::default();
+        let here = Location::caller();
+        metrics.holders.lock().push(Holder {
+            id: 0,
+            location: here,
+            write: false,
+            guest_call: Some(1),
+            since: Instant::now(),
+        });
+        assert_eq!(metrics.deadlock_diagnostic(here, false, Some(1)), None);
+        assert!(metrics.deadlock_diagnostic(here, true, Some(1)).is_some());
+    }
+
+    #[test]
+    fn nested_guest_calls_are_one_call() {
+        let (outer, inner) = guest_call(|| {
+            let outer = GUEST_CALL.with(Cell::get);
+            (outer, guest_call(|| GUEST_CALL.with(Cell::get)))
+        });
+        assert!(outer.is_some());
+        assert_eq!(outer, inner);
+        assert_eq!(GUEST_CALL.with(Cell::get), None);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    #[should_panic(expected = "isn't re-entrant")]
+    async fn guest_call_taking_the_lock_twice_panics() {
+        let test_env = test_cell_env();
+        let lock =
+            CallZomeWorkspaceLock::new(CallZomeWorkspace::new(test_env.env().into()).unwrap());
+        guest_call(|| {
+            futures::executor::block_on(async {
+                let _writer = lock.write().await;
+                let _reader = lock.read().await;
+            })
+        });
+    }
+}
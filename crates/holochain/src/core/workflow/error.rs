@@ -11,6 +11,7 @@ use crate::{
         SysValidationError,
     },
 };
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_p2p::HolochainP2pError;
 use holochain_state::error::DatabaseError;
 use holochain_types::{dht_op::error::DhtOpError, prelude::*};
@@ -61,5 +62,42 @@ pub enum WorkflowError {
     SysValidationError(#[from] SysValidationError),
 }
 
+impl HasErrorKind for WorkflowError {
+    fn error_kind(&self) -> ErrorKind {
+        use WorkflowError::*;
+        match self {
+            AgentInvalid(_) => ErrorKind::Validation,
+            ConductorApi(e) => e.error_kind(),
+            WorkspaceError(e) => e.error_kind(),
+            DatabaseError(e) => e.error_kind(),
+            RibosomeError(e) => e.error_kind(),
+            SourceChainError(e) => e.error_kind(),
+            CapabilityMissing => ErrorKind::Unauthorized,
+            SerializedBytesError(_) => ErrorKind::Serialization,
+            DhtOpConvertError(e) => e.error_kind(),
+            CellError(e) => e.error_kind(),
+            // The queue consumer has stopped, which only happens on shutdown
+            QueueTriggerClosedError(_) => ErrorKind::Cancelled,
+            HolochainP2pError(e) => e.error_kind(),
+            DhtOpError(_) => ErrorKind::Internal,
+            SysValidationError(e) => e.error_kind(),
+        }
+    }
+}
+
+impl WorkflowError {
+    /// Whether another write moved the source chain head before this
+    /// workflow could commit, so running it again may succeed
+    pub fn is_head_moved(&self) -> bool {
+        matches!(
+            self,
+            WorkflowError::SourceChainError(SourceChainError::HeadMoved(_, _))
+                | WorkflowError::WorkspaceError(WorkspaceError::SourceChainError(
+                    SourceChainError::HeadMoved(_, _)
+                ))
+        )
+    }
+}
+
 /// Internal type to handle running workflows
 pub type WorkflowResult<T> = Result<T, WorkflowError>;
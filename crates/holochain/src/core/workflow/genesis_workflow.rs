@@ -10,6 +10,7 @@
 use super::error::{WorkflowError, WorkflowResult};
 use crate::conductor::api::CellConductorApiT;
 use crate::core::{
+    clock::Clock,
     queue_consumer::OneshotWriter,
     state::{
         source_chain::SourceChainBuf,
@@ -22,6 +23,9 @@ use holochain_types::dna::DnaFile;
 use holochain_types::prelude::*;
 use tracing::*;
 
+/// How many Cells run genesis at once by default while an app is installed
+pub const DEFAULT_MAX_CONCURRENT_GENESIS: usize = 4;
+
 /// The struct which implements the genesis Workflow
 #[derive(Constructor, Debug)]
 pub struct GenesisWorkflowArgs {
@@ -93,6 +97,11 @@ impl GenesisWorkspace {
             source_chain: SourceChainBuf::new(env)?,
         })
     }
+
+    /// Timestamp the genesis headers with this clock instead of the system's
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.source_chain.set_clock(clock);
+    }
 }
 
 impl Workspace for GenesisWorkspace {
@@ -15,8 +15,9 @@ use crate::core::{
         validation_db::{ValidationLimboStatus, ValidationLimboStore, ValidationLimboValue},
         workspace::{Workspace, WorkspaceResult},
     },
+    validation_package,
 };
-use holo_hash::DhtOpHash;
+use holo_hash::{DhtOpHash, HeaderHash};
 use holochain_state::{
     buffer::BufferedStore,
     buffer::KvBufFresh,
@@ -26,25 +27,45 @@ use holochain_state::{
     prelude::{EnvironmentRead, GetDb, PendingPrefix, Writer},
 };
 use holochain_types::{dht_op::DhtOp, Timestamp};
+use holochain_zome_types::validate::ValidationPackage;
+use std::collections::HashMap;
 use tracing::instrument;
 
 #[cfg(test)]
 mod test;
 
-#[instrument(skip(state_env, sys_validation_trigger, ops))]
+#[instrument(skip(state_env, sys_validation_trigger, ops, validation_packages))]
 pub async fn incoming_dht_ops_workflow(
     state_env: &EnvironmentWrite,
     mut sys_validation_trigger: TriggerSender,
     ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+    validation_packages: Vec<(HeaderHash, ValidationPackage)>,
 ) -> WorkflowResult<()> {
     // set up our workspace
     let mut workspace = IncomingDhtOpsWorkspace::new(state_env.clone().into())?;
+    let validation_packages: HashMap<_, _> = validation_packages.into_iter().collect();
 
     // add incoming ops to the validation limbo
     for (hash, op) in ops {
         if !workspace.op_exists(&hash)? {
             tracing::debug!(?op);
-            workspace.add_to_pending(hash, op).await?;
+            let validation_package = if validation_packages.is_empty() {
+                None
+            } else {
+                validation_packages
+                    .get(&HeaderHash::with_data_sync(&op.header()))
+                    .cloned()
+            };
+            // Anyone can attach a package, so check it really is the author's chain
+            if let Some(package) = &validation_package {
+                if !validation_package::verify(package, &op.header()).await? {
+                    tracing::warn!(?hash, "Turning away an op with a forged validation package");
+                    continue;
+                }
+            }
+            workspace
+                .add_to_pending(hash, op, validation_package)
+                .await?;
         }
     }
 
@@ -99,7 +120,12 @@ impl IncomingDhtOpsWorkspace {
         })
     }
 
-    async fn add_to_pending(&mut self, hash: DhtOpHash, op: DhtOp) -> DhtOpConvertResult<()> {
+    async fn add_to_pending(
+        &mut self,
+        hash: DhtOpHash,
+        op: DhtOp,
+        validation_package: Option<ValidationPackage>,
+    ) -> DhtOpConvertResult<()> {
         let basis = op.dht_basis().await;
         let op_light = op.to_light().await;
 
@@ -117,6 +143,7 @@ impl IncomingDhtOpsWorkspace {
             last_try: None,
             num_tries: 0,
             pending_dependencies: PendingDependencies::new(),
+            validation_package,
         };
         self.validation_limbo.put(hash, vlv)?;
         Ok(())
@@ -1,7 +1,9 @@
 use super::*;
+use crate::core::{state::source_chain::SourceChain, workflow::fake_genesis};
 use ::fixt::prelude::*;
 use holochain_state::test_utils::TestEnvironment;
 use holochain_types::{dht_op::DhtOp, fixt::*};
+use holochain_zome_types::validate::RequiredValidationType;
 
 #[tokio::test(threaded_scheduler)]
 async fn incoming_ops_to_limbo() {
@@ -12,7 +14,7 @@ async fn incoming_ops_to_limbo() {
     let hash = DhtOpHash::with_data_sync(&op);
     let ops = vec![(hash.clone(), op.clone())];
 
-    incoming_dht_ops_workflow(&env, sys_validation_trigger.clone(), ops)
+    incoming_dht_ops_workflow(&env, sys_validation_trigger.clone(), ops, vec![])
         .await
         .unwrap();
     rx.listen().await.unwrap();
@@ -21,3 +23,49 @@ async fn incoming_ops_to_limbo() {
     let r = workspace.validation_limbo.get(&hash).unwrap().unwrap();
     assert_eq!(r.op, op_light);
 }
+
+#[tokio::test(threaded_scheduler)]
+async fn forged_validation_packages_are_turned_away() {
+    let TestEnvironment { env, tmpdir: _t } = holochain_state::test_utils::test_cell_env();
+    let author_env = holochain_state::test_utils::test_cell_env();
+    let mut chain = SourceChain::new(author_env.env().into()).unwrap();
+    fake_genesis(&mut chain).await.unwrap();
+    let head = chain
+        .get_header(chain.chain_head().unwrap())
+        .unwrap()
+        .unwrap();
+    let (header, signature) = head.into_header_and_signature();
+    let header = header.into_content();
+    let package =
+        validation_package::assemble(&chain, &header, RequiredValidationType::Full).unwrap();
+    let mut forged = package.clone();
+    forged.0.reverse();
+
+    let op = DhtOp::StoreElement(signature.clone(), header.clone(), None);
+    let hash = DhtOpHash::with_data_sync(&op);
+    let header_hash = HeaderHash::with_data_sync(&header);
+    let (sys_validation_trigger, _rx) = TriggerSender::new();
+    incoming_dht_ops_workflow(
+        &env,
+        sys_validation_trigger,
+        vec![(hash.clone(), op.clone())],
+        vec![(header_hash.clone(), forged)],
+    )
+    .await
+    .unwrap();
+    let workspace = IncomingDhtOpsWorkspace::new(env.clone().into()).unwrap();
+    assert!(!workspace.op_exists(&hash).unwrap());
+
+    let (sys_validation_trigger, _rx) = TriggerSender::new();
+    incoming_dht_ops_workflow(
+        &env,
+        sys_validation_trigger,
+        vec![(hash.clone(), op)],
+        vec![(header_hash, package.clone())],
+    )
+    .await
+    .unwrap();
+    let workspace = IncomingDhtOpsWorkspace::new(env.clone().into()).unwrap();
+    let r = workspace.validation_limbo.get(&hash).unwrap().unwrap();
+    assert_eq!(r.validation_package, Some(package));
+}
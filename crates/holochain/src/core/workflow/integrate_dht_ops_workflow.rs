@@ -1,42 +1,61 @@
 //! The workflow and queue consumer for DhtOp integration
 
 use super::*;
+use crate::conductor::api::CellConductorApiT;
 use crate::core::{
     queue_consumer::{OneshotWriter, TriggerSender, WorkComplete},
+    ribosome::{
+        guest_callback::{
+            index_op::{IndexOpHostAccess, IndexOpInvocation, IndexOpResult},
+            link_types::{LinkTypesHostAccess, LinkTypesInvocation, LinkTypesResult},
+        },
+        wasm_ribosome::WasmRibosome,
+        RibosomeT,
+    },
     state::{
+        app_index::AppIndexBuf,
         dht_op_integration::{
-            IntegratedDhtOpsStore, IntegratedDhtOpsValue, IntegrationLimboStore,
+            IntegratedDhtOpsBuf, IntegratedDhtOpsValue, IntegrationLimboStore,
             IntegrationLimboValue,
         },
         element_buf::ElementBuf,
-        metadata::{MetadataBuf, MetadataBufT},
+        integration_journal::{integration_journal, IntegrationJournalStore},
+        metadata::{LinkTagIndexBuf, MetadataBuf, MetadataBufT},
+        subscriptions::SubscriptionsBuf,
         workspace::{Workspace, WorkspaceResult},
     },
 };
 use error::WorkflowResult;
 use fallible_iterator::FallibleIterator;
-use holo_hash::{DhtOpHash, HeaderHash};
+use holo_hash::{AnyDhtHash, DhtOpHash, HeaderHash};
 use holochain_keystore::Signature;
+use holochain_p2p::{HolochainP2pCell, HolochainP2pCellT};
 use holochain_state::{
-    buffer::BufferedStore,
-    buffer::KvBufFresh,
-    db::{INTEGRATED_DHT_OPS, INTEGRATION_LIMBO},
-    error::DatabaseResult,
-    fresh_reader,
-    prelude::*,
+    buffer::BufferedStore, buffer::KvBufFresh, db::INTEGRATION_LIMBO, error::DatabaseResult,
+    fresh_reader, prelude::*,
 };
 use holochain_types::{
     dht_op::{produce_op_lights_from_elements, DhtOp, DhtOpLight},
     element::{Element, SignedHeaderHashed, SignedHeaderHashedExt},
+    header::NewEntryHeader,
     validate::ValidationStatus,
     Entry, EntryHashed, Timestamp,
 };
-use holochain_zome_types::{element::SignedHeader, Header};
+use holochain_zome_types::{
+    element::SignedHeader,
+    header::{CreateLink, EntryType, ZomeId},
+    link::LinkTypes,
+    zome::ZomeName,
+    Header,
+};
 use produce_dht_ops_workflow::dht_op_light::{
     error::{DhtOpConvertError, DhtOpConvertResult},
     light_to_op,
 };
-use std::{collections::BinaryHeap, convert::TryInto};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryInto,
+};
 use sys_validation_workflow::types::{DhtOpOrder, OrderedOp};
 use tracing::*;
 
@@ -45,11 +64,16 @@ pub use disintegrate::*;
 mod disintegrate;
 mod tests;
 
-#[instrument(skip(workspace, writer, trigger_sys))]
+/// Once a base has this many deleted links they're compacted into tombstones
+pub const LINK_TOMBSTONE_COMPACTION_THRESHOLD: usize = 256;
+
+#[instrument(skip(workspace, writer, trigger_sys, network, conductor_api))]
 pub async fn integrate_dht_ops_workflow(
     mut workspace: IntegrateDhtOpsWorkspace,
     writer: OneshotWriter,
     trigger_sys: &mut TriggerSender,
+    network: &HolochainP2pCell,
+    conductor_api: &impl CellConductorApiT,
 ) -> WorkflowResult<WorkComplete> {
     // one of many possible ways to access the env
     let env = workspace.elements.headers().env().clone();
@@ -62,7 +86,7 @@ pub async fn integrate_dht_ops_workflow(
         .collect())?;
 
     // Sort the ops
-    let mut sorted_ops = BinaryHeap::new();
+    let mut sorted_ops = Vec::with_capacity(ops.len());
     for iv in ops {
         let op = light_to_op(iv.op.clone(), &workspace.element_judged).await?;
         let hash = DhtOpHash::with_data_sync(&op);
@@ -73,10 +97,25 @@ pub async fn integrate_dht_ops_workflow(
             op,
             value: iv,
         };
-        sorted_ops.push(std::cmp::Reverse(v));
+        sorted_ops.push(v);
     }
+    sorted_ops.sort_unstable();
+
+    // Valid app entries are indexed by the zome that defines them.
+    // Without the dna there is nothing to run the callback with.
+    let ribosome = if sorted_ops.is_empty() {
+        None
+    } else {
+        conductor_api.get_this_dna().await.map(WasmRibosome::new)
+    };
+    // Only fetched if a typed link is integrated
+    let mut link_types = None;
 
     let mut total_integrated: usize = 0;
+    // The valid ops integrated at each basis, for any subscribers
+    let mut changed: BTreeMap<AnyDhtHash, Vec<DhtOpHash>> = BTreeMap::new();
+    // The bases which had links deleted, to compact
+    let mut link_delete_bases = BTreeSet::new();
 
     // Try to process the queue over and over again, until we either exhaust
     // the queue, or we can no longer integrate anything in the queue.
@@ -85,14 +124,38 @@ pub async fn integrate_dht_ops_workflow(
     // integration, we may be able to integrate at least one more item.
     loop {
         let mut num_integrated: usize = 0;
-        let mut next_ops = BinaryHeap::new();
+        // Deferred ops are pushed in order so this stays sorted
+        let mut next_ops = Vec::new();
         for so in sorted_ops {
             let OrderedOp {
                 hash,
                 op,
                 value,
                 order,
-            } = so.0;
+            } = so;
+            let to_index = match (&ribosome, &value.validation_status) {
+                (Some(_), ValidationStatus::Valid) => indexable_element(&op),
+                _ => None,
+            };
+            let link_to_index = match (&ribosome, &value.validation_status, &op) {
+                (Some(_), ValidationStatus::Valid, DhtOp::RegisterAddLink(_, link_add))
+                    if link_add.link_type.is_some() =>
+                {
+                    Some(link_add.clone())
+                }
+                _ => None,
+            };
+            let link_delete_base = match (&value.validation_status, &op) {
+                (ValidationStatus::Valid, DhtOp::RegisterRemoveLink(_, link_remove)) => {
+                    Some(link_remove.base_address.clone())
+                }
+                _ => None,
+            };
+            let header_timestamp = op.header().timestamp().into();
+            let changed_basis = match value.validation_status {
+                ValidationStatus::Valid => Some(value.op.dht_basis().clone()),
+                _ => None,
+            };
             // Check validation status and put in correct dbs
             let outcome = match value.validation_status {
                 ValidationStatus::Valid => integrate_single_dht_op(
@@ -108,7 +171,8 @@ pub async fn integrate_dht_ops_workflow(
                     &mut workspace.meta_rejected,
                 )?,
                 ValidationStatus::Abandoned => {
-                    // Throwing away abandoned ops
+                    // Throwing away abandoned ops.
+                    // Why they were abandoned is kept in the AbandonedDhtOpsStore.
                     // TODO: keep abandoned ops but remove the entries
                     // and put them in a AbandonedPrefix db
                     continue;
@@ -116,20 +180,37 @@ pub async fn integrate_dht_ops_workflow(
             };
             match outcome {
                 Outcome::Integrated(integrated) => {
+                    if let (Some(ribosome), Some((zome_id, element))) = (&ribosome, to_index) {
+                        index_element(ribosome, zome_id, element, &mut workspace.app_index)?;
+                    }
+                    if let (Some(ribosome), Some(link_add)) = (&ribosome, link_to_index) {
+                        index_link_tag(
+                            ribosome,
+                            &mut link_types,
+                            link_add,
+                            &mut workspace.link_tags,
+                        )?;
+                    }
+                    if let Some(basis) = changed_basis {
+                        changed.entry(basis).or_default().push(hash.clone());
+                    }
+                    if let Some(base) = link_delete_base {
+                        link_delete_bases.insert(base);
+                    }
                     // TODO We could create a prefix for the integrated ops db
                     // and separate rejected ops from valid ops.
                     // Currently you need to check the IntegratedDhtOpsValue for
                     // the status
-                    workspace.integrate(hash, integrated)?;
+                    workspace.integrate(hash, integrated, header_timestamp)?;
                     num_integrated += 1;
                     total_integrated += 1;
                 }
-                Outcome::Deferred(op) => next_ops.push(std::cmp::Reverse(OrderedOp {
+                Outcome::Deferred(op) => next_ops.push(OrderedOp {
                     hash,
                     order,
                     op,
                     value,
-                })),
+                }),
             }
         }
         sorted_ops = next_ops;
@@ -139,13 +220,18 @@ pub async fn integrate_dht_ops_workflow(
         }
     }
 
+    for base in link_delete_bases {
+        workspace
+            .meta
+            .compact_link_tombstones(&base, LINK_TOMBSTONE_COMPACTION_THRESHOLD)?;
+    }
+
     let result = if sorted_ops.is_empty() {
         // There were no ops deferred, meaning we exhausted the queue
         WorkComplete::Complete
     } else {
         // Re-add the remaining ops to the queue, to be picked up next time.
         for so in sorted_ops {
-            let so = so.0;
             // TODO: it may be desirable to retain the original timestamp
             // when re-adding items to the queue for later processing. This is
             // challenging for now since we don't have access to that original
@@ -166,9 +252,47 @@ pub async fn integrate_dht_ops_workflow(
         trigger_sys.trigger();
     }
 
+    if !changed.is_empty() {
+        notify_subscribers(env, network.clone(), changed)?;
+    }
+
     Ok(result)
 }
 
+/// Tell the agents subscribed to each basis which ops were just integrated there.
+/// Notifications are sent in the background and are best effort,
+/// so a subscriber that can't be reached misses them.
+fn notify_subscribers(
+    env: EnvironmentRead,
+    mut network: HolochainP2pCell,
+    changed: BTreeMap<AnyDhtHash, Vec<DhtOpHash>>,
+) -> WorkflowResult<()> {
+    let subscriptions = SubscriptionsBuf::new(env.clone())?;
+    let notifications = fresh_reader!(env, |r| {
+        let mut notifications = Vec::new();
+        for (basis, op_hashes) in changed {
+            for subscriber in subscriptions.subscribers(&r, &basis)? {
+                notifications.push((subscriber, basis.clone(), op_hashes.clone()));
+            }
+        }
+        DatabaseResult::Ok(notifications)
+    })?;
+    if notifications.is_empty() {
+        return Ok(());
+    }
+    tokio::task::spawn(async move {
+        for (subscriber, basis, op_hashes) in notifications {
+            if let Err(error) = network
+                .send_basis_changed(subscriber.clone(), basis, op_hashes)
+                .await
+            {
+                warn!(?error, ?subscriber, "Could not notify a subscriber");
+            }
+        }
+    });
+    Ok(())
+}
+
 /// Integrate a single DhtOp to the specified stores.
 ///
 /// The two stores are intended to be either the pair of Vaults,
@@ -199,6 +323,104 @@ fn integrate_single_dht_op<P: PrefixType>(
     }
 }
 
+/// The element to run the index_op callback on, if the op stores an app entry
+fn indexable_element(op: &DhtOp) -> Option<(ZomeId, Element)> {
+    match op {
+        DhtOp::StoreEntry(signature, new_entry_header, entry) => {
+            let header = Header::from(new_entry_header.clone());
+            let zome_id = match header.entry_data() {
+                Some((_, EntryType::App(app_entry_type))) => app_entry_type.zome_id(),
+                _ => return None,
+            };
+            let signed_header =
+                SignedHeaderHashed::from_content_sync(SignedHeader(header, signature.clone()));
+            Some((
+                zome_id,
+                Element::new(signed_header, Some((**entry).clone())),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Run the index_op callback of the zome which defines an entry
+/// and add the records it emits to the app index.
+/// A failing callback doesn't stop the op being integrated, it just isn't indexed.
+fn index_element(
+    ribosome: &WasmRibosome,
+    zome_id: ZomeId,
+    element: Element,
+    app_index: &mut AppIndexBuf,
+) -> DatabaseResult<()> {
+    let zome_name = match ribosome
+        .dna_file()
+        .dna()
+        .zomes
+        .get(u8::from(zome_id) as usize)
+    {
+        Some((zome_name, _)) => zome_name.clone(),
+        // Sys validation has already rejected entries for missing zomes
+        None => return Ok(()),
+    };
+    let header_hash = element.header_address().clone();
+    match ribosome.run_index_op(
+        IndexOpHostAccess,
+        IndexOpInvocation::new(zome_name, element),
+    ) {
+        Ok(IndexOpResult::Indexes(records)) => {
+            for (zome_name, record) in records {
+                app_index.put(&zome_name, record, header_hash.clone())?;
+            }
+        }
+        Ok(IndexOpResult::Err(zome_name, error)) => {
+            warn!(?zome_name, %error, ?header_hash, "index_op callback failed");
+        }
+        Err(error) => {
+            warn!(?error, ?header_hash, "Could not run the index_op callback");
+        }
+    }
+    Ok(())
+}
+
+/// Add a typed link to the ordered index if its link type declares a tag order.
+/// The zomes' link types are only fetched the first time they're needed.
+fn index_link_tag(
+    ribosome: &WasmRibosome,
+    link_types: &mut Option<BTreeMap<ZomeName, LinkTypes>>,
+    link_add: CreateLink,
+    link_tags: &mut LinkTagIndexBuf,
+) -> DatabaseResult<()> {
+    let link_type = match link_add.link_type {
+        Some(link_type) => link_type,
+        None => return Ok(()),
+    };
+    let link_types = link_types.get_or_insert_with(|| {
+        match ribosome.run_link_types(LinkTypesHostAccess, LinkTypesInvocation) {
+            Ok(LinkTypesResult::Defs(defs)) => defs,
+            Ok(LinkTypesResult::Err(zome_name, error)) => {
+                warn!(?zome_name, %error, "link_types callback failed");
+                BTreeMap::new()
+            }
+            Err(error) => {
+                warn!(?error, "Could not run the link_types callback");
+                BTreeMap::new()
+            }
+        }
+    });
+    let tag_order = ribosome
+        .dna_file()
+        .dna()
+        .zomes
+        .get(u8::from(link_add.zome_id) as usize)
+        .and_then(|(zome_name, _)| link_types.get(zome_name))
+        .and_then(|link_types| link_types.tag_order(u8::from(link_type) as usize));
+    if let Some(tag_order) = tag_order {
+        let link_add_hash = HeaderHash::with_data_sync(&Header::CreateLink(link_add.clone()));
+        link_tags.add_link(link_add, link_add_hash, tag_order)?;
+    }
+    Ok(())
+}
+
 /// Check if we have the required dependencies held before integrating.
 // TODO: This doesn't really check why we are holding the values.
 // We could have them for other reasons.
@@ -301,9 +523,15 @@ where
             meta_store.register_element_header(&header)?;
         }
         DhtOpLight::StoreEntry(hash, _, _) => {
-            let new_entry_header = get_header(hash, element_store)?.try_into()?;
+            let header = get_header(hash, element_store)?;
+            let forked = is_forked_entry_header(&header, element_store, meta_store)?;
+            let new_entry_header: NewEntryHeader = header.try_into()?;
+            let entry_hash = new_entry_header.entry().clone();
             // Reference to headers
             meta_store.register_header(new_entry_header)?;
+            if forked {
+                meta_store.register_conflict(entry_hash)?;
+            }
         }
         DhtOpLight::RegisterAgentActivity(hash, _) => {
             let header = get_header(hash, element_store)?;
@@ -387,6 +615,38 @@ fn put_data<P: PrefixType>(
     Ok(())
 }
 
+/// Whether another header by the same author at the same position in their
+/// chain already created this entry, which means the author forked their chain
+fn is_forked_entry_header<C, P>(
+    header: &Header,
+    element_store: &ElementBuf<P>,
+    meta_store: &C,
+) -> DhtOpConvertResult<bool>
+where
+    P: PrefixType,
+    C: MetadataBufT<P>,
+{
+    let entry_hash = match header.entry_data() {
+        Some((entry_hash, _)) => entry_hash.clone(),
+        None => return Ok(false),
+    };
+    let header_hash = HeaderHash::with_data_sync(header);
+    Ok(fresh_reader!(meta_store.env(), |r| meta_store
+        .get_headers(&r, entry_hash)?
+        .any(|other| {
+            if other.header_hash == header_hash {
+                return Ok(false);
+            }
+            Ok(element_store
+                .get_header(&other.header_hash)?
+                .map(|other| {
+                    let other = other.header();
+                    other.author() == header.author() && other.header_seq() == header.header_seq()
+                })
+                .unwrap_or(false))
+        }))?)
+}
+
 fn get_header<P: PrefixType>(
     hash: HeaderHash,
     element_store: &ElementBuf<P>,
@@ -426,7 +686,7 @@ pub struct IntegrateDhtOpsWorkspace {
     // integration queue
     pub integration_limbo: IntegrationLimboStore,
     // integrated ops
-    pub integrated_dht_ops: IntegratedDhtOpsStore,
+    pub integrated_dht_ops: IntegratedDhtOpsBuf,
     // Cas for storing
     pub elements: ElementBuf,
     // metadata store
@@ -438,6 +698,12 @@ pub struct IntegrateDhtOpsWorkspace {
     pub meta_rejected: MetadataBuf<RejectedPrefix>,
     // Ops to disintegrate
     pub to_disintegrate_judged: Vec<DhtOpLight>,
+    // Intent recorded before this run, cleared when it's flushed
+    pub journal: IntegrationJournalStore,
+    // Records emitted by the index_op callbacks
+    pub app_index: AppIndexBuf,
+    // Links of ordered link types by tag value
+    pub link_tags: LinkTagIndexBuf,
 }
 
 impl Workspace for IntegrateDhtOpsWorkspace {
@@ -455,6 +721,11 @@ impl Workspace for IntegrateDhtOpsWorkspace {
         self.meta_judged.flush_to_txn_ref(writer)?;
         self.element_rejected.flush_to_txn_ref(writer)?;
         self.meta_rejected.flush_to_txn_ref(writer)?;
+        self.app_index.flush_to_txn_ref(writer)?;
+        self.link_tags.flush_to_txn_ref(writer)?;
+        // clear the intent in the same transaction as the results
+        self.journal.delete(UnitDbKey)?;
+        self.journal.flush_to_txn_ref(writer)?;
         Ok(())
     }
 }
@@ -462,8 +733,7 @@ impl Workspace for IntegrateDhtOpsWorkspace {
 impl IntegrateDhtOpsWorkspace {
     /// Constructor
     pub fn new(env: EnvironmentRead) -> WorkspaceResult<Self> {
-        let db = env.get_db(&*INTEGRATED_DHT_OPS)?;
-        let integrated_dht_ops = KvBufFresh::new(env.clone(), db);
+        let integrated_dht_ops = IntegratedDhtOpsBuf::new(env.clone())?;
 
         let db = env.get_db(&*INTEGRATION_LIMBO)?;
         let integration_limbo = KvBufFresh::new(env.clone(), db);
@@ -475,7 +745,11 @@ impl IntegrateDhtOpsWorkspace {
         let meta_judged = MetadataBuf::judged(env.clone())?;
 
         let element_rejected = ElementBuf::rejected(env.clone())?;
-        let meta_rejected = MetadataBuf::rejected(env)?;
+        let meta_rejected = MetadataBuf::rejected(env.clone())?;
+
+        let journal = integration_journal(env.clone())?;
+        let app_index = AppIndexBuf::new(env.clone())?;
+        let link_tags = LinkTagIndexBuf::vault(env)?;
 
         Ok(Self {
             integration_limbo,
@@ -487,14 +761,23 @@ impl IntegrateDhtOpsWorkspace {
             element_rejected,
             meta_rejected,
             to_disintegrate_judged: Vec::new(),
+            journal,
+            app_index,
+            link_tags,
         })
     }
 
     #[tracing::instrument(skip(self, hash))]
-    fn integrate(&mut self, hash: DhtOpHash, v: IntegratedDhtOpsValue) -> DhtOpConvertResult<()> {
+    fn integrate(
+        &mut self,
+        hash: DhtOpHash,
+        v: IntegratedDhtOpsValue,
+        header_timestamp: Timestamp,
+    ) -> DhtOpConvertResult<()> {
         disintegrate_single_metadata(v.op.clone(), &self.element_judged, &mut self.meta_judged)?;
         self.to_disintegrate_judged.push(v.op.clone());
-        self.integrated_dht_ops.put(hash, v)?;
+        self.integrated_dht_ops
+            .integrate(hash, v, Some(header_timestamp))?;
         Ok(())
     }
 
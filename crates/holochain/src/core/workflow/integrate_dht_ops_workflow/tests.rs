@@ -6,6 +6,7 @@ use crate::fixt::CallContextFixturator;
 use crate::fixt::ZomeCallHostAccessFixturator;
 use crate::here;
 use crate::{
+    conductor::api::MockCellConductorApi,
     core::{
         queue_consumer::TriggerSender,
         ribosome::{guest_callback::entry_defs::EntryDefsResult, host_fn, MockRibosomeT},
@@ -18,6 +19,7 @@ use crate::{
 use ::fixt::prelude::*;
 use holo_hash::*;
 use holochain_keystore::Signature;
+use holochain_p2p::HolochainP2pCellFixturator;
 use holochain_state::{
     env::{EnvironmentWrite, ReadManager, WriteManager},
     error::DatabaseError,
@@ -532,9 +534,18 @@ impl Db {
 async fn call_workflow<'env>(env: EnvironmentWrite) {
     let workspace = IntegrateDhtOpsWorkspace::new(env.clone().into()).unwrap();
     let (mut qt, _rx) = TriggerSender::new();
-    integrate_dht_ops_workflow(workspace, env.clone().into(), &mut qt)
-        .await
-        .unwrap();
+    let mut conductor_api = MockCellConductorApi::new();
+    conductor_api.expect_sync_get_this_dna().return_const(None);
+    let network = fixt!(HolochainP2pCell);
+    integrate_dht_ops_workflow(
+        workspace,
+        env.clone().into(),
+        &mut qt,
+        &network,
+        &conductor_api,
+    )
+    .await
+    .unwrap();
 }
 
 // Need to clear the data from the previous test
@@ -931,7 +942,7 @@ async fn get_entry(env: EnvironmentWrite, entry_hash: EntryHash) -> Option<Entry
 
     let mut call_context = CallContextFixturator::new(Unpredictable).next().unwrap();
 
-    let input = GetInput::new((entry_hash.clone().into(), GetOptions));
+    let input = GetInput::new((entry_hash.clone().into(), GetOptions::default()));
 
     let output = {
         let mut host_access = fixt!(ZomeCallHostAccess);
@@ -974,7 +985,7 @@ async fn create_link(
     call_context.zome_name = zome_name.clone();
 
     // Call create_link
-    let input = CreateLinkInput::new((base_address.into(), target_address.into(), link_tag));
+    let input = CreateLinkInput::new((base_address.into(), target_address.into(), None, link_tag));
 
     let output = {
         let mut host_access = fixt!(ZomeCallHostAccess);
@@ -1439,6 +1450,7 @@ mod slow_tests {
                 base_address: base_entry_hash.clone(),
                 target_address: target_entry_hash.clone(),
                 zome_id: 0.into(),
+                link_type: None,
                 tag: BytesFixturator::new(Unpredictable).next().unwrap().into(),
             };
             let hh = workspace
@@ -44,11 +44,7 @@ async fn produce_dht_ops_workflow_inner(
         for op in ops {
             let (op, hash) = DhtOpHashed::from_content_sync(op).into_inner();
             debug!(?hash, ?op);
-            let value = AuthoredDhtOpsValue {
-                op: op.to_light().await,
-                receipt_count: 0,
-                last_publish_time: None,
-            };
+            let value = AuthoredDhtOpsValue::from_light(op.to_light().await);
             workspace.authored_dht_ops.put(hash, value)?;
         }
         // Mark the dht op as complete
@@ -229,15 +225,7 @@ mod tests {
                 .authored_dht_ops
                 .iter(&reader)
                 .unwrap()
-                .map(|(k, v)| {
-                    assert_matches!(v, AuthoredDhtOpsValue {
-                        receipt_count: 0,
-                        last_publish_time: None,
-                        ..
-                    });
-
-                    Ok(DhtOpHash::with_pre_hashed(k.to_vec()))
-                })
+                .map(|(k, _)| Ok(DhtOpHash::with_pre_hashed(k.to_vec())))
                 .collect::<HashSet<_>>()
                 .unwrap();
             for a in &authored_results {
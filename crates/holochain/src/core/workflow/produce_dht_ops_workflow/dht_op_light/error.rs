@@ -1,5 +1,6 @@
 use crate::core::SourceChainError;
 use holo_hash::{AnyDhtHash, HeaderHash};
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_serialized_bytes::SerializedBytesError;
 use holochain_state::error::DatabaseError;
 use holochain_types::dht_op::error::DhtOpError;
@@ -34,4 +35,22 @@ pub enum DhtOpConvertError {
     WrongHeaderError(#[from] WrongHeaderError),
 }
 
+impl HasErrorKind for DhtOpConvertError {
+    fn error_kind(&self) -> ErrorKind {
+        use DhtOpConvertError::*;
+        match self {
+            DatabaseError(e) => e.error_kind(),
+            SerializedBytesError(_) => ErrorKind::Serialization,
+            MissingEntryDataForHeader(_) | MissingData(_) => ErrorKind::NotFound,
+            HeaderEntryMismatch
+            | DeleteLinkRequiresCreateLink
+            | HeaderMismatch(_, _)
+            | WrongHeaderError(_) => ErrorKind::InvalidInput,
+            StoreEntryOnPrivate => ErrorKind::Unauthorized,
+            SourceChainError(e) => e.error_kind(),
+            DhtOpError(_) => ErrorKind::Internal,
+        }
+    }
+}
+
 pub type DhtOpConvertResult<T> = Result<T, DhtOpConvertError>;
@@ -14,13 +14,19 @@ use super::{
     error::WorkflowResult,
     produce_dht_ops_workflow::dht_op_light::{error::DhtOpConvertError, light_to_op},
 };
+use crate::conductor::api::CellConductorApiT;
 use crate::core::{
+    clock::Clock,
     queue_consumer::{OneshotWriter, WorkComplete},
+    quota::{hour_ago, AppQuota},
     state::{
         dht_op_integration::AuthoredDhtOpsStore,
         element_buf::ElementBuf,
+        publish_progress::PublishProgressBuf,
+        source_chain::SourceChain,
         workspace::{Workspace, WorkspaceResult},
     },
+    validation_package::{assemble, entry_def_of, required_validation_type},
 };
 use fallible_iterator::FallibleIterator;
 use holo_hash::*;
@@ -33,15 +39,18 @@ use holochain_state::{
     prelude::*,
     transaction::Writer,
 };
-use holochain_types::{dht_op::DhtOp, Timestamp};
-use std::collections::HashMap;
+use holochain_types::dht_op::DhtOp;
+use holochain_zome_types::{
+    validate::{RequiredValidationType, ValidationPackage},
+    Header,
+};
+use std::collections::{HashMap, HashSet};
 use std::time;
 use tracing::*;
 
-/// Default redundancy factor for validation receipts
-// TODO: Pull this from the wasm entry def and only use this if it's missing
+/// Default redundancy factor for validation receipts, for ops whose header
+/// has no app entry, or whose entry def's required validations aren't known
 // TODO: Put a default in the DnaBundle
-// TODO: build zome_types/entry_def map to get the (AppEntryType map to entry def)
 pub const DEFAULT_RECEIPT_BUNDLE_SIZE: u32 = 5;
 
 /// Don't publish a DhtOp more than once during this interval.
@@ -51,23 +60,45 @@ pub const MIN_PUBLISH_INTERVAL: time::Duration = time::Duration::from_secs(5);
 
 /// Database buffers required for publishing [DhtOp]s
 pub struct PublishDhtOpsWorkspace {
-    /// Database of authored DhtOps, with data about prior publishing
+    /// Database of authored DhtOps
     authored_dht_ops: AuthoredDhtOpsStore,
+    /// When each authored op was last published and who has sent receipts
+    progress: PublishProgressBuf,
     /// Element store for looking up data to construct ops
     elements: ElementBuf,
+    /// Our public chain, for assembling validation packages
+    source_chain: SourceChain,
+    /// Where publish times come from
+    clock: Clock,
 }
 
-#[instrument(skip(workspace, writer, network))]
+#[instrument(skip(workspace, writer, network, conductor_api, quota))]
 pub async fn publish_dht_ops_workflow(
     mut workspace: PublishDhtOpsWorkspace,
     writer: OneshotWriter,
     network: &mut HolochainP2pCell,
+    conductor_api: &impl CellConductorApiT,
+    quota: &AppQuota,
 ) -> WorkflowResult<WorkComplete> {
-    let to_publish = publish_dht_ops_workflow_inner(&mut workspace).await?;
+    // Authored ops wait in the workspace until the cell is back online
+    if network.is_offline() {
+        return Ok(WorkComplete::Complete);
+    }
+    let to_publish = publish_dht_ops_workflow_inner(&mut workspace, quota).await?;
 
     // Commit to the network
     for (basis, ops) in to_publish {
-        network.publish(true, basis, ops, None).await?;
+        for (op_hash, op) in &ops {
+            let required = required_receipts(&op.header(), conductor_api).await;
+            workspace
+                .progress
+                .set_required_receipts(op_hash.clone(), required)?;
+        }
+        let validation_packages =
+            validation_packages(&workspace.source_chain, &ops, conductor_api).await?;
+        network
+            .publish(true, basis, ops, validation_packages, None)
+            .await?;
     }
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
@@ -77,55 +108,87 @@ pub async fn publish_dht_ops_workflow(
     Ok(WorkComplete::Complete)
 }
 
-/// Read the authored for ops with receipt count < R
+/// How many validation receipts an op needs before it's no longer published:
+/// the required validations of its header's entry def
+pub async fn required_receipts(header: &Header, conductor_api: &impl CellConductorApiT) -> u32 {
+    entry_def_of(header, conductor_api)
+        .await
+        .map(|entry_def| u8::from(entry_def.required_validations) as u32)
+        .unwrap_or(DEFAULT_RECEIPT_BUNDLE_SIZE)
+}
+
+/// Read the authored for ops with receipt count < R,
+/// where R is the op's required receipts.
+/// Ops past the app's publish quota are left for a later run.
 pub async fn publish_dht_ops_workflow_inner(
     workspace: &mut PublishDhtOpsWorkspace,
+    quota: &AppQuota,
 ) -> WorkflowResult<HashMap<AnyDhtHash, Vec<(DhtOpHash, DhtOp)>>> {
     // TODO: PERF: We need to check all ops every time this runs
     // instead we could have a queue of ops where count < R and a kv for count > R.
     // Then if the count for an ops reduces below R move it to the queue.
-    let now_ts = Timestamp::now();
+    let now_ts = workspace.clock.now();
     let now: chrono::DateTime<chrono::Utc> = now_ts.into();
     // chrono cannot create const durations
     let interval =
         chrono::Duration::from_std(MIN_PUBLISH_INTERVAL).expect("const interval must be positive");
 
+    let hour_ago = hour_ago(now_ts);
+    let mut published_in_last_hour = 0;
+
     // one of many ways to access the env
     let env = workspace.elements.headers().env().clone();
 
-    let values = fresh_reader!(env, |r| workspace
-        .authored()
-        .iter(&r)?
-        .filter_map(|(k, mut r)| {
-            Ok(if r.receipt_count < DEFAULT_RECEIPT_BUNDLE_SIZE {
-                let needs_publish = r
-                    .last_publish_time
-                    .map(|last| {
-                        let duration = now.signed_duration_since(last.into());
-                        duration > interval
-                    })
-                    .unwrap_or(true);
-                if needs_publish {
-                    r.last_publish_time = Some(now_ts);
-                    Some((DhtOpHash::with_pre_hashed(k.to_vec()), r))
+    let values = fresh_reader!(env, |r| {
+        let progress: HashMap<_, _> = workspace.progress.iter_all(&r)?.into_iter().collect();
+        workspace
+            .authored_dht_ops
+            .iter(&r)?
+            .filter_map(|(k, v)| {
+                let op_hash = DhtOpHash::with_pre_hashed(k.to_vec());
+                let p = progress.get(&op_hash).cloned().unwrap_or_default();
+                if p.last_publish_time.map_or(false, |last| last > hour_ago) {
+                    published_in_last_hour += 1;
+                }
+                Ok(if !p.is_saturated(DEFAULT_RECEIPT_BUNDLE_SIZE) {
+                    let needs_publish = p
+                        .last_publish_time
+                        .map(|last| {
+                            let duration = now.signed_duration_since(last.into());
+                            duration > interval
+                        })
+                        .unwrap_or(true);
+                    if needs_publish {
+                        Some((op_hash, v))
+                    } else {
+                        None
+                    }
                 } else {
                     None
-                }
-            } else {
-                None
+                })
             })
-        })
-        .collect::<Vec<_>>())?;
+            .collect::<Vec<_>>()
+    })?;
+
+    let values = match quota.publish_allowance(published_in_last_hour) {
+        Some(allowance) if allowance < values.len() => {
+            warn!(
+                deferred = values.len() - allowance,
+                "Quota exceeded: the app has published all the ops it may this hour"
+            );
+            values.into_iter().take(allowance).collect()
+        }
+        _ => values,
+    };
 
     // Ops to publish by basis
     let mut to_publish = HashMap::new();
 
     for (op_hash, value) in values {
-        // Insert updated values into database for items about to be published
-        let op = value.op.clone();
-        workspace.authored().put(op_hash.clone(), value)?;
+        // Record the publish time for items about to be published
+        workspace.progress.record_publish(op_hash.clone(), now_ts)?;
 
-        let op = match light_to_op(op, workspace.elements()).await {
+        let op = match light_to_op(value.op, workspace.elements()).await {
             // Ignore StoreEntry ops on private
             Err(DhtOpConvertError::StoreEntryOnPrivate) => continue,
             r => r?,
@@ -141,9 +204,32 @@ pub async fn publish_dht_ops_workflow_inner(
     Ok(to_publish)
 }
 
+/// Assemble packages for the ops' headers whose entry defs ask for one,
+/// so validators don't need to come back and ask us
+async fn validation_packages(
+    source_chain: &SourceChain,
+    ops: &[(DhtOpHash, DhtOp)],
+    conductor_api: &impl CellConductorApiT,
+) -> WorkflowResult<Vec<(HeaderHash, ValidationPackage)>> {
+    let mut packages = Vec::new();
+    let mut seen = HashSet::new();
+    for (_, op) in ops {
+        let header = op.header();
+        let header_hash = HeaderHash::with_data_sync(&header);
+        if !seen.insert(header_hash.clone()) {
+            continue;
+        }
+        match required_validation_type(&header, conductor_api).await {
+            RequiredValidationType::Element => {}
+            required => packages.push((header_hash, assemble(source_chain, &header, required)?)),
+        }
+    }
+    Ok(packages)
+}
+
 impl Workspace for PublishDhtOpsWorkspace {
     fn flush_to_txn_ref(&mut self, writer: &mut Writer) -> WorkspaceResult<()> {
-        self.authored_dht_ops.flush_to_txn_ref(writer)?;
+        self.progress.flush_to_txn_ref(writer)?;
         Ok(())
     }
 }
@@ -152,16 +238,22 @@ impl PublishDhtOpsWorkspace {
     pub fn new(env: EnvironmentRead) -> WorkspaceResult<Self> {
         let db = env.get_db(&*AUTHORED_DHT_OPS)?;
         let authored_dht_ops = KvBufFresh::new(env.clone(), db);
+        let progress = PublishProgressBuf::new(env.clone())?;
         // Note that this must always be false as we don't want private entries being published
-        let elements = ElementBuf::vault(env, false)?;
+        let elements = ElementBuf::vault(env.clone(), false)?;
+        let source_chain = SourceChain::public_only(env)?;
         Ok(Self {
             authored_dht_ops,
+            progress,
             elements,
+            source_chain,
+            clock: Clock::default(),
         })
     }
 
-    fn authored(&mut self) -> &mut AuthoredDhtOpsStore {
-        &mut self.authored_dht_ops
+    /// Time publishes with this clock instead of the system's
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
     }
 
     fn elements(&self) -> &ElementBuf {
@@ -173,6 +265,7 @@ impl PublishDhtOpsWorkspace {
 mod tests {
     use super::*;
     use crate::{
+        conductor::api::MockCellConductorApi,
         core::{
             queue_consumer::TriggerSender,
             state::{dht_op_integration::AuthoredDhtOpsValue, source_chain::SourceChain},
@@ -182,7 +275,7 @@ mod tests {
             },
             SourceChainError,
         },
-        fixt::{CreateLinkFixturator, EntryFixturator},
+        fixt::{CreateLinkFixturator, DnaFileFixturator, EntryFixturator},
     };
     use ::fixt::prelude::*;
     use futures::future::FutureExt;
@@ -200,13 +293,16 @@ mod tests {
     };
     use holochain_types::{
         dht_op::{DhtOp, DhtOpHashed, DhtOpLight},
-        fixt::{AppEntryTypeFixturator, SignatureFixturator},
+        fixt::{
+            AppEntryTypeFixturator, EntryDefFixturator, SignatureFixturator, ZomeFixturator,
+            ZomeNameFixturator,
+        },
         observability, HeaderHashed,
     };
     use holochain_zome_types::entry_def::EntryVisibility;
     use holochain_zome_types::{
         element::SignedHeaderHashed,
-        header::{builder, EntryType, Update},
+        header::{builder, AppEntryType, EntryType, Update},
     };
     use matches::assert_matches;
     use std::{
@@ -333,9 +429,18 @@ mod tests {
     /// Call the workflow
     async fn call_workflow(env: EnvironmentWrite, mut cell_network: HolochainP2pCell) {
         let workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
-        publish_dht_ops_workflow(workspace, env.clone().into(), &mut cell_network)
-            .await
-            .unwrap();
+        // Without a dna no entry def asks for a validation package
+        let mut conductor_api = MockCellConductorApi::new();
+        conductor_api.expect_sync_get_this_dna().returning(|| None);
+        publish_dht_ops_workflow(
+            workspace,
+            env.clone().into(),
+            &mut cell_network,
+            &conductor_api,
+            &AppQuota::default(),
+        )
+        .await
+        .unwrap();
     }
 
     /// There is a test that shows that network messages would be sent to all agents via broadcast.
@@ -374,10 +479,22 @@ mod tests {
                 let env_ref = env.guard();
                 recv_task.await.unwrap();
                 let reader = env_ref.reader().unwrap();
-                let mut workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
-                for i in workspace.authored().iter(&reader).unwrap().iterator() {
+                let workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
+                for (op_hash, _) in workspace
+                    .authored_dht_ops
+                    .iter(&reader)
+                    .unwrap()
+                    .iterator()
+                    .map(|i| i.expect("can iterate"))
+                {
                     // Check that each item now has a publish time
-                    assert!(i.expect("can iterate").1.last_publish_time.is_some())
+                    let op_hash = DhtOpHash::with_pre_hashed(op_hash.to_vec());
+                    assert!(workspace
+                        .progress
+                        .get(&op_hash)
+                        .unwrap()
+                        .last_publish_time
+                        .is_some())
                 }
             };
 
@@ -391,7 +508,7 @@ mod tests {
         });
     }
 
-    /// There is a test that shows that if the validation_receipt_count > R
+    /// There is a test that shows that if the validation receipt count >= R
     /// for a DHTOp we don't re-publish it
     #[test_case(1, 1)]
     #[test_case(1, 10)]
@@ -415,33 +532,33 @@ mod tests {
             let (network, cell_network, recv_task, _) =
                 setup(env.clone(), num_agents, num_hash, true).await;
 
-            // Update the authored to have > R counts
+            // Give every authored op receipts from R validators
             {
                 let reader = env_ref.reader().unwrap();
                 let mut workspace = PublishDhtOpsWorkspace::new(env.clone().into()).unwrap();
 
-                // Update authored to R
-                let values = workspace
+                let hashes = workspace
                     .authored_dht_ops
                     .iter(&reader)
                     .unwrap()
-                    .map(|(k, mut v)| {
-                        v.receipt_count = DEFAULT_RECEIPT_BUNDLE_SIZE;
-                        Ok((DhtOpHash::with_pre_hashed(k.to_vec()), v))
-                    })
+                    .map(|(k, _)| Ok(DhtOpHash::with_pre_hashed(k.to_vec())))
                     .collect::<Vec<_>>()
                     .unwrap();
 
-                for (hash, v) in values.into_iter() {
-                    workspace.authored_dht_ops.put(hash, v).unwrap();
+                let validators = AgentPubKeyFixturator::new(Unpredictable)
+                    .take(DEFAULT_RECEIPT_BUNDLE_SIZE as usize)
+                    .collect::<Vec<_>>();
+                for hash in hashes {
+                    for validator in &validators {
+                        workspace
+                            .progress
+                            .record_receipt(hash.clone(), validator.clone())
+                            .unwrap();
+                    }
                 }
 
-                // Manually commit because this workspace doesn't commit to all dbs
                 env_ref
-                    .with_commit::<DatabaseError, _, _>(|writer| {
-                        workspace.authored_dht_ops.flush_to_txn(writer)?;
-                        Ok(())
-                    })
+                    .with_commit(|writer| workspace.progress.flush_to_txn(writer))
                     .unwrap();
             }
 
@@ -746,5 +863,79 @@ mod tests {
         );
     }
 
+    /// Ops are published with the packages their entry defs ask for
+    #[tokio::test(threaded_scheduler)]
+    async fn validation_packages_are_attached() {
+        let test_env = test_cell_env();
+        let env = test_env.env();
+        let mut chain = SourceChain::new(env.clone().into()).unwrap();
+        fake_genesis(&mut chain).await.unwrap();
+        let agent_header_hash = chain.chain_head().unwrap().clone();
+        let entry_type = EntryType::App(AppEntryType::new(
+            0.into(),
+            0.into(),
+            EntryVisibility::Public,
+        ));
+        let mut header_hashes = Vec::new();
+        for _ in 0..2 {
+            let entry = fixt!(Entry);
+            let builder = builder::Create {
+                entry_type: entry_type.clone(),
+                entry_hash: EntryHash::with_data_sync(&entry),
+            };
+            header_hashes.push(chain.put(builder, Some(entry)).await.unwrap());
+        }
+        let (earlier, later) = (&header_hashes[0], &header_hashes[1]);
+        let header_of = |hash: &HeaderHash| {
+            chain
+                .get_header(hash)
+                .unwrap()
+                .unwrap()
+                .into_header_and_signature()
+        };
+        let (agent_header, agent_signature) = header_of(&agent_header_hash);
+        let (later_header, signature) = header_of(later);
+        let later_header = later_header.into_content();
+        let ops = vec![
+            DhtOp::StoreElement(signature.clone(), later_header.clone(), None),
+            DhtOp::RegisterAgentActivity(signature, later_header),
+            // Agent keys have no entry def
+            DhtOp::RegisterAgentActivity(agent_signature, agent_header.into_content()),
+        ]
+        .into_iter()
+        .map(|op| (DhtOpHash::with_data_sync(&op), op))
+        .collect::<Vec<_>>();
+
+        // The one zome's entry def asks for the earlier entries of its type
+        let mut dna_file = DnaFileFixturator::new(Empty).next().unwrap();
+        dna_file.dna.zomes.clear();
+        dna_file.dna.zomes.push((fixt!(ZomeName), fixt!(Zome)));
+        let mut entry_def = fixt!(EntryDef);
+        entry_def.required_validation_type = RequiredValidationType::SubChain;
+        let mut conductor_api = MockCellConductorApi::new();
+        conductor_api
+            .expect_sync_get_this_dna()
+            .return_const(Some(dna_file));
+        conductor_api
+            .expect_sync_get_entry_def()
+            .return_const(Some(entry_def));
+
+        let packages = validation_packages(&chain, &ops, &conductor_api)
+            .await
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        let (header_hash, package) = &packages[0];
+        assert_eq!(header_hash, later);
+        // Every earlier header, but only the entries of the same type
+        assert_eq!(package.0.len(), 4);
+        let with_entries: Vec<_> = package
+            .0
+            .iter()
+            .filter(|el| el.entry().as_option().is_some())
+            .map(|el| el.header_address())
+            .collect();
+        assert_eq!(with_entries, vec![earlier]);
+    }
+
     // TODO: COVERAGE: Test public ops do publish
 }
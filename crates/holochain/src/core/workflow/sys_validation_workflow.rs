@@ -4,15 +4,20 @@ use super::*;
 use crate::{
     conductor::api::CellConductorApiT,
     core::{
+        clock::Clock,
         queue_consumer::{OneshotWriter, TriggerSender, WorkComplete},
         state::{
             cascade::Cascade,
             dht_op_integration::{IntegrationLimboStore, IntegrationLimboValue},
             element_buf::ElementBuf,
             metadata::MetadataBuf,
-            validation_db::{ValidationLimboStatus, ValidationLimboStore, ValidationLimboValue},
+            validation_db::{
+                AbandonedDhtOpsStore, AbandonedDhtOpsValue, AbandonedReason, ValidationLimboStatus,
+                ValidationLimboStore, ValidationLimboValue,
+            },
             workspace::{Workspace, WorkspaceResult},
         },
+        subconscious::{DependencyJudgement, UnresolvedDependencyPolicy},
         sys_validate::*,
     },
 };
@@ -29,15 +34,16 @@ use holochain_state::{
 };
 use holochain_types::{
     dht_op::DhtOp, dht_op::DhtOpLight, header::NewEntryHeaderRef, test_utils::which_agent,
-    validate::ValidationStatus, Entry, Timestamp,
+    validate::ValidationStatus, Entry,
 };
 use holochain_zome_types::{
     header::{CreateLink, Delete, DeleteLink, EntryType, Update},
     Header,
 };
-use std::{collections::BinaryHeap, convert::TryInto};
+use std::convert::TryInto;
 use tracing::*;
 
+use dependency_fetch::DependencyFetcher;
 use integrate_dht_ops_workflow::{
     disintegrate_single_data, disintegrate_single_metadata, integrate_single_data,
     integrate_single_metadata, reintegrate_single_data,
@@ -45,20 +51,38 @@ use integrate_dht_ops_workflow::{
 use produce_dht_ops_workflow::dht_op_light::light_to_op;
 use types::{CheckLevel, DhtOpOrder, OrderedOp, Outcome, PendingDependencies};
 
+pub mod dependency_fetch;
 pub mod types;
 
 #[cfg(test)]
 mod tests;
 
-#[instrument(skip(workspace, writer, trigger_app_validation, network, conductor_api))]
+#[instrument(skip(
+    workspace,
+    writer,
+    trigger_app_validation,
+    network,
+    conductor_api,
+    dependency_fetcher,
+    policy
+))]
 pub async fn sys_validation_workflow(
     mut workspace: SysValidationWorkspace,
     writer: OneshotWriter,
     trigger_app_validation: &mut TriggerSender,
     network: HolochainP2pCell,
     conductor_api: impl CellConductorApiT,
+    dependency_fetcher: &DependencyFetcher,
+    policy: &UnresolvedDependencyPolicy,
 ) -> WorkflowResult<WorkComplete> {
-    let complete = sys_validation_workflow_inner(&mut workspace, network, conductor_api).await?;
+    let complete = sys_validation_workflow_inner(
+        &mut workspace,
+        network,
+        conductor_api,
+        dependency_fetcher,
+        policy,
+    )
+    .await?;
 
     // --- END OF WORKFLOW, BEGIN FINISHER BOILERPLATE ---
 
@@ -75,6 +99,8 @@ async fn sys_validation_workflow_inner(
     workspace: &mut SysValidationWorkspace,
     network: HolochainP2pCell,
     conductor_api: impl CellConductorApiT,
+    dependency_fetcher: &DependencyFetcher,
+    policy: &UnresolvedDependencyPolicy,
 ) -> WorkflowResult<WorkComplete> {
     let env = workspace.validation_limbo.env().clone();
     // Drain all the ops
@@ -94,7 +120,7 @@ async fn sys_validation_workflow_inner(
         .collect())?;
 
     // Sort the ops
-    let mut sorted_ops = BinaryHeap::new();
+    let mut sorted_ops = Vec::with_capacity(ops.len());
     for vlv in ops {
         // let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
         let op = light_to_op(vlv.op.clone(), &workspace.element_pending).await?;
@@ -107,14 +133,15 @@ async fn sys_validation_workflow_inner(
             op,
             value: vlv,
         };
-        // We want a min-heap
-        sorted_ops.push(std::cmp::Reverse(v));
+        sorted_ops.push(v);
 
         // Since we are processing DhtOps in a loop, make sure we yield
         // between each one, since hashing could take a while
         tokio::task::yield_now().await;
     }
 
+    sorted_ops.sort_unstable();
+
     // Process each op
     for so in sorted_ops {
         let OrderedOp {
@@ -122,7 +149,7 @@ async fn sys_validation_workflow_inner(
             op,
             value: mut vlv,
             ..
-        } = so.0;
+        } = so;
         let outcome = validate_op(
             &op,
             workspace,
@@ -133,6 +160,7 @@ async fn sys_validation_workflow_inner(
         )
         .await?;
 
+        let now = workspace.clock.now();
         match outcome {
             Outcome::Accepted => {
                 vlv.status = ValidationLimboStatus::SysValidated;
@@ -151,22 +179,31 @@ async fn sys_validation_workflow_inner(
                 }
             }
             Outcome::AwaitingOpDep(missing_dep) => {
-                // TODO: Try and get this dependency to add to limbo
-                //
-                // I actually can't see how we can do this because there's no
-                // way to get an DhtOpHash without either having the op or the full
-                // header. We have neither that's why where here.
-                //
-                // We need to be holding the dependency because
-                // we were meant to get a StoreElement or StoreEntry or
-                // RegisterAgentActivity or RegisterAddLink.
-                vlv.status = ValidationLimboStatus::AwaitingSysDeps(missing_dep);
-                workspace.put_val_limbo(op_hash, vlv)?;
-            }
-            Outcome::MissingDhtDep => {
-                vlv.status = ValidationLimboStatus::Pending;
-                workspace.put_val_limbo(op_hash, vlv)?;
+                match policy.judge_missing(&vlv, Some(missing_dep.clone()), now) {
+                    DependencyJudgement::Pending => {
+                        // We need to be holding the dependency because
+                        // we were meant to get a StoreElement or StoreEntry or
+                        // RegisterAgentActivity or RegisterAddLink.
+                        // Rather than wait for gossip, pull the element from its
+                        // authorities and validate its ops ourselves.
+                        dependency_fetcher.fetch(missing_dep.clone());
+                        vlv.status = ValidationLimboStatus::AwaitingSysDeps(missing_dep);
+                        workspace.put_val_limbo(op_hash, vlv)?;
+                    }
+                    DependencyJudgement::Abandoned(reason) => {
+                        workspace.abandon(op_hash, vlv, op, reason)?;
+                    }
+                }
             }
+            Outcome::MissingDhtDep => match policy.judge_missing(&vlv, None, now) {
+                DependencyJudgement::Pending => {
+                    vlv.status = ValidationLimboStatus::Pending;
+                    workspace.put_val_limbo(op_hash, vlv)?;
+                }
+                DependencyJudgement::Abandoned(reason) => {
+                    workspace.abandon(op_hash, vlv, op, reason)?;
+                }
+            },
             Outcome::Rejected => {
                 let iv = IntegrationLimboValue {
                     op: vlv.op,
@@ -232,6 +269,8 @@ fn handle_failed(error: ValidationOutcome) -> Outcome {
         ValidationOutcome::EntryTooLarge(_, _) => Rejected,
         ValidationOutcome::EntryType => Rejected,
         ValidationOutcome::EntryVisibility(_) => Rejected,
+        ValidationOutcome::LinkType(_, _) => Rejected,
+        ValidationOutcome::LinkTagOrder(_, _, _) => Rejected,
         ValidationOutcome::TagTooLarge(_, _) => Rejected,
         ValidationOutcome::NotCreateLink(_) => Rejected,
         ValidationOutcome::NotNewEntry(_) => Rejected,
@@ -327,7 +366,15 @@ async fn validate_op_inner(
             Ok(())
         }
         DhtOp::RegisterAddLink(signature, header) => {
-            register_add_link(header, workspace, network, dependencies, check_level).await?;
+            register_add_link(
+                header,
+                conductor_api,
+                workspace,
+                network,
+                dependencies,
+                check_level,
+            )
+            .await?;
 
             let header = header.clone().into();
             all_op_check(signature, &header).await?;
@@ -495,6 +542,7 @@ async fn register_deleted_entry_header(
 
 async fn register_add_link(
     link_add: &CreateLink,
+    conductor_api: &impl CellConductorApiT,
     workspace: &mut SysValidationWorkspace,
     network: HolochainP2pCell,
     dependencies: &mut PendingDependencies,
@@ -512,6 +560,7 @@ async fn register_add_link(
     let dependency = check_entry_exists(target_entry_address.clone(), workspace, network).await?;
     dependencies.store_entry_any(dependency).await?;
     check_tag_size(&link_add.tag)?;
+    check_link_type(link_add, conductor_api).await?;
     Ok(())
 }
 
@@ -544,6 +593,7 @@ fn update_check(entry_update: &Update, original_header: &Header) -> SysValidatio
 pub struct SysValidationWorkspace {
     pub integration_limbo: IntegrationLimboStore,
     pub validation_limbo: ValidationLimboStore,
+    pub abandoned_dht_ops: AbandonedDhtOpsStore,
     // Integrated data
     pub element_vault: ElementBuf,
     pub meta_vault: MetadataBuf,
@@ -558,6 +608,8 @@ pub struct SysValidationWorkspace {
     pub meta_cache: MetadataBuf,
     // Ops to disintegrate
     pub to_disintegrate_pending: Vec<DhtOpLight>,
+    /// Where tries and abandonments get their timestamps from
+    pub clock: Clock,
 }
 
 impl<'a> SysValidationWorkspace {
@@ -582,6 +634,7 @@ impl SysValidationWorkspace {
         let integration_limbo = KvBufFresh::new(env.clone(), db);
 
         let validation_limbo = ValidationLimboStore::new(env.clone())?;
+        let abandoned_dht_ops = AbandonedDhtOpsStore::new(env.clone())?;
 
         let element_vault = ElementBuf::vault(env.clone(), false)?;
         let meta_vault = MetadataBuf::vault(env.clone())?;
@@ -597,6 +650,7 @@ impl SysValidationWorkspace {
         Ok(Self {
             integration_limbo,
             validation_limbo,
+            abandoned_dht_ops,
             element_vault,
             meta_vault,
             element_pending,
@@ -606,6 +660,7 @@ impl SysValidationWorkspace {
             element_cache,
             meta_cache,
             to_disintegrate_pending: Vec::new(),
+            clock: Clock::default(),
         })
     }
 
@@ -614,7 +669,7 @@ impl SysValidationWorkspace {
         hash: DhtOpHash,
         mut vlv: ValidationLimboValue,
     ) -> WorkflowResult<()> {
-        vlv.last_try = Some(Timestamp::now());
+        vlv.last_try = Some(self.clock.now());
         vlv.num_tries += 1;
         self.validation_limbo.put(hash, vlv)?;
         Ok(())
@@ -635,6 +690,31 @@ impl SysValidationWorkspace {
         Ok(())
     }
 
+    /// Give up on validating an op and record why
+    fn abandon(
+        &mut self,
+        hash: DhtOpHash,
+        vlv: ValidationLimboValue,
+        op: DhtOp,
+        reason: AbandonedReason,
+    ) -> WorkflowResult<()> {
+        debug!(?hash, ?reason, "Abandoning op");
+        self.abandoned_dht_ops.put(
+            hash.clone(),
+            AbandonedDhtOpsValue {
+                op: vlv.op.clone(),
+                reason,
+                pending_dependencies: vlv.pending_dependencies,
+                when_abandoned: self.clock.now(),
+            },
+        )?;
+        let iv = IntegrationLimboValue {
+            op: vlv.op,
+            validation_status: ValidationStatus::Abandoned,
+        };
+        self.put_int_limbo(hash, iv, op)
+    }
+
     #[tracing::instrument(skip(self, writer))]
     /// We need to cancel any deletes for the pending data
     /// where the ops still in validation limbo reference that data
@@ -655,6 +735,7 @@ impl Workspace for SysValidationWorkspace {
         self.update_element_stores(writer)?;
         self.validation_limbo.0.flush_to_txn_ref(writer)?;
         self.integration_limbo.flush_to_txn_ref(writer)?;
+        self.abandoned_dht_ops.0.flush_to_txn_ref(writer)?;
         // Flush for cascade
         self.element_cache.flush_to_txn_ref(writer)?;
         self.meta_cache.flush_to_txn_ref(writer)?;
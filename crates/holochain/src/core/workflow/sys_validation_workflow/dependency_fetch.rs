@@ -0,0 +1,207 @@
+//! Targeted pulls of the dependencies that ops are parked on.
+//!
+//! Rather than waiting for gossip to deliver a missing dependency, we ask
+//! its authorities for it via the cascade, and feed the ops of the element
+//! we get back into our own validation limbo. Each hash is only fetched
+//! once at a time, failed fetches back off exponentially, and only so many
+//! fetches run at once.
+
+use super::SysValidationWorkspace;
+use crate::core::{
+    queue_consumer::TriggerSender,
+    sys_validate::SysValidationError,
+    workflow::{error::WorkflowResult, incoming_dht_ops_workflow::incoming_dht_ops_workflow},
+};
+use holo_hash::{AnyDhtHash, DhtOpHash};
+use holochain_p2p::HolochainP2pCell;
+use holochain_state::env::EnvironmentWrite;
+use holochain_types::dht_op::produce_ops_from_element;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+use tracing::*;
+
+/// How many dependency fetches can be in flight at once
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// How long to wait before retrying a dependency that couldn't be found
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest we'll wait between retries of a dependency
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug)]
+struct Backoff {
+    attempts: u32,
+    next_try: Instant,
+}
+
+#[derive(Debug, Default)]
+struct FetchState {
+    in_flight: HashSet<AnyDhtHash>,
+    backoff: HashMap<AnyDhtHash, Backoff>,
+}
+
+impl FetchState {
+    /// Claim a hash for fetching, unless it's already being fetched
+    /// or we tried it too recently
+    fn start(&mut self, hash: &AnyDhtHash, now: Instant) -> bool {
+        if self.in_flight.contains(hash) {
+            return false;
+        }
+        if let Some(backoff) = self.backoff.get(hash) {
+            if now < backoff.next_try {
+                return false;
+            }
+        }
+        self.in_flight.insert(hash.clone());
+        true
+    }
+
+    /// Release a hash, backing off if it wasn't found
+    fn finish(&mut self, hash: &AnyDhtHash, found: bool, now: Instant) {
+        self.in_flight.remove(hash);
+        if found {
+            self.backoff.remove(hash);
+            return;
+        }
+        let attempts = self.backoff.get(hash).map(|b| b.attempts).unwrap_or(0) + 1;
+        let delay = INITIAL_BACKOFF
+            .checked_mul(2u32.saturating_pow(attempts - 1))
+            .map(|d| d.min(MAX_BACKOFF))
+            .unwrap_or(MAX_BACKOFF);
+        self.backoff.insert(
+            hash.clone(),
+            Backoff {
+                attempts,
+                next_try: now + delay,
+            },
+        );
+    }
+}
+
+/// Fetches the dependencies of parked ops for a cell.
+/// Cloning shares the deduplication, backoff and concurrency limit.
+#[derive(Clone)]
+pub struct DependencyFetcher {
+    env: EnvironmentWrite,
+    network: HolochainP2pCell,
+    sys_validation_trigger: TriggerSender,
+    state: Arc<Mutex<FetchState>>,
+    permits: Arc<Semaphore>,
+}
+
+impl DependencyFetcher {
+    /// Create a fetcher which feeds what it finds into this cell's
+    /// validation limbo, triggering sys validation when it does
+    pub fn new(
+        env: EnvironmentWrite,
+        network: HolochainP2pCell,
+        sys_validation_trigger: TriggerSender,
+    ) -> Self {
+        Self {
+            env,
+            network,
+            sys_validation_trigger,
+            state: Arc::new(Mutex::new(FetchState::default())),
+            permits: Arc::new(Semaphore::new(MAX_CONCURRENT_FETCHES)),
+        }
+    }
+
+    /// Fetch a dependency in the background,
+    /// unless it's already being fetched or is backing off
+    pub fn fetch(&self, hash: AnyDhtHash) {
+        if !self
+            .state
+            .lock()
+            .expect("Dependency fetch state poisoned")
+            .start(&hash, Instant::now())
+        {
+            return;
+        }
+        let this = self.clone();
+        tokio::task::spawn(async move {
+            let found = {
+                let _permit = this.permits.acquire().await;
+                match this.fetch_inner(hash.clone()).await {
+                    Ok(found) => found,
+                    Err(e) => {
+                        warn!(?hash, ?e, "Failed to fetch a missing dependency");
+                        false
+                    }
+                }
+            };
+            debug!(?hash, found, "Fetched a missing dependency");
+            this.state
+                .lock()
+                .expect("Dependency fetch state poisoned")
+                .finish(&hash, found, Instant::now());
+        });
+    }
+
+    /// Retrieve the element and hand all of its ops to incoming ops, as
+    /// the parked op may depend on any of them (e.g. the agent activity of
+    /// a previous header, or the link of a CreateLink)
+    async fn fetch_inner(&self, hash: AnyDhtHash) -> WorkflowResult<bool> {
+        let element = {
+            let mut workspace = SysValidationWorkspace::new(self.env.clone().into())?;
+            workspace
+                .cascade(self.network.clone())
+                .retrieve(hash, Default::default())
+                .await
+                .map_err(SysValidationError::from)?
+        };
+        let element = match element {
+            Some(element) => element,
+            None => return Ok(false),
+        };
+        let ops = produce_ops_from_element(&element)
+            .await?
+            .into_iter()
+            .map(|op| (DhtOpHash::with_data_sync(&op), op))
+            .collect();
+        incoming_dht_ops_workflow(&self.env, self.sys_validation_trigger.clone(), ops, vec![])
+            .await?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::HeaderHashFixturator;
+
+    #[test]
+    fn fetches_are_deduplicated_and_back_off() {
+        let hash: AnyDhtHash = fixt!(HeaderHash).into();
+        let mut state = FetchState::default();
+        let now = Instant::now();
+
+        assert!(state.start(&hash, now));
+        // already in flight
+        assert!(!state.start(&hash, now));
+
+        state.finish(&hash, false, now);
+        assert!(!state.start(&hash, now));
+        assert!(state.start(&hash, now + INITIAL_BACKOFF));
+
+        // the second failure waits twice as long
+        state.finish(&hash, false, now);
+        assert!(!state.start(&hash, now + INITIAL_BACKOFF));
+        assert!(state.start(&hash, now + INITIAL_BACKOFF * 2));
+
+        // but never longer than the max
+        for _ in 0..32 {
+            state.finish(&hash, false, now);
+            assert!(state.start(&hash, now + MAX_BACKOFF));
+        }
+
+        // finding it forgets the backoff
+        state.finish(&hash, true, now);
+        assert!(state.start(&hash, now));
+    }
+}
@@ -24,7 +24,8 @@ pub(super) enum Outcome {
 
 /// Type for deriving ordering of DhtOps
 /// Don't change the order of this enum unless
-/// you mean to change the order we process ops
+/// you mean to change the order we process ops.
+/// Ops of the same type are ordered by their header's timestamp.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum DhtOpOrder {
@@ -38,12 +39,13 @@ pub enum DhtOpOrder {
     RegisterRemoveLink(holochain_zome_types::timestamp::Timestamp),
 }
 
-/// Op data that will be ordered by [DhtOpOrder]
+/// Op data that will be ordered by [DhtOpOrder] then by op hash.
+/// This is a total order, so every node processes the same set of ops
+/// in the same order no matter which order they arrived in.
 #[derive(Derivative, Debug, Clone)]
 #[derivative(Eq, PartialEq, Ord, PartialOrd)]
 pub struct OrderedOp<V> {
     pub order: DhtOpOrder,
-    #[derivative(PartialEq = "ignore", PartialOrd = "ignore", Ord = "ignore")]
     pub hash: DhtOpHash,
     #[derivative(PartialEq = "ignore", PartialOrd = "ignore", Ord = "ignore")]
     pub op: DhtOp,
@@ -1,9 +1,17 @@
 pub mod curve;
 
+use crate::core::cancel::CancelToken;
+use crate::core::clock::Clock;
+use crate::core::clock::Entropy;
+use crate::core::quota::AppQuota;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsHostAccess;
 use crate::core::ribosome::guest_callback::entry_defs::EntryDefsInvocation;
+use crate::core::ribosome::guest_callback::index_op::IndexOpHostAccess;
+use crate::core::ribosome::guest_callback::index_op::IndexOpInvocation;
 use crate::core::ribosome::guest_callback::init::InitHostAccess;
 use crate::core::ribosome::guest_callback::init::InitInvocation;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesHostAccess;
+use crate::core::ribosome::guest_callback::link_types::LinkTypesInvocation;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentHostAccess;
 use crate::core::ribosome::guest_callback::migrate_agent::MigrateAgentInvocation;
 use crate::core::ribosome::guest_callback::post_commit::PostCommitHostAccess;
@@ -20,6 +28,13 @@ use crate::core::ribosome::FnComponents;
 use crate::core::ribosome::HostAccess;
 use crate::core::ribosome::ZomeCallHostAccess;
 use crate::core::state::metadata::LinkMetaVal;
+use crate::core::state::source_chain::CapGrantIndex;
+use crate::core::workflow::app_validation_workflow::AppValidationWorkspace;
+use crate::core::workflow::incoming_dht_ops_workflow::IncomingDhtOpsWorkspace;
+use crate::core::workflow::integrate_dht_ops_workflow::IntegrateDhtOpsWorkspace;
+use crate::core::workflow::produce_dht_ops_workflow::ProduceDhtOpsWorkspace;
+use crate::core::workflow::publish_dht_ops_workflow::PublishDhtOpsWorkspace;
+use crate::core::workflow::sys_validation_workflow::SysValidationWorkspace;
 use crate::core::workflow::CallZomeWorkspace;
 use crate::core::workflow::CallZomeWorkspaceLock;
 use ::fixt::prelude::*;
@@ -40,6 +55,7 @@ use holochain_wasm_test_utils::strum::IntoEnumIterator;
 use holochain_wasm_test_utils::TestWasm;
 use holochain_zome_types::header::HeaderHashes;
 use holochain_zome_types::link::LinkTag;
+use holochain_zome_types::provenance::CallProvenance;
 use holochain_zome_types::ExternInput;
 use rand::seq::IteratorRandom;
 use rand::thread_rng;
@@ -278,9 +294,105 @@ fixturator!(
     };
 );
 
+fixturator!(
+    AppValidationWorkspace;
+    curve Empty {
+        // See CallZomeWorkspaceLock for why grabbing an environment is ok
+        let env = holochain_state::test_utils::test_cell_env();
+        AppValidationWorkspace::new(env.env().into()).unwrap()
+    };
+    curve Unpredictable AppValidationWorkspaceFixturator::new(Empty).next().unwrap();
+    curve Predictable AppValidationWorkspaceFixturator::new(Empty).next().unwrap();
+);
+
+fixturator!(
+    IncomingDhtOpsWorkspace;
+    curve Empty {
+        // See CallZomeWorkspaceLock for why grabbing an environment is ok
+        let env = holochain_state::test_utils::test_cell_env();
+        IncomingDhtOpsWorkspace::new(env.env().into()).unwrap()
+    };
+    curve Unpredictable IncomingDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+    curve Predictable IncomingDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+);
+
+fixturator!(
+    IntegrateDhtOpsWorkspace;
+    curve Empty {
+        // See CallZomeWorkspaceLock for why grabbing an environment is ok
+        let env = holochain_state::test_utils::test_cell_env();
+        IntegrateDhtOpsWorkspace::new(env.env().into()).unwrap()
+    };
+    curve Unpredictable IntegrateDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+    curve Predictable IntegrateDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+);
+
+fixturator!(
+    ProduceDhtOpsWorkspace;
+    curve Empty {
+        // See CallZomeWorkspaceLock for why grabbing an environment is ok
+        let env = holochain_state::test_utils::test_cell_env();
+        ProduceDhtOpsWorkspace::new(env.env().into()).unwrap()
+    };
+    curve Unpredictable ProduceDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+    curve Predictable ProduceDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+);
+
+fixturator!(
+    PublishDhtOpsWorkspace;
+    curve Empty {
+        // See CallZomeWorkspaceLock for why grabbing an environment is ok
+        let env = holochain_state::test_utils::test_cell_env();
+        PublishDhtOpsWorkspace::new(env.env().into()).unwrap()
+    };
+    curve Unpredictable PublishDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+    curve Predictable PublishDhtOpsWorkspaceFixturator::new(Empty).next().unwrap();
+);
+
+fixturator!(
+    SysValidationWorkspace;
+    curve Empty {
+        // See CallZomeWorkspaceLock for why grabbing an environment is ok
+        let env = holochain_state::test_utils::test_cell_env();
+        SysValidationWorkspace::new(env.env().into()).unwrap()
+    };
+    curve Unpredictable SysValidationWorkspaceFixturator::new(Empty).next().unwrap();
+    curve Predictable SysValidationWorkspaceFixturator::new(Empty).next().unwrap();
+);
+
+fixturator!(
+    AppQuota;
+    constructor fn default();
+);
+
+fixturator!(
+    CancelToken;
+    constructor fn new();
+);
+
+fixturator!(
+    CapGrantIndex;
+    constructor fn default();
+);
+
+fixturator!(
+    CallProvenance;
+    constructor fn new(AgentPubKey);
+);
+
+fixturator!(
+    Clock;
+    constructor fn system();
+);
+
+fixturator!(
+    Entropy;
+    constructor fn system();
+);
+
 fixturator!(
     ZomeCallHostAccess;
-    constructor fn new(CallZomeWorkspaceLock, KeystoreSender, HolochainP2pCell);
+    constructor fn new(CallZomeWorkspaceLock, KeystoreSender, HolochainP2pCell, AppQuota, CancelToken, CapGrantIndex, CallProvenance, Clock, Entropy);
 );
 
 fixturator!(
@@ -293,6 +405,16 @@ fixturator!(
     constructor fn new();
 );
 
+fixturator!(
+    LinkTypesInvocation;
+    constructor fn new();
+);
+
+fixturator!(
+    LinkTypesHostAccess;
+    constructor fn new();
+);
+
 fixturator!(
     InitInvocation;
     constructor fn new(DnaDef);
@@ -328,6 +450,16 @@ fixturator!(
     constructor fn new(ZomeName, Entry);
 );
 
+fixturator!(
+    IndexOpInvocation;
+    constructor fn new(ZomeName, Element);
+);
+
+fixturator!(
+    IndexOpHostAccess;
+    constructor fn new();
+);
+
 fixturator!(
     ValidateCreateLinkInvocation;
     constructor fn new(ZomeName, CreateLink, Entry, Entry);
@@ -360,6 +492,8 @@ fixturator!(
         Validate(ValidateHostAccess)
         Init(InitHostAccess)
         EntryDefs(EntryDefsHostAccess)
+        LinkTypes(LinkTypesHostAccess)
+        IndexOp(IndexOpHostAccess)
         MigrateAgent(MigrateAgentHostAccess)
         ValidationPackage(ValidationPackageHostAccess)
         PostCommit(PostCommitHostAccess)
@@ -26,6 +26,7 @@ use holochain_zome_types::header::{Create, EntryType, Header};
 use std::{convert::TryInto, sync::Arc};
 use tempdir::TempDir;
 
+pub mod byzantine;
 #[cfg(test)]
 pub mod host_fn_api;
 
@@ -161,6 +162,7 @@ pub async fn setup_app(
         .config(ConductorConfig {
             admin_interfaces: Some(vec![AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port: 0 },
+                tokens: vec![],
             }]),
             ..Default::default()
         })
@@ -0,0 +1,359 @@
+//! A fake authority which can be told to lie.
+//!
+//! [ByzantineAuthority] serves elements and metadata over a test network like
+//! an honest authority would, except that its [Byzantine] behaviour corrupts
+//! every response in one particular way. Use it to check that whatever
+//! consumes authority responses rejects the bad data.
+
+use super::test_network;
+use ::fixt::prelude::*;
+use futures::future::{Either, FutureExt};
+use ghost_actor::GhostControlSender;
+use holo_hash::{
+    fixt::AgentPubKeyFixturator, hash_type::AnyDht, AgentPubKey, AnyDhtHash, HeaderHash,
+};
+use holochain_error_kind::ErrorKind;
+use holochain_p2p::{HolochainP2pCell, HolochainP2pRef};
+use holochain_types::{
+    element::{GetElementResponse, WireElement},
+    fixt::{AppEntry, EntryFixturator, SignatureFixturator},
+    header::WireDelete,
+    link::GetLinksResponse,
+    metadata::{MetadataSet, TimedHeaderHash},
+    Timestamp,
+};
+use holochain_zome_types::element::{Element, SignedHeaderHashed};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::TryInto;
+use tokio::{sync::oneshot, task::JoinHandle};
+use tracing::*;
+
+/// The ways a [ByzantineAuthority] can misbehave
+#[derive(Debug, Clone, PartialEq)]
+pub enum Byzantine {
+    /// Responds truthfully
+    Honest,
+    /// Swaps the entry of every element for a different one
+    WrongEntry,
+    /// Replaces the signature on every header with random bytes
+    ForgedSignature,
+    /// Responds with metadata as it was at this time, leaving out anything
+    /// that happened since
+    StaleMetadata(Timestamp),
+    /// Never reveals that anything has been deleted
+    WithholdDeletes,
+    /// Fails every get with an error of this kind
+    Failing(ErrorKind),
+}
+
+/// An authority on a test network, holding some elements and metadata
+pub struct ByzantineAuthority {
+    agent: AgentPubKey,
+    behaviour: Byzantine,
+    elements: BTreeMap<HeaderHash, Element>,
+    deletes: BTreeMap<HeaderHash, WireDelete>,
+    metadata: BTreeMap<AnyDhtHash, MetadataSet>,
+}
+
+/// Stops a running [ByzantineAuthority]
+pub struct ByzantineShutdown {
+    handle: JoinHandle<()>,
+    kill: oneshot::Sender<()>,
+    network: HolochainP2pRef,
+}
+
+impl ByzantineAuthority {
+    /// An authority holding nothing
+    pub fn new(behaviour: Byzantine) -> Self {
+        Self {
+            agent: fixt!(AgentPubKey),
+            behaviour,
+            elements: BTreeMap::new(),
+            deletes: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// The agent this authority responds as. It's also the agent of the
+    /// cell network returned by [ByzantineAuthority::run], as the test
+    /// network only has one agent.
+    pub fn agent(&self) -> &AgentPubKey {
+        &self.agent
+    }
+
+    /// Hold an element
+    pub fn with_element(mut self, element: Element) -> Self {
+        self.elements
+            .insert(element.header_address().clone(), element);
+        self
+    }
+
+    /// Hold a delete of the element at `deleted`.
+    /// Panics if `delete` isn't a Delete header.
+    pub fn with_delete(mut self, deleted: HeaderHash, delete: SignedHeaderHashed) -> Self {
+        self.deletes
+            .insert(deleted, delete.try_into().expect("Not a Delete header"));
+        self
+    }
+
+    /// Hold metadata on a basis
+    pub fn with_metadata(mut self, basis: AnyDhtHash, metadata: MetadataSet) -> Self {
+        self.metadata.insert(basis, metadata);
+        self
+    }
+
+    /// What this authority responds to a get of `hash`
+    pub fn get_response(&self, hash: &HeaderHash) -> GetElementResponse {
+        if let Byzantine::Failing(kind) = self.behaviour {
+            return GetElementResponse::Error(kind);
+        }
+        let element = self.elements.get(hash).cloned().map(|element| {
+            let deleted = match self.behaviour {
+                Byzantine::WithholdDeletes => None,
+                _ => self.deletes.get(hash).cloned(),
+            };
+            Box::new(WireElement::from_element(self.corrupt(element), deleted))
+        });
+        GetElementResponse::GetHeader(element)
+    }
+
+    /// What this authority responds to a get_meta of `basis`
+    pub fn get_meta_response(&self, basis: &AnyDhtHash) -> MetadataSet {
+        let mut metadata = self
+            .metadata
+            .get(basis)
+            .cloned()
+            .unwrap_or_else(|| MetadataSet {
+                headers: BTreeSet::new(),
+                invalid_headers: BTreeSet::new(),
+                deletes: BTreeSet::new(),
+                updates: BTreeSet::new(),
+                entry_dht_status: None,
+            });
+        match &self.behaviour {
+            Byzantine::StaleMetadata(as_of) => {
+                let stale = |set: &mut BTreeSet<TimedHeaderHash>| {
+                    set.retain(|h| h.timestamp <= *as_of);
+                };
+                stale(&mut metadata.headers);
+                stale(&mut metadata.invalid_headers);
+                stale(&mut metadata.deletes);
+                stale(&mut metadata.updates);
+            }
+            Byzantine::WithholdDeletes => metadata.deletes.clear(),
+            _ => (),
+        }
+        metadata
+    }
+
+    fn corrupt(&self, element: Element) -> Element {
+        let (shh, entry) = element.into_inner();
+        let entry = entry.into_option();
+        match self.behaviour {
+            Byzantine::WrongEntry => {
+                let wrong = EntryFixturator::new(AppEntry)
+                    .find(|e| Some(e) != entry.as_ref())
+                    .expect("Fixturator is infinite");
+                Element::new(shh, Some(wrong))
+            }
+            Byzantine::ForgedSignature => {
+                let (header, signature) = shh.into_header_and_signature();
+                let forged = SignatureFixturator::new(Unpredictable)
+                    .find(|s| *s != signature)
+                    .expect("Fixturator is infinite");
+                Element::new(SignedHeaderHashed::with_presigned(header, forged), entry)
+            }
+            _ => Element::new(shh, entry),
+        }
+    }
+
+    /// Join a test network and answer Get and GetMeta requests.
+    /// Returns the network of the cell making the requests.
+    pub async fn run(self) -> (HolochainP2pCell, ByzantineShutdown) {
+        let (network, mut recv, cell_network) = test_network(None, Some(self.agent.clone())).await;
+        let (kill, killed) = oneshot::channel();
+
+        let handle = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            let mut killed = killed.into_stream();
+            while let Either::Right((Some(evt), _)) =
+                futures::future::select(killed.next(), recv.next()).await
+            {
+                use holochain_p2p::event::HolochainP2pEvent::*;
+                debug!(behaviour = ?self.behaviour, ?evt);
+                match evt {
+                    Get {
+                        dht_hash, respond, ..
+                    } => {
+                        let response = match dht_hash.hash_type() {
+                            AnyDht::Header => self.get_response(&dht_hash.into()),
+                            AnyDht::Entry => GetElementResponse::GetEntryFull(None),
+                        };
+                        let response = response.try_into().unwrap();
+                        respond.respond(Ok(async move { Ok(response) }.boxed().into()));
+                    }
+                    GetMeta {
+                        dht_hash, respond, ..
+                    } => {
+                        let response = self.get_meta_response(&dht_hash).try_into().unwrap();
+                        respond.respond(Ok(async move { Ok(response) }.boxed().into()));
+                    }
+                    GetLinks { respond, .. } => {
+                        // This authority holds no links
+                        let response = GetLinksResponse {
+                            link_adds: vec![],
+                            link_removes: vec![],
+                            continuation: None,
+                        };
+                        respond.respond(Ok(async move { Ok(response) }.boxed().into()));
+                    }
+                    _ => (),
+                }
+            }
+        });
+        (
+            cell_network,
+            ByzantineShutdown {
+                handle,
+                kill,
+                network,
+            },
+        )
+    }
+}
+
+impl ByzantineShutdown {
+    /// Stop answering requests and shut down the network
+    pub async fn clean(self) {
+        let Self {
+            handle,
+            kill,
+            network,
+        } = self;
+        kill.send(()).ok();
+        // Give the network some time to clean up but don't block tests if it doesn't
+        tokio::time::timeout(
+            std::time::Duration::from_secs(2),
+            network.ghost_actor_shutdown(),
+        )
+        .await
+        .ok();
+        tokio::time::timeout(std::time::Duration::from_secs(2), handle)
+            .await
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::sys_validate::{check_entry_hash, verify_header_signature},
+        test_utils::fake_unique_element,
+    };
+    use holo_hash::fixt::HeaderHashFixturator;
+    use holochain_p2p::{actor::GetOptions, HolochainP2pCellT};
+    use holochain_state::test_utils::test_keystore;
+    use holochain_types::{observability, test_utils::fake_agent_pubkey_1};
+    use holochain_zome_types::entry_def::EntryVisibility;
+
+    async fn element() -> Element {
+        let keystore = test_keystore();
+        let (shh, entry) =
+            fake_unique_element(&keystore, fake_agent_pubkey_1(), EntryVisibility::Public)
+                .await
+                .unwrap();
+        Element::new(shh, Some(entry.into_content()))
+    }
+
+    fn timed(timestamp: i64) -> TimedHeaderHash {
+        TimedHeaderHash {
+            timestamp: Timestamp(timestamp, 0),
+            header_hash: fixt!(HeaderHash),
+        }
+    }
+
+    async fn served(behaviour: Byzantine, element: &Element) -> Element {
+        let response = ByzantineAuthority::new(behaviour)
+            .with_element(element.clone())
+            .get_response(element.header_address());
+        match response {
+            GetElementResponse::GetHeader(Some(we)) => we.into_element_and_delete().await.0,
+            r => panic!("unexpected response {:?}", r),
+        }
+    }
+
+    async fn passes_checks(element: &Element) -> bool {
+        let entry_ok = match (element.header().entry_data(), element.entry().as_option()) {
+            (Some((hash, _)), Some(entry)) => check_entry_hash(hash, entry).await.is_ok(),
+            _ => true,
+        };
+        entry_ok
+            && verify_header_signature(element.signature(), element.header())
+                .await
+                .is_ok()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn lies_are_rejected_by_validation() {
+        let element = element().await;
+        assert!(passes_checks(&served(Byzantine::Honest, &element).await).await);
+        assert!(!passes_checks(&served(Byzantine::WrongEntry, &element).await).await);
+        assert!(!passes_checks(&served(Byzantine::ForgedSignature, &element).await).await);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn metadata_lies() {
+        let basis: AnyDhtHash = fixt!(HeaderHash).into();
+        let metadata = MetadataSet {
+            headers: vec![timed(1), timed(3)].into_iter().collect(),
+            invalid_headers: BTreeSet::new(),
+            deletes: vec![timed(2), timed(4)].into_iter().collect(),
+            updates: BTreeSet::new(),
+            entry_dht_status: None,
+        };
+        let authority = |behaviour| {
+            ByzantineAuthority::new(behaviour).with_metadata(basis.clone(), metadata.clone())
+        };
+
+        assert_eq!(
+            authority(Byzantine::Honest).get_meta_response(&basis),
+            metadata
+        );
+
+        let stale = authority(Byzantine::StaleMetadata(Timestamp(2, 0))).get_meta_response(&basis);
+        assert_eq!(stale.headers.len(), 1);
+        assert_eq!(stale.deletes.len(), 1);
+
+        let withheld = authority(Byzantine::WithholdDeletes).get_meta_response(&basis);
+        assert!(withheld.deletes.is_empty());
+        assert_eq!(withheld.headers, metadata.headers);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn serves_lies_over_the_network() {
+        observability::test_run().ok();
+        let element = element().await;
+        let hash = element.header_address().clone();
+        let (mut cell_network, shutdown) = ByzantineAuthority::new(Byzantine::ForgedSignature)
+            .with_element(element.clone())
+            .run()
+            .await;
+
+        let responses = cell_network
+            .get(hash.clone().into(), GetOptions::default())
+            .await
+            .unwrap();
+        let served = match responses.into_iter().next() {
+            Some((_, GetElementResponse::GetHeader(Some(we)))) => {
+                we.into_element_and_delete().await.0
+            }
+            r => panic!("unexpected response {:?}", r),
+        };
+        assert_eq!(served.header_address(), &hash);
+        assert_ne!(served.signature(), element.signature());
+        assert!(!passes_checks(&served).await);
+
+        shutdown.clean().await;
+    }
+}
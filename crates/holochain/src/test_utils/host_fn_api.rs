@@ -1,8 +1,11 @@
 use crate::{
     conductor::ConductorHandle,
     core::{
+        cancel::CancelToken,
+        clock::{Clock, Entropy},
+        quota::AppQuota,
         ribosome::{host_fn, wasm_ribosome::WasmRibosome, CallContext, ZomeCallHostAccess},
-        state::{metadata::LinkMetaKey, workspace::Workspace},
+        state::{metadata::LinkMetaKey, source_chain::CapGrantIndex, workspace::Workspace},
         workflow::{CallZomeWorkspace, CallZomeWorkspaceLock},
     },
 };
@@ -24,6 +27,7 @@ use holochain_zome_types::{
     header::*,
     link::{Link, LinkTag},
     metadata::Details,
+    provenance::CallProvenance,
     zome::ZomeName,
     CreateInput, CreateLinkInput, DeleteInput, DeleteLinkInput, GetDetailsInput, GetInput,
     GetLinksInput, UpdateInput,
@@ -57,6 +61,7 @@ pub struct CallData {
     pub zome_name: ZomeName,
     pub network: HolochainP2pCell,
     pub keystore: KeystoreSender,
+    pub call_provenance: CallProvenance,
 }
 
 impl CallData {
@@ -73,11 +78,13 @@ impl CallData {
 
         let zome_name = dna_file.dna().zomes.get(0).unwrap().0.clone();
         let ribosome = WasmRibosome::new(dna_file.clone());
+        let call_provenance = CallProvenance::new(cell_id.agent_pubkey().clone());
         let call_data = CallData {
             ribosome,
             zome_name,
             network,
             keystore,
+            call_provenance,
         };
         (env, call_data)
     }
@@ -94,6 +101,7 @@ pub async fn commit_entry<'env, E: Into<entry_def::EntryDefId>>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
@@ -102,7 +110,17 @@ pub async fn commit_entry<'env, E: Into<entry_def::EntryDefId>>(
     let input = CreateInput::new((entry_def_id.into(), entry));
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -129,6 +147,7 @@ pub async fn delete_entry<'env>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
@@ -137,7 +156,17 @@ pub async fn delete_entry<'env>(
     let input = DeleteInput::new(hash);
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -171,15 +200,26 @@ pub async fn update_entry<'env, E: Into<entry_def::EntryDefId>>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
     let workspace_lock = CallZomeWorkspaceLock::new(workspace);
 
-    let input = UpdateInput::new((entry_def_id.into(), entry, original_header_hash));
+    let input = UpdateInput::new((entry_def_id.into(), entry, original_header_hash.into()));
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -207,17 +247,28 @@ pub async fn get(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
     let workspace_lock = CallZomeWorkspaceLock::new(workspace);
 
     let input = GetInput::new((
         entry_hash.clone().into(),
-        holochain_zome_types::entry::GetOptions,
+        holochain_zome_types::entry::GetOptions::default(),
     ));
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -237,6 +288,7 @@ pub async fn get_details<'env>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
@@ -244,11 +296,21 @@ pub async fn get_details<'env>(
 
     let input = GetDetailsInput::new((
         entry_hash.clone().into(),
-        holochain_zome_types::entry::GetOptions,
+        holochain_zome_types::entry::GetOptions::default(),
     ));
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -269,15 +331,26 @@ pub async fn create_link<'env>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
     let workspace_lock = CallZomeWorkspaceLock::new(workspace);
 
-    let input = CreateLinkInput::new((base.clone(), target.clone(), link_tag));
+    let input = CreateLinkInput::new((base.clone(), target.clone(), None, link_tag));
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -304,6 +377,7 @@ pub async fn delete_link<'env>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
@@ -312,7 +386,17 @@ pub async fn delete_link<'env>(
     let input = DeleteLinkInput::new(link_add_hash);
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -341,6 +425,7 @@ pub async fn get_links<'env>(
         keystore,
         ribosome,
         zome_name,
+        call_provenance,
     } = call_data;
 
     let workspace = CallZomeWorkspace::new(env.clone().into()).unwrap();
@@ -349,7 +434,17 @@ pub async fn get_links<'env>(
     let input = GetLinksInput::new((base.clone(), link_tag));
 
     let output = {
-        let host_access = ZomeCallHostAccess::new(workspace_lock.clone(), keystore, network);
+        let host_access = ZomeCallHostAccess::new(
+            workspace_lock.clone(),
+            keystore,
+            network,
+            AppQuota::default(),
+            CancelToken::new(),
+            CapGrantIndex::default(),
+            call_provenance,
+            Clock::system(),
+            Entropy::system(),
+        );
         let call_context = CallContext::new(zome_name, host_access.into());
         let ribosome = Arc::new(ribosome);
         let call_context = Arc::new(call_context);
@@ -82,10 +82,10 @@ async fn get_links() -> SourceChainResult<()> {
 
     let base = jimbo_entry.as_hash().clone();
     source_chain
-        .put_raw(jimbo_header, Some(jimbo_entry.as_content().clone()))
+        .put_raw_unchecked(jimbo_header, Some(jimbo_entry.as_content().clone()))
         .await?;
     source_chain
-        .put_raw(jessy_header, Some(jessy_entry.as_content().clone()))
+        .put_raw_unchecked(jessy_header, Some(jessy_entry.as_content().clone()))
         .await?;
 
     let (_n, _r, cell_network) = test_network(None, None).await;
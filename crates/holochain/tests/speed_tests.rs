@@ -198,7 +198,10 @@ async fn speed_test(n: Option<usize>) -> TestEnvironment {
 
     // Setup websocket handle and app interface
     let (mut client, _) = websocket_client(&handle).await.unwrap();
-    let request = AdminRequest::AttachAppInterface { port: None };
+    let request = AdminRequest::AttachAppInterface {
+        port: None,
+        app_ids: None,
+    };
     let response = client.request(request);
     let response = response.await.unwrap();
     let app_port = match response {
@@ -336,6 +339,7 @@ pub async fn setup_app(
         .config(ConductorConfig {
             admin_interfaces: Some(vec![AdminInterfaceConfig {
                 driver: InterfaceDriver::Websocket { port: 0 },
+                tokens: vec![],
             }]),
             ..Default::default()
         })
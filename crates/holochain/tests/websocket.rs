@@ -66,6 +66,7 @@ fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
     ConductorConfig {
         admin_interfaces: Some(vec![AdminInterfaceConfig {
             driver: InterfaceDriver::Websocket { port },
+            tokens: vec![],
         }]),
         environment_path: environment_path.into(),
         network: None,
@@ -77,6 +78,19 @@ fn create_config(port: u16, environment_path: PathBuf) -> ConductorConfig {
             passphrase: "password".into(),
         }),
         use_dangerous_test_keystore: true,
+        startup_integrity_check: false,
+        logger: None,
+        tokio_runtime: None,
+        health_check: None,
+        signal_webhooks: vec![],
+        unresolved_dependencies: Default::default(),
+        app_quotas: Default::default(),
+        wasm_io_limits: Default::default(),
+        zome_call_retries: None,
+        max_concurrent_genesis: None,
+        max_concurrent_zome_calls: None,
+        zome_call_weights: Default::default(),
+        autonomic: Default::default(),
     }
 }
 
@@ -154,6 +168,7 @@ async fn call_admin() {
         path: fake_dna_path,
         nick: "nick".into(),
         properties: Some(properties.clone()),
+        uuid: None,
         membrane_proof: None,
     };
     let agent_key = fake_agent_pubkey_1();
@@ -182,7 +197,10 @@ async fn call_admin() {
     assert_ne!(&original_dna_hash, dna.dna_hash());
 
     let expects = vec![dna.dna_hash().clone()];
-    assert_matches!(response, AdminResponse::ListDnas(a) if a == expects);
+    assert_matches!(
+        response,
+        AdminResponse::ListDnas(a) if a.iter().map(|dna| &dna.dna_hash).eq(expects.iter())
+    );
 
     holochain.kill().expect("Failed to kill holochain");
 }
@@ -232,7 +250,10 @@ pub async fn call_foo_fn(app_port: u16, original_dna_hash: DnaHash, holochain: &
 }
 
 pub async fn attach_app_interface(client: &mut WebsocketSender, holochain: &mut Child) -> u16 {
-    let request = AdminRequest::AttachAppInterface { port: None };
+    let request = AdminRequest::AttachAppInterface {
+        port: None,
+        app_ids: None,
+    };
     let response = client.request(request);
     let response = check_timeout(holochain, response, 1000).await;
     match response {
@@ -311,7 +332,10 @@ async fn call_zome() {
     let response = check_timeout(&mut holochain, response, 1000).await;
 
     let expects = vec![original_dna_hash.clone()];
-    assert_matches!(response, AdminResponse::ListDnas(a) if a == expects);
+    assert_matches!(
+        response,
+        AdminResponse::ListDnas(a) if a.iter().map(|dna| &dna.dna_hash).eq(expects.iter())
+    );
 
     // Activate cells
     let request = AdminRequest::ActivateApp {
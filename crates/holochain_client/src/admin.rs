@@ -0,0 +1,496 @@
+//! A client for a conductor's admin interface
+
+use crate::{
+    connection::{Connection, ReconnectPolicy},
+    error::{ClientError, ClientResult},
+};
+use holo_hash::{AgentPubKey, AnyDhtHash, DnaHash};
+use holochain::conductor::{
+    api::{AdminRequest, AdminResponse, GossipRound},
+    config::AdminInterfaceConfig,
+};
+use holochain::core::{
+    dht_metrics::DhtSizeEstimate,
+    dht_snapshot::SnapshotImportReport,
+    queue_consumer::{QueueInfo, QueueTrigger},
+    redundancy::RedundancyReport,
+    state::limbo_dump::LimboOpInfo,
+};
+use holochain_types::{
+    app::{AppId, AppInfo, AppStatus, InstallAppBundlePayload, InstallAppPayload, InstalledApp},
+    cell::{CellId, CellIdInfo},
+    dna::DnaInfo,
+};
+use holochain_websocket::WebsocketConfig;
+use std::{path::PathBuf, sync::Arc};
+use url2::Url2;
+
+/// A connection to a conductor's admin interface
+pub struct AdminWebsocket {
+    connection: Connection,
+    token: Option<String>,
+}
+
+impl AdminWebsocket {
+    /// Connect to the admin interface at this url,
+    /// e.g. `ws://localhost:1234`
+    pub async fn connect(url: Url2) -> ClientResult<Self> {
+        Self::connect_with(url, Default::default(), Default::default()).await
+    }
+
+    /// Connect with a particular websocket config and reconnect policy
+    pub async fn connect_with(
+        url: Url2,
+        config: Arc<WebsocketConfig>,
+        policy: ReconnectPolicy,
+    ) -> ClientResult<Self> {
+        Ok(Self {
+            connection: Connection::connect(url, config, policy).await?,
+            token: None,
+        })
+    }
+
+    /// Send every request with this token, for interfaces which need one
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Make a request, turning errors from the conductor into [ClientError]s
+    async fn send(&mut self, request: AdminRequest) -> ClientResult<AdminResponse> {
+        let request = match &self.token {
+            Some(token) => AdminRequest::WithToken {
+                token: token.clone(),
+                request: Box::new(request),
+            },
+            None => request,
+        };
+        match self.connection.request(request).await? {
+            AdminResponse::Error(e) => Err(ClientError::Conductor(e)),
+            response => Ok(response),
+        }
+    }
+
+    /// Set up more admin interfaces
+    pub async fn add_admin_interfaces(
+        &mut self,
+        configs: Vec<AdminInterfaceConfig>,
+    ) -> ClientResult<()> {
+        match self.send(AdminRequest::AddAdminInterfaces(configs)).await? {
+            AdminResponse::AdminInterfacesAdded(()) => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Install an app from a list of Dna paths
+    pub async fn install_app(&mut self, payload: InstallAppPayload) -> ClientResult<InstalledApp> {
+        match self
+            .send(AdminRequest::InstallApp(Box::new(payload)))
+            .await?
+        {
+            AdminResponse::AppInstalled(app) => Ok(app),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Install an app from an app bundle
+    pub async fn install_app_bundle(
+        &mut self,
+        payload: InstallAppBundlePayload,
+    ) -> ClientResult<InstalledApp> {
+        match self
+            .send(AdminRequest::InstallAppBundle(Box::new(payload)))
+            .await?
+        {
+            AdminResponse::AppInstalled(app) => Ok(app),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// List all installed Dnas
+    pub async fn list_dnas(&mut self) -> ClientResult<Vec<DnaInfo>> {
+        match self.send(AdminRequest::ListDnas).await? {
+            AdminResponse::ListDnas(dnas) => Ok(dnas),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Generate a new agent key in the conductor's keystore
+    pub async fn generate_agent_pub_key(&mut self) -> ClientResult<AgentPubKey> {
+        match self.send(AdminRequest::GenerateAgentPubKey).await? {
+            AdminResponse::GenerateAgentPubKey(agent) => Ok(agent),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// List all the cells running in the conductor
+    pub async fn list_cell_ids(&mut self) -> ClientResult<Vec<CellIdInfo>> {
+        match self.send(AdminRequest::ListCellIds).await? {
+            AdminResponse::ListCellIds(cell_ids) => Ok(cell_ids),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// List the installed apps, optionally only those with the given status
+    pub async fn list_apps(
+        &mut self,
+        status_filter: Option<AppStatus>,
+    ) -> ClientResult<Vec<AppInfo>> {
+        match self.send(AdminRequest::ListApps { status_filter }).await? {
+            AdminResponse::AppsListed(apps) => Ok(apps),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Activate an installed app
+    pub async fn activate_app(&mut self, app_id: AppId) -> ClientResult<()> {
+        match self.send(AdminRequest::ActivateApp { app_id }).await? {
+            AdminResponse::AppActivated => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Deactivate an active app
+    pub async fn deactivate_app(&mut self, app_id: AppId) -> ClientResult<()> {
+        match self.send(AdminRequest::DeactivateApp { app_id }).await? {
+            AdminResponse::AppDeactivated => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Attach an app interface, returning the port it's listening on.
+    /// Use `None` to let the OS choose a free port.
+    pub async fn attach_app_interface(&mut self, port: Option<u16>) -> ClientResult<u16> {
+        match self.send(AdminRequest::AttachAppInterface { port }).await? {
+            AdminResponse::AppInterfaceAttached { port } => Ok(port),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Attach an app interface which serves JSON over HTTP, returning the
+    /// port it's listening on. Use `None` to let the OS choose a free port.
+    pub async fn attach_http_app_interface(&mut self, port: Option<u16>) -> ClientResult<u16> {
+        match self
+            .send(AdminRequest::AttachHttpAppInterface { port })
+            .await?
+        {
+            AdminResponse::AppInterfaceAttached { port } => Ok(port),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Dump the state of a cell as json
+    pub async fn dump_state(&mut self, cell_id: CellId) -> ClientResult<String> {
+        match self
+            .send(AdminRequest::DumpState {
+                cell_id: Box::new(cell_id),
+            })
+            .await?
+        {
+            AdminResponse::JsonState(state) => Ok(state),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// List the ops a cell is holding in validation or integration limbo
+    pub async fn dump_limbo(&mut self, cell_id: CellId) -> ClientResult<Vec<LimboOpInfo>> {
+        match self
+            .send(AdminRequest::DumpLimbo {
+                cell_id: Box::new(cell_id),
+            })
+            .await?
+        {
+            AdminResponse::LimboDumped(ops) => Ok(ops),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Start recording a cell's network events to a file,
+    /// or stop recording with `None`
+    pub async fn record_network_events(
+        &mut self,
+        cell_id: CellId,
+        path: Option<PathBuf>,
+    ) -> ClientResult<()> {
+        match self
+            .send(AdminRequest::RecordNetworkEvents {
+                cell_id: Box::new(cell_id),
+                path,
+            })
+            .await?
+        {
+            AdminResponse::NetworkEventsRecording => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Replay a recording of network events to a cell,
+    /// returning how many were replayed
+    pub async fn replay_network_events(
+        &mut self,
+        cell_id: CellId,
+        path: PathBuf,
+    ) -> ClientResult<usize> {
+        match self
+            .send(AdminRequest::ReplayNetworkEvents {
+                cell_id: Box::new(cell_id),
+                path,
+            })
+            .await?
+        {
+            AdminResponse::NetworkEventsReplayed(count) => Ok(count),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Export every op a cell has integrated as valid to a snapshot file,
+    /// returning how many were written
+    pub async fn export_dht_snapshot(
+        &mut self,
+        cell_id: CellId,
+        path: PathBuf,
+    ) -> ClientResult<usize> {
+        match self
+            .send(AdminRequest::ExportDhtSnapshot {
+                cell_id: Box::new(cell_id),
+                path,
+            })
+            .await?
+        {
+            AdminResponse::DhtSnapshotExported(count) => Ok(count),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Import a snapshot file into a cell, queueing its ops for validation
+    pub async fn import_dht_snapshot(
+        &mut self,
+        cell_id: CellId,
+        path: PathBuf,
+    ) -> ClientResult<SnapshotImportReport> {
+        match self
+            .send(AdminRequest::ImportDhtSnapshot {
+                cell_id: Box::new(cell_id),
+                path,
+            })
+            .await?
+        {
+            AdminResponse::DhtSnapshotImported(report) => Ok(report),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// The reputation of every peer reported on a Dna's network
+    pub async fn peer_reputations(
+        &mut self,
+        dna_hash: DnaHash,
+    ) -> ClientResult<Vec<(AgentPubKey, f64)>> {
+        match self
+            .send(AdminRequest::PeerReputations { dna_hash })
+            .await?
+        {
+            AdminResponse::PeerReputations(scores) => Ok(scores),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// The most recent gossip rounds with each peer on a Dna's network
+    pub async fn gossip_rounds(
+        &mut self,
+        dna_hash: DnaHash,
+    ) -> ClientResult<Vec<(AgentPubKey, Vec<GossipRound>)>> {
+        match self.send(AdminRequest::GossipRounds { dna_hash }).await? {
+            AdminResponse::GossipRounds(rounds) => Ok(rounds),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Estimate the size and redundancy of a cell's DHT
+    /// by sampling authorities around `samples` random locations
+    pub async fn estimate_dht_size(
+        &mut self,
+        cell_id: CellId,
+        samples: u8,
+    ) -> ClientResult<DhtSizeEstimate> {
+        match self
+            .send(AdminRequest::EstimateDhtSize {
+                cell_id: Box::new(cell_id),
+                samples,
+            })
+            .await?
+        {
+            AdminResponse::DhtSizeEstimated(estimate) => Ok(estimate),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// The state of each of a cell's workflow queues
+    pub async fn list_queue_triggers(&mut self, cell_id: CellId) -> ClientResult<Vec<QueueInfo>> {
+        match self
+            .send(AdminRequest::ListQueueTriggers {
+                cell_id: Box::new(cell_id),
+            })
+            .await?
+        {
+            AdminResponse::QueueTriggersListed(queues) => Ok(queues),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Fire one of a cell's workflow triggers
+    pub async fn trigger_queue(
+        &mut self,
+        cell_id: CellId,
+        queue: QueueTrigger,
+    ) -> ClientResult<()> {
+        match self
+            .send(AdminRequest::TriggerQueue {
+                cell_id: Box::new(cell_id),
+                queue,
+            })
+            .await?
+        {
+            AdminResponse::QueueTriggered => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Check how well replicated a sample of a cell's authored ops are,
+    /// publishing again any held by too few authorities
+    pub async fn check_redundancy(&mut self, cell_id: CellId) -> ClientResult<RedundancyReport> {
+        match self
+            .send(AdminRequest::CheckRedundancy {
+                cell_id: Box::new(cell_id),
+            })
+            .await?
+        {
+            AdminResponse::RedundancyChecked(report) => Ok(report),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Pin hashes on a cell so their data is always held locally
+    pub async fn pin_hashes(
+        &mut self,
+        cell_id: CellId,
+        hashes: Vec<AnyDhtHash>,
+    ) -> ClientResult<()> {
+        match self
+            .send(AdminRequest::PinHashes {
+                cell_id: Box::new(cell_id),
+                hashes,
+            })
+            .await?
+        {
+            AdminResponse::HashesPinned => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Unpin hashes on a cell
+    pub async fn unpin_hashes(
+        &mut self,
+        cell_id: CellId,
+        hashes: Vec<AnyDhtHash>,
+    ) -> ClientResult<()> {
+        match self
+            .send(AdminRequest::UnpinHashes {
+                cell_id: Box::new(cell_id),
+                hashes,
+            })
+            .await?
+        {
+            AdminResponse::HashesUnpinned => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Every hash pinned on a cell
+    pub async fn list_pins(&mut self, cell_id: CellId) -> ClientResult<Vec<AnyDhtHash>> {
+        match self
+            .send(AdminRequest::ListPins {
+                cell_id: Box::new(cell_id),
+            })
+            .await?
+        {
+            AdminResponse::PinsListed(pins) => Ok(pins),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Give a locked keystore its passphrase, starting the conductor's Cells
+    pub async fn unlock_keystore(&mut self, passphrase: String) -> ClientResult<()> {
+        match self
+            .send(AdminRequest::UnlockKeystore { passphrase })
+            .await?
+        {
+            AdminResponse::KeystoreUnlocked => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Gracefully shut down the conductor, giving in-flight work until
+    /// the deadline to finish
+    pub async fn shutdown(&mut self, deadline_ms: Option<u64>) -> ClientResult<()> {
+        match self.send(AdminRequest::Shutdown { deadline_ms }).await? {
+            AdminResponse::ShuttingDown => Ok(()),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_websocket::{websocket_bind, WebsocketMessage};
+    use std::convert::TryInto;
+    use tokio::stream::StreamExt;
+    use url2::url2;
+
+    /// A fake admin interface which answers every request with the Dnas it
+    /// has, then hangs up
+    async fn fake_conductor(dnas: Vec<DnaInfo>) -> Url2 {
+        let mut listener = websocket_bind(
+            url2!("ws://127.0.0.1:0"),
+            Arc::new(WebsocketConfig::default()),
+        )
+        .await
+        .unwrap();
+        let url = listener.local_addr().clone();
+        tokio::task::spawn(async move {
+            while let Some(Ok((mut send, mut recv))) = listener.next().await {
+                if let Some(WebsocketMessage::Request(_, respond)) = recv.next().await {
+                    let response = AdminResponse::ListDnas(dnas.clone());
+                    respond(response.try_into().unwrap()).await.unwrap();
+                }
+                send.close(1000, "bye".into()).await.ok();
+            }
+        });
+        url
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn reconnects_after_the_conductor_hangs_up() {
+        let dna_hash = DnaHash::from_raw_bytes(vec![0xdb; 36]);
+        let dnas = vec![DnaInfo {
+            dna_hash_b64: dna_hash.to_string(),
+            dna_hash,
+            name: "fake".into(),
+            uuid: "".into(),
+            zome_names: vec![],
+        }];
+        let url = fake_conductor(dnas.clone()).await;
+        let mut client = AdminWebsocket::connect(url).await.unwrap();
+
+        assert_eq!(client.list_dnas().await.unwrap(), dnas);
+        // Give the close time to arrive
+        tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+        assert_eq!(client.list_dnas().await.unwrap(), dnas);
+
+        // Responses must match the request
+        assert!(matches!(
+            client.generate_agent_pub_key().await,
+            Err(ClientError::UnexpectedResponse(_))
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+//! A client for a conductor's app interface
+
+use crate::{
+    connection::{Connection, ReconnectPolicy},
+    error::{ClientError, ClientResult},
+};
+use holo_hash::AgentPubKey;
+use holochain::conductor::api::{AppRequest, AppResponse};
+use holochain::core::{
+    chain_audit::ChainAudit, offline::CellNetworkStatus, ribosome::ZomeCallInvocation,
+    signal::Signal, state::source_chain::ChainCapabilities,
+};
+use holochain_types::{
+    activity::ChainRange,
+    app::{AppId, InstalledApp},
+    cell::CellId,
+};
+use holochain_websocket::WebsocketConfig;
+use holochain_zome_types::ExternOutput;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+use url2::Url2;
+
+/// A connection to a conductor's app interface
+pub struct AppWebsocket {
+    connection: Connection,
+}
+
+impl AppWebsocket {
+    /// Connect to the app interface at this url,
+    /// e.g. `ws://localhost:1234`
+    pub async fn connect(url: Url2) -> ClientResult<Self> {
+        Self::connect_with(url, Default::default(), Default::default()).await
+    }
+
+    /// Connect with a particular websocket config and reconnect policy
+    pub async fn connect_with(
+        url: Url2,
+        config: Arc<WebsocketConfig>,
+        policy: ReconnectPolicy,
+    ) -> ClientResult<Self> {
+        Ok(Self {
+            connection: Connection::connect(url, config, policy).await?,
+        })
+    }
+
+    /// Signals emitted by the conductor's cells from now on.
+    /// Subscriptions carry on across reconnects, but signals sent while
+    /// disconnected are lost, and a subscriber that falls too far behind
+    /// misses the oldest signals.
+    pub fn signals(&self) -> broadcast::Receiver<Signal> {
+        self.connection.subscribe()
+    }
+
+    /// Make a request, turning errors from the conductor into [ClientError]s
+    async fn send(&mut self, request: AppRequest) -> ClientResult<AppResponse> {
+        match self.connection.request(request).await? {
+            AppResponse::Error(e) => Err(ClientError::Conductor(e)),
+            response => Ok(response),
+        }
+    }
+
+    /// Get info about an installed app
+    pub async fn app_info(&mut self, app_id: AppId) -> ClientResult<Option<InstalledApp>> {
+        match self.send(AppRequest::AppInfo { app_id }).await? {
+            AppResponse::AppInfo(app) => Ok(app),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Call a zome function
+    pub async fn call_zome(
+        &mut self,
+        invocation: ZomeCallInvocation,
+    ) -> ClientResult<ExternOutput> {
+        match self
+            .send(AppRequest::ZomeCallInvocation(Box::new(invocation)))
+            .await?
+        {
+            AppResponse::ZomeCallInvocation(output) => Ok(*output),
+            AppResponse::ZomeCallUnauthorized => Err(ClientError::ZomeCallUnauthorized),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Call a zome function, having the conductor cancel it
+    /// if it hasn't finished within `timeout`
+    pub async fn call_zome_with_timeout(
+        &mut self,
+        invocation: ZomeCallInvocation,
+        timeout: Duration,
+    ) -> ClientResult<ExternOutput> {
+        match self
+            .send(AppRequest::ZomeCallInvocationWithTimeout {
+                invocation: Box::new(invocation),
+                timeout_ms: timeout.as_millis() as u64,
+            })
+            .await?
+        {
+            AppResponse::ZomeCallInvocation(output) => Ok(*output),
+            AppResponse::ZomeCallUnauthorized => Err(ClientError::ZomeCallUnauthorized),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Verify a range of an agent's source chain using only its headers,
+    /// fetched through a cell's network
+    pub async fn audit_source_chain(
+        &mut self,
+        cell_id: CellId,
+        agent: AgentPubKey,
+        range: ChainRange,
+    ) -> ClientResult<ChainAudit> {
+        match self
+            .send(AppRequest::AuditSourceChain {
+                cell_id,
+                agent,
+                range,
+            })
+            .await?
+        {
+            AppResponse::SourceChainAudit(audit) => Ok(audit),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Verify an agent's whole source chain, only fetching the headers
+    /// after those the cell has already verified
+    pub async fn poll_agent_activity(
+        &mut self,
+        cell_id: CellId,
+        agent: AgentPubKey,
+    ) -> ClientResult<ChainAudit> {
+        match self
+            .send(AppRequest::PollAgentActivity { cell_id, agent })
+            .await?
+        {
+            AppResponse::SourceChainAudit(audit) => Ok(audit),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Put a cell in or out of offline mode
+    pub async fn set_cell_offline(
+        &mut self,
+        cell_id: CellId,
+        offline: bool,
+    ) -> ClientResult<CellNetworkStatus> {
+        match self
+            .send(AppRequest::SetCellOffline { cell_id, offline })
+            .await?
+        {
+            AppResponse::CellNetworkStatus(status) => Ok(status),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// Whether a cell is offline and how much it has waiting to publish
+    pub async fn cell_network_status(
+        &mut self,
+        cell_id: CellId,
+    ) -> ClientResult<CellNetworkStatus> {
+        match self.send(AppRequest::CellNetworkStatus { cell_id }).await? {
+            AppResponse::CellNetworkStatus(status) => Ok(status),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+
+    /// The live cap grants and claims on a cell's source chain
+    pub async fn list_capabilities(&mut self, cell_id: CellId) -> ClientResult<ChainCapabilities> {
+        match self.send(AppRequest::ListCapabilities { cell_id }).await? {
+            AppResponse::Capabilities(capabilities) => Ok(capabilities),
+            r => Err(ClientError::unexpected(r)),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! A websocket connection to a conductor interface, which reconnects when
+//! the conductor goes away and forwards any signals it sends.
+
+use crate::error::{ClientError, ClientResult};
+use holochain::core::signal::Signal;
+use holochain_serialized_bytes::prelude::*;
+use holochain_websocket::{websocket_connect, WebsocketConfig, WebsocketMessage, WebsocketSender};
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{stream::StreamExt, sync::broadcast};
+use tracing::*;
+use url2::Url2;
+
+/// Number of signals buffered for each subscriber before the oldest are dropped
+const SIGNAL_BUFFER: usize = 1000;
+
+/// How to reconnect when the connection to the conductor drops
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// How many times to try reconnecting before giving up.
+    /// Zero never reconnects.
+    pub attempts: usize,
+    /// How long to wait before the first attempt.
+    /// Each attempt after that waits twice as long as the last.
+    pub initial_delay: Duration,
+    /// The longest to wait between attempts
+    pub max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// How long to wait before this attempt, counting from zero
+    fn delay(&self, attempt: usize) -> Duration {
+        self.initial_delay
+            .checked_mul(2u32.saturating_pow(attempt as u32))
+            .map(|d| d.min(self.max_delay))
+            .unwrap_or(self.max_delay)
+    }
+}
+
+pub(crate) struct Connection {
+    url: Url2,
+    config: Arc<WebsocketConfig>,
+    policy: ReconnectPolicy,
+    sender: WebsocketSender,
+    /// Cleared when this connection's websocket closes
+    connected: Arc<AtomicBool>,
+    signals: broadcast::Sender<Signal>,
+}
+
+impl Connection {
+    pub async fn connect(
+        url: Url2,
+        config: Arc<WebsocketConfig>,
+        policy: ReconnectPolicy,
+    ) -> ClientResult<Self> {
+        let (signals, _) = broadcast::channel(SIGNAL_BUFFER);
+        let (sender, connected) = open(url.clone(), config.clone(), signals.clone()).await?;
+        Ok(Self {
+            url,
+            config,
+            policy,
+            sender,
+            connected,
+            signals,
+        })
+    }
+
+    /// Signals sent by the conductor from now on,
+    /// including after any reconnects
+    pub fn subscribe(&self) -> broadcast::Receiver<Signal> {
+        self.signals.subscribe()
+    }
+
+    /// Make a request, reconnecting first if the connection has dropped.
+    /// Requests which fail aren't retried, as the conductor may have
+    /// acted on them before the connection dropped.
+    pub async fn request<Req, Res>(&mut self, request: Req) -> ClientResult<Res>
+    where
+        Req: 'static + TryInto<SerializedBytes> + Send + std::fmt::Debug,
+        <Req as TryInto<SerializedBytes>>::Error: 'static + std::error::Error + Send + Sync,
+        Res: 'static + TryFrom<SerializedBytes> + Send,
+        <Res as TryFrom<SerializedBytes>>::Error: 'static + std::error::Error + Send + Sync,
+    {
+        if !self.connected.load(Ordering::SeqCst) {
+            self.reconnect().await?;
+        }
+        match self.sender.request(request).await {
+            Ok(response) => Ok(response),
+            Err(e) => {
+                self.connected.store(false, Ordering::SeqCst);
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn reconnect(&mut self) -> ClientResult<()> {
+        for attempt in 0..self.policy.attempts {
+            tokio::time::delay_for(self.policy.delay(attempt)).await;
+            match open(self.url.clone(), self.config.clone(), self.signals.clone()).await {
+                Ok((sender, connected)) => {
+                    debug!(url = %self.url, attempt, "Reconnected to the conductor");
+                    self.sender = sender;
+                    self.connected = connected;
+                    return Ok(());
+                }
+                Err(e) => warn!(url = %self.url, attempt, ?e, "Failed to reconnect"),
+            }
+        }
+        Err(ClientError::ReconnectFailed(self.policy.attempts))
+    }
+}
+
+/// Open a websocket, forwarding its signals until it closes
+async fn open(
+    url: Url2,
+    config: Arc<WebsocketConfig>,
+    signals: broadcast::Sender<Signal>,
+) -> ClientResult<(WebsocketSender, Arc<AtomicBool>)> {
+    let (sender, mut receiver) = websocket_connect(url, config).await?;
+    let connected = Arc::new(AtomicBool::new(true));
+    let still_connected = connected.clone();
+    tokio::task::spawn(async move {
+        while let Some(msg) = receiver.next().await {
+            match msg {
+                WebsocketMessage::Signal(bytes) => match Signal::try_from(bytes) {
+                    // No subscribers is fine
+                    Ok(signal) => {
+                        signals.send(signal).ok();
+                    }
+                    Err(e) => warn!(?e, "Failed to decode a signal from the conductor"),
+                },
+                WebsocketMessage::Request(..) => {
+                    warn!("Ignoring an unexpected request from the conductor")
+                }
+                WebsocketMessage::Close(close) => {
+                    debug!(?close, "The conductor closed the connection");
+                    break;
+                }
+            }
+        }
+        still_connected.store(false, Ordering::SeqCst);
+    });
+    Ok((sender, connected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delays_double_up_to_the_max() {
+        let policy = ReconnectPolicy {
+            attempts: 10,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+        let delays: Vec<_> = (0..6).map(|a| policy.delay(a).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.delay(usize::MAX), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,37 @@
+//! Errors which can occur while talking to a conductor
+
+use holochain::conductor::api::error::ExternalApiWireError;
+
+/// Errors which can occur while talking to a conductor
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// The websocket couldn't be opened, or failed while in use
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The conductor couldn't be reached again after the connection dropped
+    #[error("Failed to reconnect to the conductor after {0} attempts")]
+    ReconnectFailed(usize),
+
+    /// The conductor handled the request, but returned an error
+    #[error("The conductor returned an error: {0:?}")]
+    Conductor(ExternalApiWireError),
+
+    /// The conductor refused to make a zome call
+    #[error("The zome call was unauthorized")]
+    ZomeCallUnauthorized,
+
+    /// The conductor responded with the wrong kind of response for the request
+    #[error("Unexpected response from the conductor: {0}")]
+    UnexpectedResponse(String),
+}
+
+impl ClientError {
+    /// A response which doesn't match the request it was for
+    pub(crate) fn unexpected<R: std::fmt::Debug>(response: R) -> Self {
+        ClientError::UnexpectedResponse(format!("{:?}", response))
+    }
+}
+
+/// Result type for conductor clients
+pub type ClientResult<T> = Result<T, ClientError>;
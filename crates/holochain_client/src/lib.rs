@@ -0,0 +1,36 @@
+#![deny(missing_docs)]
+//! A typed client for a Holochain conductor's admin and app interfaces.
+//!
+//! [AdminWebsocket] and [AppWebsocket] wrap the websocket protocols of the
+//! two kinds of interface, with a method for each request which returns the
+//! matching response. Both reconnect if the conductor goes away, and
+//! [AppWebsocket] streams the signals emitted by the conductor's cells.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # async fn example() -> holochain_client::ClientResult<()> {
+//! use holochain_client::*;
+//! use url2::url2;
+//!
+//! let mut admin = AdminWebsocket::connect(url2!("ws://localhost:1234")).await?;
+//! let port = admin.attach_app_interface(None).await?;
+//!
+//! let app = AppWebsocket::connect(url2!("ws://localhost:{}", port)).await?;
+//! let mut signals = app.signals();
+//! while let Ok(signal) = signals.recv().await {
+//!     println!("{:?}", signal);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod admin;
+mod app;
+mod connection;
+mod error;
+
+pub use admin::AdminWebsocket;
+pub use app::AppWebsocket;
+pub use connection::ReconnectPolicy;
+pub use error::{ClientError, ClientResult};
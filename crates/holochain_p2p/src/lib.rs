@@ -5,8 +5,14 @@ use holo_hash::*;
 use holochain_keystore::*;
 use holochain_serialized_bytes::prelude::*;
 use holochain_zome_types::zome::FunctionName;
-use holochain_zome_types::{capability::CapSecret, zome::ZomeName};
-use std::sync::Arc;
+use holochain_zome_types::{
+    capability::CapSecret, observation::SignedChainHeadObservation, provenance::CallProvenance,
+    zome::ZomeName,
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 mod types;
 pub use types::actor::{HolochainP2pRef, HolochainP2pSender};
@@ -16,8 +22,11 @@ mod spawn;
 use ghost_actor::dependencies::{tracing, tracing_futures::Instrument};
 use holochain_types::element::GetElementResponse;
 use holochain_types::{
+    activity::{AgentActivityResponse, ChainHeadResponse, ChainRange},
+    dht_op::DhtOpCounts,
     link::{GetLinksResponse, WireLinkMetaKey},
-    metadata::MetadataSet,
+    metadata::{MetadataSet, TimedHeaderHash},
+    validate::ValidationPackageResponse,
 };
 pub use spawn::*;
 pub use test::HolochainP2pCellFixturator;
@@ -40,12 +49,14 @@ pub trait HolochainP2pCellT {
     async fn leave(&mut self) -> actor::HolochainP2pResult<()>;
 
     /// Invoke a zome function on a remote node (if you have been granted the capability).
+    /// A call made from a zome function passes on the path which led to it.
     async fn call_remote(
         &mut self,
         to_agent: AgentPubKey,
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        provenance: Option<CallProvenance>,
         request: SerializedBytes,
     ) -> actor::HolochainP2pResult<SerializedBytes>;
 
@@ -56,18 +67,27 @@ pub trait HolochainP2pCellT {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        validation_packages: Vec<(
+            holo_hash::HeaderHash,
+            holochain_zome_types::validate::ValidationPackage,
+        )>,
         timeout_ms: Option<u64>,
     ) -> actor::HolochainP2pResult<()>;
 
-    /// Request a validation package.
-    async fn get_validation_package(&mut self) -> actor::HolochainP2pResult<()>;
+    /// Request the validation package for a header from its author.
+    async fn get_validation_package(
+        &mut self,
+        request_from: AgentPubKey,
+        header_hash: HeaderHash,
+    ) -> actor::HolochainP2pResult<ValidationPackageResponse>;
 
     /// Get an entry from the DHT.
+    /// Each response is paired with the agent who sent it.
     async fn get(
         &mut self,
         dht_hash: holo_hash::AnyDhtHash,
         options: actor::GetOptions,
-    ) -> actor::HolochainP2pResult<Vec<GetElementResponse>>;
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, GetElementResponse)>>;
 
     /// Get metadata from the DHT.
     async fn get_meta(
@@ -77,11 +97,49 @@ pub trait HolochainP2pCellT {
     ) -> actor::HolochainP2pResult<Vec<MetadataSet>>;
 
     /// Get links from the DHT.
+    /// Each response is paired with the agent who sent it.
     async fn get_links(
         &mut self,
         link_key: WireLinkMetaKey,
         options: actor::GetLinksOptions,
-    ) -> actor::HolochainP2pResult<Vec<GetLinksResponse>>;
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, GetLinksResponse)>>;
+
+    /// Get the signed headers of an agent's source chain.
+    /// Each response is paired with the agent who sent it.
+    async fn get_agent_activity(
+        &mut self,
+        agent: AgentPubKey,
+        range: ChainRange,
+        options: actor::GetActivityOptions,
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, AgentActivityResponse)>>;
+
+    /// Ask an agent's activity authorities to observe the head of its
+    /// source chain, and sign what they saw.
+    async fn observe_chain_head(
+        &mut self,
+        agent: AgentPubKey,
+        options: actor::GetActivityOptions,
+    ) -> actor::HolochainP2pResult<Vec<SignedChainHeadObservation>>;
+
+    /// Ask the authorities for a basis how many ops they hold around it.
+    /// Each response is paired with the agent who sent it.
+    async fn get_dht_op_counts(
+        &mut self,
+        basis: AnyDhtHash,
+        half_length: u32,
+        since: holochain_types::Timestamp,
+        until: holochain_types::Timestamp,
+        options: actor::GetDhtOpCountsOptions,
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, DhtOpCounts)>>;
+
+    /// Ask the authorities for a basis which of these ops they hold.
+    /// Each response is paired with the agent who sent it.
+    async fn holds_ops(
+        &mut self,
+        basis: AnyDhtHash,
+        op_hashes: Vec<DhtOpHash>,
+        options: actor::HoldsOpsOptions,
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, Vec<DhtOpHash>)>>;
 
     /// Send a validation receipt to a remote node.
     async fn send_validation_receipt(
@@ -89,6 +147,47 @@ pub trait HolochainP2pCellT {
         to_agent: AgentPubKey,
         receipt: SerializedBytes,
     ) -> actor::HolochainP2pResult<()>;
+
+    /// Ask the authorities for a basis to notify us when ops integrate at it.
+    async fn subscribe(&mut self, basis: AnyDhtHash) -> actor::HolochainP2pResult<()>;
+
+    /// Ask the authorities for a basis to stop notifying us.
+    async fn unsubscribe(&mut self, basis: AnyDhtHash) -> actor::HolochainP2pResult<()>;
+
+    /// Notify a subscriber of the ops we have integrated at a basis.
+    async fn send_basis_changed(
+        &mut self,
+        to_agent: AgentPubKey,
+        basis: AnyDhtHash,
+        op_hashes: Vec<DhtOpHash>,
+    ) -> actor::HolochainP2pResult<()>;
+
+    /// Push an op we hold to the authorities closest to its basis,
+    /// before we stop holding it ourselves.
+    /// Returns the agents which acknowledged receiving it in time.
+    async fn hand_off(
+        &mut self,
+        dht_hash: AnyDhtHash,
+        op_hash: DhtOpHash,
+        op: holochain_types::dht_op::DhtOp,
+        timeout_ms: Option<u64>,
+    ) -> actor::HolochainP2pResult<Vec<AgentPubKey>>;
+
+    /// Report a remote agent for misbehaving, lowering its reputation.
+    async fn report_peer(
+        &mut self,
+        agent: AgentPubKey,
+        report: actor::PeerReport,
+    ) -> actor::HolochainP2pResult<()>;
+
+    /// What this cell's storage arc would be resized to, to reach its
+    /// nearest neighbors. Returns the current arc and the resized one.
+    async fn resized_arc(
+        &mut self,
+    ) -> actor::HolochainP2pResult<(dht_arc::DhtArc, dht_arc::DhtArc)>;
+
+    /// Set this cell's storage arc.
+    async fn set_arc(&mut self, arc: dht_arc::DhtArc) -> actor::HolochainP2pResult<()>;
 }
 
 /// A wrapper around HolochainP2pSender that partially applies the dna_hash / agent_pub_key.
@@ -98,6 +197,31 @@ pub struct HolochainP2pCell {
     sender: ghost_actor::GhostSender<actor::HolochainP2p>,
     dna_hash: Arc<DnaHash>,
     from_agent: Arc<AgentPubKey>,
+    offline: Arc<AtomicBool>,
+}
+
+impl HolochainP2pCell {
+    /// Whether this cell is in offline mode.
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::SeqCst)
+    }
+
+    /// Put this cell in or out of offline mode.
+    /// While offline, queries are answered as if no authority responded,
+    /// so callers fall back to local state, and anything that must reach
+    /// another agent fails with [HolochainP2pError::Offline].
+    /// The mode is shared by every clone of this cell's network.
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+
+    fn check_online(&self) -> actor::HolochainP2pResult<()> {
+        if self.is_offline() {
+            Err(HolochainP2pError::Offline)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -133,8 +257,10 @@ impl HolochainP2pCellT for HolochainP2pCell {
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        provenance: Option<CallProvenance>,
         request: SerializedBytes,
     ) -> actor::HolochainP2pResult<SerializedBytes> {
+        self.check_online()?;
         self.sender
             .call_remote(
                 (*self.dna_hash).clone(),
@@ -143,6 +269,7 @@ impl HolochainP2pCellT for HolochainP2pCell {
                 zome_name,
                 fn_name,
                 cap,
+                provenance,
                 request,
             )
             .await
@@ -154,8 +281,13 @@ impl HolochainP2pCellT for HolochainP2pCell {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        validation_packages: Vec<(
+            holo_hash::HeaderHash,
+            holochain_zome_types::validate::ValidationPackage,
+        )>,
         timeout_ms: Option<u64>,
     ) -> actor::HolochainP2pResult<()> {
+        self.check_online()?;
         self.sender
             .publish(
                 (*self.dna_hash).clone(),
@@ -163,27 +295,41 @@ impl HolochainP2pCellT for HolochainP2pCell {
                 request_validation_receipt,
                 dht_hash,
                 ops,
+                validation_packages,
                 timeout_ms,
             )
             .await
     }
 
-    /// Request a validation package.
-    async fn get_validation_package(&mut self) -> actor::HolochainP2pResult<()> {
+    /// Request the validation package for a header from its author.
+    async fn get_validation_package(
+        &mut self,
+        request_from: AgentPubKey,
+        header_hash: HeaderHash,
+    ) -> actor::HolochainP2pResult<ValidationPackageResponse> {
+        if self.is_offline() {
+            return Ok(ValidationPackageResponse(None));
+        }
         self.sender
             .get_validation_package(actor::GetValidationPackage {
                 dna_hash: (*self.dna_hash).clone(),
                 agent_pub_key: (*self.from_agent).clone(),
+                request_from,
+                header_hash,
             })
             .await
     }
 
     /// Get an entry from the DHT.
+    /// Each response is paired with the agent who sent it.
     async fn get(
         &mut self,
         dht_hash: holo_hash::AnyDhtHash,
         options: actor::GetOptions,
-    ) -> actor::HolochainP2pResult<Vec<GetElementResponse>> {
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, GetElementResponse)>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
         self.sender
             .get(
                 (*self.dna_hash).clone(),
@@ -201,6 +347,9 @@ impl HolochainP2pCellT for HolochainP2pCell {
         dht_hash: holo_hash::AnyDhtHash,
         options: actor::GetMetaOptions,
     ) -> actor::HolochainP2pResult<Vec<MetadataSet>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
         self.sender
             .get_meta(
                 (*self.dna_hash).clone(),
@@ -212,11 +361,15 @@ impl HolochainP2pCellT for HolochainP2pCell {
     }
 
     /// Get links from the DHT.
+    /// Each response is paired with the agent who sent it.
     async fn get_links(
         &mut self,
         link_key: WireLinkMetaKey,
         options: actor::GetLinksOptions,
-    ) -> actor::HolochainP2pResult<Vec<GetLinksResponse>> {
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, GetLinksResponse)>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
         self.sender
             .get_links(
                 (*self.dna_hash).clone(),
@@ -227,12 +380,103 @@ impl HolochainP2pCellT for HolochainP2pCell {
             .await
     }
 
+    /// Get the signed headers of an agent's source chain.
+    /// Each response is paired with the agent who sent it.
+    async fn get_agent_activity(
+        &mut self,
+        agent: AgentPubKey,
+        range: ChainRange,
+        options: actor::GetActivityOptions,
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, AgentActivityResponse)>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
+        self.sender
+            .get_agent_activity(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                agent,
+                range,
+                options,
+            )
+            .await
+    }
+
+    /// Ask an agent's activity authorities to observe the head of its
+    /// source chain, and sign what they saw.
+    async fn observe_chain_head(
+        &mut self,
+        agent: AgentPubKey,
+        options: actor::GetActivityOptions,
+    ) -> actor::HolochainP2pResult<Vec<SignedChainHeadObservation>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
+        self.sender
+            .observe_chain_head(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                agent,
+                options,
+            )
+            .await
+    }
+
+    /// Ask the authorities for a basis how many ops they hold around it.
+    /// Each response is paired with the agent who sent it.
+    async fn get_dht_op_counts(
+        &mut self,
+        basis: AnyDhtHash,
+        half_length: u32,
+        since: holochain_types::Timestamp,
+        until: holochain_types::Timestamp,
+        options: actor::GetDhtOpCountsOptions,
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, DhtOpCounts)>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
+        self.sender
+            .get_dht_op_counts(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                basis,
+                half_length,
+                since,
+                until,
+                options,
+            )
+            .await
+    }
+
+    /// Ask the authorities for a basis which of these ops they hold.
+    /// Each response is paired with the agent who sent it.
+    async fn holds_ops(
+        &mut self,
+        basis: AnyDhtHash,
+        op_hashes: Vec<DhtOpHash>,
+        options: actor::HoldsOpsOptions,
+    ) -> actor::HolochainP2pResult<Vec<(AgentPubKey, Vec<DhtOpHash>)>> {
+        if self.is_offline() {
+            return Ok(Vec::new());
+        }
+        self.sender
+            .holds_ops(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                basis,
+                op_hashes,
+                options,
+            )
+            .await
+    }
+
     /// Send a validation receipt to a remote node.
     async fn send_validation_receipt(
         &mut self,
         to_agent: AgentPubKey,
         receipt: SerializedBytes,
     ) -> actor::HolochainP2pResult<()> {
+        self.check_online()?;
         self.sender
             .send_validation_receipt(
                 (*self.dna_hash).clone(),
@@ -242,6 +486,101 @@ impl HolochainP2pCellT for HolochainP2pCell {
             )
             .await
     }
+
+    /// Ask the authorities for a basis to notify us when ops integrate at it.
+    async fn subscribe(&mut self, basis: AnyDhtHash) -> actor::HolochainP2pResult<()> {
+        self.check_online()?;
+        self.sender
+            .subscribe(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                basis,
+                None,
+            )
+            .await
+    }
+
+    /// Ask the authorities for a basis to stop notifying us.
+    async fn unsubscribe(&mut self, basis: AnyDhtHash) -> actor::HolochainP2pResult<()> {
+        self.check_online()?;
+        self.sender
+            .unsubscribe(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                basis,
+                None,
+            )
+            .await
+    }
+
+    /// Notify a subscriber of the ops we have integrated at a basis.
+    async fn send_basis_changed(
+        &mut self,
+        to_agent: AgentPubKey,
+        basis: AnyDhtHash,
+        op_hashes: Vec<DhtOpHash>,
+    ) -> actor::HolochainP2pResult<()> {
+        self.check_online()?;
+        self.sender
+            .send_basis_changed(
+                (*self.dna_hash).clone(),
+                to_agent,
+                (*self.from_agent).clone(),
+                basis,
+                op_hashes,
+            )
+            .await
+    }
+
+    /// Push an op we hold to the authorities closest to its basis,
+    /// before we stop holding it ourselves.
+    /// Returns the agents which acknowledged receiving it in time.
+    async fn hand_off(
+        &mut self,
+        dht_hash: AnyDhtHash,
+        op_hash: DhtOpHash,
+        op: holochain_types::dht_op::DhtOp,
+        timeout_ms: Option<u64>,
+    ) -> actor::HolochainP2pResult<Vec<AgentPubKey>> {
+        self.check_online()?;
+        self.sender
+            .hand_off(
+                (*self.dna_hash).clone(),
+                (*self.from_agent).clone(),
+                dht_hash,
+                op_hash,
+                op,
+                timeout_ms,
+            )
+            .await
+    }
+
+    /// Report a remote agent for misbehaving, lowering its reputation.
+    async fn report_peer(
+        &mut self,
+        agent: AgentPubKey,
+        report: actor::PeerReport,
+    ) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .report_peer((*self.dna_hash).clone(), agent, report)
+            .await
+    }
+
+    /// What this cell's storage arc would be resized to.
+    async fn resized_arc(
+        &mut self,
+    ) -> actor::HolochainP2pResult<(dht_arc::DhtArc, dht_arc::DhtArc)> {
+        self.sender
+            .resized_arc((*self.dna_hash).clone(), (*self.from_agent).clone())
+            .await
+    }
+
+    /// Set this cell's storage arc.
+    async fn set_arc(&mut self, arc: dht_arc::DhtArc) -> actor::HolochainP2pResult<()> {
+        self.sender
+            .set_arc((*self.dna_hash).clone(), (*self.from_agent).clone(), arc)
+            .await
+    }
 }
 
 pub use kitsune_p2p::dht_arc;
@@ -2,16 +2,44 @@ use crate::{actor::*, event::*, *};
 
 use futures::future::FutureExt;
 
-use crate::types::AgentPubKeyExt;
+use crate::types::{
+    recording::{read_recorded_events, EventRecorder, RecordedEvent, RecordedEventKind},
+    AgentPubKeyExt,
+};
 
 use ghost_actor::dependencies::{tracing, tracing_futures::Instrument};
 use holochain_types::{element::GetElementResponse, Timestamp};
 use holochain_zome_types::zome::FunctionName;
-use kitsune_p2p::actor::KitsuneP2pSender;
+use kitsune_p2p::{actor::KitsuneP2pSender, restart::RestartCounter};
+use std::collections::{HashMap, HashSet};
+
+ghost_actor::ghost_chan! {
+    pub(crate) chan Internal<crate::HolochainP2pError> {
+        /// The kitsune endpoint terminated and should be restarted
+        fn kitsune_terminated(reason: String) -> ();
+
+        /// Start sending to the restarted kitsune endpoint
+        fn kitsune_restarted(kitsune_p2p: ghost_actor::GhostSender<kitsune_p2p::actor::KitsuneP2p>) -> ();
+    }
+}
+
+/// A kitsune endpoint which keeps failing is given up on after this many restarts
+const MAX_KITSUNE_RESTARTS: u32 = 10;
+
+/// A kitsune endpoint which runs this long without failing
+/// starts its restart count over
+const KITSUNE_STABLE_AFTER: std::time::Duration = std::time::Duration::from_secs(5 * 60);
 
 pub(crate) struct HolochainP2pActor {
+    channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<Self>,
+    internal_sender: ghost_actor::GhostSender<Internal>,
     evt_sender: futures::channel::mpsc::Sender<HolochainP2pEvent>,
     kitsune_p2p: ghost_actor::GhostSender<kitsune_p2p::actor::KitsuneP2p>,
+    /// Every joined agent, so they can be re-joined if the kitsune
+    /// endpoint has to be restarted
+    joined: HashSet<(DnaHash, AgentPubKey)>,
+    kitsune_restarts: RestartCounter,
+    recorders: HashMap<(DnaHash, AgentPubKey), EventRecorder>,
 }
 
 impl ghost_actor::GhostControlHandler for HolochainP2pActor {}
@@ -22,16 +50,227 @@ impl HolochainP2pActor {
         channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<Self>,
         evt_sender: futures::channel::mpsc::Sender<HolochainP2pEvent>,
     ) -> HolochainP2pResult<Self> {
-        let (kitsune_p2p, kitsune_p2p_events) = kitsune_p2p::spawn_kitsune_p2p().await?;
+        let internal_sender = channel_factory.create_channel::<Internal>().await?;
 
-        channel_factory.attach_receiver(kitsune_p2p_events).await?;
+        let kitsune_p2p =
+            spawn_supervised_kitsune(channel_factory.clone(), internal_sender.clone()).await?;
 
         Ok(Self {
+            channel_factory,
+            internal_sender,
             evt_sender,
             kitsune_p2p,
+            joined: HashSet::new(),
+            kitsune_restarts: RestartCounter::new(KITSUNE_STABLE_AFTER),
+            recorders: HashMap::new(),
         })
     }
 
+    /// record an incoming event, if we're recording for its recipient
+    fn record(
+        &mut self,
+        dna_hash: &DnaHash,
+        to_agent: &AgentPubKey,
+        event: impl FnOnce() -> RecordedEvent,
+    ) {
+        let key = (dna_hash.clone(), to_agent.clone());
+        if let Some(recorder) = self.recorders.get_mut(&key) {
+            if let Err(e) = recorder.record(event()) {
+                // a broken recording shouldn't affect the network
+                tracing::error!(
+                    ?dna_hash,
+                    ?to_agent,
+                    path = ?recorder.path(),
+                    error = ?e,
+                    "stopped recording network events"
+                );
+                self.recorders.remove(&key);
+            }
+        }
+    }
+
+    /// broadcast a payload to the authorities for a basis
+    fn notify_authorities(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        timeout_ms: Option<u64>,
+        payload: Vec<u8>,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let from_agent = from_agent.into_kitsune();
+        let basis = basis.to_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            kitsune_p2p
+                .notify_multi(kitsune_p2p::actor::NotifyMulti {
+                    space,
+                    from_agent,
+                    basis,
+                    remote_agent_count: None, // default best-effort
+                    timeout_ms,
+                    payload,
+                })
+                .await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
+
+    /// decode and dispatch an incoming request
+    fn dispatch_call(
+        &mut self,
+        space: DnaHash,
+        to_agent: AgentPubKey,
+        from_agent: AgentPubKey,
+        payload: Vec<u8>,
+    ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<Vec<u8>> {
+        let request = crate::wire::WireMessage::decode(payload).map_err(HolochainP2pError::from)?;
+
+        match request {
+            crate::wire::WireMessage::CallRemote {
+                zome_name,
+                fn_name,
+                cap,
+                provenance,
+                data,
+            } => self.handle_incoming_call_remote(
+                space, to_agent, from_agent, zome_name, fn_name, cap, provenance, data,
+            ),
+            crate::wire::WireMessage::Get { dht_hash, options } => {
+                self.handle_incoming_get(space, to_agent, dht_hash, options)
+            }
+            crate::wire::WireMessage::GetMeta { dht_hash, options } => {
+                self.handle_incoming_get_meta(space, to_agent, dht_hash, options)
+            }
+            crate::wire::WireMessage::GetLinks { link_key, options } => {
+                self.handle_incoming_get_links(space, to_agent, link_key, options)
+            }
+            crate::wire::WireMessage::GetAgentActivity { agent, range } => {
+                self.handle_incoming_get_agent_activity(space, to_agent, agent, range)
+            }
+            crate::wire::WireMessage::ObserveChainHead { agent } => {
+                self.handle_incoming_observe_chain_head(space, to_agent, agent)
+            }
+            crate::wire::WireMessage::GetDhtOpCounts {
+                center_loc,
+                half_length,
+                since,
+                until,
+            } => self.handle_incoming_get_dht_op_counts(
+                space,
+                to_agent,
+                dht_arc::DhtArc::new(center_loc, half_length),
+                since,
+                until,
+            ),
+            crate::wire::WireMessage::GetValidationPackage { header_hash } => {
+                self.handle_incoming_get_validation_package(space, to_agent, header_hash)
+            }
+            crate::wire::WireMessage::HoldsOps { op_hashes } => {
+                self.handle_incoming_holds_ops(space, to_agent, op_hashes)
+            }
+            // holochain_p2p never publishes via request
+            // these only occur on broadcasts
+            crate::wire::WireMessage::Publish { .. } => {
+                Err(HolochainP2pError::invalid_p2p_message(
+                    "invalid: publish is a broadcast type, not a request".to_string(),
+                )
+                .into())
+            }
+            crate::wire::WireMessage::ValidationReceipt { receipt } => {
+                self.handle_incoming_validation_receipt(space, to_agent, receipt)
+            }
+            crate::wire::WireMessage::BasisChanged { basis, op_hashes } => {
+                self.handle_incoming_basis_changed(space, to_agent, from_agent, basis, op_hashes)
+            }
+            // subscriptions are broadcast to every authority for the basis
+            crate::wire::WireMessage::Subscribe { .. }
+            | crate::wire::WireMessage::Unsubscribe { .. } => {
+                Err(HolochainP2pError::invalid_p2p_message(
+                    "invalid: subscribe is a broadcast type, not a request".to_string(),
+                )
+                .into())
+            }
+        }
+    }
+
+    /// decode and dispatch an incoming broadcast
+    fn dispatch_notify(
+        &mut self,
+        space: DnaHash,
+        to_agent: AgentPubKey,
+        from_agent: AgentPubKey,
+        payload: Vec<u8>,
+    ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<()> {
+        let request = crate::wire::WireMessage::decode(payload).map_err(HolochainP2pError::from)?;
+
+        match request {
+            // error on these call type messages
+            crate::wire::WireMessage::CallRemote { .. }
+            | crate::wire::WireMessage::Get { .. }
+            | crate::wire::WireMessage::GetMeta { .. }
+            | crate::wire::WireMessage::GetLinks { .. }
+            | crate::wire::WireMessage::GetAgentActivity { .. }
+            | crate::wire::WireMessage::ObserveChainHead { .. }
+            | crate::wire::WireMessage::GetDhtOpCounts { .. }
+            | crate::wire::WireMessage::GetValidationPackage { .. }
+            | crate::wire::WireMessage::HoldsOps { .. }
+            | crate::wire::WireMessage::ValidationReceipt { .. }
+            | crate::wire::WireMessage::BasisChanged { .. } => {
+                Err(HolochainP2pError::invalid_p2p_message(
+                    "invalid call type message in a notify".to_string(),
+                )
+                .into())
+            }
+            crate::wire::WireMessage::Publish {
+                request_validation_receipt,
+                dht_hash,
+                ops,
+                validation_packages,
+            } => self.handle_incoming_publish(
+                space,
+                to_agent,
+                from_agent,
+                request_validation_receipt,
+                dht_hash,
+                ops,
+                validation_packages,
+            ),
+            crate::wire::WireMessage::Subscribe { basis } => {
+                self.handle_incoming_subscribe(space, to_agent, from_agent, basis, true)
+            }
+            crate::wire::WireMessage::Unsubscribe { basis } => {
+                self.handle_incoming_subscribe(space, to_agent, from_agent, basis, false)
+            }
+        }
+    }
+
+    /// decode and dispatch an op received through gossip
+    fn dispatch_gossip(
+        &mut self,
+        space: DnaHash,
+        to_agent: AgentPubKey,
+        op_hash: DhtOpHash,
+        op_data: Vec<u8>,
+    ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<()> {
+        let op_data =
+            crate::wire::WireDhtOpData::decode(op_data).map_err(HolochainP2pError::from)?;
+        self.handle_incoming_publish(
+            space,
+            to_agent,
+            op_data.from_agent,
+            false,
+            op_data.dht_hash,
+            vec![(op_hash, op_data.op_data)],
+            // Gossip carries no packages, validators can ask the author
+            vec![],
+        )
+    }
+
     /// receiving an incoming request from a remote node
     #[allow(clippy::too_many_arguments)]
     fn handle_incoming_call_remote(
@@ -42,6 +281,7 @@ impl HolochainP2pActor {
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        provenance: Option<CallProvenance>,
         data: Vec<u8>,
     ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
         let data: SerializedBytes = UnsafeBytes::from(data).into();
@@ -49,7 +289,7 @@ impl HolochainP2pActor {
         Ok(async move {
             let res = evt_sender
                 .call_remote(
-                    dna_hash, to_agent, from_agent, zome_name, fn_name, cap, data,
+                    dna_hash, to_agent, from_agent, zome_name, fn_name, cap, provenance, data,
                 )
                 .await;
             res.map_err(kitsune_p2p::KitsuneP2pError::from)
@@ -122,7 +362,109 @@ impl HolochainP2pActor {
         .into())
     }
 
+    /// receiving an incoming get_agent_activity request from a remote node
+    fn handle_incoming_get_agent_activity(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        agent: AgentPubKey,
+        range: ChainRange,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            let res = evt_sender
+                .get_agent_activity(dna_hash, to_agent, agent, range)
+                .await;
+            res.and_then(|r| Ok(SerializedBytes::try_from(r)?))
+                .map_err(kitsune_p2p::KitsuneP2pError::from)
+                .map(|res| UnsafeBytes::from(res).into())
+        }
+        .boxed()
+        .into())
+    }
+
+    /// receiving an incoming observe_chain_head request from a remote node
+    fn handle_incoming_observe_chain_head(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        agent: AgentPubKey,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            let res = evt_sender
+                .observe_chain_head(dna_hash, to_agent, agent)
+                .await;
+            res.and_then(|r| Ok(SerializedBytes::try_from(r)?))
+                .map_err(kitsune_p2p::KitsuneP2pError::from)
+                .map(|res| UnsafeBytes::from(res).into())
+        }
+        .boxed()
+        .into())
+    }
+
+    /// receiving an incoming holds_ops request from a remote node
+    fn handle_incoming_holds_ops(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            let res = evt_sender.holds_ops(dna_hash, to_agent, op_hashes).await;
+            res.and_then(|r| Ok(SerializedBytes::try_from(crate::wire::WireHeldOps(r))?))
+                .map_err(kitsune_p2p::KitsuneP2pError::from)
+                .map(|res| UnsafeBytes::from(res).into())
+        }
+        .boxed()
+        .into())
+    }
+
+    /// receiving an incoming get_dht_op_counts request from a remote node
+    fn handle_incoming_get_dht_op_counts(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        dht_arc: dht_arc::DhtArc,
+        since: Timestamp,
+        until: Timestamp,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            let res = evt_sender
+                .get_dht_op_counts(dna_hash, to_agent, dht_arc, since, until)
+                .await;
+            res.and_then(|r| Ok(SerializedBytes::try_from(r)?))
+                .map_err(kitsune_p2p::KitsuneP2pError::from)
+                .map(|res| UnsafeBytes::from(res).into())
+        }
+        .boxed()
+        .into())
+    }
+
+    /// receiving an incoming get_validation_package request from a remote node
+    fn handle_incoming_get_validation_package(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        header_hash: HeaderHash,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            let res = evt_sender
+                .get_validation_package(dna_hash, to_agent, header_hash)
+                .await;
+            res.and_then(|r| Ok(SerializedBytes::try_from(r)?))
+                .map_err(kitsune_p2p::KitsuneP2pError::from)
+                .map(|res| UnsafeBytes::from(res).into())
+        }
+        .boxed()
+        .into())
+    }
+
     /// receiving an incoming publish from a remote node
+    #[allow(clippy::too_many_arguments)]
     fn handle_incoming_publish(
         &mut self,
         dna_hash: DnaHash,
@@ -131,6 +473,10 @@ impl HolochainP2pActor {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        validation_packages: Vec<(
+            holo_hash::HeaderHash,
+            holochain_zome_types::validate::ValidationPackage,
+        )>,
     ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<()> {
         let evt_sender = self.evt_sender.clone();
         Ok(async move {
@@ -142,6 +488,7 @@ impl HolochainP2pActor {
                     request_validation_receipt,
                     dht_hash,
                     ops,
+                    validation_packages,
                 )
                 .await?;
             Ok(())
@@ -171,55 +518,155 @@ impl HolochainP2pActor {
         .boxed()
         .into())
     }
-}
-
-impl ghost_actor::GhostHandler<kitsune_p2p::event::KitsuneP2pEvent> for HolochainP2pActor {}
 
-impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
-    #[tracing::instrument(skip(self, space, to_agent, from_agent, payload))]
-    fn handle_call(
+    /// receiving an incoming subscribe or unsubscribe from a remote node
+    fn handle_incoming_subscribe(
         &mut self,
-        space: Arc<kitsune_p2p::KitsuneSpace>,
-        to_agent: Arc<kitsune_p2p::KitsuneAgent>,
-        from_agent: Arc<kitsune_p2p::KitsuneAgent>,
-        payload: Vec<u8>,
-    ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<Vec<u8>> {
-        let space = DnaHash::from_kitsune(&space);
-        let to_agent = AgentPubKey::from_kitsune(&to_agent);
-        let from_agent = AgentPubKey::from_kitsune(&from_agent);
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        subscribe: bool,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<()> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            if subscribe {
+                evt_sender
+                    .subscribe(dna_hash, to_agent, from_agent, basis)
+                    .await?;
+            } else {
+                evt_sender
+                    .unsubscribe(dna_hash, to_agent, from_agent, basis)
+                    .await?;
+            }
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
 
-        let request = crate::wire::WireMessage::decode(payload).map_err(HolochainP2pError::from)?;
+    /// receiving a notification from an authority we're subscribed to
+    fn handle_incoming_basis_changed(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    ) -> kitsune_p2p::actor::KitsuneP2pHandlerResult<Vec<u8>> {
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            evt_sender
+                .basis_changed(dna_hash, to_agent, from_agent, basis, op_hashes)
+                .await?;
 
-        match request {
-            crate::wire::WireMessage::CallRemote {
-                zome_name,
-                fn_name,
-                cap,
-                data,
-            } => self.handle_incoming_call_remote(
-                space, to_agent, from_agent, zome_name, fn_name, cap, data,
-            ),
-            crate::wire::WireMessage::Get { dht_hash, options } => {
-                self.handle_incoming_get(space, to_agent, dht_hash, options)
-            }
-            crate::wire::WireMessage::GetMeta { dht_hash, options } => {
-                self.handle_incoming_get_meta(space, to_agent, dht_hash, options)
-            }
-            crate::wire::WireMessage::GetLinks { link_key, options } => {
-                self.handle_incoming_get_links(space, to_agent, link_key, options)
-            }
-            // holochain_p2p never publishes via request
-            // these only occur on broadcasts
-            crate::wire::WireMessage::Publish { .. } => {
-                Err(HolochainP2pError::invalid_p2p_message(
-                    "invalid: publish is a broadcast type, not a request".to_string(),
-                )
-                .into())
-            }
-            crate::wire::WireMessage::ValidationReceipt { receipt } => {
-                self.handle_incoming_validation_receipt(space, to_agent, receipt)
+            // notifications don't need a response
+            Ok(Vec::with_capacity(0))
+        }
+        .boxed()
+        .into())
+    }
+}
+
+/// Spawn a kitsune endpoint, routing its events through the HolochainP2p
+/// actor and watching it so that it is restarted if it terminates.
+async fn spawn_supervised_kitsune(
+    channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<HolochainP2pActor>,
+    internal_sender: ghost_actor::GhostSender<Internal>,
+) -> HolochainP2pResult<ghost_actor::GhostSender<kitsune_p2p::actor::KitsuneP2p>> {
+    let (kitsune_p2p, kitsune_p2p_events, driver) =
+        kitsune_p2p::spawn_kitsune_p2p_with_driver().await?;
+
+    channel_factory.attach_receiver(kitsune_p2p_events).await?;
+
+    tokio::task::spawn(supervise_kitsune(driver, internal_sender));
+    Ok(kitsune_p2p)
+}
+
+/// Wait for a kitsune endpoint to terminate, then report why so it can be
+/// restarted
+async fn supervise_kitsune(
+    driver: tokio::task::JoinHandle<ghost_actor::GhostResult<()>>,
+    internal_sender: ghost_actor::GhostSender<Internal>,
+) {
+    let reason = match driver.await {
+        Ok(Ok(())) => "kitsune endpoint shut down".to_string(),
+        Ok(Err(e)) => format!("kitsune endpoint errored: {}", e),
+        Err(e) => format!("kitsune endpoint panicked: {}", e),
+    };
+    // if this fails the HolochainP2p actor is gone too, so there's nothing to restart
+    let _ = internal_sender.kitsune_terminated(reason).await;
+}
+
+impl ghost_actor::GhostHandler<Internal> for HolochainP2pActor {}
+
+impl InternalHandler for HolochainP2pActor {
+    fn handle_kitsune_terminated(&mut self, reason: String) -> InternalHandlerResult<()> {
+        tracing::error!(%reason, "kitsune endpoint terminated");
+        let restart_count = self.kitsune_restarts.restart(std::time::Instant::now());
+        if restart_count > MAX_KITSUNE_RESTARTS {
+            tracing::error!("kitsune endpoint failed too many times, giving up");
+            return Ok(async move { Ok(()) }.boxed().into());
+        }
+        tracing::warn!(restart_count, "restarting kitsune endpoint");
+
+        let channel_factory = self.channel_factory.clone();
+        let internal_sender = self.internal_sender.clone();
+        let evt_sender = self.evt_sender.clone();
+        let joined = self.joined.clone();
+        Ok(async move {
+            let kitsune_p2p =
+                spawn_supervised_kitsune(channel_factory, internal_sender.clone()).await?;
+            internal_sender
+                .kitsune_restarted(kitsune_p2p.clone())
+                .await?;
+            // the new endpoint starts out empty, so re-join everyone who was
+            // joined to the old one, and let their cells know
+            for (dna_hash, agent) in joined {
+                kitsune_p2p
+                    .join(
+                        dna_hash.clone().into_kitsune(),
+                        agent.clone().into_kitsune(),
+                    )
+                    .await?;
+                evt_sender
+                    .network_restarted(dna_hash, agent, reason.clone())
+                    .await?;
             }
+            Ok(())
         }
+        .boxed()
+        .into())
+    }
+
+    fn handle_kitsune_restarted(
+        &mut self,
+        kitsune_p2p: ghost_actor::GhostSender<kitsune_p2p::actor::KitsuneP2p>,
+    ) -> InternalHandlerResult<()> {
+        self.kitsune_p2p = kitsune_p2p;
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+}
+
+impl ghost_actor::GhostHandler<kitsune_p2p::event::KitsuneP2pEvent> for HolochainP2pActor {}
+
+impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
+    #[tracing::instrument(skip(self, space, to_agent, from_agent, payload))]
+    fn handle_call(
+        &mut self,
+        space: Arc<kitsune_p2p::KitsuneSpace>,
+        to_agent: Arc<kitsune_p2p::KitsuneAgent>,
+        from_agent: Arc<kitsune_p2p::KitsuneAgent>,
+        payload: Vec<u8>,
+    ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<Vec<u8>> {
+        let space = DnaHash::from_kitsune(&space);
+        let to_agent = AgentPubKey::from_kitsune(&to_agent);
+        let from_agent = AgentPubKey::from_kitsune(&from_agent);
+
+        self.record(&space, &to_agent, || {
+            RecordedEvent::now(from_agent.clone(), RecordedEventKind::Call(payload.clone()))
+        });
+        self.dispatch_call(space, to_agent, from_agent, payload)
     }
 
     fn handle_notify(
@@ -233,33 +680,13 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
         let to_agent = AgentPubKey::from_kitsune(&to_agent);
         let from_agent = AgentPubKey::from_kitsune(&from_agent);
 
-        let request = crate::wire::WireMessage::decode(payload).map_err(HolochainP2pError::from)?;
-
-        match request {
-            // error on these call type messages
-            crate::wire::WireMessage::CallRemote { .. }
-            | crate::wire::WireMessage::Get { .. }
-            | crate::wire::WireMessage::GetMeta { .. }
-            | crate::wire::WireMessage::GetLinks { .. }
-            | crate::wire::WireMessage::ValidationReceipt { .. } => {
-                Err(HolochainP2pError::invalid_p2p_message(
-                    "invalid call type message in a notify".to_string(),
-                )
-                .into())
-            }
-            crate::wire::WireMessage::Publish {
-                request_validation_receipt,
-                dht_hash,
-                ops,
-            } => self.handle_incoming_publish(
-                space,
-                to_agent,
-                from_agent,
-                request_validation_receipt,
-                dht_hash,
-                ops,
-            ),
-        }
+        self.record(&space, &to_agent, || {
+            RecordedEvent::now(
+                from_agent.clone(),
+                RecordedEventKind::Notify(payload.clone()),
+            )
+        });
+        self.dispatch_notify(space, to_agent, from_agent, payload)
     }
 
     fn handle_gossip(
@@ -272,18 +699,18 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
     ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<()> {
         let space = DnaHash::from_kitsune(&space);
         let to_agent = AgentPubKey::from_kitsune(&to_agent);
-        let _from_agent = AgentPubKey::from_kitsune(&from_agent);
+        let from_agent = AgentPubKey::from_kitsune(&from_agent);
         let op_hash = DhtOpHash::from_kitsune(&op_hash);
-        let op_data =
-            crate::wire::WireDhtOpData::decode(op_data).map_err(HolochainP2pError::from)?;
-        self.handle_incoming_publish(
-            space,
-            to_agent,
-            op_data.from_agent,
-            false,
-            op_data.dht_hash,
-            vec![(op_hash, op_data.op_data)],
-        )
+        self.record(&space, &to_agent, || {
+            RecordedEvent::now(
+                from_agent,
+                RecordedEventKind::Gossip {
+                    op_hash: op_hash.clone(),
+                    op_data: op_data.clone(),
+                },
+            )
+        });
+        self.dispatch_gossip(space, to_agent, op_hash, op_data)
     }
 
     fn handle_fetch_op_hashes_for_constraints(
@@ -364,6 +791,37 @@ impl kitsune_p2p::event::KitsuneP2pEventHandler for HolochainP2pActor {
     ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<kitsune_p2p::KitsuneSignature> {
         unimplemented!()
     }
+
+    fn handle_space_restarted(
+        &mut self,
+        input: kitsune_p2p::event::SpaceRestartedEvt,
+    ) -> kitsune_p2p::event::KitsuneP2pEventHandlerResult<()> {
+        let kitsune_p2p::event::SpaceRestartedEvt {
+            space,
+            agents,
+            reason,
+            restart_count,
+        } = input;
+        let dna_hash = DnaHash::from_kitsune(&space);
+        tracing::warn!(?dna_hash, %reason, restart_count, "network restarted");
+
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            // let each cell in the space know
+            for agent in agents {
+                evt_sender
+                    .network_restarted(
+                        dna_hash.clone(),
+                        AgentPubKey::from_kitsune(&agent),
+                        reason.clone(),
+                    )
+                    .await?;
+            }
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
 }
 
 impl ghost_actor::GhostHandler<HolochainP2p> for HolochainP2pActor {}
@@ -374,6 +832,8 @@ impl HolochainP2pHandler for HolochainP2pActor {
         dna_hash: DnaHash,
         agent_pub_key: AgentPubKey,
     ) -> HolochainP2pHandlerResult<()> {
+        self.joined
+            .insert((dna_hash.clone(), agent_pub_key.clone()));
         let space = dna_hash.into_kitsune();
         let agent = agent_pub_key.into_kitsune();
 
@@ -388,6 +848,8 @@ impl HolochainP2pHandler for HolochainP2pActor {
         dna_hash: DnaHash,
         agent_pub_key: AgentPubKey,
     ) -> HolochainP2pHandlerResult<()> {
+        self.joined
+            .remove(&(dna_hash.clone(), agent_pub_key.clone()));
         let space = dna_hash.into_kitsune();
         let agent = agent_pub_key.into_kitsune();
 
@@ -405,6 +867,7 @@ impl HolochainP2pHandler for HolochainP2pActor {
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        provenance: Option<CallProvenance>,
         request: SerializedBytes,
     ) -> HolochainP2pHandlerResult<SerializedBytes> {
         let space = dna_hash.into_kitsune();
@@ -412,7 +875,8 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let from_agent = from_agent.into_kitsune();
 
         let req =
-            crate::wire::WireMessage::call_remote(zome_name, fn_name, cap, request).encode()?;
+            crate::wire::WireMessage::call_remote(zome_name, fn_name, cap, provenance, request)
+                .encode()?;
 
         let kitsune_p2p = self.kitsune_p2p.clone();
         Ok(async move {
@@ -433,14 +897,23 @@ impl HolochainP2pHandler for HolochainP2pActor {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        validation_packages: Vec<(
+            holo_hash::HeaderHash,
+            holochain_zome_types::validate::ValidationPackage,
+        )>,
         timeout_ms: Option<u64>,
     ) -> HolochainP2pHandlerResult<()> {
         let space = dna_hash.into_kitsune();
         let from_agent = from_agent.into_kitsune();
         let basis = dht_hash.to_kitsune();
 
-        let payload = crate::wire::WireMessage::publish(request_validation_receipt, dht_hash, ops)
-            .encode()?;
+        let payload = crate::wire::WireMessage::publish(
+            request_validation_receipt,
+            dht_hash,
+            ops,
+            validation_packages,
+        )
+        .encode()?;
 
         let kitsune_p2p = self.kitsune_p2p.clone();
         Ok(async move {
@@ -462,9 +935,24 @@ impl HolochainP2pHandler for HolochainP2pActor {
 
     fn handle_get_validation_package(
         &mut self,
-        _input: actor::GetValidationPackage,
-    ) -> HolochainP2pHandlerResult<()> {
-        Ok(async move { Ok(()) }.boxed().into())
+        input: actor::GetValidationPackage,
+    ) -> HolochainP2pHandlerResult<ValidationPackageResponse> {
+        let space = input.dna_hash.into_kitsune();
+        let to_agent = input.request_from.into_kitsune();
+        let from_agent = input.agent_pub_key.into_kitsune();
+
+        let req = crate::wire::WireMessage::get_validation_package(input.header_hash).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let response = kitsune_p2p
+                .rpc_single(space, to_agent, from_agent, req)
+                .await?;
+            let response = SerializedBytes::from(UnsafeBytes::from(response)).try_into()?;
+            Ok(response)
+        }
+        .boxed()
+        .into())
     }
 
     #[tracing::instrument(skip(self, dna_hash, from_agent, dht_hash, options))]
@@ -474,7 +962,7 @@ impl HolochainP2pHandler for HolochainP2pActor {
         from_agent: AgentPubKey,
         dht_hash: holo_hash::AnyDhtHash,
         options: actor::GetOptions,
-    ) -> HolochainP2pHandlerResult<Vec<GetElementResponse>> {
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, GetElementResponse)>> {
         let space = dna_hash.into_kitsune();
         let from_agent = from_agent.into_kitsune();
         let basis = dht_hash.to_kitsune();
@@ -500,8 +988,11 @@ impl HolochainP2pHandler for HolochainP2pActor {
 
             let mut out = Vec::new();
             for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
+                let kitsune_p2p::actor::RpcMultiResponse { agent, response } = item;
+                out.push((
+                    AgentPubKey::from_kitsune(&agent),
+                    SerializedBytes::from(UnsafeBytes::from(response)).try_into()?,
+                ));
             }
 
             Ok(out)
@@ -557,7 +1048,7 @@ impl HolochainP2pHandler for HolochainP2pActor {
         from_agent: AgentPubKey,
         link_key: WireLinkMetaKey,
         options: actor::GetLinksOptions,
-    ) -> HolochainP2pHandlerResult<Vec<GetLinksResponse>> {
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, GetLinksResponse)>> {
         let space = dna_hash.into_kitsune();
         let from_agent = from_agent.into_kitsune();
         let basis = link_key.basis().to_kitsune();
@@ -568,7 +1059,7 @@ impl HolochainP2pHandler for HolochainP2pActor {
         let kitsune_p2p = self.kitsune_p2p.clone();
         Ok(async move {
             // TODO - We're just targeting a single remote node for now
-            //        without doing any pagination / etc...
+            //        without doing any aggregation / etc...
             //        Setting up RpcMulti to act like RpcSingle
             let result = kitsune_p2p
                 .rpc_multi(kitsune_p2p::actor::RpcMulti {
@@ -585,8 +1076,189 @@ impl HolochainP2pHandler for HolochainP2pActor {
 
             let mut out = Vec::new();
             for item in result {
-                let kitsune_p2p::actor::RpcMultiResponse { response, .. } = item;
-                out.push(SerializedBytes::from(UnsafeBytes::from(response)).try_into()?);
+                let kitsune_p2p::actor::RpcMultiResponse { agent, response } = item;
+                out.push((
+                    AgentPubKey::from_kitsune(&agent),
+                    SerializedBytes::from(UnsafeBytes::from(response)).try_into()?,
+                ));
+            }
+
+            Ok(out)
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_get_agent_activity(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        agent: AgentPubKey,
+        range: ChainRange,
+        options: actor::GetActivityOptions,
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, AgentActivityResponse)>> {
+        let space = dna_hash.into_kitsune();
+        let from_agent = from_agent.into_kitsune();
+        // agent activity is held by the authorities for the agent's key
+        let basis = holo_hash::AnyDhtHash::from(agent.clone()).to_kitsune();
+
+        let payload = crate::wire::WireMessage::get_agent_activity(agent, range).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let result = kitsune_p2p
+                .rpc_multi(kitsune_p2p::actor::RpcMulti {
+                    space,
+                    from_agent,
+                    basis,
+                    remote_agent_count: options.remote_agent_count,
+                    timeout_ms: options.timeout_ms,
+                    as_race: false,
+                    race_timeout_ms: options.timeout_ms,
+                    payload,
+                })
+                .await?;
+
+            let mut out = Vec::new();
+            for item in result {
+                let kitsune_p2p::actor::RpcMultiResponse { agent, response } = item;
+                out.push((
+                    AgentPubKey::from_kitsune(&agent),
+                    SerializedBytes::from(UnsafeBytes::from(response)).try_into()?,
+                ));
+            }
+
+            Ok(out)
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_observe_chain_head(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        agent: AgentPubKey,
+        options: actor::GetActivityOptions,
+    ) -> HolochainP2pHandlerResult<Vec<SignedChainHeadObservation>> {
+        let space = dna_hash.into_kitsune();
+        let from_agent = from_agent.into_kitsune();
+        // chain heads are observed by the authorities for the agent's key
+        let basis = holo_hash::AnyDhtHash::from(agent.clone()).to_kitsune();
+
+        let payload = crate::wire::WireMessage::observe_chain_head(agent).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let result = kitsune_p2p
+                .rpc_multi(kitsune_p2p::actor::RpcMulti {
+                    space,
+                    from_agent,
+                    basis,
+                    remote_agent_count: options.remote_agent_count,
+                    timeout_ms: options.timeout_ms,
+                    as_race: false,
+                    race_timeout_ms: options.timeout_ms,
+                    payload,
+                })
+                .await?;
+
+            let mut out = Vec::new();
+            for item in result {
+                let response: ChainHeadResponse =
+                    SerializedBytes::from(UnsafeBytes::from(item.response)).try_into()?;
+                out.extend(response.observation);
+            }
+
+            Ok(out)
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_get_dht_op_counts(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        half_length: u32,
+        since: Timestamp,
+        until: Timestamp,
+        options: actor::GetDhtOpCountsOptions,
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, DhtOpCounts)>> {
+        let space = dna_hash.into_kitsune();
+        let from_agent = from_agent.into_kitsune();
+        let dht_arc = dht_arc::DhtArc::new(basis.get_loc(), half_length);
+        let basis = basis.to_kitsune();
+
+        let payload =
+            crate::wire::WireMessage::get_dht_op_counts(dht_arc, since, until).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let result = kitsune_p2p
+                .rpc_multi(kitsune_p2p::actor::RpcMulti {
+                    space,
+                    from_agent,
+                    basis,
+                    remote_agent_count: options.remote_agent_count,
+                    timeout_ms: options.timeout_ms,
+                    as_race: false,
+                    race_timeout_ms: options.timeout_ms,
+                    payload,
+                })
+                .await?;
+
+            let mut out = Vec::new();
+            for item in result {
+                let kitsune_p2p::actor::RpcMultiResponse { agent, response } = item;
+                out.push((
+                    AgentPubKey::from_kitsune(&agent),
+                    SerializedBytes::from(UnsafeBytes::from(response)).try_into()?,
+                ));
+            }
+
+            Ok(out)
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_holds_ops(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+        options: actor::HoldsOpsOptions,
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, Vec<holo_hash::DhtOpHash>)>> {
+        let space = dna_hash.into_kitsune();
+        let from_agent = from_agent.into_kitsune();
+        let basis = basis.to_kitsune();
+
+        let payload = crate::wire::WireMessage::holds_ops(op_hashes).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let result = kitsune_p2p
+                .rpc_multi(kitsune_p2p::actor::RpcMulti {
+                    space,
+                    from_agent,
+                    basis,
+                    remote_agent_count: options.remote_agent_count,
+                    timeout_ms: options.timeout_ms,
+                    as_race: false,
+                    race_timeout_ms: options.timeout_ms,
+                    payload,
+                })
+                .await?;
+
+            let mut out = Vec::new();
+            for item in result {
+                let kitsune_p2p::actor::RpcMultiResponse { agent, response } = item;
+                let crate::wire::WireHeldOps(held) =
+                    SerializedBytes::from(UnsafeBytes::from(response)).try_into()?;
+                out.push((AgentPubKey::from_kitsune(&agent), held));
             }
 
             Ok(out)
@@ -618,4 +1290,347 @@ impl HolochainP2pHandler for HolochainP2pActor {
         .boxed()
         .into())
     }
+
+    fn handle_subscribe(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        timeout_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<()> {
+        let payload = crate::wire::WireMessage::subscribe(basis.clone()).encode()?;
+        self.notify_authorities(dna_hash, from_agent, basis, timeout_ms, payload)
+    }
+
+    fn handle_unsubscribe(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        timeout_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<()> {
+        let payload = crate::wire::WireMessage::unsubscribe(basis.clone()).encode()?;
+        self.notify_authorities(dna_hash, from_agent, basis, timeout_ms, payload)
+    }
+
+    fn handle_send_basis_changed(
+        &mut self,
+        dna_hash: DnaHash,
+        to_agent: AgentPubKey,
+        from_agent: AgentPubKey,
+        basis: holo_hash::AnyDhtHash,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let to_agent = to_agent.into_kitsune();
+        let from_agent = from_agent.into_kitsune();
+
+        let req = crate::wire::WireMessage::basis_changed(basis, op_hashes).encode()?;
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            kitsune_p2p
+                .rpc_single(space, to_agent, from_agent, req)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_hand_off(
+        &mut self,
+        dna_hash: DnaHash,
+        from_agent: AgentPubKey,
+        dht_hash: holo_hash::AnyDhtHash,
+        op_hash: holo_hash::DhtOpHash,
+        op: holochain_types::dht_op::DhtOp,
+        timeout_ms: Option<u64>,
+    ) -> HolochainP2pHandlerResult<Vec<AgentPubKey>> {
+        let space = dna_hash.into_kitsune();
+        let basis = dht_hash.to_kitsune();
+        let op_hash = op_hash.into_kitsune();
+
+        // The receivers handle this just like an op that was gossiped to them
+        let op_data = crate::wire::WireDhtOpData {
+            from_agent: from_agent.clone(),
+            dht_hash,
+            op_data: op,
+        }
+        .encode()?;
+        let from_agent = from_agent.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let acked = kitsune_p2p
+                .publish(kitsune_p2p::actor::Publish {
+                    space,
+                    from_agent,
+                    basis,
+                    op_hash,
+                    op_data,
+                    remote_agent_count: None,
+                    timeout_ms,
+                })
+                .await?;
+            Ok(acked.iter().map(AgentPubKey::from_kitsune).collect())
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_record_events(
+        &mut self,
+        dna_hash: DnaHash,
+        agent_pub_key: AgentPubKey,
+        path: Option<std::path::PathBuf>,
+    ) -> HolochainP2pHandlerResult<()> {
+        let key = (dna_hash, agent_pub_key);
+        match path {
+            Some(path) => {
+                tracing::info!(dna_hash = ?key.0, agent = ?key.1, ?path, "recording network events");
+                let recorder = EventRecorder::open(path)?;
+                self.recorders.insert(key, recorder);
+            }
+            None => {
+                self.recorders.remove(&key);
+            }
+        }
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_replay_events(
+        &mut self,
+        dna_hash: DnaHash,
+        agent_pub_key: AgentPubKey,
+        path: std::path::PathBuf,
+    ) -> HolochainP2pHandlerResult<usize> {
+        let events = read_recorded_events(&path)?;
+        let count = events.len();
+
+        // decode every event up front, exactly as it would have been when
+        // it was received, then run them one at a time in the same order
+        let mut replayed = Vec::with_capacity(count);
+        for RecordedEvent {
+            from_agent, kind, ..
+        } in events
+        {
+            let space = dna_hash.clone();
+            let to_agent = agent_pub_key.clone();
+            replayed.push(match kind {
+                RecordedEventKind::Call(payload) => self
+                    .dispatch_call(space, to_agent, from_agent, payload)
+                    .map(|f| f.map(|r| r.map(|_| ())).boxed()),
+                RecordedEventKind::Notify(payload) => self
+                    .dispatch_notify(space, to_agent, from_agent, payload)
+                    .map(|f| f.boxed()),
+                RecordedEventKind::Gossip { op_hash, op_data } => self
+                    .dispatch_gossip(space, to_agent, op_hash, op_data)
+                    .map(|f| f.boxed()),
+            });
+        }
+
+        Ok(async move {
+            for event in replayed {
+                let result = match event {
+                    Ok(f) => f.await,
+                    Err(e) => Err(e),
+                };
+                // failures are part of what's being reproduced, so carry on
+                if let Err(e) = result {
+                    tracing::warn!(error = ?e, "replayed network event failed");
+                }
+            }
+            Ok(count)
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_report_peer(
+        &mut self,
+        dna_hash: DnaHash,
+        agent_pub_key: AgentPubKey,
+        report: PeerReport,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent_pub_key.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.report_peer(space, agent, report).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_peer_reputations(
+        &mut self,
+        dna_hash: DnaHash,
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, f64)>> {
+        let space = dna_hash.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let scores = kitsune_p2p.peer_reputations(space).await?;
+            Ok(scores
+                .into_iter()
+                .map(|(agent, score)| (AgentPubKey::from_kitsune(&agent), score))
+                .collect())
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_resized_arc(
+        &mut self,
+        dna_hash: DnaHash,
+        agent_pub_key: AgentPubKey,
+    ) -> HolochainP2pHandlerResult<(dht_arc::DhtArc, dht_arc::DhtArc)> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent_pub_key.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.resized_arc(space, agent).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_set_arc(
+        &mut self,
+        dna_hash: DnaHash,
+        agent_pub_key: AgentPubKey,
+        arc: dht_arc::DhtArc,
+    ) -> HolochainP2pHandlerResult<()> {
+        let space = dna_hash.into_kitsune();
+        let agent = agent_pub_key.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(
+            async move { Ok(kitsune_p2p.set_arc(space, agent, arc).await?) }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_gossip_rounds(
+        &mut self,
+        dna_hash: DnaHash,
+    ) -> HolochainP2pHandlerResult<Vec<(AgentPubKey, Vec<GossipRound>)>> {
+        let space = dna_hash.into_kitsune();
+
+        let kitsune_p2p = self.kitsune_p2p.clone();
+        Ok(async move {
+            let rounds = kitsune_p2p.gossip_rounds(space).await?;
+            Ok(rounds
+                .into_iter()
+                .map(|(agent, rounds)| {
+                    (
+                        AgentPubKey::from_kitsune(&agent),
+                        rounds.into_iter().map(GossipRound::from).collect(),
+                    )
+                })
+                .collect())
+        }
+        .boxed()
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn restarted_kitsune_keeps_its_agents() {
+        let dna = DnaHash::from_raw_bytes([b's'; 36].to_vec());
+        let a1 = AgentPubKey::from_raw_bytes([b'1'; 36].to_vec());
+        let a2 = AgentPubKey::from_raw_bytes([b'2'; 36].to_vec());
+
+        // spawn by hand so we can get at the internal sender
+        let (evt_send, mut evt) = futures::channel::mpsc::channel(10);
+        let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
+        let channel_factory = builder.channel_factory().clone();
+        let p2p = channel_factory
+            .create_channel::<HolochainP2p>()
+            .await
+            .unwrap();
+        let actor = HolochainP2pActor::new(channel_factory, evt_send)
+            .await
+            .unwrap();
+        let internal_sender = actor.internal_sender.clone();
+        tokio::task::spawn(builder.spawn(actor));
+
+        let (restarted_send, mut restarted_recv) = futures::channel::mpsc::channel(2);
+        tokio::task::spawn(async move {
+            use futures::sink::SinkExt;
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                match evt {
+                    HolochainP2pEvent::CallRemote { respond, .. } => {
+                        respond.r(Ok(
+                            async move { Ok(UnsafeBytes::from(b"yada".to_vec()).into()) }
+                                .boxed()
+                                .into(),
+                        ));
+                    }
+                    HolochainP2pEvent::NetworkRestarted {
+                        respond,
+                        to_agent,
+                        reason,
+                        ..
+                    } => {
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                        restarted_send
+                            .clone()
+                            .send((to_agent, reason))
+                            .await
+                            .unwrap();
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone()).await.unwrap();
+        p2p.join(dna.clone(), a2.clone()).await.unwrap();
+
+        // pretend the kitsune endpoint died
+        internal_sender
+            .kitsune_terminated("test".into())
+            .await
+            .unwrap();
+
+        // both cells hear about it
+        use tokio::stream::StreamExt;
+        let mut restarted = HashSet::new();
+        for _ in 0..2 {
+            let (agent, reason) = restarted_recv.next().await.unwrap();
+            assert_eq!(reason, "test");
+            restarted.insert(agent);
+        }
+        assert_eq!(
+            restarted,
+            vec![a1.clone(), a2.clone()].into_iter().collect()
+        );
+
+        // and the new endpoint knows about both agents
+        let res = p2p
+            .call_remote(
+                dna,
+                a1,
+                a2,
+                "".into(),
+                "".into(),
+                None,
+                None,
+                UnsafeBytes::from(b"yippo".to_vec()).into(),
+            )
+            .await
+            .unwrap();
+        let res: Vec<u8> = UnsafeBytes::from(res).into();
+        assert_eq!(b"yada".to_vec(), res);
+    }
 }
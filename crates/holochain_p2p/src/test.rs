@@ -98,6 +98,7 @@ mod tests {
                 "".into(),
                 "".into(),
                 None,
+                None,
                 UnsafeBytes::from(b"yippo".to_vec()).into(),
             )
             .await
@@ -149,6 +150,185 @@ mod tests {
         r_task.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_send_basis_changed_workflow() {
+        let (dna, a1, a2, _) = test_setup();
+        let basis: holo_hash::AnyDhtHash = newhash!(EntryHash, 'b').into();
+        let op_hashes = vec![newhash!(DhtOpHash, 'o')];
+
+        let (p2p, mut evt) = spawn_holochain_p2p().await.unwrap();
+
+        let expected = (a1.clone(), basis.clone(), op_hashes.clone());
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use crate::types::event::HolochainP2pEvent::*;
+                match evt {
+                    BasisChanged {
+                        respond,
+                        from_agent,
+                        basis,
+                        op_hashes,
+                        ..
+                    } => {
+                        assert_eq!(expected, (from_agent, basis, op_hashes));
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone()).await.unwrap();
+        p2p.join(dna.clone(), a2.clone()).await.unwrap();
+
+        p2p.send_basis_changed(dna, a2, a1, basis, op_hashes)
+            .await
+            .unwrap();
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_offline_cell() {
+        let (dna, a1, a2, _) = test_setup();
+
+        let (p2p, mut evt) = spawn_holochain_p2p().await.unwrap();
+
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            if let Some(evt) = evt.next().await {
+                panic!("offline cell reached the network: {:?}", evt);
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone()).await.unwrap();
+        p2p.join(dna.clone(), a2.clone()).await.unwrap();
+
+        let cell = actor::HolochainP2pRefToCell::to_cell(&p2p, dna, a1);
+        // The mode is shared with every clone
+        let mut network = cell.clone();
+        cell.set_offline(true);
+        assert!(network.is_offline());
+
+        let hash = holo_hash::AnyDhtHash::from(newhash!(EntryHash, 'e'));
+        let res = network
+            .get(hash, actor::GetOptions::default())
+            .await
+            .unwrap();
+        assert!(res.is_empty());
+
+        let res = network
+            .call_remote(
+                a2,
+                "".into(),
+                "".into(),
+                None,
+                None,
+                UnsafeBytes::from(b"request".to_vec()).into(),
+            )
+            .await;
+        assert!(matches!(res, Err(HolochainP2pError::Offline)));
+
+        cell.set_offline(false);
+        assert!(!network.is_offline());
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_resize_arc() {
+        let (dna, a1, a2, a3) = test_setup();
+        let a4 = newhash!(AgentPubKey, '4');
+
+        let (p2p, _evt) = spawn_holochain_p2p().await.unwrap();
+        let mut cell = actor::HolochainP2pRefToCell::to_cell(&p2p, dna.clone(), a2.clone());
+
+        // Only joined agents have an arc
+        assert!(cell.resized_arc().await.is_err());
+
+        // Two agents across the circle from each other each hold everything
+        p2p.join(dna.clone(), a1).await.unwrap();
+        p2p.join(dna.clone(), a2).await.unwrap();
+        let (_, resized) = cell.resized_arc().await.unwrap();
+        assert_eq!(resized.coverage(), 1.0);
+        cell.set_arc(resized).await.unwrap();
+
+        // Once the quarters in between are taken, it would hold half
+        p2p.join(dna.clone(), a3).await.unwrap();
+        p2p.join(dna.clone(), a4).await.unwrap();
+        let (current, resized) = cell.resized_arc().await.unwrap();
+        let coverage = resized.coverage();
+        assert!(coverage > 0.49 && coverage < 0.51, "{}", coverage);
+
+        // but keeps what it has until the new arc is set
+        assert_eq!(current.coverage(), 1.0);
+        cell.set_arc(resized).await.unwrap();
+        let (current, _) = cell.resized_arc().await.unwrap();
+        assert_eq!(current, resized);
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_record_and_replay_events() {
+        let (dna, a1, a2, _) = test_setup();
+        let tmp = tempdir::TempDir::new("holochain_p2p_replay").unwrap();
+        let path = tmp.path().join("events");
+
+        let (p2p, mut evt) = spawn_holochain_p2p().await.unwrap();
+
+        let recv_count = Arc::new(std::sync::atomic::AtomicU8::new(0));
+
+        let recv_count_clone = recv_count.clone();
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use crate::types::event::HolochainP2pEvent::*;
+                match evt {
+                    ValidationReceiptReceived {
+                        respond, receipt, ..
+                    } => {
+                        let receipt: Vec<u8> = UnsafeBytes::from(receipt).into();
+                        assert_eq!(b"receipt-test".to_vec(), receipt);
+                        // count before responding, so the sender sees it
+                        recv_count_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone()).await.unwrap();
+        p2p.join(dna.clone(), a2.clone()).await.unwrap();
+
+        p2p.record_events(dna.clone(), a2.clone(), Some(path.clone()))
+            .await
+            .unwrap();
+        p2p.send_validation_receipt(
+            dna.clone(),
+            a2.clone(),
+            a1,
+            UnsafeBytes::from(b"receipt-test".to_vec()).into(),
+        )
+        .await
+        .unwrap();
+        p2p.record_events(dna.clone(), a2.clone(), None)
+            .await
+            .unwrap();
+
+        let replayed = p2p.replay_events(dna, a2, path).await.unwrap();
+        assert_eq!(1, replayed);
+        // the original receipt, then the replayed one
+        assert_eq!(2, recv_count.load(std::sync::atomic::Ordering::SeqCst));
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     // @TODO flakey test
     // ---- test::tests::test_publish_workflow stdout ----
@@ -186,7 +366,7 @@ mod tests {
             holo_hash::hash_type::AnyDht::Header,
         );
 
-        p2p.publish(dna, a1, true, header_hash, vec![], Some(20))
+        p2p.publish(dna, a1, true, header_hash, vec![], vec![], Some(20))
             .await
             .unwrap();
 
@@ -196,6 +376,71 @@ mod tests {
         r_task.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_hand_off_workflow() {
+        let (dna, a1, a2, a3) = test_setup();
+
+        let (p2p, mut evt) = spawn_holochain_p2p().await.unwrap();
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let received_clone = received.clone();
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use crate::types::event::HolochainP2pEvent::*;
+                match evt {
+                    Publish {
+                        respond,
+                        to_agent,
+                        ops,
+                        ..
+                    } => {
+                        received_clone.lock().unwrap().push((to_agent, ops));
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        p2p.join(dna.clone(), a1.clone()).await.unwrap();
+        p2p.join(dna.clone(), a2.clone()).await.unwrap();
+        p2p.join(dna.clone(), a3.clone()).await.unwrap();
+
+        let dht_hash = holo_hash::AnyDhtHash::from(newhash!(EntryHash, 'e'));
+        let op_hash = newhash!(DhtOpHash, 'o');
+        let op =
+            holochain_types::dht_op::DhtOp::RegisterAgentActivity(fixt!(Signature), fixt!(Header));
+
+        let mut acked = p2p
+            .hand_off(
+                dna,
+                a1.clone(),
+                dht_hash,
+                op_hash.clone(),
+                op.clone(),
+                Some(200),
+            )
+            .await
+            .unwrap();
+        acked.sort();
+        let mut expected = vec![a2, a3];
+        expected.sort();
+        // Every other authority has the op, and we never hand off to ourselves
+        assert_eq!(expected, acked);
+        let received = received.lock().unwrap();
+        assert_eq!(2, received.len());
+        for (to_agent, ops) in received.iter() {
+            assert_ne!(&a1, to_agent);
+            assert_eq!(&vec![(op_hash.clone(), op.clone())], ops);
+        }
+        drop(received);
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_get_workflow() {
         let (dna, a1, a2, a3) = test_setup();
@@ -258,7 +503,7 @@ mod tests {
 
         assert_eq!(1, res.len());
 
-        for r in res {
+        for (_agent, r) in res {
             assert!(r == test_1 || r == test_2);
         }
 
@@ -275,6 +520,7 @@ mod tests {
         let test_1 = GetLinksResponse {
             link_adds: vec![(fixt!(CreateLink), fixt!(Signature))],
             link_removes: vec![(fixt!(DeleteLink), fixt!(Signature))],
+            continuation: None,
         };
 
         let test_1_clone = test_1.clone();
@@ -308,7 +554,7 @@ mod tests {
 
         assert_eq!(1, res.len());
 
-        for r in res {
+        for (_agent, r) in res {
             assert_eq!(r, test_1);
         }
 
@@ -1,3 +1,5 @@
+use holochain_error_kind::{kind_of_boxed, ErrorKind, HasErrorKind, KindedError};
+
 /// Error type for Holochain P2p.
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -26,6 +28,10 @@ pub enum HolochainP2pError {
     #[error("InvalidP2pMessage: {0}")]
     InvalidP2pMessage(String),
 
+    /// The cell is in offline mode
+    #[error("The cell is in offline mode")]
+    Offline,
+
     /// Other
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -66,7 +72,22 @@ impl From<HolochainP2pError> for kitsune_p2p::KitsuneP2pError {
             RoutingDnaError(dna) => Self::RoutingSpaceError(dna.to_kitsune()),
             RoutingAgentError(agent) => Self::RoutingAgentError(agent.to_kitsune()),
             OtherKitsuneP2pError(e) => e,
-            _ => Self::other(e),
+            // keep the kind so it can be recovered on the kitsune side
+            _ => Self::other(KindedError::new(e)),
+        }
+    }
+}
+
+impl HasErrorKind for HolochainP2pError {
+    fn error_kind(&self) -> ErrorKind {
+        use HolochainP2pError::*;
+        match self {
+            GhostError(_) | Offline => ErrorKind::Unavailable,
+            RoutingDnaError(_) | RoutingAgentError(_) => ErrorKind::NotFound,
+            OtherKitsuneP2pError(e) => e.error_kind(),
+            SerializedBytesError(_) => ErrorKind::Serialization,
+            InvalidP2pMessage(_) => ErrorKind::InvalidInput,
+            Other(e) => kind_of_boxed(e.as_ref()),
         }
     }
 }
@@ -93,6 +114,7 @@ impl From<&str> for HolochainP2pError {
 
 pub mod actor;
 pub mod event;
+pub mod recording;
 
 pub(crate) mod wire;
 
@@ -5,13 +5,48 @@ use crate::*;
 use holochain_zome_types::request::MetadataRequest;
 use holochain_zome_types::zome::FunctionName;
 
+pub use kitsune_p2p::actor::PeerReport;
+
+/// The outcome of one round of gossip with a peer.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GossipRound {
+    /// When the round started, in seconds since the UNIX epoch.
+    pub started_utc_epoch_s: i64,
+    /// How long the round took.
+    pub duration_ms: u64,
+    /// How many ops the peer held which we didn't.
+    pub ops_offered: u32,
+    /// How many of the offered ops we fetched from the peer and took.
+    pub ops_accepted: u32,
+    /// How many ops we held which the peer didn't, and sent it.
+    pub ops_sent: u32,
+    /// Everything that went wrong during the round.
+    pub errors: Vec<String>,
+}
+
+impl From<kitsune_p2p::actor::GossipRound> for GossipRound {
+    fn from(round: kitsune_p2p::actor::GossipRound) -> Self {
+        Self {
+            started_utc_epoch_s: round.started_utc_epoch_s,
+            duration_ms: round.duration_ms,
+            ops_offered: round.ops_offered,
+            ops_accepted: round.ops_accepted,
+            ops_sent: round.ops_sent,
+            errors: round.errors,
+        }
+    }
+}
+
 /// Request a validation package.
 pub struct GetValidationPackage {
     /// The dna_hash / space_hash context.
     pub dna_hash: DnaHash,
     /// The agent_id / agent_pub_key context.
     pub agent_pub_key: AgentPubKey,
-    // TODO - parameters
+    /// The author of the header, who assembles the package from their chain.
+    pub request_from: AgentPubKey,
+    /// The header which needs validating.
+    pub header_hash: HeaderHash,
 }
 
 #[derive(Clone, Debug)]
@@ -53,6 +88,17 @@ pub struct GetOptions {
     /// Return all live headers even if there is deletes.
     /// Useful for metadata calls.
     pub all_live_headers_with_metadata: bool,
+
+    /// [Remote]
+    /// Ask for entries as a [GetElementResponse::GetEntryCollapsed],
+    /// which the remote-end assembles in a single pass.
+    pub collapsed: bool,
+
+    /// [Remote]
+    /// Ask the remote-end to include its signed validation receipts, and
+    /// only return data as live once this many authorities have vouched
+    /// for it. Set to `0` to not ask for receipts.
+    pub min_validations: u8,
 }
 
 impl Default for GetOptions {
@@ -64,13 +110,18 @@ impl Default for GetOptions {
             race_timeout_ms: None,
             follow_redirects: true,
             all_live_headers_with_metadata: false,
+            collapsed: false,
+            min_validations: 0,
         }
     }
 }
 
 impl From<holochain_zome_types::entry::GetOptions> for GetOptions {
-    fn from(_: holochain_zome_types::entry::GetOptions) -> Self {
-        Self::default()
+    fn from(options: holochain_zome_types::entry::GetOptions) -> Self {
+        Self {
+            min_validations: options.min_validations,
+            ..Self::default()
+        }
     }
 }
 
@@ -125,6 +176,7 @@ impl Default for GetMetaOptions {
 /// Fields tagged with `[Network]` are network-level controls.
 /// Fields tagged with `[Remote]` are controls that will be forwarded to the
 /// remote agent processing this `GetLinks` request.
+/// Fields tagged with `[Local]` only change how the results are put together.
 pub struct GetLinksOptions {
     /// [Network]
     /// Timeout to await responses for aggregation.
@@ -132,11 +184,110 @@ pub struct GetLinksOptions {
     /// Note - if all requests time-out you will receive an empty result,
     /// not a timeout error.
     pub timeout_ms: Option<u64>,
+
+    /// [Remote]
+    /// Only fetch the links added after this one.
+    /// Set to the continuation of a response to fetch the next page.
+    pub after: Option<TimedHeaderHash>,
+
+    /// [Local]
+    /// Also return the links this agent has authored, even if they haven't
+    /// been integrated yet, including those committed earlier in the same
+    /// zome call. Set to `false` to only see what the DHT holds.
+    pub include_authored: bool,
+
+    /// [Local]
+    /// Stop fetching further pages once this many link adds have been
+    /// fetched. Set to `None` to fetch every page the authorities hold.
+    pub max_links: Option<usize>,
 }
 
 impl Default for GetLinksOptions {
     fn default() -> Self {
-        Self { timeout_ms: None }
+        Self {
+            timeout_ms: None,
+            after: None,
+            include_authored: true,
+            max_links: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Get an agent's activity from the DHT.
+/// Fields tagged with `[Network]` are network-level controls.
+pub struct GetActivityOptions {
+    /// [Network]
+    /// How many remote nodes should we make requests of / aggregate.
+    /// Set to `None` for a default "best-effort".
+    pub remote_agent_count: Option<u8>,
+
+    /// [Network]
+    /// Timeout to await responses for aggregation.
+    /// Set to `None` for a default "best-effort".
+    /// Note - if all requests time-out you will receive an empty result,
+    /// not a timeout error.
+    pub timeout_ms: Option<u64>,
+}
+
+impl Default for GetActivityOptions {
+    fn default() -> Self {
+        Self {
+            remote_agent_count: None,
+            timeout_ms: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Get counts of the ops held by authorities.
+/// Fields tagged with `[Network]` are network-level controls.
+pub struct GetDhtOpCountsOptions {
+    /// [Network]
+    /// How many remote nodes should we make requests of / aggregate.
+    /// Set to `None` for a default "best-effort".
+    pub remote_agent_count: Option<u8>,
+
+    /// [Network]
+    /// Timeout to await responses for aggregation.
+    /// Set to `None` for a default "best-effort".
+    /// Note - if all requests time-out you will receive an empty result,
+    /// not a timeout error.
+    pub timeout_ms: Option<u64>,
+}
+
+impl Default for GetDhtOpCountsOptions {
+    fn default() -> Self {
+        Self {
+            remote_agent_count: None,
+            timeout_ms: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Ask authorities which ops they hold.
+/// Fields tagged with `[Network]` are network-level controls.
+pub struct HoldsOpsOptions {
+    /// [Network]
+    /// How many remote nodes should we make requests of / aggregate.
+    /// Set to `None` for a default "best-effort".
+    pub remote_agent_count: Option<u8>,
+
+    /// [Network]
+    /// Timeout to await responses for aggregation.
+    /// Set to `None` for a default "best-effort".
+    /// Note - if all requests time-out you will receive an empty result,
+    /// not a timeout error.
+    pub timeout_ms: Option<u64>,
+}
+
+impl Default for HoldsOpsOptions {
+    fn default() -> Self {
+        Self {
+            remote_agent_count: None,
+            timeout_ms: None,
+        }
     }
 }
 
@@ -158,6 +309,7 @@ ghost_actor::ghost_chan! {
             zome_name: ZomeName,
             fn_name: FunctionName,
             cap: Option<CapSecret>,
+            provenance: Option<CallProvenance>,
             request: SerializedBytes,
         ) -> SerializedBytes;
 
@@ -168,19 +320,24 @@ ghost_actor::ghost_chan! {
             request_validation_receipt: bool,
             dht_hash: holo_hash::AnyDhtHash,
             ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+            validation_packages: Vec<(
+            holo_hash::HeaderHash,
+                holochain_zome_types::validate::ValidationPackage,
+            )>,
             timeout_ms: Option<u64>,
         ) -> ();
 
         /// Request a validation package.
-        fn get_validation_package(input: GetValidationPackage) -> (); // TODO - proper return type
+        fn get_validation_package(input: GetValidationPackage) -> ValidationPackageResponse;
 
         /// Get an entry from the DHT.
+        /// Each response is paired with the agent who sent it.
         fn get(
             dna_hash: DnaHash,
             from_agent: AgentPubKey,
             dht_hash: holo_hash::AnyDhtHash,
             options: GetOptions,
-        ) -> Vec<GetElementResponse>;
+        ) -> Vec<(AgentPubKey, GetElementResponse)>;
 
         /// Get metadata from the DHT.
         fn get_meta(
@@ -191,15 +348,128 @@ ghost_actor::ghost_chan! {
         ) -> Vec<MetadataSet>;
 
         /// Get links from the DHT.
+        /// Each response is paired with the agent who sent it.
         fn get_links(
             dna_hash: DnaHash,
             from_agent: AgentPubKey,
             link_key: WireLinkMetaKey,
             options: GetLinksOptions,
-        ) -> Vec<GetLinksResponse>;
+        ) -> Vec<(AgentPubKey, GetLinksResponse)>;
+
+        /// Get the signed headers of an agent's source chain from its
+        /// agent activity authorities.
+        /// Each response is paired with the agent who sent it.
+        fn get_agent_activity(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            agent: AgentPubKey,
+            range: ChainRange,
+            options: GetActivityOptions,
+        ) -> Vec<(AgentPubKey, AgentActivityResponse)>;
+
+        /// Ask an agent's activity authorities to observe the head of its
+        /// source chain, and sign what they saw.
+        /// Authorities which hold none of the chain send nothing.
+        fn observe_chain_head(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            agent: AgentPubKey,
+            options: GetActivityOptions,
+        ) -> Vec<SignedChainHeadObservation>;
+
+        /// Ask the authorities for a basis how many ops of each type they
+        /// hold within the arc of `half_length` around the basis,
+        /// integrated within a time window.
+        /// Each response is paired with the agent who sent it.
+        fn get_dht_op_counts(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+            half_length: u32,
+            since: holochain_types::Timestamp,
+            until: holochain_types::Timestamp,
+            options: GetDhtOpCountsOptions,
+        ) -> Vec<(AgentPubKey, DhtOpCounts)>;
+
+        /// Ask the authorities for a basis which of these ops, all stored at
+        /// that basis, they hold.
+        /// Each response is paired with the agent who sent it.
+        fn holds_ops(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+            op_hashes: Vec<holo_hash::DhtOpHash>,
+            options: HoldsOpsOptions,
+        ) -> Vec<(AgentPubKey, Vec<holo_hash::DhtOpHash>)>;
 
         /// Send a validation receipt to a remote node.
         fn send_validation_receipt(dna_hash: DnaHash, to_agent: AgentPubKey, from_agent: AgentPubKey, receipt: SerializedBytes) -> ();
+
+        /// Ask the authorities for a basis to send a notification
+        /// whenever they integrate ops at it.
+        fn subscribe(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+            timeout_ms: Option<u64>,
+        ) -> ();
+
+        /// Ask the authorities for a basis to stop sending notifications.
+        fn unsubscribe(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+            timeout_ms: Option<u64>,
+        ) -> ();
+
+        /// Tell a subscriber which ops we have integrated at a basis.
+        fn send_basis_changed(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+            op_hashes: Vec<holo_hash::DhtOpHash>,
+        ) -> ();
+
+        /// Push an op we hold to the authorities closest to its basis,
+        /// before we stop holding it ourselves.
+        /// Returns the agents which acknowledged receiving it in time.
+        fn hand_off(
+            dna_hash: DnaHash,
+            from_agent: AgentPubKey,
+            dht_hash: holo_hash::AnyDhtHash,
+            op_hash: holo_hash::DhtOpHash,
+            op: holochain_types::dht_op::DhtOp,
+            timeout_ms: Option<u64>,
+        ) -> Vec<AgentPubKey>;
+
+        /// Start recording every network event received by this dna/agent
+        /// pair to the file at `path`, or stop recording if `None`.
+        fn record_events(dna_hash: DnaHash, agent_pub_key: AgentPubKey, path: Option<std::path::PathBuf>) -> ();
+
+        /// Feed the events from a recording back through the event handlers
+        /// of this dna/agent pair, in order. Returns how many were replayed.
+        fn replay_events(dna_hash: DnaHash, agent_pub_key: AgentPubKey, path: std::path::PathBuf) -> usize;
+
+        /// Report a remote agent for misbehaving, so it's less likely to be
+        /// asked for data or gossiped with.
+        fn report_peer(dna_hash: DnaHash, agent_pub_key: AgentPubKey, report: PeerReport) -> ();
+
+        /// The current reputation of every agent that has been reported
+        /// for this dna, from 1.0 (nothing against it) towards 0.0.
+        fn peer_reputations(dna_hash: DnaHash) -> Vec<(AgentPubKey, f64)>;
+
+        /// What the storage arc of a dna/agent pair would be resized to,
+        /// to reach its nearest neighbors. Returns the current arc and the
+        /// resized one, without changing anything.
+        fn resized_arc(dna_hash: DnaHash, agent_pub_key: AgentPubKey) -> (kitsune_p2p::dht_arc::DhtArc, kitsune_p2p::dht_arc::DhtArc);
+
+        /// Set the storage arc of a dna/agent pair.
+        fn set_arc(dna_hash: DnaHash, agent_pub_key: AgentPubKey, arc: kitsune_p2p::dht_arc::DhtArc) -> ();
+
+        /// The most recent gossip rounds with each peer for this dna,
+        /// oldest first.
+        fn gossip_rounds(dna_hash: DnaHash) -> Vec<(AgentPubKey, Vec<GossipRound>)>;
     }
 }
 
@@ -223,6 +493,7 @@ impl HolochainP2pRefToCell for HolochainP2pRef {
             sender: self,
             dna_hash: Arc::new(dna_hash),
             from_agent: Arc::new(from_agent),
+            offline: Arc::new(AtomicBool::new(false)),
         }
     }
 
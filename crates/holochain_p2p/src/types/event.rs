@@ -12,6 +12,12 @@ pub struct GetOptions {
     /// Return all live headers even if there is deletes.
     /// Useful for metadata calls.
     pub all_live_headers_with_metadata: bool,
+    /// Return entries as a collapsed response.
+    #[serde(default)]
+    pub collapsed: bool,
+    /// Include signed validation receipts for the ops returned.
+    #[serde(default)]
+    pub with_receipts: bool,
 }
 
 impl From<&actor::GetOptions> for GetOptions {
@@ -19,6 +25,8 @@ impl From<&actor::GetOptions> for GetOptions {
         Self {
             follow_redirects: a.follow_redirects,
             all_live_headers_with_metadata: a.all_live_headers_with_metadata,
+            collapsed: a.collapsed,
+            with_receipts: a.min_validations > 0,
         }
     }
 }
@@ -35,11 +43,18 @@ impl From<&actor::GetMetaOptions> for GetMetaOptions {
 
 /// GetLinks options help control how the get is processed at various levels.
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
-pub struct GetLinksOptions {}
+pub struct GetLinksOptions {
+    /// Only return the links added after this one.
+    /// Taken from the continuation of the previous page.
+    #[serde(default)]
+    pub after: Option<TimedHeaderHash>,
+}
 
 impl From<&actor::GetLinksOptions> for GetLinksOptions {
-    fn from(_a: &actor::GetLinksOptions) -> Self {
-        Self {}
+    fn from(a: &actor::GetLinksOptions) -> Self {
+        Self {
+            after: a.after.clone(),
+        }
     }
 }
 
@@ -48,6 +63,8 @@ ghost_actor::ghost_chan! {
     /// the HolochainP2p actor.
     pub chan HolochainP2pEvent<super::HolochainP2pError> {
         /// A remote node is attempting to make a remote call on us.
+        /// If the call was made from a zome function, `provenance` is the
+        /// path which led to it, as reported by the caller.
         fn call_remote(
             dna_hash: DnaHash,
             to_agent: AgentPubKey,
@@ -55,6 +72,7 @@ ghost_actor::ghost_chan! {
             zome_name: ZomeName,
             fn_name: FunctionName,
             cap: Option<CapSecret>,
+            provenance: Option<CallProvenance>,
             request: SerializedBytes,
         ) -> SerializedBytes;
 
@@ -66,6 +84,10 @@ ghost_actor::ghost_chan! {
             request_validation_receipt: bool,
             dht_hash: holo_hash::AnyDhtHash,
             ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+            validation_packages: Vec<(
+            holo_hash::HeaderHash,
+                holochain_zome_types::validate::ValidationPackage,
+            )>,
         ) -> ();
 
         /// A remote node is requesting a validation package.
@@ -74,8 +96,8 @@ ghost_actor::ghost_chan! {
             dna_hash: DnaHash,
             // The agent_id / agent_pub_key context.
             to_agent: AgentPubKey,
-            // TODO - parameters
-        ) -> (); // TODO - proper return type
+            header_hash: HeaderHash,
+        ) -> ValidationPackageResponse;
 
         /// A remote node is requesting entry data from us.
         fn get(
@@ -101,6 +123,40 @@ ghost_actor::ghost_chan! {
             options: GetLinksOptions,
         ) -> GetLinksResponse;
 
+        /// A remote node is requesting the headers of an agent's source
+        /// chain from us.
+        fn get_agent_activity(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            agent: AgentPubKey,
+            range: ChainRange,
+        ) -> AgentActivityResponse;
+
+        /// A remote node is asking us to sign an observation of the head
+        /// of an agent's source chain.
+        fn observe_chain_head(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            agent: AgentPubKey,
+        ) -> ChainHeadResponse;
+
+        /// A remote node is asking how many ops of each type we hold
+        /// within an arc, integrated within a time window.
+        fn get_dht_op_counts(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            dht_arc: kitsune_p2p::dht_arc::DhtArc,
+            since: holochain_types::Timestamp,
+            until: holochain_types::Timestamp,
+        ) -> DhtOpCounts;
+
+        /// A remote node is asking which of these ops we hold.
+        fn holds_ops(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            op_hashes: Vec<holo_hash::DhtOpHash>,
+        ) -> Vec<holo_hash::DhtOpHash>;
+
         /// A remote node has sent us a validation receipt.
         fn validation_receipt_received(
             dna_hash: DnaHash,
@@ -108,6 +164,32 @@ ghost_actor::ghost_chan! {
             receipt: SerializedBytes,
         ) -> ();
 
+        /// A remote node wants to be told when ops integrate at a basis
+        /// we are an authority for.
+        fn subscribe(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+        ) -> ();
+
+        /// A remote node no longer wants to be told about a basis.
+        fn unsubscribe(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+        ) -> ();
+
+        /// An authority we subscribed to has integrated ops at a basis.
+        fn basis_changed(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            from_agent: AgentPubKey,
+            basis: holo_hash::AnyDhtHash,
+            op_hashes: Vec<holo_hash::DhtOpHash>,
+        ) -> ();
+
         /// The p2p module wishes to query our DhtOpHash store.
         fn fetch_op_hashes_for_constraints(
             dna_hash: DnaHash,
@@ -133,6 +215,15 @@ ghost_actor::ghost_chan! {
             // The data to sign.
             data: Vec<u8>,
         ) -> Signature;
+
+        /// The network for this agent, or for its whole space, died and was restarted.
+        /// The agent has already been re-joined.
+        fn network_restarted(
+            dna_hash: DnaHash,
+            to_agent: AgentPubKey,
+            // Why the network died.
+            reason: String,
+        ) -> ();
     }
 }
 
@@ -146,10 +237,18 @@ macro_rules! match_p2p_evt {
             HolochainP2pEvent::Get { $i, .. } => { $($t)* }
             HolochainP2pEvent::GetMeta { $i, .. } => { $($t)* }
             HolochainP2pEvent::GetLinks { $i, .. } => { $($t)* }
+            HolochainP2pEvent::GetAgentActivity { $i, .. } => { $($t)* }
+            HolochainP2pEvent::ObserveChainHead { $i, .. } => { $($t)* }
+            HolochainP2pEvent::GetDhtOpCounts { $i, .. } => { $($t)* }
+            HolochainP2pEvent::HoldsOps { $i, .. } => { $($t)* }
             HolochainP2pEvent::ValidationReceiptReceived { $i, .. } => { $($t)* }
+            HolochainP2pEvent::Subscribe { $i, .. } => { $($t)* }
+            HolochainP2pEvent::Unsubscribe { $i, .. } => { $($t)* }
+            HolochainP2pEvent::BasisChanged { $i, .. } => { $($t)* }
             HolochainP2pEvent::FetchOpHashesForConstraints { $i, .. } => { $($t)* }
             HolochainP2pEvent::FetchOpHashData { $i, .. } => { $($t)* }
             HolochainP2pEvent::SignNetworkData { $i, .. } => { $($t)* }
+            HolochainP2pEvent::NetworkRestarted { $i, .. } => { $($t)* }
         }
     };
 }
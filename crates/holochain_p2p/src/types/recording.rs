@@ -0,0 +1,161 @@
+//! Recording the network events a cell receives, so that they can later be
+//! replayed through the same handlers to reproduce bugs seen in the field.
+//!
+//! A recording is a file of length-prefixed, msgpack encoded
+//! [RecordedEvent]s, in the order they were received.
+
+use crate::{actor::HolochainP2pResult, *};
+use ghost_actor::dependencies::tracing;
+use holochain_types::Timestamp;
+use std::{
+    convert::TryInto,
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// An incoming event from kitsune, as it was received
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub struct RecordedEvent {
+    /// When the event was received
+    pub received_at: Timestamp,
+    /// The agent who sent it
+    pub from_agent: AgentPubKey,
+    /// The event's payload
+    pub kind: RecordedEventKind,
+}
+
+/// The payload of a [RecordedEvent]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", content = "content")]
+pub enum RecordedEventKind {
+    /// A request which expected a response
+    Call(#[serde(with = "serde_bytes")] Vec<u8>),
+    /// A message which expected no response
+    Notify(#[serde(with = "serde_bytes")] Vec<u8>),
+    /// An op received through gossip
+    Gossip {
+        /// The hash of the op
+        op_hash: DhtOpHash,
+        /// The encoded op
+        #[serde(with = "serde_bytes")]
+        op_data: Vec<u8>,
+    },
+}
+
+impl RecordedEvent {
+    /// An event received just now
+    pub fn now(from_agent: AgentPubKey, kind: RecordedEventKind) -> Self {
+        Self {
+            received_at: Timestamp::now(),
+            from_agent,
+            kind,
+        }
+    }
+}
+
+/// Appends events to a recording
+pub struct EventRecorder {
+    path: PathBuf,
+    file: File,
+}
+
+impl EventRecorder {
+    /// Start recording to `path`, appending if it already exists
+    pub fn open(path: PathBuf) -> HolochainP2pResult<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(HolochainP2pError::other)?;
+        Ok(Self { path, file })
+    }
+
+    /// Where this recorder is writing to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an event. Each event is flushed as it's written, so that a
+    /// recording survives the conductor crashing.
+    pub fn record(&mut self, event: RecordedEvent) -> HolochainP2pResult<()> {
+        let bytes: Vec<u8> = UnsafeBytes::from(SerializedBytes::try_from(event)?).into();
+        let len = bytes.len() as u32;
+        self.file
+            .write_all(&len.to_be_bytes())
+            .and_then(|_| self.file.write_all(&bytes))
+            .and_then(|_| self.file.flush())
+            .map_err(HolochainP2pError::other)
+    }
+}
+
+/// Read every event from a recording, in the order they were received.
+/// A partially written final event, e.g. from a crash, is ignored.
+pub fn read_recorded_events(path: &Path) -> HolochainP2pResult<Vec<RecordedEvent>> {
+    let mut data = Vec::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_end(&mut data))
+        .map_err(HolochainP2pError::other)?;
+
+    let mut events = Vec::new();
+    let mut rest = &data[..];
+    while rest.len() >= 4 {
+        let (len, tail) = rest.split_at(4);
+        let len = u32::from_be_bytes(len.try_into().expect("split at 4")) as usize;
+        if tail.len() < len {
+            break;
+        }
+        let (event, tail) = tail.split_at(len);
+        let event: SerializedBytes = UnsafeBytes::from(event.to_vec()).into();
+        events.push(event.try_into()?);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        tracing::warn!(
+            ?path,
+            trailing_bytes = rest.len(),
+            "Ignoring incomplete event at the end of a recording"
+        );
+    }
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::*;
+
+    #[test]
+    fn recording_round_trip() {
+        let tmp = tempdir::TempDir::new("holochain_p2p_recording").unwrap();
+        let path = tmp.path().join("events");
+        let events = vec![
+            RecordedEvent::now(fixt!(AgentPubKey), RecordedEventKind::Call(vec![1, 2, 3])),
+            RecordedEvent::now(fixt!(AgentPubKey), RecordedEventKind::Notify(vec![4])),
+            RecordedEvent::now(
+                fixt!(AgentPubKey),
+                RecordedEventKind::Gossip {
+                    op_hash: fixt!(DhtOpHash),
+                    op_data: vec![5, 6],
+                },
+            ),
+        ];
+
+        {
+            let mut recorder = EventRecorder::open(path.clone()).unwrap();
+            for event in events.clone() {
+                recorder.record(event).unwrap();
+            }
+        }
+        // A crash part way through writing the length of another event
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&[0, 0])
+            .unwrap();
+
+        assert_eq!(read_recorded_events(&path).unwrap(), events);
+    }
+}
@@ -19,6 +19,10 @@ impl WireDhtOpData {
     }
 }
 
+/// The response to a [WireMessage::HoldsOps]
+#[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
+pub(crate) struct WireHeldOps(pub Vec<holo_hash::DhtOpHash>);
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
 #[serde(tag = "type", content = "content")]
 pub(crate) enum WireMessage {
@@ -26,6 +30,9 @@ pub(crate) enum WireMessage {
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        /// The path which led to the call, if a zome function made it
+        #[serde(default)]
+        provenance: Option<CallProvenance>,
         #[serde(with = "serde_bytes")]
         data: Vec<u8>,
     },
@@ -33,6 +40,12 @@ pub(crate) enum WireMessage {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        /// Packages for the ops' headers whose entry defs ask for one
+        #[serde(default)]
+        validation_packages: Vec<(
+            holo_hash::HeaderHash,
+            holochain_zome_types::validate::ValidationPackage,
+        )>,
     },
     ValidationReceipt {
         #[serde(with = "serde_bytes")]
@@ -50,6 +63,40 @@ pub(crate) enum WireMessage {
         link_key: WireLinkMetaKey,
         options: event::GetLinksOptions,
     },
+    GetAgentActivity {
+        agent: AgentPubKey,
+        range: ChainRange,
+    },
+    /// Ask an agent activity authority to sign what it sees at the head
+    /// of the agent's chain
+    ObserveChainHead {
+        agent: AgentPubKey,
+    },
+    GetDhtOpCounts {
+        center_loc: u32,
+        half_length: u32,
+        since: holochain_types::Timestamp,
+        until: holochain_types::Timestamp,
+    },
+    GetValidationPackage {
+        header_hash: HeaderHash,
+    },
+    /// Ask an authority which of these ops it holds
+    HoldsOps {
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    },
+    /// Ask the authorities for a basis to tell us when ops integrate there
+    Subscribe {
+        basis: holo_hash::AnyDhtHash,
+    },
+    Unsubscribe {
+        basis: holo_hash::AnyDhtHash,
+    },
+    /// Sent by an authority to its subscribers for a basis
+    BasisChanged {
+        basis: holo_hash::AnyDhtHash,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    },
 }
 
 impl WireMessage {
@@ -66,12 +113,14 @@ impl WireMessage {
         zome_name: ZomeName,
         fn_name: FunctionName,
         cap: Option<CapSecret>,
+        provenance: Option<CallProvenance>,
         request: SerializedBytes,
     ) -> WireMessage {
         Self::CallRemote {
             zome_name,
             fn_name,
             cap,
+            provenance,
             data: UnsafeBytes::from(request).into(),
         }
     }
@@ -80,11 +129,16 @@ impl WireMessage {
         request_validation_receipt: bool,
         dht_hash: holo_hash::AnyDhtHash,
         ops: Vec<(holo_hash::DhtOpHash, holochain_types::dht_op::DhtOp)>,
+        validation_packages: Vec<(
+            holo_hash::HeaderHash,
+            holochain_zome_types::validate::ValidationPackage,
+        )>,
     ) -> WireMessage {
         Self::Publish {
             request_validation_receipt,
             dht_hash,
             ops,
+            validation_packages,
         }
     }
 
@@ -108,4 +162,186 @@ impl WireMessage {
     pub fn get_links(link_key: WireLinkMetaKey, options: event::GetLinksOptions) -> WireMessage {
         Self::GetLinks { link_key, options }
     }
+
+    pub fn get_agent_activity(agent: AgentPubKey, range: ChainRange) -> WireMessage {
+        Self::GetAgentActivity { agent, range }
+    }
+
+    pub fn observe_chain_head(agent: AgentPubKey) -> WireMessage {
+        Self::ObserveChainHead { agent }
+    }
+
+    pub fn get_dht_op_counts(
+        dht_arc: dht_arc::DhtArc,
+        since: holochain_types::Timestamp,
+        until: holochain_types::Timestamp,
+    ) -> WireMessage {
+        Self::GetDhtOpCounts {
+            center_loc: dht_arc.center_loc.into(),
+            half_length: dht_arc.half_length,
+            since,
+            until,
+        }
+    }
+
+    pub fn get_validation_package(header_hash: HeaderHash) -> WireMessage {
+        Self::GetValidationPackage { header_hash }
+    }
+
+    pub fn holds_ops(op_hashes: Vec<holo_hash::DhtOpHash>) -> WireMessage {
+        Self::HoldsOps { op_hashes }
+    }
+
+    pub fn subscribe(basis: holo_hash::AnyDhtHash) -> WireMessage {
+        Self::Subscribe { basis }
+    }
+
+    pub fn unsubscribe(basis: holo_hash::AnyDhtHash) -> WireMessage {
+        Self::Unsubscribe { basis }
+    }
+
+    pub fn basis_changed(
+        basis: holo_hash::AnyDhtHash,
+        op_hashes: Vec<holo_hash::DhtOpHash>,
+    ) -> WireMessage {
+        Self::BasisChanged { basis, op_hashes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_types::{link::WireLinkMetaKey, prop::*};
+    use holochain_zome_types::{provenance::CallHop, validate::ValidationPackage};
+    use proptest::{collection::vec, prelude::*};
+
+    fn wire_link_meta_key() -> impl Strategy<Value = WireLinkMetaKey> {
+        prop_oneof![
+            entry_hash().prop_map(WireLinkMetaKey::Base),
+            (entry_hash(), any::<u8>()).prop_map(|(b, z)| WireLinkMetaKey::BaseZome(b, z.into())),
+            (entry_hash(), any::<u8>(), link_tag())
+                .prop_map(|(b, z, t)| WireLinkMetaKey::BaseZomeTag(b, z.into(), t)),
+            (entry_hash(), any::<u8>(), link_tag(), header_hash())
+                .prop_map(|(b, z, t, h)| WireLinkMetaKey::Full(b, z.into(), t, h)),
+        ]
+    }
+
+    fn call_provenance() -> impl Strategy<Value = CallProvenance> {
+        (
+            agent_pub_key(),
+            vec((agent_pub_key(), dna_hash(), ".{0,32}", ".{0,32}"), 0..4),
+        )
+            .prop_map(|(origin, hops)| CallProvenance {
+                origin,
+                hops: hops
+                    .into_iter()
+                    .map(|(agent, dna_hash, zome_name, fn_name)| CallHop {
+                        agent,
+                        dna_hash,
+                        zome_name: zome_name.into(),
+                        fn_name: fn_name.into(),
+                    })
+                    .collect(),
+            })
+    }
+
+    fn wire_message() -> impl Strategy<Value = WireMessage> {
+        prop_oneof![
+            (
+                ".{0,32}",
+                ".{0,32}",
+                proptest::option::of(cap_secret()),
+                proptest::option::of(call_provenance()),
+                vec(any::<u8>(), 0..512)
+            )
+                .prop_map(|(zome_name, fn_name, cap, provenance, data)| {
+                    WireMessage::CallRemote {
+                        zome_name: zome_name.into(),
+                        fn_name: fn_name.into(),
+                        cap,
+                        provenance,
+                        data,
+                    }
+                }),
+            (
+                any::<bool>(),
+                any_dht_hash(),
+                vec((dht_op_hash(), dht_op()), 0..4),
+                vec((header_hash(), Just(ValidationPackage::new(vec![]))), 0..2)
+            )
+                .prop_map(|(r, h, ops, packages)| WireMessage::publish(r, h, ops, packages)),
+            vec(any::<u8>(), 0..512).prop_map(|receipt| WireMessage::ValidationReceipt { receipt }),
+            (
+                any_dht_hash(),
+                any::<bool>(),
+                any::<bool>(),
+                any::<bool>(),
+                any::<bool>()
+            )
+                .prop_map(|(dht_hash, f, a, c, r)| {
+                    WireMessage::get(
+                        dht_hash,
+                        event::GetOptions {
+                            follow_redirects: f,
+                            all_live_headers_with_metadata: a,
+                            collapsed: c,
+                            with_receipts: r,
+                        },
+                    )
+                }),
+            any_dht_hash().prop_map(|h| WireMessage::get_meta(h, event::GetMetaOptions {})),
+            wire_link_meta_key()
+                .prop_map(|k| WireMessage::get_links(k, event::GetLinksOptions { after: None })),
+            (
+                agent_pub_key(),
+                any::<u32>(),
+                proptest::option::of(any::<u32>())
+            )
+                .prop_map(|(agent, start_seq, end_seq)| {
+                    WireMessage::get_agent_activity(agent, ChainRange { start_seq, end_seq })
+                }),
+            agent_pub_key().prop_map(WireMessage::observe_chain_head),
+            (any::<u32>(), any::<u32>(), any::<i64>(), any::<i64>()).prop_map(
+                |(center_loc, half_length, since, until)| WireMessage::GetDhtOpCounts {
+                    center_loc,
+                    half_length,
+                    since: holochain_types::Timestamp(since, 0),
+                    until: holochain_types::Timestamp(until, 0),
+                }
+            ),
+            header_hash().prop_map(WireMessage::get_validation_package),
+            vec(dht_op_hash(), 0..4).prop_map(WireMessage::holds_ops),
+            any_dht_hash().prop_map(WireMessage::subscribe),
+            any_dht_hash().prop_map(WireMessage::unsubscribe),
+            (any_dht_hash(), vec(dht_op_hash(), 0..4))
+                .prop_map(|(basis, op_hashes)| WireMessage::basis_changed(basis, op_hashes)),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn wire_message_round_trip(msg in wire_message()) {
+            let bytes = msg.encode().unwrap();
+            let decoded = WireMessage::decode(bytes.clone()).unwrap();
+            prop_assert_eq!(decoded.encode().unwrap(), bytes);
+        }
+
+        #[test]
+        fn wire_dht_op_data_round_trip(
+            from_agent in agent_pub_key(),
+            dht_hash in any_dht_hash(),
+            op_data in dht_op(),
+        ) {
+            let data = WireDhtOpData { from_agent, dht_hash, op_data };
+            let bytes = data.encode().unwrap();
+            let decoded = WireDhtOpData::decode(bytes.clone()).unwrap();
+            prop_assert_eq!(decoded.encode().unwrap(), bytes);
+        }
+
+        #[test]
+        fn arbitrary_bytes_never_panic(bytes in vec(any::<u8>(), 0..1024)) {
+            let _ = WireMessage::decode(bytes.clone());
+            let _ = WireDhtOpData::decode(bytes);
+        }
+    }
 }
@@ -21,6 +21,12 @@ pub trait KeystoreSenderExt {
 
     /// Generate a signature for a given blob of binary data.
     fn sign(&self, input: SignInput) -> KeystoreApiFuture<Signature>;
+
+    /// Check that the keystore is answering requests.
+    fn check_reachable(&self) -> KeystoreApiFuture<()>;
+
+    /// Check that the keystore can read its keys, i.e. it is unlocked.
+    fn check_unlocked(&self) -> KeystoreApiFuture<()>;
 }
 
 impl KeystoreSenderExt for KeystoreSender {
@@ -48,4 +54,26 @@ impl KeystoreSenderExt for KeystoreSender {
         .boxed()
         .into()
     }
+
+    fn check_reachable(&self) -> KeystoreApiFuture<()> {
+        use lair_keystore_api::actor::LairClientApiSender;
+        let fut = self.lair_get_server_info();
+        async move {
+            fut.await?;
+            Ok(())
+        }
+        .boxed()
+        .into()
+    }
+
+    fn check_unlocked(&self) -> KeystoreApiFuture<()> {
+        use lair_keystore_api::actor::LairClientApiSender;
+        let fut = self.lair_get_last_entry_index();
+        async move {
+            fut.await?;
+            Ok(())
+        }
+        .boxed()
+        .into()
+    }
 }
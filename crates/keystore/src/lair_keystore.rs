@@ -4,10 +4,77 @@ use crate::*;
 use ghost_actor::dependencies::futures::{future::FutureExt, stream::StreamExt};
 use lair_keystore_api::actor::*;
 use lair_keystore_api::*;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// The passphrase lair asks for to unlock its keys.
+///
+/// It can be set after the keystore is spawned. Until then the keystore is
+/// locked, and any of lair's requests for the passphrase wait for it.
+#[derive(Clone)]
+pub struct UnlockPassphrase {
+    tx: Arc<watch::Sender<Option<String>>>,
+    rx: watch::Receiver<Option<String>>,
+}
+
+impl UnlockPassphrase {
+    /// A passphrase which hasn't been given yet
+    pub fn locked() -> Self {
+        let (tx, rx) = watch::channel(None);
+        Self {
+            tx: Arc::new(tx),
+            rx,
+        }
+    }
+
+    /// A passphrase which is known up front
+    pub fn new(passphrase: String) -> Self {
+        let this = Self::locked();
+        this.set(passphrase);
+        this
+    }
+
+    /// A placeholder for keystores which aren't protected by a passphrase
+    pub fn blank() -> Self {
+        Self::new("[blank-passphrase]".to_string())
+    }
+
+    /// Give the passphrase, unlocking the keystore
+    pub fn set(&self, passphrase: String) {
+        // can't fail because we hold a receiver
+        let _ = self.tx.broadcast(Some(passphrase));
+    }
+
+    /// Take back a passphrase which didn't unlock the keystore,
+    /// so lair's next request waits for it to be given again
+    pub fn clear(&self) {
+        // can't fail because we hold a receiver
+        let _ = self.tx.broadcast(None);
+    }
+
+    /// Whether the passphrase is still to be given
+    pub fn is_locked(&self) -> bool {
+        self.rx.borrow().is_none()
+    }
+
+    /// Wait until the passphrase is given
+    async fn wait(&self) -> String {
+        let mut rx = self.rx.clone();
+        while let Some(passphrase) = rx.recv().await {
+            if let Some(passphrase) = passphrase {
+                return passphrase;
+            }
+        }
+        unreachable!("We hold the sender, so the channel can't close")
+    }
+}
 
 /// Spawn a new keystore backed by lair_keystore_client.
+///
+/// Lair is unlocked with the passphrase once it's given.
 pub async fn spawn_lair_keystore(
     lair_dir: Option<&std::path::Path>,
+    passphrase: UnlockPassphrase,
 ) -> KeystoreApiResult<KeystoreSender> {
     let mut config = Config::builder();
     if let Some(lair_dir) = lair_dir {
@@ -16,12 +83,12 @@ pub async fn spawn_lair_keystore(
     let config = config.build();
     let (api, mut evt) = lair_keystore_client::assert_running_lair_and_connect(config).await?;
 
-    // TODO - actual passphrase handling
     tokio::task::spawn(async move {
         while let Some(r) = evt.next().await {
             match r {
                 LairClientEvent::RequestUnlockPassphrase { respond, .. } => {
-                    respond.respond(Ok(async move { Ok("[blank-passphrase]".to_string()) }
+                    let passphrase = passphrase.clone();
+                    respond.respond(Ok(async move { Ok(passphrase.wait().await) }
                         .boxed()
                         .into()));
                 }
@@ -31,3 +98,36 @@ pub async fn spawn_lair_keystore(
 
     Ok(api)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn requests_wait_for_the_passphrase() {
+        let passphrase = UnlockPassphrase::locked();
+        assert!(passphrase.is_locked());
+
+        let waiting = tokio::task::spawn({
+            let passphrase = passphrase.clone();
+            async move { passphrase.wait().await }
+        });
+        passphrase.set("hunter2".to_string());
+        assert!(!passphrase.is_locked());
+        assert_eq!(waiting.await.unwrap(), "hunter2");
+
+        // once given it's answered straight away
+        assert_eq!(passphrase.wait().await, "hunter2");
+        assert!(!UnlockPassphrase::blank().is_locked());
+
+        // until it's taken back
+        passphrase.clear();
+        assert!(passphrase.is_locked());
+        let waiting = tokio::task::spawn({
+            let passphrase = passphrase.clone();
+            async move { passphrase.wait().await }
+        });
+        passphrase.set("correct horse".to_string());
+        assert_eq!(waiting.await.unwrap(), "correct horse");
+    }
+}
@@ -50,3 +50,20 @@ pub use agent_pubkey_ext::*;
 
 pub mod lair_keystore;
 pub mod test_keystore;
+
+#[cfg(test)]
+mod tests {
+    use holochain_crypto::DynCryptoBytes;
+    use holochain_zome_types::capability::CapSecret;
+    use zeroize::Zeroize;
+
+    fn assert_zeroize<T: Zeroize>() {}
+
+    /// Any type which can hold secret material must be able to wipe it.
+    /// Add new secret-bearing types here.
+    #[test]
+    fn secret_bearing_types_are_zeroize() {
+        assert_zeroize::<CapSecret>();
+        assert_zeroize::<DynCryptoBytes>();
+    }
+}
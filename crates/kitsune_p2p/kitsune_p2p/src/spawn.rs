@@ -8,6 +8,17 @@ use actor::*;
 pub async fn spawn_kitsune_p2p() -> KitsuneP2pResult<(
     ghost_actor::GhostSender<KitsuneP2p>,
     KitsuneP2pEventReceiver,
+)> {
+    let (sender, evt_recv, _driver) = spawn_kitsune_p2p_with_driver().await?;
+    Ok((sender, evt_recv))
+}
+
+/// Spawn a new KitsuneP2p actor. The returned JoinHandle resolves when the
+/// actor terminates, so that it can be supervised.
+pub async fn spawn_kitsune_p2p_with_driver() -> KitsuneP2pResult<(
+    ghost_actor::GhostSender<KitsuneP2p>,
+    KitsuneP2pEventReceiver,
+    tokio::task::JoinHandle<ghost_actor::GhostResult<()>>,
 )> {
     let (evt_send, evt_recv) = futures::channel::mpsc::channel(10);
     let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
@@ -18,11 +29,14 @@ pub async fn spawn_kitsune_p2p() -> KitsuneP2pResult<(
 
     let sender = channel_factory.create_channel::<KitsuneP2p>().await?;
 
-    tokio::task::spawn(builder.spawn(KitsuneP2pActor::new(
+    let driver = tokio::task::spawn(builder.spawn(KitsuneP2pActor::new(
         channel_factory,
-        internal_sender,
+        internal_sender.clone(),
         evt_send,
+        // until there is real networking, envelopes loop back
+        // to this endpoint's own agents
+        internal_sender,
     )?));
 
-    Ok((sender, evt_recv))
+    Ok((sender, evt_recv, driver))
 }
@@ -2,31 +2,66 @@
 
 use crate::{actor, actor::*, event::*, types::*};
 use futures::future::FutureExt;
-use kitsune_p2p_types::async_lazy::AsyncLazy;
+use ghost_actor::GhostControlSender;
+use kitsune_p2p_types::{
+    async_lazy::{AsyncTryLazy, RetryPolicy},
+    dht_arc::DhtArc,
+    restart::RestartCounter,
+};
 use std::{
     collections::{hash_map::Entry, HashMap},
     sync::Arc,
 };
 
+mod broadcast;
 mod gossip;
+mod gossip_metrics;
+mod initial_arc;
+mod mux;
+mod reputation;
 mod space;
 use ghost_actor::dependencies::tracing;
+use mux::LocalAgents;
 use space::*;
 
 ghost_actor::ghost_chan! {
     pub(crate) chan Internal<crate::KitsuneP2pError> {
         /// Register space event handler
         fn register_space_event_handler(recv: futures::channel::mpsc::Receiver<KitsuneP2pEvent>) -> ();
+
+        /// A space actor has terminated and should be restarted
+        fn space_terminated(space: Arc<KitsuneSpace>, reason: String) -> ();
+
+        /// An agent envelope arrived on one of this endpoint's connections
+        fn incoming_request(data: Vec<u8>) -> Vec<u8>;
+
+        /// Send an agent envelope out over this endpoint's connections
+        fn outgoing_request(data: Vec<u8>) -> Vec<u8>;
     }
 }
 
+/// A space which keeps failing is given up on after this many restarts
+const MAX_SPACE_RESTARTS: u32 = 10;
+
+/// A space which runs this long without failing starts its restart count over
+const SPACE_STABLE_AFTER: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
 pub(crate) struct KitsuneP2pActor {
     channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<Self>,
     #[allow(dead_code)]
     internal_sender: ghost_actor::GhostSender<Internal>,
     #[allow(dead_code)]
     evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
-    spaces: HashMap<Arc<KitsuneSpace>, AsyncLazy<ghost_actor::GhostSender<KitsuneP2p>>>,
+    spaces: HashMap<Arc<KitsuneSpace>, AsyncTryLazy<SpaceSenders>>,
+    /// The agents joined to each space, so incoming requests for agents
+    /// which aren't here are turned away, and so they can be re-joined if
+    /// the space actor has to be restarted
+    local_agents: LocalAgents,
+    restart_counts: HashMap<Arc<KitsuneSpace>, RestartCounter>,
+    /// Where outgoing envelopes are delivered. While we are in
+    /// "short-circuit-only" mode this is the endpoint itself,
+    /// so only agents joined here can be reached.
+    transport: ghost_actor::GhostSender<Internal>,
 }
 
 impl KitsuneP2pActor {
@@ -34,16 +69,58 @@ impl KitsuneP2pActor {
         channel_factory: ghost_actor::actor_builder::GhostActorChannelFactory<Self>,
         internal_sender: ghost_actor::GhostSender<Internal>,
         evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+        transport: ghost_actor::GhostSender<Internal>,
     ) -> KitsuneP2pResult<Self> {
         Ok(Self {
             channel_factory,
             internal_sender,
             evt_sender,
             spaces: HashMap::new(),
+            local_agents: LocalAgents::default(),
+            restart_counts: HashMap::new(),
+            transport,
         })
     }
 }
 
+/// Lazily spawn a space actor, routing its events through the top-level
+/// actor and watching it so that it is restarted if it terminates.
+/// Spawning is retried, and requests to the space fail if it never starts.
+fn spawn_supervised_space(
+    space: Arc<KitsuneSpace>,
+    internal_sender: ghost_actor::GhostSender<Internal>,
+) -> AsyncTryLazy<SpaceSenders> {
+    AsyncTryLazy::new(RetryPolicy::default(), move || {
+        let space = space.clone();
+        let internal_sender = internal_sender.clone();
+        async move {
+            let (senders, evt_recv, driver) =
+                spawn_space(space.clone(), internal_sender.clone()).await?;
+            internal_sender
+                .register_space_event_handler(evt_recv)
+                .await?;
+            tokio::task::spawn(supervise_space(space, driver, internal_sender));
+            KitsuneP2pResult::Ok(senders)
+        }
+    })
+}
+
+/// Wait for a space actor to terminate, then report why so it can be
+/// restarted, unless it was shut down because its last agent left.
+async fn supervise_space(
+    space: Arc<KitsuneSpace>,
+    driver: tokio::task::JoinHandle<ghost_actor::GhostResult<()>>,
+    internal_sender: ghost_actor::GhostSender<Internal>,
+) {
+    let reason = match driver.await {
+        Ok(Ok(())) => "space actor shut down".to_string(),
+        Ok(Err(e)) => format!("space actor errored: {}", e),
+        Err(e) => format!("space actor panicked: {}", e),
+    };
+    // if this fails the top-level actor is gone too, so there's nothing to restart
+    let _ = internal_sender.space_terminated(space, reason).await;
+}
+
 impl ghost_actor::GhostControlHandler for KitsuneP2pActor {}
 
 impl ghost_actor::GhostHandler<Internal> for KitsuneP2pActor {}
@@ -61,6 +138,91 @@ impl InternalHandler for KitsuneP2pActor {
         .boxed()
         .into())
     }
+
+    fn handle_space_terminated(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        reason: String,
+    ) -> InternalHandlerResult<()> {
+        if !self.spaces.contains_key(&space) {
+            tracing::debug!(?space, %reason, "space actor shut down after its agents left");
+            return Ok(async move { Ok(()) }.boxed().into());
+        }
+        tracing::error!(?space, %reason, "space actor terminated");
+        let restart_count = self
+            .restart_counts
+            .entry(space.clone())
+            .or_insert_with(|| RestartCounter::new(SPACE_STABLE_AFTER))
+            .restart(std::time::Instant::now());
+        if restart_count > MAX_SPACE_RESTARTS {
+            tracing::error!(?space, "space actor failed too many times, giving up");
+            if let Some(space_sender) = self.spaces.remove(&space) {
+                space_sender.cancel();
+            }
+            return Ok(async move { Ok(()) }.boxed().into());
+        }
+        tracing::warn!(?space, restart_count, "restarting space actor");
+
+        let space_sender = spawn_supervised_space(space.clone(), self.internal_sender.clone());
+        let space_sender_fut = space_sender.get();
+        if let Some(old) = self.spaces.insert(space.clone(), space_sender) {
+            old.cancel();
+        }
+        let agents = self.local_agents.agents(&space);
+        let evt_sender = self.evt_sender.clone();
+        Ok(async move {
+            let space_sender = space_sender_fut.await?.api;
+            // the new space starts out empty, so re-join everyone who was
+            // joined to the old one
+            for agent in agents.iter() {
+                space_sender.join(space.clone(), agent.clone()).await?;
+            }
+            evt_sender
+                .space_restarted(SpaceRestartedEvt {
+                    space,
+                    agents,
+                    reason,
+                    restart_count,
+                })
+                .await
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_incoming_request(&mut self, data: Vec<u8>) -> InternalHandlerResult<Vec<u8>> {
+        let mux::Opened {
+            space,
+            to_agent,
+            from_agent,
+            inner,
+        } = mux::open(data)?;
+        // turn away requests for agents which aren't here without
+        // bothering the space actor
+        if !self.local_agents.is_joined(&space, &to_agent) {
+            return Err(KitsuneP2pError::RoutingAgentError(to_agent));
+        }
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            Some(space) => space.get(),
+        };
+        Ok(async move {
+            space_sender
+                .await?
+                .internal
+                .incoming_request(space, to_agent, from_agent, inner)
+                .await
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_outgoing_request(&mut self, data: Vec<u8>) -> InternalHandlerResult<Vec<u8>> {
+        let transport = self.transport.clone();
+        Ok(async move { transport.incoming_request(data).await }
+            .boxed()
+            .into())
+    }
 }
 
 impl ghost_actor::GhostHandler<KitsuneP2pEvent> for KitsuneP2pActor {}
@@ -119,6 +281,13 @@ impl KitsuneP2pEventHandler for KitsuneP2pActor {
     ) -> KitsuneP2pEventHandlerResult<KitsuneSignature> {
         Ok(self.evt_sender.sign_network_data(input))
     }
+
+    fn handle_space_restarted(
+        &mut self,
+        input: SpaceRestartedEvt,
+    ) -> KitsuneP2pEventHandlerResult<()> {
+        Ok(self.evt_sender.space_restarted(input))
+    }
 }
 
 impl ghost_actor::GhostHandler<KitsuneP2p> for KitsuneP2pActor {}
@@ -129,25 +298,20 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         space: Arc<KitsuneSpace>,
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
+        self.local_agents.join(space.clone(), agent.clone());
         let internal_sender = self.internal_sender.clone();
-        let space2 = space.clone();
         let space_sender = match self.spaces.entry(space.clone()) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(AsyncLazy::new(async move {
-                let (send, evt_recv) = spawn_space(space2)
-                    .await
-                    .expect("cannot fail to create space");
-                internal_sender
-                    .register_space_event_handler(evt_recv)
-                    .await
-                    .expect("FAIL");
-                send
-            })),
+            Entry::Vacant(entry) => {
+                entry.insert(spawn_supervised_space(space.clone(), internal_sender))
+            }
         };
         let space_sender = space_sender.get();
-        Ok(async move { space_sender.await.join(space, agent).await }
-            .boxed()
-            .into())
+        Ok(
+            async move { space_sender.await?.api.join(space, agent).await }
+                .boxed()
+                .into(),
+        )
     }
 
     fn handle_leave(
@@ -155,12 +319,22 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         space: Arc<KitsuneSpace>,
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
+        let last_agent = self.local_agents.leave(&space, &agent);
         let space_sender = match self.spaces.get_mut(&space) {
             None => return Ok(async move { Ok(()) }.boxed().into()),
             Some(space) => space.get(),
         };
+        // the last local agent to leave takes the space with it
+        if last_agent {
+            self.spaces.remove(&space);
+            self.restart_counts.remove(&space);
+        }
         Ok(async move {
-            space_sender.await.leave(space.clone(), agent).await?;
+            let space_sender = space_sender.await?.api;
+            space_sender.leave(space.clone(), agent).await?;
+            if last_agent {
+                space_sender.ghost_actor_shutdown().await?;
+            }
             Ok(())
         }
         .boxed()
@@ -180,7 +354,8 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
         };
         Ok(async move {
             space_sender
-                .await
+                .await?
+                .api
                 .rpc_single(space, to_agent, from_agent, payload)
                 .await
         }
@@ -197,9 +372,11 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
             None => return Err(KitsuneP2pError::RoutingSpaceError(input.space)),
             Some(space) => space.get(),
         };
-        Ok(async move { space_sender.await.rpc_multi(input).await }
-            .boxed()
-            .into())
+        Ok(
+            async move { space_sender.await?.api.rpc_multi(input).await }
+                .boxed()
+                .into(),
+        )
     }
 
     fn handle_notify_multi(&mut self, input: actor::NotifyMulti) -> KitsuneP2pHandlerResult<u8> {
@@ -207,8 +384,381 @@ impl KitsuneP2pHandler for KitsuneP2pActor {
             None => return Err(KitsuneP2pError::RoutingSpaceError(input.space)),
             Some(space) => space.get(),
         };
-        Ok(async move { space_sender.await.notify_multi(input).await }
+        Ok(
+            async move { space_sender.await?.api.notify_multi(input).await }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_broadcast(&mut self, input: actor::Broadcast) -> KitsuneP2pHandlerResult<u8> {
+        let space_sender = match self.spaces.get_mut(&input.space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(input.space)),
+            Some(space) => space.get(),
+        };
+        Ok(
+            async move { space_sender.await?.api.broadcast(input).await }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_publish(
+        &mut self,
+        input: actor::Publish,
+    ) -> KitsuneP2pHandlerResult<Vec<Arc<KitsuneAgent>>> {
+        let space_sender = match self.spaces.get_mut(&input.space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(input.space)),
+            Some(space) => space.get(),
+        };
+        Ok(async move { space_sender.await?.api.publish(input).await }
             .boxed()
             .into())
     }
+
+    fn handle_report_peer(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        report: PeerReport,
+    ) -> KitsuneP2pHandlerResult<()> {
+        // nothing to count against if we've never joined this space
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Ok(async move { Ok(()) }.boxed().into()),
+            Some(space) => space.get(),
+        };
+        Ok(async move {
+            space_sender
+                .await?
+                .api
+                .report_peer(space, agent, report)
+                .await
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_peer_reputations(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<(Arc<KitsuneAgent>, f64)>> {
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            Some(space) => space.get(),
+        };
+        Ok(
+            async move { space_sender.await?.api.peer_reputations(space).await }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_agent_arcs(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<(Arc<KitsuneAgent>, DhtArc)>> {
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            Some(space) => space.get(),
+        };
+        Ok(
+            async move { space_sender.await?.api.agent_arcs(space).await }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_resized_arc(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+    ) -> KitsuneP2pHandlerResult<(DhtArc, DhtArc)> {
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            Some(space) => space.get(),
+        };
+        Ok(
+            async move { space_sender.await?.api.resized_arc(space, agent).await }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_set_arc(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        arc: DhtArc,
+    ) -> KitsuneP2pHandlerResult<()> {
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            Some(space) => space.get(),
+        };
+        Ok(
+            async move { space_sender.await?.api.set_arc(space, agent, arc).await }
+                .boxed()
+                .into(),
+        )
+    }
+
+    fn handle_gossip_rounds(
+        &mut self,
+        space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<(Arc<KitsuneAgent>, Vec<GossipRound>)>> {
+        let space_sender = match self.spaces.get_mut(&space) {
+            None => return Err(KitsuneP2pError::RoutingSpaceError(space)),
+            Some(space) => space.get(),
+        };
+        Ok(
+            async move { space_sender.await?.api.gossip_rounds(space).await }
+                .boxed()
+                .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::*;
+    use std::collections::HashSet;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn restarted_space_keeps_its_agents() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+
+        // spawn by hand so we can get at the internal sender
+        let (evt_send, mut evt) = futures::channel::mpsc::channel(10);
+        let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
+        let channel_factory = builder.channel_factory().clone();
+        let internal_sender = channel_factory.create_channel::<Internal>().await.unwrap();
+        let p2p = channel_factory
+            .create_channel::<KitsuneP2p>()
+            .await
+            .unwrap();
+        tokio::task::spawn(
+            builder.spawn(
+                KitsuneP2pActor::new(
+                    channel_factory,
+                    internal_sender.clone(),
+                    evt_send,
+                    internal_sender.clone(),
+                )
+                .unwrap(),
+            ),
+        );
+
+        let (restarted_send, mut restarted_recv) = futures::channel::mpsc::channel(1);
+        tokio::task::spawn(async move {
+            use futures::sink::SinkExt;
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                match evt {
+                    KitsuneP2pEvent::Call { respond, .. } => {
+                        respond.r(Ok(async move { Ok(b"echo".to_vec()) }.boxed().into()));
+                    }
+                    KitsuneP2pEvent::SpaceRestarted { respond, input, .. } => {
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                        restarted_send.clone().send(input).await.unwrap();
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        p2p.join(space1.clone(), a1.clone()).await.unwrap();
+        p2p.join(space1.clone(), a2.clone()).await.unwrap();
+
+        // pretend the space actor died
+        internal_sender
+            .space_terminated(space1.clone(), "test".into())
+            .await
+            .unwrap();
+
+        use tokio::stream::StreamExt;
+        let restarted = restarted_recv.next().await.unwrap();
+        assert_eq!(restarted.space, space1);
+        assert_eq!(restarted.restart_count, 1);
+        assert_eq!(restarted.reason, "test");
+        let agents: HashSet<_> = restarted.agents.into_iter().collect();
+        assert_eq!(agents, vec![a1.clone(), a2.clone()].into_iter().collect());
+
+        // the new space knows about both agents
+        let res = p2p.rpc_single(space1, a2, a1, b"hello".to_vec()).await;
+        assert_eq!(b"echo".to_vec(), res.unwrap());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn incoming_envelopes_reach_the_right_local_agent() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let space2: Arc<KitsuneSpace> =
+            Arc::new(b"tttttttttttttttttttttttttttttttttttt".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+        let a3: Arc<KitsuneAgent> =
+            Arc::new(b"333333333333333333333333333333333333".to_vec().into());
+
+        let (evt_send, mut evt) = futures::channel::mpsc::channel(10);
+        let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
+        let channel_factory = builder.channel_factory().clone();
+        let internal_sender = channel_factory.create_channel::<Internal>().await.unwrap();
+        let p2p = channel_factory
+            .create_channel::<KitsuneP2p>()
+            .await
+            .unwrap();
+        tokio::task::spawn(
+            builder.spawn(
+                KitsuneP2pActor::new(
+                    channel_factory,
+                    internal_sender.clone(),
+                    evt_send,
+                    internal_sender.clone(),
+                )
+                .unwrap(),
+            ),
+        );
+
+        // answer each call with who it was delivered to
+        tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                if let KitsuneP2pEvent::Call {
+                    respond,
+                    space,
+                    to_agent,
+                    ..
+                } = evt
+                {
+                    let mut res = space.0.clone();
+                    res.extend_from_slice(&to_agent.0);
+                    respond.r(Ok(async move { Ok(res) }.boxed().into()));
+                }
+            }
+        });
+
+        p2p.join(space1.clone(), a1.clone()).await.unwrap();
+        p2p.join(space1.clone(), a2.clone()).await.unwrap();
+        p2p.join(space2.clone(), a2.clone()).await.unwrap();
+
+        let call = |space: &Arc<KitsuneSpace>, to_agent: &Arc<KitsuneAgent>| {
+            mux::seal(
+                space,
+                to_agent,
+                &a3,
+                wire::Wire::call(b"hello".to_vec()).encode(),
+            )
+        };
+
+        for (space, to_agent) in vec![(&space1, &a1), (&space1, &a2), (&space2, &a2)] {
+            let res = internal_sender
+                .incoming_request(call(space, to_agent))
+                .await
+                .unwrap();
+            let mut expected = space.0.clone();
+            expected.extend_from_slice(&to_agent.0);
+            assert_eq!(res, expected);
+        }
+
+        // a1 isn't in space2, and nobody here is in space3
+        let res = internal_sender.incoming_request(call(&space2, &a1)).await;
+        assert_matches!(res, Err(KitsuneP2pError::RoutingAgentError(_)));
+        let space3: Arc<KitsuneSpace> =
+            Arc::new(b"uuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuuu".to_vec().into());
+        let res = internal_sender.incoming_request(call(&space3, &a1)).await;
+        assert_matches!(res, Err(KitsuneP2pError::RoutingAgentError(_)));
+
+        // once the last agent leaves a space, its envelopes are turned away
+        p2p.leave(space2.clone(), a2.clone()).await.unwrap();
+        let res = internal_sender.incoming_request(call(&space2, &a2)).await;
+        assert_matches!(res, Err(KitsuneP2pError::RoutingAgentError(_)));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn requests_round_trip_between_two_endpoints() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+
+        // each endpoint's transport delivers to the other one
+        let builder1 = ghost_actor::actor_builder::GhostActorBuilder::new();
+        let builder2 = ghost_actor::actor_builder::GhostActorBuilder::new();
+        let channel_factory1 = builder1.channel_factory().clone();
+        let channel_factory2 = builder2.channel_factory().clone();
+        let internal1 = channel_factory1.create_channel::<Internal>().await.unwrap();
+        let internal2 = channel_factory2.create_channel::<Internal>().await.unwrap();
+        let p2p1 = channel_factory1
+            .create_channel::<KitsuneP2p>()
+            .await
+            .unwrap();
+        let p2p2 = channel_factory2
+            .create_channel::<KitsuneP2p>()
+            .await
+            .unwrap();
+        let (evt_send1, evt1) = futures::channel::mpsc::channel(10);
+        let (evt_send2, evt2) = futures::channel::mpsc::channel(10);
+        tokio::task::spawn(
+            builder1.spawn(
+                KitsuneP2pActor::new(
+                    channel_factory1,
+                    internal1.clone(),
+                    evt_send1,
+                    internal2.clone(),
+                )
+                .unwrap(),
+            ),
+        );
+        tokio::task::spawn(builder2.spawn(
+            KitsuneP2pActor::new(channel_factory2, internal2, evt_send2, internal1).unwrap(),
+        ));
+
+        // answer each call with the payload and who it was from
+        for mut evt in vec![evt1, evt2] {
+            tokio::task::spawn(async move {
+                use tokio::stream::StreamExt;
+                while let Some(evt) = evt.next().await {
+                    if let KitsuneP2pEvent::Call {
+                        respond,
+                        from_agent,
+                        payload,
+                        ..
+                    } = evt
+                    {
+                        let mut res = payload;
+                        res.extend_from_slice(&from_agent.0);
+                        respond.r(Ok(async move { Ok(res) }.boxed().into()));
+                    }
+                }
+            });
+        }
+
+        p2p1.join(space1.clone(), a1.clone()).await.unwrap();
+        p2p2.join(space1.clone(), a2.clone()).await.unwrap();
+
+        let res = p2p1
+            .rpc_single(space1.clone(), a2.clone(), a1.clone(), b"hello".to_vec())
+            .await
+            .unwrap();
+        let mut expected = b"hello".to_vec();
+        expected.extend_from_slice(&a1.0);
+        assert_eq!(res, expected);
+
+        let res = p2p2
+            .rpc_single(space1, a1, a2.clone(), b"hi".to_vec())
+            .await
+            .unwrap();
+        let mut expected = b"hi".to_vec();
+        expected.extend_from_slice(&a2.0);
+        assert_eq!(res, expected);
+    }
 }
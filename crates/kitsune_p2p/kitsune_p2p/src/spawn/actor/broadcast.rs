@@ -0,0 +1,122 @@
+//! Bookkeeping for broadcasts: small messages flooded through a space, each
+//! agent passing them on to a few peers until their ttl runs out.
+//!
+//! Every agent a broadcast reaches remembers its message id for a while, so
+//! that the copies which arrive from other peers are dropped rather than
+//! delivered and passed on again.
+
+use super::space::ring_distance;
+use crate::types::*;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+};
+
+/// How many recent broadcasts are remembered, across all local agents
+const SEEN_CAPACITY: usize = 1024;
+
+/// The broadcasts each local agent has already received, oldest first.
+/// Forgetting the oldest keeps this bounded; by the time one is forgotten
+/// its copies should have stopped arriving.
+#[derive(Default)]
+pub(crate) struct SeenBroadcasts {
+    seen: HashSet<(Vec<u8>, Arc<KitsuneAgent>)>,
+    order: VecDeque<(Vec<u8>, Arc<KitsuneAgent>)>,
+    /// Counts the broadcasts started here, so their ids are unique
+    started: u64,
+}
+
+impl SeenBroadcasts {
+    /// A fresh message id for a broadcast by `origin`
+    pub fn new_id(&mut self, origin: &KitsuneAgent) -> Vec<u8> {
+        self.started += 1;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        let mut id = Vec::<u8>::from(origin.clone());
+        id.extend_from_slice(&now.to_le_bytes());
+        id.extend_from_slice(&self.started.to_le_bytes());
+        id
+    }
+
+    /// Remember that `agent` has received the broadcast.
+    /// Returns false if it already had.
+    pub fn insert(&mut self, msg_id: Vec<u8>, agent: Arc<KitsuneAgent>) -> bool {
+        let key = (msg_id, agent);
+        if self.seen.contains(&key) {
+            return false;
+        }
+        if self.order.len() == SEEN_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(key.clone());
+        self.order.push_back(key);
+        true
+    }
+}
+
+/// Choose up to `fanout` of `candidates` for `sender` to pass a broadcast
+/// on to. They're the closest to a point on the dht ring which depends on
+/// both the message and the sender, so each sender covers a different part
+/// of the space rather than all of them picking the same peers.
+/// Candidates should be ranked by reputation already; the sort is stable,
+/// so that order breaks ties.
+pub(crate) fn choose_peers(
+    msg_id: &[u8],
+    sender: &KitsuneAgent,
+    mut candidates: Vec<Arc<KitsuneAgent>>,
+    fanout: u8,
+) -> Vec<Arc<KitsuneAgent>> {
+    let seed = msg_id.chunks(4).fold(sender.get_loc(), |acc, chunk| {
+        let mut bytes = [0; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        acc.rotate_left(5) ^ u32::from_le_bytes(bytes)
+    });
+    candidates.sort_by_key(|a| ring_distance(a.get_loc(), seed));
+    candidates.truncate(fanout as usize);
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(b: u8) -> Arc<KitsuneAgent> {
+        Arc::new(vec![b; 36].into())
+    }
+
+    #[test]
+    fn remembers_each_agents_broadcasts() {
+        let mut seen = SeenBroadcasts::default();
+        let (a, b) = (agent(1), agent(2));
+        let id = seen.new_id(&a);
+        assert_ne!(seen.new_id(&a), id);
+
+        assert!(seen.insert(id.clone(), a.clone()));
+        assert!(!seen.insert(id.clone(), a.clone()));
+        // another local agent still needs it
+        assert!(seen.insert(id.clone(), b.clone()));
+
+        // the oldest are forgotten once full
+        for i in 0..SEEN_CAPACITY {
+            assert!(seen.insert((i as u64).to_le_bytes().to_vec(), a.clone()));
+        }
+        assert!(seen.insert(id, a));
+    }
+
+    #[test]
+    fn chooses_a_bounded_spread_of_peers() {
+        let candidates: Vec<_> = (1..=10).map(agent).collect();
+        let peers = choose_peers(b"msg", &agent(0), candidates.clone(), 3);
+        assert_eq!(peers.len(), 3);
+        assert!(peers.iter().all(|p| candidates.contains(p)));
+        assert_eq!(
+            choose_peers(b"msg", &agent(0), candidates.clone(), 3),
+            peers
+        );
+        assert_eq!(choose_peers(b"msg", &agent(0), candidates, 20).len(), 10);
+    }
+}
@@ -1,10 +1,18 @@
 //! This is a temporary quick-hack gossip module for use with the
 //! in-memory / full-sync / non-sharded networking module
 
-use crate::{types::actor::KitsuneP2pResult, *};
+use crate::{
+    types::actor::{GossipRound, KitsuneP2pResult},
+    *,
+};
 use ghost_actor::dependencies::{tracing, tracing_futures};
 use kitsune_p2p_types::dht_arc::DhtArc;
-use std::{collections::HashSet, iter::FromIterator, sync::Arc};
+use std::{
+    collections::HashSet,
+    iter::FromIterator,
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
 
 ghost_actor::ghost_chan! {
     /// "Event" requests emitted by the gossip module
@@ -34,6 +42,9 @@ ghost_actor::ghost_chan! {
             to_agent: Arc<KitsuneAgent>,
             ops: Vec<(Arc<KitsuneOpHash>, Vec<u8>)>,
         ) -> ();
+
+        /// a round of gossip with a peer has finished
+        fn record_gossip_round(peer: Arc<KitsuneAgent>, round: GossipRound) -> ();
     }
 }
 
@@ -100,9 +111,44 @@ impl GossipData {
     }
 
     async fn process_next_gossip(&mut self) -> KitsuneP2pResult<()> {
-        // !is_empty() checked above in take_action
+        // stop gossiping with agents which have left since the list was fetched
+        let neighbors: HashSet<Arc<KitsuneAgent>> =
+            HashSet::from_iter(self.evt_send.list_neighbor_agents().await?);
+        self.pending_gossip_list
+            .retain(|(a1, a2)| neighbors.contains(a1) && neighbors.contains(a2));
+        if self.pending_gossip_list.is_empty() {
+            return Ok(());
+        }
         let (from_agent, to_agent) = self.pending_gossip_list.remove(0);
 
+        let started = Instant::now();
+        let mut round = GossipRound {
+            started_utc_epoch_s: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default(),
+            ..Default::default()
+        };
+        if let Err(e) = self.gossip_round(&from_agent, &to_agent, &mut round).await {
+            round.errors.push(e.to_string());
+        }
+        round.duration_ms = started.elapsed().as_millis() as u64;
+        if !round.errors.is_empty() {
+            tracing::warn!(?to_agent, errors = ?round.errors, "gossip round failed");
+        }
+        self.evt_send.record_gossip_round(to_agent, round).await?;
+
+        Ok(())
+    }
+
+    /// Exchange ops between the pair, counting what moved in the round.
+    /// Failing to fetch or send ops is recorded and the round carries on.
+    async fn gossip_round(
+        &mut self,
+        from_agent: &Arc<KitsuneAgent>,
+        to_agent: &Arc<KitsuneAgent>,
+        round: &mut GossipRound,
+    ) -> KitsuneP2pResult<()> {
         // required so from_iters below know the build_hasher type
         type S = HashSet<Arc<KitsuneOpHash>>;
 
@@ -137,6 +183,7 @@ impl GossipData {
             .difference(&op_hashes_from)
             .cloned()
             .collect::<Vec<_>>();
+        round.ops_offered = from_needs.len() as u32;
 
         // values that from_agent has, and to_agent needs
         let to_needs = op_hashes_from
@@ -146,7 +193,7 @@ impl GossipData {
 
         // fetch values that to_agent needs from from_agent
         if !to_needs.is_empty() {
-            if let Ok(result) = self
+            match self
                 .evt_send
                 .req_op_data(
                     from_agent.clone(), // from not to because we're initiating
@@ -155,27 +202,32 @@ impl GossipData {
                 )
                 .await
             {
-                if !result.is_empty() {
-                    if let Err(e) = self
+                Ok(result) if !result.is_empty() => {
+                    let count = result.len() as u32;
+                    match self
                         .evt_send
                         .gossip_ops(from_agent.clone(), to_agent.clone(), result)
                         .await
                     {
-                        tracing::error!(?e);
+                        Ok(()) => round.ops_sent = count,
+                        Err(e) => round.errors.push(e.to_string()),
                     }
                 }
+                Ok(_) => (),
+                Err(e) => round.errors.push(e.to_string()),
             }
         }
 
         // fetch values that from_agent needs from to_agent
         if !from_needs.is_empty() {
-            if let Ok(result) = self
+            match self
                 .evt_send
                 .req_op_data(from_agent.clone(), to_agent.clone(), from_needs)
                 .await
             {
-                if !result.is_empty() {
-                    if let Err(e) = self
+                Ok(result) if !result.is_empty() => {
+                    let count = result.len() as u32;
+                    match self
                         .evt_send
                         .gossip_ops(
                             to_agent.clone(), // we fetched from to
@@ -184,9 +236,12 @@ impl GossipData {
                         )
                         .await
                     {
-                        tracing::error!(?e);
+                        Ok(()) => round.ops_accepted = count,
+                        Err(e) => round.errors.push(e.to_string()),
                     }
                 }
+                Ok(_) => (),
+                Err(e) => round.errors.push(e.to_string()),
             }
         }
 
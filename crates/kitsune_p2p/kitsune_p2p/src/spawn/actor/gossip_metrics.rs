@@ -0,0 +1,76 @@
+//! Keeps the outcome of the most recent gossip rounds with each peer,
+//! so that when data fails to converge it's possible to see which peers
+//! are being gossiped with, what moved and what went wrong.
+
+use crate::{actor::GossipRound, types::*};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+/// How many of the most recent rounds are kept for each peer
+const ROUND_HISTORY_LEN: usize = 32;
+
+/// A ring buffer of gossip rounds for each peer
+#[derive(Default)]
+pub(crate) struct GossipMetrics {
+    peers: HashMap<Arc<KitsuneAgent>, VecDeque<GossipRound>>,
+}
+
+impl GossipMetrics {
+    /// Record a round with a peer, forgetting its oldest round if
+    /// the history is full
+    pub fn record(&mut self, peer: Arc<KitsuneAgent>, round: GossipRound) {
+        let rounds = self.peers.entry(peer).or_default();
+        if rounds.len() == ROUND_HISTORY_LEN {
+            rounds.pop_front();
+        }
+        rounds.push_back(round);
+    }
+
+    /// The recorded rounds with every peer, oldest first
+    pub fn rounds(&self) -> Vec<(Arc<KitsuneAgent>, Vec<GossipRound>)> {
+        self.peers
+            .iter()
+            .map(|(peer, rounds)| (peer.clone(), rounds.iter().cloned().collect()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(b: u8) -> Arc<KitsuneAgent> {
+        Arc::new(vec![b; 36].into())
+    }
+
+    fn round(ops_sent: u32) -> GossipRound {
+        GossipRound {
+            ops_sent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keeps_the_most_recent_rounds_per_peer() {
+        let mut metrics = GossipMetrics::default();
+        let (a, b) = (agent(1), agent(2));
+        for i in 0..ROUND_HISTORY_LEN as u32 + 2 {
+            metrics.record(a.clone(), round(i));
+        }
+        metrics.record(b.clone(), round(7));
+
+        let mut rounds = metrics.rounds();
+        rounds.sort_by(|x, y| x.0.cmp(&y.0));
+        assert_eq!(rounds.len(), 2);
+
+        let (peer, a_rounds) = &rounds[0];
+        assert_eq!(*peer, a);
+        assert_eq!(a_rounds.len(), ROUND_HISTORY_LEN);
+        assert_eq!(a_rounds.first(), Some(&round(2)));
+        assert_eq!(a_rounds.last(), Some(&round(ROUND_HISTORY_LEN as u32 + 1)));
+
+        assert_eq!(rounds[1], (b, vec![round(7)]));
+    }
+}
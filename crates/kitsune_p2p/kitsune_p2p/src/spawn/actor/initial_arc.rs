@@ -0,0 +1,176 @@
+//! Chooses the storage arc an agent claims when it joins a space,
+//! and resizes it as its neighbors come and go.
+//!
+//! Rather than every agent starting with the same default, a joining agent
+//! looks at the arcs its neighbors have already claimed and fills the
+//! largest stretch of the DHT that nobody holds. If every location is
+//! already held it goes where the neighbors are most spread out, so the
+//! least redundant data gains a holder. In a small network this means the
+//! first agents between them quickly hold everything.
+//!
+//! Once joined, an agent keeps its center but is periodically resized to
+//! reach the nearest neighboring centers, shrinking as neighbors join
+//! nearby and growing to cover for neighbors which leave.
+
+use kitsune_p2p_types::dht_arc::{gaps, ArcRange, DhtArc, DhtLocation, MAX_HALF_LENGTH};
+use std::{num::Wrapping, ops::Bound};
+
+/// The arc for an agent joining a space where its neighbors
+/// have claimed these arcs
+pub(crate) fn initial_arc(claimed: &[DhtArc]) -> DhtArc {
+    let largest_gap = gaps(claimed).into_iter().max_by_key(ArcRange::len);
+    match largest_gap {
+        Some(gap) => covering(&gap),
+        None => between_widest_neighbors(claimed),
+    }
+}
+
+/// The arc for an agent centered at `center` whose neighbors have claimed
+/// these arcs. It reaches the nearest neighboring center on each side, so
+/// between them neighbors hold every location. Alone, it holds everything.
+pub(crate) fn resized_arc(center: DhtLocation, neighbors: &[DhtArc]) -> DhtArc {
+    let center = center.0;
+    let nearest = neighbors
+        .iter()
+        .map(|neighbor| neighbor.center_loc.0)
+        // Sharing a center doesn't help either hold anything else
+        .filter(|other| *other != center)
+        .map(|other| ((other - center).0, (center - other).0))
+        .fold(None, |nearest, (ahead, behind)| match nearest {
+            None => Some((ahead, behind)),
+            Some((a, b)) => Some((std::cmp::min(a, ahead), std::cmp::min(b, behind))),
+        });
+    match nearest {
+        Some((ahead, behind)) => {
+            DhtArc::new(center, std::cmp::max(ahead, behind).saturating_add(1))
+        }
+        None => DhtArc::new(center, MAX_HALF_LENGTH),
+    }
+}
+
+/// The smallest arc which holds every location in the range
+fn covering(range: &ArcRange) -> DhtArc {
+    let start = match range.start {
+        Bound::Included(start) => start,
+        _ => return DhtArc::new(0, 0),
+    };
+    let half = range.len() / 2;
+    let center = (Wrapping(start) + Wrapping(half as u32)).0;
+    DhtArc::new(
+        center,
+        std::cmp::min(half + 1, MAX_HALF_LENGTH as u64) as u32,
+    )
+}
+
+/// Every location is held, so hold the stretch between the two
+/// neighboring arc centers which are furthest apart
+fn between_widest_neighbors(claimed: &[DhtArc]) -> DhtArc {
+    let mut centers: Vec<u32> = claimed.iter().map(|a| a.center_loc.into()).collect();
+    centers.sort_unstable();
+    centers.dedup();
+    let first = match centers.first() {
+        Some(first) => *first,
+        None => return DhtArc::new(0, MAX_HALF_LENGTH),
+    };
+    // Pair each center with the next one round the circle.
+    // A lone center is paired with itself, the whole way round.
+    let (start, len) = centers
+        .iter()
+        .zip(centers.iter().skip(1).chain(std::iter::once(&first)))
+        .map(|(a, b)| match (Wrapping(*b) - Wrapping(*a)).0 {
+            0 => (*a, u32::MAX as u64 + 1),
+            len => (*a, len as u64),
+        })
+        .max_by_key(|(_, len)| *len)
+        .expect("There is at least one center");
+    covering(&ArcRange {
+        start: Bound::Included(start),
+        end: Bound::Included((Wrapping(start) + Wrapping((len - 1) as u32)).0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_the_largest_gap() {
+        let quarter = (u32::MAX as f64 / 4.0).round() as u32;
+        let half = (u32::MAX as f64 / 2.0).round() as u32;
+
+        // The first agent holds everything
+        assert_eq!(initial_arc(&[]).coverage(), 1.0);
+
+        // The second holds everything the first doesn't
+        let first = DhtArc::new(0, quarter);
+        let second = initial_arc(&[first]);
+        assert!(!first.overlaps(&DhtArc::new(second.center_loc, 1)));
+        assert!(gaps(&[first, second]).is_empty());
+
+        // Of two gaps, the larger is filled and the smaller left
+        let claimed = [
+            DhtArc::new(0, quarter / 2),
+            DhtArc::new(half - quarter / 2, quarter / 4),
+        ];
+        let arc = initial_arc(&claimed);
+        assert!(arc.contains(half + quarter));
+        assert!(!arc.contains(quarter));
+        assert_eq!(gaps(&[claimed[0], claimed[1], arc]).len(), 1);
+    }
+
+    #[test]
+    fn goes_between_the_widest_neighbors_when_all_is_held() {
+        let quarter = (u32::MAX as f64 / 4.0).round() as u32;
+        let half = (u32::MAX as f64 / 2.0).round() as u32;
+
+        // A lone agent holding everything is joined by another
+        // which also holds everything
+        let arc = initial_arc(&[DhtArc::new(0, MAX_HALF_LENGTH)]);
+        assert_eq!(arc.coverage(), 1.0);
+
+        // Centers at 0, a quarter and a half leave the widest
+        // stretch from a half back round to 0
+        let claimed = [
+            DhtArc::new(0, MAX_HALF_LENGTH),
+            DhtArc::new(quarter, MAX_HALF_LENGTH),
+            DhtArc::new(half, MAX_HALF_LENGTH),
+        ];
+        let arc = initial_arc(&claimed);
+        assert!(arc.contains(half + quarter));
+        assert!(arc.contains(half + 1));
+        assert!(!arc.contains(quarter));
+    }
+
+    #[test]
+    fn resizes_to_reach_the_nearest_neighbors() {
+        let quarter = (u32::MAX as f64 / 4.0).round() as u32;
+        let eighth = quarter / 2;
+        let center = DhtLocation::from(quarter);
+
+        // Alone, or only sharing a center, it holds everything
+        assert_eq!(resized_arc(center, &[]).coverage(), 1.0);
+        let twin = DhtArc::new(center, 1);
+        assert_eq!(resized_arc(center, &[twin]).coverage(), 1.0);
+
+        // Otherwise it reaches the further of the nearest neighbors
+        let neighbors = [
+            DhtArc::new(quarter + quarter, 1),
+            DhtArc::new(quarter - eighth, 1),
+            DhtArc::new(quarter * 3, 1),
+        ];
+        let arc = resized_arc(center, &neighbors);
+        assert_eq!(arc.center_loc, center);
+        assert!(arc.contains(quarter + quarter));
+        assert!(arc.contains(0));
+        assert!(!arc.contains(quarter + quarter + 1));
+
+        // Resizing everyone leaves nothing unheld
+        let mut centers: Vec<DhtArc> = neighbors.to_vec();
+        centers.push(twin);
+        let resized: Vec<DhtArc> = centers
+            .iter()
+            .map(|arc| resized_arc(arc.center_loc, &centers))
+            .collect();
+        assert!(gaps(&resized).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+//! Multiplexing every local agent over one endpoint.
+//!
+//! A conductor hosting many cells has many agents joined to the same
+//! spaces. Rather than each agent having its own connections, all of them
+//! share the endpoint's connections, and each message travels in a
+//! [Wire::Envelope] tagged with its space and its sending and receiving
+//! agents. When an envelope arrives, the endpoint checks the receiving
+//! agent is joined locally before handing the inner message to that
+//! space, which delivers it to the agent.
+//!
+//! [Wire::Envelope]: crate::types::wire::Wire::Envelope
+
+use crate::types::*;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// An incoming message, addressed to one local agent of a space
+pub(crate) struct Opened {
+    pub space: Arc<KitsuneSpace>,
+    pub to_agent: Arc<KitsuneAgent>,
+    pub from_agent: Arc<KitsuneAgent>,
+    pub inner: Vec<u8>,
+}
+
+/// Tag a message with who it is for, so it can share a connection
+/// with the messages of every other agent
+pub(crate) fn seal(
+    space: &KitsuneSpace,
+    to_agent: &KitsuneAgent,
+    from_agent: &KitsuneAgent,
+    inner: Vec<u8>,
+) -> Vec<u8> {
+    wire::Wire::envelope(
+        space.clone().into(),
+        to_agent.clone().into(),
+        from_agent.clone().into(),
+        inner,
+    )
+    .encode()
+}
+
+/// Read who an incoming message is for
+pub(crate) fn open(data: Vec<u8>) -> KitsuneP2pResult<Opened> {
+    match wire::Wire::decode(data)? {
+        wire::Wire::Envelope {
+            space,
+            to_agent,
+            from_agent,
+            inner,
+        } => Ok(Opened {
+            space: Arc::new(space.into()),
+            to_agent: Arc::new(to_agent.into()),
+            from_agent: Arc::new(from_agent.into()),
+            inner,
+        }),
+        _ => Err(KitsuneP2pError::decoding_error(
+            "expected a kitsune p2p agent envelope".to_string(),
+        )),
+    }
+}
+
+/// The agents joined to each space on this endpoint
+#[derive(Default)]
+pub(crate) struct LocalAgents(HashMap<Arc<KitsuneSpace>, HashSet<Arc<KitsuneAgent>>>);
+
+impl LocalAgents {
+    /// Record an agent joining a space
+    pub fn join(&mut self, space: Arc<KitsuneSpace>, agent: Arc<KitsuneAgent>) {
+        self.0.entry(space).or_default().insert(agent);
+    }
+
+    /// Record an agent leaving a space.
+    /// Returns whether it was the last local agent in that space.
+    pub fn leave(&mut self, space: &KitsuneSpace, agent: &KitsuneAgent) -> bool {
+        match self.0.get_mut(space) {
+            Some(agents) => {
+                agents.remove(agent);
+                if agents.is_empty() {
+                    self.0.remove(space);
+                    true
+                } else {
+                    false
+                }
+            }
+            None => false,
+        }
+    }
+
+    /// Whether an agent is joined to a space here
+    pub fn is_joined(&self, space: &KitsuneSpace, agent: &KitsuneAgent) -> bool {
+        self.0
+            .get(space)
+            .map(|agents| agents.contains(agent))
+            .unwrap_or(false)
+    }
+
+    /// Every agent joined to a space here
+    pub fn agents(&self, space: &KitsuneSpace) -> Vec<Arc<KitsuneAgent>> {
+        self.0
+            .get(space)
+            .map(|agents| agents.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::*;
+
+    #[test]
+    fn seal_then_open() {
+        let space: KitsuneSpace = b"ssss".to_vec().into();
+        let to_agent: KitsuneAgent = b"1111".to_vec().into();
+        let from_agent: KitsuneAgent = b"2222".to_vec().into();
+        let opened = open(seal(&space, &to_agent, &from_agent, b"hello".to_vec())).unwrap();
+        assert_eq!(*opened.space, space);
+        assert_eq!(*opened.to_agent, to_agent);
+        assert_eq!(*opened.from_agent, from_agent);
+        assert_eq!(opened.inner, b"hello".to_vec());
+
+        // only envelopes say who they're for
+        let res = open(wire::Wire::call(b"hello".to_vec()).encode());
+        assert_matches!(res, Err(KitsuneP2pError::DecodingError(_)));
+    }
+
+    #[test]
+    fn last_agent_to_leave_empties_the_space() {
+        let space: Arc<KitsuneSpace> = Arc::new(b"ssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> = Arc::new(b"1111".to_vec().into());
+        let a2: Arc<KitsuneAgent> = Arc::new(b"2222".to_vec().into());
+        let mut local = LocalAgents::default();
+        local.join(space.clone(), a1.clone());
+        local.join(space.clone(), a2.clone());
+        assert!(local.is_joined(&space, &a1));
+
+        assert!(!local.leave(&space, &a1));
+        assert!(!local.is_joined(&space, &a1));
+        assert_eq!(local.agents(&space), vec![a2.clone()]);
+
+        assert!(local.leave(&space, &a2));
+        assert!(local.agents(&space).is_empty());
+        assert!(!local.leave(&space, &a2));
+    }
+}
@@ -0,0 +1,169 @@
+//! Tracks how well remote agents have behaved, so that we can prefer
+//! the well-behaved ones when choosing who to ask for data or gossip with.
+
+use crate::{actor::PeerReport, types::*};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Penalties halve over this interval, so peers can recover from
+/// transient problems.
+const DEFAULT_HALF_LIFE: Duration = Duration::from_secs(10 * 60);
+
+/// Agents scoring below this are skipped when ranking,
+/// unless there is no one else.
+const MIN_SCORE: f64 = 0.1;
+
+impl PeerReport {
+    /// How much a single report of this kind counts against a peer
+    fn weight(self) -> f64 {
+        match self {
+            PeerReport::Timeout | PeerReport::Error => 1.0,
+            PeerReport::BadResponse => 5.0,
+            PeerReport::ValidationFailure => 10.0,
+        }
+    }
+}
+
+struct PeerScore {
+    penalty: f64,
+    last_update: Instant,
+}
+
+/// Decaying penalties for remote agents, keyed by agent
+pub(crate) struct ReputationStore {
+    peers: HashMap<Arc<KitsuneAgent>, PeerScore>,
+    half_life: Duration,
+}
+
+impl Default for ReputationStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_HALF_LIFE)
+    }
+}
+
+impl ReputationStore {
+    /// A store where penalties halve every `half_life`
+    pub fn new(half_life: Duration) -> Self {
+        Self {
+            peers: HashMap::new(),
+            half_life,
+        }
+    }
+
+    fn decayed(&self, score: &PeerScore, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(score.last_update);
+        score.penalty * 0.5f64.powf(elapsed.as_secs_f64() / self.half_life.as_secs_f64())
+    }
+
+    /// Count something an agent did wrong
+    pub fn report(&mut self, agent: Arc<KitsuneAgent>, report: PeerReport) {
+        self.report_at(agent, report, Instant::now())
+    }
+
+    fn report_at(&mut self, agent: Arc<KitsuneAgent>, report: PeerReport, now: Instant) {
+        let penalty = match self.peers.get(&agent) {
+            Some(score) => self.decayed(score, now),
+            None => 0.0,
+        } + report.weight();
+        self.peers.insert(
+            agent,
+            PeerScore {
+                penalty,
+                last_update: now,
+            },
+        );
+    }
+
+    /// An agent's reputation, from 1.0 for an agent with nothing against
+    /// it, falling towards 0.0 as penalties accumulate
+    fn score_at(&self, agent: &Arc<KitsuneAgent>, now: Instant) -> f64 {
+        match self.peers.get(agent) {
+            Some(score) => 1.0 / (1.0 + self.decayed(score, now)),
+            None => 1.0,
+        }
+    }
+
+    /// Order agents best reputation first, dropping the disreputable ones
+    /// unless that would leave nobody
+    pub fn rank(&self, agents: Vec<Arc<KitsuneAgent>>) -> Vec<Arc<KitsuneAgent>> {
+        self.rank_at(agents, Instant::now())
+    }
+
+    fn rank_at(&self, agents: Vec<Arc<KitsuneAgent>>, now: Instant) -> Vec<Arc<KitsuneAgent>> {
+        let mut scored: Vec<_> = agents
+            .into_iter()
+            .map(|a| (self.score_at(&a, now), a))
+            .collect();
+        // sort is stable, so equally reputable agents keep their order
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        if scored.iter().any(|(s, _)| *s >= MIN_SCORE) {
+            scored.retain(|(s, _)| *s >= MIN_SCORE);
+        }
+        scored.into_iter().map(|(_, a)| a).collect()
+    }
+
+    /// The current score of every agent that has been reported
+    pub fn scores(&self) -> Vec<(Arc<KitsuneAgent>, f64)> {
+        let now = Instant::now();
+        self.peers
+            .keys()
+            .map(|a| (a.clone(), self.score_at(a, now)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(b: u8) -> Arc<KitsuneAgent> {
+        Arc::new(vec![b; 36].into())
+    }
+
+    #[test]
+    fn penalties_lower_score_and_decay() {
+        let mut store = ReputationStore::new(Duration::from_secs(60));
+        let start = Instant::now();
+        let a = agent(1);
+        assert_eq!(store.score_at(&a, start), 1.0);
+
+        store.report_at(a.clone(), PeerReport::Timeout, start);
+        let after_timeout = store.score_at(&a, start);
+        assert!(after_timeout < 1.0);
+
+        store.report_at(a.clone(), PeerReport::ValidationFailure, start);
+        let after_failure = store.score_at(&a, start);
+        assert!(after_failure < after_timeout);
+
+        // one half-life later the penalty has halved
+        let later = start + Duration::from_secs(60);
+        let penalty = 1.0 / after_failure - 1.0;
+        let expected = 1.0 / (1.0 + penalty / 2.0);
+        assert!((store.score_at(&a, later) - expected).abs() < 1e-9);
+
+        // and eventually it is forgotten
+        let much_later = start + Duration::from_secs(60 * 60);
+        assert!(store.score_at(&a, much_later) > 0.999);
+    }
+
+    #[test]
+    fn rank_prefers_reputable_agents() {
+        let mut store = ReputationStore::default();
+        let now = Instant::now();
+        let (good, meh, bad) = (agent(1), agent(2), agent(3));
+        store.report_at(meh.clone(), PeerReport::Timeout, now);
+        for _ in 0..2 {
+            store.report_at(bad.clone(), PeerReport::ValidationFailure, now);
+        }
+
+        let ranked = store.rank_at(vec![bad.clone(), meh.clone(), good.clone()], now);
+        assert_eq!(ranked, vec![good, meh]);
+
+        // a disreputable agent is still better than nobody
+        let ranked = store.rank_at(vec![bad.clone()], now);
+        assert_eq!(ranked, vec![bad]);
+    }
+}
@@ -1,4 +1,10 @@
-use super::*;
+use super::{
+    broadcast::{choose_peers, SeenBroadcasts},
+    gossip_metrics::GossipMetrics,
+    initial_arc::{initial_arc, resized_arc},
+    reputation::ReputationStore,
+    *,
+};
 use ghost_actor::dependencies::{tracing, tracing_futures::Instrument};
 use std::collections::HashSet;
 
@@ -17,6 +23,25 @@ const DEFAULT_RPC_MULTI_TIMEOUT_MS: u64 = 1000;
 /// if the user specifies None or zero (0) for race_timeout_ms
 const DEFAULT_RPC_MULTI_RACE_TIMEOUT_MS: u64 = 200;
 
+/// how many peers an rpc_multi request may be passed through
+/// if the chosen agent can't be reached directly
+const RPC_MULTI_MAX_RELAY_HOPS: u8 = 2;
+
+/// if the user specifies None or zero (0) for remote_agent_count
+const DEFAULT_PUBLISH_REMOTE_AGENT_COUNT: u8 = 5;
+
+/// if the user specifies None or zero (0) for timeout_ms
+const DEFAULT_PUBLISH_TIMEOUT_MS: u64 = 500;
+
+/// if the user specifies None or zero (0) for fanout
+const DEFAULT_BROADCAST_FANOUT: u8 = 3;
+
+/// if the user specifies None for ttl
+const DEFAULT_BROADCAST_TTL: u8 = 3;
+
+/// How long to wait for each peer to acknowledge a broadcast
+const BROADCAST_TIMEOUT_MS: u64 = 500;
+
 /// Normally network lookups / connections will be async / take some time.
 /// While we are in "short-circuit-only" mode - we just need to allow some
 /// time for other agenst to be connected to this conductor.
@@ -26,22 +51,48 @@ const NET_CONNECT_INTERVAL_MS: u64 = 20;
 /// Max amount of time we should wait for connections to be established.
 const NET_CONNECT_MAX_MS: u64 = 2000;
 
+/// Max amount of time leaving waits for requests to the agent to finish.
+const LEAVE_DRAIN_MAX_MS: u64 = 5000;
+
 ghost_actor::ghost_chan! {
     pub(crate) chan SpaceInternal<crate::KitsuneP2pError> {
         /// Make a remote request right-now if we have an open connection,
         /// otherwise, return an error.
         fn immediate_request(space: Arc<KitsuneSpace>, to_agent: Arc<KitsuneAgent>, from_agent: Arc<KitsuneAgent>, data: Arc<Vec<u8>>) -> Vec<u8>;
 
+        /// Deliver a request which arrived at the endpoint to one of our agents
+        fn incoming_request(space: Arc<KitsuneSpace>, to_agent: Arc<KitsuneAgent>, from_agent: Arc<KitsuneAgent>, data: Vec<u8>) -> Vec<u8>;
+
         /// List online agents that claim to be covering a basis hash
         fn list_online_agents_for_basis_hash(space: Arc<KitsuneSpace>, basis: Arc<KitsuneBasis>) -> Vec<Arc<KitsuneAgent>>;
+
+        /// List the online agents closest to a basis hash, other than `exclude`
+        fn list_closest_agents_for_basis(basis: Arc<KitsuneBasis>, exclude: Arc<KitsuneAgent>, count: u8) -> Vec<Arc<KitsuneAgent>>;
+
+        /// Count something a remote agent did wrong against its reputation
+        fn report_peer(agent: Arc<KitsuneAgent>, report: PeerReport) -> ();
     }
 }
 
+/// The ways into a running space actor
+#[derive(Clone)]
+pub(crate) struct SpaceSenders {
+    /// The space's share of the KitsuneP2p api
+    pub api: ghost_actor::GhostSender<KitsuneP2p>,
+    /// For delivering requests which arrived at the endpoint
+    /// to the space's agents
+    pub internal: ghost_actor::GhostSender<SpaceInternal>,
+}
+
+/// Spawn a space actor. The returned JoinHandle resolves when the actor
+/// terminates, so that it can be supervised.
 pub(crate) async fn spawn_space(
     space: Arc<KitsuneSpace>,
+    endpoint: ghost_actor::GhostSender<Internal>,
 ) -> KitsuneP2pResult<(
-    ghost_actor::GhostSender<KitsuneP2p>,
+    SpaceSenders,
     KitsuneP2pEventReceiver,
+    tokio::task::JoinHandle<ghost_actor::GhostResult<()>>,
 )> {
     let (evt_send, evt_recv) = futures::channel::mpsc::channel(10);
 
@@ -64,9 +115,18 @@ pub(crate) async fn spawn_space(
         .create_channel::<KitsuneP2p>()
         .await?;
 
-    tokio::task::spawn(builder.spawn(Space::new(space, internal_sender, evt_send)));
-
-    Ok((sender, evt_recv))
+    let driver = tokio::task::spawn(builder.spawn(Space::new(
+        space,
+        internal_sender.clone(),
+        evt_send,
+        endpoint,
+    )));
+
+    let senders = SpaceSenders {
+        api: sender,
+        internal: internal_sender,
+    };
+    Ok((senders, evt_recv, driver))
 }
 
 impl ghost_actor::GhostHandler<gossip::GossipEvent> for Space {}
@@ -76,7 +136,7 @@ impl gossip::GossipEventHandler for Space {
         &mut self,
     ) -> gossip::GossipEventHandlerResult<Vec<Arc<KitsuneAgent>>> {
         // while full-sync this is just a clone of list_by_basis
-        let res = self.agents.keys().cloned().collect();
+        let res = self.reputation.rank(self.agents.keys().cloned().collect());
         Ok(async move { Ok(res) }.boxed().into())
     }
 
@@ -118,6 +178,15 @@ impl gossip::GossipEventHandler for Space {
         Ok(async move { fut.await }.boxed().into())
     }
 
+    fn handle_record_gossip_round(
+        &mut self,
+        peer: Arc<KitsuneAgent>,
+        round: GossipRound,
+    ) -> gossip::GossipEventHandlerResult<()> {
+        self.gossip_metrics.record(peer, round);
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
     fn handle_gossip_ops(
         &mut self,
         from_agent: Arc<KitsuneAgent>,
@@ -162,13 +231,28 @@ impl SpaceInternalHandler for Space {
         from_agent: Arc<KitsuneAgent>,
         data: Arc<Vec<u8>>,
     ) -> SpaceInternalHandlerResult<Vec<u8>> {
-        // Right now we are only implementing the "short-circuit"
-        // that routes messages to other agents joined on this same system.
-        // I.e. we don't bother with peer discovery because we know the
-        // remote is local.
-        if !self.agents.contains_key(&to_agent) {
-            return Err(KitsuneP2pError::RoutingAgentError(to_agent));
-        }
+        // every agent shares the endpoint's connections,
+        // so tag the request with who it is for
+        let data = mux::seal(&self.space, &to_agent, &from_agent, (*data).clone());
+        let endpoint = self.endpoint.clone();
+        Ok(async move { endpoint.outgoing_request(data).await }
+            .boxed()
+            .into())
+    }
+
+    fn handle_incoming_request(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        to_agent: Arc<KitsuneAgent>,
+        from_agent: Arc<KitsuneAgent>,
+        data: Vec<u8>,
+    ) -> SpaceInternalHandlerResult<Vec<u8>> {
+        // The endpoint has checked to_agent is joined here, but it may
+        // not have finished joining this space actor yet.
+        let in_flight = match self.agents.get(&to_agent) {
+            None => return Err(KitsuneP2pError::RoutingAgentError(to_agent)),
+            Some(info) => info.in_flight.clone(),
+        };
 
         // to_agent *is* joined - let's forward the request
         let space = self.space.clone();
@@ -176,23 +260,19 @@ impl SpaceInternalHandler for Space {
         // clone the event sender
         let evt_sender = self.evt_sender.clone();
 
-        // As this is a short-circuit - we need to decode the data inline - here.
-        // In the future, we will probably need to branch here, so the real
-        // networking can forward the encoded data. Or, split immediate_request
-        // into two variants, one for short-circuit, and one for real networking.
-        let data = wire::Wire::decode((*data).clone())?;
+        let data = wire::Wire::decode(data)?;
 
         match data {
-            wire::Wire::Call(payload) => {
-                Ok(
-                    async move { evt_sender.call(space, to_agent, from_agent, payload).await }
-                        .instrument(tracing::debug_span!("wire_call"))
-                        .boxed()
-                        .into(),
-                )
+            wire::Wire::Call(payload) => Ok(async move {
+                let _in_flight = in_flight;
+                evt_sender.call(space, to_agent, from_agent, payload).await
             }
+            .instrument(tracing::debug_span!("wire_call"))
+            .boxed()
+            .into()),
             wire::Wire::Notify(payload) => {
                 Ok(async move {
+                    let _in_flight = in_flight;
                     evt_sender
                         .notify(space, to_agent, from_agent, payload)
                         .await?;
@@ -202,6 +282,125 @@ impl SpaceInternalHandler for Space {
                 .boxed()
                 .into())
             }
+            wire::Wire::Publish { op_hash, op_data } => Ok(async move {
+                let _in_flight = in_flight;
+                evt_sender
+                    .gossip(
+                        space,
+                        to_agent,
+                        from_agent,
+                        Arc::new(op_hash.into()),
+                        op_data,
+                    )
+                    .await?;
+                // an empty response acknowledges the op is held
+                Ok(vec![])
+            }
+            .boxed()
+            .into()),
+            wire::Wire::Broadcast {
+                msg_id,
+                origin,
+                fanout,
+                ttl,
+                payload,
+            } => {
+                // already delivered via another peer
+                if !self
+                    .seen_broadcasts
+                    .insert(msg_id.clone(), to_agent.clone())
+                {
+                    return Ok(async move { Ok(vec![]) }.boxed().into());
+                }
+                let origin: Arc<KitsuneAgent> = Arc::new(origin.into());
+                if ttl > 0 {
+                    let candidates = self.reputation.rank(
+                        self.agents
+                            .keys()
+                            .filter(|a| ![&origin, &from_agent, &to_agent].contains(a))
+                            .cloned()
+                            .collect(),
+                    );
+                    let peers = choose_peers(&msg_id, &to_agent, candidates, fanout);
+                    let data = wire::Wire::broadcast(
+                        msg_id,
+                        (*origin).clone().into(),
+                        fanout,
+                        ttl - 1,
+                        payload.clone(),
+                    )
+                    .encode();
+                    // don't hold up delivery here on the next hop
+                    tokio::task::spawn(
+                        send_broadcast(
+                            self.internal_sender.clone(),
+                            space.clone(),
+                            to_agent.clone(),
+                            peers,
+                            Arc::new(data),
+                        )
+                        .instrument(tracing::debug_span!("wire_broadcast_forward")),
+                    );
+                }
+                Ok(async move {
+                    let _in_flight = in_flight;
+                    evt_sender.notify(space, to_agent, origin, payload).await?;
+                    Ok(vec![])
+                }
+                .boxed()
+                .into())
+            }
+            wire::Wire::Relay {
+                hops_left,
+                to_agent: target,
+                visited,
+                inner,
+            } => {
+                // to_agent is the peer being asked to pass the request on
+                let internal_sender = self.internal_sender.clone();
+                Ok(async move {
+                    let _in_flight = in_flight;
+                    let target: Arc<KitsuneAgent> = Arc::new(target.into());
+                    let mut visited: Vec<Arc<KitsuneAgent>> =
+                        visited.into_iter().map(|a| Arc::new(a.into())).collect();
+                    // never pass a request round in a loop
+                    if hops_left == 0 || visited.contains(&to_agent) {
+                        return Err(KitsuneP2pError::RoutingAgentError(target));
+                    }
+                    visited.push(to_agent);
+                    let inner = Arc::new(inner);
+                    match internal_sender
+                        .immediate_request(
+                            space.clone(),
+                            target.clone(),
+                            from_agent.clone(),
+                            inner.clone(),
+                        )
+                        .await
+                    {
+                        Err(_) if hops_left > 1 => {
+                            relay_via_peer(
+                                internal_sender,
+                                space,
+                                from_agent,
+                                target,
+                                hops_left - 1,
+                                visited,
+                                inner,
+                            )
+                            .await
+                        }
+                        res => res,
+                    }
+                }
+                .instrument(tracing::debug_span!("wire_relay"))
+                .boxed()
+                .into())
+            }
+            // the endpoint opens envelopes before they reach a space
+            wire::Wire::Envelope { .. } => Err(KitsuneP2pError::decoding_error(
+                "unexpected nested kitsune p2p agent envelope".to_string(),
+            )),
         }
     }
 
@@ -212,9 +411,39 @@ impl SpaceInternalHandler for Space {
         // we're ignoring the basis_hash and just returning everyone.
         _basis: Arc<KitsuneBasis>,
     ) -> SpaceInternalHandlerResult<Vec<Arc<KitsuneAgent>>> {
-        let res = self.agents.keys().cloned().collect();
+        let res = self.reputation.rank(self.agents.keys().cloned().collect());
+        Ok(async move { Ok(res) }.boxed().into())
+    }
+
+    fn handle_list_closest_agents_for_basis(
+        &mut self,
+        basis: Arc<KitsuneBasis>,
+        exclude: Arc<KitsuneAgent>,
+        count: u8,
+    ) -> SpaceInternalHandlerResult<Vec<Arc<KitsuneAgent>>> {
+        let basis_loc = basis.get_loc();
+        let mut res = self.reputation.rank(
+            self.agents
+                .keys()
+                .filter(|a| **a != exclude)
+                .cloned()
+                .collect(),
+        );
+        // sort is stable, so equally close agents keep their reputation order
+        res.sort_by_key(|a| ring_distance(a.get_loc(), basis_loc));
+        res.truncate(count as usize);
         Ok(async move { Ok(res) }.boxed().into())
     }
+
+    fn handle_report_peer(
+        &mut self,
+        agent: Arc<KitsuneAgent>,
+        report: PeerReport,
+    ) -> SpaceInternalHandlerResult<()> {
+        tracing::debug!(?agent, ?report, "peer reported");
+        self.reputation.report(agent, report);
+        Ok(async move { Ok(()) }.boxed().into())
+    }
 }
 
 impl ghost_actor::GhostControlHandler for Space {}
@@ -227,11 +456,18 @@ impl KitsuneP2pHandler for Space {
         _space: Arc<KitsuneSpace>,
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
-        match self.agents.entry(agent.clone()) {
-            Entry::Occupied(_) => (),
-            Entry::Vacant(entry) => {
-                entry.insert(AgentInfo { agent });
-            }
+        if !self.agents.contains_key(&agent) {
+            let claimed: Vec<DhtArc> = self.agents.values().map(|info| info.arc).collect();
+            let arc = initial_arc(&claimed);
+            tracing::debug!(?agent, ?arc, "claimed initial arc");
+            self.agents.insert(
+                agent.clone(),
+                AgentInfo {
+                    agent,
+                    arc,
+                    in_flight: Arc::new(()),
+                },
+            );
         }
         Ok(async move { Ok(()) }.boxed().into())
     }
@@ -241,8 +477,27 @@ impl KitsuneP2pHandler for Space {
         _space: Arc<KitsuneSpace>,
         agent: Arc<KitsuneAgent>,
     ) -> KitsuneP2pHandlerResult<()> {
-        self.agents.remove(&agent);
-        Ok(async move { Ok(()) }.boxed().into())
+        // once removed the agent isn't gossiped with, listed as online,
+        // or sent any new requests
+        let info = match self.agents.remove(&agent) {
+            None => return Ok(async move { Ok(()) }.boxed().into()),
+            Some(info) => info,
+        };
+        Ok(async move {
+            // let requests already on their way to the agent finish
+            let start = std::time::Instant::now();
+            while Arc::strong_count(&info.in_flight) > 1 {
+                if start.elapsed().as_millis() as u64 > LEAVE_DRAIN_MAX_MS {
+                    tracing::warn!(?agent, "left with requests still in flight");
+                    break;
+                }
+                tokio::time::delay_for(std::time::Duration::from_millis(NET_CONNECT_INTERVAL_MS))
+                    .await;
+            }
+            Ok(())
+        }
+        .boxed()
+        .into())
     }
 
     fn handle_rpc_single(
@@ -365,12 +620,160 @@ impl KitsuneP2pHandler for Space {
             Ok(inner_fut)
         }
     }
+
+    fn handle_publish(
+        &mut self,
+        mut input: actor::Publish,
+    ) -> KitsuneP2pHandlerResult<Vec<Arc<KitsuneAgent>>> {
+        // if the user doesn't care about remote_agent_count, apply default
+        match input.remote_agent_count {
+            None | Some(0) => {
+                input.remote_agent_count = Some(DEFAULT_PUBLISH_REMOTE_AGENT_COUNT);
+            }
+            _ => (),
+        }
+
+        // if the user doesn't care about timeout_ms, apply default
+        match input.timeout_ms {
+            None | Some(0) => {
+                input.timeout_ms = Some(DEFAULT_PUBLISH_TIMEOUT_MS);
+            }
+            _ => (),
+        }
+
+        self.handle_publish_inner(input)
+    }
+
+    fn handle_broadcast(&mut self, input: actor::Broadcast) -> KitsuneP2pHandlerResult<u8> {
+        let actor::Broadcast {
+            space,
+            from_agent,
+            fanout,
+            ttl,
+            payload,
+        } = input;
+
+        // if the user doesn't care about fanout, apply default
+        let fanout = match fanout {
+            None | Some(0) => DEFAULT_BROADCAST_FANOUT,
+            Some(fanout) => fanout,
+        };
+
+        // if the user doesn't care about ttl, apply default
+        // zero (0) is kept, as it means never forward
+        let ttl = ttl.unwrap_or(DEFAULT_BROADCAST_TTL);
+
+        // the sender's peers may send it straight back
+        let msg_id = self.seen_broadcasts.new_id(&from_agent);
+        self.seen_broadcasts
+            .insert(msg_id.clone(), from_agent.clone());
+
+        let candidates = self.reputation.rank(
+            self.agents
+                .keys()
+                .filter(|a| **a != from_agent)
+                .cloned()
+                .collect(),
+        );
+        let peers = choose_peers(&msg_id, &from_agent, candidates, fanout);
+        let data =
+            wire::Wire::broadcast(msg_id, (*from_agent).clone().into(), fanout, ttl, payload)
+                .encode();
+
+        Ok(send_broadcast(
+            self.internal_sender.clone(),
+            space,
+            from_agent,
+            peers,
+            Arc::new(data),
+        )
+        .instrument(tracing::debug_span!("broadcast"))
+        .boxed()
+        .into())
+    }
+
+    fn handle_report_peer(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        report: PeerReport,
+    ) -> KitsuneP2pHandlerResult<()> {
+        SpaceInternalHandler::handle_report_peer(self, agent, report)
+    }
+
+    fn handle_peer_reputations(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<(Arc<KitsuneAgent>, f64)>> {
+        let res = self.reputation.scores();
+        Ok(async move { Ok(res) }.boxed().into())
+    }
+
+    fn handle_agent_arcs(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<(Arc<KitsuneAgent>, DhtArc)>> {
+        let res = self
+            .agents
+            .iter()
+            .map(|(agent, info)| (agent.clone(), info.arc))
+            .collect::<Vec<_>>();
+        Ok(async move { Ok(res) }.boxed().into())
+    }
+
+    fn handle_resized_arc(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+    ) -> KitsuneP2pHandlerResult<(DhtArc, DhtArc)> {
+        let current = match self.agents.get(&agent) {
+            Some(info) => info.arc,
+            None => return Err(KitsuneP2pError::RoutingAgentError(agent)),
+        };
+        let neighbors: Vec<DhtArc> = self
+            .agents
+            .iter()
+            .filter(|(other, _)| **other != agent)
+            .map(|(_, info)| info.arc)
+            .collect();
+        let resized = resized_arc(current.center_loc, &neighbors);
+        Ok(async move { Ok((current, resized)) }.boxed().into())
+    }
+
+    fn handle_set_arc(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+        agent: Arc<KitsuneAgent>,
+        arc: DhtArc,
+    ) -> KitsuneP2pHandlerResult<()> {
+        let info = match self.agents.get_mut(&agent) {
+            Some(info) => info,
+            None => return Err(KitsuneP2pError::RoutingAgentError(agent)),
+        };
+        info.arc = arc;
+        tracing::debug!(?agent, ?arc, "set arc");
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_gossip_rounds(
+        &mut self,
+        _space: Arc<KitsuneSpace>,
+    ) -> KitsuneP2pHandlerResult<Vec<(Arc<KitsuneAgent>, Vec<GossipRound>)>> {
+        let res = self.gossip_metrics.rounds();
+        Ok(async move { Ok(res) }.boxed().into())
+    }
 }
 
 /// Local helper struct for associating info with a connected agent.
 struct AgentInfo {
     #[allow(dead_code)]
     agent: Arc<KitsuneAgent>,
+    /// The storage arc this agent claimed when it joined,
+    /// or was last set to
+    arc: DhtArc,
+    /// Cloned by each request to the agent until it's handled,
+    /// so leaving can wait for them
+    in_flight: Arc<()>,
 }
 
 /// A Kitsune P2p Node can track multiple "spaces" -- Non-interacting namespaced
@@ -379,7 +782,16 @@ pub(crate) struct Space {
     space: Arc<KitsuneSpace>,
     internal_sender: ghost_actor::GhostSender<SpaceInternal>,
     evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+    /// The endpoint requests are sent out over
+    endpoint: ghost_actor::GhostSender<Internal>,
     agents: HashMap<Arc<KitsuneAgent>, AgentInfo>,
+    /// Reputations aren't carried over if the space is restarted,
+    /// so every peer gets a clean slate.
+    reputation: ReputationStore,
+    /// The recent gossip rounds with each peer, for diagnostics
+    gossip_metrics: GossipMetrics,
+    /// The broadcasts already delivered to each agent
+    seen_broadcasts: SeenBroadcasts,
 }
 
 impl Space {
@@ -388,12 +800,17 @@ impl Space {
         space: Arc<KitsuneSpace>,
         internal_sender: ghost_actor::GhostSender<SpaceInternal>,
         evt_sender: futures::channel::mpsc::Sender<KitsuneP2pEvent>,
+        endpoint: ghost_actor::GhostSender<Internal>,
     ) -> Self {
         Self {
             space,
             internal_sender,
             evt_sender,
+            endpoint,
             agents: HashMap::new(),
+            reputation: ReputationStore::default(),
+            gossip_metrics: GossipMetrics::default(),
+            seen_broadcasts: SeenBroadcasts::default(),
         }
     }
 
@@ -410,13 +827,15 @@ impl Space {
             from_agent,
             basis,
             //remote_agent_count,
-            //timeout_ms,
+            timeout_ms,
             //as_race,
             //race_timeout_ms,
             payload,
             ..
         } = input;
 
+        let timeout_ms = timeout_ms.expect("set by handle_rpc_multi");
+
         // encode the data to send
         let payload = Arc::new(wire::Wire::call(payload).encode());
 
@@ -450,16 +869,53 @@ impl Space {
             // Timeout on immediate requests after a small interval.
             // TODO: 20 ms is only appropriate for local calls and not
             // real networking
-            if let Ok(Ok(response)) = tokio::time::timeout(
+            match tokio::time::timeout(
                 std::time::Duration::from_millis(20),
-                i_s.immediate_request(space, to_agent.clone(), from_agent.clone(), payload),
+                i_s.immediate_request(
+                    space.clone(),
+                    to_agent.clone(),
+                    from_agent.clone(),
+                    payload.clone(),
+                ),
             )
             .await
             {
-                out.push(actor::RpcMultiResponse {
+                Ok(Ok(response)) => out.push(actor::RpcMultiResponse {
                     agent: to_agent,
                     response,
-                });
+                }),
+                // reflecting to ourselves isn't the remote's fault
+                _ if to_agent == from_agent => (),
+                _ => {
+                    // the agent can't be reached directly,
+                    // see if a peer can get the request there for us
+                    let relayed = tokio::time::timeout(
+                        std::time::Duration::from_millis(timeout_ms),
+                        relay_via_peer(
+                            i_s.clone(),
+                            space,
+                            from_agent.clone(),
+                            to_agent.clone(),
+                            RPC_MULTI_MAX_RELAY_HOPS,
+                            vec![from_agent],
+                            payload,
+                        ),
+                    )
+                    .await
+                    .ok()
+                    .and_then(Result::ok);
+                    match relayed {
+                        Some(response) => out.push(actor::RpcMultiResponse {
+                            agent: to_agent,
+                            response,
+                        }),
+                        None => {
+                            if let Err(e) = i_s.report_peer(to_agent, PeerReport::Timeout).await {
+                                tracing::warn!(?e, "failed to report peer");
+                            }
+                        }
+                    }
+                }
             }
 
             Ok(out)
@@ -554,4 +1010,171 @@ impl Space {
         .boxed()
         .into())
     }
+
+    /// actual logic for handle_publish ...
+    /// pushes to every chosen authority at once, so the op is visible
+    /// as soon as the slowest of them acknowledges it
+    fn handle_publish_inner(
+        &mut self,
+        input: actor::Publish,
+    ) -> KitsuneP2pHandlerResult<Vec<Arc<KitsuneAgent>>> {
+        let actor::Publish {
+            space,
+            from_agent,
+            basis,
+            op_hash,
+            op_data,
+            remote_agent_count,
+            timeout_ms,
+        } = input;
+
+        let remote_agent_count = remote_agent_count.expect("set by handle_publish");
+        let timeout = std::time::Duration::from_millis(timeout_ms.expect("set by handle_publish"));
+
+        // encode the data to send
+        let payload = Arc::new(wire::Wire::publish((*op_hash).clone().into(), op_data).encode());
+
+        let internal_sender = self.internal_sender.clone();
+
+        Ok(async move {
+            let authorities = internal_sender
+                .list_closest_agents_for_basis(basis, from_agent.clone(), remote_agent_count)
+                .await?;
+
+            let pushes = authorities.into_iter().map(|to_agent| {
+                let internal_sender = internal_sender.clone();
+                let space = space.clone();
+                let from_agent = from_agent.clone();
+                let payload = payload.clone();
+                async move {
+                    match tokio::time::timeout(
+                        timeout,
+                        internal_sender.immediate_request(
+                            space,
+                            to_agent.clone(),
+                            from_agent,
+                            payload,
+                        ),
+                    )
+                    .await
+                    {
+                        Ok(Ok(_)) => Some(to_agent),
+                        // gossip will get the op there eventually
+                        Ok(Err(e)) => {
+                            tracing::debug!(?e, ?to_agent, "publish was not acknowledged");
+                            None
+                        }
+                        Err(_) => {
+                            if let Err(e) = internal_sender
+                                .report_peer(to_agent, PeerReport::Timeout)
+                                .await
+                            {
+                                tracing::warn!(?e, "failed to report peer");
+                            }
+                            None
+                        }
+                    }
+                }
+            });
+
+            Ok(futures::future::join_all(pushes)
+                .await
+                .into_iter()
+                .flatten()
+                .collect())
+        }
+        .instrument(tracing::debug_span!("publish_inner"))
+        .boxed()
+        .into())
+    }
+}
+
+/// Ask the first online peer which isn't already on the relay path to pass
+/// `data` on to `to_agent`.
+/// While we are short-circuit only, every peer reaches the same agents we
+/// do, so relaying won't find a way round until there is real networking.
+async fn relay_via_peer(
+    internal_sender: ghost_actor::GhostSender<SpaceInternal>,
+    space: Arc<KitsuneSpace>,
+    from_agent: Arc<KitsuneAgent>,
+    to_agent: Arc<KitsuneAgent>,
+    hops_left: u8,
+    visited: Vec<Arc<KitsuneAgent>>,
+    data: Arc<Vec<u8>>,
+) -> KitsuneP2pResult<Vec<u8>> {
+    // peers near the target are the most likely to be connected to it
+    let near: Arc<KitsuneBasis> = Arc::new(Vec::<u8>::from((*to_agent).clone()).into());
+    let peers = internal_sender
+        .list_online_agents_for_basis_hash(space.clone(), near)
+        .await?;
+    let relay_peer = match peers
+        .into_iter()
+        .find(|a| *a != to_agent && !visited.contains(a))
+    {
+        None => return Err(KitsuneP2pError::RoutingAgentError(to_agent)),
+        Some(relay_peer) => relay_peer,
+    };
+    let payload = wire::Wire::relay(
+        hops_left,
+        (*to_agent).clone().into(),
+        visited.into_iter().map(|a| (*a).clone().into()).collect(),
+        (*data).clone(),
+    )
+    .encode();
+    internal_sender
+        .immediate_request(space, relay_peer, from_agent, Arc::new(payload))
+        .await
+}
+
+/// Send an encoded broadcast from `from_agent` to each of `peers` at once.
+/// Returns how many acknowledged it in time; there's no retrying the rest,
+/// the other paths through the space should reach them.
+async fn send_broadcast(
+    internal_sender: ghost_actor::GhostSender<SpaceInternal>,
+    space: Arc<KitsuneSpace>,
+    from_agent: Arc<KitsuneAgent>,
+    peers: Vec<Arc<KitsuneAgent>>,
+    data: Arc<Vec<u8>>,
+) -> KitsuneP2pResult<u8> {
+    let timeout = std::time::Duration::from_millis(BROADCAST_TIMEOUT_MS);
+    let sends = peers.into_iter().map(|to_agent| {
+        let internal_sender = internal_sender.clone();
+        let space = space.clone();
+        let from_agent = from_agent.clone();
+        let data = data.clone();
+        async move {
+            match tokio::time::timeout(
+                timeout,
+                internal_sender.immediate_request(space, to_agent.clone(), from_agent, data),
+            )
+            .await
+            {
+                Ok(Ok(_)) => true,
+                Ok(Err(e)) => {
+                    tracing::debug!(?e, ?to_agent, "broadcast was not acknowledged");
+                    false
+                }
+                Err(_) => {
+                    if let Err(e) = internal_sender
+                        .report_peer(to_agent, PeerReport::Timeout)
+                        .await
+                    {
+                        tracing::warn!(?e, "failed to report peer");
+                    }
+                    false
+                }
+            }
+        }
+    });
+    Ok(futures::future::join_all(sends)
+        .await
+        .into_iter()
+        .filter(|acked| *acked)
+        .count() as u8)
+}
+
+/// The distance between two locations, going whichever way round the
+/// dht ring is shorter
+pub(crate) fn ring_distance(a: u32, b: u32) -> u32 {
+    std::cmp::min(a.wrapping_sub(b), b.wrapping_sub(a))
 }
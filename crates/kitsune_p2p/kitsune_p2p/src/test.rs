@@ -5,6 +5,7 @@ mod tests {
         spawn::*,
         types::{actor::KitsuneP2pSender, *},
     };
+    use assert_matches::assert_matches;
     use futures::future::FutureExt;
     use ghost_actor::GhostControlSender;
     use std::sync::Arc;
@@ -65,6 +66,55 @@ mod tests {
         r_task.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_leave_workflow() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+
+        let (p2p, mut evt) = spawn_kitsune_p2p().await.unwrap();
+
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                if let KitsuneP2pEvent::Call { respond, .. } = evt {
+                    respond.r(Ok(async move { Ok(b"echo".to_vec()) }.boxed().into()));
+                }
+            }
+        });
+
+        p2p.join(space1.clone(), a1.clone()).await.unwrap();
+        p2p.join(space1.clone(), a2.clone()).await.unwrap();
+
+        // a2 can no longer be reached once it has left
+        p2p.leave(space1.clone(), a2.clone()).await.unwrap();
+        let res = p2p
+            .rpc_single(space1.clone(), a2.clone(), a1.clone(), b"hello".to_vec())
+            .await;
+        assert_matches!(res, Err(KitsuneP2pError::RoutingAgentError(_)));
+
+        // the last agent leaving frees the space
+        p2p.leave(space1.clone(), a1.clone()).await.unwrap();
+        let res = p2p
+            .rpc_single(space1.clone(), a1.clone(), a1.clone(), b"hello".to_vec())
+            .await;
+        assert_matches!(res, Err(KitsuneP2pError::RoutingSpaceError(_)));
+
+        // and joining again starts it afresh
+        p2p.join(space1.clone(), a1.clone()).await.unwrap();
+        let res = p2p
+            .rpc_single(space1, a1.clone(), a1, b"hello".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(b"echo".to_vec(), res);
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_broadcast_workflow() {
         let space1: Arc<KitsuneSpace> =
@@ -131,6 +181,160 @@ mod tests {
         r_task.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_flood_broadcast_workflow() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+        let a3: Arc<KitsuneAgent> =
+            Arc::new(b"333333333333333333333333333333333333".to_vec().into());
+        let a4: Arc<KitsuneAgent> =
+            Arc::new(b"444444444444444444444444444444444444".to_vec().into());
+
+        let (p2p, mut evt) = spawn_kitsune_p2p().await.unwrap();
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let a1_clone = a1.clone();
+        let received_clone = received.clone();
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use KitsuneP2pEvent::*;
+                match evt {
+                    Notify {
+                        respond,
+                        to_agent,
+                        from_agent,
+                        payload,
+                        ..
+                    } => {
+                        // forwarded copies still come from the sender
+                        assert_eq!(from_agent, a1_clone);
+                        assert_eq!(&*payload, b"test-flood");
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                        received_clone.lock().unwrap().push(to_agent);
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        for agent in &[&a1, &a2, &a3, &a4] {
+            p2p.join(space1.clone(), (*agent).clone()).await.unwrap();
+        }
+
+        // a1 reaches two peers, who each pass it on to the other two
+        // agents, one of which already has it
+        let res = p2p
+            .broadcast(actor::Broadcast {
+                space: space1,
+                from_agent: a1,
+                fanout: Some(2),
+                ttl: Some(1),
+                payload: b"test-flood".to_vec(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(2, res);
+
+        // forwarding happens in the background
+        tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+        assert_eq!(received, vec![a2, a3, a4]);
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_publish_workflow() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+        let a3: Arc<KitsuneAgent> =
+            Arc::new(b"333333333333333333333333333333333333".to_vec().into());
+        // closer to a3's location than a2's
+        let basis: Arc<KitsuneBasis> =
+            Arc::new(b"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_vec().into());
+        let op_hash: Arc<KitsuneOpHash> =
+            Arc::new(b"oooooooooooooooooooooooooooooooooooo".to_vec().into());
+
+        let (p2p, mut evt) = spawn_kitsune_p2p().await.unwrap();
+
+        let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let received_clone = received.clone();
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                use KitsuneP2pEvent::*;
+                match evt {
+                    Gossip {
+                        respond,
+                        to_agent,
+                        op_hash,
+                        op_data,
+                        ..
+                    } => {
+                        received_clone
+                            .lock()
+                            .unwrap()
+                            .push((to_agent, op_hash, op_data));
+                        respond.r(Ok(async move { Ok(()) }.boxed().into()));
+                    }
+                    // gossip is running too
+                    FetchOpHashesForConstraints { respond, .. } => {
+                        respond.r(Ok(async move { Ok(vec![]) }.boxed().into()));
+                    }
+                    _ => (),
+                }
+            }
+        });
+
+        p2p.join(space1.clone(), a1.clone()).await.unwrap();
+        p2p.join(space1.clone(), a2.clone()).await.unwrap();
+        p2p.join(space1.clone(), a3.clone()).await.unwrap();
+
+        let publish = actor::Publish {
+            space: space1,
+            from_agent: a1,
+            basis,
+            op_hash: op_hash.clone(),
+            op_data: b"op".to_vec(),
+            remote_agent_count: Some(1),
+            timeout_ms: Some(200),
+        };
+
+        // only the closest authority is pushed to
+        let acked = p2p.publish(publish.clone()).await.unwrap();
+        assert_eq!(vec![a3.clone()], acked);
+        assert_eq!(
+            vec![(a3.clone(), op_hash.clone(), b"op".to_vec())],
+            *received.lock().unwrap()
+        );
+
+        // the author is never pushed to
+        let acked = p2p
+            .publish(actor::Publish {
+                remote_agent_count: Some(5),
+                ..publish
+            })
+            .await
+            .unwrap();
+        assert_eq!(vec![a3, a2], acked);
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_multi_request_workflow() {
         let space1: Arc<KitsuneSpace> =
@@ -200,6 +404,69 @@ mod tests {
         r_task.await.unwrap();
     }
 
+    #[tokio::test(threaded_scheduler)]
+    async fn test_relay_workflow() {
+        let space1: Arc<KitsuneSpace> =
+            Arc::new(b"ssssssssssssssssssssssssssssssssssss".to_vec().into());
+        let a1: Arc<KitsuneAgent> =
+            Arc::new(b"111111111111111111111111111111111111".to_vec().into());
+        let a2: Arc<KitsuneAgent> =
+            Arc::new(b"222222222222222222222222222222222222".to_vec().into());
+        let a3: Arc<KitsuneAgent> =
+            Arc::new(b"333333333333333333333333333333333333".to_vec().into());
+
+        let (p2p, mut evt) = spawn_kitsune_p2p().await.unwrap();
+
+        let calls = Arc::new(std::sync::atomic::AtomicU8::new(0));
+
+        let calls_clone = calls.clone();
+        let r_task = tokio::task::spawn(async move {
+            use tokio::stream::StreamExt;
+            while let Some(evt) = evt.next().await {
+                if let KitsuneP2pEvent::Call { respond, .. } = evt {
+                    // the first request fails,
+                    // as if the agent couldn't be reached directly
+                    let call = calls_clone.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    respond.r(Ok(async move {
+                        if call == 0 {
+                            Err(KitsuneP2pError::from("unreachable"))
+                        } else {
+                            Ok(b"echo".to_vec())
+                        }
+                    }
+                    .boxed()
+                    .into()));
+                }
+            }
+        });
+
+        p2p.join(space1.clone(), a1.clone()).await.unwrap();
+        p2p.join(space1.clone(), a2.clone()).await.unwrap();
+        p2p.join(space1.clone(), a3.clone()).await.unwrap();
+
+        let input = actor::RpcMulti {
+            space: space1,
+            from_agent: a1,
+            // this is just a dummy value right now
+            basis: Arc::new(b"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb".to_vec().into()),
+            remote_agent_count: Some(1),
+            timeout_ms: Some(200),
+            as_race: false,
+            race_timeout_ms: None,
+            payload: b"hello".to_vec(),
+        };
+
+        // the request gets there via the other agent
+        let res = p2p.rpc_multi(input).await.unwrap();
+        assert_eq!(1, res.len());
+        assert_eq!(b"echo".to_vec(), res[0].response);
+        assert!(res[0].agent == a2 || res[0].agent == a3);
+        assert_eq!(2, calls.load(std::sync::atomic::Ordering::SeqCst));
+
+        p2p.ghost_actor_shutdown().await.unwrap();
+        r_task.await.unwrap();
+    }
+
     #[tokio::test(threaded_scheduler)]
     async fn test_single_agent_multi_request_workflow() {
         let space1: Arc<KitsuneSpace> =
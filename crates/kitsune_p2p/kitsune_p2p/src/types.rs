@@ -1,3 +1,4 @@
+use holochain_error_kind::{kind_of_boxed, ErrorKind, HasErrorKind};
 use std::sync::Arc;
 
 /// KitsuneP2p Error Type.
@@ -20,6 +21,10 @@ pub enum KitsuneP2pError {
     #[error("Decoding Error: {0}")]
     DecodingError(Arc<String>),
 
+    /// The space actor couldn't be started
+    #[error("Space failed to start: {0}")]
+    SpaceInitError(#[from] kitsune_p2p_types::async_lazy::AsyncLazyError),
+
     /// Other
     #[error("Other: {0}")]
     Other(Box<dyn std::error::Error + Send + Sync>),
@@ -37,6 +42,19 @@ impl KitsuneP2pError {
     }
 }
 
+impl HasErrorKind for KitsuneP2pError {
+    fn error_kind(&self) -> ErrorKind {
+        use KitsuneP2pError::*;
+        match self {
+            GhostError(_) => ErrorKind::Unavailable,
+            RoutingSpaceError(_) | RoutingAgentError(_) => ErrorKind::NotFound,
+            DecodingError(_) => ErrorKind::Serialization,
+            SpaceInitError(e) => e.error_kind(),
+            Other(e) => kind_of_boxed(e.as_ref()),
+        }
+    }
+}
+
 impl From<String> for KitsuneP2pError {
     fn from(s: String) -> Self {
         #[derive(Debug, thiserror::Error)]
@@ -177,3 +195,4 @@ pub mod event;
 pub(crate) mod wire;
 
 pub use kitsune_p2p_types::dht_arc;
+pub use kitsune_p2p_types::restart;
@@ -62,6 +62,85 @@ pub struct NotifyMulti {
     pub payload: Vec<u8>,
 }
 
+/// Push a newly authored op straight to the agents closest to its basis,
+/// rather than waiting for gossip to carry it there.
+#[derive(Clone, Debug)]
+pub struct Publish {
+    /// The "space" context.
+    pub space: Arc<super::KitsuneSpace>,
+    /// The agent which authored the op.
+    pub from_agent: Arc<super::KitsuneAgent>,
+    /// The "basis" hash/coordinate the op is stored at.
+    pub basis: Arc<super::KitsuneBasis>,
+    /// The hash of the op.
+    pub op_hash: Arc<super::KitsuneOpHash>,
+    /// The op itself.
+    pub op_data: Vec<u8>,
+    /// How many of the closest authorities to push to.
+    /// Set to None for the default.
+    pub remote_agent_count: Option<u8>,
+    /// How long to wait for authorities to acknowledge the op.
+    /// Set to None for the default.
+    pub timeout_ms: Option<u64>,
+}
+
+/// Flood a small message to every agent in a space, e.g. a signal for many
+/// agents at once. Each agent it reaches passes it on to `fanout` peers,
+/// until it has gone `ttl` hops from the sender. Agents which receive it
+/// more than once only see it once.
+/// Unlike gossip nothing is stored or retried, so it's quick but not
+/// guaranteed to reach everyone.
+#[derive(Clone, Debug)]
+pub struct Broadcast {
+    /// The "space" context.
+    pub space: Arc<super::KitsuneSpace>,
+    /// The agent sending the message.
+    pub from_agent: Arc<super::KitsuneAgent>,
+    /// How many peers each agent passes the message on to.
+    /// Set to None or zero (0) for the default.
+    pub fanout: Option<u8>,
+    /// How many hops from the sender the message may go.
+    /// Set to None for the default, or `Some(0)` to only reach the
+    /// sender's own peers.
+    pub ttl: Option<u8>,
+    /// Broadcast data.
+    pub payload: Vec<u8>,
+}
+
+/// Something a remote agent did wrong, which counts against its reputation.
+/// Agents with poor reputations are avoided when choosing who to make
+/// requests of, or gossip with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PeerReport {
+    /// The agent didn't respond in time
+    Timeout,
+    /// The agent responded with data that wasn't what was asked for,
+    /// or that failed integrity checks
+    BadResponse,
+    /// The agent sent us data that failed validation
+    ValidationFailure,
+    /// The agent responded with an error instead of an answer
+    Error,
+}
+
+/// The outcome of one round of gossip with a peer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GossipRound {
+    /// When the round started, in seconds since the UNIX epoch.
+    pub started_utc_epoch_s: i64,
+    /// How long the round took.
+    pub duration_ms: u64,
+    /// How many ops the peer held which we didn't.
+    pub ops_offered: u32,
+    /// How many of the offered ops we fetched from the peer and took.
+    pub ops_accepted: u32,
+    /// How many ops we held which the peer didn't, and sent it.
+    pub ops_sent: u32,
+    /// Everything that went wrong during the round.
+    /// The round is cut short by an error listing op hashes.
+    pub errors: Vec<String>,
+}
+
 ghost_actor::ghost_chan! {
     /// The KitsuneP2pSender allows async remote-control of the KitsuneP2p actor.
     pub chan KitsuneP2p<super::KitsuneP2pError> {
@@ -83,5 +162,43 @@ ghost_actor::ghost_chan! {
         /// Returns an approximate number of nodes reached.
         /// The remote sides will see these messages as "Notify" events.
         fn notify_multi(input: NotifyMulti) -> u8;
+
+        /// Push an op to the authorities closest to its basis.
+        /// Returns the agents which acknowledged holding it in time,
+        /// gossip will carry it to the rest.
+        /// The remote sides will see these messages as "Gossip" events.
+        fn publish(input: Publish) -> Vec<Arc<super::KitsuneAgent>>;
+
+        /// Flood a message through a space.
+        /// Returns how many of the sender's own peers acknowledged it.
+        /// The remote sides will see these messages as "Notify" events
+        /// from the sender.
+        fn broadcast(input: Broadcast) -> u8;
+
+        /// Report a remote agent for misbehaving in this space,
+        /// lowering its reputation.
+        fn report_peer(space: Arc<super::KitsuneSpace>, agent: Arc<super::KitsuneAgent>, report: PeerReport) -> ();
+
+        /// The current reputation of every agent that has been reported in
+        /// this space, from 1.0 (nothing against it) towards 0.0.
+        fn peer_reputations(space: Arc<super::KitsuneSpace>) -> Vec<(Arc<super::KitsuneAgent>, f64)>;
+
+        /// The storage arc claimed by every agent joined to this space.
+        /// Joining agents pick theirs to fill the largest gap in these.
+        fn agent_arcs(space: Arc<super::KitsuneSpace>) -> Vec<(Arc<super::KitsuneAgent>, super::dht_arc::DhtArc)>;
+
+        /// What a local agent's storage arc would be resized to, keeping its
+        /// center, to reach the nearest other agents' centers on either side.
+        /// Returns the agent's current arc and the resized one. Nothing
+        /// changes until the resized arc is set, so anything held in the
+        /// part being given up can be handed off first.
+        fn resized_arc(space: Arc<super::KitsuneSpace>, agent: Arc<super::KitsuneAgent>) -> (super::dht_arc::DhtArc, super::dht_arc::DhtArc);
+
+        /// Set a local agent's storage arc.
+        fn set_arc(space: Arc<super::KitsuneSpace>, agent: Arc<super::KitsuneAgent>, arc: super::dht_arc::DhtArc) -> ();
+
+        /// The most recent gossip rounds with each peer in this space,
+        /// oldest first.
+        fn gossip_rounds(space: Arc<super::KitsuneSpace>) -> Vec<(Arc<super::KitsuneAgent>, Vec<GossipRound>)>;
     }
 }
@@ -39,6 +39,19 @@ pub struct SignNetworkDataEvt {
     pub data: Arc<Vec<u8>>,
 }
 
+/// A space actor terminated unexpectedly and has been restarted.
+#[derive(Debug)]
+pub struct SpaceRestartedEvt {
+    /// The "space" context.
+    pub space: Arc<super::KitsuneSpace>,
+    /// The agents which were re-joined to the restarted space.
+    pub agents: Vec<Arc<super::KitsuneAgent>>,
+    /// Why the previous space actor terminated.
+    pub reason: String,
+    /// How many times this space has been restarted since it was last stable.
+    pub restart_count: u32,
+}
+
 ghost_actor::ghost_chan! {
     /// The KitsuneP2pEvent stream allows handling events generated from the
     /// KitsuneP2p actor.
@@ -66,6 +79,9 @@ ghost_actor::ghost_chan! {
 
         /// Request that our implementor sign some data on behalf of an agent.
         fn sign_network_data(input: SignNetworkDataEvt) -> super::KitsuneSignature;
+
+        /// A space actor died and was restarted with its agents re-joined.
+        fn space_restarted(input: SpaceRestartedEvt) -> ();
     }
 }
 
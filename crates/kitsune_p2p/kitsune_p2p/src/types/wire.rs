@@ -10,6 +10,38 @@ use crate::types::KitsuneP2pError;
 pub enum Wire {
     Call(Vec<u8>),
     Notify(Vec<u8>),
+    /// A newly authored op pushed straight to an authority for its basis
+    Publish {
+        op_hash: Vec<u8>,
+        op_data: Vec<u8>,
+    },
+    /// A message flooded through a space, passed on to `fanout` peers
+    /// by each agent it reaches while `ttl` hops remain
+    Broadcast {
+        msg_id: Vec<u8>,
+        origin: Vec<u8>,
+        fanout: u8,
+        ttl: u8,
+        payload: Vec<u8>,
+    },
+    /// A request which couldn't be delivered directly, passed via peers
+    /// towards `to_agent`. `visited` is every agent the request has been
+    /// through, so it never goes round in a loop.
+    Relay {
+        hops_left: u8,
+        to_agent: Vec<u8>,
+        visited: Vec<Vec<u8>>,
+        inner: Vec<u8>,
+    },
+    /// A message for one local agent of a space. Every agent of every space
+    /// on an endpoint shares the same connections, so each message is tagged
+    /// with who it is for and who it is from.
+    Envelope {
+        space: Vec<u8>,
+        to_agent: Vec<u8>,
+        from_agent: Vec<u8>,
+        inner: Vec<u8>,
+    },
 }
 
 impl Wire {
@@ -28,6 +60,49 @@ impl Wire {
     pub fn notify(payload: Vec<u8>) -> Self {
         Self::Notify(payload)
     }
+
+    pub fn publish(op_hash: Vec<u8>, op_data: Vec<u8>) -> Self {
+        Self::Publish { op_hash, op_data }
+    }
+
+    pub fn broadcast(
+        msg_id: Vec<u8>,
+        origin: Vec<u8>,
+        fanout: u8,
+        ttl: u8,
+        payload: Vec<u8>,
+    ) -> Self {
+        Self::Broadcast {
+            msg_id,
+            origin,
+            fanout,
+            ttl,
+            payload,
+        }
+    }
+
+    pub fn relay(hops_left: u8, to_agent: Vec<u8>, visited: Vec<Vec<u8>>, inner: Vec<u8>) -> Self {
+        Self::Relay {
+            hops_left,
+            to_agent,
+            visited,
+            inner,
+        }
+    }
+
+    pub fn envelope(
+        space: Vec<u8>,
+        to_agent: Vec<u8>,
+        from_agent: Vec<u8>,
+        inner: Vec<u8>,
+    ) -> Self {
+        Self::Envelope {
+            space,
+            to_agent,
+            from_agent,
+            inner,
+        }
+    }
 }
 
 // -- private -- //
@@ -49,6 +124,53 @@ const WIRE_CALL: u8 = 0x10;
 /// a kitsune notify message
 const WIRE_NOTIFY: u8 = 0x20;
 
+/// a kitsune publish message
+const WIRE_PUBLISH: u8 = 0x30;
+
+/// a kitsune relay message
+const WIRE_RELAY: u8 = 0x40;
+
+/// a kitsune broadcast message
+const WIRE_BROADCAST: u8 = 0x50;
+
+/// a kitsune agent envelope message
+const WIRE_ENVELOPE: u8 = 0x60;
+
+/// append a length prefixed field
+fn push_prefixed(out: &mut Vec<u8>, field: &[u8]) {
+    out.extend_from_slice(&(field.len() as u32).to_le_bytes());
+    out.extend_from_slice(field);
+}
+
+/// take a single byte off the front of `data`
+fn take_u8(data: &mut &[u8]) -> Result<u8, KitsuneP2pError> {
+    match data.split_first() {
+        Some((byte, rest)) => {
+            *data = rest;
+            Ok(*byte)
+        }
+        None => Err(truncated()),
+    }
+}
+
+/// take a length prefixed field off the front of `data`
+fn take_prefixed(data: &mut &[u8]) -> Result<Vec<u8>, KitsuneP2pError> {
+    if data.len() < 4 {
+        return Err(truncated());
+    }
+    let len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    if data.len() - 4 < len {
+        return Err(truncated());
+    }
+    let field = data[4..4 + len].to_vec();
+    *data = &data[4 + len..];
+    Ok(field)
+}
+
+fn truncated() -> KitsuneP2pError {
+    KitsuneP2pError::decoding_error("kitsune p2p message is truncated".to_string())
+}
+
 impl Wire {
     fn priv_encode_inner(msg_type: u8, mut msg: Vec<u8>) -> Vec<u8> {
         let mut out = Vec::with_capacity(msg.len() + 4);
@@ -64,6 +186,62 @@ impl Wire {
         match self {
             Wire::Call(payload) => Wire::priv_encode_inner(WIRE_CALL, payload),
             Wire::Notify(payload) => Wire::priv_encode_inner(WIRE_NOTIFY, payload),
+            Wire::Publish {
+                op_hash,
+                mut op_data,
+            } => {
+                // the op hash is length prefixed, the op data is the rest
+                let mut payload = Vec::with_capacity(op_hash.len() + op_data.len() + 4);
+                push_prefixed(&mut payload, &op_hash);
+                payload.append(&mut op_data);
+                Wire::priv_encode_inner(WIRE_PUBLISH, payload)
+            }
+            Wire::Broadcast {
+                msg_id,
+                origin,
+                fanout,
+                ttl,
+                mut payload,
+            } => {
+                // the broadcast payload is the rest
+                let mut out = vec![fanout, ttl];
+                push_prefixed(&mut out, &msg_id);
+                push_prefixed(&mut out, &origin);
+                out.append(&mut payload);
+                Wire::priv_encode_inner(WIRE_BROADCAST, out)
+            }
+            Wire::Relay {
+                hops_left,
+                to_agent,
+                visited,
+                mut inner,
+            } => {
+                // the inner message is the rest
+                let mut payload = vec![hops_left];
+                push_prefixed(&mut payload, &to_agent);
+                payload.push(visited.len() as u8);
+                for agent in visited {
+                    push_prefixed(&mut payload, &agent);
+                }
+                payload.append(&mut inner);
+                Wire::priv_encode_inner(WIRE_RELAY, payload)
+            }
+            Wire::Envelope {
+                space,
+                to_agent,
+                from_agent,
+                mut inner,
+            } => {
+                // the inner message is the rest
+                let mut payload = Vec::with_capacity(
+                    space.len() + to_agent.len() + from_agent.len() + inner.len() + 12,
+                );
+                push_prefixed(&mut payload, &space);
+                push_prefixed(&mut payload, &to_agent);
+                push_prefixed(&mut payload, &from_agent);
+                payload.append(&mut inner);
+                Wire::priv_encode_inner(WIRE_ENVELOPE, payload)
+            }
         }
     }
 
@@ -77,6 +255,54 @@ impl Wire {
                 data.drain(..4);
                 Ok(Wire::Notify(data))
             }
+            [KITSUNE_MAGIC_1, KITSUNE_MAGIC_2, KITSUNE_PROTO_VER, WIRE_PUBLISH, ..] => {
+                let mut rest = &data[4..];
+                let op_hash = take_prefixed(&mut rest)?;
+                Ok(Wire::Publish {
+                    op_hash,
+                    op_data: rest.to_vec(),
+                })
+            }
+            [KITSUNE_MAGIC_1, KITSUNE_MAGIC_2, KITSUNE_PROTO_VER, WIRE_BROADCAST, ..] => {
+                let mut rest = &data[4..];
+                let fanout = take_u8(&mut rest)?;
+                let ttl = take_u8(&mut rest)?;
+                let msg_id = take_prefixed(&mut rest)?;
+                let origin = take_prefixed(&mut rest)?;
+                Ok(Wire::Broadcast {
+                    msg_id,
+                    origin,
+                    fanout,
+                    ttl,
+                    payload: rest.to_vec(),
+                })
+            }
+            [KITSUNE_MAGIC_1, KITSUNE_MAGIC_2, KITSUNE_PROTO_VER, WIRE_RELAY, ..] => {
+                let mut rest = &data[4..];
+                let hops_left = take_u8(&mut rest)?;
+                let to_agent = take_prefixed(&mut rest)?;
+                let visited = (0..take_u8(&mut rest)?)
+                    .map(|_| take_prefixed(&mut rest))
+                    .collect::<Result<_, _>>()?;
+                Ok(Wire::Relay {
+                    hops_left,
+                    to_agent,
+                    visited,
+                    inner: rest.to_vec(),
+                })
+            }
+            [KITSUNE_MAGIC_1, KITSUNE_MAGIC_2, KITSUNE_PROTO_VER, WIRE_ENVELOPE, ..] => {
+                let mut rest = &data[4..];
+                let space = take_prefixed(&mut rest)?;
+                let to_agent = take_prefixed(&mut rest)?;
+                let from_agent = take_prefixed(&mut rest)?;
+                Ok(Wire::Envelope {
+                    space,
+                    to_agent,
+                    from_agent,
+                    inner: rest.to_vec(),
+                })
+            }
             _ => Err(KitsuneP2pError::decoding_error(
                 "invalid or corrupt kitsune p2p message".to_string(),
             )),
@@ -100,6 +326,76 @@ mod tests {
         assert_matches!(res, Ok(Wire::Call(vec)) if vec.is_empty());
     }
 
+    #[test]
+    fn publish_round_trip() {
+        let res = Wire::decode(Wire::publish(vec![1, 2, 3], vec![4, 5]).encode());
+        assert_matches!(
+            res,
+            Ok(Wire::Publish { op_hash, op_data }) if op_hash == vec![1, 2, 3] && op_data == vec![4, 5]
+        );
+    }
+
+    #[test]
+    fn bad_decode_publish_hash_len() {
+        let res = Wire::decode(vec![
+            KITSUNE_MAGIC_1,
+            KITSUNE_MAGIC_2,
+            KITSUNE_PROTO_VER,
+            WIRE_PUBLISH,
+            4,
+            0,
+            0,
+            0,
+            1,
+        ]);
+        assert_matches!(res, Err(KitsuneP2pError::DecodingError(_)));
+    }
+
+    #[test]
+    fn relay_round_trip() {
+        let inner = Wire::call(b"hello".to_vec()).encode();
+        let res = Wire::decode(
+            Wire::relay(2, vec![1], vec![vec![2], vec![3, 3]], inner.clone()).encode(),
+        );
+        assert_matches!(
+            res,
+            Ok(Wire::Relay { hops_left: 2, to_agent, visited, inner: res_inner })
+                if to_agent == vec![1] && visited == vec![vec![2], vec![3, 3]] && res_inner == inner
+        );
+    }
+
+    #[test]
+    fn bad_decode_relay_visited() {
+        let mut data = Wire::relay(2, vec![1], vec![vec![2]], vec![]).encode();
+        // claim there are more visited agents than there are
+        data[4 + 1 + 4 + 1] = 2;
+        let res = Wire::decode(data);
+        assert_matches!(res, Err(KitsuneP2pError::DecodingError(_)));
+    }
+
+    #[test]
+    fn envelope_round_trip() {
+        let inner = Wire::call(b"hello".to_vec()).encode();
+        let res =
+            Wire::decode(Wire::envelope(vec![1], vec![2, 2], vec![3], inner.clone()).encode());
+        assert_matches!(
+            res,
+            Ok(Wire::Envelope { space, to_agent, from_agent, inner: res_inner })
+                if space == vec![1] && to_agent == vec![2, 2] && from_agent == vec![3] && res_inner == inner
+        );
+    }
+
+    #[test]
+    fn broadcast_round_trip() {
+        let res =
+            Wire::decode(Wire::broadcast(vec![1, 2], vec![3], 4, 5, b"hello".to_vec()).encode());
+        assert_matches!(
+            res,
+            Ok(Wire::Broadcast { msg_id, origin, fanout: 4, ttl: 5, payload })
+                if msg_id == vec![1, 2] && origin == vec![3] && payload == b"hello".to_vec()
+        );
+    }
+
     #[test]
     fn bad_decode_size() {
         let res = Wire::decode(vec![KITSUNE_MAGIC_1, KITSUNE_MAGIC_2, KITSUNE_PROTO_VER]);
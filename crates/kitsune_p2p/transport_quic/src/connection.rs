@@ -1,3 +1,4 @@
+use crate::{listener::*, punch::*};
 use futures::{future::FutureExt, stream::StreamExt};
 use kitsune_p2p_types::{
     dependencies::{ghost_actor, url2::*},
@@ -45,14 +46,15 @@ impl TransportConnectionHandler for TransportConnectionQuic {
 }
 
 /// Spawn a new QUIC TransportConnectionSender.
+/// The connection is registered with the listener which made or accepted it,
+/// so it can be used to coordinate hole punching.
 pub(crate) async fn spawn_transport_connection_quic(
-    maybe_con: quinn::Connecting,
+    con: quinn::NewConnection,
+    listener: ghost_actor::GhostSender<ListenerInner>,
 ) -> TransportConnectionResult<(
     ghost_actor::GhostSender<TransportConnection>,
     TransportConnectionEventReceiver,
 )> {
-    let con = maybe_con.await.map_err(TransportError::other)?;
-
     let quinn::NewConnection {
         connection,
         mut bi_streams,
@@ -68,11 +70,17 @@ pub(crate) async fn spawn_transport_connection_quic(
         .create_channel::<TransportConnection>()
         .await?;
 
+    let remote_addr = connection.remote_address();
+    let id = listener
+        .register_connection(remote_addr, sender.clone())
+        .await?;
+
     let sender_clone = sender.clone();
     tokio::task::spawn(async move {
         while let Some(Ok((mut bi_send, bi_recv))) = bi_streams.next().await {
             let sender_clone = sender_clone.clone();
             let incoming_sender = incoming_sender.clone();
+            let listener = listener.clone();
             tokio::task::spawn(async move {
                 let req_data = bi_recv
                     .read_to_end(std::usize::MAX)
@@ -83,7 +91,12 @@ pub(crate) async fn spawn_transport_connection_quic(
                     .await
                     .map_err(TransportError::other)?;
 
-                let res_data = incoming_sender.incoming_request(url, req_data).await?;
+                let res_data = match PunchMsg::decode(&req_data) {
+                    None => incoming_sender.incoming_request(url, req_data).await?,
+                    Some(msg) => {
+                        encode_response(handle_punch(&listener, &sender_clone, url, msg).await)
+                    }
+                };
 
                 bi_send
                     .write_all(&res_data)
@@ -94,6 +107,8 @@ pub(crate) async fn spawn_transport_connection_quic(
                 TransportResult::Ok(())
             });
         }
+        // the connection is closed
+        listener.unregister_connection(remote_addr, id).await.ok();
     });
 
     let actor = TransportConnectionQuic {
@@ -103,3 +118,22 @@ pub(crate) async fn spawn_transport_connection_quic(
 
     Ok((sender, receiver))
 }
+
+/// Handle a hole punching message from the peer at `from` on `con`
+async fn handle_punch(
+    listener: &ghost_actor::GhostSender<ListenerInner>,
+    con: &ghost_actor::GhostSender<TransportConnection>,
+    from: Url2,
+    msg: TransportResult<PunchMsg>,
+) -> TransportResult<()> {
+    match msg? {
+        PunchMsg::Request { target } => listener.relay_punch(from, target).await,
+        PunchMsg::Notify { peer } => {
+            // Unless we asked for this, have the peer confirm it did
+            if !listener.punch_requested(observed_addr(&from)?).await? {
+                request_punch(listener, con, peer.clone()).await?;
+            }
+            listener.punch_toward(peer).await
+        }
+    }
+}
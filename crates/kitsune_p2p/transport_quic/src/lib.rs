@@ -52,4 +52,6 @@ mod connection;
 mod listener;
 pub use listener::*;
 
+mod punch;
+
 mod test;
@@ -1,23 +1,89 @@
+use crate::punch::*;
 use futures::{future::FutureExt, stream::StreamExt};
+use ghost_actor::dependencies::tracing;
 use kitsune_p2p_types::{
     dependencies::{ghost_actor, url2::*},
     transport::transport_connection::*,
     transport::transport_listener::*,
     transport::*,
 };
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+/// How long a probe may take to connect before the address is
+/// counted as unreachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 
 ghost_actor::ghost_chan! {
-    chan ListenerInner<TransportError> {
+    pub(crate) chan ListenerInner<TransportError> {
         /// internal raw connect fn
         fn raw_connect(addr: SocketAddr) -> quinn::Connecting;
+
+        /// keep track of an open connection, returning an id to forget it by
+        fn register_connection(
+            addr: SocketAddr,
+            con: ghost_actor::GhostSender<TransportConnection>,
+        ) -> u64;
+
+        /// forget a connection which has closed
+        fn unregister_connection(addr: SocketAddr, id: u64) -> ();
+
+        /// ask the connected peer at `target` to start connecting to `from`
+        fn relay_punch(from: Url2, target: Url2) -> ();
+
+        /// note that we've asked the peer at `rendezvous` to coordinate
+        /// hole punching for us, until the request is done
+        fn begin_punch_request(rendezvous: SocketAddr) -> ();
+
+        /// note that a punch request to the peer at `rendezvous` is done
+        fn end_punch_request(rendezvous: SocketAddr) -> ();
+
+        /// whether we're waiting on a punch request to the peer at `rendezvous`
+        fn punch_requested(rendezvous: SocketAddr) -> bool;
+
+        /// start connecting to `peer`, opening our NAT to packets from it
+        fn punch_toward(peer: Url2) -> ();
     }
 }
 
 /// QUIC implementation of kitsune TransportListener actor.
 struct TransportListenerQuic {
     internal_sender: ghost_actor::GhostSender<ListenerInner>,
-    quinn_endpoint: quinn::Endpoint,
+    /// One endpoint for each address bound, in the order they were bound
+    quinn_endpoints: Vec<quinn::Endpoint>,
+    /// The open connections, incoming and outgoing, by remote address
+    connections: HashMap<SocketAddr, (u64, ghost_actor::GhostSender<TransportConnection>)>,
+    next_connection_id: u64,
+    /// How many punch requests we're waiting on, by rendezvous address
+    punch_requests: HashMap<SocketAddr, usize>,
+}
+
+impl TransportListenerQuic {
+    fn bound_urls(&self) -> TransportResult<Vec<Url2>> {
+        self.quinn_endpoints
+            .iter()
+            .map(|endpoint| {
+                Ok(url2!(
+                    "{}://{}",
+                    crate::SCHEME,
+                    endpoint.local_addr().map_err(TransportError::other)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// The endpoint to connect to an address from: the first bound
+    /// to the same IP version, or failing that the first of all
+    fn endpoint_for(&self, addr: &SocketAddr) -> &quinn::Endpoint {
+        self.quinn_endpoints
+            .iter()
+            .find(|endpoint| {
+                endpoint
+                    .local_addr()
+                    .map(|local| local.is_ipv6() == addr.is_ipv6())
+                    .unwrap_or(false)
+            })
+            .unwrap_or(&self.quinn_endpoints[0])
+    }
 }
 
 impl ghost_actor::GhostControlHandler for TransportListenerQuic {}
@@ -30,27 +96,130 @@ impl ListenerInnerHandler for TransportListenerQuic {
         addr: SocketAddr,
     ) -> ListenerInnerHandlerResult<quinn::Connecting> {
         let out = self
-            .quinn_endpoint
+            .endpoint_for(&addr)
             .connect(&addr, "stub.stub")
             .map_err(TransportError::other)?;
         Ok(async move { Ok(out) }.boxed().into())
     }
+
+    fn handle_register_connection(
+        &mut self,
+        addr: SocketAddr,
+        con: ghost_actor::GhostSender<TransportConnection>,
+    ) -> ListenerInnerHandlerResult<u64> {
+        let id = self.next_connection_id;
+        self.next_connection_id += 1;
+        self.connections.insert(addr, (id, con));
+        Ok(async move { Ok(id) }.boxed().into())
+    }
+
+    fn handle_unregister_connection(
+        &mut self,
+        addr: SocketAddr,
+        id: u64,
+    ) -> ListenerInnerHandlerResult<()> {
+        // a newer connection from the same address may have replaced it
+        if self.connections.get(&addr).map(|(i, _)| *i) == Some(id) {
+            self.connections.remove(&addr);
+        }
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_relay_punch(&mut self, from: Url2, target: Url2) -> ListenerInnerHandlerResult<()> {
+        let con = self
+            .connections
+            .get(&observed_addr(&target)?)
+            .map(|(_, con)| con.clone())
+            .ok_or_else(|| TransportError::from(format!("not connected to {}", target)))?;
+        Ok(async move {
+            let res = tokio::time::timeout(
+                PUNCH_REQUEST_TIMEOUT,
+                con.request(PunchMsg::Notify { peer: from }.encode()),
+            )
+            .await
+            .map_err(TransportError::other)??;
+            decode_response(&res)
+        }
+        .boxed()
+        .into())
+    }
+
+    fn handle_begin_punch_request(
+        &mut self,
+        rendezvous: SocketAddr,
+    ) -> ListenerInnerHandlerResult<()> {
+        *self.punch_requests.entry(rendezvous).or_insert(0) += 1;
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_end_punch_request(
+        &mut self,
+        rendezvous: SocketAddr,
+    ) -> ListenerInnerHandlerResult<()> {
+        if let Some(count) = self.punch_requests.get_mut(&rendezvous) {
+            *count -= 1;
+            if *count == 0 {
+                self.punch_requests.remove(&rendezvous);
+            }
+        }
+        Ok(async move { Ok(()) }.boxed().into())
+    }
+
+    fn handle_punch_requested(
+        &mut self,
+        rendezvous: SocketAddr,
+    ) -> ListenerInnerHandlerResult<bool> {
+        let out = self.punch_requests.contains_key(&rendezvous);
+        Ok(async move { Ok(out) }.boxed().into())
+    }
+
+    fn handle_punch_toward(&mut self, peer: Url2) -> ListenerInnerHandlerResult<()> {
+        let addr = observed_addr(&peer)?;
+        let endpoint = self.endpoint_for(&addr).clone();
+        // The peer is connecting to us at the same time. Whichever attempt
+        // gets through, the holes are open, so ours is closed straight away.
+        tokio::task::spawn(async move {
+            for _ in 0..PUNCH_ATTEMPTS {
+                if let Ok(connecting) = endpoint.connect(&addr, "stub.stub") {
+                    if let Ok(Ok(_)) = tokio::time::timeout(PUNCH_ATTEMPT_TIMEOUT, connecting).await
+                    {
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(async move { Ok(()) }.boxed().into())
+    }
 }
 
 impl ghost_actor::GhostHandler<TransportListener> for TransportListenerQuic {}
 
 impl TransportListenerHandler for TransportListenerQuic {
     fn handle_bound_url(&mut self) -> TransportListenerHandlerResult<Url2> {
-        let out = url2!(
-            "{}://{}",
-            crate::SCHEME,
-            self.quinn_endpoint
-                .local_addr()
-                .map_err(TransportError::other)?,
-        );
+        let out = self.bound_urls()?.remove(0);
+        Ok(async move { Ok(out) }.boxed().into())
+    }
+
+    fn handle_bound_urls(&mut self) -> TransportListenerHandlerResult<Vec<Url2>> {
+        let out = self.bound_urls()?;
         Ok(async move { Ok(out) }.boxed().into())
     }
 
+    fn handle_advertised_urls(&mut self) -> TransportListenerHandlerResult<Vec<Url2>> {
+        let bound = self.bound_urls()?;
+        let i_s = self.internal_sender.clone();
+        Ok(async move {
+            let mut probed = Vec::with_capacity(bound.len());
+            for url in bound {
+                let reachable = probe(&i_s, &url).await;
+                probed.push((url, reachable));
+            }
+            Ok(kitsune_p2p_types::address::prioritize_urls(probed))
+        }
+        .boxed()
+        .into())
+    }
+
     fn handle_connect(
         &mut self,
         input: Url2,
@@ -59,16 +228,117 @@ impl TransportListenerHandler for TransportListenerQuic {
         TransportConnectionEventReceiver,
     )> {
         let i_s = self.internal_sender.clone();
+        let connected: Vec<_> = self
+            .connections
+            .iter()
+            .map(|(addr, (_, con))| (*addr, con.clone()))
+            .collect();
         Ok(async move {
             let addr = crate::url_to_addr(&input, crate::SCHEME).await?;
-            let maybe_con = i_s.raw_connect(addr).await?;
-            crate::connection::spawn_transport_connection_quic(maybe_con).await
+            // anyone we're connected to, other than the peer itself,
+            // may be able to coordinate hole punching
+            let rendezvous = connected
+                .into_iter()
+                .filter(|(a, _)| *a != addr)
+                .map(|(_, con)| con)
+                .collect();
+            let con = connect_or_punch(&i_s, addr, rendezvous).await?;
+            crate::connection::spawn_transport_connection_quic(con, i_s).await
         }
         .boxed()
         .into())
     }
 }
 
+/// Connect to an address directly, or if that times out, ask each of
+/// `rendezvous` in turn to coordinate hole punching with it
+async fn connect_or_punch(
+    i_s: &ghost_actor::GhostSender<ListenerInner>,
+    addr: SocketAddr,
+    rendezvous: Vec<ghost_actor::GhostSender<TransportConnection>>,
+) -> TransportResult<quinn::NewConnection> {
+    if rendezvous.is_empty() {
+        return i_s
+            .raw_connect(addr)
+            .await?
+            .await
+            .map_err(TransportError::other);
+    }
+
+    let err = match connect_within(i_s, addr, DIRECT_CONNECT_TIMEOUT).await {
+        Ok(con) => return Ok(con),
+        Err(err) => err,
+    };
+    tracing::debug!(%addr, ?err, "direct connect failed, punching through");
+
+    let target = url2!("{}://{}", crate::SCHEME, addr);
+    for con in rendezvous {
+        if let Err(err) = request_punch(i_s, &con, target.clone()).await {
+            tracing::debug!(%addr, ?err, "peer can't coordinate punching");
+            continue;
+        }
+        for _ in 0..PUNCH_ATTEMPTS {
+            if let Ok(con) = connect_within(i_s, addr, PUNCH_ATTEMPT_TIMEOUT).await {
+                return Ok(con);
+            }
+        }
+    }
+
+    Err(format!(
+        "could not connect to {}, directly or by hole punching",
+        addr
+    )
+    .into())
+}
+
+/// Ask the peer on `con` to coordinate hole punching with `target`.
+/// While the request is out, notifications through the same peer to start
+/// connecting are taken to be our own request's doing.
+pub(crate) async fn request_punch(
+    i_s: &ghost_actor::GhostSender<ListenerInner>,
+    con: &ghost_actor::GhostSender<TransportConnection>,
+    target: Url2,
+) -> TransportResult<()> {
+    let rendezvous = observed_addr(&con.remote_url().await?)?;
+    i_s.begin_punch_request(rendezvous).await?;
+    let res = tokio::time::timeout(
+        PUNCH_REQUEST_TIMEOUT,
+        con.request(PunchMsg::Request { target }.encode()),
+    )
+    .await;
+    i_s.end_punch_request(rendezvous).await?;
+    decode_response(&res.map_err(TransportError::other)??)
+}
+
+/// Connect to an address, giving up after `timeout`
+async fn connect_within(
+    i_s: &ghost_actor::GhostSender<ListenerInner>,
+    addr: SocketAddr,
+    timeout: Duration,
+) -> TransportResult<quinn::NewConnection> {
+    let connecting = i_s.raw_connect(addr).await?;
+    tokio::time::timeout(timeout, connecting)
+        .await
+        .map_err(TransportError::other)?
+        .map_err(TransportError::other)
+}
+
+/// Connect to one of our own urls, to see whether it can be reached.
+/// The probe connection shows up as an incoming connection, and is
+/// closed again straight away.
+async fn probe(i_s: &ghost_actor::GhostSender<ListenerInner>, url: &Url2) -> bool {
+    let res: TransportResult<()> = async {
+        let addr = crate::url_to_addr(url, crate::SCHEME).await?;
+        connect_within(i_s, addr, PROBE_TIMEOUT).await?;
+        Ok(())
+    }
+    .await;
+    if let Err(err) = &res {
+        tracing::debug!(%url, ?err, "address is unreachable");
+    }
+    res.is_ok()
+}
+
 /// Spawn a new QUIC TransportListenerSender.
 pub async fn spawn_transport_listener_quic(
     bind_to: Url2,
@@ -80,47 +350,86 @@ pub async fn spawn_transport_listener_quic(
     ghost_actor::GhostSender<TransportListener>,
     TransportListenerEventReceiver,
 )> {
+    spawn_transport_listener_quic_multi(vec![bind_to], cert).await
+}
+
+/// Spawn a new QUIC TransportListenerSender bound to every one of
+/// `bind_to`, e.g. an IPv4 and an IPv6 address. Incoming connections on
+/// any of them arrive on the same event receiver, and outgoing connections
+/// are made from the first address of the same IP version as the remote.
+///
+/// If a direct connection times out, and the listener has connections to
+/// other peers, it asks them to coordinate hole punching with the remote.
+pub async fn spawn_transport_listener_quic_multi(
+    bind_to: Vec<Url2>,
+    cert: Option<(
+        lair_keystore_api::actor::Cert,
+        lair_keystore_api::actor::CertPrivKey,
+    )>,
+) -> TransportListenerResult<(
+    ghost_actor::GhostSender<TransportListener>,
+    TransportListenerEventReceiver,
+)> {
+    if bind_to.is_empty() {
+        return Err("a listener must be bound to at least one url".into());
+    }
     let server_config = danger::configure_server(cert)
         .await
         .map_err(|e| TransportError::from(format!("cert error: {:?}", e)))?;
-    let mut builder = quinn::Endpoint::builder();
-    builder.listen(server_config);
-    builder.default_client_config(danger::configure_client());
-    let (quinn_endpoint, incoming) = builder
-        .bind(&crate::url_to_addr(&bind_to, crate::SCHEME).await?)
-        .map_err(TransportError::other)?;
 
     let (incoming_sender, receiver) = futures::channel::mpsc::channel(10);
 
     let builder = ghost_actor::actor_builder::GhostActorBuilder::new();
 
-    let internal_sender = builder.channel_factory().create_channel().await?;
+    let internal_sender: ghost_actor::GhostSender<ListenerInner> =
+        builder.channel_factory().create_channel().await?;
 
-    let sender = builder.channel_factory().create_channel().await?;
+    let mut quinn_endpoints = Vec::with_capacity(bind_to.len());
+    for url in bind_to {
+        let mut endpoint_builder = quinn::Endpoint::builder();
+        endpoint_builder.listen(server_config.clone());
+        endpoint_builder.default_client_config(danger::configure_client());
+        let (quinn_endpoint, incoming) = endpoint_builder
+            .bind(&crate::url_to_addr(&url, crate::SCHEME).await?)
+            .map_err(TransportError::other)?;
+        quinn_endpoints.push(quinn_endpoint);
 
-    tokio::task::spawn(async move {
-        incoming
-            .for_each_concurrent(10, |maybe_con| async {
-                let res: TransportResult<()> = async {
-                    let (con_send, con_recv) =
-                        crate::connection::spawn_transport_connection_quic(maybe_con).await?;
-                    incoming_sender
-                        .incoming_connection(con_send, con_recv)
-                        .await?;
-
-                    Ok(())
-                }
+        let incoming_sender = incoming_sender.clone();
+        let internal_sender = internal_sender.clone();
+        tokio::task::spawn(async move {
+            incoming
+                .for_each_concurrent(10, |maybe_con| async {
+                    let res: TransportResult<()> = async {
+                        let con = maybe_con.await.map_err(TransportError::other)?;
+                        let (con_send, con_recv) =
+                            crate::connection::spawn_transport_connection_quic(
+                                con,
+                                internal_sender.clone(),
+                            )
+                            .await?;
+                        incoming_sender
+                            .incoming_connection(con_send, con_recv)
+                            .await?;
+
+                        Ok(())
+                    }
+                    .await;
+                    if let Err(err) = res {
+                        tracing::error!(?err);
+                    }
+                })
                 .await;
-                if let Err(err) = res {
-                    ghost_actor::dependencies::tracing::error!(?err);
-                }
-            })
-            .await;
-    });
+        });
+    }
+
+    let sender = builder.channel_factory().create_channel().await?;
 
     let actor = TransportListenerQuic {
         internal_sender,
-        quinn_endpoint,
+        quinn_endpoints,
+        connections: HashMap::new(),
+        next_connection_id: 0,
+        punch_requests: HashMap::new(),
     };
 
     tokio::task::spawn(builder.spawn(actor));
@@ -0,0 +1,159 @@
+//! NAT hole punching, coordinated through a node both peers are connected to.
+//!
+//! When A can't connect to B directly, because both are behind NATs which
+//! drop unsolicited packets, A asks a node R which it's connected to for
+//! help. If R is connected to B too, it tells B the address it sees A
+//! connecting from, and B starts connecting to that address, which opens
+//! B's NAT to packets from A. Meanwhile A connects to B again, which opens
+//! A's NAT to packets from B, so the attempts meet in the middle. This only
+//! works if each NAT maps a socket to the same public address whatever the
+//! destination, which most home routers do.
+//!
+//! B only starts connecting to A if it asked R to punch through to A, as
+//! A did to B. Otherwise anyone could have B connect to any address over
+//! and over. So if B hasn't asked, it asks R to punch through to A in
+//! turn, and only connects once A's answer comes back that it did ask.
+//!
+//! The messages are requests on the QUIC connections, marked with a prefix
+//! so that they're handled by the transport rather than passed up as
+//! incoming requests. If punching fails too, connecting returns an error,
+//! and the caller can fall back to relaying through another node.
+
+use kitsune_p2p_types::{dependencies::url2::*, transport::*};
+use std::{net::SocketAddr, time::Duration};
+
+/// Marks a request as a hole punching message
+const PUNCH_PREFIX: &[u8] = b"\0kitsune-quic-punch\0";
+
+/// How long a direct connect may take before trying to punch through,
+/// if there's anyone to coordinate with
+pub(crate) const DIRECT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many times each side tries to connect while punching
+pub(crate) const PUNCH_ATTEMPTS: usize = 5;
+
+/// How long each connect while punching may take
+pub(crate) const PUNCH_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long to wait on a punch request or notification to be answered.
+/// Answering one can wait on another through the same peer, so this is
+/// generous.
+pub(crate) const PUNCH_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A hole punching message
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PunchMsg {
+    /// From A to R: tell this peer to start connecting to me
+    Request {
+        /// The peer A wants to connect to
+        target: Url2,
+    },
+    /// From R to B: start connecting to this peer
+    Notify {
+        /// The address R sees A connecting from
+        peer: Url2,
+    },
+}
+
+impl PunchMsg {
+    /// Encode as a request
+    pub fn encode(&self) -> Vec<u8> {
+        let (kind, url) = match self {
+            PunchMsg::Request { target } => (1, target),
+            PunchMsg::Notify { peer } => (2, peer),
+        };
+        let mut out = PUNCH_PREFIX.to_vec();
+        out.push(kind);
+        out.extend_from_slice(url.as_str().as_bytes());
+        out
+    }
+
+    /// Decode a request, or None if it isn't a hole punching message
+    pub fn decode(data: &[u8]) -> Option<TransportResult<Self>> {
+        if !data.starts_with(PUNCH_PREFIX) {
+            return None;
+        }
+        let data = &data[PUNCH_PREFIX.len()..];
+        Some((|| {
+            let (kind, url) = data.split_first().ok_or("empty punch message")?;
+            let url = std::str::from_utf8(url).map_err(TransportError::other)?;
+            let url = Url2::try_parse(url).map_err(TransportError::other)?;
+            match kind {
+                1 => Ok(PunchMsg::Request { target: url }),
+                2 => Ok(PunchMsg::Notify { peer: url }),
+                _ => Err(format!("unknown punch message kind {}", kind).into()),
+            }
+        })())
+    }
+}
+
+/// Encode the response to a hole punching message
+pub(crate) fn encode_response(res: TransportResult<()>) -> Vec<u8> {
+    match res {
+        Ok(()) => vec![0],
+        Err(e) => {
+            let mut out = vec![1];
+            out.extend_from_slice(e.to_string().as_bytes());
+            out
+        }
+    }
+}
+
+/// Decode the response to a hole punching message
+pub(crate) fn decode_response(data: &[u8]) -> TransportResult<()> {
+    match data.split_first() {
+        Some((0, _)) => Ok(()),
+        Some((_, msg)) => Err(String::from_utf8_lossy(msg).into_owned().into()),
+        None => Err("empty punch response".into()),
+    }
+}
+
+/// The socket address of a url with an IP address for its host,
+/// as the urls of observed addresses always have
+pub(crate) fn observed_addr(url: &Url2) -> TransportResult<SocketAddr> {
+    let host = url
+        .host_str()
+        .ok_or("url has no host")?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let ip = host.parse().map_err(TransportError::other)?;
+    let port = url.port().ok_or("url has no port")?;
+    Ok(SocketAddr::new(ip, port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        for msg in vec![
+            PunchMsg::Request {
+                target: url2!("kitsune-quic://1.2.3.4:5678"),
+            },
+            PunchMsg::Notify {
+                peer: url2!("kitsune-quic://[2001:db8::1]:5678"),
+            },
+        ] {
+            assert_eq!(PunchMsg::decode(&msg.encode()).unwrap().unwrap(), msg);
+        }
+        assert!(PunchMsg::decode(b"hello").is_none());
+        assert!(PunchMsg::decode(PUNCH_PREFIX).unwrap().is_err());
+
+        assert!(decode_response(&encode_response(Ok(()))).is_ok());
+        assert!(decode_response(&encode_response(Err("nope".into()))).is_err());
+    }
+
+    #[test]
+    fn observed_addrs() {
+        assert_eq!(
+            observed_addr(&url2!("kitsune-quic://[::1]:1234")).unwrap(),
+            "[::1]:1234".parse().unwrap()
+        );
+        assert_eq!(
+            observed_addr(&url2!("kitsune-quic://1.2.3.4:1234")).unwrap(),
+            "1.2.3.4:1234".parse().unwrap()
+        );
+        assert!(observed_addr(&url2!("kitsune-quic://example.com:1234")).is_err());
+    }
+}
@@ -73,4 +73,122 @@ mod tests {
 
         assert_eq!("echo: hello", &String::from_utf8_lossy(&resp));
     }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_multi_address() {
+        let (listener, _events) = spawn_transport_listener_quic_multi(
+            vec![
+                url2!("kitsune-quic://127.0.0.1:0"),
+                url2!("kitsune-quic://0.0.0.0:0"),
+                url2!("kitsune-quic://127.0.0.1:0"),
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+
+        let bound = listener.bound_urls().await.unwrap();
+        assert_eq!(bound.len(), 3);
+        assert_eq!(listener.bound_url().await.unwrap(), bound[0]);
+
+        // the wildcard address can't be advertised,
+        // and both loopback addresses answer the probe
+        let advertised = listener.advertised_urls().await.unwrap();
+        assert_eq!(advertised, vec![bound[0].clone(), bound[2].clone()]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_punch_coordination() {
+        use crate::punch::*;
+
+        let (listener_a, mut events_a) =
+            spawn_transport_listener_quic(url2!("kitsune-quic://127.0.0.1:0"), None)
+                .await
+                .unwrap();
+        let (listener_b, _events_b) =
+            spawn_transport_listener_quic(url2!("kitsune-quic://127.0.0.1:0"), None)
+                .await
+                .unwrap();
+        let (listener_r, _events_r) =
+            spawn_transport_listener_quic(url2!("kitsune-quic://127.0.0.1:0"), None)
+                .await
+                .unwrap();
+        let url_b = listener_b.bound_url().await.unwrap();
+        let url_r = listener_r.bound_url().await.unwrap();
+
+        // both A and B are connected to R
+        let (a_to_r, _) = listener_a.connect(url_r.clone()).await.unwrap();
+        let (_b_to_r, _) = listener_b.connect(url_r).await.unwrap();
+
+        // R can't help with peers it isn't connected to
+        let res = a_to_r
+            .request(
+                PunchMsg::Request {
+                    target: url2!("kitsune-quic://127.0.0.1:1"),
+                }
+                .encode(),
+            )
+            .await
+            .unwrap();
+        assert!(decode_response(&res).is_err());
+
+        // when A asks R to coordinate with B, B starts connecting to A
+        let res = a_to_r
+            .request(
+                PunchMsg::Request {
+                    target: url_b.clone(),
+                }
+                .encode(),
+            )
+            .await
+            .unwrap();
+        decode_response(&res).unwrap();
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while let Some(evt) = events_a.next().await {
+                let TransportListenerEvent::IncomingConnection {
+                    respond, sender, ..
+                } = evt;
+                respond.respond(Ok(async move { Ok(()) }.boxed().into()));
+                if sender.remote_url().await.unwrap() == url_b {
+                    return;
+                }
+            }
+            panic!("listener A closed");
+        })
+        .await
+        .expect("B should connect to A");
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn test_unrequested_punch_is_refused() {
+        use crate::punch::*;
+
+        let (listener_a, _events_a) =
+            spawn_transport_listener_quic(url2!("kitsune-quic://127.0.0.1:0"), None)
+                .await
+                .unwrap();
+        let (listener_b, _events_b) =
+            spawn_transport_listener_quic(url2!("kitsune-quic://127.0.0.1:0"), None)
+                .await
+                .unwrap();
+        let (listener_c, mut events_c) =
+            spawn_transport_listener_quic(url2!("kitsune-quic://127.0.0.1:0"), None)
+                .await
+                .unwrap();
+        let url_b = listener_b.bound_url().await.unwrap();
+        let url_c = listener_c.bound_url().await.unwrap();
+
+        // A tells B to connect to C, which never asked to punch through
+        // to B, and which A isn't connected to, so can't confirm it
+        let (a_to_b, _) = listener_a.connect(url_b).await.unwrap();
+        let res = a_to_b
+            .request(PunchMsg::Notify { peer: url_c }.encode())
+            .await
+            .unwrap();
+        assert!(decode_response(&res).is_err());
+
+        let res = tokio::time::timeout(std::time::Duration::from_secs(2), events_c.next()).await;
+        assert!(res.is_err(), "B shouldn't connect to C");
+    }
 }
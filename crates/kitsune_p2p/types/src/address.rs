@@ -0,0 +1,130 @@
+//! Choosing the order in which to advertise a node's addresses.
+//!
+//! A node may be bound to several addresses, e.g. an IPv4 and an IPv6
+//! address, or a LAN and a public interface. Peers try them in the order
+//! they're advertised, so addresses which answered a reachability probe go
+//! first, then addresses which are usable from further away: public before
+//! private, private before link-local, link-local before loopback. Ties keep
+//! the order the addresses were bound in.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use url2::Url2;
+
+/// How widely an address can be used, from anywhere to nowhere
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AddressScope {
+    /// A public address, or a hostname
+    Global,
+    /// A private network or IPv6 unique local address
+    Private,
+    /// Only reachable on the local link
+    LinkLocal,
+    /// Only reachable from this machine
+    Loopback,
+    /// A wildcard address, which can't be connected to
+    Unspecified,
+}
+
+impl AddressScope {
+    /// The scope of the host in a url
+    pub fn of(url: &Url2) -> Self {
+        let host = match url.host_str() {
+            Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+            None => return AddressScope::Unspecified,
+        };
+        match host.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => Self::of_v4(ip),
+            Ok(IpAddr::V6(ip)) => Self::of_v6(ip),
+            Err(_) => AddressScope::Global,
+        }
+    }
+
+    fn of_v4(ip: Ipv4Addr) -> Self {
+        if ip.is_unspecified() {
+            AddressScope::Unspecified
+        } else if ip.is_loopback() {
+            AddressScope::Loopback
+        } else if ip.is_link_local() {
+            AddressScope::LinkLocal
+        } else if ip.is_private() {
+            AddressScope::Private
+        } else {
+            AddressScope::Global
+        }
+    }
+
+    fn of_v6(ip: Ipv6Addr) -> Self {
+        // IPv4 addresses mapped into IPv6 are scoped as the IPv4 address
+        if let [0, 0, 0, 0, 0, 0xffff, ..] = ip.segments() {
+            if let Some(ip) = ip.to_ipv4() {
+                return Self::of_v4(ip);
+            }
+        }
+        let first = ip.segments()[0];
+        if ip.is_unspecified() {
+            AddressScope::Unspecified
+        } else if ip.is_loopback() {
+            AddressScope::Loopback
+        } else if first & 0xffc0 == 0xfe80 {
+            AddressScope::LinkLocal
+        } else if first & 0xfe00 == 0xfc00 {
+            AddressScope::Private
+        } else {
+            AddressScope::Global
+        }
+    }
+}
+
+/// Order urls for advertising, given whether each answered a reachability
+/// probe. Unspecified addresses are dropped, as peers can't connect to them.
+pub fn prioritize_urls(urls: Vec<(Url2, bool)>) -> Vec<Url2> {
+    let mut ranked: Vec<_> = urls
+        .into_iter()
+        .map(|(url, reachable)| (!reachable, AddressScope::of(&url), url))
+        .filter(|(_, scope, _)| *scope != AddressScope::Unspecified)
+        .collect();
+    // sort is stable, so equally good addresses keep their bind order
+    ranked.sort_by_key(|(unreachable, scope, _)| (*unreachable, *scope));
+    ranked.into_iter().map(|(_, _, url)| url).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url2::url2;
+
+    #[test]
+    fn scopes() {
+        let scope = |s: &str| AddressScope::of(&url2!("kitsune-quic://{}:1234", s));
+        assert_eq!(scope("8.8.8.8"), AddressScope::Global);
+        assert_eq!(scope("example.com"), AddressScope::Global);
+        assert_eq!(scope("[2001:db8::1]"), AddressScope::Global);
+        assert_eq!(scope("192.168.1.2"), AddressScope::Private);
+        assert_eq!(scope("[fd00::1]"), AddressScope::Private);
+        assert_eq!(scope("169.254.0.1"), AddressScope::LinkLocal);
+        assert_eq!(scope("[fe80::1]"), AddressScope::LinkLocal);
+        assert_eq!(scope("127.0.0.1"), AddressScope::Loopback);
+        assert_eq!(scope("[::1]"), AddressScope::Loopback);
+        assert_eq!(scope("[::ffff:127.0.0.1]"), AddressScope::Loopback);
+        assert_eq!(scope("0.0.0.0"), AddressScope::Unspecified);
+        assert_eq!(scope("[::]"), AddressScope::Unspecified);
+    }
+
+    #[test]
+    fn reachable_and_wider_addresses_first() {
+        let loopback = url2!("kitsune-quic://127.0.0.1:1");
+        let lan = url2!("kitsune-quic://192.168.1.2:1");
+        let public_v6 = url2!("kitsune-quic://[2001:db8::1]:1");
+        let public_v4 = url2!("kitsune-quic://8.8.8.8:1");
+        let wildcard = url2!("kitsune-quic://0.0.0.0:1");
+
+        let urls = prioritize_urls(vec![
+            (loopback.clone(), true),
+            (wildcard, true),
+            (public_v6.clone(), false),
+            (lan.clone(), true),
+            (public_v4.clone(), false),
+        ]);
+        assert_eq!(urls, vec![lan, loopback, public_v6, public_v4]);
+    }
+}
@@ -1,5 +1,8 @@
 //! utility for lazy init-ing things
 
+use holochain_error_kind::{ErrorKind, HasErrorKind};
+use std::time::Duration;
+
 /// utility for lazy init-ing things
 /// note how new is not async so we can do it in an actor handler
 pub struct AsyncLazy<O: 'static + Clone + Send + Sync>(tokio::sync::watch::Receiver<Option<O>>);
@@ -36,10 +39,136 @@ impl<O: 'static + Clone + Send + Sync> AsyncLazy<O> {
     }
 }
 
+/// How an [AsyncTryLazy] retries a failed initialization
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The most attempts made before giving up
+    pub max_attempts: u32,
+    /// How long each attempt may take before it's abandoned
+    pub attempt_timeout: Duration,
+    /// The wait before the first retry, doubled for each retry after
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            attempt_timeout: Duration::from_secs(30),
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Why an [AsyncTryLazy] has no value.
+/// Every getter sees the same error, so the cause is kept as a string.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum AsyncLazyError {
+    /// The last attempt took longer than the policy allows
+    #[error("initialization timed out after {0} attempts")]
+    TimedOut(u32),
+
+    /// The last attempt returned an error
+    #[error("initialization failed after {attempts} attempts: {reason}")]
+    Failed {
+        /// How many attempts were made
+        attempts: u32,
+        /// The error from the last attempt
+        reason: String,
+    },
+
+    /// Initialization was cancelled, or its task stopped, before it finished
+    #[error("initialization was cancelled")]
+    Cancelled,
+}
+
+impl HasErrorKind for AsyncLazyError {
+    fn error_kind(&self) -> ErrorKind {
+        match self {
+            AsyncLazyError::TimedOut(_) => ErrorKind::Timeout,
+            AsyncLazyError::Failed { .. } => ErrorKind::Unavailable,
+            AsyncLazyError::Cancelled => ErrorKind::Cancelled,
+        }
+    }
+}
+
+/// utility for lazy init-ing things which can fail
+/// like [AsyncLazy], new is not async, but initialization is retried by
+/// a [RetryPolicy] and can be cancelled, so getters never wait forever
+pub struct AsyncTryLazy<O: 'static + Clone + Send + Sync> {
+    recv: tokio::sync::watch::Receiver<Option<Result<O, AsyncLazyError>>>,
+    abort: futures::future::AbortHandle,
+}
+
+impl<O: 'static + Clone + Send + Sync> AsyncTryLazy<O> {
+    /// sync create a new lazy-init value
+    /// `f` is called for each attempt, so it must be able to start over
+    pub fn new<F, Fut, E>(policy: RetryPolicy, mut f: F) -> Self
+    where
+        F: 'static + FnMut() -> Fut + Send,
+        Fut: std::future::Future<Output = Result<O, E>> + Send,
+        E: std::fmt::Display,
+    {
+        let (s, r) = tokio::sync::watch::channel(None);
+        let (init, abort) = futures::future::abortable(async move {
+            let mut backoff = policy.backoff;
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                let err = match tokio::time::timeout(policy.attempt_timeout, f()).await {
+                    Ok(Ok(val)) => return Ok(val),
+                    Ok(Err(e)) => AsyncLazyError::Failed {
+                        attempts,
+                        reason: e.to_string(),
+                    },
+                    Err(_) => AsyncLazyError::TimedOut(attempts),
+                };
+                if attempts >= policy.max_attempts {
+                    return Err(err);
+                }
+                tokio::time::delay_for(backoff).await;
+                backoff *= 2;
+            }
+        });
+        tokio::task::spawn(async move {
+            let result = init
+                .await
+                .unwrap_or_else(|_| Err(AsyncLazyError::Cancelled));
+            let _ = s.broadcast(Some(result));
+        });
+        Self { recv: r, abort }
+    }
+
+    /// async get the value of this lazy type
+    /// will return once initialization succeeds, gives up or is cancelled.
+    /// Dropping the returned future doesn't affect initialization.
+    pub fn get(&self) -> impl std::future::Future<Output = Result<O, AsyncLazyError>> + 'static {
+        let mut r = self.recv.clone();
+        async move {
+            loop {
+                match r.recv().await {
+                    Some(Some(result)) => return result,
+                    None => return Err(AsyncLazyError::Cancelled),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /// stop initializing, if it hasn't finished yet.
+    /// Getters waiting and any after get [AsyncLazyError::Cancelled].
+    pub fn cancel(&self) {
+        self.abort.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
 
     #[tokio::test(threaded_scheduler)]
     async fn async_lazy() {
@@ -54,4 +183,62 @@ mod tests {
         assert_eq!(42, *s.get().await);
         assert_eq!(42, *s.get().await);
     }
+
+    fn quick_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            attempt_timeout: Duration::from_millis(50),
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn async_try_lazy_retries() {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let counter = attempts.clone();
+        let s = AsyncTryLazy::new(quick_policy(), move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if attempt < 3 {
+                    Err(format!("bind failed {}", attempt))
+                } else {
+                    Ok(Arc::new(42))
+                }
+            }
+        });
+        assert_eq!(Ok(Arc::new(42)), s.get().await);
+        assert_eq!(Ok(Arc::new(42)), s.get().await);
+        assert_eq!(3, attempts.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn async_try_lazy_gives_up() {
+        let s: AsyncTryLazy<Arc<u32>> =
+            AsyncTryLazy::new(quick_policy(), || async { Err("bind failed") });
+        assert_eq!(
+            Err(AsyncLazyError::Failed {
+                attempts: 3,
+                reason: "bind failed".to_string()
+            }),
+            s.get().await
+        );
+
+        let s: AsyncTryLazy<Arc<u32>> = AsyncTryLazy::new(quick_policy(), || async {
+            tokio::time::delay_for(Duration::from_secs(10)).await;
+            Result::<_, String>::Ok(Arc::new(42))
+        });
+        assert_eq!(Err(AsyncLazyError::TimedOut(3)), s.get().await);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn async_try_lazy_cancel() {
+        let s: AsyncTryLazy<Arc<u32>> = AsyncTryLazy::new(RetryPolicy::default(), || async {
+            tokio::time::delay_for(Duration::from_secs(10)).await;
+            Result::<_, String>::Ok(Arc::new(42))
+        });
+        let waiting = s.get();
+        s.cancel();
+        assert_eq!(Err(AsyncLazyError::Cancelled), waiting.await);
+        assert_eq!(Err(AsyncLazyError::Cancelled), s.get().await);
+    }
 }
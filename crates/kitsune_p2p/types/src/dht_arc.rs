@@ -20,6 +20,9 @@ pub struct DhtLocation(pub Wrapping<u32>);
 /// 1 more is added to represent the middle point of an odd length array
 pub const MAX_HALF_LENGTH: u32 = (u32::MAX / 2) + 1 + 1;
 
+/// The number of locations on the whole circle
+const FULL_LEN: u64 = u32::MAX as u64 + 1;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Represents how much of a dht arc is held
 /// center_loc is where the hash is.
@@ -101,6 +104,123 @@ impl DhtArc {
             }
         }
     }
+
+    /// The fraction of the whole circle held by this arc,
+    /// from 0.0 for nothing to 1.0 for everything
+    pub fn coverage(&self) -> f64 {
+        self.range().len() as f64 / FULL_LEN as f64
+    }
+
+    /// Check if every location held by another arc is held by this arc
+    pub fn contains_arc(&self, other: &DhtArc) -> bool {
+        let held = self
+            .intersection(other)
+            .iter()
+            .map(ArcRange::len)
+            .sum::<u64>();
+        held == other.range().len()
+    }
+
+    /// Check if this arc and another hold any location in common
+    pub fn overlaps(&self, other: &DhtArc) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// The locations held by either arc.
+    /// Arcs apart from each other give two ranges, one of which may wrap
+    /// past u32::MAX. The ranges are ordered by start and never empty.
+    pub fn union(&self, other: &DhtArc) -> Vec<ArcRange> {
+        let mut both = segments(&self.range());
+        both.extend(segments(&other.range()));
+        to_ranges(both)
+    }
+
+    /// The locations held by both arcs.
+    /// Arcs which between them cover the whole circle can overlap at both
+    /// ends, giving two ranges. The ranges are ordered by start and never empty.
+    pub fn intersection(&self, other: &DhtArc) -> Vec<ArcRange> {
+        let ours = segments(&self.range());
+        let theirs = segments(&other.range());
+        let mut segments = Vec::new();
+        for (a_start, a_end) in &ours {
+            for (b_start, b_end) in &theirs {
+                let start = std::cmp::max(*a_start, *b_start);
+                let end = std::cmp::min(*a_end, *b_end);
+                if start < end {
+                    segments.push((start, end));
+                }
+            }
+        }
+        to_ranges(segments)
+    }
+}
+
+/// The locations which none of the arcs hold.
+/// The ranges are ordered by start and never empty; a gap which wraps
+/// past u32::MAX is one range.
+pub fn gaps(arcs: &[DhtArc]) -> Vec<ArcRange> {
+    let mut held: Vec<(u64, u64)> = arcs.iter().flat_map(|a| segments(&a.range())).collect();
+    held.sort_unstable();
+    let mut gaps = Vec::new();
+    let mut next = 0;
+    for (start, end) in held {
+        if start > next {
+            gaps.push((next, start));
+        }
+        next = std::cmp::max(next, end);
+    }
+    if next < FULL_LEN {
+        gaps.push((next, FULL_LEN));
+    }
+    to_ranges(gaps)
+}
+
+/// Lay a range out on the circle cut open at 0, giving the half open
+/// segments between 0 and [FULL_LEN] it covers.
+/// A range which wraps past u32::MAX gives two segments.
+fn segments(range: &ArcRange) -> Vec<(u64, u64)> {
+    let len = range.len();
+    let start = match range.start {
+        Bound::Included(start) if len > 0 => start as u64,
+        _ => return Vec::new(),
+    };
+    let end = start + len;
+    if end <= FULL_LEN {
+        vec![(start, end)]
+    } else {
+        vec![(start, FULL_LEN), (0, end - FULL_LEN)]
+    }
+}
+
+/// Merge segments into the fewest ranges covering the same locations,
+/// joining the segments either side of 0 into one wrapping range
+fn to_ranges(mut segments: Vec<(u64, u64)>) -> Vec<ArcRange> {
+    segments.sort_unstable();
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(segments.len());
+    for (start, end) in segments {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => {
+                *last_end = std::cmp::max(*last_end, end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    let wraps = merged.len() > 1
+        && merged.first().map(|(start, _)| *start) == Some(0)
+        && merged.last().map(|(_, end)| *end) == Some(FULL_LEN);
+    if wraps {
+        let (_, first_end) = merged.remove(0);
+        if let Some(last) = merged.last_mut() {
+            last.1 = FULL_LEN + first_end;
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(start, end)| ArcRange {
+            start: Bound::Included(start as u32),
+            end: Bound::Included(((end - 1) % FULL_LEN) as u32),
+        })
+        .collect()
 }
 
 impl From<u32> for DhtLocation {
@@ -143,6 +263,16 @@ impl ArcRange {
         }
     }
 
+    /// How many locations this range covers, up to the whole circle
+    pub fn len(&self) -> u64 {
+        match (&self.start, &self.end) {
+            (Bound::Included(start), Bound::Included(end)) => {
+                (Wrapping(*end) - Wrapping(*start)).0 as u64 + 1
+            }
+            _ => 0,
+        }
+    }
+
     #[cfg(test)]
     fn to_inc(self: ArcRange) -> RangeInclusive<usize> {
         match self {
@@ -180,8 +310,8 @@ impl RangeBounds<u32> for ArcRange {
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    // TODO: This is a really good place for prop testing
+    use proptest::prelude::*;
+    use std::ops::Bound::*;
 
     #[test]
     fn test_arc_dist() {
@@ -318,4 +448,196 @@ mod tests {
         );
         check_bounds_full(0, MAX_HALF_LENGTH, half, half - 1);
     }
+
+    #[test]
+    fn test_arc_set_ops_wrap() {
+        let quarter = (u32::MAX as f64 / 4.0).round() as u32;
+        let half = (u32::MAX as f64 / 2.0).round() as u32;
+
+        // Both arcs wrap past u32::MAX
+        let a = DhtArc::new(0, 3);
+        let b = DhtArc::new(u32::MAX, 3);
+        assert_eq!(
+            a.union(&b),
+            vec![ArcRange {
+                start: Included(u32::MAX - 2),
+                end: Included(2)
+            }]
+        );
+        assert_eq!(
+            a.intersection(&b),
+            vec![ArcRange {
+                start: Included(u32::MAX - 1),
+                end: Included(1)
+            }]
+        );
+        assert!(a.overlaps(&b));
+        assert!(!a.contains_arc(&b));
+        assert!(a.contains_arc(&DhtArc::new(u32::MAX, 2)));
+
+        // Arcs apart from each other
+        let a = DhtArc::new(0, 2);
+        let b = DhtArc::new(half, 2);
+        assert!(!a.overlaps(&b));
+        assert!(a.intersection(&b).is_empty());
+        assert_eq!(
+            a.union(&b),
+            vec![
+                ArcRange {
+                    start: Included(half - 1),
+                    end: Included(half + 1)
+                },
+                ArcRange {
+                    start: Included(u32::MAX),
+                    end: Included(1)
+                },
+            ]
+        );
+
+        // Arcs covering more than the circle between them overlap at both ends
+        let a = DhtArc::new(0, quarter + 100);
+        let b = DhtArc::new(half, quarter + 100);
+        assert_eq!(a.intersection(&b).len(), 2);
+        assert_eq!(
+            a.union(&b),
+            vec![ArcRange {
+                start: Included(0),
+                end: Included(u32::MAX)
+            }]
+        );
+
+        // Empty arcs hold nothing but are contained in anything
+        let empty = DhtArc::new(half, 0);
+        assert!(empty.union(&empty).is_empty());
+        assert!(!empty.overlaps(&DhtArc::new(half, MAX_HALF_LENGTH)));
+        assert!(DhtArc::new(0, 1).contains_arc(&empty));
+    }
+
+    #[test]
+    fn test_gaps() {
+        let half = (u32::MAX as f64 / 2.0).round() as u32;
+
+        assert_eq!(
+            gaps(&[]),
+            vec![ArcRange {
+                start: Included(0),
+                end: Included(u32::MAX)
+            }]
+        );
+        assert!(gaps(&[DhtArc::new(half, MAX_HALF_LENGTH)]).is_empty());
+
+        // The gap between two arcs either side of 0 wraps into one range
+        assert_eq!(
+            gaps(&[DhtArc::new(0, 2), DhtArc::new(half, 2)]),
+            vec![
+                ArcRange {
+                    start: Included(2),
+                    end: Included(half - 2)
+                },
+                ArcRange {
+                    start: Included(half + 2),
+                    end: Included(u32::MAX - 1)
+                },
+            ]
+        );
+        assert_eq!(
+            gaps(&[DhtArc::new(half, 2)]),
+            vec![ArcRange {
+                start: Included(half + 2),
+                end: Included(half - 2)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_arc_coverage() {
+        assert_eq!(DhtArc::new(0, 0).coverage(), 0.0);
+        assert_eq!(DhtArc::new(u32::MAX, MAX_HALF_LENGTH).coverage(), 1.0);
+        assert_eq!(DhtArc::new(0, 1).coverage(), 1.0 / FULL_LEN as f64);
+        assert_eq!(
+            DhtArc::new(0, MAX_HALF_LENGTH - 1).coverage(),
+            (FULL_LEN - 1) as f64 / FULL_LEN as f64
+        );
+    }
+
+    fn arc() -> impl Strategy<Value = DhtArc> {
+        let half_length = prop_oneof![
+            Just(0),
+            Just(1),
+            Just(2),
+            Just(MAX_HALF_LENGTH - 1),
+            Just(MAX_HALF_LENGTH),
+            0..=MAX_HALF_LENGTH,
+        ];
+        (any::<u32>(), half_length)
+            .prop_map(|(center, half_length)| DhtArc::new(center, half_length))
+    }
+
+    fn ranges_contain(ranges: &[ArcRange], loc: u32) -> bool {
+        ranges.iter().any(|range| match range.start {
+            Included(start) => ((Wrapping(loc) - Wrapping(start)).0 as u64) < range.len(),
+            _ => false,
+        })
+    }
+
+    /// Locations either side of each end of the arcs, where off by one
+    /// errors would show, plus one anywhere
+    fn edges(a: &DhtArc, b: &DhtArc, anywhere: u32) -> Vec<u32> {
+        let mut locs = vec![anywhere];
+        for range in &[a.range(), b.range()] {
+            for bound in &[range.start, range.end] {
+                if let Included(loc) | Excluded(loc) = *bound {
+                    let loc = Wrapping(loc);
+                    locs.extend(&[(loc - Wrapping(1)).0, loc.0, (loc + Wrapping(1)).0]);
+                }
+            }
+        }
+        locs
+    }
+
+    proptest! {
+        #[test]
+        fn range_len_matches_contains(a in arc(), anywhere in any::<u32>()) {
+            let range = a.range();
+            prop_assert!(range.len() <= FULL_LEN);
+            for loc in edges(&a, &a, anywhere) {
+                prop_assert_eq!(ranges_contain(&[range.clone()], loc), a.contains(loc));
+            }
+        }
+
+        #[test]
+        fn union_holds_either(a in arc(), b in arc(), anywhere in any::<u32>()) {
+            let union = a.union(&b);
+            prop_assert!(union.len() <= 2);
+            prop_assert!(union.iter().all(|range| !range.is_empty()));
+            for loc in edges(&a, &b, anywhere) {
+                prop_assert_eq!(ranges_contain(&union, loc), a.contains(loc) || b.contains(loc));
+            }
+            prop_assert_eq!(union, b.union(&a));
+        }
+
+        #[test]
+        fn intersection_holds_both(a in arc(), b in arc(), anywhere in any::<u32>()) {
+            let intersection = a.intersection(&b);
+            prop_assert!(intersection.len() <= 2);
+            prop_assert!(intersection.iter().all(|range| !range.is_empty()));
+            for loc in edges(&a, &b, anywhere) {
+                prop_assert_eq!(ranges_contain(&intersection, loc), a.contains(loc) && b.contains(loc));
+            }
+            prop_assert_eq!(a.overlaps(&b), !intersection.is_empty());
+            prop_assert_eq!(&intersection, &b.intersection(&a));
+        }
+
+        #[test]
+        fn union_and_intersection_add_up(a in arc(), b in arc()) {
+            let len = |ranges: Vec<ArcRange>| ranges.iter().map(ArcRange::len).sum::<u64>();
+            prop_assert_eq!(
+                len(a.union(&b)) + len(a.intersection(&b)),
+                a.range().len() + b.range().len()
+            );
+            prop_assert!((0.0..=1.0).contains(&a.coverage()));
+            prop_assert_eq!(a.contains_arc(&b), len(a.union(&b)) == a.range().len());
+            prop_assert!(a.contains_arc(&a));
+        }
+    }
 }
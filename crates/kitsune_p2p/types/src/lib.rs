@@ -5,16 +5,21 @@
 pub mod dependencies {
     pub use ::futures;
     pub use ::ghost_actor;
+    pub use ::holochain_error_kind;
     pub use ::thiserror;
     pub use ::tokio;
     pub use ::url2;
 }
 
+pub mod address;
 pub mod async_lazy;
 pub mod dht_arc;
+pub mod restart;
 
 /// A collection of definitions related to remote communication.
 pub mod transport {
+    use holochain_error_kind::{kind_of_boxed, ErrorKind, HasErrorKind};
+
     /// Error related to remote communication.
     #[derive(Debug, thiserror::Error)]
     #[non_exhaustive]
@@ -55,6 +60,16 @@ pub mod transport {
         }
     }
 
+    impl HasErrorKind for TransportError {
+        fn error_kind(&self) -> ErrorKind {
+            match self {
+                // the actor on the other side has gone away
+                TransportError::GhostError(_) => ErrorKind::Unavailable,
+                TransportError::Other(e) => kind_of_boxed(e.as_ref()),
+            }
+        }
+    }
+
     impl From<TransportError> for () {
         fn from(_: TransportError) {}
     }
@@ -111,8 +126,18 @@ pub mod transport {
             /// Represents a socket binding for establishing connections.
             pub chan TransportListener<super::TransportError> {
                 /// Retrieve the current url (address) this listener is bound to.
+                /// If it's bound to more than one, this is the first.
                 fn bound_url() -> url2::Url2;
 
+                /// Retrieve every url (address) this listener is bound to,
+                /// in the order they were bound.
+                fn bound_urls() -> Vec<url2::Url2>;
+
+                /// Probe each bound url for reachability, and list the urls
+                /// peers should use to reach this listener, best first.
+                /// See [crate::address::prioritize_urls].
+                fn advertised_urls() -> Vec<url2::Url2>;
+
                 /// Attempt to establish an outgoing connection to a remote.
                 fn connect(url: url2::Url2) -> (
                     ghost_actor::GhostSender<super::transport_connection::TransportConnection>,
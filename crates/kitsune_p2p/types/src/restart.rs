@@ -0,0 +1,58 @@
+//! Counting the restarts of a supervised actor.
+
+use std::time::{Duration, Instant};
+
+/// Counts how many times an actor has been restarted, so one which keeps
+/// failing can be given up on. Once the actor has run for `stable_after`
+/// without needing a restart the count starts over, so occasional
+/// failures over a long uptime don't add up to giving up.
+#[derive(Debug, Clone)]
+pub struct RestartCounter {
+    stable_after: Duration,
+    count: u32,
+    last_restart: Option<Instant>,
+}
+
+impl RestartCounter {
+    /// A counter for an actor which hasn't been restarted yet
+    pub fn new(stable_after: Duration) -> Self {
+        Self {
+            stable_after,
+            count: 0,
+            last_restart: None,
+        }
+    }
+
+    /// Count a restart at `now`.
+    /// Returns how many restarts there have been since the actor was last
+    /// stable, including this one.
+    pub fn restart(&mut self, now: Instant) -> u32 {
+        let stable = self
+            .last_restart
+            .map(|last| now.saturating_duration_since(last) >= self.stable_after)
+            .unwrap_or(false);
+        if stable {
+            self.count = 0;
+        }
+        self.count += 1;
+        self.last_restart = Some(now);
+        self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_starts_over_once_stable() {
+        let start = Instant::now();
+        let mut counter = RestartCounter::new(Duration::from_secs(60));
+        assert_eq!(1, counter.restart(start));
+        assert_eq!(2, counter.restart(start + Duration::from_secs(10)));
+        // each restart pushes back when the actor counts as stable
+        assert_eq!(3, counter.restart(start + Duration::from_secs(65)));
+        assert_eq!(1, counter.restart(start + Duration::from_secs(130)));
+        assert_eq!(2, counter.restart(start + Duration::from_secs(131)));
+    }
+}
@@ -0,0 +1,250 @@
+//! Writing logs to files which rotate by size and by age.
+//!
+//! The current log is always `<directory>/<file_prefix>.log`. When it is
+//! rotated it is renamed to `<file_prefix>.<timestamp>.log` and a fresh file
+//! is started, so rotated files sort oldest-first by name.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+/// Where and how to write log files
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FileLogConfig {
+    /// The directory to write log files into. Created if missing.
+    pub directory: PathBuf,
+    /// The name log files start with
+    #[serde(default = "default_file_prefix")]
+    pub file_prefix: String,
+    /// Rotate once the current file would grow past this many bytes
+    #[serde(default)]
+    pub max_file_size_bytes: Option<u64>,
+    /// Rotate when the current file was started in an earlier period
+    #[serde(default)]
+    pub rotation: RotationPeriod,
+    /// Delete the oldest rotated files once there are more than this many
+    #[serde(default)]
+    pub max_files: Option<usize>,
+}
+
+fn default_file_prefix() -> String {
+    "holochain".into()
+}
+
+/// How often to rotate log files regardless of their size
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationPeriod {
+    /// Only rotate on size
+    Never,
+    /// Start a new file every hour
+    Hourly,
+    /// Start a new file every day
+    Daily,
+}
+
+impl Default for RotationPeriod {
+    fn default() -> Self {
+        RotationPeriod::Daily
+    }
+}
+
+impl RotationPeriod {
+    /// Whether `then` and `now` fall in different periods
+    fn has_elapsed(&self, then: &DateTime<Utc>, now: &DateTime<Utc>) -> bool {
+        let period = match self {
+            RotationPeriod::Never => return false,
+            RotationPeriod::Hourly => "%Y%m%d%H",
+            RotationPeriod::Daily => "%Y%m%d",
+        };
+        then.format(period).to_string() != now.format(period).to_string()
+    }
+}
+
+/// A writer which can be handed to a tracing subscriber, rotating the
+/// underlying file as described by a [FileLogConfig].
+#[derive(Clone)]
+pub struct RotatingFileWriter(Arc<Mutex<RotatingFile>>);
+
+struct RotatingFile {
+    config: FileLogConfig,
+    file: File,
+    size: u64,
+    opened_at: DateTime<Utc>,
+}
+
+impl RotatingFileWriter {
+    /// Open (or continue appending to) the current log file
+    pub fn new(config: FileLogConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.directory)?;
+        let file = open_append(&current_path(&config))?;
+        let size = file.metadata()?.len();
+        Ok(Self(Arc::new(Mutex::new(RotatingFile {
+            config,
+            file,
+            size,
+            opened_at: Utc::now(),
+        }))))
+    }
+}
+
+fn current_path(config: &FileLogConfig) -> PathBuf {
+    config.directory.join(format!("{}.log", config.file_prefix))
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl RotatingFile {
+    fn needs_rotation(&self, incoming: usize, now: &DateTime<Utc>) -> bool {
+        // Never rotate an empty file, or a single huge line could loop forever
+        if self.size == 0 {
+            return false;
+        }
+        let too_big = self
+            .config
+            .max_file_size_bytes
+            .map(|max| self.size + incoming as u64 > max)
+            .unwrap_or(false);
+        too_big || self.config.rotation.has_elapsed(&self.opened_at, now)
+    }
+
+    fn rotate(&mut self, now: DateTime<Utc>) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = self.config.directory.join(format!(
+            "{}.{}.log",
+            self.config.file_prefix,
+            now.format("%Y-%m-%dT%H-%M-%S%.9f")
+        ));
+        let current = current_path(&self.config);
+        fs::rename(&current, rotated)?;
+        self.file = open_append(&current)?;
+        self.size = 0;
+        self.opened_at = now;
+        self.prune()
+    }
+
+    /// Remove the oldest rotated files beyond `max_files`
+    fn prune(&self) -> io::Result<()> {
+        let max_files = match self.config.max_files {
+            Some(max_files) => max_files,
+            None => return Ok(()),
+        };
+        let prefix = format!("{}.", self.config.file_prefix);
+        let current = current_path(&self.config);
+        let mut rotated: Vec<PathBuf> = fs::read_dir(&self.config.directory)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                *path != current
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .map(|name| name.starts_with(&prefix) && name.ends_with(".log"))
+                        .unwrap_or(false)
+            })
+            .collect();
+        if rotated.len() > max_files {
+            rotated.sort();
+            for path in &rotated[..rotated.len() - max_files] {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut inner = self
+            .0
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Log file lock poisoned"))?;
+        let now = Utc::now();
+        if inner.needs_rotation(buf.len(), &now) {
+            inner.rotate(now)?;
+        }
+        let written = inner.file.write(buf)?;
+        inner.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0
+            .lock()
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "Log file lock poisoned"))?
+            .file
+            .flush()
+    }
+}
+
+impl MakeWriter for RotatingFileWriter {
+    type Writer = Self;
+
+    fn make_writer(&self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_files(dir: &std::path::Path) -> Vec<String> {
+        let mut files: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn rotates_on_size_and_prunes() {
+        let tmp = tempdir::TempDir::new("observability_file").unwrap();
+        let mut writer = RotatingFileWriter::new(FileLogConfig {
+            directory: tmp.path().to_owned(),
+            file_prefix: "test".into(),
+            max_file_size_bytes: Some(10),
+            rotation: RotationPeriod::Never,
+            max_files: Some(2),
+        })
+        .unwrap();
+
+        // Each line fills the file, so every subsequent write rotates
+        for i in 0..5 {
+            writer
+                .write_all(format!("line {:04}\n", i).as_bytes())
+                .unwrap();
+        }
+        writer.flush().unwrap();
+
+        let files = log_files(tmp.path());
+        assert_eq!(files.len(), 3);
+        assert_eq!(files.last().unwrap(), "test.log");
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("test.log")).unwrap(),
+            "line 0004\n"
+        );
+        // Only the newest rotated files are kept
+        assert_eq!(
+            fs::read_to_string(tmp.path().join(&files[1])).unwrap(),
+            "line 0003\n"
+        );
+    }
+
+    #[test]
+    fn period_elapsed() {
+        let then = "2020-01-01T10:59:00Z".parse::<DateTime<Utc>>().unwrap();
+        let now = "2020-01-01T11:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert!(RotationPeriod::Hourly.has_elapsed(&then, &now));
+        assert!(!RotationPeriod::Daily.has_elapsed(&then, &now));
+        assert!(!RotationPeriod::Never.has_elapsed(&then, &now));
+    }
+}
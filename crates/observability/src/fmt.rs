@@ -4,7 +4,7 @@ use tracing_core::field::Field;
 use tracing_serde::AsSerde;
 use tracing_subscriber::{
     field::Visit,
-    fmt::{FmtContext, FormatFields},
+    fmt::{FmtContext, FormatFields, FormattedFields},
     registry::LookupSpan,
 };
 
@@ -72,6 +72,42 @@ where
     writeln!(writer, "{}", json)
 }
 
+// Formatting the events for json, including the fields recorded on each span
+pub(crate) fn format_event_with_span_fields<S, N>(
+    ctx: &FmtContext<'_, S, N>,
+    writer: &mut dyn std::fmt::Write,
+    event: &Event<'_>,
+) -> std::fmt::Result
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'writer> FormatFields<'writer> + 'static,
+{
+    let now = chrono::offset::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let mut parents = vec![];
+    ctx.visit_spans::<(), _>(|span| {
+        let meta = span.metadata();
+        // The fields are already formatted by the subscriber's field formatter,
+        // so they're only structured if that formatter produces json
+        let fields = span
+            .extensions()
+            .get::<FormattedFields<N>>()
+            .map(|f| {
+                serde_json::from_str::<serde_json::Value>(&f.fields)
+                    .unwrap_or_else(|_| json!(f.fields))
+            })
+            .unwrap_or(serde_json::Value::Null);
+        let json = json!({"id": span.id().as_serde(), "name": meta.name(), "level": meta.level().as_serde(), "target": meta.target(), "module_path": meta.module_path(), "file": meta.file(), "line": meta.line(), "fields": fields});
+        parents.push(json);
+        Ok(())
+    })
+    .ok();
+    let meta = event.metadata();
+    let mut values = EventFieldVisitor::new();
+    event.record(&mut values);
+    let json = json!({"time": now, "name": meta.name(), "level": meta.level().as_serde(), "target": meta.target(), "module_path": meta.module_path(), "file": meta.file(), "line": meta.line(), "fields": values.json, "spans": parents});
+    writeln!(writer, "{}", json)
+}
+
 // Formatting the events for json
 pub(crate) fn format_event_flame<S, N>(
     ctx: &FmtContext<'_, S, N>,
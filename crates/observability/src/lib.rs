@@ -59,6 +59,18 @@
 //! cat out.json | jq '. | {time: .time, name: .name, message: .fields.message, file: .file, line: .line, fields: .fields, spans: .spans}' | json2csv -o log.csv
 //! tad log.csv
 //! ```
+//!
+//! #### Log files
+//! For production nodes [init_fmt_with_file] writes the same Json, plus the fields of every
+//! parent span, to a log file which is rotated by size and/or every hour or day
+//! (see [FileLogConfig]). Human readable output still goes to stderr.
+//! The conductor enables this with a `[logger.file]` section in its config.
+//!
+//! #### Distributed traces
+//! Built with the `otlp` feature, [init_fmt_with_otlp] also exports spans to an
+//! OpenTelemetry collector (see [OtlpConfig]), so the spans of a zome call can be
+//! followed through the cascade and workflows in Jaeger or Grafana.
+//! The conductor enables this with a `[logger.otlp]` section in its config.
 
 use tracing::{Event, Subscriber};
 use tracing_subscriber::{
@@ -75,8 +87,13 @@ use tracing_flame::FlameLayer;
 use flames::{toml_path, Flame, FlameTimed};
 use fmt::*;
 
+pub use file::{FileLogConfig, RotatingFileWriter, RotationPeriod};
+pub use otlp::{OtlpConfig, OtlpGuard};
+
+mod file;
 mod flames;
 mod fmt;
+mod otlp;
 
 #[derive(Debug, Clone)]
 /// Sets the kind of structured logging output you want
@@ -229,7 +246,7 @@ pub fn flame_run() -> Result<Option<impl Drop>, errors::TracingError> {
 
 /// This checks RUST_LOG for a filter but doesn't complain if there is none or it doesn't parse.
 /// It then checks for CUSTOM_FILTER which if set will output an error if it doesn't parse.
-pub fn init_fmt(output: Output) -> Result<(), errors::TracingError> {
+fn env_filter() -> EnvFilter {
     let mut filter = EnvFilter::from_default_env();
     if std::env::var("CUSTOM_FILTER").is_ok() {
         EnvFilter::try_from_env("CUSTOM_FILTER")
@@ -239,6 +256,13 @@ pub fn init_fmt(output: Output) -> Result<(), errors::TracingError> {
             })
             .ok();
     }
+    filter
+}
+
+/// This checks RUST_LOG for a filter but doesn't complain if there is none or it doesn't parse.
+/// It then checks for CUSTOM_FILTER which if set will output an error if it doesn't parse.
+pub fn init_fmt(output: Output) -> Result<(), errors::TracingError> {
+    let filter = env_filter();
     let fm: fn(
         ctx: &FmtContext<'_, _, _>,
         &mut dyn std::fmt::Write,
@@ -306,6 +330,100 @@ pub fn init_fmt(output: Output) -> Result<(), errors::TracingError> {
     }
 }
 
+/// Same as [init_fmt] but also writes every event as json, including the
+/// fields of its parent spans, to rotating log files for shipping elsewhere.
+/// When logging to files `output` only controls stderr, where anything
+/// other than `None` gives the human readable `Log` format.
+pub fn init_fmt_with_file(
+    output: Output,
+    file_config: FileLogConfig,
+) -> Result<(), errors::TracingError> {
+    let filter = env_filter();
+    let fm: fn(
+        ctx: &FmtContext<'_, _, _>,
+        &mut dyn std::fmt::Write,
+        &Event<'_>,
+    ) -> std::fmt::Result = format_event_with_span_fields;
+
+    let file_layer = tracing_subscriber::fmt::layer()
+        .with_writer(RotatingFileWriter::new(file_config)?)
+        .json()
+        .event_format(fm);
+    let subscriber = Registry::default().with(filter);
+
+    match output {
+        Output::None => finish(subscriber.with(file_layer)),
+        _ => {
+            let stderr_layer = tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_target(true);
+            finish(subscriber.with(stderr_layer).with(file_layer))
+        }
+    }
+}
+
+/// Same as [init_fmt_with_file], or [init_fmt] with the `Log` output when
+/// there's no `file_config`, but also exports spans over OTLP.
+/// Spans are exported until the returned guard is dropped.
+pub fn init_fmt_with_otlp(
+    output: Output,
+    file_config: Option<FileLogConfig>,
+    otlp_config: OtlpConfig,
+) -> Result<OtlpGuard, errors::TracingError> {
+    #[cfg(feature = "otlp")]
+    {
+        let filter = env_filter();
+        let (tracer, guard) = otlp::tracer(otlp_config)?;
+        let otlp_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+        let subscriber = Registry::default().with(filter).with(otlp_layer);
+        let stderr_layer = match output {
+            Output::None => None,
+            _ => Some(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(std::io::stderr)
+                    .with_target(true),
+            ),
+        };
+
+        match (stderr_layer, file_config) {
+            (None, None) => finish(subscriber)?,
+            (None, Some(file_config)) => {
+                let fm: fn(
+                    ctx: &FmtContext<'_, _, _>,
+                    &mut dyn std::fmt::Write,
+                    &Event<'_>,
+                ) -> std::fmt::Result = format_event_with_span_fields;
+                let file_layer = tracing_subscriber::fmt::layer()
+                    .with_writer(RotatingFileWriter::new(file_config)?)
+                    .json()
+                    .event_format(fm);
+                finish(subscriber.with(file_layer))?
+            }
+            (Some(stderr_layer), None) => finish(subscriber.with(stderr_layer))?,
+            (Some(stderr_layer), Some(file_config)) => {
+                let fm: fn(
+                    ctx: &FmtContext<'_, _, _>,
+                    &mut dyn std::fmt::Write,
+                    &Event<'_>,
+                ) -> std::fmt::Result = format_event_with_span_fields;
+                let file_layer = tracing_subscriber::fmt::layer()
+                    .with_writer(RotatingFileWriter::new(file_config)?)
+                    .json()
+                    .event_format(fm);
+                finish(subscriber.with(stderr_layer).with(file_layer))?
+            }
+        }
+        Ok(guard)
+    }
+    #[cfg(not(feature = "otlp"))]
+    {
+        let _ = (output, file_config, otlp_config);
+        Err(errors::TracingError::Otlp(
+            "observability was built without the otlp feature".into(),
+        ))
+    }
+}
+
 fn finish<S>(subscriber: S) -> Result<(), errors::TracingError>
 where
     S: Subscriber + Send + Sync + for<'span> LookupSpan<'span>,
@@ -332,5 +450,9 @@ pub mod errors {
         TracingFlame,
         #[error(transparent)]
         TracingFlameError(#[from] tracing_flame::Error),
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Failed to export spans over OTLP: {0}")]
+        Otlp(String),
     }
 }
@@ -0,0 +1,96 @@
+//! Exporting spans to an OpenTelemetry collector over OTLP, so traces can be
+//! viewed in tools like Jaeger or Grafana Tempo.
+//!
+//! The exporter is only built with the `otlp` feature. Without it
+//! [init_fmt_with_otlp](crate::init_fmt_with_otlp) returns an error.
+
+use serde::{Deserialize, Serialize};
+
+/// Where to export spans to and how many of them
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OtlpConfig {
+    /// The collector's OTLP gRPC endpoint
+    #[serde(default = "default_endpoint")]
+    pub endpoint: String,
+    /// The service name traces from this conductor are reported under
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// The fraction of traces to export, from 0.0 to 1.0
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: f64,
+}
+
+fn default_endpoint() -> String {
+    "localhost:55680".into()
+}
+
+fn default_service_name() -> String {
+    "holochain".into()
+}
+
+fn default_sample_ratio() -> f64 {
+    1.0
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: default_endpoint(),
+            service_name: default_service_name(),
+            sample_ratio: default_sample_ratio(),
+        }
+    }
+}
+
+/// Keeps spans being exported. Dropping it flushes any spans
+/// which haven't been sent yet and shuts the exporter down.
+pub struct OtlpGuard {
+    #[cfg(feature = "otlp")]
+    _uninstall: opentelemetry_otlp::Uninstall,
+}
+
+#[cfg(feature = "otlp")]
+pub(crate) fn tracer(
+    config: OtlpConfig,
+) -> Result<(opentelemetry::sdk::Tracer, OtlpGuard), crate::errors::TracingError> {
+    use opentelemetry::{api::KeyValue, sdk};
+    use std::sync::Arc;
+
+    let (tracer, uninstall) = opentelemetry_otlp::new_pipeline()
+        .with_endpoint(config.endpoint)
+        .with_trace_config(sdk::Config {
+            default_sampler: Box::new(sdk::Sampler::Probability(
+                config.sample_ratio.max(0.0).min(1.0),
+            )),
+            resource: Arc::new(sdk::Resource::new(vec![KeyValue::new(
+                "service.name",
+                config.service_name,
+            )])),
+            ..Default::default()
+        })
+        .install()
+        .map_err(|e| crate::errors::TracingError::Otlp(e.to_string()))?;
+    Ok((
+        tracer,
+        OtlpGuard {
+            _uninstall: uninstall,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_defaults() {
+        let config: OtlpConfig = serde_json::from_str(r#"{"service_name": "alice"}"#).unwrap();
+        assert_eq!(
+            config,
+            OtlpConfig {
+                service_name: "alice".into(),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -92,6 +92,36 @@ where
         })))
     }
 
+    /// Iterate over the persisted data with this prefix, returning each value
+    /// alongside the hash it is stored under, *without* checking that the
+    /// hash matches the content. This is only meant for integrity checks,
+    /// which need to find corrupt data without triggering the fatal check.
+    pub fn iter_unchecked<'r, R: Readable>(
+        &'r self,
+        r: &'r R,
+    ) -> DatabaseResult<impl FallibleIterator<Item = (HoloHashOf<C>, C), Error = DatabaseError> + 'r>
+    {
+        Ok(Box::new(
+            self.0
+                .store()
+                .iter(r)?
+                .filter(|(k, _)| Ok(k.first() == Some(&P::PREFIX)))
+                .map(|(k, c)| {
+                    let k: PrefixHashKey<P> = PrefixHashKey::from_key_bytes_or_friendly_panic(k);
+                    Ok((
+                        HoloHashOf::<C>::from_raw_bytes(k.as_hash_bytes().to_vec()),
+                        c,
+                    ))
+                }),
+        ))
+    }
+
+    /// Put a value under a hash without checking that the hash matches.
+    /// This is only meant for moving corrupt data into quarantine.
+    pub fn put_unchecked(&mut self, hash: HoloHashOf<C>, content: C) {
+        self.put(HoloHashed::with_pre_hashed(content, hash))
+    }
+
     fn deserialize_and_hash(hash_bytes: &[u8], content: C) -> HoloHashed<C> {
         let data = HoloHashed::from_content_sync(content);
         fatal_db_hash_integrity_check!(
@@ -29,6 +29,8 @@ pub enum DbName {
     MetaVaultSys,
     /// Vault database: Kv store of links
     MetaVaultLinks,
+    /// Vault database: Kv store of links of ordered link types, keyed by the tag's value
+    MetaVaultLinkTags,
     /// Vault database: Kv store of entry dht status
     MetaVaultMisc,
     /// int KV store storing the sequence of committed headers,
@@ -57,12 +59,32 @@ pub enum DbName {
     AuthoredDhtOps,
     /// Integrated [DhtOp]s KV store
     IntegratedDhtOps,
+    /// Index of the integrated [DhtOp]s, keyed by their header's timestamp then
+    /// [DhtOpHash] so they can be read in the same order on every node
+    IntegratedDhtOpsOrder,
     /// Integration Queue of [DhtOp]s KV store where key is [DhtOpHash]
     IntegrationLimbo,
     /// Place for [DhtOp]s waiting to be validated to hang out. KV store where key is a [DhtOpHash]
     ValidationLimbo,
     /// KVV store to accumulate validation receipts for a published EntryHash
     ValidationReceipts,
+    /// Record of an integration batch in progress, for crash recovery
+    IntegrationJournal,
+    /// Why [DhtOp]s were abandoned by validation. KV store where key is a [DhtOpHash]
+    AbandonedDhtOps,
+    /// Records emitted by the zomes' index_op callbacks, keyed by index name and key
+    AppIndex,
+    /// The remote agents subscribed to changes at a basis we are an authority for
+    Subscriptions,
+    /// Hashes pinned to be held locally. KV store where key is the hash
+    Pins,
+    /// How far publishing each authored [DhtOp] has got. KV store where key is a [DhtOpHash]
+    PublishProgress,
+    /// Other agents' verified source chain headers. KV store where key is an [AgentPubKey]
+    AgentActivityCache,
+    /// The schema version of each of the other databases in the environment,
+    /// keyed by [DbName]
+    SchemaVersion,
 }
 
 impl DbName {
@@ -76,6 +98,7 @@ impl DbName {
             ElementVaultHeaders => Single,
             MetaVaultSys => Multi,
             MetaVaultLinks => Single,
+            MetaVaultLinkTags => Single,
             MetaVaultMisc => Single,
             ChainSequence => SingleInt,
             ElementCacheEntries => Single,
@@ -89,9 +112,18 @@ impl DbName {
             EntryDef => Single,
             AuthoredDhtOps => Single,
             IntegratedDhtOps => Single,
+            IntegratedDhtOpsOrder => Single,
             IntegrationLimbo => Single,
             ValidationLimbo => Single,
             ValidationReceipts => Multi,
+            IntegrationJournal => Single,
+            AbandonedDhtOps => Single,
+            AppIndex => Single,
+            Subscriptions => Single,
+            Pins => Single,
+            PublishProgress => Single,
+            AgentActivityCache => Single,
+            SchemaVersion => Single,
         }
     }
 }
@@ -128,6 +160,8 @@ lazy_static! {
     pub static ref META_VAULT_SYS: DbKey<MultiStore> = DbKey::new(DbName::MetaVaultSys);
     /// The key to access the links database of the Vault
     pub static ref META_VAULT_LINKS: DbKey<SingleStore> = DbKey::new(DbName::MetaVaultLinks);
+    /// The key to access the ordered link tags database of the Vault
+    pub static ref META_VAULT_LINK_TAGS: DbKey<SingleStore> = DbKey::new(DbName::MetaVaultLinkTags);
     /// The key to access the miscellaneous metadata database of the Vault
     pub static ref META_VAULT_MISC: DbKey<SingleStore> = DbKey::new(DbName::MetaVaultMisc);
     /// The key to access the ChainSequence database
@@ -156,12 +190,30 @@ lazy_static! {
     pub static ref AUTHORED_DHT_OPS: DbKey<SingleStore> = DbKey::new(DbName::AuthoredDhtOps);
     /// The key to access the IntegratedDhtOps database
     pub static ref INTEGRATED_DHT_OPS: DbKey<SingleStore> = DbKey::new(DbName::IntegratedDhtOps);
+    /// The key to access the IntegratedDhtOpsOrder database
+    pub static ref INTEGRATED_DHT_OPS_ORDER: DbKey<SingleStore> = DbKey::new(DbName::IntegratedDhtOpsOrder);
     /// The key to access the IntegrationLimbo database
     pub static ref INTEGRATION_LIMBO: DbKey<SingleStore> = DbKey::new(DbName::IntegrationLimbo);
     /// The key to access the IntegrationLimbo database
     pub static ref VALIDATION_LIMBO: DbKey<SingleStore> = DbKey::new(DbName::ValidationLimbo);
     /// The key to access the ValidationReceipts database
     pub static ref VALIDATION_RECEIPTS: DbKey<MultiStore> = DbKey::new(DbName::ValidationReceipts);
+    /// The key to access the IntegrationJournal database
+    pub static ref INTEGRATION_JOURNAL: DbKey<SingleStore> = DbKey::new(DbName::IntegrationJournal);
+    /// The key to access the AbandonedDhtOps database
+    pub static ref ABANDONED_DHT_OPS: DbKey<SingleStore> = DbKey::new(DbName::AbandonedDhtOps);
+    /// The key to access the AppIndex database
+    pub static ref APP_INDEX: DbKey<SingleStore> = DbKey::new(DbName::AppIndex);
+    /// The key to access the Subscriptions database
+    pub static ref SUBSCRIPTIONS: DbKey<SingleStore> = DbKey::new(DbName::Subscriptions);
+    /// The key to access the Pins database
+    pub static ref PINS: DbKey<SingleStore> = DbKey::new(DbName::Pins);
+    /// The key to access the PublishProgress database
+    pub static ref PUBLISH_PROGRESS: DbKey<SingleStore> = DbKey::new(DbName::PublishProgress);
+    /// The key to access the AgentActivityCache database
+    pub static ref AGENT_ACTIVITY_CACHE: DbKey<SingleStore> = DbKey::new(DbName::AgentActivityCache);
+    /// The key to access the SchemaVersion database
+    pub static ref SCHEMA_VERSION: DbKey<SingleStore> = DbKey::new(DbName::SchemaVersion);
 }
 
 lazy_static! {
@@ -169,21 +221,25 @@ lazy_static! {
 }
 
 /// Get access to the singleton database manager ([GetDb]),
-/// in order to access individual LMDB databases
-pub(super) fn initialize_databases(rkv: &Rkv, kind: &EnvironmentKind) -> DatabaseResult<()> {
+/// in order to access individual LMDB databases.
+/// Returns the names of the environment's databases, other than
+/// [DbName::SchemaVersion].
+pub(super) fn initialize_databases(
+    rkv: &Rkv,
+    kind: &EnvironmentKind,
+    read_only: bool,
+) -> DatabaseResult<Vec<DbName>> {
     let mut dbmap = DB_MAP_MAP.write();
     let path = rkv.path().to_owned();
     match dbmap.entry(path.clone()) {
-        hash_map::Entry::Occupied(_) => {
-            return Err(DatabaseError::EnvironmentDoubleInitialized(path))
-        }
-        hash_map::Entry::Vacant(e) => e.insert({
+        hash_map::Entry::Occupied(_) => Err(DatabaseError::EnvironmentDoubleInitialized(path)),
+        hash_map::Entry::Vacant(e) => {
             let mut um = UniversalMap::new();
-            register_databases(&rkv, kind, &mut um)?;
-            um
-        }),
-    };
-    Ok(())
+            let names = register_databases(&rkv, kind, &mut um, read_only)?;
+            e.insert(um);
+            Ok(names)
+        }
+    }
 }
 
 pub(super) fn get_db<V: 'static + Copy + Send + Sync>(
@@ -200,58 +256,88 @@ pub(super) fn get_db<V: 'static + Copy + Send + Sync>(
     Ok(db)
 }
 
-fn register_databases(env: &Rkv, kind: &EnvironmentKind, um: &mut DbMap) -> DatabaseResult<()> {
+fn register_databases(
+    env: &Rkv,
+    kind: &EnvironmentKind,
+    um: &mut DbMap,
+    read_only: bool,
+) -> DatabaseResult<Vec<DbName>> {
+    register_db(env, um, read_only, &*SCHEMA_VERSION)?;
+    let mut names = Vec::new();
     match kind {
         EnvironmentKind::Cell(_) => {
-            register_db(env, um, &*ELEMENT_VAULT_PUBLIC_ENTRIES)?;
-            register_db(env, um, &*ELEMENT_VAULT_PRIVATE_ENTRIES)?;
-            register_db(env, um, &*ELEMENT_VAULT_HEADERS)?;
-            register_db(env, um, &*META_VAULT_SYS)?;
-            register_db(env, um, &*META_VAULT_LINKS)?;
-            register_db(env, um, &*META_VAULT_MISC)?;
-            register_db(env, um, &*CHAIN_SEQUENCE)?;
-            register_db(env, um, &*ELEMENT_CACHE_ENTRIES)?;
-            register_db(env, um, &*ELEMENT_CACHE_HEADERS)?;
-            register_db(env, um, &*CACHE_SYSTEM_META)?;
-            register_db(env, um, &*CACHE_LINKS_META)?;
-            register_db(env, um, &*CACHE_STATUS_META)?;
-            register_db(env, um, &*AUTHORED_DHT_OPS)?;
-            register_db(env, um, &*INTEGRATED_DHT_OPS)?;
-            register_db(env, um, &*INTEGRATION_LIMBO)?;
-            register_db(env, um, &*VALIDATION_LIMBO)?;
-            register_db(env, um, &*VALIDATION_RECEIPTS)?;
+            names.push(register_db(
+                env,
+                um,
+                read_only,
+                &*ELEMENT_VAULT_PUBLIC_ENTRIES,
+            )?);
+            names.push(register_db(
+                env,
+                um,
+                read_only,
+                &*ELEMENT_VAULT_PRIVATE_ENTRIES,
+            )?);
+            names.push(register_db(env, um, read_only, &*ELEMENT_VAULT_HEADERS)?);
+            names.push(register_db(env, um, read_only, &*META_VAULT_SYS)?);
+            names.push(register_db(env, um, read_only, &*META_VAULT_LINKS)?);
+            names.push(register_db(env, um, read_only, &*META_VAULT_LINK_TAGS)?);
+            names.push(register_db(env, um, read_only, &*META_VAULT_MISC)?);
+            names.push(register_db(env, um, read_only, &*CHAIN_SEQUENCE)?);
+            names.push(register_db(env, um, read_only, &*ELEMENT_CACHE_ENTRIES)?);
+            names.push(register_db(env, um, read_only, &*ELEMENT_CACHE_HEADERS)?);
+            names.push(register_db(env, um, read_only, &*CACHE_SYSTEM_META)?);
+            names.push(register_db(env, um, read_only, &*CACHE_LINKS_META)?);
+            names.push(register_db(env, um, read_only, &*CACHE_STATUS_META)?);
+            names.push(register_db(env, um, read_only, &*AUTHORED_DHT_OPS)?);
+            names.push(register_db(env, um, read_only, &*INTEGRATED_DHT_OPS)?);
+            names.push(register_db(env, um, read_only, &*INTEGRATED_DHT_OPS_ORDER)?);
+            names.push(register_db(env, um, read_only, &*INTEGRATION_LIMBO)?);
+            names.push(register_db(env, um, read_only, &*VALIDATION_LIMBO)?);
+            names.push(register_db(env, um, read_only, &*VALIDATION_RECEIPTS)?);
+            names.push(register_db(env, um, read_only, &*INTEGRATION_JOURNAL)?);
+            names.push(register_db(env, um, read_only, &*ABANDONED_DHT_OPS)?);
+            names.push(register_db(env, um, read_only, &*APP_INDEX)?);
+            names.push(register_db(env, um, read_only, &*SUBSCRIPTIONS)?);
+            names.push(register_db(env, um, read_only, &*PINS)?);
+            names.push(register_db(env, um, read_only, &*PUBLISH_PROGRESS)?);
+            names.push(register_db(env, um, read_only, &*AGENT_ACTIVITY_CACHE)?);
         }
         EnvironmentKind::Conductor => {
-            register_db(env, um, &*CONDUCTOR_STATE)?;
+            names.push(register_db(env, um, read_only, &*CONDUCTOR_STATE)?);
         }
         EnvironmentKind::Wasm => {
-            register_db(env, um, &*WASM)?;
-            register_db(env, um, &*DNA_DEF)?;
-            register_db(env, um, &*ENTRY_DEF)?;
+            names.push(register_db(env, um, read_only, &*WASM)?);
+            names.push(register_db(env, um, read_only, &*DNA_DEF)?);
+            names.push(register_db(env, um, read_only, &*ENTRY_DEF)?);
         }
     }
-    Ok(())
+    Ok(names)
 }
 
 fn register_db<V: 'static + Send + Sync>(
     env: &Rkv,
     um: &mut DbMap,
+    read_only: bool,
     key: &DbKey<V>,
-) -> DatabaseResult<()> {
+) -> DatabaseResult<DbName> {
     let db_name = key.key();
     let db_str = format!("{}", db_name);
-    let _ = match db_name.kind() {
-        DbKind::Single => um.insert(
-            key.with_value_type(),
-            env.open_single(db_str.as_str(), StoreOptions::create())?,
-        ),
-        DbKind::SingleInt => um.insert(
-            key.with_value_type(),
-            env.open_integer::<&str, IntKey>(db_str.as_str(), StoreOptions::create())?,
-        ),
+    // Nothing can be created in a read-only environment,
+    // so only existing databases are opened
+    let mut opts = if read_only {
+        StoreOptions::default()
+    } else {
+        StoreOptions::create()
+    };
+    let opened = match db_name.kind() {
+        DbKind::Single => env
+            .open_single(db_str.as_str(), opts)
+            .map(|db| um.insert(key.with_value_type(), db)),
+        DbKind::SingleInt => env
+            .open_integer::<&str, IntKey>(db_str.as_str(), opts)
+            .map(|db| um.insert(key.with_value_type(), db)),
         DbKind::Multi => {
-            let mut opts = StoreOptions::create();
-
             // This is needed for the optional put flag NO_DUP_DATA on KvvBufUsed.
             // As far as I can tell, if we are not using NO_DUP_DATA, it will
             // only affect the sorting of the values in case there are dups,
@@ -265,13 +351,18 @@ fn register_db<V: 'static + Send + Sync>(
             // it is removed out from under us at some point in the future.
             opts.flags.set(rkv::DatabaseFlags::DUP_SORT, true);
 
-            um.insert(
-                key.with_value_type(),
-                env.open_multi(db_str.as_str(), opts)?,
-            )
+            env.open_multi(db_str.as_str(), opts)
+                .map(|db| um.insert(key.with_value_type(), db))
         }
     };
-    Ok(())
+    match opened {
+        // An environment written by an older version may be missing newer
+        // databases. When reading, these are simply left unregistered.
+        Err(rkv::StoreError::LmdbError(rkv::LmdbError::NotFound)) if read_only => {
+            Ok(db_name.clone())
+        }
+        r => r.map(|_| db_name.clone()).map_err(Into::into),
+    }
 }
 
 /// GetDb allows access to the UniversalMap which stores the heterogeneously typed
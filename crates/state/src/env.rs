@@ -3,6 +3,7 @@
 use crate::{
     db::{get_db, initialize_databases, DbKey, GetDb},
     error::{DatabaseError, DatabaseResult},
+    migration::migrate,
     transaction::{Reader, Writer},
 };
 use derive_more::Into;
@@ -46,6 +47,8 @@ lazy_static! {
 
         RwLock::new(HashMap::new())
     };
+    static ref READ_ONLY_ENVIRONMENTS: RwLock<HashMap<PathBuf, EnvironmentRead>> =
+        RwLock::new(HashMap::new());
 }
 
 fn default_flags() -> EnvironmentFlags {
@@ -54,6 +57,14 @@ fn default_flags() -> EnvironmentFlags {
     EnvironmentFlags::WRITE_MAP | EnvironmentFlags::MAP_ASYNC
 }
 
+fn read_only_flags() -> EnvironmentFlags {
+    // WRITE_MAP would need write access to the data file, so it's left off.
+    // The lock file is still used, which registers our readers with any
+    // process writing to the same environment, so that it won't reuse pages
+    // which our open transactions can still see.
+    EnvironmentFlags::READ_ONLY
+}
+
 #[cfg(feature = "lmdb_no_tls")]
 fn required_flags() -> EnvironmentFlags {
     // NO_TLS associates read slots with the transaction object instead of the thread, which is crucial for us
@@ -66,7 +77,7 @@ fn required_flags() -> EnvironmentFlags {
     EnvironmentFlags::default()
 }
 
-fn rkv_builder(
+pub(crate) fn rkv_builder(
     initial_map_size: Option<usize>,
     flags: Option<EnvironmentFlags>,
 ) -> impl (Fn(&Path) -> Result<Rkv, rkv::StoreError>) {
@@ -117,6 +128,55 @@ impl EnvironmentRead {
     pub fn path(&self) -> &PathBuf {
         &self.path
     }
+
+    /// How many bytes of the environment's data file are in use
+    pub fn used_bytes(&self) -> DatabaseResult<u64> {
+        let rkv = self.arc.read();
+        let pages = rkv.info()?.last_pgno() as u64 + 1;
+        Ok(pages * rkv.stat()?.page_size() as u64)
+    }
+
+    /// Open an existing environment read-only, e.g. to inspect the databases
+    /// of a running conductor from another process.
+    ///
+    /// LMDB allows any number of processes to read while one writes: each
+    /// read transaction sees a consistent snapshot of the last commit made
+    /// before it began, and never blocks the writer. Keep read transactions
+    /// short, as the writer can't reclaim space while they are open.
+    ///
+    /// If this process already has the environment open for writing, that
+    /// environment is returned instead, as LMDB must not be opened twice
+    /// by the same process.
+    pub fn open_read_only(
+        path_prefix: &Path,
+        kind: EnvironmentKind,
+        keystore: KeystoreSender,
+    ) -> DatabaseResult<EnvironmentRead> {
+        let path = path_prefix.join(kind.path());
+        if let Some(env) = ENVIRONMENTS.read().get(&path) {
+            return Ok(env.clone().into());
+        }
+        let mut map = READ_ONLY_ENVIRONMENTS.write();
+        let env = match map.entry(path.clone()) {
+            hash_map::Entry::Occupied(e) => e.get().clone(),
+            hash_map::Entry::Vacant(e) => {
+                if !path.is_dir() {
+                    return Err(DatabaseError::EnvironmentMissing(path));
+                }
+                let rkv = rkv_builder(None, Some(read_only_flags()))(&path)?;
+                tracing::debug!("Opening databases read-only for path {:?}", path);
+                initialize_databases(&rkv, &kind, true)?;
+                e.insert(EnvironmentRead {
+                    arc: Arc::new(RwLock::new(rkv)),
+                    kind,
+                    keystore,
+                    path,
+                })
+                .clone()
+            }
+        };
+        Ok(env)
+    }
 }
 
 impl GetDb for EnvironmentWrite {
@@ -146,7 +206,8 @@ impl GetDb for EnvironmentRead {
 pub struct EnvironmentWrite(EnvironmentRead);
 
 impl EnvironmentWrite {
-    /// Create an environment,
+    /// Create an environment, or open an existing one and migrate
+    /// its databases to the latest schema versions
     pub fn new(
         path_prefix: &Path,
         kind: EnvironmentKind,
@@ -154,7 +215,8 @@ impl EnvironmentWrite {
     ) -> DatabaseResult<EnvironmentWrite> {
         let mut map = ENVIRONMENTS.write();
         let path = path_prefix.join(kind.path());
-        if !path.is_dir() {
+        let fresh = !path.is_dir();
+        if fresh {
             std::fs::create_dir(path.clone())
                 .map_err(|_e| DatabaseError::EnvironmentMissing(path.clone()))?;
         }
@@ -164,7 +226,8 @@ impl EnvironmentWrite {
                 .insert({
                     let rkv = rkv_builder(None, None)(&path)?;
                     tracing::debug!("Initializing databases for path {:?}", path);
-                    initialize_databases(&rkv, &kind)?;
+                    let dbs = initialize_databases(&rkv, &kind, false)?;
+                    migrate(&rkv, &path, &dbs, fresh)?;
                     EnvironmentWrite(EnvironmentRead {
                         arc: Arc::new(RwLock::new(rkv)),
                         kind,
@@ -192,6 +255,15 @@ impl EnvironmentWrite {
         EnvironmentWriteRef(self.0.guard())
     }
 
+    /// Flush everything written so far to disk, and stop handing this
+    /// environment out to new callers. The environment itself is closed once
+    /// the last clone of it is dropped.
+    pub fn close(&self) -> DatabaseResult<()> {
+        self.0.arc.read().sync(true)?;
+        ENVIRONMENTS.write().remove(&self.0.path);
+        Ok(())
+    }
+
     /// Remove the db and directory
     pub async fn remove(self) -> DatabaseResult<()> {
         let mut map = ENVIRONMENTS.write();
@@ -239,6 +311,11 @@ impl<'e> EnvironmentReadRef<'e> {
 }
 
 /// Implementors are able to create a new read-only LMDB transaction
+///
+/// The managed transactions of this trait and [WriteManager] are run with
+/// [tokio::task::block_in_place], so they don't hold up the other tasks on
+/// an async worker thread. This means they can't be run on tokio's basic
+/// scheduler.
 pub trait ReadManager<'e> {
     /// Create a new read-only LMDB transaction
     fn reader(&'e self) -> DatabaseResult<Reader<'e>>;
@@ -273,7 +350,7 @@ impl<'e> ReadManager<'e> for EnvironmentReadRef<'e> {
         E: From<DatabaseError>,
         F: FnOnce(Reader) -> Result<R, E>,
     {
-        f(self.reader()?)
+        tokio::task::block_in_place(move || f(self.reader()?))
     }
 }
 
@@ -283,10 +360,12 @@ impl<'e> WriteManager<'e> for EnvironmentWriteRef<'e> {
         E: From<DatabaseError>,
         F: FnOnce(&mut Writer) -> Result<R, E>,
     {
-        let mut writer = Writer::from(self.rkv.write().map_err(Into::into)?);
-        let result = f(&mut writer)?;
-        writer.commit().map_err(Into::into)?;
-        Ok(result)
+        tokio::task::block_in_place(move || {
+            let mut writer = Writer::from(self.rkv.write().map_err(Into::into)?);
+            let result = f(&mut writer)?;
+            writer.commit().map_err(Into::into)?;
+            Ok(result)
+        })
     }
 }
 
@@ -323,3 +402,41 @@ impl<'e> ReadManager<'e> for EnvironmentWriteRef<'e> {
         self.0.with_reader(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::WASM, test_utils::test_keystore};
+    use rkv::StoreOptions;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn read_only_env_reads_existing_databases() -> DatabaseResult<()> {
+        let tmpdir = tempdir::TempDir::new("holochain-read-only-env").unwrap();
+        let path = tmpdir.path().join(EnvironmentKind::Wasm.path());
+        std::fs::create_dir(&path)?;
+
+        // Write the environment as another process would,
+        // creating only one of its databases
+        {
+            let rkv = rkv_builder(None, None)(&path)?;
+            let db = rkv.open_single("Wasm", StoreOptions::create())?;
+            let mut writer = rkv.write()?;
+            db.put(&mut writer, "key", &rkv::Value::Str("value"))?;
+            writer.commit()?;
+        }
+
+        let env =
+            EnvironmentRead::open_read_only(tmpdir.path(), EnvironmentKind::Wasm, test_keystore())?;
+        let db = env.get_db(&*WASM)?;
+        let g = env.guard();
+        let r = g.reader()?;
+        assert_eq!(db.get(&r, "key")?, Some(rkv::Value::Str("value")));
+
+        // Databases which were never created aren't available
+        assert!(matches!(
+            env.get_db(&*crate::db::DNA_DEF),
+            Err(DatabaseError::StoreNotInitialized(_, _))
+        ));
+        Ok(())
+    }
+}
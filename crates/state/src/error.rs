@@ -5,6 +5,7 @@
 
 use crate::db::DbName;
 use failure::Fail;
+use holochain_error_kind::{ErrorKind, HasErrorKind};
 use holochain_types::{element::error::ElementGroupError, prelude::SerializedBytesError};
 use std::path::PathBuf;
 use thiserror::Error;
@@ -79,6 +80,13 @@ pub enum DatabaseError {
 
     #[error("Key range must be not empty and start < end")]
     InvalidKeyRange,
+
+    #[error("The {db} database is at schema version {found}, but this version of Holochain only supports up to version {supported}")]
+    SchemaTooNew {
+        db: DbName,
+        found: u32,
+        supported: u32,
+    },
 }
 
 impl PartialEq for DatabaseError {
@@ -87,6 +95,29 @@ impl PartialEq for DatabaseError {
     }
 }
 
+impl HasErrorKind for DatabaseError {
+    fn error_kind(&self) -> ErrorKind {
+        use DatabaseError::*;
+        match self {
+            MsgPackEncodeError(_) | MsgPackDecodeError(_) | SerializedBytes(_) => {
+                ErrorKind::Serialization
+            }
+            NoPrivateDb(_) => ErrorKind::Unauthorized,
+            EmptyKey | InvalidKeyRange => ErrorKind::InvalidInput,
+            KeystoreError(_) | ElementGroupError(_) | Other(_) => ErrorKind::Internal,
+            EmptyStore(_)
+            | StoreNotInitialized(_, _)
+            | EnvironmentDoubleInitialized(_)
+            | EnvironmentMissing(_)
+            | InvalidValue
+            | LmdbStoreError(_)
+            | LmdbDataError(_)
+            | DirectoryError(_)
+            | SchemaTooNew { .. } => ErrorKind::Storage,
+        }
+    }
+}
+
 pub type DatabaseResult<T> = Result<T, DatabaseError>;
 
 // Note: these are necessary since rkv Errors do not have std::Error impls,
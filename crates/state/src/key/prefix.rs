@@ -9,6 +9,8 @@ const PENDING_PREFIX: u8 = 0x1;
 const JUDGED_PREFIX: u8 = 0x2;
 /// Prefix for the database of rejected data (has been judged and found invalid)
 const REJECTED_PREFIX: u8 = 0x3;
+/// Prefix for the database of data which failed an integrity check
+const QUARANTINE_PREFIX: u8 = 0x4;
 
 /// Prefix length 1 + hash length 36
 // TODO: B-02112 change to 39 bytes
@@ -59,6 +61,10 @@ pub struct JudgedPrefix;
 /// Prefix key for data that has been rejected
 pub struct RejectedPrefix;
 
+#[derive(PartialOrd, Clone, Ord, PartialEq, Eq, Debug)]
+/// Prefix key for data that has been quarantined because it is corrupt
+pub struct QuarantinePrefix;
+
 impl PrefixType for IntegratedPrefix {
     const PREFIX: u8 = INTEGRATED_PREFIX;
 }
@@ -75,6 +81,10 @@ impl PrefixType for RejectedPrefix {
     const PREFIX: u8 = REJECTED_PREFIX;
 }
 
+impl PrefixType for QuarantinePrefix {
+    const PREFIX: u8 = QUARANTINE_PREFIX;
+}
+
 impl<P: PrefixType> PrefixHashKey<P> {
     /// Create prefix key from a hash
     pub fn new<C>(hash: &HoloHash<C>) -> Self
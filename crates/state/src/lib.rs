@@ -58,6 +58,7 @@ pub mod error;
 pub mod exports;
 pub mod fatal;
 pub mod key;
+pub mod migration;
 pub mod prelude;
 pub mod transaction;
 
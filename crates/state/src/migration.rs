@@ -0,0 +1,265 @@
+//! Versioning of the format of each LMDB database, and the migrations which
+//! move existing databases from one version to the next.
+//!
+//! Every environment has a [DbName::SchemaVersion] database, which stamps
+//! each of the other databases with the version its contents are in. When an
+//! environment is opened for writing, any [Migration]s which haven't been
+//! applied are run in order, in a single transaction, after copying the
+//! environment's data file to a backup directory alongside it.
+//!
+//! Databases written before versioning existed have no stamp, and are taken
+//! to be at [INITIAL_SCHEMA_VERSION].
+
+use crate::{
+    db::{get_db, DbName, SCHEMA_VERSION},
+    error::{DatabaseError, DatabaseResult},
+    transaction::{Reader, Writer},
+};
+use rkv::{Rkv, SingleStore, Value};
+use std::path::{Path, PathBuf};
+
+/// The version of every database before it has been migrated
+pub const INITIAL_SCHEMA_VERSION: u32 = 1;
+
+/// One change to the format of a database
+pub struct Migration {
+    /// The database this migration changes
+    pub db: DbName,
+    /// The version the database is at once this has run.
+    /// Each migration of a database must be one more than the last.
+    pub to_version: u32,
+    /// What the migration changes, for the logs
+    pub description: &'static str,
+    /// Rewrite the contents of the database, which is found in the
+    /// environment at the given path, into the new format
+    pub run: fn(&Path, &mut Writer) -> DatabaseResult<()>,
+}
+
+/// Every migration, in the order they are applied.
+/// Add new migrations to the end, and never change or remove old ones.
+pub static MIGRATIONS: &[Migration] = &[];
+
+/// The version a database is at once every migration has been applied
+pub fn latest_version(db: &DbName) -> u32 {
+    latest_version_of(db, MIGRATIONS)
+}
+
+fn latest_version_of(db: &DbName, migrations: &[Migration]) -> u32 {
+    migrations
+        .iter()
+        .filter(|m| m.db == *db)
+        .map(|m| m.to_version)
+        .max()
+        .unwrap_or(INITIAL_SCHEMA_VERSION)
+}
+
+/// Bring the databases of a newly opened environment up to date.
+/// A `fresh` environment has only just been created, so is stamped with
+/// the latest versions without migrating anything.
+pub(crate) fn migrate(rkv: &Rkv, path: &Path, dbs: &[DbName], fresh: bool) -> DatabaseResult<()> {
+    migrate_with(rkv, path, dbs, fresh, MIGRATIONS)
+}
+
+fn migrate_with(
+    rkv: &Rkv,
+    path: &Path,
+    dbs: &[DbName],
+    fresh: bool,
+    migrations: &[Migration],
+) -> DatabaseResult<()> {
+    let stamps = get_db::<SingleStore>(path, &*SCHEMA_VERSION)?;
+
+    // Work out where each database is at
+    let mut versions = Vec::with_capacity(dbs.len());
+    {
+        let reader = Reader::from(rkv.read()?);
+        for db in dbs {
+            let latest = latest_version_of(db, migrations);
+            let stamped = read_version(stamps, &reader, db)?;
+            let current = match stamped {
+                Some(v) => v,
+                None if fresh => latest,
+                None => INITIAL_SCHEMA_VERSION,
+            };
+            if current > latest {
+                return Err(DatabaseError::SchemaTooNew {
+                    db: db.clone(),
+                    found: current,
+                    supported: latest,
+                });
+            }
+            versions.push((db, stamped, current));
+        }
+    }
+
+    let pending: Vec<&Migration> = migrations
+        .iter()
+        .filter(|m| {
+            versions
+                .iter()
+                .any(|(db, _, current)| **db == m.db && m.to_version > *current)
+        })
+        .collect();
+    let unstamped = versions.iter().any(|(_, stamped, _)| stamped.is_none());
+    if pending.is_empty() && !unstamped {
+        return Ok(());
+    }
+
+    if !pending.is_empty() {
+        let backup = backup(rkv, path)?;
+        tracing::info!(?path, ?backup, "Migrating databases");
+    }
+
+    let mut writer = Writer::from(rkv.write()?);
+    for m in pending {
+        tracing::info!(db = %m.db, to_version = m.to_version, "{}", m.description);
+        (m.run)(path, &mut writer)?;
+    }
+    for (db, _, _) in versions {
+        write_version(stamps, &mut writer, db, latest_version_of(db, migrations))?;
+    }
+    writer.commit()
+}
+
+fn read_version(stamps: SingleStore, r: &Reader, db: &DbName) -> DatabaseResult<Option<u32>> {
+    match stamps.get(r, db.to_string())? {
+        Some(Value::U64(v)) => Ok(Some(v as u32)),
+        Some(_) => Err(DatabaseError::InvalidValue),
+        None => Ok(None),
+    }
+}
+
+fn write_version(
+    stamps: SingleStore,
+    writer: &mut Writer,
+    db: &DbName,
+    version: u32,
+) -> DatabaseResult<()> {
+    stamps.put(writer, db.to_string(), &Value::U64(version as u64))?;
+    Ok(())
+}
+
+/// Copy the environment's data file into a new directory inside it,
+/// so an install can be rolled back if a migration goes wrong
+fn backup(rkv: &Rkv, path: &Path) -> DatabaseResult<PathBuf> {
+    // Make sure everything written so far is in the file
+    rkv.sync(true)?;
+    let dir = path.join(format!(
+        "backup-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3f")
+    ));
+    std::fs::create_dir_all(&dir)?;
+    std::fs::copy(path.join("data.mdb"), dir.join("data.mdb"))?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{initialize_databases, WASM},
+        env::{rkv_builder, EnvironmentKind, EnvironmentWrite},
+        test_utils::test_keystore,
+    };
+
+    fn rewrite_wasm(path: &Path, writer: &mut Writer) -> DatabaseResult<()> {
+        let db = get_db::<SingleStore>(path, &*WASM)?;
+        db.put(writer, "key", &Value::Str("migrated"))?;
+        Ok(())
+    }
+
+    static TEST_MIGRATIONS: &[Migration] = &[Migration {
+        db: DbName::Wasm,
+        to_version: 2,
+        description: "test migration",
+        run: rewrite_wasm,
+    }];
+
+    fn versions(rkv: &Rkv, path: &Path) -> Vec<(DbName, Option<u32>)> {
+        let stamps = get_db::<SingleStore>(path, &*SCHEMA_VERSION).unwrap();
+        let reader = Reader::from(rkv.read().unwrap());
+        [DbName::Wasm, DbName::DnaDef]
+            .iter()
+            .map(|db| (db.clone(), read_version(stamps, &reader, db).unwrap()))
+            .collect()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn fresh_environments_are_stamped() {
+        let tmpdir = tempdir::TempDir::new("holochain-migration").unwrap();
+        let env =
+            EnvironmentWrite::new(tmpdir.path(), EnvironmentKind::Wasm, test_keystore()).unwrap();
+        let g = env.guard();
+        assert_eq!(
+            versions(g.rkv(), env.path()),
+            vec![
+                (DbName::Wasm, Some(INITIAL_SCHEMA_VERSION)),
+                (DbName::DnaDef, Some(INITIAL_SCHEMA_VERSION))
+            ]
+        );
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn unstamped_environments_are_backed_up_and_migrated() {
+        let tmpdir = tempdir::TempDir::new("holochain-migration").unwrap();
+        let path = tmpdir.path().join("wasm");
+        std::fs::create_dir(&path).unwrap();
+
+        // An environment written before versioning existed
+        let rkv = rkv_builder(None, None)(&path).unwrap();
+        let dbs = initialize_databases(&rkv, &EnvironmentKind::Wasm, false).unwrap();
+        {
+            let db = get_db::<SingleStore>(&path, &*WASM).unwrap();
+            let mut writer = rkv.write().unwrap();
+            db.put(&mut writer, "key", &Value::Str("original")).unwrap();
+            writer.commit().unwrap();
+        }
+
+        migrate_with(&rkv, &path, &dbs, false, TEST_MIGRATIONS).unwrap();
+
+        let db = get_db::<SingleStore>(&path, &*WASM).unwrap();
+        let reader = rkv.read().unwrap();
+        assert_eq!(
+            db.get(&reader, "key").unwrap(),
+            Some(Value::Str("migrated"))
+        );
+        drop(reader);
+        assert_eq!(
+            versions(&rkv, &path),
+            vec![
+                (DbName::Wasm, Some(2)),
+                (DbName::DnaDef, Some(INITIAL_SCHEMA_VERSION))
+            ]
+        );
+
+        // The backup has the data from before the migration
+        let backup = std::fs::read_dir(&path)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .find(|p| p.is_dir())
+            .expect("no backup");
+        let backup = rkv_builder(None, None)(&backup).unwrap();
+        let db = backup
+            .open_single("Wasm", rkv::StoreOptions::default())
+            .unwrap();
+        let reader = backup.read().unwrap();
+        assert_eq!(
+            db.get(&reader, "key").unwrap(),
+            Some(Value::Str("original"))
+        );
+        drop(reader);
+
+        // Running again has nothing to do
+        migrate_with(&rkv, &path, &dbs, false, TEST_MIGRATIONS).unwrap();
+
+        // and a newer version than we know about is refused
+        assert!(matches!(
+            migrate_with(&rkv, &path, &dbs, false, &[]),
+            Err(DatabaseError::SchemaTooNew {
+                db: DbName::Wasm,
+                found: 2,
+                supported: 1
+            })
+        ));
+    }
+}
@@ -1,11 +1,24 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashSet};
+use std::hash::Hasher;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
+/// Holds the hash of the sources the wasms were last built from,
+/// inside the wasm target dir
+const BUILD_STAMP: &str = "test_wasm_build.hash";
+/// Holds the hash of the sources the wasms were last checked from
+const CHECK_STAMP: &str = "test_wasm_check.hash";
+
 fn main() {
     let should_build = std::env::var_os("CARGO_FEATURE_BUILD").is_some();
     let wasms_path = format!("{}/{}/", env!("CARGO_MANIFEST_DIR"), "wasm_workspace");
     println!("cargo:rerun-if-changed=Cargo.toml");
     println!("cargo:rerun-if-changed=../../../Cargo.lock");
+    println!("cargo:rerun-if-env-changed=HC_TEST_WASM_DIR");
+    println!("cargo:rerun-if-env-changed=HC_TEST_WASM_PREBUILT");
+    println!("cargo:rerun-if-env-changed=HC_TEST_WASM_JOBS");
     // We want to rebuild if anything upstream of the wasms has changed.
     // Since we use local paths, changes to those crates will not affect the
     // Cargo.toml, so we check each upstream local source directory directly.
@@ -31,11 +44,41 @@ fn main() {
     {
         println!("cargo:rerun-if-changed={}", item.path().display());
     }
-    let wasm_out = std::env::var_os("HC_TEST_WASM_DIR");
+    let wasm_out = match std::env::var_os("HC_TEST_WASM_DIR") {
+        Some(wasm_out) => PathBuf::from(wasm_out),
+        None => PathBuf::from(format!("{}/target", wasms_path)),
+    };
+
+    // Use wasms which were built elsewhere, e.g. by CI
+    if std::env::var_os("HC_TEST_WASM_PREBUILT").is_some() {
+        let missing = missing_artifacts(&wasms_path, &wasm_out);
+        assert!(
+            missing.is_empty(),
+            "HC_TEST_WASM_PREBUILT is set but these wasms are missing: {:?}",
+            missing
+        );
+        return;
+    }
+
+    // Cargo takes a while to find out nothing needs doing for this many
+    // crates, so skip it if nothing the wasms are built from has changed
+    let stamp = wasm_out.join(if should_build {
+        BUILD_STAMP
+    } else {
+        CHECK_STAMP
+    });
+    let hash = hash_sources(&wasms_path);
+    let up_to_date = std::fs::read_to_string(&stamp)
+        .map(|last| last == hash)
+        .unwrap_or(false);
+    if up_to_date && (!should_build || missing_artifacts(&wasms_path, &wasm_out).is_empty()) {
+        return;
+    }
+
     let cargo_command = std::env::var_os("CARGO");
     let cargo_command = cargo_command.as_deref().unwrap_or_else(|| "cargo".as_ref());
+    let mut cmd = std::process::Command::new(cargo_command);
     if should_build {
-        let mut cmd = std::process::Command::new(cargo_command);
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
         cmd.arg("build")
@@ -45,48 +88,121 @@ fn main() {
             .arg("--workspace")
             .arg("--target")
             .arg("wasm32-unknown-unknown");
-        match wasm_out {
-            Some(wasm_out) => {
-                cmd.env("CARGO_TARGET_DIR", wasm_out);
-            }
-            None => {
-                cmd.env("CARGO_TARGET_DIR", format!("{}/target", wasms_path));
-            }
-        }
-        let output = cmd.output().unwrap();
-
-        assert!(
-            output.status.success(),
-            std::io::stderr().write_all(&output.stderr)
-        );
     } else {
-        let mut cmd = std::process::Command::new(cargo_command);
         cmd.arg("check")
             .arg("--manifest-path")
             .arg("wasm_workspace/Cargo.toml");
-        match wasm_out {
-            Some(wasm_out) => {
-                cmd.env("CARGO_TARGET_DIR", wasm_out);
+    }
+    // Cargo compiles the workspace's wasms in parallel, so let it use
+    // as many jobs as the outer build was given
+    if let Some(jobs) =
+        std::env::var_os("HC_TEST_WASM_JOBS").or_else(|| std::env::var_os("NUM_JOBS"))
+    {
+        cmd.arg("--jobs").arg(jobs);
+    }
+    cmd.env("CARGO_TARGET_DIR", &wasm_out);
+    let output = cmd.output().unwrap();
+    assert!(
+        output.status.success(),
+        std::io::stderr().write_all(&output.stderr)
+    );
+    std::fs::write(stamp, hash).unwrap();
+}
+
+/// Hash every source file the wasms are built from.
+/// The hash is only compared against earlier builds on the same machine,
+/// so the std hasher is good enough.
+fn hash_sources(wasms_path: &str) -> String {
+    let files: BTreeSet<PathBuf> = local_crate_dirs(Path::new(wasms_path))
+        .into_iter()
+        .flat_map(|dir| {
+            walkdir::WalkDir::new(dir)
+                .into_iter()
+                .filter_entry(|e| e.file_name() != "target")
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .map(|e| e.into_path())
+        })
+        .collect();
+    let mut hasher = DefaultHasher::new();
+    for file in files {
+        hasher.write(file.to_string_lossy().as_bytes());
+        hasher.write(&std::fs::read(&file).unwrap());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// The wasm workspace, its members and every crate they depend on by local path
+fn local_crate_dirs(wasms_path: &Path) -> HashSet<PathBuf> {
+    let mut seen = HashSet::new();
+    let mut to_visit = vec![wasms_path.canonicalize().unwrap()];
+    while let Some(dir) = to_visit.pop() {
+        if !seen.insert(dir.clone()) {
+            continue;
+        }
+        let mut table = read_cargo_toml(&dir);
+        if let Some(workspace) = table.remove("workspace") {
+            if let Some(toml::Value::Array(members)) = toml_table(workspace).remove("members") {
+                to_visit.extend(
+                    members
+                        .into_iter()
+                        .map(|m| dir.join(toml_string(m)).canonicalize().unwrap()),
+                );
             }
-            None => {
-                cmd.env("CARGO_TARGET_DIR", format!("{}/target", wasms_path));
+        }
+        // dev dependencies don't go into the wasms
+        for section in &["dependencies", "build-dependencies"] {
+            if let Some(deps) = table.remove(*section) {
+                to_visit.extend(toml_table(deps).into_iter().filter_map(|(_, v)| {
+                    match v {
+                        toml::Value::Table(mut dep) => dep
+                            .remove("path")
+                            .map(|p| dir.join(toml_string(p)).canonicalize().unwrap()),
+                        _ => None,
+                    }
+                }));
             }
         }
-        let output = cmd.output().unwrap();
-        assert!(
-            output.status.success(),
-            std::io::stderr().write_all(&output.stderr)
-        );
     }
+    seen
+}
+
+/// The wasm files which a release build of the wasm workspace should produce
+/// but which aren't in the target dir
+fn missing_artifacts(wasms_path: &str, wasm_out: &Path) -> Vec<PathBuf> {
+    let mut workspace = read_cargo_toml(Path::new(wasms_path));
+    let members = match toml_table(workspace.remove("workspace").unwrap()).remove("members") {
+        Some(toml::Value::Array(members)) => members,
+        _ => Vec::new(),
+    };
+    members
+        .into_iter()
+        .map(|member| {
+            let mut table = read_cargo_toml(&Path::new(wasms_path).join(toml_string(member)));
+            let lib_name = table
+                .remove("lib")
+                .and_then(|lib| toml_table(lib).remove("name"))
+                .map(toml_string);
+            let name = match lib_name {
+                Some(name) => name,
+                None => toml_string(
+                    toml_table(table.remove("package").unwrap())
+                        .remove("name")
+                        .unwrap(),
+                )
+                .replace('-', "_"),
+            };
+            wasm_out
+                .join("wasm32-unknown-unknown/release")
+                .join(format!("{}.wasm", name))
+        })
+        .filter(|path| !path.exists())
+        .collect()
 }
 
 /// Return the list of local path dependencies specified in the Cargo.toml
 fn parse_cargo_toml_local_dependency_paths() -> Vec<String> {
-    let cargo_toml: toml::Value = std::fs::read_to_string("Cargo.toml")
-        .unwrap()
-        .parse()
-        .unwrap();
-    let mut table = toml_table(cargo_toml);
+    let mut table = read_cargo_toml(Path::new("."));
 
     let deps: Vec<_> = match (
         table.remove("dependencies"),
@@ -113,6 +229,15 @@ fn parse_cargo_toml_local_dependency_paths() -> Vec<String> {
         .collect()
 }
 
+/// Parse the Cargo.toml in a crate or workspace dir
+fn read_cargo_toml(dir: &Path) -> toml::value::Table {
+    let cargo_toml: toml::Value = std::fs::read_to_string(dir.join("Cargo.toml"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    toml_table(cargo_toml)
+}
+
 /// Interpret toml Value as a String or panic
 fn toml_string(value: toml::Value) -> String {
     if let toml::Value::String(string) = value {
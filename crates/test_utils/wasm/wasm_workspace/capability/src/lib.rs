@@ -15,6 +15,7 @@ fn init(_: ()) -> ExternResult<InitCallbackResult> {
             // empty access converts to unrestricted
             access: ().into(),
             functions,
+            expires: None,
         }
     )?;
 
@@ -34,6 +35,7 @@ fn cap_grant_entry(secret: CapSecret) -> ExternResult<CapGrantEntry> {
         tag: "".into(),
         access: secret.into(),
         functions,
+        expires: None,
     })
 }
 
@@ -100,6 +102,7 @@ fn send_assigned_cap_claim(agent: AgentPubKey) -> ExternResult<()> {
         access: (secret, agent.clone()).into(),
         functions,
         tag: tag.clone(),
+        expires: None,
     })?;
 
     // send the assigned cap token
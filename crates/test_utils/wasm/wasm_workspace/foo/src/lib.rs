@@ -13,6 +13,7 @@ fn init(_: ()) -> ExternResult<InitCallbackResult> {
             // empty access converts to unrestricted
             access: ().into(),
             functions,
+            expires: None,
         }
     )?;
 
@@ -2,6 +2,11 @@ use hdk3::prelude::*;
 
 entry_defs![Path::entry_def()];
 
+link_types![
+    "follows",
+    LinkTypeDef::ordered("posted_at", LinkTagOrder::Timestamp)
+];
+
 fn path(s: &str) -> ExternResult<EntryHash> {
     let path = Path::from(s);
     path.ensure()?;
@@ -21,11 +26,31 @@ fn create_link(_: ()) -> ExternResult<HeaderHash> {
     Ok(create_link!(base()?, target()?)?)
 }
 
+#[hdk_extern]
+fn create_typed_link(_: ()) -> ExternResult<HeaderHash> {
+    Ok(create_typed_link!(base()?, target()?, "follows")?)
+}
+
+#[hdk_extern]
+fn create_links(_: ()) -> ExternResult<Vec<HeaderHash>> {
+    let (base, target) = (base()?, target()?);
+    Ok(create_links!(vec![
+        CreateLinkInput::new((base.clone(), target.clone(), None, LinkTag::new("one"))),
+        CreateLinkInput::new((base.clone(), target.clone(), None, LinkTag::new("two"))),
+        CreateLinkInput::new((base, target, Some("follows".into()), LinkTag::new("three"))),
+    ])?)
+}
+
 #[hdk_extern]
 fn delete_link(input: DeleteLinkInput) -> ExternResult<HeaderHash> {
     Ok(delete_link!(input.into_inner())?)
 }
 
+#[hdk_extern]
+fn delete_links(input: DeleteLinksInput) -> ExternResult<Vec<HeaderHash>> {
+    Ok(delete_links!(input.into_inner())?)
+}
+
 #[hdk_extern]
 fn get_links(_: ()) -> ExternResult<Links> {
     Ok(get_links!(base()?)?)
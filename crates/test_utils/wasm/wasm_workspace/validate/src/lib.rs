@@ -41,6 +41,8 @@ impl From<&ThisWasmEntry> for EntryDef {
             id: entry.into(),
             crdt_type: entry.into(),
             required_validations: entry.into(),
+            required_validation_type: Default::default(),
+            references: vec![],
             visibility: entry.into(),
         }
     }
@@ -64,8 +66,8 @@ entry_defs![
 ];
 
 #[hdk_extern]
-fn validate(entry: Entry) -> ExternResult<ValidateCallbackResult> {
-    Ok(match ThisWasmEntry::try_from(&entry) {
+fn validate(data: ValidateData) -> ExternResult<ValidateCallbackResult> {
+    Ok(match ThisWasmEntry::try_from(&data.entry) {
         Ok(ThisWasmEntry::AlwaysValidates) => ValidateCallbackResult::Valid,
         Ok(ThisWasmEntry::NeverValidates) => {
             ValidateCallbackResult::Invalid("NeverValidates never validates".to_string())
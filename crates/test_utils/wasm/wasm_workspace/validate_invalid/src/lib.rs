@@ -1,11 +1,11 @@
 use hdk3::prelude::*;
 
 #[hdk_extern]
-fn validate_agent(_: Entry) -> ExternResult<ValidateCallbackResult> {
+fn validate_agent(_: ValidateData) -> ExternResult<ValidateCallbackResult> {
     Ok(ValidateCallbackResult::Valid)
 }
 
 #[hdk_extern]
-fn validate(_: Entry) -> ExternResult<ValidateCallbackResult> {
+fn validate(_: ValidateData) -> ExternResult<ValidateCallbackResult> {
     Ok(ValidateCallbackResult::Invalid("esoteric edge case".into()))
 }
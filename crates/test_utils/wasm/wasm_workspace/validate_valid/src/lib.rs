@@ -1,6 +1,6 @@
 use hdk3::prelude::*;
 
 #[hdk_extern]
-fn validate(_: Entry) -> ExternResult<ValidateCallbackResult> {
+fn validate(_: ValidateData) -> ExternResult<ValidateCallbackResult> {
     Ok(ValidateCallbackResult::Valid)
 }
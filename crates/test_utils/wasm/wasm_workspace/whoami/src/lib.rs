@@ -10,6 +10,7 @@ fn set_access(_: ()) -> ExternResult<()> {
             // empty access converts to unrestricted
             access: ().into(),
             functions,
+            expires: None,
         }
     )?;
 
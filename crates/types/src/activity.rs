@@ -0,0 +1,132 @@
+//! Agent activity: the headers of an agent's source chain, as held by the
+//! authorities for that agent.
+
+use holochain_keystore::{AgentPubKeyExt, KeystoreError, KeystoreSender};
+use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::{
+    element::SignedHeader,
+    observation::{ChainHeadObservation, SignedChainHeadObservation},
+};
+
+/// A range of sequence numbers on a source chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainRange {
+    /// The first header to include
+    pub start_seq: u32,
+    /// The last header to include.
+    /// `None` includes everything up to the chain head.
+    pub end_seq: Option<u32>,
+}
+
+impl ChainRange {
+    /// Whether a header sequence number falls in this range
+    pub fn contains(&self, seq: u32) -> bool {
+        seq >= self.start_seq && self.end_seq.map_or(true, |end| seq <= end)
+    }
+}
+
+/// Response to a request for an agent's activity.
+/// Only the signed headers are sent, so the chain can be audited without
+/// downloading any entries.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct AgentActivityResponse {
+    /// The agent's headers in the requested range, in sequence order
+    pub headers: Vec<SignedHeader>,
+}
+
+/// Response to a request for an observation of an agent's chain head.
+/// An authority which holds none of the agent's headers has nothing to observe.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct ChainHeadResponse {
+    /// The signed observation, if the authority holds any of the chain
+    pub observation: Option<SignedChainHeadObservation>,
+}
+
+/// Sign an observation of an agent's chain head as its observer
+pub async fn sign_observation(
+    observation: ChainHeadObservation,
+    keystore: &KeystoreSender,
+) -> Result<SignedChainHeadObservation, KeystoreError> {
+    let signature = observation
+        .observer
+        .sign(keystore, observation.clone())
+        .await?;
+    Ok(SignedChainHeadObservation {
+        observation,
+        signature,
+    })
+}
+
+/// Check that an observation was signed by its observer, and that the head
+/// it observed was signed by the agent, so it can be held up as evidence
+/// against the agent.
+pub async fn verify_observation(
+    signed: &SignedChainHeadObservation,
+) -> Result<bool, KeystoreError> {
+    let observation = &signed.observation;
+    let SignedHeader(head, head_signature) = &observation.head;
+    Ok(head.author() == &observation.agent
+        && head.author().verify_signature(head_signature, head).await?
+        && observation
+            .observer
+            .verify_signature(&signed.signature, observation.clone())
+            .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_keystore::KeystoreSenderExt;
+    use holochain_zome_types::{
+        header::{Dna, Header},
+        observation::ChainHeadDispute,
+        timestamp::Timestamp,
+    };
+
+    #[tokio::test(threaded_scheduler)]
+    async fn observations_verify_and_forgeries_dont() {
+        let keystore = holochain_keystore::test_keystore::spawn_test_keystore()
+            .await
+            .unwrap();
+        let agent = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let observer = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let head = Header::Dna(Dna {
+            author: agent.clone(),
+            timestamp: Timestamp(0, 0),
+            hash: crate::test_utils::fake_dna_hash(1),
+        });
+        let head_signature = agent.sign(&keystore, &head).await.unwrap();
+        let observation = ChainHeadObservation {
+            agent: agent.clone(),
+            head: SignedHeader(head, head_signature),
+            observer: observer.clone(),
+            observed_at: Timestamp(1, 0),
+        };
+
+        let signed = sign_observation(observation.clone(), &keystore)
+            .await
+            .unwrap();
+        assert!(verify_observation(&signed).await.unwrap());
+        assert_eq!(
+            signed.observation.dispute(&[]),
+            Some(ChainHeadDispute::RolledBack(0))
+        );
+
+        // The observer didn't sign this
+        let mut reattributed = signed.clone();
+        reattributed.observation.observer = agent.clone();
+        assert!(!verify_observation(&reattributed).await.unwrap());
+
+        // The agent didn't sign this head
+        let mut forged_head = observation;
+        forged_head.head.1 = signed.signature.clone();
+        let forged_head = sign_observation(forged_head, &keystore).await.unwrap();
+        assert!(!verify_observation(&forged_head).await.unwrap());
+    }
+}
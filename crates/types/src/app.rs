@@ -1,9 +1,18 @@
 //! Collection of cells to form a holochain application
+
+pub mod app_bundle;
+pub mod app_manifest;
+pub mod error;
+
+pub use app_bundle::AppBundle;
+pub use app_manifest::AppManifest;
+pub use error::{AppBundleError, AppBundleResult};
+
 use crate::{cell::CellId, dna::JsonProperties};
 use derive_more::Into;
 use holo_hash::AgentPubKey;
 use holochain_serialized_bytes::SerializedBytes;
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 /// Placeholder used to identify apps
 pub type AppId = String;
@@ -31,22 +40,54 @@ pub struct InstallAppDnaPayload {
     pub nick: CellNick,
     /// Properties to override when installing this Dna
     pub properties: Option<JsonProperties>,
+    /// A UUID to override when installing this Dna, putting it on its own
+    /// network, separate from every other install of the same Dna
+    #[serde(default)]
+    pub uuid: Option<String>,
     /// App-specific proof-of-membrane-membership, if required by this app
     pub membrane_proof: Option<MembraneProof>,
 }
 
 impl InstallAppDnaPayload {
-    /// Create a payload with no JsonProperties or MembraneProof. Good for tests.
+    /// Create a payload with no JsonProperties, UUID or MembraneProof. Good for tests.
     pub fn path_only(path: PathBuf, nick: CellNick) -> Self {
         Self {
             path,
             nick,
             properties: None,
+            uuid: None,
             membrane_proof: None,
         }
     }
 }
 
+/// Everything needed to install an App from an [AppBundle]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct InstallAppBundlePayload {
+    /// The AppId to install under. Defaults to the name in the manifest.
+    pub app_id: Option<AppId>,
+    /// The agent that installed this app
+    pub agent_key: AgentPubKey,
+    /// Where to get the bundle from
+    pub source: AppBundleSource,
+    /// A UUID to override for every Dna in the bundle, putting the whole
+    /// app on its own networks, i.e. for staging
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// App-specific proofs-of-membrane-membership, keyed by the role's CellNick
+    #[serde(default)]
+    pub membrane_proofs: HashMap<CellNick, MembraneProof>,
+}
+
+/// The source of an [AppBundle] to install
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum AppBundleSource {
+    /// The packed bytes of a bundle, as produced by [AppBundle::pack]
+    Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+    /// A path to a packed bundle on the conductor's filesystem
+    Path(PathBuf),
+}
+
 /// App-specific payload for proving membership in the membrane of the app
 pub type MembraneProof = SerializedBytes;
 
@@ -94,3 +135,81 @@ pub struct InstalledApp {
     /// Cell data for this app
     pub cell_data: Vec<InstalledCell>,
 }
+
+/// Whether an installed app is active
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppStatus {
+    /// The app's Cells are loaded when the conductor starts
+    Active,
+    /// The app is installed but its Cells aren't loaded
+    Inactive,
+}
+
+/// Whether an installed app's Cells are actually running, and why not.
+/// An active app is paused or disabled when its Cells fail to start.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "state", content = "reason", rename_all = "snake_case")]
+pub enum AppRunState {
+    /// All the app's Cells are running
+    Running,
+    /// The app's Cells aren't running yet, and the conductor keeps
+    /// trying to start them
+    Paused(PausedAppReason),
+    /// The app's Cells aren't running, and the conductor won't try to
+    /// start them again on its own
+    Disabled(DisabledAppReason),
+}
+
+/// Why an active app is paused
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PausedAppReason {
+    /// The app's Cells haven't been started yet
+    Starting,
+    /// The keystore is locked. Cells start once it's unlocked.
+    KeystoreLocked,
+    /// Starting a Cell failed in a way which may clear up,
+    /// e.g. the network or keystore couldn't be reached
+    Error(String),
+}
+
+/// Why an app is disabled
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisabledAppReason {
+    /// The app is inactive
+    Deactivated,
+    /// Starting a Cell failed in a way retrying won't fix.
+    /// Activating the app tries again.
+    Error(String),
+}
+
+/// An installed app as listed on the admin interface
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AppInfo {
+    /// The app's id
+    pub app_id: AppId,
+    /// Whether the app is active
+    pub status: AppStatus,
+    /// Whether the app's Cells are running
+    pub run_state: AppRunState,
+    /// The app's Cells
+    pub cells: Vec<CellInfo>,
+}
+
+/// One of an installed app's Cells as listed on the admin interface.
+/// The hashes are also given base64 encoded, for UIs to display.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CellInfo {
+    /// The Cell's nick within the app
+    pub cell_nick: CellNick,
+    /// The Cell's id
+    pub cell_id: CellId,
+    /// The Cell's DnaHash, base64 encoded
+    pub dna_hash_b64: String,
+    /// The Cell's AgentPubKey, base64 encoded
+    pub agent_pub_key_b64: String,
+    /// Whether the Cell is currently running in the conductor
+    pub running: bool,
+}
@@ -0,0 +1,271 @@
+//! An App bundle is an [AppManifest] packed together with the resources
+//! (typically DnaFiles) it references, so that a whole App can be shipped
+//! and installed as a single file.
+
+use super::{
+    app_manifest::{AppManifest, CellProvisioning, DnaLocation},
+    error::{AppBundleError, AppBundleResult},
+    CellNick,
+};
+use crate::dna::DnaFile;
+use holochain_serialized_bytes::prelude::*;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// An [AppManifest] along with the bundled resources it references
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppBundle {
+    manifest: AppManifest,
+    resources: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+/// The on-disk representation of a bundle.
+/// The manifest is kept as YAML so that it round-trips exactly.
+#[derive(Debug, serde::Serialize, serde::Deserialize, SerializedBytes)]
+struct AppBundleWire {
+    manifest: String,
+    resources: BTreeMap<PathBuf, ResourceBytes>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct ResourceBytes(#[serde(with = "serde_bytes")] Vec<u8>);
+
+impl AppBundle {
+    /// Constructor
+    pub fn new(manifest: AppManifest, resources: BTreeMap<PathBuf, Vec<u8>>) -> Self {
+        Self {
+            manifest,
+            resources,
+        }
+    }
+
+    /// Build a bundle from a manifest file on disk. Every role with a
+    /// `bundled` location is read from a path relative to the manifest's
+    /// directory and included in the bundle.
+    pub async fn from_manifest_path(manifest_path: impl AsRef<Path>) -> AppBundleResult<Self> {
+        let manifest_path = manifest_path.as_ref();
+        let yaml = tokio::fs::read_to_string(manifest_path).await?;
+        let manifest = AppManifest::from_yaml(&yaml)?;
+        let base_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+
+        let mut resources = BTreeMap::new();
+        for role in manifest.roles() {
+            if let DnaLocation::Bundled(path) = &role.dna.location {
+                let content = tokio::fs::read(base_dir.join(path)).await?;
+                resources.insert(path.clone(), content);
+            }
+        }
+        Ok(Self::new(manifest, resources))
+    }
+
+    /// The manifest of this bundle
+    pub fn manifest(&self) -> &AppManifest {
+        &self.manifest
+    }
+
+    /// The resources included in this bundle
+    pub fn resources(&self) -> &BTreeMap<PathBuf, Vec<u8>> {
+        &self.resources
+    }
+
+    /// Render this bundle as gzipped bytes to send over the wire, or store in a file.
+    pub async fn pack(&self) -> AppBundleResult<Vec<u8>> {
+        let wire = AppBundleWire {
+            manifest: self.manifest.to_yaml()?,
+            resources: self
+                .resources
+                .iter()
+                .map(|(k, v)| (k.clone(), ResourceBytes(v.clone())))
+                .collect(),
+        };
+        // Not super efficient memory-wise, but doesn't block any threads
+        tokio::task::spawn_blocking(move || {
+            let data: SerializedBytes = wire.try_into()?;
+            let mut enc = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            use std::io::Write;
+            enc.write_all(data.bytes())?;
+            Ok(enc.finish()?)
+        })
+        .await
+        .expect("blocking thread panicked - panicking here too")
+    }
+
+    /// Load a bundle from gzipped bytes produced by [AppBundle::pack].
+    pub async fn unpack(data: &[u8]) -> AppBundleResult<Self> {
+        let data = data.to_vec();
+        let wire: AppBundleWire = tokio::task::spawn_blocking(move || {
+            let mut gz = flate2::read::GzDecoder::new(&data[..]);
+            let mut bytes = Vec::new();
+            use std::io::Read;
+            gz.read_to_end(&mut bytes)?;
+            let sb: SerializedBytes = UnsafeBytes::from(bytes).into();
+            AppBundleResult::Ok(sb.try_into()?)
+        })
+        .await
+        .expect("blocking thread panicked - panicking here too")?;
+        Ok(Self::new(
+            AppManifest::from_yaml(&wire.manifest)?,
+            wire.resources
+                .into_iter()
+                .map(|(k, ResourceBytes(v))| (k, v))
+                .collect(),
+        ))
+    }
+
+    /// Read a packed bundle from a file
+    pub async fn read_from_file(path: impl AsRef<Path>) -> AppBundleResult<Self> {
+        Self::unpack(&tokio::fs::read(path).await?).await
+    }
+
+    /// Pack this bundle and write it to a file
+    pub async fn write_to_file(&self, path: impl AsRef<Path>) -> AppBundleResult<()> {
+        Ok(tokio::fs::write(path, self.pack().await?).await?)
+    }
+
+    /// Produce the DnaFile for every role which should be provisioned at
+    /// install time, with the manifest's version checks and overrides applied.
+    ///
+    /// Roles which are `disabled` are skipped. Only `create` without deferral
+    /// is currently supported; other strategies are an error.
+    pub async fn resolve_cells(&self) -> AppBundleResult<Vec<(CellNick, DnaFile)>> {
+        let mut cells = Vec::new();
+        for role in self.manifest.roles() {
+            match role.provisioning() {
+                CellProvisioning::Disabled => continue,
+                CellProvisioning::Create { deferred: false } => (),
+                other => {
+                    return Err(AppBundleError::UnsupportedProvisioning(
+                        role.id.clone(),
+                        format!("{:?}", other),
+                    ))
+                }
+            }
+
+            let content = match &role.dna.location {
+                DnaLocation::Bundled(path) => self
+                    .resources
+                    .get(path)
+                    .cloned()
+                    .ok_or_else(|| AppBundleError::MissingResource(path.clone()))?,
+                DnaLocation::Path(path) => tokio::fs::read(path).await?,
+                DnaLocation::Url(url) => return Err(AppBundleError::UrlNotSupported(url.clone())),
+            };
+            let mut dna = DnaFile::from_file_content(&content).await?;
+
+            if let Some(version) = &role.dna.version {
+                if !version.matches(dna.dna_hash())? {
+                    return Err(AppBundleError::DnaVersionMismatch(
+                        role.id.clone(),
+                        dna.dna_hash().clone(),
+                    ));
+                }
+            }
+            if let Some(properties) = role.dna.properties.clone() {
+                dna = dna.with_properties(properties.try_into()?).await?;
+            }
+            if let Some(uuid) = role.dna.uuid.clone() {
+                dna = dna.with_uuid(uuid).await?;
+            }
+            cells.push((role.id.clone(), dna));
+        }
+        Ok(cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::app_manifest::{AppManifestV1, AppRoleDnaManifest, AppRoleManifest, DnaVersionSpec},
+        test_utils::fake_dna_file,
+    };
+
+    fn role(id: &str, path: &str, version: Option<DnaVersionSpec>) -> AppRoleManifest {
+        AppRoleManifest {
+            id: id.into(),
+            provisioning: None,
+            dna: AppRoleDnaManifest {
+                location: DnaLocation::Bundled(path.into()),
+                properties: None,
+                uuid: None,
+                version,
+            },
+        }
+    }
+
+    async fn bundle_with_roles(roles: Vec<AppRoleManifest>) -> AppBundle {
+        let dna = fake_dna_file("a");
+        let manifest = AppManifest::V1(AppManifestV1 {
+            name: "test-app".into(),
+            description: None,
+            roles,
+        });
+        let mut resources = BTreeMap::new();
+        resources.insert("a.dna.gz".into(), dna.to_file_content().await.unwrap());
+        AppBundle::new(manifest, resources)
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn pack_unpack_roundtrip() {
+        let bundle = bundle_with_roles(vec![role("a", "a.dna.gz", None)]).await;
+        let packed = bundle.pack().await.unwrap();
+        let unpacked = AppBundle::unpack(&packed).await.unwrap();
+        assert_eq!(bundle, unpacked);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn build_from_manifest_path() {
+        let tmp_dir = tempdir::TempDir::new("app_bundle_test").unwrap();
+        let dna = fake_dna_file("a");
+        tokio::fs::write(
+            tmp_dir.path().join("a.dna.gz"),
+            dna.to_file_content().await.unwrap(),
+        )
+        .await
+        .unwrap();
+        let manifest = AppManifest::V1(AppManifestV1 {
+            name: "test-app".into(),
+            description: None,
+            roles: vec![role("a", "a.dna.gz", None)],
+        });
+        let manifest_path = tmp_dir.path().join("happ.yaml");
+        tokio::fs::write(&manifest_path, manifest.to_yaml().unwrap())
+            .await
+            .unwrap();
+
+        let bundle = AppBundle::from_manifest_path(&manifest_path).await.unwrap();
+        let bundle_path = tmp_dir.path().join("test-app.happ");
+        bundle.write_to_file(&bundle_path).await.unwrap();
+        let read = AppBundle::read_from_file(&bundle_path).await.unwrap();
+
+        let cells = read.resolve_cells().await.unwrap();
+        assert_eq!(cells, vec![("a".to_string(), dna)]);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn resolve_checks_version_and_applies_uuid() {
+        let dna = fake_dna_file("a");
+        let good = DnaVersionSpec::new(vec![dna.dna_hash().clone()]);
+        let bad = DnaVersionSpec::new(vec![fake_dna_file("b").dna_hash().clone()]);
+
+        let mut with_uuid = role("a", "a.dna.gz", Some(good));
+        with_uuid.dna.uuid = Some("b".into());
+        let bundle = bundle_with_roles(vec![with_uuid]).await;
+        let cells = bundle.resolve_cells().await.unwrap();
+        assert_eq!(cells[0].1.dna_hash(), fake_dna_file("b").dna_hash());
+
+        let bundle = bundle_with_roles(vec![role("a", "a.dna.gz", Some(bad))]).await;
+        assert!(matches!(
+            bundle.resolve_cells().await,
+            Err(AppBundleError::DnaVersionMismatch(_, _))
+        ));
+
+        let bundle = bundle_with_roles(vec![role("a", "missing.dna.gz", None)]).await;
+        assert!(matches!(
+            bundle.resolve_cells().await,
+            Err(AppBundleError::MissingResource(_))
+        ));
+    }
+}
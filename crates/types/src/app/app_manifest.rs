@@ -0,0 +1,267 @@
+//! The App Manifest format, i.e. the `happ.yaml` which describes how an app's
+//! Cells should be provisioned.
+//!
+//! A minimal manifest looks like:
+//! ```yaml
+//! manifest_version: "1"
+//! name: my-app
+//! roles:
+//!   - id: chat
+//!     provisioning:
+//!       strategy: create
+//!       deferred: false
+//!     dna:
+//!       bundled: ./chat.dna.gz
+//!       uuid: 00000000-0000-0000-0000-000000000000
+//!       version:
+//!         - uhC0kAAAA...
+//! ```
+
+use super::{
+    error::{AppBundleError, AppBundleResult},
+    CellNick,
+};
+use crate::dna::JsonProperties;
+use holo_hash::DnaHash;
+use std::{convert::TryFrom, path::PathBuf};
+
+/// The manifest for an App, versioned so that the format can evolve
+/// without breaking existing bundles.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "manifest_version")]
+pub enum AppManifest {
+    /// Version 1 of the manifest format
+    #[serde(rename = "1")]
+    V1(AppManifestV1),
+}
+
+/// Version 1 of the App manifest
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AppManifestV1 {
+    /// Name of the App. Used as the default AppId when installing.
+    pub name: String,
+    /// Description of the App, for display purposes only.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// The Cell roles which make up this App, each of which will be
+    /// provisioned as a Cell when the App is installed.
+    pub roles: Vec<AppRoleManifest>,
+}
+
+/// Description of a single role in the App, and the Dna which fills it
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AppRoleManifest {
+    /// The CellNick which will be given to the installed Cell for this role
+    pub id: CellNick,
+    /// How the Cell for this role should be provisioned.
+    /// Defaults to creating the Cell at install time.
+    #[serde(default)]
+    pub provisioning: Option<CellProvisioning>,
+    /// Where to find the Dna, and how to modify it before installing
+    pub dna: AppRoleDnaManifest,
+}
+
+/// The Dna portion of an [AppRoleManifest]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AppRoleDnaManifest {
+    /// Where the DnaFile can be found
+    #[serde(flatten)]
+    pub location: DnaLocation,
+    /// Properties to override the Dna's own properties with
+    #[serde(default)]
+    pub properties: Option<JsonProperties>,
+    /// UUID to override the Dna's own UUID with
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// The set of acceptable Dna versions. If present, the Dna found at
+    /// `location` must match one of these hashes (before any overrides
+    /// are applied).
+    #[serde(default)]
+    pub version: Option<DnaVersionSpec>,
+}
+
+/// Where to find a DnaFile referenced by a manifest
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DnaLocation {
+    /// The DnaFile is included in the bundle's resources at this path
+    Bundled(PathBuf),
+    /// The DnaFile is on the local filesystem at this path
+    Path(PathBuf),
+    /// The DnaFile can be fetched from this Url
+    Url(String),
+}
+
+/// Strategies for provisioning the Cell for a role
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum CellProvisioning {
+    /// Always create a new Cell when installing this App
+    Create {
+        /// Wait to create the Cell until it is first needed
+        #[serde(default)]
+        deferred: bool,
+    },
+    /// Use an existing Cell which matches the Dna version spec,
+    /// failing if none is found
+    UseExisting {
+        /// Wait to look up the Cell until it is first needed
+        #[serde(default)]
+        deferred: bool,
+    },
+    /// Use an existing Cell if one matches, otherwise create a new one
+    CreateIfNotExists {
+        /// Wait to provision the Cell until it is first needed
+        #[serde(default)]
+        deferred: bool,
+    },
+    /// Do not provision a Cell for this role
+    Disabled,
+}
+
+impl Default for CellProvisioning {
+    fn default() -> Self {
+        CellProvisioning::Create { deferred: false }
+    }
+}
+
+/// A list of encoded DnaHashes, any of which is an acceptable version of
+/// the Dna for a role.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct DnaVersionSpec(Vec<String>);
+
+impl DnaVersionSpec {
+    /// Build a spec which accepts exactly these hashes
+    pub fn new(hashes: Vec<DnaHash>) -> Self {
+        Self(hashes.into_iter().map(|h| h.to_string()).collect())
+    }
+
+    /// Decode the hashes of this spec
+    pub fn dna_hashes(&self) -> AppBundleResult<Vec<DnaHash>> {
+        self.0
+            .iter()
+            .map(|s| {
+                DnaHash::try_from(s.as_str())
+                    .map_err(|_| AppBundleError::InvalidDnaVersion(s.clone()))
+            })
+            .collect()
+    }
+
+    /// Check whether a hash is accepted by this spec
+    pub fn matches(&self, hash: &DnaHash) -> AppBundleResult<bool> {
+        Ok(self.dna_hashes()?.contains(hash))
+    }
+}
+
+impl AppManifest {
+    /// Parse a manifest from YAML
+    pub fn from_yaml(yaml: &str) -> AppBundleResult<Self> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    /// Render this manifest as YAML
+    pub fn to_yaml(&self) -> AppBundleResult<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// The name of the App
+    pub fn app_name(&self) -> &str {
+        match self {
+            AppManifest::V1(m) => &m.name,
+        }
+    }
+
+    /// The roles declared by this manifest
+    pub fn roles(&self) -> &[AppRoleManifest] {
+        match self {
+            AppManifest::V1(m) => &m.roles,
+        }
+    }
+}
+
+impl AppRoleManifest {
+    /// The provisioning strategy, falling back to the default
+    pub fn provisioning(&self) -> CellProvisioning {
+        self.provisioning.clone().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fake_dna_file;
+
+    #[test]
+    fn manifest_roundtrip() {
+        let hash = fake_dna_file("a").dna_hash().clone();
+        let manifest = AppManifest::V1(AppManifestV1 {
+            name: "test-app".into(),
+            description: Some("an app for tests".into()),
+            roles: vec![
+                AppRoleManifest {
+                    id: "nick-1".into(),
+                    provisioning: None,
+                    dna: AppRoleDnaManifest {
+                        location: DnaLocation::Bundled("./1.dna.gz".into()),
+                        properties: None,
+                        uuid: Some("uuid".into()),
+                        version: Some(DnaVersionSpec::new(vec![hash.clone()])),
+                    },
+                },
+                AppRoleManifest {
+                    id: "nick-2".into(),
+                    provisioning: Some(CellProvisioning::Disabled),
+                    dna: AppRoleDnaManifest {
+                        location: DnaLocation::Url("https://example.com/2.dna.gz".into()),
+                        properties: None,
+                        uuid: None,
+                        version: None,
+                    },
+                },
+            ],
+        });
+        let yaml = manifest.to_yaml().unwrap();
+        let parsed = AppManifest::from_yaml(&yaml).unwrap();
+        assert_eq!(manifest, parsed);
+        assert!(parsed.roles()[0]
+            .dna
+            .version
+            .as_ref()
+            .unwrap()
+            .matches(&hash)
+            .unwrap());
+    }
+
+    #[test]
+    fn manifest_from_handwritten_yaml() {
+        let yaml = r#"
+manifest_version: "1"
+name: my-app
+roles:
+  - id: chat
+    dna:
+      path: /tmp/chat.dna.gz
+  - id: deferred
+    provisioning:
+      strategy: create
+      deferred: true
+    dna:
+      bundled: ./deferred.dna.gz
+"#;
+        let manifest = AppManifest::from_yaml(yaml).unwrap();
+        assert_eq!(manifest.app_name(), "my-app");
+        assert_eq!(
+            manifest.roles()[0].provisioning(),
+            CellProvisioning::Create { deferred: false }
+        );
+        assert_eq!(
+            manifest.roles()[0].dna.location,
+            DnaLocation::Path("/tmp/chat.dna.gz".into())
+        );
+        assert_eq!(
+            manifest.roles()[1].provisioning(),
+            CellProvisioning::Create { deferred: true }
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Errors occurring while reading, building or resolving App bundles
+
+use super::CellNick;
+use holo_hash::DnaHash;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors occurring while working with an [AppManifest] or [AppBundle]
+#[derive(Debug, Error)]
+pub enum AppBundleError {
+    /// The manifest could not be parsed or rendered
+    #[error("Invalid app manifest: {0}")]
+    ManifestError(#[from] serde_yaml::Error),
+
+    /// A bundled resource referenced by the manifest is missing
+    #[error("The bundle has no resource at path: {0:?}")]
+    MissingResource(PathBuf),
+
+    /// The Dna for a role does not match any hash in its version spec
+    #[error(
+        "The Dna for role {0} has hash {1}, which does not satisfy the manifest's version spec"
+    )]
+    DnaVersionMismatch(CellNick, DnaHash),
+
+    /// A hash in a version spec could not be decoded
+    #[error("Invalid DnaHash in version spec: {0}")]
+    InvalidDnaVersion(String),
+
+    /// The provisioning strategy for a role is not yet supported
+    #[error("The provisioning strategy for role {0} is not supported: {1}")]
+    UnsupportedProvisioning(CellNick, String),
+
+    /// Fetching a Dna from a Url is not yet supported
+    #[error("Fetching Dnas from Urls is not supported: {0}")]
+    UrlNotSupported(String),
+
+    /// DnaError
+    #[error(transparent)]
+    DnaError(#[from] crate::dna::DnaError),
+
+    /// SerializedBytesError
+    #[error(transparent)]
+    SerializedBytesError(#[from] holochain_serialized_bytes::SerializedBytesError),
+
+    /// std::io::Error
+    #[error("IO error: {0}")]
+    StdIoError(#[from] std::io::Error),
+}
+
+/// Result type for App bundle operations
+pub type AppBundleResult<T> = Result<T, AppBundleError>;
@@ -1,7 +1,8 @@
 //! Holochain autonomic type helpers.
 
 /// The various processes which run "autonomically", aka subconsciously.
-/// These are currently not used.
+/// Each Cell runs them on the schedule in the conductor config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum AutonomicProcess {
     /// Validation / Correction may propagate much slower.
     SlowHeal,
@@ -9,18 +10,31 @@ pub enum AutonomicProcess {
     /// See how many validators we can find on the network for all of our entries
     /// Push out new hold requests if the health is too low.
     HealthCheck,
+
+    /// Drop stale entries from in-memory caches, such as expired cap grants.
+    PruneCaches,
+
+    /// Resize the Cell's storage arc to reach its nearest neighbors,
+    /// so it holds more as neighbors leave and less as they join.
+    ResizeArc,
 }
 
 /// A cue that the autonomic system should perform one of its functions now,
 /// rather than at the next scheduled time
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum AutonomicCue {
+    /// Run a process now. The scheduler cues each process as its interval
+    /// comes round, and anything else may cue one early.
+    Run(AutonomicProcess),
     // /// Cue sent when it is known that entries are ready for initial publishing,
-// /// i.e. after committing new entries to your source chain
-// Publish(Address),
+    // /// i.e. after committing new entries to your source chain
+    // Publish(Address),
 }
 
 impl From<AutonomicCue> for AutonomicProcess {
     fn from(cue: AutonomicCue) -> AutonomicProcess {
-        match cue {}
+        match cue {
+            AutonomicCue::Run(process) => process,
+        }
     }
 }
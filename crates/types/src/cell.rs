@@ -38,3 +38,25 @@ impl From<(DnaHash, AgentPubKey)> for CellId {
         Self(pair.0, pair.1)
     }
 }
+
+/// A Cell running in the conductor as listed on the admin interface.
+/// The hashes are also given base64 encoded, for UIs to display.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CellIdInfo {
+    /// The Cell's id
+    pub cell_id: CellId,
+    /// The Cell's DnaHash, base64 encoded
+    pub dna_hash_b64: String,
+    /// The Cell's AgentPubKey, base64 encoded
+    pub agent_pub_key_b64: String,
+}
+
+impl From<CellId> for CellIdInfo {
+    fn from(cell_id: CellId) -> Self {
+        Self {
+            dna_hash_b64: cell_id.dna_hash().to_string(),
+            agent_pub_key_b64: cell_id.agent_pubkey().to_string(),
+            cell_id,
+        }
+    }
+}
@@ -98,6 +98,20 @@ pub enum DhtOpLight {
     RegisterRemoveLink(HeaderHash, DhtBasis),
 }
 
+/// The type of a [DhtOp], without any of its data
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[allow(missing_docs)]
+pub enum DhtOpType {
+    StoreElement,
+    StoreEntry,
+    RegisterAgentActivity,
+    RegisterUpdatedBy,
+    RegisterDeletedBy,
+    RegisterDeletedEntryHeader,
+    RegisterAddLink,
+    RegisterRemoveLink,
+}
+
 impl DhtOp {
     fn as_unique_form(&self) -> UniqueForm<'_> {
         match self {
@@ -178,6 +192,20 @@ impl DhtOp {
             | DhtOp::RegisterRemoveLink(s, _) => s,
         }
     }
+
+    /// Get the header this op is about, which its signature is over
+    pub fn header(&self) -> Header {
+        match self {
+            DhtOp::StoreElement(_, h, _) | DhtOp::RegisterAgentActivity(_, h) => h.clone(),
+            DhtOp::StoreEntry(_, h, _) => h.clone().into(),
+            DhtOp::RegisterUpdatedBy(_, h) => h.clone().into(),
+            DhtOp::RegisterDeletedBy(_, h) | DhtOp::RegisterDeletedEntryHeader(_, h) => {
+                h.clone().into()
+            }
+            DhtOp::RegisterAddLink(_, h) => h.clone().into(),
+            DhtOp::RegisterRemoveLink(_, h) => h.clone().into(),
+        }
+    }
 }
 
 impl DhtOpLight {
@@ -207,6 +235,40 @@ impl DhtOpLight {
             | DhtOpLight::RegisterRemoveLink(h, _) => h,
         }
     }
+
+    /// Get the type of this op
+    pub fn get_type(&self) -> DhtOpType {
+        match self {
+            DhtOpLight::StoreElement(_, _, _) => DhtOpType::StoreElement,
+            DhtOpLight::StoreEntry(_, _, _) => DhtOpType::StoreEntry,
+            DhtOpLight::RegisterAgentActivity(_, _) => DhtOpType::RegisterAgentActivity,
+            DhtOpLight::RegisterUpdatedBy(_, _, _) => DhtOpType::RegisterUpdatedBy,
+            DhtOpLight::RegisterDeletedBy(_, _) => DhtOpType::RegisterDeletedBy,
+            DhtOpLight::RegisterDeletedEntryHeader(_, _) => DhtOpType::RegisterDeletedEntryHeader,
+            DhtOpLight::RegisterAddLink(_, _) => DhtOpType::RegisterAddLink,
+            DhtOpLight::RegisterRemoveLink(_, _) => DhtOpType::RegisterRemoveLink,
+        }
+    }
+}
+
+/// How many ops of each type an authority holds in some part of the DHT
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct DhtOpCounts {
+    /// The number of ops held of each type.
+    /// Types with no ops held are left out.
+    pub counts: std::collections::HashMap<DhtOpType, u64>,
+}
+
+impl DhtOpCounts {
+    /// Count one more op of this type
+    pub fn add(&mut self, op_type: DhtOpType) {
+        *self.counts.entry(op_type).or_default() += 1;
+    }
+
+    /// The number of ops held of all types
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
 }
 
 // FIXME: need to use this in HashableContent
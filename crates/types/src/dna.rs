@@ -3,11 +3,13 @@
 //! It includes utilities for representing dna structures in memory,
 //! as well as serializing and deserializing dna, mainly to json format.
 
+pub mod dna_manifest;
 pub mod error;
 pub mod wasm;
 pub mod zome;
 use crate::prelude::*;
 use derive_more::From;
+pub use dna_manifest::DnaManifest;
 pub use error::DnaError;
 use holo_hash::impl_hashable_content;
 pub use holo_hash::*;
@@ -18,7 +20,7 @@ use std::collections::BTreeMap;
 pub type Zomes = Vec<(ZomeName, zome::Zome)>;
 
 /// A type to allow json values to be used as [SerializedBytes]
-#[derive(Debug, Clone, From, serde::Serialize, serde::Deserialize, SerializedBytes)]
+#[derive(Debug, Clone, PartialEq, From, serde::Serialize, serde::Deserialize, SerializedBytes)]
 pub struct JsonProperties(serde_json::Value);
 
 impl JsonProperties {
@@ -144,6 +146,27 @@ impl DnaFile {
         DnaFile::new(dna, wasm).await
     }
 
+    /// Transform this DnaFile into a new DnaFile with whichever of the UUID
+    /// and properties are given, hashing it only once.
+    /// If neither is given the DnaFile is returned as it is.
+    pub async fn with_overrides(
+        self,
+        uuid: Option<String>,
+        properties: Option<SerializedBytes>,
+    ) -> Result<Self, DnaError> {
+        if uuid.is_none() && properties.is_none() {
+            return Ok(self);
+        }
+        let (mut dna, wasm): (DnaDef, Vec<wasm::DnaWasm>) = self.into();
+        if let Some(uuid) = uuid {
+            dna.uuid = uuid;
+        }
+        if let Some(properties) = properties {
+            dna.properties = properties;
+        }
+        DnaFile::new(dna, wasm).await
+    }
+
     /// The hashable portion that can be shared with hApp code.
     pub fn dna(&self) -> &DnaDef {
         &self.dna
@@ -188,3 +211,36 @@ impl std::fmt::Debug for DnaFile {
         f.write_fmt(format_args!("DnaFile(dna_hash = {})", self.dna_hash))
     }
 }
+
+/// An installed Dna as listed on the admin interface.
+/// The hash is also given base64 encoded, for UIs to display.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DnaInfo {
+    /// The Dna's hash
+    pub dna_hash: DnaHash,
+    /// The Dna's hash, base64 encoded
+    pub dna_hash_b64: String,
+    /// The friendly name of the Dna
+    pub name: String,
+    /// The Dna's UUID
+    pub uuid: String,
+    /// The names of the Dna's zomes, in order
+    pub zome_names: Vec<ZomeName>,
+}
+
+impl From<&DnaFile> for DnaInfo {
+    fn from(dna_file: &DnaFile) -> Self {
+        Self {
+            dna_hash: dna_file.dna_hash().clone(),
+            dna_hash_b64: dna_file.dna_hash().to_string(),
+            name: dna_file.dna().name.clone(),
+            uuid: dna_file.dna().uuid.clone(),
+            zome_names: dna_file
+                .dna()
+                .zomes
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+        }
+    }
+}
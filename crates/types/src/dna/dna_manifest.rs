@@ -0,0 +1,240 @@
+//! The Dna Manifest format, i.e. the `dna.yaml` which describes how to build
+//! a [DnaFile] from a set of wasms on disk.
+//!
+//! ```yaml
+//! manifest_version: "1"
+//! name: my-dna
+//! uuid: 00000000-0000-0000-0000-000000000000
+//! properties:
+//!   foo: bar
+//! zomes:
+//!   - name: zome1
+//!     path: ./zome1.wasm
+//! ```
+
+use super::{error::DnaError, wasm::DnaWasm, zome::Zome, DnaDef, DnaFile, JsonProperties};
+use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::zome::ZomeName;
+use std::path::{Path, PathBuf};
+
+/// The filename a manifest is written to when unpacking a DnaFile
+pub const DNA_MANIFEST_FILENAME: &str = "dna.yaml";
+
+/// The manifest for a Dna, versioned so that the format can evolve
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "manifest_version")]
+pub enum DnaManifest {
+    /// Version 1 of the manifest format
+    #[serde(rename = "1")]
+    V1(DnaManifestV1),
+}
+
+/// Version 1 of the Dna manifest
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DnaManifestV1 {
+    /// The friendly "name" of the Dna
+    pub name: String,
+    /// A UUID for uniquifying this Dna. Defaults to the empty string.
+    #[serde(default)]
+    pub uuid: Option<String>,
+    /// Any arbitrary application properties can be included in this object.
+    #[serde(default)]
+    pub properties: Option<JsonProperties>,
+    /// The zomes of this Dna, in order
+    pub zomes: Vec<ZomeManifest>,
+}
+
+/// A zome in a [DnaManifest]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ZomeManifest {
+    /// The name of the zome
+    pub name: ZomeName,
+    /// Path to the zome's wasm, relative to the manifest
+    pub path: PathBuf,
+}
+
+impl DnaManifest {
+    /// Constructor for the current manifest version
+    pub fn new(
+        name: String,
+        uuid: Option<String>,
+        properties: Option<JsonProperties>,
+        zomes: Vec<ZomeManifest>,
+    ) -> Self {
+        DnaManifest::V1(DnaManifestV1 {
+            name,
+            uuid,
+            properties,
+            zomes,
+        })
+    }
+
+    /// Parse a manifest from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self, DnaError> {
+        serde_yaml::from_str(yaml).map_err(|e| DnaError::ManifestError(e.to_string()))
+    }
+
+    /// Render this manifest as YAML
+    pub fn to_yaml(&self) -> Result<String, DnaError> {
+        serde_yaml::to_string(self).map_err(|e| DnaError::ManifestError(e.to_string()))
+    }
+
+    /// Read and parse a manifest file
+    pub async fn read_from_file(path: impl AsRef<Path>) -> Result<Self, DnaError> {
+        Self::from_yaml(&tokio::fs::read_to_string(path).await?)
+    }
+
+    /// Build a DnaFile from a manifest file, reading wasms relative
+    /// to the manifest's directory.
+    pub async fn build_from_path(path: impl AsRef<Path>) -> Result<DnaFile, DnaError> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::read_from_file(path)
+            .await?
+            .build_dna_file(base_dir)
+            .await
+    }
+
+    /// Read the wasms referenced by this manifest, relative to `base_dir`,
+    /// hash them, and produce the DnaFile.
+    pub async fn build_dna_file(&self, base_dir: impl AsRef<Path>) -> Result<DnaFile, DnaError> {
+        let DnaManifest::V1(manifest) = self;
+        let base_dir = base_dir.as_ref();
+
+        let mut zomes = Vec::new();
+        let mut wasms = Vec::new();
+        for zome in manifest.zomes.iter() {
+            let wasm: DnaWasm = tokio::fs::read(base_dir.join(&zome.path)).await?.into();
+            let wasm_hash = holo_hash::WasmHash::with_data(&wasm).await;
+            zomes.push((zome.name.clone(), Zome { wasm_hash }));
+            wasms.push(wasm);
+        }
+
+        let properties = manifest
+            .properties
+            .clone()
+            .unwrap_or_else(|| JsonProperties::new(serde_json::Value::Null));
+
+        let dna = DnaDef {
+            name: manifest.name.clone(),
+            uuid: manifest.uuid.clone().unwrap_or_default(),
+            properties: properties.try_into()?,
+            zomes,
+        };
+        DnaFile::new(dna, wasms).await
+    }
+
+    /// The reverse of [DnaManifest::build_dna_file]: write each zome's wasm
+    /// and a manifest describing them into `dir`, returning the manifest.
+    pub async fn unpack(dna_file: &DnaFile, dir: impl AsRef<Path>) -> Result<Self, DnaError> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir).await?;
+
+        let mut zomes = Vec::new();
+        for (zome_name, _) in dna_file.dna().zomes.iter() {
+            let wasm = dna_file.get_wasm_for_zome(zome_name)?;
+            let path = wasm_file_name(zome_name)?;
+            tokio::fs::write(dir.join(&path), &*wasm.code()).await?;
+            zomes.push(ZomeManifest {
+                name: zome_name.clone(),
+                path,
+            });
+        }
+
+        let properties: JsonProperties = dna_file.dna().properties.clone().try_into()?;
+        let properties = if properties == JsonProperties::new(serde_json::Value::Null) {
+            None
+        } else {
+            Some(properties)
+        };
+        let uuid = Some(dna_file.dna().uuid.clone()).filter(|uuid| !uuid.is_empty());
+
+        let manifest = Self::new(dna_file.dna().name.clone(), uuid, properties, zomes);
+        tokio::fs::write(dir.join(DNA_MANIFEST_FILENAME), manifest.to_yaml()?).await?;
+        Ok(manifest)
+    }
+}
+
+/// The file a zome's wasm is unpacked to. The zome name comes from the
+/// DnaFile, so names which could write outside the directory are refused.
+fn wasm_file_name(zome_name: &ZomeName) -> Result<PathBuf, DnaError> {
+    let name = zome_name.to_string();
+    if name.is_empty() || name.contains('/') || name.contains('\\') || name.contains("..") {
+        return Err(DnaError::ManifestError(format!(
+            "zome name {:?} can't be used as a file name",
+            name
+        )));
+    }
+    Ok(PathBuf::from(format!("{}.wasm", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fake_dna_zomes;
+
+    #[tokio::test(threaded_scheduler)]
+    async fn unpack_then_build() {
+        let tmp_dir = tempdir::TempDir::new("dna_manifest_test").unwrap();
+        let dna_file = fake_dna_zomes(
+            "uuid",
+            vec![
+                ("zome-1".into(), vec![1, 2, 3, 4].into()),
+                ("zome-2".into(), vec![5, 6, 7, 8].into()),
+            ],
+        );
+
+        let manifest = DnaManifest::unpack(&dna_file, tmp_dir.path())
+            .await
+            .unwrap();
+        let DnaManifest::V1(v1) = &manifest;
+        assert_eq!(v1.uuid, Some("uuid".to_string()));
+        assert_eq!(v1.zomes.len(), 2);
+
+        let built = DnaManifest::build_from_path(tmp_dir.path().join(DNA_MANIFEST_FILENAME))
+            .await
+            .unwrap();
+        assert_eq!(built, dna_file);
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn unpack_refuses_zome_names_that_are_paths() {
+        let tmp_dir = tempdir::TempDir::new("dna_manifest_test").unwrap();
+        for name in vec!["../escape", "a/b", "a\\b", ".."] {
+            let dna_file = fake_dna_zomes("uuid", vec![(name.into(), vec![1, 2, 3].into())]);
+            let result = DnaManifest::unpack(&dna_file, tmp_dir.path().join("dna")).await;
+            assert!(
+                matches!(result, Err(DnaError::ManifestError(_))),
+                "{} was unpacked",
+                name
+            );
+        }
+        assert!(!tmp_dir.path().join("escape.wasm").exists());
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn build_from_handwritten_yaml() {
+        let tmp_dir = tempdir::TempDir::new("dna_manifest_test").unwrap();
+        tokio::fs::write(tmp_dir.path().join("a.wasm"), vec![1, 2, 3])
+            .await
+            .unwrap();
+        let yaml = r#"
+manifest_version: "1"
+name: my-dna
+zomes:
+  - name: a
+    path: ./a.wasm
+"#;
+        let dna_file = DnaManifest::from_yaml(yaml)
+            .unwrap()
+            .build_dna_file(tmp_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(dna_file.dna().name, "my-dna");
+        assert_eq!(dna_file.dna().uuid, "");
+        assert_eq!(
+            dna_file.get_wasm_for_zome(&"a".into()).unwrap().code(),
+            std::sync::Arc::new(vec![1, 2, 3])
+        );
+    }
+}
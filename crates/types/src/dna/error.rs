@@ -37,6 +37,10 @@ pub enum DnaError {
     /// InvalidWasmHash
     #[error("InvalidWasmHash")]
     InvalidWasmHash,
+
+    /// The dna manifest could not be parsed or rendered
+    #[error("Invalid dna manifest: {0}")]
+    ManifestError(String),
 }
 
 impl From<std::io::Error> for DnaError {
@@ -8,6 +8,7 @@
 use holo_hash::*;
 use holochain_zome_types::element::ElementEntry;
 pub use holochain_zome_types::entry::Entry;
+use std::collections::BTreeMap;
 
 /// An Entry paired with its EntryHash
 pub type EntryHashed = HoloHashed<Entry>;
@@ -20,3 +21,103 @@ pub async fn option_entry_hashed(entry: ElementEntry) -> Option<EntryHashed> {
         _ => None,
     }
 }
+
+/// The value of a field which may hold references.
+/// Hash types serialize differently so each is tried in turn.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ReferenceField {
+    Entry(EntryHash),
+    Header(HeaderHash),
+    AnyDht(AnyDhtHash),
+    Entries(Vec<EntryHash>),
+    Headers(Vec<HeaderHash>),
+    AnyDhts(Vec<AnyDhtHash>),
+    Other(serde::de::IgnoredAny),
+}
+
+/// The hashes held by the named fields of an app entry, in field order,
+/// as declared by the `references` of the entry's def.
+/// A field can hold one hash or a list of them. Fields holding anything
+/// else, and entries which aren't structs with named fields, have none.
+pub fn entry_references(entry: &Entry, fields: &[String]) -> Vec<AnyDhtHash> {
+    let bytes = match entry {
+        Entry::App(bytes) if !fields.is_empty() => bytes,
+        _ => return Vec::new(),
+    };
+    let mut values: BTreeMap<String, ReferenceField> =
+        match holochain_serialized_bytes::decode(bytes.as_ref().bytes()) {
+            Ok(values) => values,
+            Err(_) => return Vec::new(),
+        };
+    let mut references = Vec::new();
+    for field in fields {
+        match values.remove(field) {
+            Some(ReferenceField::Entry(hash)) => references.push(hash.into()),
+            Some(ReferenceField::Header(hash)) => references.push(hash.into()),
+            Some(ReferenceField::AnyDht(hash)) => references.push(hash),
+            Some(ReferenceField::Entries(hashes)) => {
+                references.extend(hashes.into_iter().map(AnyDhtHash::from))
+            }
+            Some(ReferenceField::Headers(hashes)) => {
+                references.extend(hashes.into_iter().map(AnyDhtHash::from))
+            }
+            Some(ReferenceField::AnyDhts(hashes)) => references.extend(hashes),
+            Some(ReferenceField::Other(_)) | None => (),
+        }
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::fixt::prelude::*;
+    use holo_hash::fixt::{EntryHashFixturator, HeaderHashFixturator};
+    use holochain_serialized_bytes::prelude::*;
+    use holochain_zome_types::entry::AppEntryBytes;
+
+    #[derive(Serialize, Deserialize, SerializedBytes, Debug)]
+    struct Comment {
+        post: EntryHash,
+        replies_to: Option<HeaderHash>,
+        mentions: Vec<EntryHash>,
+        text: String,
+    }
+
+    fn app_entry<T: TryInto<SerializedBytes, Error = SerializedBytesError>>(t: T) -> Entry {
+        Entry::App(AppEntryBytes::try_from(t.try_into().unwrap()).unwrap())
+    }
+
+    #[test]
+    fn entry_references_test() {
+        let post = fixt!(EntryHash);
+        let replies_to = fixt!(HeaderHash);
+        let mentions = vec![fixt!(EntryHash), fixt!(EntryHash)];
+        let entry = app_entry(Comment {
+            post: post.clone(),
+            replies_to: Some(replies_to.clone()),
+            mentions: mentions.clone(),
+            text: "hi".into(),
+        });
+        let fields = |f: &[&str]| f.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            entry_references(&entry, &fields(&["replies_to", "post", "mentions"])),
+            vec![
+                replies_to.into(),
+                post.clone().into(),
+                mentions[0].clone().into(),
+                mentions[1].clone().into(),
+            ]
+        );
+        // Fields which aren't hashes, or aren't there, are skipped
+        assert_eq!(
+            entry_references(&entry, &fields(&["text", "missing", "post"])),
+            vec![AnyDhtHash::from(post)]
+        );
+        assert!(entry_references(&entry, &[]).is_empty());
+        // Entries which aren't structs have no fields
+        assert!(entry_references(&app_entry(5u32), &fields(&["post"])).is_empty());
+    }
+}
@@ -5,11 +5,20 @@
 #![allow(missing_docs)]
 
 use crate::cell::CellId;
+use crate::dht_op::{produce_ops_from_element, DhtOp};
 use crate::dna::zome::Zome;
 use crate::dna::zome::{HostFnAccess, Permission};
 use crate::dna::DnaDef;
 use crate::dna::Zomes;
-use crate::header::NewEntryHeader;
+use crate::element::{
+    CollapsedGetEntryResponse, Element, GetElementResponse, RawGetEntryResponse,
+    SignedHeaderHashed, SignedHeaderHashedExt, WireElement,
+};
+use crate::header::{
+    NewEntryHeader, WireCreate, WireDelete, WireNewEntryHeader, WireUpdate, WireUpdateRelationship,
+};
+use crate::link::GetLinksResponse;
+use crate::metadata::{MetadataSet, TimedHeaderHash};
 use crate::Timestamp;
 use ::fixt::prelude::*;
 use holo_hash::fixt::AgentPubKeyFixturator;
@@ -18,6 +27,9 @@ use holo_hash::fixt::EntryHashFixturator;
 use holo_hash::fixt::HeaderHashFixturator;
 use holo_hash::fixt::WasmHashFixturator;
 use holo_hash::AgentPubKey;
+use holo_hash::EntryHash;
+use holo_hash::HeaderHash;
+use holochain_error_kind::ErrorKind;
 use holochain_keystore::Signature;
 use holochain_serialized_bytes::SerializedBytes;
 use holochain_zome_types::capability::CapAccess;
@@ -30,6 +42,7 @@ use holochain_zome_types::capability::GrantedFunctions;
 use holochain_zome_types::capability::ZomeCallCapGrant;
 use holochain_zome_types::capability::CAP_SECRET_BYTES;
 use holochain_zome_types::crdt::CrdtType;
+use holochain_zome_types::element::SignedHeader;
 use holochain_zome_types::entry::AppEntryBytes;
 use holochain_zome_types::entry_def::EntryDef;
 use holochain_zome_types::entry_def::EntryDefId;
@@ -56,6 +69,7 @@ use rand::seq::IteratorRandom;
 use rand::thread_rng;
 use rand::Rng;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::iter::Iterator;
 
@@ -573,3 +587,288 @@ fixturator!(
     CellId;
     constructor fn new(DnaHash, AgentPubKey);
 );
+
+fixturator!(
+    Element;
+    vanilla fn element(Header, Signature, Entry);
+);
+
+/// An element which only has an entry if its header creates one,
+/// in which case the header points at it
+fn element(header: Header, signature: Signature, entry: Entry) -> Element {
+    let (header, maybe_entry) = match header {
+        Header::Create(mut create) => {
+            create.entry_hash = EntryHash::with_data_sync(&entry);
+            (Header::Create(create), Some(entry))
+        }
+        Header::Update(mut update) => {
+            update.entry_hash = EntryHash::with_data_sync(&entry);
+            (Header::Update(update), Some(entry))
+        }
+        header => (header, None),
+    };
+    Element::new(
+        SignedHeaderHashed::from_content_sync(SignedHeader(header, signature)),
+        maybe_entry,
+    )
+}
+
+fixturator!(
+    WireElement;
+    vanilla fn wire_element(Element);
+);
+
+fn wire_element(element: Element) -> WireElement {
+    WireElement::from_element(element, None)
+}
+
+fixturator!(
+    DhtOp;
+    curve Empty {
+        let element = ElementFixturator::new_indexed(Empty, self.0.index).next().unwrap();
+        dht_ops(&element).into_iter().choose(&mut thread_rng()).unwrap()
+    };
+    curve Unpredictable {
+        let element = ElementFixturator::new_indexed(Unpredictable, self.0.index).next().unwrap();
+        dht_ops(&element).into_iter().choose(&mut thread_rng()).unwrap()
+    };
+    curve Predictable {
+        let element = ElementFixturator::new_indexed(Predictable, self.0.index).next().unwrap();
+        let ops = dht_ops(&element);
+        ops[self.0.index % ops.len()].clone()
+    };
+);
+
+/// Every op an element produces, so the ops are as realistic as the element.
+/// Every element produces at least a StoreElement op.
+fn dht_ops(element: &Element) -> Vec<DhtOp> {
+    tokio_safe_block_on::tokio_safe_block_forever_on(produce_ops_from_element(element)).unwrap()
+}
+
+fixturator!(
+    WireCreate;
+    vanilla fn wire_create(Create, Signature);
+);
+
+fn wire_create(create: Create, signature: Signature) -> WireCreate {
+    (create, signature).into()
+}
+
+fixturator!(
+    WireUpdate;
+    vanilla fn wire_update(Update, Signature);
+);
+
+fn wire_update(update: Update, signature: Signature) -> WireUpdate {
+    (update, signature).into()
+}
+
+fixturator!(
+    WireNewEntryHeader;
+    variants [
+        Create(WireCreate)
+        Update(WireUpdate)
+    ];
+);
+
+fixturator!(
+    WireDelete;
+    vanilla fn wire_delete(Delete, Signature);
+);
+
+fn wire_delete(delete: Delete, signature: Signature) -> WireDelete {
+    WireDelete { delete, signature }
+}
+
+fixturator!(
+    WireUpdateRelationship;
+    vanilla fn wire_update_relationship(Update, Signature);
+);
+
+fn wire_update_relationship(update: Update, signature: Signature) -> WireUpdateRelationship {
+    WireUpdateRelationship {
+        timestamp: update.timestamp,
+        author: update.author,
+        header_seq: update.header_seq,
+        prev_header: update.prev_header,
+        original_header_address: update.original_header_address,
+        new_entry_address: update.entry_hash,
+        new_entry_type: update.entry_type,
+        signature,
+    }
+}
+
+fixturator!(
+    RawGetEntryResponse;
+    curve Empty RawGetEntryResponse {
+        live_headers: BTreeSet::new(),
+        deletes: Vec::new(),
+        updates: Vec::new(),
+        entry: EntryFixturator::new_indexed(Empty, self.0.index).next().unwrap(),
+        entry_type: EntryTypeFixturator::new_indexed(Empty, self.0.index).next().unwrap(),
+        receipts: Vec::new(),
+    };
+    curve Unpredictable {
+        let mut rng = rand::thread_rng();
+        RawGetEntryResponse {
+            live_headers: WireNewEntryHeaderFixturator::new(Unpredictable)
+                .take(rng.gen_range(1, 5))
+                .collect(),
+            deletes: WireDeleteFixturator::new(Unpredictable)
+                .take(rng.gen_range(0, 3))
+                .collect(),
+            updates: WireUpdateRelationshipFixturator::new(Unpredictable)
+                .take(rng.gen_range(0, 3))
+                .collect(),
+            entry: EntryFixturator::new(Unpredictable).next().unwrap(),
+            entry_type: EntryTypeFixturator::new(Unpredictable).next().unwrap(),
+            receipts: Vec::new(),
+        }
+    };
+    curve Predictable RawGetEntryResponse {
+        live_headers: WireNewEntryHeaderFixturator::new_indexed(Predictable, self.0.index)
+            .take(self.0.index % 3 + 1)
+            .collect(),
+        deletes: WireDeleteFixturator::new_indexed(Predictable, self.0.index)
+            .take(self.0.index % 3)
+            .collect(),
+        updates: WireUpdateRelationshipFixturator::new_indexed(Predictable, self.0.index)
+            .take(self.0.index % 3)
+            .collect(),
+        entry: EntryFixturator::new_indexed(Predictable, self.0.index).next().unwrap(),
+        entry_type: EntryTypeFixturator::new_indexed(Predictable, self.0.index).next().unwrap(),
+        receipts: Vec::new(),
+    };
+);
+
+fixturator!(
+    CollapsedGetEntryResponse;
+    curve Empty RawGetEntryResponseFixturator::new_indexed(Empty, self.0.index)
+        .next()
+        .unwrap()
+        .into();
+    curve Unpredictable fixt!(RawGetEntryResponse).into();
+    curve Predictable RawGetEntryResponseFixturator::new_indexed(Predictable, self.0.index)
+        .next()
+        .unwrap()
+        .into();
+);
+
+fixturator!(
+    GetElementResponse;
+    enum [ GetEntryFull GetEntryPartial GetEntryCollapsed GetHeader Error ];
+    curve Empty GetElementResponse::GetEntryFull(None);
+    curve Unpredictable match GetElementResponseVariant::random() {
+        GetElementResponseVariant::GetEntryFull => GetElementResponse::GetEntryFull(
+            Some(Box::new(fixt!(RawGetEntryResponse)))
+        ),
+        GetElementResponseVariant::GetEntryPartial => GetElementResponse::GetEntryPartial,
+        GetElementResponseVariant::GetEntryCollapsed => GetElementResponse::GetEntryCollapsed(
+            Some(Box::new(fixt!(CollapsedGetEntryResponse)))
+        ),
+        GetElementResponseVariant::GetHeader => GetElementResponse::GetHeader(
+            Some(Box::new(fixt!(WireElement)))
+        ),
+        GetElementResponseVariant::Error => GetElementResponse::Error(ErrorKind::Internal),
+    };
+    curve Predictable match GetElementResponseVariant::nth(self.0.index) {
+        GetElementResponseVariant::GetEntryFull => GetElementResponse::GetEntryFull(
+            Some(Box::new(RawGetEntryResponseFixturator::new_indexed(Predictable, self.0.index).next().unwrap()))
+        ),
+        GetElementResponseVariant::GetEntryPartial => GetElementResponse::GetEntryPartial,
+        GetElementResponseVariant::GetEntryCollapsed => GetElementResponse::GetEntryCollapsed(
+            Some(Box::new(CollapsedGetEntryResponseFixturator::new_indexed(Predictable, self.0.index).next().unwrap()))
+        ),
+        GetElementResponseVariant::GetHeader => GetElementResponse::GetHeader(
+            Some(Box::new(WireElementFixturator::new_indexed(Predictable, self.0.index).next().unwrap()))
+        ),
+        GetElementResponseVariant::Error => GetElementResponse::Error(ErrorKind::Storage),
+    };
+);
+
+fixturator!(
+    GetLinksResponse;
+    curve Empty GetLinksResponse {
+        link_adds: Vec::new(),
+        link_removes: Vec::new(),
+        continuation: None,
+    };
+    curve Unpredictable {
+        let mut rng = rand::thread_rng();
+        GetLinksResponse {
+            link_adds: CreateLinkFixturator::new(Unpredictable)
+                .zip(SignatureFixturator::new(Unpredictable))
+                .take(rng.gen_range(0, 5))
+                .collect(),
+            link_removes: DeleteLinkFixturator::new(Unpredictable)
+                .zip(SignatureFixturator::new(Unpredictable))
+                .take(rng.gen_range(0, 5))
+                .collect(),
+            continuation: None,
+        }
+    };
+    curve Predictable GetLinksResponse {
+        link_adds: CreateLinkFixturator::new_indexed(Predictable, self.0.index)
+            .zip(SignatureFixturator::new_indexed(Predictable, self.0.index))
+            .take(self.0.index % 3 + 1)
+            .collect(),
+        link_removes: DeleteLinkFixturator::new_indexed(Predictable, self.0.index)
+            .zip(SignatureFixturator::new_indexed(Predictable, self.0.index))
+            .take(self.0.index % 3)
+            .collect(),
+        continuation: None,
+    };
+);
+
+fixturator!(
+    TimedHeaderHash;
+    vanilla fn timed_header_hash(Timestamp, HeaderHash);
+);
+
+fn timed_header_hash(timestamp: Timestamp, header_hash: HeaderHash) -> TimedHeaderHash {
+    TimedHeaderHash {
+        timestamp,
+        header_hash,
+    }
+}
+
+fixturator!(
+    MetadataSet;
+    curve Empty MetadataSet {
+        headers: BTreeSet::new(),
+        invalid_headers: BTreeSet::new(),
+        deletes: BTreeSet::new(),
+        updates: BTreeSet::new(),
+        entry_dht_status: None,
+    };
+    curve Unpredictable {
+        let mut rng = rand::thread_rng();
+        let mut timed = TimedHeaderHashFixturator::new(Unpredictable);
+        MetadataSet {
+            headers: timed.by_ref().take(rng.gen_range(0, 5)).collect(),
+            invalid_headers: timed.by_ref().take(rng.gen_range(0, 5)).collect(),
+            deletes: timed.by_ref().take(rng.gen_range(0, 5)).collect(),
+            updates: timed.by_ref().take(rng.gen_range(0, 5)).collect(),
+            entry_dht_status: if rng.gen() {
+                Some(EntryDhtStatusFixturator::new(Unpredictable).next().unwrap())
+            } else {
+                None
+            },
+        }
+    };
+    curve Predictable {
+        let n = self.0.index % 3;
+        let mut timed = TimedHeaderHashFixturator::new_indexed(Predictable, self.0.index);
+        MetadataSet {
+            headers: timed.by_ref().take(n + 1).collect(),
+            invalid_headers: timed.by_ref().take(n).collect(),
+            deletes: timed.by_ref().take(n).collect(),
+            updates: timed.by_ref().take(n).collect(),
+            entry_dht_status: Some(
+                EntryDhtStatusFixturator::new_indexed(Predictable, self.0.index)
+                    .next()
+                    .unwrap(),
+            ),
+        }
+    };
+);
@@ -171,6 +171,19 @@ impl From<(Update, Signature)> for WireUpdate {
     }
 }
 
+/// The minimum unique data for Delete headers
+/// that share a common entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, SerializedBytes)]
+pub struct WireDeleteOnEntry {
+    pub timestamp: holochain_zome_types::timestamp::Timestamp,
+    pub author: AgentPubKey,
+    pub header_seq: u32,
+    pub prev_header: HeaderHash,
+    /// Address of the header being deleted
+    pub deletes_address: HeaderHash,
+    pub signature: Signature,
+}
+
 impl WireDelete {
     pub async fn into_element(self) -> Element {
         Element::new(
@@ -180,6 +193,36 @@ impl WireDelete {
     }
 }
 
+impl WireDeleteOnEntry {
+    /// Recreate the full Delete using the entry it was sent with
+    pub fn into_wire_delete(self, deletes_entry_address: EntryHash) -> WireDelete {
+        WireDelete {
+            delete: Delete {
+                author: self.author,
+                timestamp: self.timestamp,
+                header_seq: self.header_seq,
+                prev_header: self.prev_header,
+                deletes_address: self.deletes_address,
+                deletes_entry_address,
+            },
+            signature: self.signature,
+        }
+    }
+}
+
+impl From<WireDelete> for WireDeleteOnEntry {
+    fn from(wd: WireDelete) -> Self {
+        Self {
+            timestamp: wd.delete.timestamp,
+            author: wd.delete.author,
+            header_seq: wd.delete.header_seq,
+            prev_header: wd.delete.prev_header,
+            deletes_address: wd.delete.deletes_address,
+            signature: wd.signature,
+        }
+    }
+}
+
 impl WireUpdateRelationship {
     /// Recreate the Update Element without an Entry.
     /// Useful for creating dht ops
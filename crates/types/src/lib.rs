@@ -4,6 +4,7 @@
 
 #![deny(missing_docs)]
 
+pub mod activity;
 pub mod app;
 pub mod autonomic;
 pub mod cell;
@@ -17,8 +18,11 @@ pub mod header;
 pub mod link;
 pub mod metadata;
 pub mod prelude;
+#[cfg(any(test, feature = "proptest"))]
+pub mod prop;
 pub mod timestamp;
 pub mod validate;
+pub mod validation_receipt;
 
 /// Placeholders to allow other things to compile
 #[allow(missing_docs)]
@@ -1,5 +1,6 @@
 //! Links interrelate entries in a source chain.
 
+use crate::metadata::TimedHeaderHash;
 use holo_hash::{AnyDhtHash, EntryHash, HeaderHash};
 use holochain_keystore::Signature;
 use holochain_serialized_bytes::prelude::*;
@@ -30,6 +31,10 @@ pub enum WireLinkMetaKey {
     Full(EntryHash, ZomeId, LinkTag, HeaderHash),
 }
 
+/// The most link add and remove headers an authority puts in one
+/// [GetLinksResponse]. Any links after those are left for the next page.
+pub const MAX_GET_LINKS_RESPONSE_HEADERS: usize = 512;
+
 // TODO: Probably don't want to send the whole headers.
 // We could probably come up with a more compact
 // network Wire type in the future
@@ -40,6 +45,11 @@ pub struct GetLinksResponse {
     pub link_adds: Vec<(CreateLink, Signature)>,
     /// All the link removes on the key you searched for
     pub link_removes: Vec<(DeleteLink, Signature)>,
+    /// Set when the authority holds more links than fit in one response,
+    /// to the last link add in this one. Ask again for the links after it
+    /// to get the next page.
+    #[serde(default)]
+    pub continuation: Option<TimedHeaderHash>,
 }
 
 impl WireLinkMetaKey {
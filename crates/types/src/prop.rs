@@ -0,0 +1,371 @@
+//! Proptest strategies for headers, entries and DhtOps.
+//!
+//! Unlike the fixturators, these generate adversarial values: arbitrary hash
+//! bytes, out of order timestamps, zero and max sequence numbers, oversized
+//! tags and entries, and headers paired with entries of the wrong type.
+//! Enable the `proptest` feature to use them from other crates.
+
+#![allow(missing_docs)]
+
+use crate::{dht_op::DhtOp, header::NewEntryHeader};
+use holo_hash::{hash_type, AgentPubKey, AnyDhtHash, DhtOpHash, DnaHash, EntryHash, HeaderHash};
+use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::{
+    capability::{CapAccess, CapClaim, CapSecret, ZomeCallCapGrant, CAP_SECRET_BYTES},
+    entry::AppEntryBytes,
+    entry_def::EntryVisibility,
+    header::{self, AppEntryType, EntryType},
+    link::LinkTag,
+    signature::Signature,
+    timestamp::Timestamp,
+    Entry, Header,
+};
+use proptest::{collection::vec, prelude::*};
+use std::convert::TryFrom;
+
+/// Bytes for a hash, including the 4 byte location suffix
+pub fn hash_bytes() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 36)
+}
+
+pub fn agent_pub_key() -> impl Strategy<Value = AgentPubKey> {
+    hash_bytes().prop_map(AgentPubKey::from_raw_bytes)
+}
+
+pub fn dna_hash() -> impl Strategy<Value = DnaHash> {
+    hash_bytes().prop_map(DnaHash::from_raw_bytes)
+}
+
+pub fn entry_hash() -> impl Strategy<Value = EntryHash> {
+    hash_bytes().prop_map(EntryHash::from_raw_bytes)
+}
+
+pub fn header_hash() -> impl Strategy<Value = HeaderHash> {
+    hash_bytes().prop_map(HeaderHash::from_raw_bytes)
+}
+
+pub fn dht_op_hash() -> impl Strategy<Value = DhtOpHash> {
+    hash_bytes().prop_map(DhtOpHash::from_raw_bytes)
+}
+
+pub fn any_dht_hash() -> impl Strategy<Value = AnyDhtHash> {
+    prop_oneof![
+        hash_bytes().prop_map(|b| AnyDhtHash::from_raw_bytes_and_type(b, hash_type::AnyDht::Entry)),
+        hash_bytes()
+            .prop_map(|b| AnyDhtHash::from_raw_bytes_and_type(b, hash_type::AnyDht::Header)),
+    ]
+}
+
+/// Any valid timestamp, including ones before the epoch
+pub fn timestamp() -> impl Strategy<Value = Timestamp> {
+    (any::<i64>(), 0..1_000_000_000u32).prop_map(|(secs, nanos)| Timestamp(secs, nanos))
+}
+
+pub fn signature() -> impl Strategy<Value = Signature> {
+    vec(any::<u8>(), 64).prop_map(Signature)
+}
+
+/// Tags up to twice the maximum size sys validation allows
+pub fn link_tag() -> impl Strategy<Value = LinkTag> {
+    vec(any::<u8>(), 0..2048).prop_map(LinkTag)
+}
+
+pub fn cap_secret() -> impl Strategy<Value = CapSecret> {
+    vec(any::<u8>(), CAP_SECRET_BYTES).prop_map(|bytes| {
+        let mut inner = [0; CAP_SECRET_BYTES];
+        inner.copy_from_slice(&bytes);
+        CapSecret::from(inner)
+    })
+}
+
+pub fn entry_visibility() -> impl Strategy<Value = EntryVisibility> {
+    prop_oneof![
+        Just(EntryVisibility::Public),
+        Just(EntryVisibility::Private)
+    ]
+}
+
+pub fn entry_type() -> impl Strategy<Value = EntryType> {
+    prop_oneof![
+        Just(EntryType::AgentPubKey),
+        (any::<u8>(), any::<u8>(), entry_visibility()).prop_map(|(id, zome_id, visibility)| {
+            EntryType::App(AppEntryType::new(id.into(), zome_id.into(), visibility))
+        }),
+        Just(EntryType::CapClaim),
+        Just(EntryType::CapGrant),
+    ]
+}
+
+/// App entries are arbitrary bytes, so they needn't be valid msgpack
+pub fn app_entry_bytes() -> impl Strategy<Value = AppEntryBytes> {
+    vec(any::<u8>(), 0..4096).prop_map(|bytes| {
+        AppEntryBytes::try_from(SerializedBytes::from(UnsafeBytes::from(bytes)))
+            .expect("well under the entry size limit")
+    })
+}
+
+pub fn entry() -> impl Strategy<Value = Entry> {
+    prop_oneof![
+        agent_pub_key().prop_map(Entry::Agent),
+        app_entry_bytes().prop_map(Entry::App),
+        (".{0,32}", agent_pub_key(), cap_secret()).prop_map(|(tag, grantor, secret)| {
+            Entry::CapClaim(CapClaim::new(tag, grantor, secret))
+        }),
+        ".{0,32}".prop_map(|tag| Entry::CapGrant(ZomeCallCapGrant::new(
+            tag,
+            CapAccess::Unrestricted,
+            Default::default()
+        ))),
+    ]
+}
+
+/// The fields every header but [Header::Dna] starts with
+fn common() -> impl Strategy<Value = (AgentPubKey, Timestamp, u32, HeaderHash)> {
+    (
+        agent_pub_key(),
+        timestamp(),
+        prop_oneof![Just(0), Just(u32::MAX), any::<u32>()],
+        header_hash(),
+    )
+}
+
+pub fn create() -> impl Strategy<Value = header::Create> {
+    (common(), entry_type(), entry_hash()).prop_map(
+        |((author, timestamp, header_seq, prev_header), entry_type, entry_hash)| header::Create {
+            author,
+            timestamp,
+            header_seq,
+            prev_header,
+            entry_type,
+            entry_hash,
+        },
+    )
+}
+
+pub fn update() -> impl Strategy<Value = header::Update> {
+    (
+        common(),
+        header_hash(),
+        entry_hash(),
+        entry_type(),
+        entry_hash(),
+    )
+        .prop_map(
+            |(
+                (author, timestamp, header_seq, prev_header),
+                original_header_address,
+                original_entry_address,
+                entry_type,
+                entry_hash,
+            )| header::Update {
+                author,
+                timestamp,
+                header_seq,
+                prev_header,
+                original_header_address,
+                original_entry_address,
+                entry_type,
+                entry_hash,
+            },
+        )
+}
+
+pub fn delete() -> impl Strategy<Value = header::Delete> {
+    (common(), header_hash(), entry_hash()).prop_map(
+        |((author, timestamp, header_seq, prev_header), deletes_address, deletes_entry_address)| {
+            header::Delete {
+                author,
+                timestamp,
+                header_seq,
+                prev_header,
+                deletes_address,
+                deletes_entry_address,
+            }
+        },
+    )
+}
+
+pub fn create_link() -> impl Strategy<Value = header::CreateLink> {
+    (
+        common(),
+        entry_hash(),
+        entry_hash(),
+        any::<u8>(),
+        proptest::option::of(any::<u8>()),
+        link_tag(),
+    )
+        .prop_map(
+            |(
+                (author, timestamp, header_seq, prev_header),
+                base_address,
+                target_address,
+                zome_id,
+                link_type,
+                tag,
+            )| header::CreateLink {
+                author,
+                timestamp,
+                header_seq,
+                prev_header,
+                base_address,
+                target_address,
+                zome_id: zome_id.into(),
+                link_type: link_type.map(Into::into),
+                tag,
+            },
+        )
+}
+
+pub fn delete_link() -> impl Strategy<Value = header::DeleteLink> {
+    (common(), entry_hash(), header_hash()).prop_map(
+        |((author, timestamp, header_seq, prev_header), base_address, link_add_address)| {
+            header::DeleteLink {
+                author,
+                timestamp,
+                header_seq,
+                prev_header,
+                base_address,
+                link_add_address,
+            }
+        },
+    )
+}
+
+pub fn new_entry_header() -> impl Strategy<Value = NewEntryHeader> {
+    prop_oneof![
+        create().prop_map(NewEntryHeader::Create),
+        update().prop_map(NewEntryHeader::Update),
+    ]
+}
+
+/// Every kind of header, with no attempt to make them valid
+pub fn any_header() -> impl Strategy<Value = Header> {
+    prop_oneof![
+        (agent_pub_key(), timestamp(), dna_hash()).prop_map(|(author, timestamp, hash)| {
+            Header::Dna(header::Dna {
+                author,
+                timestamp,
+                hash,
+            })
+        }),
+        (common(), proptest::option::of(vec(any::<u8>(), 0..256))).prop_map(
+            |((author, timestamp, header_seq, prev_header), membrane_proof)| {
+                Header::AgentValidationPkg(header::AgentValidationPkg {
+                    author,
+                    timestamp,
+                    header_seq,
+                    prev_header,
+                    membrane_proof: membrane_proof
+                        .map(|bytes| SerializedBytes::from(UnsafeBytes::from(bytes))),
+                })
+            }
+        ),
+        common().prop_map(|(author, timestamp, header_seq, prev_header)| {
+            Header::InitZomesComplete(header::InitZomesComplete {
+                author,
+                timestamp,
+                header_seq,
+                prev_header,
+            })
+        }),
+        create_link().prop_map(Header::CreateLink),
+        delete_link().prop_map(Header::DeleteLink),
+        (common(), dna_hash()).prop_map(
+            |((author, timestamp, header_seq, prev_header), prev_dna_hash)| {
+                Header::OpenChain(header::OpenChain {
+                    author,
+                    timestamp,
+                    header_seq,
+                    prev_header,
+                    prev_dna_hash,
+                })
+            }
+        ),
+        (common(), dna_hash()).prop_map(
+            |((author, timestamp, header_seq, prev_header), new_dna_hash)| {
+                Header::CloseChain(header::CloseChain {
+                    author,
+                    timestamp,
+                    header_seq,
+                    prev_header,
+                    new_dna_hash,
+                })
+            }
+        ),
+        create().prop_map(Header::Create),
+        update().prop_map(Header::Update),
+        delete().prop_map(Header::Delete),
+    ]
+}
+
+/// Every kind of op. Headers and entries are generated independently, so an
+/// op's entry rarely matches its header.
+pub fn dht_op() -> impl Strategy<Value = DhtOp> {
+    prop_oneof![
+        (signature(), any_header(), proptest::option::of(entry()))
+            .prop_map(|(s, h, e)| DhtOp::StoreElement(s, h, e.map(Box::new))),
+        (signature(), new_entry_header(), entry()).prop_map(|(s, h, e)| DhtOp::StoreEntry(
+            s,
+            h,
+            Box::new(e)
+        )),
+        (signature(), any_header()).prop_map(|(s, h)| DhtOp::RegisterAgentActivity(s, h)),
+        (signature(), update()).prop_map(|(s, h)| DhtOp::RegisterUpdatedBy(s, h)),
+        (signature(), delete()).prop_map(|(s, h)| DhtOp::RegisterDeletedBy(s, h)),
+        (signature(), delete()).prop_map(|(s, h)| DhtOp::RegisterDeletedEntryHeader(s, h)),
+        (signature(), create_link()).prop_map(|(s, h)| DhtOp::RegisterAddLink(s, h)),
+        (signature(), delete_link()).prop_map(|(s, h)| DhtOp::RegisterRemoveLink(s, h)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeaderHashed;
+    use holo_hash::HasHash;
+    use std::convert::TryInto;
+
+    proptest! {
+        #[test]
+        fn header_round_trip(header in any_header()) {
+            let hash = HeaderHashed::from_content_sync(header.clone()).into_hash();
+            let sb = SerializedBytes::try_from(header.clone()).unwrap();
+            let decoded: Header = sb.try_into().unwrap();
+            prop_assert_eq!(&decoded, &header);
+            prop_assert_eq!(HeaderHashed::from_content_sync(decoded).into_hash(), hash);
+        }
+
+        #[test]
+        fn entry_round_trip(entry in entry()) {
+            let hash = EntryHash::with_data_sync(&entry);
+            let sb = SerializedBytes::try_from(entry.clone()).unwrap();
+            let decoded: Entry = sb.try_into().unwrap();
+            prop_assert_eq!(&decoded, &entry);
+            prop_assert_eq!(EntryHash::with_data_sync(&decoded), hash);
+        }
+
+        #[test]
+        fn dht_op_round_trip(op in dht_op()) {
+            let sb = SerializedBytes::try_from(op.clone()).unwrap();
+            let decoded: DhtOp = sb.try_into().unwrap();
+            prop_assert_eq!(decoded, op);
+        }
+
+        #[test]
+        fn arbitrary_bytes_never_panic(bytes in vec(any::<u8>(), 0..1024)) {
+            let sb = || SerializedBytes::from(UnsafeBytes::from(bytes.clone()));
+            let _ = Header::try_from(sb());
+            let _ = Entry::try_from(sb());
+            let _ = DhtOp::try_from(sb());
+        }
+
+        /// Flipping any one byte of a valid encoding must not panic either
+        #[test]
+        fn corrupted_op_never_panics(op in dht_op(), index in any::<prop::sample::Index>(), flip in 1..=255u8) {
+            let mut bytes: Vec<u8> = UnsafeBytes::from(SerializedBytes::try_from(op).unwrap()).into();
+            let i = index.index(bytes.len());
+            bytes[i] ^= flip;
+            let _ = DhtOp::try_from(SerializedBytes::from(UnsafeBytes::from(bytes)));
+        }
+    }
+}
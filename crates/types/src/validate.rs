@@ -1,6 +1,9 @@
 //! the _host_ types used to track the status/result of validating entries
 //! c.f. _guest_ types for validation callbacks and packages across the wasm boudary in zome_types
 
+use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::validate::ValidationPackage;
+
 /// the validation status for an op
 /// much of this happens in the subconscious
 /// an entry missing validation dependencies may cycle through Pending many times before finally
@@ -15,3 +18,8 @@ pub enum ValidationStatus {
     /// commonly due to missing validation dependencies remaining missing for "too long"
     Abandoned,
 }
+
+/// Response to a request for the validation package of one of an author's headers.
+/// `None` if the author doesn't have the header.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct ValidationPackageResponse(pub Option<ValidationPackage>);
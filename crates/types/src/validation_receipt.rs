@@ -0,0 +1,209 @@
+//! Signed receipts from validators, saying that they validated an op.
+//!
+//! Validators send receipts to the author of an op, so the author knows when
+//! enough of them hold it, and authorities include them in their responses
+//! to gets, so the requester can count how many authorities vouch for the
+//! data before treating it as live.
+
+use crate::dht_op::UniqueForm;
+use crate::header::{NewEntryHeader, WireNewEntryHeader};
+use holo_hash::{AgentPubKey, DhtOpHash, EntryHash, HeaderHash};
+use holochain_keystore::{AgentPubKeyExt, KeystoreError, KeystoreSender, Signature};
+use holochain_serialized_bytes::prelude::*;
+use holochain_zome_types::{header::EntryType, Header};
+use std::{collections::BTreeSet, convert::TryFrom};
+
+/// The result of a DhtOp Validation.
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(tag = "type")]
+pub enum ValidationResult {
+    /// Successful validation.
+    Valid,
+    // TODO - fill out with additional options, which may (or may not) have content
+    // Abandoned { .. },
+    // Warrant { .. },
+}
+
+/// Validation receipt content - to be signed.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    SerializedBytes,
+)]
+pub struct ValidationReceipt {
+    /// the op this validation receipt is for.
+    pub dht_op_hash: DhtOpHash,
+
+    /// the result of this validation.
+    pub validation_result: ValidationResult,
+
+    /// the remote validator which is signing this receipt.
+    pub validator: AgentPubKey,
+}
+
+impl ValidationReceipt {
+    /// Sign this validation receipt.
+    pub async fn sign(
+        self,
+        keystore: &KeystoreSender,
+    ) -> Result<SignedValidationReceipt, KeystoreError> {
+        let signature = self.validator.sign(keystore, self.clone()).await?;
+        Ok(SignedValidationReceipt {
+            receipt: self,
+            validator_signature: signature,
+        })
+    }
+}
+
+/// A full, signed validation receipt.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    serde::Serialize,
+    serde::Deserialize,
+    SerializedBytes,
+)]
+pub struct SignedValidationReceipt {
+    /// the content of the validation receipt.
+    pub receipt: ValidationReceipt,
+
+    /// the signature of the remote validator.
+    pub validator_signature: Signature,
+}
+
+impl SignedValidationReceipt {
+    /// Check that the validator really signed this receipt.
+    pub async fn verify(&self) -> Result<bool, KeystoreError> {
+        self.receipt
+            .validator
+            .verify_signature(&self.validator_signature, self.receipt.clone())
+            .await
+    }
+}
+
+/// The validators which vouch for an op with a correctly signed receipt
+/// saying it's valid. A validator with several receipts is counted once.
+pub async fn vouching_validators<'a, I>(
+    receipts: I,
+    dht_op_hash: &DhtOpHash,
+) -> BTreeSet<AgentPubKey>
+where
+    I: IntoIterator<Item = &'a SignedValidationReceipt>,
+{
+    let mut validators = BTreeSet::new();
+    for r in receipts {
+        if r.receipt.dht_op_hash != *dht_op_hash
+            || r.receipt.validation_result != ValidationResult::Valid
+            || validators.contains(&r.receipt.validator)
+        {
+            continue;
+        }
+        if r.verify().await.unwrap_or(false) {
+            validators.insert(r.receipt.validator.clone());
+        }
+    }
+    validators
+}
+
+/// The op an entry authority vouches for when it returns a header for the
+/// entry: the header's StoreEntry op
+pub fn store_entry_op_hash(header: &NewEntryHeader) -> DhtOpHash {
+    DhtOpHash::with_data_sync(&UniqueForm::StoreEntry(header))
+}
+
+/// The live headers of a get entry response, each paired with the
+/// StoreEntry op the authority's receipt for it is for
+pub async fn live_store_entry_ops(
+    live_headers: &BTreeSet<WireNewEntryHeader>,
+    entry_type: &EntryType,
+    entry_hash: &EntryHash,
+) -> Vec<(HeaderHash, DhtOpHash)> {
+    let mut ops = Vec::with_capacity(live_headers.len());
+    for header in live_headers {
+        let shh = header
+            .clone()
+            .into_header(entry_type.clone(), entry_hash.clone())
+            .await;
+        let header_hash = shh.header_address().clone();
+        // Wire headers always render as a Create or an Update
+        if let Ok(header) = NewEntryHeader::try_from(shh.header().clone()) {
+            ops.push((header_hash, store_entry_op_hash(&header)));
+        }
+    }
+    ops
+}
+
+/// The op a header authority vouches for when it returns the header's
+/// element: the header's StoreElement op
+pub fn store_element_op_hash(header: &Header) -> DhtOpHash {
+    DhtOpHash::with_data_sync(&UniqueForm::StoreElement(header))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use holochain_keystore::KeystoreSenderExt;
+
+    async fn receipt(
+        keystore: &KeystoreSender,
+        validator: &AgentPubKey,
+        dht_op_hash: &DhtOpHash,
+    ) -> SignedValidationReceipt {
+        ValidationReceipt {
+            dht_op_hash: dht_op_hash.clone(),
+            validation_result: ValidationResult::Valid,
+            validator: validator.clone(),
+        }
+        .sign(keystore)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test(threaded_scheduler)]
+    async fn counts_each_validator_with_a_good_receipt_once() {
+        let keystore = holochain_keystore::test_keystore::spawn_test_keystore()
+            .await
+            .unwrap();
+        let alice = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let bob = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let carol = keystore
+            .generate_sign_keypair_from_pure_entropy()
+            .await
+            .unwrap();
+        let op = crate::test_utils::fake_dht_op_hash(1);
+        let other_op = crate::test_utils::fake_dht_op_hash(2);
+
+        let mut forged = receipt(&keystore, &carol, &op).await;
+        forged.validator_signature = receipt(&keystore, &bob, &op).await.validator_signature;
+
+        let receipts = vec![
+            receipt(&keystore, &alice, &op).await,
+            receipt(&keystore, &alice, &op).await,
+            receipt(&keystore, &bob, &op).await,
+            receipt(&keystore, &carol, &other_op).await,
+            forged,
+        ];
+        let validators = vouching_validators(&receipts, &op).await;
+        assert_eq!(validators, vec![alice, bob].into_iter().collect());
+    }
+}
@@ -1,4 +1,5 @@
 use super::CapSecret;
+use crate::timestamp::Timestamp;
 use crate::zome::FunctionName;
 use crate::zome::ZomeName;
 use holo_hash::*;
@@ -53,6 +54,10 @@ pub struct ZomeCallCapGrant {
     pub access: CapAccess,
     /// Set of functions to which this capability grants ZomeCall access
     pub functions: GrantedFunctions,
+    /// When this capability stops granting access, for short-lived access tokens.
+    /// A grant without an expiry lasts until it's updated or deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<Timestamp>,
     // @todo the payloads to curry to the functions
     // pub curry_payloads: CurryPayloads,
 }
@@ -69,9 +74,21 @@ impl ZomeCallCapGrant {
             tag,
             access,
             functions,
+            expires: None,
             // @todo curry_payloads,
         }
     }
+
+    /// Stop granting access at this time
+    pub fn expiring_at(mut self, expires: Timestamp) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    /// Whether this grant has stopped granting access by this time
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        self.expires.map_or(false, |expires| expires <= now)
+    }
 }
 
 impl From<ZomeCallCapGrant> for CapGrant {
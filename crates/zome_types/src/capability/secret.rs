@@ -1,6 +1,7 @@
 use holochain_serialized_bytes::prelude::*;
 use serde::de::Error;
 use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
 
 /// The number of bits we want for a comfy secret.
 pub const CAP_SECRET_BITS: usize = 512;
@@ -71,6 +72,22 @@ impl PartialEq for CapSecret {
 
 impl Eq for CapSecret {}
 
+/// Hashing is consistent with the constant time PartialEq, so secrets can be
+/// used to look up grants.
+impl std::hash::Hash for CapSecret {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+/// CapSecret is Copy so it can't zero itself on drop, but holders of a
+/// secret can wipe it once it's no longer needed.
+impl Zeroize for CapSecret {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 impl std::fmt::Debug for CapSecret {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Debug::fmt(&self.0.to_vec(), f)
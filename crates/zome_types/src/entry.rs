@@ -29,9 +29,36 @@ pub type CapGrantEntry = ZomeCallCapGrant;
 /// The data type written to the source chain to denote a capability claim
 pub type CapClaimEntry = CapClaim;
 
-#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
-/// @todo make some options for get
-pub struct GetOptions;
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize)]
+/// @todo make some more options for get
+pub struct GetOptions {
+    /// How many levels of references get_details resolves, see [GetOptions::follow_references]
+    #[serde(default)]
+    pub follow_references: u8,
+    /// How many authorities must vouch for data, see [GetOptions::min_validations]
+    #[serde(default)]
+    pub min_validations: u8,
+}
+
+impl GetOptions {
+    /// Have get_details also return the elements which the entry references, and the elements
+    /// which those reference, up to `depth` levels. References are the hashes held in the
+    /// fields named by the entry def's `references`. They are resolved by the host in the
+    /// same call, saving a round trip for each.
+    pub fn follow_references(mut self, depth: u8) -> Self {
+        self.follow_references = depth;
+        self
+    }
+
+    /// Only return data as live once at least `count` authorities have vouched for it with a
+    /// signed validation receipt. The authorities include their receipts in their responses,
+    /// so this costs no extra round trips, but data which hasn't yet been validated by enough
+    /// authorities is returned as missing.
+    pub fn min_validations(mut self, count: u8) -> Self {
+        self.min_validations = count;
+        self
+    }
+}
 
 /// Structure holding the entry portion of a chain element.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, SerializedBytes)]
@@ -1,4 +1,6 @@
 use crate::crdt::CrdtType;
+use crate::validate::RequiredValidationType;
+use crate::zome::ZomeName;
 use crate::zome_io::ExternOutput;
 use crate::CallbackResult;
 use holochain_serialized_bytes::prelude::*;
@@ -74,6 +76,13 @@ pub struct EntryDef {
     pub crdt_type: CrdtType,
     /// how many validations to receive before considered "network saturated" (MAX value of 50?)
     pub required_validations: RequiredValidations,
+    /// How much of the author's chain validators need to validate an entry
+    #[serde(default)]
+    pub required_validation_type: RequiredValidationType,
+    /// Names of the entry's fields which hold the hash of another entry or header,
+    /// or a list of them, for get_details to follow
+    #[serde(default)]
+    pub references: Vec<String>,
 }
 
 impl EntryDef {
@@ -82,12 +91,16 @@ impl EntryDef {
         visibility: EntryVisibility,
         crdt_type: CrdtType,
         required_validations: RequiredValidations,
+        required_validation_type: RequiredValidationType,
+        references: Vec<String>,
     ) -> Self {
         Self {
             id,
             visibility,
             crdt_type,
             required_validations,
+            required_validation_type,
+            references,
         }
     }
 }
@@ -101,6 +114,12 @@ impl EntryDefs {
             .iter()
             .position(|entry_def| entry_def.id == entry_def_id)
     }
+
+    /// The entry defs in the order the zome declared them,
+    /// which is the order of their EntryDefIndex
+    pub fn iter(&self) -> std::slice::Iter<'_, EntryDef> {
+        self.0.iter()
+    }
 }
 
 impl std::ops::Index<usize> for EntryDefs {
@@ -124,6 +143,18 @@ impl From<Vec<EntryDef>> for EntryDefs {
     }
 }
 
+/// Which zomes to look up the registered entry defs of at runtime,
+/// so generic code can adapt to the entry types a DNA declares
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EntryDefsQuery {
+    /// The zome making the call
+    ThisZome,
+    /// Another zome in the same DNA
+    Zome(ZomeName),
+    /// Every zome in the DNA
+    AllZomes,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
 pub enum EntryDefsCallbackResult {
     Defs(EntryDefs),
@@ -172,6 +203,8 @@ mod tests {
                 visibility: EntryVisibility::Public,
                 crdt_type: CrdtType,
                 required_validations: 5.into(),
+                required_validation_type: Default::default(),
+                references: vec![],
             }]
             .into(),
         );
@@ -7,6 +7,7 @@ use holochain_serialized_bytes::prelude::SerializedBytes;
 use crate::entry_def::EntryVisibility;
 use crate::header::*;
 use crate::link::LinkTag;
+use crate::metadata::EntryDhtStatus;
 use crate::timestamp::Timestamp;
 
 pub use holo_hash::fixt::*;
@@ -126,15 +127,41 @@ fixturator!(
     constructor fn from_builder(HeaderBuilderCommon, HeaderHash, EntryHash);
 );
 
+fixturator!(
+    LinkTypeIndex; from U8;
+);
+
+/// Alias
+pub type MaybeLinkTypeIndex = Option<LinkTypeIndex>;
+
+fixturator! {
+    MaybeLinkTypeIndex;
+    enum [ Some None ];
+    curve Empty MaybeLinkTypeIndex::None;
+    curve Unpredictable match MaybeLinkTypeIndexVariant::random() {
+        MaybeLinkTypeIndexVariant::None => MaybeLinkTypeIndex::None,
+        MaybeLinkTypeIndexVariant::Some => MaybeLinkTypeIndex::Some(fixt!(LinkTypeIndex)),
+    };
+    curve Predictable match MaybeLinkTypeIndexVariant::nth(self.0.index) {
+        MaybeLinkTypeIndexVariant::None => MaybeLinkTypeIndex::None,
+        MaybeLinkTypeIndexVariant::Some => MaybeLinkTypeIndex::Some(LinkTypeIndexFixturator::new_indexed(Predictable, self.0.index).next().unwrap()),
+    };
+}
+
 fixturator!(
     CreateLink;
-    constructor fn from_builder(HeaderBuilderCommon, EntryHash, EntryHash, u8, LinkTag);
+    constructor fn from_builder(HeaderBuilderCommon, EntryHash, EntryHash, u8, MaybeLinkTypeIndex, LinkTag);
 );
 
 fixturator!(
     LinkTag; from Bytes;
 );
 
+fixturator!(
+    EntryDhtStatus;
+    unit variants [ Live Dead Pending Rejected Abandoned Conflict Withdrawn Purged ] empty Live;
+);
+
 pub struct KnownCreateLink {
     pub base_address: EntryHash,
     pub target_address: EntryHash,
@@ -154,6 +181,7 @@ impl Iterator for CreateLinkFixturator<KnownCreateLink> {
         f.target_address = self.0.curve.target_address.clone();
         f.tag = self.0.curve.tag.clone();
         f.zome_id = self.0.curve.zome_id;
+        f.link_type = None;
         Some(f)
     }
 }
@@ -235,6 +235,24 @@ pub struct ZomeId(u8);
 )]
 pub struct EntryDefIndex(u8);
 
+/// The position of a link type in the zome's declared link types.
+/// Links without a declared type carry `None` and are not checked against
+/// the zome's link types during sys validation.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    SerializedBytes,
+)]
+pub struct LinkTypeIndex(u8);
+
 /// The Dna Header is always the first header in a source chain
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, SerializedBytes)]
 pub struct Dna {
@@ -277,6 +295,7 @@ pub struct CreateLink {
     pub base_address: EntryHash,
     pub target_address: EntryHash,
     pub zome_id: ZomeId,
+    pub link_type: Option<LinkTypeIndex>,
     pub tag: LinkTag,
 }
 
@@ -359,6 +378,30 @@ pub struct Update {
     pub entry_hash: EntryHash,
 }
 
+/// What an [Update] replaces.
+///
+/// Targeting an entry saves looking up its header first: the latest live
+/// header for the entry is found when the update is committed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum UpdateTarget {
+    /// Update this element
+    Header(HeaderHash),
+    /// Update whichever element is the latest live one for this entry
+    Entry(EntryHash),
+}
+
+impl From<HeaderHash> for UpdateTarget {
+    fn from(header_hash: HeaderHash) -> Self {
+        Self::Header(header_hash)
+    }
+}
+
+impl From<EntryHash> for UpdateTarget {
+    fn from(entry_hash: EntryHash) -> Self {
+        Self::Entry(entry_hash)
+    }
+}
+
 /// Declare that a previously published Header should be nullified and
 /// considered deleted.
 ///
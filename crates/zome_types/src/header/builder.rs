@@ -1,10 +1,37 @@
 use super::{EntryType, Timestamp};
-use crate::header::{self, HeaderInner, ZomeId};
+use crate::entry::Entry;
+use crate::header::{self, HeaderHashed, HeaderInner, LinkTypeIndex, ZomeId};
 use crate::link::LinkTag;
 use header::Dna;
-use holo_hash::{AgentPubKey, DnaHash, EntryHash, HeaderHash};
+use holo_hash::{AgentPubKey, DnaHash, EntryHash, HasHash, HeaderHash};
 use holochain_serialized_bytes::SerializedBytes;
 
+/// The values given to a builder would make a header which breaks the
+/// rules of the chain
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum HeaderBuilderError {
+    /// No header can follow one with the largest header_seq
+    #[error("The chain is full: no header can follow header_seq {0}")]
+    ChainFull(u32),
+
+    /// Timestamps never go backwards along a chain
+    #[error("Timestamp {timestamp} is before the previous header's {prev}")]
+    TimestampBeforePrev {
+        /// The timestamp given
+        timestamp: Timestamp,
+        /// The previous header's timestamp
+        prev: Timestamp,
+    },
+
+    /// The entry type is a different kind from the entry
+    #[error("Entry type {0:?} doesn't match the entry")]
+    EntryTypeMismatch(EntryType),
+
+    /// The entry of an agent key is the key itself, so its hash must be too
+    #[error("The hash {0:?} of an agent key entry isn't the key")]
+    AgentEntryHashMismatch(EntryHash),
+}
+
 #[derive(Debug)]
 pub struct HeaderBuilderCommon {
     pub author: AgentPubKey,
@@ -27,6 +54,30 @@ impl HeaderBuilderCommon {
             prev_header,
         }
     }
+
+    /// The common fields of the header which follows `prev` on its chain.
+    /// The author is `prev`'s, the header_seq is one more and the
+    /// prev_header is `prev` itself, so only the timestamp is left to
+    /// choose, and it can't be before `prev`'s.
+    pub fn next(prev: &HeaderHashed, timestamp: Timestamp) -> Result<Self, HeaderBuilderError> {
+        let header = prev.as_content();
+        if timestamp < header.timestamp() {
+            return Err(HeaderBuilderError::TimestampBeforePrev {
+                timestamp,
+                prev: header.timestamp(),
+            });
+        }
+        let header_seq = header
+            .header_seq()
+            .checked_add(1)
+            .ok_or_else(|| HeaderBuilderError::ChainFull(header.header_seq()))?;
+        Ok(Self::new(
+            header.author().clone(),
+            timestamp,
+            header_seq,
+            prev.as_hash().clone(),
+        ))
+    }
 }
 
 /// Builder for non-genesis Headers
@@ -112,6 +163,7 @@ builder_variant!(CreateLink {
     base_address: EntryHash,
     target_address: EntryHash,
     zome_id: ZomeId,
+    link_type: Option<LinkTypeIndex>,
     tag: LinkTag,
 });
 
@@ -150,6 +202,65 @@ builder_variant!(AgentValidationPkg {
     membrane_proof: Option<SerializedBytes>,
 });
 
+impl Create {
+    /// Builder for the header which creates `entry`.
+    /// See [check_entry_data] for what is checked.
+    pub fn for_entry(
+        entry_type: EntryType,
+        entry_hash: EntryHash,
+        entry: &Entry,
+    ) -> Result<Self, HeaderBuilderError> {
+        check_entry_data(&entry_type, &entry_hash, entry)?;
+        Ok(Self::new(entry_type, entry_hash))
+    }
+}
+
+impl Update {
+    /// Builder for the header which updates the original entry to `entry`.
+    /// See [check_entry_data] for what is checked.
+    pub fn for_entry(
+        original_entry_address: EntryHash,
+        original_header_address: HeaderHash,
+        entry_type: EntryType,
+        entry_hash: EntryHash,
+        entry: &Entry,
+    ) -> Result<Self, HeaderBuilderError> {
+        check_entry_data(&entry_type, &entry_hash, entry)?;
+        Ok(Self::new(
+            original_entry_address,
+            original_header_address,
+            entry_type,
+            entry_hash,
+        ))
+    }
+}
+
+/// Check a header's entry data matches its entry: the entry type must be
+/// the same kind as the entry, and an agent key entry's hash must be the key.
+/// Any other entry's hash can only be checked by hashing the entry, which is
+/// left to whoever has the hashing to hand.
+pub fn check_entry_data(
+    entry_type: &EntryType,
+    entry_hash: &EntryHash,
+    entry: &Entry,
+) -> Result<(), HeaderBuilderError> {
+    match (entry_type, entry) {
+        (EntryType::AgentPubKey, Entry::Agent(agent)) => {
+            if *entry_hash == EntryHash::from(agent.clone()) {
+                Ok(())
+            } else {
+                Err(HeaderBuilderError::AgentEntryHashMismatch(
+                    entry_hash.clone(),
+                ))
+            }
+        }
+        (EntryType::App(_), Entry::App(_))
+        | (EntryType::CapClaim, Entry::CapClaim(_))
+        | (EntryType::CapGrant, Entry::CapGrant(_)) => Ok(()),
+        _ => Err(HeaderBuilderError::EntryTypeMismatch(entry_type.clone())),
+    }
+}
+
 impl Dna {
     /// The Dna header can't implement HeaderBuilder because it lacks a
     /// `prev_header` field, so this helper is provided as a special case
@@ -161,3 +272,55 @@ impl Dna {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        header::Header,
+        test_utils::{fake_agent_pub_key, fake_entry_hash, fake_header_hash},
+    };
+    use holo_hash::HoloHashed;
+
+    #[test]
+    fn next_follows_prev() {
+        let prev = HoloHashed::with_pre_hashed(
+            Header::InitZomesComplete(header::InitZomesComplete {
+                author: fake_agent_pub_key(1),
+                timestamp: Timestamp(10, 0),
+                header_seq: 3,
+                prev_header: fake_header_hash(1),
+            }),
+            fake_header_hash(2),
+        );
+        let common = HeaderBuilderCommon::next(&prev, Timestamp(10, 0)).unwrap();
+        assert_eq!(common.author, fake_agent_pub_key(1));
+        assert_eq!(common.header_seq, 4);
+        assert_eq!(common.prev_header, fake_header_hash(2));
+
+        assert_eq!(
+            HeaderBuilderCommon::next(&prev, Timestamp(9, 0)).unwrap_err(),
+            HeaderBuilderError::TimestampBeforePrev {
+                timestamp: Timestamp(9, 0),
+                prev: Timestamp(10, 0),
+            }
+        );
+    }
+
+    #[test]
+    fn entry_data_matches_entry() {
+        let agent = fake_agent_pub_key(1);
+        let entry = Entry::Agent(agent.clone());
+        assert!(Create::for_entry(EntryType::AgentPubKey, agent.clone().into(), &entry).is_ok());
+        assert_eq!(
+            Create::for_entry(EntryType::AgentPubKey, fake_entry_hash(1), &entry),
+            Err(HeaderBuilderError::AgentEntryHashMismatch(fake_entry_hash(
+                1
+            )))
+        );
+        assert_eq!(
+            Create::for_entry(EntryType::CapClaim, agent.into(), &entry),
+            Err(HeaderBuilderError::EntryTypeMismatch(EntryType::CapClaim))
+        );
+    }
+}
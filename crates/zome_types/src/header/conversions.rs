@@ -24,6 +24,18 @@ impl From<u8> for EntryDefIndex {
     }
 }
 
+impl From<u8> for LinkTypeIndex {
+    fn from(a: u8) -> Self {
+        Self(a)
+    }
+}
+
+impl From<LinkTypeIndex> for u8 {
+    fn from(a: LinkTypeIndex) -> Self {
+        a.0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WrongHeaderError(pub String);
 
@@ -0,0 +1,232 @@
+//! App defined indexes.
+//!
+//! A zome can implement the `index_op` callback to emit [IndexRecord]s for
+//! each entry it defines. An authority runs the callback as it integrates the
+//! entry and keeps the records in its own index database, which the zome can
+//! then search by exact key or by key range with `query_index`, instead of
+//! getting every entry and filtering them itself.
+//!
+//! The callback must be deterministic, so that every authority holding an
+//! entry indexes it in the same way.
+
+use crate::zome_io::ExternOutput;
+use crate::CallbackResult;
+use holo_hash::HeaderHash;
+use holochain_serialized_bytes::prelude::*;
+
+/// The name of one of a zome's indexes
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IndexName(pub String);
+
+impl From<String> for IndexName {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for IndexName {
+    fn from(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+/// A key in an index. Keys are compared byte by byte, so numbers should be
+/// converted with [IndexKey::from_u64] (or be big endian) to sort properly.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct IndexKey(#[serde(with = "serde_bytes")] pub Vec<u8>);
+
+impl IndexKey {
+    /// A key which sorts in numeric order
+    pub fn from_u64(n: u64) -> Self {
+        Self(n.to_be_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for IndexKey {
+    fn from(v: Vec<u8>) -> Self {
+        Self(v)
+    }
+}
+
+impl From<String> for IndexKey {
+    fn from(s: String) -> Self {
+        Self(s.into_bytes())
+    }
+}
+
+impl From<&str> for IndexKey {
+    fn from(s: &str) -> Self {
+        Self(s.as_bytes().to_vec())
+    }
+}
+
+/// One record emitted by the `index_op` callback
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexRecord {
+    /// The index to add the record to
+    pub index: IndexName,
+    /// What the record can be found by
+    pub key: IndexKey,
+    /// Anything the zome wants to get back along with the key
+    pub value: SerializedBytes,
+}
+
+impl IndexRecord {
+    /// Create a new record
+    pub fn new<I: Into<IndexName>, K: Into<IndexKey>>(
+        index: I,
+        key: K,
+        value: SerializedBytes,
+    ) -> Self {
+        Self {
+            index: index.into(),
+            key: key.into(),
+            value,
+        }
+    }
+}
+
+/// The result of the `index_op` callback
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub enum IndexOpCallbackResult {
+    /// The records to add for the op, which may be none
+    Indexes(Vec<IndexRecord>),
+    /// The callback failed
+    Err(String),
+}
+
+impl From<Vec<IndexRecord>> for IndexOpCallbackResult {
+    fn from(v: Vec<IndexRecord>) -> Self {
+        Self::Indexes(v)
+    }
+}
+
+impl From<ExternOutput> for IndexOpCallbackResult {
+    fn from(callback_guest_output: ExternOutput) -> Self {
+        match callback_guest_output.into_inner().try_into() {
+            Ok(v) => v,
+            Err(e) => Self::Err(format!("{:?}", e)),
+        }
+    }
+}
+
+impl CallbackResult for IndexOpCallbackResult {
+    fn is_definitive(&self) -> bool {
+        match self {
+            IndexOpCallbackResult::Indexes(_) => false,
+            IndexOpCallbackResult::Err(_) => true,
+        }
+    }
+}
+
+/// Search one index by key.
+/// Keys from `start` (inclusive) to `end` (exclusive) are returned in key order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexQuery {
+    /// The index to search
+    pub index: IndexName,
+    /// The lowest key to return, or from the first key if None
+    pub start: Option<IndexKey>,
+    /// Only keys below this are returned, or up to the last key if None
+    pub end: Option<IndexKey>,
+    /// The most records to return
+    pub limit: Option<u32>,
+}
+
+impl IndexQuery {
+    /// Every record in an index
+    pub fn all<I: Into<IndexName>>(index: I) -> Self {
+        Self {
+            index: index.into(),
+            start: None,
+            end: None,
+            limit: None,
+        }
+    }
+
+    /// Records with exactly this key
+    pub fn exact<I: Into<IndexName>, K: Into<IndexKey>>(index: I, key: K) -> Self {
+        let key = key.into();
+        // Nothing sorts between a key and the same key with a zero appended
+        let mut end = key.0.clone();
+        end.push(0);
+        Self {
+            index: index.into(),
+            start: Some(key),
+            end: Some(IndexKey(end)),
+            limit: None,
+        }
+    }
+
+    /// Records with keys from `start` up to but not including `end`
+    pub fn range<I: Into<IndexName>, K: Into<IndexKey>>(index: I, start: K, end: K) -> Self {
+        Self {
+            index: index.into(),
+            start: Some(start.into()),
+            end: Some(end.into()),
+            limit: None,
+        }
+    }
+
+    /// Return at most `limit` records
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether a key is in the queried range
+    pub fn contains(&self, key: &IndexKey) -> bool {
+        self.start
+            .as_ref()
+            .map(|start| key >= start)
+            .unwrap_or(true)
+            && self.end.as_ref().map(|end| key < end).unwrap_or(true)
+    }
+}
+
+/// A record found by `query_index`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexHit {
+    /// The record's key
+    pub key: IndexKey,
+    /// The record's value
+    pub value: SerializedBytes,
+    /// The header of the entry which the record was emitted for
+    pub header_hash: HeaderHash,
+}
+
+/// The records found by `query_index`, in key order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexHits(pub Vec<IndexHit>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_guest_output_test() {
+        let index_op_callback_result = IndexOpCallbackResult::Indexes(vec![IndexRecord::new(
+            "by_name",
+            "alice",
+            SerializedBytes::try_from(()).unwrap(),
+        )]);
+        let guest_output = ExternOutput::new(index_op_callback_result.clone().try_into().unwrap());
+        assert_eq!(index_op_callback_result, guest_output.into());
+    }
+
+    #[test]
+    fn query_contains_test() {
+        let exact = IndexQuery::exact("by_name", "bob");
+        assert!(exact.contains(&"bob".into()));
+        assert!(!exact.contains(&"bo".into()));
+        assert!(!exact.contains(&"bobby".into()));
+
+        let range = IndexQuery::range("by_age", IndexKey::from_u64(18), IndexKey::from_u64(65));
+        assert!(range.contains(&IndexKey::from_u64(18)));
+        assert!(range.contains(&IndexKey::from_u64(64)));
+        assert!(!range.contains(&IndexKey::from_u64(65)));
+        assert!(!range.contains(&IndexKey::from_u64(7)));
+
+        assert!(IndexQuery::all("by_age").contains(&IndexKey::from_u64(7)));
+    }
+}
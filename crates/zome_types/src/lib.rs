@@ -24,6 +24,7 @@ pub mod entry;
 pub mod entry_def;
 #[allow(missing_docs)]
 pub mod header;
+pub mod index;
 #[allow(missing_docs)]
 pub mod init;
 #[allow(missing_docs)]
@@ -31,12 +32,15 @@ pub mod link;
 pub mod metadata;
 #[allow(missing_docs)]
 pub mod migrate_agent;
+pub mod observation;
 #[allow(missing_docs)]
 pub mod post_commit;
+pub mod provenance;
 pub mod query;
 pub mod request;
 pub mod signature;
 pub mod timestamp;
+pub mod trace;
 #[allow(missing_docs)]
 pub mod validate;
 #[allow(missing_docs)]
@@ -1,7 +1,269 @@
 use crate::header::CreateLink;
 use crate::header::DeleteLink;
+use crate::timestamp::Timestamp;
+use crate::zome_io::ExternOutput;
+use crate::CallbackResult;
 use holochain_serialized_bytes::prelude::*;
 
+/// Zome-unique identifier for a type of link.
+///
+/// Link types are declared by a zome in the `link_types` callback, in the same
+/// way that entry defs are declared in `entry_defs`. The position of the id in
+/// the declared list becomes the [LinkTypeIndex] carried in the `CreateLink` header.
+///
+/// [LinkTypeIndex]: crate::header::LinkTypeIndex
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct LinkTypeId(pub String);
+
+impl From<String> for LinkTypeId {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for LinkTypeId {
+    fn from(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+/// How the tags of a link type are ordered.
+///
+/// By default a tag is opaque bytes. A link type can instead declare that its
+/// tags start with a typed value, which authorities then keep an ordered index
+/// of so that links can be found by a range of values with `get_links_in_range`.
+/// Any bytes after the value are left to the app.
+/// Sys validation rejects typed links whose tag doesn't start with a value of
+/// the declared type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum LinkTagOrder {
+    /// 8 big endian bytes of an unsigned integer, see [LinkTag::from_u64]
+    U64,
+    /// 8 big endian bytes of a signed integer, see [LinkTag::from_i64]
+    I64,
+    /// 8 big endian bytes of the seconds and 4 of the nanoseconds,
+    /// see [LinkTag::from_timestamp]
+    Timestamp,
+}
+
+impl LinkTagOrder {
+    /// The number of tag bytes holding the value
+    pub fn value_len(&self) -> usize {
+        match self {
+            LinkTagOrder::U64 | LinkTagOrder::I64 => 8,
+            LinkTagOrder::Timestamp => 12,
+        }
+    }
+
+    /// Bytes which sort in the same order as the tag's value,
+    /// or None if the tag is too short to hold one
+    pub fn sort_key(&self, tag: &LinkTag) -> Option<Vec<u8>> {
+        let mut key = tag.0.get(..self.value_len())?.to_vec();
+        match self {
+            LinkTagOrder::U64 => (),
+            // Flipping the sign bit puts negative numbers first
+            LinkTagOrder::I64 | LinkTagOrder::Timestamp => key[0] ^= 0x80,
+        }
+        Some(key)
+    }
+
+    /// The value at the start of the tag,
+    /// or None if the tag is too short to hold one
+    pub fn value(&self, tag: &LinkTag) -> Option<LinkTagValue> {
+        let bytes = tag.0.get(..self.value_len())?;
+        let mut n = [0; 8];
+        n.copy_from_slice(&bytes[..8]);
+        Some(match self {
+            LinkTagOrder::U64 => LinkTagValue::U64(u64::from_be_bytes(n)),
+            LinkTagOrder::I64 => LinkTagValue::I64(i64::from_be_bytes(n)),
+            LinkTagOrder::Timestamp => {
+                let mut nanos = [0; 4];
+                nanos.copy_from_slice(&bytes[8..]);
+                LinkTagValue::Timestamp(Timestamp(i64::from_be_bytes(n), u32::from_be_bytes(nanos)))
+            }
+        })
+    }
+}
+
+/// The typed value at the start of a tag of an ordered link type
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub enum LinkTagValue {
+    U64(u64),
+    I64(i64),
+    Timestamp(Timestamp),
+}
+
+/// An aggregate of the tag values of an ordered link type,
+/// computed by the authority so only the result is returned
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum LinkAggregateOp {
+    /// The number of links
+    Count,
+    /// The sum of the values, saturating at the bounds of the type.
+    /// Timestamps can't be summed.
+    Sum,
+    /// The smallest value
+    Min,
+    /// The largest value
+    Max,
+}
+
+/// The result of a [LinkAggregateOp]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct LinkAggregate {
+    /// The number of links aggregated
+    pub count: u64,
+    /// The sum, min or max of the values.
+    /// None for a count, or if there were no links.
+    pub value: Option<LinkTagValue>,
+}
+
+impl LinkAggregateOp {
+    /// Aggregate the values of tags ordered by `tag_order`, skipping tags
+    /// without a value. None if this op can't be applied to the order.
+    pub fn aggregate<'a>(
+        &self,
+        tag_order: LinkTagOrder,
+        tags: impl IntoIterator<Item = &'a LinkTag>,
+    ) -> Option<LinkAggregate> {
+        if let (LinkAggregateOp::Sum, LinkTagOrder::Timestamp) = (self, tag_order) {
+            return None;
+        }
+        let mut count = 0;
+        let mut value = None;
+        for v in tags.into_iter().filter_map(|tag| tag_order.value(tag)) {
+            count += 1;
+            value = match (self, value) {
+                (LinkAggregateOp::Count, _) => None,
+                (_, None) => Some(v),
+                (LinkAggregateOp::Sum, Some(acc)) => Some(match (acc, v) {
+                    (LinkTagValue::U64(a), LinkTagValue::U64(b)) => {
+                        LinkTagValue::U64(a.saturating_add(b))
+                    }
+                    (LinkTagValue::I64(a), LinkTagValue::I64(b)) => {
+                        LinkTagValue::I64(a.saturating_add(b))
+                    }
+                    // Every value has the type of the order
+                    _ => acc,
+                }),
+                (LinkAggregateOp::Min, Some(acc)) => Some(acc.min(v)),
+                (LinkAggregateOp::Max, Some(acc)) => Some(acc.max(v)),
+            };
+        }
+        Some(LinkAggregate { count, value })
+    }
+}
+
+/// The declaration of a link type
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LinkTypeDef {
+    /// The zome-unique id
+    pub id: LinkTypeId,
+    /// How tags are ordered, or None if they are opaque bytes
+    pub tag_order: Option<LinkTagOrder>,
+}
+
+impl LinkTypeDef {
+    /// A link type whose tags are ordered by a typed value
+    pub fn ordered<I: Into<LinkTypeId>>(id: I, tag_order: LinkTagOrder) -> Self {
+        Self {
+            id: id.into(),
+            tag_order: Some(tag_order),
+        }
+    }
+}
+
+impl From<LinkTypeId> for LinkTypeDef {
+    fn from(id: LinkTypeId) -> Self {
+        Self {
+            id,
+            tag_order: None,
+        }
+    }
+}
+
+impl From<&str> for LinkTypeDef {
+    fn from(s: &str) -> Self {
+        LinkTypeId::from(s).into()
+    }
+}
+
+/// All the link types declared by a single zome, in declaration order.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LinkTypes(Vec<LinkTypeDef>);
+
+impl LinkTypes {
+    pub fn link_type_id_position(&self, link_type_id: &LinkTypeId) -> Option<usize> {
+        self.0.iter().position(|def| &def.id == link_type_id)
+    }
+
+    /// The tag order declared for the link type at this position
+    pub fn tag_order(&self, i: usize) -> Option<LinkTagOrder> {
+        self.0.get(i).and_then(|def| def.tag_order)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl std::ops::Index<usize> for LinkTypes {
+    type Output = LinkTypeDef;
+    fn index(&self, i: usize) -> &Self::Output {
+        &self.0[i]
+    }
+}
+
+impl IntoIterator for LinkTypes {
+    type Item = LinkTypeDef;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<Vec<LinkTypeDef>> for LinkTypes {
+    fn from(v: Vec<LinkTypeDef>) -> Self {
+        Self(v)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub enum LinkTypesCallbackResult {
+    Defs(LinkTypes),
+    Err(String),
+}
+
+impl From<Vec<LinkTypeDef>> for LinkTypesCallbackResult {
+    fn from(v: Vec<LinkTypeDef>) -> Self {
+        Self::Defs(v.into())
+    }
+}
+
+impl From<ExternOutput> for LinkTypesCallbackResult {
+    fn from(callback_guest_output: ExternOutput) -> Self {
+        match callback_guest_output.into_inner().try_into() {
+            Ok(v) => v,
+            Err(e) => Self::Err(format!("{:?}", e)),
+        }
+    }
+}
+
+impl CallbackResult for LinkTypesCallbackResult {
+    fn is_definitive(&self) -> bool {
+        match self {
+            LinkTypesCallbackResult::Defs(_) => false,
+            LinkTypesCallbackResult::Err(_) => true,
+        }
+    }
+}
+
 /// Opaque tag for the link applied at the app layer, used to differentiate
 /// between different semantics and validation rules for different links
 #[derive(
@@ -26,6 +288,27 @@ impl LinkTag {
     {
         Self(t.into())
     }
+
+    /// Tag for a link type ordered by [LinkTagOrder::U64]
+    pub fn from_u64(n: u64) -> Self {
+        Self(n.to_be_bytes().to_vec())
+    }
+
+    /// Tag for a link type ordered by [LinkTagOrder::I64]
+    pub fn from_i64(n: i64) -> Self {
+        Self(n.to_be_bytes().to_vec())
+    }
+
+    /// Tag for a link type ordered by [LinkTagOrder::Timestamp]
+    pub fn from_timestamp(timestamp: Timestamp) -> Self {
+        Self(
+            [
+                &timestamp.0.to_be_bytes()[..],
+                &timestamp.1.to_be_bytes()[..],
+            ]
+            .concat(),
+        )
+    }
 }
 
 impl From<Vec<u8>> for LinkTag {
@@ -102,3 +385,173 @@ impl LinkDetails {
         self.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::LinkAggregate;
+    use super::LinkAggregateOp;
+    use super::LinkTag;
+    use super::LinkTagOrder;
+    use super::LinkTagValue;
+    use super::LinkTypeDef;
+    use super::LinkTypeId;
+    use super::LinkTypes;
+    use super::LinkTypesCallbackResult;
+    use crate::timestamp::Timestamp;
+    use crate::zome_io::ExternOutput;
+    use std::convert::TryInto;
+
+    #[test]
+    fn from_guest_output_test() {
+        let link_types_callback_result =
+            LinkTypesCallbackResult::Defs(vec!["follows".into(), "likes".into()].into());
+        let guest_output =
+            ExternOutput::new(link_types_callback_result.clone().try_into().unwrap());
+        assert_eq!(link_types_callback_result, guest_output.into());
+    }
+
+    #[test]
+    fn link_type_id_position_test() {
+        let link_types: LinkTypes = vec!["follows".into(), "likes".into()].into();
+        assert_eq!(
+            Some(1),
+            link_types.link_type_id_position(&LinkTypeId::from("likes"))
+        );
+        assert_eq!(
+            None,
+            link_types.link_type_id_position(&LinkTypeId::from("blocks"))
+        );
+    }
+
+    #[test]
+    fn tag_order_test() {
+        let link_types: LinkTypes = vec![
+            "follows".into(),
+            LinkTypeDef::ordered("posted_at", LinkTagOrder::Timestamp),
+        ]
+        .into();
+        assert_eq!(None, link_types.tag_order(0));
+        assert_eq!(Some(LinkTagOrder::Timestamp), link_types.tag_order(1));
+        assert_eq!(None, link_types.tag_order(2));
+    }
+
+    #[test]
+    fn sort_key_test() {
+        let sorted = |order: LinkTagOrder, tags: Vec<LinkTag>| {
+            let keys: Vec<_> = tags.iter().map(|t| order.sort_key(t).unwrap()).collect();
+            let mut sorted_keys = keys.clone();
+            sorted_keys.sort();
+            keys == sorted_keys
+        };
+        assert!(sorted(
+            LinkTagOrder::U64,
+            vec![0, 1, 256, u64::MAX]
+                .into_iter()
+                .map(LinkTag::from_u64)
+                .collect()
+        ));
+        assert!(sorted(
+            LinkTagOrder::I64,
+            vec![i64::MIN, -256, -1, 0, 1, i64::MAX]
+                .into_iter()
+                .map(LinkTag::from_i64)
+                .collect()
+        ));
+        assert!(sorted(
+            LinkTagOrder::Timestamp,
+            vec![
+                Timestamp(-1, 500),
+                Timestamp(0, 0),
+                Timestamp(0, 1),
+                Timestamp(1, 0)
+            ]
+            .into_iter()
+            .map(LinkTag::from_timestamp)
+            .collect()
+        ));
+
+        // Bytes after the value don't change its order
+        let mut tag = LinkTag::from_u64(7);
+        tag.0.extend_from_slice(b"app data");
+        assert_eq!(
+            LinkTagOrder::U64.sort_key(&tag),
+            LinkTagOrder::U64.sort_key(&LinkTag::from_u64(7))
+        );
+
+        // Tags too short to hold a value have no key
+        assert_eq!(
+            None,
+            LinkTagOrder::Timestamp.sort_key(&LinkTag::from_u64(7))
+        );
+    }
+
+    #[test]
+    fn aggregate_test() {
+        let mut short_tag = LinkTag::from_i64(1000);
+        short_tag.0.truncate(4);
+        let tags: Vec<_> = vec![5, -3, 10]
+            .into_iter()
+            .map(LinkTag::from_i64)
+            .chain(std::iter::once(short_tag))
+            .collect();
+        let aggregate = |op: LinkAggregateOp| op.aggregate(LinkTagOrder::I64, &tags);
+
+        // Tags without a value aren't counted
+        assert_eq!(
+            aggregate(LinkAggregateOp::Count),
+            Some(LinkAggregate {
+                count: 3,
+                value: None
+            })
+        );
+        assert_eq!(
+            aggregate(LinkAggregateOp::Sum).unwrap().value,
+            Some(LinkTagValue::I64(12))
+        );
+        assert_eq!(
+            aggregate(LinkAggregateOp::Min).unwrap().value,
+            Some(LinkTagValue::I64(-3))
+        );
+        assert_eq!(
+            aggregate(LinkAggregateOp::Max).unwrap().value,
+            Some(LinkTagValue::I64(10))
+        );
+
+        // Sums saturate
+        let big = vec![LinkTag::from_u64(u64::MAX), LinkTag::from_u64(1)];
+        assert_eq!(
+            LinkAggregateOp::Sum
+                .aggregate(LinkTagOrder::U64, &big)
+                .unwrap()
+                .value,
+            Some(LinkTagValue::U64(u64::MAX))
+        );
+
+        // No links
+        assert_eq!(
+            LinkAggregateOp::Max.aggregate(LinkTagOrder::U64, &[]),
+            Some(LinkAggregate {
+                count: 0,
+                value: None
+            })
+        );
+
+        // Timestamps can be compared but not summed
+        let times = vec![
+            LinkTag::from_timestamp(Timestamp(1, 0)),
+            LinkTag::from_timestamp(Timestamp(0, 5)),
+        ];
+        assert_eq!(
+            LinkAggregateOp::Min
+                .aggregate(LinkTagOrder::Timestamp, &times)
+                .unwrap()
+                .value,
+            Some(LinkTagValue::Timestamp(Timestamp(0, 5)))
+        );
+        assert_eq!(
+            LinkAggregateOp::Sum.aggregate(LinkTagOrder::Timestamp, &times),
+            None
+        );
+    }
+}
@@ -27,6 +27,10 @@ pub struct ElementDetails {
     pub element: Element,
     /// Any Delete on this element.
     pub deletes: Vec<Delete>,
+    /// The elements the entry references, if they were followed.
+    /// See [crate::entry::GetOptions::follow_references].
+    #[serde(default)]
+    pub references: Vec<Element>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, SerializedBytes)]
@@ -47,6 +51,10 @@ pub struct EntryDetails {
     /// The status of this entry currently
     /// according to your view of the metadata
     pub entry_dht_status: EntryDhtStatus,
+    /// The elements the entry references, if they were followed.
+    /// See [crate::entry::GetOptions::follow_references].
+    #[serde(default)]
+    pub references: Vec<Element>,
 }
 
 /// The status of an [Entry] in the Dht
@@ -0,0 +1,128 @@
+//! Signed observations of the head of an agent's source chain.
+//!
+//! An agent activity authority can attest that, when it was asked, the head
+//! of an agent's chain was a particular header. The attestation carries the
+//! agent's own signed header, so whoever holds it can later prove that the
+//! agent signed that header at that position. If the agent then presents a
+//! chain which has a different header there, or which is shorter, it has
+//! forked or rolled back its chain.
+
+use crate::{element::SignedHeader, signature::Signature, timestamp::Timestamp};
+use holo_hash::AgentPubKey;
+use holochain_serialized_bytes::prelude::*;
+
+/// What an authority saw at the head of an agent's chain - to be signed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct ChainHeadObservation {
+    /// The agent whose chain was observed
+    pub agent: AgentPubKey,
+    /// The header at the head of the chain, as signed by the agent
+    pub head: SignedHeader,
+    /// The authority which observed the chain
+    pub observer: AgentPubKey,
+    /// When the authority observed the chain
+    pub observed_at: Timestamp,
+}
+
+/// A chain head observation, signed by the authority which made it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+pub struct SignedChainHeadObservation {
+    /// The observation
+    pub observation: ChainHeadObservation,
+    /// The observer's signature of the observation
+    pub signature: Signature,
+}
+
+/// How a chain disagrees with an earlier observation of its head.
+/// Each carries the sequence number of the observed head.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChainHeadDispute {
+    /// The chain has a different header at the observed position
+    Forked(u32),
+    /// The chain ends before the observed position
+    RolledBack(u32),
+}
+
+impl ChainHeadObservation {
+    /// The sequence number of the observed head
+    pub fn seq(&self) -> u32 {
+        self.head.header().header_seq()
+    }
+
+    /// Check a chain presented by the agent against this observation.
+    /// `chain` must run up to what the agent claims is its head, though it
+    /// may start anywhere. Headers by other agents are ignored.
+    pub fn dispute(&self, chain: &[SignedHeader]) -> Option<ChainHeadDispute> {
+        let seq = self.seq();
+        let mut head_seq = None;
+        for header in chain
+            .iter()
+            .map(SignedHeader::header)
+            .filter(|h| h.author() == &self.agent)
+        {
+            if header.header_seq() == seq && header != self.head.header() {
+                return Some(ChainHeadDispute::Forked(seq));
+            }
+            head_seq = head_seq.max(Some(header.header_seq()));
+        }
+        match head_seq {
+            Some(head_seq) if head_seq >= seq => None,
+            _ => Some(ChainHeadDispute::RolledBack(seq)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        header::{Header, InitZomesComplete},
+        test_utils::{fake_agent_pub_key, fake_header_hash},
+    };
+
+    fn header(author: u8, header_seq: u32, prev: u8) -> SignedHeader {
+        SignedHeader(
+            Header::InitZomesComplete(InitZomesComplete {
+                author: fake_agent_pub_key(author),
+                timestamp: Timestamp(header_seq as i64, 0),
+                header_seq,
+                prev_header: fake_header_hash(prev),
+            }),
+            Signature(vec![0; 64]),
+        )
+    }
+
+    #[test]
+    fn disputes() {
+        let observation = ChainHeadObservation {
+            agent: fake_agent_pub_key(1),
+            head: header(1, 2, 1),
+            observer: fake_agent_pub_key(2),
+            observed_at: Timestamp(10, 0),
+        };
+        assert_eq!(observation.seq(), 2);
+
+        // The chain carried on from the observed head
+        let honest = vec![header(1, 1, 0), header(1, 2, 1), header(1, 3, 2)];
+        assert_eq!(observation.dispute(&honest), None);
+        assert_eq!(observation.dispute(&honest[1..2]), None);
+
+        // A different header at the observed position
+        let forked = vec![header(1, 1, 0), header(1, 2, 9)];
+        assert_eq!(
+            observation.dispute(&forked),
+            Some(ChainHeadDispute::Forked(2))
+        );
+
+        // The chain is shorter than it was, and other agents' headers don't count
+        let rolled_back = vec![header(1, 1, 0), header(3, 2, 1)];
+        assert_eq!(
+            observation.dispute(&rolled_back),
+            Some(ChainHeadDispute::RolledBack(2))
+        );
+        assert_eq!(
+            observation.dispute(&[]),
+            Some(ChainHeadDispute::RolledBack(2))
+        );
+    }
+}
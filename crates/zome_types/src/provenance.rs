@@ -0,0 +1,78 @@
+//! The path a zome call took to reach the function which is running.
+//!
+//! A call from outside, e.g. through an app interface, starts a path with the
+//! calling agent as its origin. Each zome function the call passes through,
+//! including across `call_remote`, adds a hop to the end.
+//!
+//! Only the immediate caller is authenticated by the network. Any hops before
+//! it, and the origin, are as reported by that caller.
+
+use crate::zome::{FunctionName, ZomeName};
+use holo_hash::{AgentPubKey, DnaHash};
+use holochain_serialized_bytes::prelude::*;
+
+/// Every call which led to the zome function which is running, oldest first
+#[derive(Clone, Debug, Serialize, Deserialize, SerializedBytes, PartialEq)]
+pub struct CallProvenance {
+    /// The agent who made the original call from outside any zome
+    pub origin: AgentPubKey,
+    /// Each zome function on the path, ending with the one which is running
+    pub hops: Vec<CallHop>,
+}
+
+/// A zome function which a call passed through
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct CallHop {
+    /// The agent whose cell ran the function
+    pub agent: AgentPubKey,
+    /// The DNA of the cell which ran the function
+    pub dna_hash: DnaHash,
+    /// The zome the function is in
+    pub zome_name: ZomeName,
+    /// The function
+    pub fn_name: FunctionName,
+}
+
+impl CallProvenance {
+    /// A path which starts with a call from outside by this agent
+    pub fn new(origin: AgentPubKey) -> Self {
+        Self {
+            origin,
+            hops: Vec::new(),
+        }
+    }
+
+    /// The agent which called the running function: the agent of the hop
+    /// before it, or the origin if it was called from outside
+    pub fn immediate_caller(&self) -> &AgentPubKey {
+        match self.hops.len() {
+            0 | 1 => &self.origin,
+            n => &self.hops[n - 2].agent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hop(agent: u8) -> CallHop {
+        CallHop {
+            agent: AgentPubKey::from_raw_bytes(vec![agent; 36]),
+            dna_hash: DnaHash::from_raw_bytes(vec![0; 36]),
+            zome_name: "zome".into(),
+            fn_name: "fn".into(),
+        }
+    }
+
+    #[test]
+    fn immediate_caller() {
+        let origin = AgentPubKey::from_raw_bytes(vec![9; 36]);
+        let mut provenance = CallProvenance::new(origin.clone());
+        assert_eq!(provenance.immediate_caller(), &origin);
+        provenance.hops.push(hop(1));
+        assert_eq!(provenance.immediate_caller(), &origin);
+        provenance.hops.push(hop(2));
+        assert_eq!(provenance.immediate_caller(), &hop(1).agent);
+    }
+}
@@ -1,6 +1,9 @@
 //! Types for source chain queries
 
-use crate::header::{EntryType, Header, HeaderType};
+use crate::{
+    header::{EntryType, Header, HeaderType},
+    timestamp::Timestamp,
+};
 pub use holochain_serialized_bytes::prelude::*;
 
 /// Query arguments
@@ -17,6 +20,9 @@ pub struct ChainQueryFilter {
     pub entry_type: Option<EntryType>,
     /// Filter by HeaderType
     pub header_type: Option<HeaderType>,
+    /// The range of header timestamps to match.
+    /// Inclusive start, exclusive end.
+    pub timestamp_range: Option<std::ops::Range<Timestamp>>,
     /// Include the entries in the elements
     pub include_entries: bool,
 }
@@ -48,6 +54,12 @@ impl ChainQueryFilter {
         self
     }
 
+    /// Filter on timestamp range
+    pub fn timestamp_range(mut self, timestamp_range: std::ops::Range<Timestamp>) -> Self {
+        self.timestamp_range = Some(timestamp_range);
+        self
+    }
+
     /// Include the entries in the ElementsVec that is returned
     pub fn include_entries(mut self, include_entries: bool) -> Self {
         self.include_entries = include_entries;
@@ -61,6 +73,11 @@ impl ChainQueryFilter {
             .as_ref()
             .map(|range| range.contains(&header.header_seq()))
             .unwrap_or(true);
+        let check_timestamp = self
+            .timestamp_range
+            .as_ref()
+            .map(|range| range.contains(&header.timestamp()))
+            .unwrap_or(true);
         let check_header_type = self
             .header_type
             .as_ref()
@@ -76,7 +93,7 @@ impl ChainQueryFilter {
                     .unwrap_or(true)
             })
             .unwrap_or(true);
-        check_range && check_header_type && check_entry_type
+        check_range && check_timestamp && check_header_type && check_entry_type
     }
 }
 
@@ -85,6 +102,7 @@ impl ChainQueryFilter {
 mod tests {
     use crate::fixt::AppEntryTypeFixturator;
     use crate::header::EntryType;
+    use crate::timestamp::Timestamp;
     use crate::{fixt::*, Header};
     use ::fixt::prelude::*;
 
@@ -99,24 +117,30 @@ mod tests {
         let mut h1 = fixt!(Create);
         h1.entry_type = entry_type_1.clone();
         h1.header_seq = 0;
+        h1.timestamp = Timestamp(0, 0);
 
         let mut h2 = fixt!(Update);
         h2.entry_type = entry_type_2.clone();
         h2.header_seq = 1;
+        h2.timestamp = Timestamp(1, 0);
 
         let mut h3 = fixt!(CreateLink);
         h3.header_seq = 2;
+        h3.timestamp = Timestamp(2, 0);
 
         let mut h4 = fixt!(Create);
         h4.entry_type = entry_type_2.clone();
         h4.header_seq = 3;
+        h4.timestamp = Timestamp(3, 0);
 
         let mut h5 = fixt!(Update);
         h5.entry_type = entry_type_1.clone();
         h5.header_seq = 4;
+        h5.timestamp = Timestamp(4, 0);
 
         let mut h6 = fixt!(CreateLink);
         h6.header_seq = 5;
+        h6.timestamp = Timestamp(5, 0);
 
         let headers = [
             h1.into(),
@@ -201,6 +225,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn filter_by_timestamp() {
+        let headers = fixtures();
+
+        let query_1 = ChainQueryFilter::new().timestamp_range(Timestamp(1, 0)..Timestamp(4, 0));
+        let query_2 = ChainQueryFilter::new().timestamp_range(Timestamp(4, 0)..Timestamp(4, 1));
+
+        assert_eq!(
+            map_query(&query_1, &headers),
+            [false, true, true, true, false, false].to_vec()
+        );
+        assert_eq!(
+            map_query(&query_2, &headers),
+            [false, false, false, false, true, false].to_vec()
+        );
+    }
+
     #[test]
     fn filter_by_multi() {
         let headers = fixtures();
@@ -1,10 +1,25 @@
 //! # Timestamp
+//!
+//! Besides the type itself, this has the timestamp maths which apps and
+//! validation rules need: checked arithmetic with [Duration]s, conversion
+//! to and from [SystemTime] and microseconds, and RFC3339 strings.
+//! None of it needs the system clock, so all of it works in wasm.
 
 use holochain_serialized_bytes::prelude::*;
+use std::{
+    convert::TryFrom,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const NANOS_PER_SEC: i128 = 1_000_000_000;
+const NANOS_PER_MICRO: i128 = 1_000;
+const SECS_PER_DAY: i64 = 86_400;
 
 /// A UTC timestamp for use in Holochain's headers.
 ///
-/// Timestamp implements `Serialize` and `Display` as rfc3339 time strings.
+/// Timestamp implements `Display` as rfc3339 time strings, and can be
+/// serialized as one with [rfc3339] or as microseconds with [micros].
 /// - Field 0: i64 - Seconds since UNIX epoch UTC (midnight 1970-01-01).
 /// - Field 1: u32 - Nanoseconds in addition to above seconds.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -14,3 +29,408 @@ pub struct Timestamp(
     // nsec
     pub u32,
 );
+
+/// Errors converting timestamps
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TimestampError {
+    /// The result is too far from the epoch to represent
+    #[error("Timestamp out of range")]
+    Overflow,
+
+    /// A string isn't an RFC3339 time
+    #[error("Invalid RFC3339 time {0:?}")]
+    Rfc3339(String),
+}
+
+impl Timestamp {
+    /// The UNIX epoch, midnight 1970-01-01 UTC
+    pub const EPOCH: Timestamp = Timestamp(0, 0);
+
+    /// The timestamp this many microseconds after the epoch
+    pub fn from_micros(micros: i64) -> Self {
+        Self(
+            micros.div_euclid(1_000_000),
+            (micros.rem_euclid(1_000_000) * 1_000) as u32,
+        )
+    }
+
+    /// Microseconds since the epoch, rounded down.
+    /// None if that doesn't fit in an i64, which is about 292,000 years.
+    pub fn as_micros(&self) -> Option<i64> {
+        i64::try_from(self.as_nanos().div_euclid(NANOS_PER_MICRO)).ok()
+    }
+
+    /// The timestamp `duration` after this one, or None on overflow
+    pub fn checked_add(&self, duration: &Duration) -> Option<Self> {
+        Self::from_nanos(self.as_nanos() + duration.as_nanos() as i128)
+    }
+
+    /// The timestamp `duration` before this one, or None on overflow
+    pub fn checked_sub(&self, duration: &Duration) -> Option<Self> {
+        Self::from_nanos(self.as_nanos() - duration.as_nanos() as i128)
+    }
+
+    /// How long after `earlier` this timestamp is.
+    /// None if `earlier` is actually later.
+    pub fn duration_since(&self, earlier: &Timestamp) -> Option<Duration> {
+        let nanos = self.as_nanos() - earlier.as_nanos();
+        if nanos < 0 {
+            return None;
+        }
+        let secs = u64::try_from(nanos / NANOS_PER_SEC).ok()?;
+        Some(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+    }
+
+    /// Whether the two timestamps are no more than `tolerance` apart,
+    /// in either order
+    pub fn is_within(&self, other: &Timestamp, tolerance: &Duration) -> bool {
+        (self.as_nanos() - other.as_nanos()).abs() <= tolerance.as_nanos() as i128
+    }
+
+    /// The same time with the nanoseconds less than a second, or None if
+    /// carrying the extra nanoseconds into the seconds overflows
+    pub fn normalized(&self) -> Option<Self> {
+        Self::from_nanos(self.as_nanos())
+    }
+
+    fn as_nanos(&self) -> i128 {
+        self.0 as i128 * NANOS_PER_SEC + self.1 as i128
+    }
+
+    fn from_nanos(nanos: i128) -> Option<Self> {
+        let secs = i64::try_from(nanos.div_euclid(NANOS_PER_SEC)).ok()?;
+        Some(Self(secs, nanos.rem_euclid(NANOS_PER_SEC) as u32))
+    }
+}
+
+impl TryFrom<SystemTime> for Timestamp {
+    type Error = TimestampError;
+
+    fn try_from(t: SystemTime) -> Result<Self, Self::Error> {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(after) => Timestamp::EPOCH.checked_add(&after),
+            Err(before) => Timestamp::EPOCH.checked_sub(&before.duration()),
+        }
+        .ok_or(TimestampError::Overflow)
+    }
+}
+
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = TimestampError;
+
+    fn try_from(t: Timestamp) -> Result<Self, Self::Error> {
+        let Timestamp(secs, nanos) = t.normalized().ok_or(TimestampError::Overflow)?;
+        let whole_secs = if secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs((-(secs as i128)) as u64))
+        };
+        whole_secs
+            .and_then(|t| t.checked_add(Duration::from_nanos(nanos as u64)))
+            .ok_or(TimestampError::Overflow)
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    /// RFC3339 in UTC, with as many digits of fractional seconds as needed
+    /// out of none, 3, 6 or 9. Years outside 0000 to 9999, which RFC3339
+    /// can't represent, are written with a sign and at least five digits.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Timestamp(secs, nanos) = match self.normalized() {
+            Some(t) => t,
+            None => return write!(f, "{:?}", self),
+        };
+        let (year, month, day) = civil_from_days(secs.div_euclid(SECS_PER_DAY));
+        let secs_of_day = secs.rem_euclid(SECS_PER_DAY);
+        if (0..=9999).contains(&year) {
+            write!(f, "{:04}", year)?;
+        } else {
+            write!(f, "{:+05}", year)?;
+        }
+        write!(
+            f,
+            "-{:02}-{:02}T{:02}:{:02}:{:02}",
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        )?;
+        match nanos {
+            0 => write!(f, "Z"),
+            _ if nanos % 1_000_000 == 0 => write!(f, ".{:03}Z", nanos / 1_000_000),
+            _ if nanos % 1_000 == 0 => write!(f, ".{:06}Z", nanos / 1_000),
+            _ => write!(f, ".{:09}Z", nanos),
+        }
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = TimestampError;
+
+    /// Parse an RFC3339 time, with any offset from UTC.
+    /// Digits of fractional seconds past nanoseconds are dropped, and leap
+    /// seconds aren't accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_rfc3339(s.as_bytes()).ok_or_else(|| TimestampError::Rfc3339(s.to_string()))
+    }
+}
+
+fn parse_rfc3339(s: &[u8]) -> Option<Timestamp> {
+    let digits = |range: std::ops::Range<usize>| -> Option<i64> {
+        let field = s.get(range)?;
+        if !field.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        Some(field.iter().fold(0, |n, d| n * 10 + (d - b'0') as i64))
+    };
+    let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+    if s.len() < 20
+        || !separators.iter().all(|(i, c)| s[*i] == *c)
+        || !matches!(s[10], b'T' | b't' | b' ')
+    {
+        return None;
+    }
+    let (year, month, day) = (digits(0..4)?, digits(5..7)?, digits(8..10)?);
+    let (hour, minute, second) = (digits(11..13)?, digits(14..16)?, digits(17..19)?);
+    if !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month as u32) as i64
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    // Fractional seconds, as nanoseconds
+    let mut rest = &s[19..];
+    let mut nanos = 0;
+    if rest[0] == b'.' {
+        let len = rest[1..].iter().take_while(|d| d.is_ascii_digit()).count();
+        if len == 0 {
+            return None;
+        }
+        for (i, d) in rest[1..=len].iter().enumerate().take(9) {
+            nanos += (d - b'0') as u32 * 10u32.pow(8 - i as u32);
+        }
+        rest = &rest[len + 1..];
+    }
+
+    let offset = match rest {
+        [b'Z'] | [b'z'] => 0,
+        [b'+', h1, h2, b':', m1, m2] => parse_rfc3339_offset([*h1, *h2], [*m1, *m2])?,
+        [b'-', h1, h2, b':', m1, m2] => -parse_rfc3339_offset([*h1, *h2], [*m1, *m2])?,
+        _ => return None,
+    };
+
+    let secs = days_from_civil(year, month as u32, day as u32) * SECS_PER_DAY
+        + hour * 3600
+        + minute * 60
+        + second
+        - offset;
+    Some(Timestamp(secs, nanos))
+}
+
+fn parse_rfc3339_offset(hours: [u8; 2], minutes: [u8; 2]) -> Option<i64> {
+    let two_digits = |[a, b]: [u8; 2]| -> Option<i64> {
+        if a.is_ascii_digit() && b.is_ascii_digit() {
+            Some(((a - b'0') * 10 + (b - b'0')) as i64)
+        } else {
+            None
+        }
+    };
+    let (hours, minutes) = (two_digits(hours)?, two_digits(minutes)?);
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(hours * 3600 + minutes * 60)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The days since the epoch of a date in the proleptic Gregorian calendar.
+/// From Howard Hinnant's `days_from_civil`.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let (month, day) = (month as i64, day as i64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date in the proleptic Gregorian calendar of a number of days since
+/// the epoch. From Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = ((shifted_month + 2) % 12 + 1) as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+/// Serialize a [Timestamp] as an RFC3339 string, with
+/// `#[serde(with = "holochain_zome_types::timestamp::rfc3339")]`
+pub mod rfc3339 {
+    use super::Timestamp;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    /// Serialize as an RFC3339 string
+    pub fn serialize<S: Serializer>(t: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(t)
+    }
+
+    /// Deserialize from an RFC3339 string
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Serialize a [Timestamp] as microseconds since the epoch, with
+/// `#[serde(with = "holochain_zome_types::timestamp::micros")]`.
+/// Nanoseconds are rounded down to the microsecond.
+pub mod micros {
+    use super::{Timestamp, TimestampError};
+    use serde::{ser::Error, Deserialize, Deserializer, Serializer};
+
+    /// Serialize as microseconds since the epoch
+    pub fn serialize<S: Serializer>(t: &Timestamp, serializer: S) -> Result<S::Ok, S::Error> {
+        let micros = t
+            .as_micros()
+            .ok_or_else(|| S::Error::custom(TimestampError::Overflow))?;
+        serializer.serialize_i64(micros)
+    }
+
+    /// Deserialize from microseconds since the epoch
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        i64::deserialize(deserializer).map(Timestamp::from_micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let t = Timestamp(10, 500_000_000);
+        let second = Duration::from_secs(1);
+        assert_eq!(t.checked_add(&second), Some(Timestamp(11, 500_000_000)));
+        assert_eq!(
+            t.checked_sub(&Duration::from_millis(10_600)),
+            Some(Timestamp(-1, 900_000_000))
+        );
+        assert_eq!(Timestamp(i64::MAX, 0).checked_add(&second), None);
+        assert_eq!(Timestamp(i64::MIN, 0).checked_sub(&second), None);
+
+        assert_eq!(
+            t.duration_since(&Timestamp(-1, 900_000_000)),
+            Some(Duration::from_millis(10_600))
+        );
+        assert_eq!(Timestamp::EPOCH.duration_since(&t), None);
+        assert!(t.is_within(&Timestamp(11, 0), &Duration::from_millis(500)));
+        assert!(!Timestamp(11, 1).is_within(&t, &Duration::from_millis(500)));
+
+        assert_eq!(
+            Timestamp(1, 2_500_000_000).normalized(),
+            Some(Timestamp(3, 500_000_000))
+        );
+        assert_eq!(Timestamp(i64::MAX, 1_000_000_000).normalized(), None);
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Timestamp::from_micros(-1), Timestamp(-1, 999_999_000));
+        assert_eq!(Timestamp(-1, 999_999_999).as_micros(), Some(-1));
+        assert_eq!(
+            Timestamp::from_micros(1_500_000).as_micros(),
+            Some(1_500_000)
+        );
+        assert_eq!(Timestamp(i64::MAX, 0).as_micros(), None);
+
+        for t in &[Timestamp(1_601_555_696, 789), Timestamp(-10, 5)] {
+            let system_time = SystemTime::try_from(*t).unwrap();
+            assert_eq!(Timestamp::try_from(system_time), Ok(*t));
+        }
+    }
+
+    #[test]
+    fn rfc3339_round_trips() {
+        let cases = [
+            (
+                Timestamp(1_601_555_696, 789_000_000),
+                "2020-10-01T12:34:56.789Z",
+            ),
+            (Timestamp(951_782_400, 0), "2000-02-29T00:00:00Z"),
+            (Timestamp(-1, 500_000_000), "1969-12-31T23:59:59.500Z"),
+            (Timestamp(0, 1_000), "1970-01-01T00:00:00.000001Z"),
+            (Timestamp(0, 1), "1970-01-01T00:00:00.000000001Z"),
+        ];
+        for (t, s) in cases.iter() {
+            assert_eq!(&t.to_string(), s);
+            assert_eq!(&s.parse::<Timestamp>().unwrap(), t);
+        }
+        assert_eq!(
+            Timestamp(253_402_300_800, 0).to_string(),
+            "+10000-01-01T00:00:00Z"
+        );
+
+        // Offsets are taken off, and extra precision is dropped
+        assert_eq!(
+            "2020-10-01T14:04:56.7891234567+01:30".parse(),
+            Ok(Timestamp(1_601_555_696, 789_123_456))
+        );
+        for bad in &[
+            "2020-10-01T12:34:56",
+            "2020-02-30T00:00:00Z",
+            "2020-10-01T24:00:00Z",
+            "2020-10-01T12:34:60Z",
+            "2020-10-01T12:34:56.Z",
+            "2020-10-01T12:34:56+0100",
+            "2020-10-01X12:34:56Z",
+        ] {
+            assert_eq!(
+                bad.parse::<Timestamp>(),
+                Err(TimestampError::Rfc3339(bad.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn serde_formats() {
+        #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
+        struct Formats {
+            #[serde(with = "rfc3339")]
+            rfc3339: Timestamp,
+            #[serde(with = "micros")]
+            micros: Timestamp,
+        }
+        let formats = Formats {
+            rfc3339: Timestamp(-1, 500_000_000),
+            micros: Timestamp(1_601_555_696, 789_000),
+        };
+        let bytes = SerializedBytes::try_from(formats.clone()).unwrap();
+        assert_eq!(Formats::try_from(bytes).unwrap(), formats);
+    }
+}
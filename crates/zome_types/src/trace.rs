@@ -0,0 +1,54 @@
+//! Types for the `trace_event` host function, which lets zomes instrument
+//! their own logic.
+//!
+//! Each event is recorded on the tracing span of the zome call which emitted
+//! it, so it's exported along with that span when the conductor exports spans
+//! to a collector.
+
+use holochain_serialized_bytes::prelude::*;
+
+/// Something a zome wants to record about what it's doing
+#[derive(Clone, Debug, Serialize, Deserialize, SerializedBytes, PartialEq)]
+pub struct TraceEvent {
+    /// The name of the event or counter
+    pub name: String,
+    /// Whether this is a one-off event or adds to a counter
+    pub kind: TraceEventKind,
+    /// Named values describing the event, or labelling the counter
+    pub fields: Vec<(String, String)>,
+}
+
+/// Whether a [TraceEvent] is a one-off event or adds to a counter
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TraceEventKind {
+    /// Something happened
+    Event,
+    /// Add this much to the named counter, e.g. to count cache misses
+    Counter(u64),
+}
+
+impl TraceEvent {
+    /// A one-off event
+    pub fn event(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind: TraceEventKind::Event,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add to a counter
+    pub fn counter(name: impl Into<String>, increment: u64) -> Self {
+        Self {
+            name: name.into(),
+            kind: TraceEventKind::Counter(increment),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a named value
+    pub fn with_field(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push((key.into(), value.to_string()));
+        self
+    }
+}
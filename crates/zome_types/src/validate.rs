@@ -1,8 +1,20 @@
+use crate::element::Element;
+use crate::entry::Entry;
+use crate::query::ChainQueryFilter;
 use crate::zome_io::ExternOutput;
 use crate::CallbackResult;
 use holo_hash::EntryHash;
 use holochain_serialized_bytes::prelude::*;
 
+/// The input to the validate callbacks
+#[derive(Serialize, Deserialize, SerializedBytes)]
+pub struct ValidateData {
+    pub entry: Entry,
+    /// The earlier elements from the author's chain which the entry def
+    /// asks for, if it asks for any
+    pub validation_package: Option<ValidationPackage>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
 pub enum ValidateCallbackResult {
     Valid,
@@ -30,8 +42,56 @@ impl From<ExternOutput> for ValidateCallbackResult {
     }
 }
 
+/// The elements from an author's source chain which validators need to
+/// validate one of the author's headers, in sequence order
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, SerializedBytes)]
-pub struct ValidationPackage;
+pub struct ValidationPackage(pub Vec<Element>);
+
+impl ValidationPackage {
+    /// Constructor
+    pub fn new(elements: Vec<Element>) -> Self {
+        Self(elements)
+    }
+
+    /// The elements which match a filter, in sequence order.
+    /// Entries are left out unless the filter includes them.
+    pub fn query(&self, filter: &ChainQueryFilter) -> Vec<Element> {
+        self.0
+            .iter()
+            .filter(|el| filter.check(el.header()))
+            .map(|el| {
+                if filter.include_entries {
+                    el.clone()
+                } else {
+                    Element::new(el.signed_header().clone(), None)
+                }
+            })
+            .collect()
+    }
+
+    /// How many elements match a filter
+    pub fn count(&self, filter: &ChainQueryFilter) -> usize {
+        self.0.iter().filter(|el| filter.check(el.header())).count()
+    }
+}
+
+/// How much of an author's source chain validators need
+/// to validate one of the author's headers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, SerializedBytes)]
+pub enum RequiredValidationType {
+    /// Just the element itself, so there's no package
+    Element,
+    /// Every earlier element on the author's chain with the same entry type
+    SubChain,
+    /// The author's whole chain before the element
+    Full,
+}
+
+impl Default for RequiredValidationType {
+    fn default() -> Self {
+        Self::Element
+    }
+}
 
 #[derive(Clone, PartialEq, Serialize, Deserialize, SerializedBytes)]
 pub enum ValidationPackageCallbackResult {
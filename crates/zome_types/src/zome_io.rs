@@ -38,6 +38,10 @@ wasm_io_types!(
     // @todo Call is arbitrary so we need to send and receive SerializedBytes.
     pub struct CallInput(SerializedBytes);
     pub struct CallOutput(SerializedBytes);
+    // Every call which led to the running zome function, oldest first.
+    // None in callbacks, which aren't called by anyone.
+    pub struct CallProvenanceInput(());
+    pub struct CallProvenanceOutput(Option<crate::provenance::CallProvenance>);
     // @todo List all the local capability claims.
     pub struct CapabilityClaimsInput(());
     pub struct CapabilityClaimsOutput(());
@@ -68,6 +72,17 @@ wasm_io_types!(
     // Query the source chain for data.
     pub struct QueryInput(crate::query::ChainQueryFilter);
     pub struct QueryOutput(ElementVec);
+    // Query the validation package of the element being validated.
+    // Only usable in validate callbacks.
+    pub struct ValidationPackageQueryInput(crate::query::ChainQueryFilter);
+    pub struct ValidationPackageQueryOutput(ElementVec);
+    // Count the elements of the validation package of the element being validated
+    // which match a filter. Only usable in validate callbacks.
+    pub struct ValidationPackageCountInput(crate::query::ChainQueryFilter);
+    pub struct ValidationPackageCountOutput(u32);
+    // Search an app defined index held by this cell.
+    pub struct QueryIndexInput(crate::index::IndexQuery);
+    pub struct QueryIndexOutput(crate::index::IndexHits);
     // the length of random bytes to create
     pub struct RandomBytesInput(u32);
     pub struct RandomBytesOutput(crate::bytes::Bytes);
@@ -75,8 +90,23 @@ wasm_io_types!(
     pub struct DeleteLinkInput(holo_hash::HeaderHash);
     // Header hash of the DeleteLink element.
     pub struct DeleteLinkOutput(holo_hash::HeaderHash);
+    // Delete many links by the headers which created them, in one pass over the source chain.
+    pub struct DeleteLinksInput(Vec<holo_hash::HeaderHash>);
+    pub struct DeleteLinksOutput(Vec<holo_hash::HeaderHash>);
     pub struct CallRemoteInput(crate::call_remote::CallRemote);
     pub struct CallRemoteOutput(ZomeCallResponse);
+    // Ask the authorities for a basis to signal this agent when ops are integrated there.
+    pub struct SubscribeInput(holo_hash::AnyDhtHash);
+    pub struct SubscribeOutput(());
+    pub struct UnsubscribeInput(holo_hash::AnyDhtHash);
+    pub struct UnsubscribeOutput(());
+    // Pin a hash so its data is always held locally.
+    pub struct PinInput(holo_hash::AnyDhtHash);
+    pub struct PinOutput(());
+    pub struct UnpinInput(holo_hash::AnyDhtHash);
+    pub struct UnpinOutput(());
+    pub struct ListPinsInput(());
+    pub struct ListPinsOutput(Vec<holo_hash::AnyDhtHash>);
     // @todo
     pub struct SendInput(());
     pub struct SendOutput(());
@@ -86,12 +116,13 @@ wasm_io_types!(
     // @todo
     pub struct ScheduleInput(core::time::Duration);
     pub struct ScheduleOutput(());
-    // Same as CreateInput but also takes the HeaderHash of the updated element.
+    // Same as CreateInput but also takes what is updated:
+    // the HeaderHash of an element or the EntryHash of an entry.
     pub struct UpdateInput(
         (
             crate::entry_def::EntryDefId,
             crate::entry::Entry,
-            holo_hash::HeaderHash,
+            crate::header::UpdateTarget,
         ),
     );
     // Header hash of the newly committed element.
@@ -103,14 +134,20 @@ wasm_io_types!(
     pub struct DeleteInput(holo_hash::HeaderHash);
     pub struct DeleteOutput(holo_hash::HeaderHash);
     // Create a link between two entries.
+    // The optional LinkTypeId is resolved against the zome's declared link types by the host.
     pub struct CreateLinkInput(
         (
             holo_hash::EntryHash,
             holo_hash::EntryHash,
+            Option<crate::link::LinkTypeId>,
             crate::link::LinkTag,
         ),
     );
     pub struct CreateLinkOutput(holo_hash::HeaderHash);
+    // Create many links in one pass over the source chain.
+    // The hashes of the headers are in the same order as the links.
+    pub struct CreateLinksInput(Vec<CreateLinkInput>);
+    pub struct CreateLinksOutput(Vec<holo_hash::HeaderHash>);
     // @todo
     pub struct KeystoreInput(());
     pub struct KeystoreOutput(());
@@ -119,14 +156,45 @@ wasm_io_types!(
     pub struct GetLinksOutput(crate::link::Links);
     pub struct GetLinkDetailsInput((holo_hash::EntryHash, Option<crate::link::LinkTag>));
     pub struct GetLinkDetailsOutput(crate::link::LinkDetails);
+    // Get links of an ordered link type with tags from the start (inclusive)
+    // to the end (exclusive) tag, from this cell's ordered link index.
+    pub struct GetLinksInRangeInput(
+        (
+            holo_hash::EntryHash,
+            crate::link::LinkTypeId,
+            Option<crate::link::LinkTag>,
+            Option<crate::link::LinkTag>,
+        ),
+    );
+    pub struct GetLinksInRangeOutput(crate::link::Links);
+    // Aggregate the tag values of the links of an ordered link type on a base,
+    // from this cell's ordered link index.
+    pub struct GetLinkAggregateInput(
+        (
+            holo_hash::EntryHash,
+            crate::link::LinkTypeId,
+            crate::link::LinkAggregateOp,
+        ),
+    );
+    pub struct GetLinkAggregateOutput(crate::link::LinkAggregate);
     // Attempt to get a live entry from the cascade.
     pub struct GetInput((holo_hash::AnyDhtHash, crate::entry::GetOptions));
     pub struct GetOutput(Option<crate::element::Element>);
     pub struct GetDetailsInput((holo_hash::AnyDhtHash, crate::entry::GetOptions));
     pub struct GetDetailsOutput(Option<crate::metadata::Details>);
-    // @todo
-    pub struct EntryTypePropertiesInput(());
-    pub struct EntryTypePropertiesOutput(());
+    // Ask an agent's activity authorities to sign what they see at the head of its chain.
+    // Only observations with good signatures are returned.
+    pub struct ObserveChainHeadInput(holo_hash::AgentPubKey);
+    pub struct ObserveChainHeadOutput(Vec<crate::observation::SignedChainHeadObservation>);
+    // Check an observation was signed by its observer, and its head by the observed agent.
+    pub struct VerifyChainHeadObservationInput(crate::observation::SignedChainHeadObservation);
+    pub struct VerifyChainHeadObservationOutput(bool);
+    // The entry defs registered by zomes in the DNA, keyed by zome.
+    // A zome which doesn't define any entry types has no defs.
+    pub struct EntryTypePropertiesInput(crate::entry_def::EntryDefsQuery);
+    pub struct EntryTypePropertiesOutput(
+        std::collections::BTreeMap<crate::zome::ZomeName, crate::entry_def::EntryDefs>,
+    );
     // Hash an entry on the host.
     pub struct HashEntryInput(crate::entry::Entry);
     pub struct HashEntryOutput(holo_hash::EntryHash);
@@ -137,6 +205,10 @@ wasm_io_types!(
     // DebugMsg includes line numbers. so the wasm tells the host about it's own code structure.
     pub struct DebugInput(crate::debug::DebugMsg);
     pub struct DebugOutput(());
+    // Record an event or counter on the span of the running zome call,
+    // so zomes can profile their own logic.
+    pub struct TraceEventInput(crate::trace::TraceEvent);
+    pub struct TraceEventOutput(());
     // There's nothing to go in or out of a noop.
     // Used to "defuse" host functions when side effects are not allowed.
     pub struct UnreachableInput(());
@@ -157,6 +229,20 @@ wasm_io_types!(
     pub struct ExternOutput(crate::SerializedBytes);
 );
 
+impl ExternInput {
+    /// How many bytes this takes up crossing the wasm boundary
+    pub fn size(&self) -> usize {
+        self.0.bytes().len()
+    }
+}
+
+impl ExternOutput {
+    /// How many bytes this takes up crossing the wasm boundary
+    pub fn size(&self) -> usize {
+        self.0.bytes().len()
+    }
+}
+
 /// Response to a zome call.
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SerializedBytes, PartialEq)]
 pub enum ZomeCallResponse {